@@ -0,0 +1,60 @@
+use anyhow::Result;
+
+use crate::transcript::Transcript;
+use crate::{Error, VideoTranscriber};
+
+impl VideoTranscriber {
+    /// Mark a video in the library private, noting who may see it, or make it public again.
+    /// Returns the video ID.
+    pub fn set_private(&self, url: &str, private: bool, note: Option<&str>) -> Result<String> {
+        let video_id = self.extract_video_id(url)?;
+        if !self.registry.set_private(&video_id, private, note)? {
+            // Transcripts cached by `ask` alone are not registered yet
+            let Some(transcript) = self.cache.get(&video_id)? else {
+                anyhow::bail!("{} is not in the library; index it first with `index`", video_id);
+            };
+            self.registry.record_video(&transcript)?;
+            self.registry.set_private(&video_id, private, note)?;
+        }
+        Ok(video_id)
+    }
+
+    /// Fail with `Error::PrivateVideo` when the video is private, unless `include_private`
+    pub fn check_shareable(&self, transcript: &Transcript, include_private: bool) -> Result<()> {
+        if include_private {
+            return Ok(());
+        }
+        match self.registry.private_videos()?.remove(&transcript.video_id) {
+            Some(note) => Err(Error::PrivateVideo {
+                label: transcript.label().to_string(),
+                note,
+            }
+            .into()),
+            None => Ok(()),
+        }
+    }
+
+    /// The transcripts that are not private, or all of them with `include_private`, saying how
+    /// many were left out
+    pub fn shareable_transcripts(&self, transcripts: Vec<Transcript>, include_private: bool) -> Result<Vec<Transcript>> {
+        if include_private {
+            return Ok(transcripts);
+        }
+        let private = self.registry.private_videos()?;
+        let total = transcripts.len();
+        let shareable: Vec<Transcript> = transcripts
+            .into_iter()
+            .filter(|transcript| !private.contains_key(&transcript.video_id))
+            .collect();
+        if shareable.len() < total {
+            status!(
+                "🔒 Leaving out {} private video(s) (pass --include-private to use them)",
+                total - shareable.len()
+            );
+        }
+        if shareable.is_empty() {
+            anyhow::bail!("Every video here is private; pass --include-private to use them");
+        }
+        Ok(shareable)
+    }
+}
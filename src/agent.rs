@@ -0,0 +1,350 @@
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::cancel::SendCancellable;
+use crate::companion::parse_timestamp;
+use crate::guardrail::fence;
+use crate::retrieval::{bm25_rank, chunk_transcript, Chunk, DEFAULT_CHUNK_CHARS, DEFAULT_CHUNK_OVERLAP};
+use crate::transcript::{format_timestamp, Transcript};
+use crate::VideoTranscriber;
+
+/// Tool calls the model may make before it has to answer
+const MAX_STEPS: usize = 8;
+/// Excerpts one `search_library` call returns
+const SEARCH_RESULTS: usize = 5;
+/// Longest window `get_transcript_window` returns, in seconds
+const MAX_WINDOW_SECS: f64 = 600.0;
+/// Results one `web_search` call returns
+const WEB_RESULTS: usize = 5;
+/// Replies that are neither a tool call nor an answer tolerated before giving up
+const MAX_STRAYS: usize = 3;
+
+const AGENT_SYSTEM_PROMPT: &str =
+    "You answer questions about a library of video transcripts by calling tools until you have the \
+     evidence you need. Every tool result is numbered as evidence [n]; ground every claim of your final \
+     answer in that evidence and cite it as [n]. Never answer from memory alone. Reply with one JSON \
+     object per turn and nothing else.";
+
+/// One turn of the model: a tool call or the final answer
+#[derive(Deserialize)]
+struct AgentTurn {
+    #[serde(default)]
+    thought: Option<String>,
+    #[serde(default)]
+    tool: Option<String>,
+    #[serde(default)]
+    arguments: Value,
+    #[serde(default)]
+    answer: Option<String>,
+}
+
+/// Something a tool returned, citable as `[n]` in the answer
+pub struct Evidence {
+    pub label: String,
+    pub link: String,
+}
+
+/// One tool call, for `--show-steps`
+pub struct AgentStep {
+    pub thought: Option<String>,
+    pub call: String,
+    pub result: String,
+}
+
+/// The answer and how the agent got there
+pub struct AgentAnswer {
+    pub answer: String,
+    pub steps: Vec<AgentStep>,
+    pub evidence: Vec<Evidence>,
+}
+
+impl AgentAnswer {
+    /// The answer followed by the evidence it cites
+    pub fn render(&self) -> String {
+        let cited: String = self
+            .evidence
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| self.answer.contains(&format!("[{}]", i + 1)))
+            .map(|(i, evidence)| format!("  [{}] {} — {}\n", i + 1, evidence.label, evidence.link))
+            .collect();
+        if cited.is_empty() {
+            self.answer.trim().to_string()
+        } else {
+            format!("{}\n\nSources:\n{}", self.answer.trim(), cited)
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct BraveResponse {
+    #[serde(default)]
+    web: Option<BraveResults>,
+}
+
+#[derive(Deserialize)]
+struct BraveResults {
+    #[serde(default)]
+    results: Vec<BraveResult>,
+}
+
+#[derive(Deserialize)]
+struct BraveResult {
+    title: String,
+    url: String,
+    #[serde(default)]
+    description: String,
+}
+
+/// State shared by the tools during one run
+struct Tools<'a> {
+    transcriber: &'a VideoTranscriber,
+    library: Vec<Transcript>,
+    chunks: Vec<Chunk>,
+    web_search_key: Option<String>,
+    evidence: Vec<Evidence>,
+}
+
+impl Tools<'_> {
+    fn descriptions(&self) -> String {
+        let mut tools = String::from(
+            "- search_library {\"query\": string, \"video_id\": string (optional, limits the search to one video)}: \
+             keyword search over every transcript; returns excerpts with their video ID and time\n\
+             - get_transcript_window {\"video_id\": string, \"start\": \"MM:SS\", \"seconds\": number (max 600)}: \
+             the exact transcript of a stretch of one video\n",
+        );
+        if self.web_search_key.is_some() {
+            tools.push_str(
+                "- web_search {\"query\": string}: web results, for context the videos do not cover; \
+                 label web claims as such in the answer\n",
+            );
+        }
+        tools
+    }
+
+    /// Run a tool call, returning the text the model sees next
+    fn call(&mut self, tool: &str, arguments: &Value) -> Result<String> {
+        let text = |name: &str| arguments[name].as_str().map(str::trim).filter(|s| !s.is_empty());
+        match tool {
+            "search_library" => {
+                let query = text("query").context("search_library needs a \"query\"")?;
+                let video_id = text("video_id");
+                let candidates: Vec<Chunk> = self
+                    .chunks
+                    .iter()
+                    .filter(|chunk| video_id.is_none_or(|id| chunk.video_id == id))
+                    .cloned()
+                    .collect();
+                let hits = bm25_rank(query, &candidates, SEARCH_RESULTS);
+                if hits.is_empty() {
+                    return Ok("No excerpts match; try other keywords.".to_string());
+                }
+                Ok(hits
+                    .into_iter()
+                    .map(|(i, _)| {
+                        let chunk = &candidates[i];
+                        let n = cite(&mut self.evidence, chunk.citation(), chunk.link());
+                        format!("[{}] video_id={} ({})\n{}\n", n, chunk.video_id, chunk.citation(), fence(&chunk.text))
+                    })
+                    .collect::<Vec<_>>()
+                    .join("\n"))
+            }
+            "get_transcript_window" => {
+                let video_id = text("video_id").context("get_transcript_window needs a \"video_id\"")?;
+                let transcript = self
+                    .library
+                    .iter()
+                    .find(|t| t.video_id == video_id)
+                    .with_context(|| format!("No video {} in the library", video_id))?;
+                let start = match &arguments["start"] {
+                    Value::Number(n) => n.as_f64().unwrap_or(0.0),
+                    Value::String(s) => parse_timestamp(s)?,
+                    _ => 0.0,
+                };
+                let seconds = arguments["seconds"].as_f64().unwrap_or(120.0).clamp(1.0, MAX_WINDOW_SECS);
+                if transcript.segments.is_empty() {
+                    anyhow::bail!("{} has no timed segments; use search_library instead", video_id);
+                }
+                let window: Vec<&str> = transcript
+                    .segments
+                    .iter()
+                    .filter(|segment| segment.end >= start && segment.start <= start + seconds)
+                    .map(|segment| segment.text.as_str())
+                    .collect();
+                if window.is_empty() {
+                    return Ok(format!("{} has nothing between {} and {}", video_id, format_timestamp(start), format_timestamp(start + seconds)));
+                }
+                let label = format!("{} @ {}–{}", transcript.label(), format_timestamp(start), format_timestamp(start + seconds));
+                let n = cite(&mut self.evidence, label.clone(), transcript.link_at(start));
+                Ok(format!("[{}] {}\n{}", n, label, fence(&window.join(" "))))
+            }
+            "web_search" => {
+                let key = self.web_search_key.as_deref().context("web_search is not available")?;
+                let query = text("query").context("web_search needs a \"query\"")?;
+                let results = self.transcriber.web_search(key, query)?;
+                if results.is_empty() {
+                    return Ok("No web results.".to_string());
+                }
+                Ok(results
+                    .into_iter()
+                    .map(|result| {
+                        let n = cite(&mut self.evidence, format!("{} (web)", result.title), result.url.clone());
+                        format!("[{}] {} — {}\n{}\n", n, result.title, result.url, fence(&result.description))
+                    })
+                    .collect::<Vec<_>>()
+                    .join("\n"))
+            }
+            other => anyhow::bail!("There is no tool called {}", other),
+        }
+    }
+
+}
+
+/// The evidence number of a tool result, adding it when it is new
+fn cite(evidence: &mut Vec<Evidence>, label: String, link: String) -> usize {
+    if let Some(i) = evidence.iter().position(|e| e.label == label && e.link == link) {
+        return i + 1;
+    }
+    evidence.push(Evidence { label, link });
+    evidence.len()
+}
+
+/// A short, one-line preview of a tool result for the step trace
+fn preview(result: &str) -> String {
+    let line = result.split_whitespace().collect::<Vec<_>>().join(" ");
+    if line.chars().count() > 160 {
+        format!("{}…", line.chars().take(159).collect::<String>())
+    } else {
+        line
+    }
+}
+
+impl VideoTranscriber {
+    /// Search the web with the Brave Search API
+    fn web_search(&self, key: &str, query: &str) -> Result<Vec<BraveResult>> {
+        let response = self
+            .client
+            .get(format!("{}/res/v1/web/search", self.endpoints.brave))
+            .query(&[("q", query), ("count", &WEB_RESULTS.to_string())])
+            .header("X-Subscription-Token", key)
+            .header("Accept", "application/json")
+            .send_with(&self.cancel)
+            .context("Failed to search the web")?;
+        if !response.status().is_success() {
+            anyhow::bail!("Web search failed with status {}", response.status());
+        }
+        let body: BraveResponse = response.json().context("Failed to parse web search results")?;
+        Ok(body.web.map(|web| web.results).unwrap_or_default())
+    }
+
+    /// Answer a complex question in several steps: the model searches the library, reads
+    /// transcript windows, and (with a Brave Search key) searches the web before answering.
+    /// `video` is the video the question is about, if any.
+    pub fn agent_answer(&self, question: &str, video: Option<&Transcript>, show_steps: bool) -> Result<AgentAnswer> {
+        let mut library = self.cache.list()?;
+        if let Some(video) = video {
+            if !library.iter().any(|t| t.video_id == video.video_id) {
+                library.push(video.clone());
+            }
+        }
+        if library.is_empty() {
+            anyhow::bail!("The library is empty. Index some videos first with `index`.");
+        }
+        let chunks = library
+            .iter()
+            .flat_map(|t| chunk_transcript(t, DEFAULT_CHUNK_CHARS, DEFAULT_CHUNK_OVERLAP))
+            .collect();
+        let mut tools = Tools {
+            transcriber: self,
+            library,
+            chunks,
+            web_search_key: self.setting("BRAVE_SEARCH_API_KEY", &self.config.web_search_api_key),
+            evidence: Vec::new(),
+        };
+
+        let focus = match video {
+            Some(video) => format!(
+                "The question is about the video \"{}\" (video_id={}), but you may use the whole library.\n\n",
+                video.label(),
+                video.video_id
+            ),
+            None => String::new(),
+        };
+        let mut conversation = format!(
+            "{}Question: {}\n\nTools:\n{}\n\
+             Each turn, reply with either {{\"thought\": \"why\", \"tool\": \"name\", \"arguments\": {{...}}}} \
+             or, once the evidence is enough, {{\"thought\": \"why\", \"answer\": \"the final answer, citing [n]\"}}. \
+             You have at most {} tool calls.\n",
+            focus,
+            question,
+            tools.descriptions(),
+            MAX_STEPS
+        );
+
+        status!("🕵️  Working on it step by step (at most {} tool calls)...", MAX_STEPS);
+        let mut steps = Vec::new();
+        let mut strays = 0;
+        loop {
+            if strays > MAX_STRAYS {
+                anyhow::bail!("The model stopped following the tool-call format; try again without --agent");
+            }
+            if steps.len() == MAX_STEPS {
+                conversation.push_str("\nNo tool calls are left: reply with the final answer now.\n");
+            }
+            let reply = self.complete(AGENT_SYSTEM_PROMPT, &conversation)?;
+            let turn: AgentTurn = match serde_json::from_str(crate::llm::extract_json(&reply)) {
+                Ok(turn) => turn,
+                // A model that stops following the protocol at the end has usually just answered
+                Err(_) if steps.len() == MAX_STEPS => return Ok(AgentAnswer { answer: reply, steps, evidence: tools.evidence }),
+                Err(_) => {
+                    strays += 1;
+                    conversation.push_str(&format!(
+                        "\nAssistant: {}\nThat was not a JSON object in the required format. Reply again.\n",
+                        reply.trim()
+                    ));
+                    continue;
+                }
+            };
+
+            let tool = match (turn.answer, turn.tool) {
+                (Some(answer), _) => {
+                    return Ok(AgentAnswer {
+                        answer,
+                        steps,
+                        evidence: tools.evidence,
+                    })
+                }
+                (None, Some(tool)) if steps.len() < MAX_STEPS => tool,
+                _ => {
+                    strays += 1;
+                    conversation.push_str("\nReply with the final answer now.\n");
+                    continue;
+                }
+            };
+
+            let call = format!("{}({})", tool, turn.arguments);
+            let result = tools.call(&tool, &turn.arguments).unwrap_or_else(|e| format!("Error: {:#}", e));
+            if show_steps {
+                status!("\n🔧 Step {}: {}", steps.len() + 1, call);
+                if let Some(thought) = &turn.thought {
+                    status!("   💭 {}", thought);
+                }
+                status!("   → {}", preview(&result));
+            } else {
+                status!("🔧 {}", tool);
+            }
+            conversation.push_str(&format!(
+                "\nAssistant: {}\nResult of {}:\n{}\n",
+                serde_json::json!({"thought": turn.thought, "tool": tool, "arguments": turn.arguments}),
+                tool,
+                result
+            ));
+            steps.push(AgentStep {
+                thought: turn.thought,
+                call,
+                result,
+            });
+        }
+    }
+}
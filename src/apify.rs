@@ -0,0 +1,584 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
+
+use crate::cancel::{self, CancellationToken, SendCancellable};
+use crate::captions::{self, CaptionTrack};
+use crate::companion::parse_timestamp;
+use crate::hooks::PipelineEvent;
+use crate::progress::Progress;
+use crate::transcript::{self, CaptionSource, Transcript};
+use crate::{Error, VideoTranscriber};
+
+// ===== Apify Scraper Structures =====
+
+#[derive(Serialize)]
+struct ApifyRunInput {
+    #[serde(rename = "startUrls")]
+    start_urls: Vec<ApifyUrl>,
+    #[serde(rename = "maxResults")]
+    max_results: i32,
+    #[serde(rename = "downloadSubtitles")]
+    download_subtitles: bool,
+    #[serde(rename = "subtitlesFormat")]
+    subtitles_format: String,
+    /// Only videos published on or after this date (`YYYY-MM-DD`), for channel URLs
+    #[serde(rename = "oldestPostDate", skip_serializing_if = "Option::is_none")]
+    oldest_post_date: Option<String>,
+    /// Newest first, so a date cutoff ends the scrape early
+    #[serde(rename = "sortVideosBy", skip_serializing_if = "Option::is_none")]
+    sort_videos_by: Option<&'static str>,
+}
+
+#[derive(Serialize)]
+struct ApifyUrl {
+    url: String,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct ApifyDatasetItem {
+    pub id: Option<String>,
+    pub url: Option<String>,
+    pub text: Option<String>,
+    #[serde(rename = "channelName")]
+    pub channel_name: Option<String>,
+    pub title: Option<String>,
+    pub date: Option<String>,
+    /// `HH:MM:SS`
+    pub duration: Option<String>,
+    pub subtitles: Option<Vec<ApifySubtitle>>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct ApifySubtitle {
+    pub srt: Option<String>,
+    /// `user_generated` for creator-uploaded captions, `auto_generated` for speech recognition
+    #[serde(rename = "type")]
+    pub kind: Option<String>,
+    pub language: Option<String>,
+}
+
+// ===== Apify Runs API Structures =====
+
+#[derive(Deserialize, Debug)]
+struct ApifyResponse<T> {
+    data: T,
+}
+
+#[derive(Deserialize, Debug)]
+struct ApifyList<T> {
+    items: Vec<T>,
+    total: Option<u64>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct ApifyRun {
+    pub id: String,
+    #[serde(rename = "actId")]
+    pub act_id: Option<String>,
+    pub status: String,
+    #[serde(rename = "startedAt")]
+    pub started_at: Option<String>,
+    #[serde(rename = "finishedAt")]
+    pub finished_at: Option<String>,
+    #[serde(rename = "usageTotalUsd")]
+    pub usage_total_usd: Option<f64>,
+    #[serde(rename = "defaultDatasetId")]
+    pub default_dataset_id: Option<String>,
+    pub stats: Option<ApifyRunStats>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct ApifyRunStats {
+    #[serde(rename = "runTimeSecs")]
+    pub run_time_secs: Option<f64>,
+    #[serde(rename = "computeUnits")]
+    pub compute_units: Option<f64>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct ApifyAccountLimits {
+    #[serde(rename = "monthlyUsageCycle")]
+    pub monthly_usage_cycle: ApifyUsageCycle,
+    pub limits: ApifyLimits,
+    pub current: ApifyCurrentUsage,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct ApifyUsageCycle {
+    #[serde(rename = "startAt")]
+    pub start_at: String,
+    #[serde(rename = "endAt")]
+    pub end_at: String,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct ApifyLimits {
+    #[serde(rename = "maxMonthlyUsageUsd")]
+    pub max_monthly_usage_usd: f64,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct ApifyCurrentUsage {
+    #[serde(rename = "monthlyUsageUsd")]
+    pub monthly_usage_usd: f64,
+}
+
+impl ApifyAccountLimits {
+    /// Credits left in the current monthly cycle (never negative)
+    pub fn remaining_usd(&self) -> f64 {
+        (self.limits.max_monthly_usage_usd - self.current.monthly_usage_usd).max(0.0)
+    }
+}
+
+/// How often a run's status is checked by default
+pub const APIFY_POLL_INTERVAL: Duration = Duration::from_secs(5);
+/// How long to wait for a run before giving up on it
+const APIFY_MAX_WAIT: Duration = Duration::from_secs(5 * 60);
+
+/// Default remaining-credit threshold (USD) below which indexing warns
+const DEFAULT_LOW_CREDIT_USD: f64 = 1.0;
+/// How long a credit check holds before the next run checks again
+const CREDIT_CHECK_INTERVAL: Duration = Duration::from_secs(10 * 60);
+
+impl VideoTranscriber {
+    /// Fetch transcript from YouTube using Apify YouTube Scraper
+    pub fn fetch_from_apify(&self, youtube_url: &str) -> Result<Transcript> {
+        status!("📥 Fetching transcript from YouTube using Apify...");
+
+        let items = self.run_scraper(&[youtube_url.to_string()], 1)?;
+        let item = items
+            .first()
+            .ok_or_else(|| Error::NoTranscript(youtube_url.to_string()))?;
+
+        let mut transcript = self.transcript_from_item(item, youtube_url)?;
+        transcript.video_id = self.extract_video_id(youtube_url)?;
+        transcript.url = youtube_url.to_string();
+        Ok(transcript)
+    }
+
+    /// The Apify token, required only once something actually calls Apify
+    fn apify_key(&self) -> Result<&str> {
+        if self.apify_api_key.is_empty() {
+            return Err(Error::MissingApiKey("APIFY_API_KEY").into());
+        }
+        Ok(&self.apify_api_key)
+    }
+
+    /// Run the Apify YouTube scraper over video, playlist, or channel URLs and return its dataset items
+    pub fn run_scraper(&self, start_urls: &[String], max_results: i32) -> Result<Vec<ApifyDatasetItem>> {
+        let run_id = self.start_scraper(start_urls, max_results, None)?;
+        status!("⏳ Waiting for Apify to process the video (run ID: {})...", run_id);
+
+        self.wait_for_run(&run_id, &mut || Ok(()))?;
+        status!("\n✅ Apify processing complete!");
+
+        self.dataset_page(&run_id, 0, None)
+    }
+
+    /// Start the Apify actor on the given URLs, returning the run ID. `since` limits channel
+    /// URLs to videos published from that day on.
+    pub fn start_scraper(&self, start_urls: &[String], max_results: i32, since: Option<DateTime<Utc>>) -> Result<String> {
+        if self.credit_check_due() {
+            self.warn_if_low_credits();
+        }
+
+        let run_input = ApifyRunInput {
+            start_urls: start_urls
+                .iter()
+                .map(|url| ApifyUrl { url: url.clone() })
+                .collect(),
+            max_results,
+            download_subtitles: true,
+            subtitles_format: "srt".to_string(),
+            oldest_post_date: since.map(|since| since.format("%Y-%m-%d").to_string()),
+            sort_videos_by: since.map(|_| "NEWEST"),
+        };
+
+        let run_url = format!(
+            "{}/v2/acts/streamers~youtube-scraper/runs?token={}",
+            self.endpoints.apify, self.apify_key()?
+        );
+
+        let run_response = self
+            .client
+            .post(&run_url)
+            .json(&run_input)
+            .send_with(&self.cancel)
+            .context("Failed to start Apify actor run")?;
+
+        if !run_response.status().is_success() {
+            let status = run_response.status();
+            let body = run_response.text().unwrap_or_default();
+            return Err(self.http_failure("Apify run", "POST", &run_url, status, &body));
+        }
+
+        let run_data: serde_json::Value = run_response
+            .json()
+            .context("Failed to parse Apify run response")?;
+
+        run_data["data"]["id"]
+            .as_str()
+            .map(str::to_string)
+            .context("Failed to get run ID from Apify response")
+    }
+
+    /// Poll a run every 5 seconds (or the builder's `apify_poll_interval`) until it succeeds, calling `on_poll` after each check.
+    /// If the wait is cancelled the run is aborted too, so it stops using Apify credits.
+    pub fn wait_for_run(&self, run_id: &str, on_poll: &mut dyn FnMut() -> Result<()>) -> Result<()> {
+        let result = self.poll_run(run_id, on_poll);
+        if let Some(reason) = result.as_ref().err().and_then(cancel::cancelled) {
+            status!("\n🛑 Run {}; aborting Apify run {}", reason, run_id);
+            let cleanup = CancellationToken::new(Some(Duration::from_secs(10)));
+            if let Err(e) = self.abort_run_with(run_id, &cleanup) {
+                status!("⚠️  Could not abort the run: {}", e);
+            }
+        }
+        result?;
+        // What the run cost takes one more request, so it is only looked up for subscribers
+        if self.has_subscribers() {
+            let run = self.get_run(run_id)?;
+            self.emit(PipelineEvent::CostIncurred {
+                service: "apify",
+                model: None,
+                input_tokens: 0,
+                output_tokens: 0,
+                usd: run.usage_total_usd,
+            })?;
+        }
+        Ok(())
+    }
+
+    pub fn poll_run(&self, run_id: &str, on_poll: &mut dyn FnMut() -> Result<()>) -> Result<()> {
+        let mut attempts = 0;
+        let interval = self.apify_poll_interval.max(Duration::from_millis(1));
+        let max_attempts = APIFY_MAX_WAIT.as_millis().div_ceil(interval.as_millis()).max(1) as u32;
+        loop {
+            self.cancel.sleep(interval)?;
+            attempts += 1;
+
+            let status_url = format!(
+                "{}/v2/acts/streamers~youtube-scraper/runs/{}?token={}",
+                self.endpoints.apify, run_id, self.apify_key()?
+            );
+
+            let status_response = self
+                .send_retrying("Apify", || self.client.get(&status_url))
+                .context("Failed to check Apify run status")?;
+
+            if !status_response.status().is_success() {
+                let status = status_response.status();
+                let body = status_response.text().unwrap_or_default();
+                return Err(self.http_failure("Apify run status", "GET", &status_url, status, &body));
+            }
+
+            let status_data: serde_json::Value = status_response
+                .json()
+                .context("Failed to parse Apify status response")?;
+
+            let status = status_data["data"]["status"]
+                .as_str()
+                .context("Failed to get status from Apify response")?;
+
+            match status {
+                "SUCCEEDED" => return Ok(()),
+                "FAILED" | "ABORTED" | "TIMED-OUT" => {
+                    return Err(Error::ApifyRun(status.to_string()).into());
+                }
+                _ => {
+                    if attempts >= max_attempts {
+                        anyhow::bail!(
+                            "Apify run still {} after {} minutes; giving up",
+                            status,
+                            APIFY_MAX_WAIT.as_secs() / 60
+                        );
+                    }
+                    self.report(Progress::ApifyRunning {
+                        run_id: run_id.to_string(),
+                        polls: attempts,
+                    });
+                    on_poll()?;
+                }
+            }
+        }
+    }
+
+    /// Read dataset items from a run, starting at `offset`; items can be read while the run is still going
+    pub fn dataset_page(&self, run_id: &str, offset: usize, limit: Option<usize>) -> Result<Vec<ApifyDatasetItem>> {
+        let mut dataset_url = format!(
+            "{}/v2/actor-runs/{}/dataset/items?token={}&offset={}",
+            self.endpoints.apify, run_id, self.apify_key()?, offset
+        );
+        if let Some(limit) = limit {
+            dataset_url.push_str(&format!("&limit={}", limit));
+        }
+
+        let dataset_response = self
+            .send_retrying("Apify", || self.client.get(&dataset_url))
+            .context("Failed to fetch Apify dataset")?;
+
+        if !dataset_response.status().is_success() {
+            let status = dataset_response.status();
+            let body = dataset_response.text().unwrap_or_default();
+            return Err(self.http_failure("Apify dataset", "GET", &dataset_url, status, &body));
+        }
+
+        let items: Vec<ApifyDatasetItem> = dataset_response
+            .json()
+            .context("Failed to parse Apify dataset items")?;
+
+        Ok(items)
+    }
+
+    /// Convert a scraper dataset item into a transcript, using `source_url` when the item has no URL
+    pub fn transcript_from_item(&self, item: &ApifyDatasetItem, source_url: &str) -> Result<Transcript> {
+        // Prefer timed caption segments, merging creator and auto-generated tracks; fall back to
+        // the plain text field
+        let tracks = item
+            .subtitles
+            .iter()
+            .flatten()
+            .filter_map(|subtitle| {
+                Some(CaptionTrack {
+                    source: match subtitle.kind.as_deref() {
+                        Some("auto_generated") => CaptionSource::Auto,
+                        _ => CaptionSource::Creator,
+                    },
+                    language: subtitle.language.clone(),
+                    segments: transcript::parse_srt(subtitle.srt.as_deref()?),
+                })
+            })
+            .collect();
+        let segments = captions::merge_tracks(tracks);
+        let text = if segments.is_empty() {
+            item.text
+                .clone()
+                .context("No transcript text found in the video data")?
+        } else {
+            transcript::join_segments(&segments)
+        };
+
+        let url = item.url.clone().unwrap_or_else(|| source_url.to_string());
+        let video_id = match &item.id {
+            Some(id) => id.clone(),
+            None => self.extract_video_id(&url)?,
+        };
+
+        Ok(Transcript {
+            video_id,
+            url,
+            title: item.title.clone(),
+            channel: item.channel_name.clone(),
+            text: text.into(),
+            segments,
+            published_at: item.date.as_deref().and_then(transcript::parse_published_date),
+            duration_secs: item.duration.as_deref().and_then(|d| parse_timestamp(d).ok()),
+            fetched_at: chrono::Utc::now(),
+        })
+    }
+
+    /// List the most recent Apify actor runs on the account
+    pub fn list_runs(&self, limit: u32) -> Result<Vec<ApifyRun>> {
+        let url = format!(
+            "{}/v2/actor-runs?token={}&limit={}&desc=true",
+            self.endpoints.apify, self.apify_key()?, limit
+        );
+
+        let response = self
+            .client
+            .get(&url)
+            .send_with(&self.cancel)
+            .context("Failed to list Apify runs")?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().unwrap_or_default();
+            return Err(self.http_failure("Apify runs list", "GET", &url, status, &body));
+        }
+
+        let runs: ApifyResponse<ApifyList<ApifyRun>> = response
+            .json()
+            .context("Failed to parse Apify runs list")?;
+
+        if let Some(total) = runs.data.total {
+            status!("📊 {} run(s) on the account, showing the latest {}", total, runs.data.items.len());
+        }
+
+        Ok(runs.data.items)
+    }
+
+    /// Get the details of a single Apify actor run
+    pub fn get_run(&self, run_id: &str) -> Result<ApifyRun> {
+        let url = format!(
+            "{}/v2/actor-runs/{}?token={}",
+            self.endpoints.apify, run_id, self.apify_key()?
+        );
+
+        let response = self
+            .client
+            .get(&url)
+            .send_with(&self.cancel)
+            .context("Failed to fetch Apify run")?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().unwrap_or_default();
+            return Err(self.http_failure("Apify run lookup", "GET", &url, status, &body));
+        }
+
+        let run: ApifyResponse<ApifyRun> = response
+            .json()
+            .context("Failed to parse Apify run")?;
+
+        Ok(run.data)
+    }
+
+    /// Abort a running Apify actor run
+    pub fn abort_run(&self, run_id: &str) -> Result<ApifyRun> {
+        self.abort_run_with(run_id, &self.cancel)
+    }
+
+    /// Abort a run under a token other than the command's own, for cleanup after it was cancelled
+    pub fn abort_run_with(&self, run_id: &str, cancel: &CancellationToken) -> Result<ApifyRun> {
+        let url = format!(
+            "{}/v2/actor-runs/{}/abort?token={}",
+            self.endpoints.apify, run_id, self.apify_key()?
+        );
+
+        let response = self
+            .client
+            .post(&url)
+            .send_with(cancel)
+            .context("Failed to abort Apify run")?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().unwrap_or_default();
+            return Err(self.http_failure("Apify run abort", "POST", &url, status, &body));
+        }
+
+        let run: ApifyResponse<ApifyRun> = response
+            .json()
+            .context("Failed to parse Apify abort response")?;
+
+        Ok(run.data)
+    }
+}
+
+impl VideoTranscriber {
+    /// Fetch the account's monthly usage limits and current consumption
+    pub fn get_account_limits(&self) -> Result<ApifyAccountLimits> {
+        let url = format!(
+            "{}/v2/users/me/limits?token={}",
+            self.endpoints.apify, self.apify_key()?
+        );
+
+        let response = self
+            .client
+            .get(&url)
+            .send_with(&self.cancel)
+            .context("Failed to fetch Apify account limits")?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().unwrap_or_default();
+            return Err(self.http_failure("Apify limits request", "GET", &url, status, &body));
+        }
+
+        let limits: ApifyResponse<ApifyAccountLimits> = response
+            .json()
+            .context("Failed to parse Apify account limits")?;
+
+        Ok(limits.data)
+    }
+
+    /// Whether credits should be checked before this run: not when this transcriber (or a clone
+    /// of it) checked them within `CREDIT_CHECK_INTERVAL`, so a batch doesn't ask before every
+    /// video while long-running commands still check now and then
+    fn credit_check_due(&self) -> bool {
+        let mut checked_at = self.credits_checked_at.lock().unwrap();
+        if checked_at.is_some_and(|at| at.elapsed() < CREDIT_CHECK_INTERVAL) {
+            return false;
+        }
+        *checked_at = Some(Instant::now());
+        true
+    }
+
+    /// Warn when the remaining monthly Apify credits are running low.
+    ///
+    /// The threshold is read from `APIFY_LOW_CREDIT_USD` (default $1.00). A failed
+    /// check never blocks indexing; it only means no warning can be shown.
+    pub fn warn_if_low_credits(&self) {
+        let threshold = self
+            .env_var("APIFY_LOW_CREDIT_USD")
+            .and_then(|value| value.parse::<f64>().ok())
+            .unwrap_or(DEFAULT_LOW_CREDIT_USD);
+
+        match self.get_account_limits() {
+            Ok(limits) if limits.remaining_usd() < threshold => {
+                status!(
+                    "⚠️  Only ${:.2} of ${:.2} Apify credits left this cycle (resets {}). Runs may fail mid-batch.",
+                    limits.remaining_usd(),
+                    limits.limits.max_monthly_usage_usd,
+                    limits.monthly_usage_cycle.end_at
+                );
+            }
+            Ok(_) => {}
+            Err(e) => status!("⚠️  Could not check Apify credits: {}", e),
+        }
+    }
+}
+
+/// The account usage report for `apify-usage`
+pub fn render_account_usage(limits: &ApifyAccountLimits) -> String {
+    let max = limits.limits.max_monthly_usage_usd;
+    let used = limits.current.monthly_usage_usd;
+    let percent = if max > 0.0 { used / max * 100.0 } else { 0.0 };
+
+    let mut out = String::from("💳 Apify usage this cycle\n");
+    out.push_str(&format!(
+        "   Cycle: {} → {}\n",
+        limits.monthly_usage_cycle.start_at, limits.monthly_usage_cycle.end_at
+    ));
+    out.push_str(&format!("   Used: ${:.2} of ${:.2} ({:.1}%)\n", used, max, percent));
+    out.push_str(&format!("   Remaining: ${:.2}\n", limits.remaining_usd()));
+    out
+}
+
+/// A one-line summary of a run for `runs list`
+pub fn render_run_line(run: &ApifyRun) -> String {
+    format!(
+        "{:<20} {:<10} {:<26} {}",
+        run.id,
+        run.status,
+        run.started_at.as_deref().unwrap_or("-"),
+        run.usage_total_usd
+            .map(|usd| format!("${:.4}", usd))
+            .unwrap_or_else(|| "-".to_string())
+    )
+}
+
+/// The full details of a run for `runs show`
+pub fn render_run_details(run: &ApifyRun) -> String {
+    let mut out = format!("🏃 Run: {}\n", run.id);
+    out.push_str(&format!("   Actor: {}\n", run.act_id.as_deref().unwrap_or("-")));
+    out.push_str(&format!("   Status: {}\n", run.status));
+    out.push_str(&format!("   Started: {}\n", run.started_at.as_deref().unwrap_or("-")));
+    out.push_str(&format!("   Finished: {}\n", run.finished_at.as_deref().unwrap_or("-")));
+    if let Some(stats) = &run.stats {
+        if let Some(secs) = stats.run_time_secs {
+            out.push_str(&format!("   Run time: {:.1}s\n", secs));
+        }
+        if let Some(units) = stats.compute_units {
+            out.push_str(&format!("   Compute units: {:.4}\n", units));
+        }
+    }
+    if let Some(usd) = run.usage_total_usd {
+        out.push_str(&format!("   Cost: ${:.4}\n", usd));
+    }
+    if let Some(dataset) = &run.default_dataset_id {
+        out.push_str(&format!("   Dataset: {}\n", dataset));
+    }
+    out
+}
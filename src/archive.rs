@@ -0,0 +1,184 @@
+use anyhow::{Context, Result};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::path::Path;
+
+use crate::VideoTranscriber;
+
+/// Manifest listing every archived file with its hash, chained so entries cannot be
+/// reordered, removed, or altered without detection.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Manifest {
+    pub created_at: String,
+    pub tool_version: String,
+    pub video_id: String,
+    pub url: String,
+    pub entries: Vec<ManifestEntry>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ManifestEntry {
+    pub path: String,
+    pub bytes: usize,
+    pub sha256: String,
+    /// SHA-256 of the previous entry's chain hash followed by this entry's file hash
+    pub chain_sha256: String,
+}
+
+#[derive(Serialize)]
+struct ArchivedAnswer<'a> {
+    question: &'a str,
+    answer: &'a str,
+    provider: String,
+    model: &'a str,
+    generated_at: String,
+    /// What the model was shown when answering
+    context: ArchivedContext<'a>,
+}
+
+#[derive(Serialize)]
+struct ArchivedContext<'a> {
+    strategy: &'a str,
+    source_file: &'a str,
+    source_sha256: &'a str,
+}
+
+fn sha256_hex(bytes: &[u8]) -> String {
+    hex::encode(Sha256::digest(bytes))
+}
+
+fn chain_hash(previous: &str, file_hash: &str) -> String {
+    sha256_hex(format!("{}{}", previous, file_hash).as_bytes())
+}
+
+impl VideoTranscriber {
+    /// Write a tamper-evident archive of a video's transcript, metadata, and answers. Private
+    /// videos are refused unless `include_private`.
+    pub fn create_archive(
+        &self,
+        url: &str,
+        questions: &[String],
+        out_dir: &Path,
+        include_private: bool,
+    ) -> Result<Manifest> {
+        if out_dir.exists() && fs::read_dir(out_dir)?.next().is_some() {
+            anyhow::bail!("{} already exists and is not empty; archives are never overwritten", out_dir.display());
+        }
+        let transcript = self.load_transcript(url, false)?;
+        self.check_shareable(&transcript, include_private)
+            .context("Private videos are only archived with --include-private")?;
+        fs::create_dir_all(out_dir.join("answers"))
+            .with_context(|| format!("Failed to create {}", out_dir.display()))?;
+
+        let mut files: Vec<(String, Vec<u8>)> = Vec::new();
+
+        files.push(("transcript.txt".to_string(), transcript.text.as_bytes().to_vec()));
+        files.push((
+            "transcript.json".to_string(),
+            serde_json::to_vec_pretty(&transcript)?,
+        ));
+        let transcript_hash = sha256_hex(transcript.text.as_bytes());
+
+        let (provider, model) = match self.llm_provider {
+            crate::llm::LlmProvider::Groq => ("groq", self.groq_model.as_str()),
+            crate::llm::LlmProvider::Gemini => ("gemini", self.gemini_model.as_str()),
+        };
+        for (i, question) in questions.iter().enumerate() {
+            let answer = self.ask_question_direct(&transcript, question)?;
+            let record = ArchivedAnswer {
+                question,
+                answer: &answer,
+                provider: provider.to_string(),
+                model,
+                generated_at: Utc::now().to_rfc3339(),
+                context: ArchivedContext {
+                    strategy: "full_transcript",
+                    source_file: "transcript.txt",
+                    source_sha256: &transcript_hash,
+                },
+            };
+            files.push((
+                format!("answers/{:03}.json", i + 1),
+                serde_json::to_vec_pretty(&record)?,
+            ));
+        }
+
+        let mut entries = Vec::new();
+        let mut previous = String::new();
+        for (path, contents) in &files {
+            fs::write(out_dir.join(path), contents)
+                .with_context(|| format!("Failed to write {}", path))?;
+            let sha256 = sha256_hex(contents);
+            let chain_sha256 = chain_hash(&previous, &sha256);
+            previous = chain_sha256.clone();
+            entries.push(ManifestEntry {
+                path: path.clone(),
+                bytes: contents.len(),
+                sha256,
+                chain_sha256,
+            });
+        }
+
+        let manifest = Manifest {
+            created_at: Utc::now().to_rfc3339(),
+            tool_version: env!("CARGO_PKG_VERSION").to_string(),
+            video_id: transcript.video_id.clone(),
+            url: transcript.url.clone(),
+            entries,
+        };
+        let manifest_bytes = serde_json::to_vec_pretty(&manifest)?;
+        fs::write(out_dir.join("manifest.json"), &manifest_bytes)?;
+        fs::write(
+            out_dir.join("manifest.sha256"),
+            format!("{}  manifest.json\n", sha256_hex(&manifest_bytes)),
+        )?;
+
+        make_read_only(out_dir)?;
+        Ok(manifest)
+    }
+}
+
+/// Check every hash in an archive, returning a list of problems (empty when intact)
+pub fn verify_archive(dir: &Path) -> Result<Vec<String>> {
+    let mut problems = Vec::new();
+
+    let manifest_bytes = fs::read(dir.join("manifest.json")).context("Archive has no manifest.json")?;
+    let recorded = fs::read_to_string(dir.join("manifest.sha256")).context("Archive has no manifest.sha256")?;
+    if recorded.split_whitespace().next() != Some(sha256_hex(&manifest_bytes).as_str()) {
+        problems.push("manifest.json does not match manifest.sha256".to_string());
+    }
+
+    let manifest: Manifest = serde_json::from_slice(&manifest_bytes).context("Failed to parse manifest.json")?;
+    let mut previous = String::new();
+    for entry in &manifest.entries {
+        match fs::read(dir.join(&entry.path)) {
+            Ok(contents) if sha256_hex(&contents) != entry.sha256 => {
+                problems.push(format!("{} has been modified", entry.path));
+            }
+            Ok(_) => {}
+            Err(_) => problems.push(format!("{} is missing", entry.path)),
+        }
+        if chain_hash(&previous, &entry.sha256) != entry.chain_sha256 {
+            problems.push(format!("hash chain broken at {}", entry.path));
+        }
+        previous = entry.chain_sha256.clone();
+    }
+
+    Ok(problems)
+}
+
+fn make_read_only(dir: &Path) -> Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            make_read_only(&path)?;
+        } else {
+            let mut permissions = fs::metadata(&path)?.permissions();
+            permissions.set_readonly(true);
+            fs::set_permissions(&path, permissions)?;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,137 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::{Error, VideoTranscriber, ANSWER_SYSTEM_PROMPT};
+
+/// Audio formats the Gemini File API accepts, by file extension
+const AUDIO_TYPES: &[(&str, &str)] = &[
+    ("mp3", "audio/mp3"),
+    ("wav", "audio/wav"),
+    ("aac", "audio/aac"),
+    ("ogg", "audio/ogg"),
+    ("flac", "audio/flac"),
+    ("aiff", "audio/aiff"),
+];
+
+/// A video's audio track uploaded to Gemini, reused until Gemini deletes it
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct AudioUpload {
+    pub video_id: String,
+    pub title: Option<String>,
+    pub file_uri: String,
+    pub mime_type: String,
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+/// The part of yt-dlp's video information used for the prompt
+#[derive(Deserialize)]
+struct AudioInfo {
+    title: Option<String>,
+}
+
+/// Removes the downloaded audio however the upload ends
+struct TempDir(PathBuf);
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
+
+/// Gemini's MIME type for an audio file, from its extension
+fn audio_mime_type(path: &Path) -> Option<&'static str> {
+    let extension = path.extension()?.to_str()?.to_ascii_lowercase();
+    AUDIO_TYPES
+        .iter()
+        .find(|(ext, _)| *ext == extension)
+        .map(|(_, mime)| *mime)
+}
+
+impl VideoTranscriber {
+    /// Answer a question from the video's audio rather than its transcript, so videos
+    /// without captions can be asked about too. Needs a Gemini API key.
+    pub fn ask_audio(&self, url: &str, question: &str) -> Result<String> {
+        if self.gemini_api_key.is_empty() {
+            return Err(Error::MissingApiKey("GEMINI_API_KEY").into());
+        }
+        let upload = self.audio_upload(url)?;
+
+        status!("🤔 Asking Gemini about the audio: \"{}\"", question);
+        let prompt = self.instructed_prompt(
+            &upload.video_id,
+            format!(
+                "The attached file is the audio of the video \"{}\". Answer this question from what is \
+                 said and heard in it: {}\n\nGive the time (MM:SS) of the moments your answer relies on.",
+                upload.title.as_deref().unwrap_or(&upload.video_id),
+                question
+            ),
+        )?;
+        self.complete_gemini_with_media(ANSWER_SYSTEM_PROMPT, &prompt, &upload.file_uri, &upload.mime_type)
+    }
+
+    /// The video's uploaded audio: the earlier upload while Gemini still has it, otherwise
+    /// a fresh download and upload
+    fn audio_upload(&self, url: &str) -> Result<AudioUpload> {
+        let video_id = self.extract_video_id(url)?;
+        if let Some(upload) = self.cache.get_audio_upload(&video_id)? {
+            let fresh = upload.expires_at.is_none_or(|at| at > Utc::now() + chrono::Duration::minutes(5));
+            if fresh && self.gemini_file(&upload.file_uri)?.is_some_and(|file| file.state == "ACTIVE") {
+                status!("📦 Reusing the audio uploaded earlier");
+                return Ok(upload);
+            }
+        }
+
+        // Local recordings are uploaded as they are when Gemini reads their format
+        if let Some(path) = url.strip_prefix("file://").map(Path::new) {
+            if let Some(mime_type) = audio_mime_type(path) {
+                let title = path.file_stem().map(|stem| stem.to_string_lossy().into_owned());
+                return self.upload_audio(video_id, title, path, mime_type);
+            }
+        }
+
+        let dir = TempDir(std::env::temp_dir().join(format!("cvt-gemini-audio-{}", video_id)));
+        std::fs::create_dir_all(&dir.0)?;
+        status!("📥 Downloading audio with yt-dlp...");
+        self.cancel.check()?;
+        // Mono MP3 at a speech bitrate keeps uploads small; Gemini downsamples audio anyway
+        let output = Command::new("yt-dlp")
+            .args(["--extract-audio", "--audio-format", "mp3", "--audio-quality", "7"])
+            .args(["--postprocessor-args", "ffmpeg:-ac 1", "--no-playlist", "--no-warnings"])
+            .args(["--dump-json", "--no-simulate", "--output"])
+            .arg(dir.0.join("audio.%(ext)s"))
+            .arg(url)
+            .output()
+            .context("Failed to run yt-dlp, which downloads the audio (https://github.com/yt-dlp/yt-dlp)")?;
+        if !output.status.success() {
+            anyhow::bail!("yt-dlp failed: {}", String::from_utf8_lossy(&output.stderr).trim());
+        }
+        let info: AudioInfo =
+            serde_json::from_slice(&output.stdout).context("Failed to parse yt-dlp's video information")?;
+        let title = info.title.or_else(|| self.cache.get(&video_id).ok().flatten().and_then(|t| t.title));
+        self.upload_audio(video_id, title, &dir.0.join("audio.mp3"), "audio/mp3")
+    }
+
+    fn upload_audio(&self, video_id: String, title: Option<String>, path: &Path, mime_type: &str) -> Result<AudioUpload> {
+        let size = std::fs::metadata(path)
+            .with_context(|| format!("Failed to read {}", path.display()))?
+            .len() as usize;
+        status!("☁️  Uploading {:.1} MB of audio to Gemini File API...", size as f64 / 1_048_576.0);
+        let file_name = format!("youtube_audio_{}.{}", video_id, path.extension().and_then(|e| e.to_str()).unwrap_or("mp3"));
+        let file = self.upload_file(&file_name, path, 0..size, mime_type)?;
+
+        let upload = AudioUpload {
+            video_id,
+            title,
+            file_uri: file.uri,
+            mime_type: mime_type.to_string(),
+            expires_at: file.expiration_time,
+        };
+        if let Err(e) = self.cache.put_audio_upload(&upload) {
+            status!("⚠️  Could not remember the audio upload: {}", e);
+        }
+        Ok(upload)
+    }
+}
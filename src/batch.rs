@@ -0,0 +1,165 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::collections::HashSet;
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Instant;
+
+use crate::parallel::map_bounded;
+use crate::playlist::PlaylistOutcome;
+use crate::VideoTranscriber;
+
+/// Read a list of video URLs, one per line. Blank lines and lines starting with `#` are
+/// skipped, as is anything after whitespace on a line, so a URL can be followed by a note.
+pub fn read_url_list(path: &Path) -> Result<Vec<String>> {
+    let contents = std::fs::read_to_string(path).with_context(|| format!("Failed to read {}", path.display()))?;
+    let urls: Vec<String> = contents
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .filter_map(|line| line.split_whitespace().next())
+        .map(str::to_string)
+        .collect();
+    if urls.is_empty() {
+        anyhow::bail!("{} has no URLs", path.display());
+    }
+    Ok(urls)
+}
+
+/// What happened to one line of a batch
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum BatchStatus {
+    Indexed,
+    /// Its Gemini files were still live from an earlier run
+    AlreadyIndexed,
+    /// The same video is on an earlier line
+    Duplicate,
+    Failed,
+}
+
+impl BatchStatus {
+    pub fn label(self) -> &'static str {
+        match self {
+            BatchStatus::Indexed => "indexed",
+            BatchStatus::AlreadyIndexed => "already indexed",
+            BatchStatus::Duplicate => "duplicate",
+            BatchStatus::Failed => "failed",
+        }
+    }
+}
+
+/// The outcome of one URL of a batch
+#[derive(Serialize, Debug)]
+pub struct BatchItem {
+    /// The URL as written in the file
+    pub url: String,
+    /// Absent when the line isn't a video URL
+    pub video_id: Option<String>,
+    pub title: Option<String>,
+    pub status: BatchStatus,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub file_uris: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    pub secs: f64,
+}
+
+/// Every URL of a batch in file order, with totals; written as the results file
+#[derive(Serialize, Debug)]
+pub struct BatchSummary {
+    pub started_at: DateTime<Utc>,
+    pub finished_at: DateTime<Utc>,
+    pub concurrency: usize,
+    pub total: usize,
+    pub indexed: usize,
+    pub already_indexed: usize,
+    pub duplicates: usize,
+    pub failed: usize,
+    pub videos: Vec<BatchItem>,
+}
+
+impl BatchSummary {
+    fn count(&self, status: BatchStatus) -> usize {
+        self.videos.iter().filter(|item| item.status == status).count()
+    }
+}
+
+impl VideoTranscriber {
+    /// Index every video in `urls`, `concurrency` at a time. A video that fails doesn't stop
+    /// the others; it is recorded with its error. Videos still indexed from an earlier run are
+    /// skipped unless `reindex`, so a batch that was interrupted can simply be run again.
+    pub fn index_batch(&self, urls: &[String], reindex: bool, concurrency: usize) -> BatchSummary {
+        let started_at = Utc::now();
+        let total = urls.len();
+        status!("🚀 Indexing {} video(s), {} at a time", total, concurrency.max(1));
+
+        // Resolve IDs up front, so duplicates are caught before two workers fetch the same video
+        let mut seen = HashSet::new();
+        let lines: Vec<(&String, Result<String>, bool)> = urls
+            .iter()
+            .map(|url| {
+                let video_id = self.extract_video_id(url);
+                let duplicate = video_id.as_ref().is_ok_and(|id| !seen.insert(id.clone()));
+                (url, video_id, duplicate)
+            })
+            .collect();
+
+        let done = AtomicUsize::new(0);
+        let videos = map_bounded(&lines, concurrency, |(url, video_id, duplicate)| {
+            let started = Instant::now();
+            let mut item = BatchItem {
+                url: url.to_string(),
+                video_id: video_id.as_ref().ok().cloned(),
+                title: None,
+                status: BatchStatus::Failed,
+                file_uris: Vec::new(),
+                error: None,
+                secs: 0.0,
+            };
+            match video_id {
+                Err(e) => item.error = Some(format!("{:#}", e)),
+                Ok(_) if *duplicate => item.status = BatchStatus::Duplicate,
+                Ok(id) => {
+                    // A watch URL copied from a playlist would otherwise fetch the whole playlist
+                    let video_url = if url.starts_with("file://") {
+                        url.to_string()
+                    } else {
+                        format!("https://www.youtube.com/watch?v={}", id)
+                    };
+                    match self.index_unless_live(id, &video_url, url, reindex) {
+                        PlaylistOutcome::Indexed(parts) => {
+                            item.status = BatchStatus::Indexed;
+                            item.file_uris = parts.into_iter().map(|part| part.file_uri).collect();
+                        }
+                        PlaylistOutcome::AlreadyIndexed => item.status = BatchStatus::AlreadyIndexed,
+                        PlaylistOutcome::Failed(e) => item.error = Some(format!("{:#}", e)),
+                    }
+                    item.title = self.cache.get(id).ok().flatten().and_then(|transcript| transcript.title);
+                }
+            }
+            item.secs = started.elapsed().as_secs_f64();
+            let finished = done.fetch_add(1, Ordering::SeqCst) + 1;
+            status!("   [{}/{}] {}: {}", finished, total, item.status.label(), url);
+            item
+        });
+
+        let mut summary = BatchSummary {
+            started_at,
+            finished_at: Utc::now(),
+            concurrency: concurrency.max(1),
+            total,
+            indexed: 0,
+            already_indexed: 0,
+            duplicates: 0,
+            failed: 0,
+            videos,
+        };
+        summary.indexed = summary.count(BatchStatus::Indexed);
+        summary.already_indexed = summary.count(BatchStatus::AlreadyIndexed);
+        summary.duplicates = summary.count(BatchStatus::Duplicate);
+        summary.failed = summary.count(BatchStatus::Failed);
+        summary
+    }
+}
@@ -0,0 +1,199 @@
+use anyhow::Result;
+use std::time::{Duration, Instant};
+
+use crate::embeddings::plan_batches;
+use crate::guardrail::fence;
+use crate::retrieval::chunk_transcript;
+use crate::{VideoTranscriber, ANSWER_SYSTEM_PROMPT};
+
+const BENCH_QUESTION: &str = "Summarize this video in three sentences.";
+
+/// Approximate list prices in USD per million (input, output) tokens
+const MODEL_PRICES: &[(&str, f64, f64)] = &[
+    ("llama-3.3-70b-versatile", 0.59, 0.79),
+    ("llama-3.1-8b-instant", 0.05, 0.08),
+    ("gemini-1.5-flash", 0.075, 0.30),
+    ("gemini-1.5-pro", 1.25, 5.00),
+    ("gemini-2.0-flash", 0.10, 0.40),
+    ("text-embedding-004", 0.0, 0.0),
+];
+
+/// Rough token count used for cost estimates (about four characters per token)
+fn estimate_tokens(text: &str) -> f64 {
+    text.len() as f64 / 4.0
+}
+
+fn estimate_cost(model: &str, input: &str, output: &str) -> Option<f64> {
+    model_cost_usd(model, estimate_tokens(input) as u64, estimate_tokens(output) as u64)
+}
+
+/// Estimated cost in USD of a call to `model` with the given token counts, for models with a
+/// known list price
+pub fn model_cost_usd(model: &str, input_tokens: u64, output_tokens: u64) -> Option<f64> {
+    let (_, input_price, output_price) = MODEL_PRICES.iter().find(|(name, _, _)| *name == model)?;
+    Some((input_tokens as f64 * input_price + output_tokens as f64 * output_price) / 1_000_000.0)
+}
+
+/// Timing and estimated cost of one pipeline stage
+pub struct StageResult {
+    pub stage: String,
+    pub provider: String,
+    pub outcome: Result<Duration>,
+    pub cost_usd: Option<f64>,
+    pub note: String,
+}
+
+fn timed<T>(f: impl FnOnce() -> Result<T>) -> (Result<Duration>, Option<T>) {
+    let started = Instant::now();
+    match f() {
+        Ok(value) => (Ok(started.elapsed()), Some(value)),
+        Err(e) => (Err(e), None),
+    }
+}
+
+impl VideoTranscriber {
+    /// Time each pipeline stage on one video, continuing past stages that fail
+    pub fn bench(&self, url: &str, fresh_fetch: bool, embed_chunks: usize) -> Result<Vec<StageResult>> {
+        let mut results = Vec::new();
+
+        // Apify bills per run, so its cost is the change in account usage across the fetch
+        let usage_before = if fresh_fetch {
+            self.get_account_limits().ok().map(|l| l.current.monthly_usage_usd)
+        } else {
+            None
+        };
+        let (outcome, transcript) = timed(|| self.load_transcript(url, fresh_fetch));
+        let usage_after = if fresh_fetch {
+            self.get_account_limits().ok().map(|l| l.current.monthly_usage_usd)
+        } else {
+            None
+        };
+        results.push(StageResult {
+            stage: "fetch".to_string(),
+            provider: if fresh_fetch { "apify" } else { "cache" }.to_string(),
+            outcome,
+            cost_usd: if fresh_fetch {
+                usage_before.zip(usage_after).map(|(before, after)| (after - before).max(0.0))
+            } else {
+                Some(0.0)
+            },
+            note: String::new(),
+        });
+        let Some(transcript) = transcript else {
+            return Ok(results);
+        };
+
+        let params = self.retrieval_params();
+        let (outcome, chunks) =
+            timed(|| Ok(chunk_transcript(&transcript, params.chunk_chars, params.chunk_overlap)));
+        let chunks = chunks.unwrap_or_default();
+        results.push(StageResult {
+            stage: "chunk".to_string(),
+            provider: "local".to_string(),
+            outcome,
+            cost_usd: Some(0.0),
+            note: format!("{} chunks from {} chars", chunks.len(), transcript.text.len()),
+        });
+
+        if !self.gemini_api_key.is_empty() {
+            let sample: Vec<&str> = chunks.iter().take(embed_chunks).map(|c| c.text.as_str()).collect();
+            let (outcome, _) = timed(|| self.embed_texts(&sample));
+            let input: String = sample.concat();
+            results.push(StageResult {
+                stage: "embed".to_string(),
+                provider: "gemini".to_string(),
+                outcome,
+                cost_usd: estimate_cost(crate::embeddings::EMBEDDING_MODEL, &input, ""),
+                note: format!(
+                    "{} chunk(s) in {} batch request(s)",
+                    sample.len(),
+                    plan_batches(&sample).len()
+                ),
+            });
+
+            let (outcome, _) = timed(|| self.upload_to_gemini(&transcript));
+            results.push(StageResult {
+                stage: "upload".to_string(),
+                provider: "gemini".to_string(),
+                outcome,
+                cost_usd: Some(0.0),
+                note: String::new(),
+            });
+        }
+
+        let prompt = format!("{}\n\n{}", BENCH_QUESTION, fence(&transcript.text));
+        let providers: [(&str, &str, bool); 2] = [
+            ("groq", &self.groq_model, !self.groq_api_key.is_empty()),
+            ("gemini", &self.gemini_model, !self.gemini_api_key.is_empty()),
+        ];
+        for (provider, model, available) in providers {
+            if !available {
+                continue;
+            }
+            let (outcome, answer) = timed(|| match provider {
+                "groq" => self.complete_groq(ANSWER_SYSTEM_PROMPT, &prompt),
+                _ => self.complete_gemini(ANSWER_SYSTEM_PROMPT, &prompt),
+            });
+            results.push(StageResult {
+                stage: "generate".to_string(),
+                provider: provider.to_string(),
+                cost_usd: answer
+                    .as_deref()
+                    .and_then(|answer| estimate_cost(model, &prompt, answer)),
+                outcome,
+                note: model.to_string(),
+            });
+        }
+
+        Ok(results)
+    }
+}
+
+/// Plain-text table of stage timings and costs
+pub fn render_bench(results: &[StageResult]) -> String {
+    let mut out = format!(
+        "{:<10} {:<8} {:>10} {:>11}  Notes\n",
+        "Stage", "Provider", "Time", "Est. cost"
+    );
+    let mut total_time = Duration::ZERO;
+    let mut total_cost = 0.0;
+
+    for result in results {
+        let cost = match result.cost_usd {
+            Some(cost) => {
+                total_cost += cost;
+                format!("${:.4}", cost)
+            }
+            None => "n/a".to_string(),
+        };
+        match &result.outcome {
+            Ok(elapsed) => {
+                total_time += *elapsed;
+                let line = format!(
+                    "{:<10} {:<8} {:>9.2}s {:>11}  {}",
+                    result.stage,
+                    result.provider,
+                    elapsed.as_secs_f64(),
+                    cost,
+                    result.note
+                );
+                out.push_str(line.trim_end());
+                out.push('\n');
+            }
+            Err(e) => out.push_str(&format!(
+                "{:<10} {:<8} {:>10} {:>11}  {}\n",
+                result.stage, result.provider, "failed", "-", e
+            )),
+        }
+    }
+
+    out.push_str(&format!(
+        "{:<10} {:<8} {:>9.2}s {:>11}\n",
+        "total",
+        "",
+        total_time.as_secs_f64(),
+        format!("${:.4}", total_cost)
+    ));
+    out.push_str("\nCosts are estimates from list prices and ~4 characters per token.\n");
+    out
+}
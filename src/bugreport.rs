@@ -0,0 +1,100 @@
+use anyhow::{Context, Result};
+use chrono::Utc;
+use std::env;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use zip::write::SimpleFileOptions;
+use zip::ZipWriter;
+
+use crate::config::Config;
+use crate::debuglog::{last_error_path, last_http_failure_path, scrub};
+
+const REPORT_README: &str = "\
+Bug report bundle for claude-video-transcribe.
+
+versions.txt             app version, OS, and which settings are configured (not their values)
+config.toml              your config file with API keys and webhook URLs replaced by [REDACTED]
+last_error.log           the most recent failed command and its full error chain
+last_http_failure.json   the most recent API call that returned an error status
+
+API keys are scrubbed from every file. Please look through them before attaching
+this zip to a GitHub issue: transcripts, questions, and video URLs from the failing
+command may appear in the logs.
+";
+
+/// The config with every credential replaced, so the file can be shared
+fn redacted_config() -> Result<String> {
+    let mut config = Config::load()?;
+    let redact = |value: &mut Option<String>| {
+        if value.is_some() {
+            *value = Some("[REDACTED]".to_string());
+        }
+    };
+    redact(&mut config.apify_api_key);
+    redact(&mut config.gemini_api_key);
+    redact(&mut config.groq_api_key);
+    redact(&mut config.web_search_api_key);
+    if let Some(notify) = config.notify.as_mut() {
+        redact(&mut notify.webhook_url);
+    }
+    Ok(scrub(&toml::to_string_pretty(&config)?))
+}
+
+fn versions() -> String {
+    let is_set = |name: &str| if env::var(name).is_ok() { "set" } else { "not set" };
+    format!(
+        "claude-video-transcribe {}\nos: {} ({}, {})\n\nenvironment:\n  APIFY_API_KEY: {}\n  GEMINI_API_KEY: {}\n  GROQ_API_KEY: {}\n  LLM_PROVIDER: {}\n  GROQ_MODEL: {}\n  GEMINI_MODEL: {}\n",
+        env!("CARGO_PKG_VERSION"),
+        env::consts::OS,
+        env::consts::ARCH,
+        env::consts::FAMILY,
+        is_set("APIFY_API_KEY"),
+        is_set("GEMINI_API_KEY"),
+        is_set("GROQ_API_KEY"),
+        env::var("LLM_PROVIDER").unwrap_or_else(|_| "not set".to_string()),
+        env::var("GROQ_MODEL").unwrap_or_else(|_| "not set".to_string()),
+        env::var("GEMINI_MODEL").unwrap_or_else(|_| "not set".to_string()),
+    )
+}
+
+/// Write a zip with the logs kept in `data_dir`, versions, and the redacted config, returning
+/// its path
+pub fn create_bundle(data_dir: &Path, out: Option<PathBuf>) -> Result<PathBuf> {
+    let path = out.unwrap_or_else(|| {
+        PathBuf::from(format!(
+            "cvt-bug-report-{}.zip",
+            Utc::now().format("%Y%m%d-%H%M%S")
+        ))
+    });
+
+    let mut files: Vec<(&str, String)> = vec![
+        ("README.txt", REPORT_README.to_string()),
+        ("versions.txt", versions()),
+        ("config.toml", redacted_config()?),
+    ];
+    for (name, source) in [
+        ("last_error.log", last_error_path(data_dir)),
+        ("last_http_failure.json", last_http_failure_path(data_dir)),
+    ] {
+        if source.exists() {
+            let contents = fs::read_to_string(&source)
+                .with_context(|| format!("Failed to read {}", source.display()))?;
+            // Logs are scrubbed when written; scrub again in case keys changed since
+            files.push((name, scrub(&contents)));
+        } else {
+            status!("ℹ️  No {} yet", name);
+        }
+    }
+
+    let file = fs::File::create(&path)
+        .with_context(|| format!("Failed to create {}", path.display()))?;
+    let mut zip = ZipWriter::new(file);
+    for (name, contents) in files {
+        zip.start_file(name, SimpleFileOptions::default())?;
+        zip.write_all(contents.as_bytes())?;
+    }
+    zip.finish().context("Failed to finish the bug report zip")?;
+
+    Ok(path)
+}
@@ -0,0 +1,294 @@
+use anyhow::{Context, Result};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::cache::TranscriptCache;
+use crate::cancel::CancellationToken;
+use crate::config::{self, setting, Config};
+use crate::hooks::Subscriber;
+use crate::llm::LlmProvider;
+use crate::registry::Registry;
+use crate::sources::{SourceKind, TranscriptSource};
+use crate::store::IndexStore;
+use crate::apify::APIFY_POLL_INTERVAL;
+use crate::http::Endpoints;
+use crate::{http, Error, VideoTranscriber, DEFAULT_GEMINI_MODEL, DEFAULT_GROQ_MODEL};
+
+/// Configures a `VideoTranscriber` in code. Anything not set falls back to the environment,
+/// `.env`, and the config file like the CLI, unless `ignore_environment` is called.
+#[derive(Default)]
+pub struct VideoTranscriberBuilder {
+    cancel: Option<CancellationToken>,
+    config: Option<Config>,
+    ignore_environment: bool,
+    apify_api_key: Option<String>,
+    gemini_api_key: Option<String>,
+    groq_api_key: Option<String>,
+    provider: Option<LlmProvider>,
+    groq_model: Option<String>,
+    gemini_model: Option<String>,
+    source_kind: Option<SourceKind>,
+    source: Option<Arc<dyn TranscriptSource>>,
+    store_dir: Option<PathBuf>,
+    index_store: Option<Arc<dyn IndexStore>>,
+    client: Option<reqwest::blocking::Client>,
+    endpoints: Option<Endpoints>,
+    apify_poll_interval: Option<Duration>,
+    subscribers: Vec<Arc<dyn Subscriber>>,
+}
+
+impl VideoTranscriber {
+    /// Start configuring a transcriber in code rather than through environment variables
+    pub fn builder() -> VideoTranscriberBuilder {
+        VideoTranscriberBuilder::default()
+    }
+}
+
+impl VideoTranscriberBuilder {
+    /// The token that cancels every call; a fresh one without a deadline by default
+    pub fn cancel_token(mut self, cancel: CancellationToken) -> Self {
+        self.cancel = Some(cancel);
+        self
+    }
+
+    /// Use this config instead of reading the config file
+    pub fn config(mut self, config: Config) -> Self {
+        self.config = Some(config);
+        self
+    }
+
+    /// Read nothing from environment variables or `.env`, and use only the setters and
+    /// `config` (the config file is still read when `config` is not given). This covers
+    /// tuning variables such as `CHUNK_CHARS` and `CVT_DATA_DIR` as well as keys.
+    pub fn ignore_environment(mut self) -> Self {
+        self.ignore_environment = true;
+        self
+    }
+
+    pub fn apify_api_key(mut self, key: impl Into<String>) -> Self {
+        self.apify_api_key = Some(key.into());
+        self
+    }
+
+    pub fn gemini_api_key(mut self, key: impl Into<String>) -> Self {
+        self.gemini_api_key = Some(key.into());
+        self
+    }
+
+    pub fn groq_api_key(mut self, key: impl Into<String>) -> Self {
+        self.groq_api_key = Some(key.into());
+        self
+    }
+
+    /// Which provider answers questions
+    pub fn provider(mut self, provider: LlmProvider) -> Self {
+        self.provider = Some(provider);
+        self
+    }
+
+    /// Model for Groq answers
+    pub fn groq_model(mut self, model: impl Into<String>) -> Self {
+        self.groq_model = Some(model.into());
+        self
+    }
+
+    /// Model for Gemini answers
+    pub fn gemini_model(mut self, model: impl Into<String>) -> Self {
+        self.gemini_model = Some(model.into());
+        self
+    }
+
+    /// One of the built-in transcript backends
+    pub fn source(mut self, kind: SourceKind) -> Self {
+        self.source_kind = Some(kind);
+        self.source = None;
+        self
+    }
+
+    /// A transcript backend of the caller's own; see `sources::TranscriptSource`
+    pub fn transcript_source(mut self, source: impl TranscriptSource + 'static) -> Self {
+        self.source = Some(Arc::new(source));
+        self.source_kind = None;
+        self
+    }
+
+    /// Keep everything the transcriber stores in this directory instead of the platform cache
+    /// and data directories: the transcript cache (`transcripts/`), the index registry
+    /// (`index.db`, unless `index_store` is given), and feedback, instructions, search indexes,
+    /// and job, usage, and failure logs.
+    pub fn store_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.store_dir = Some(dir.into());
+        self
+    }
+
+    /// Keep the index in the caller's own database instead of the SQLite registry; see
+    /// `store::IndexStore`
+    pub fn index_store(mut self, store: impl IndexStore + 'static) -> Self {
+        self.index_store = Some(Arc::new(store));
+        self
+    }
+
+    /// Send every API call through this client, e.g. one with a proxy or custom TLS roots,
+    /// instead of one built from the `[http]` config section
+    pub fn http_client(mut self, client: reqwest::blocking::Client) -> Self {
+        self.client = Some(client);
+        self
+    }
+
+    /// Send API requests to these base URLs instead of the public services, e.g. a gateway or
+    /// a test server; see `http::Endpoints`
+    pub fn endpoints(mut self, endpoints: Endpoints) -> Self {
+        self.endpoints = Some(endpoints);
+        self
+    }
+
+    /// Check on Apify runs this often instead of every 5 seconds. The wait for a run still
+    /// gives up after 5 minutes.
+    pub fn apify_poll_interval(mut self, interval: Duration) -> Self {
+        self.apify_poll_interval = Some(interval);
+        self
+    }
+
+    /// Observe, meter, or veto pipeline stages; see `hooks::Subscriber`. Can be called more
+    /// than once.
+    pub fn subscriber(mut self, subscriber: impl Subscriber + 'static) -> Self {
+        self.subscribers.push(Arc::new(subscriber));
+        self
+    }
+
+    /// An explicit value, else the environment variable or config file value
+    fn resolve(&self, explicit: &Option<String>, env_name: &str, file_value: &Option<String>) -> Option<String> {
+        explicit.clone().filter(|value| !value.is_empty()).or_else(|| {
+            if self.ignore_environment {
+                file_value.clone().filter(|value| !value.is_empty())
+            } else {
+                setting(env_name, file_value)
+            }
+        })
+    }
+
+    pub fn build(self) -> Result<VideoTranscriber> {
+        if !self.ignore_environment {
+            dotenv::dotenv().ok(); // Load .env file if it exists
+        }
+        let config = match &self.config {
+            Some(config) => config.clone(),
+            None if self.ignore_environment => Config::load_from(&Config::platform_path()?)?,
+            None => Config::load()?,
+        };
+        if let Some(retrieval) = &config.retrieval {
+            retrieval.validate()?;
+        }
+
+        let apify_api_key = self.resolve(&self.apify_api_key, "APIFY_API_KEY", &config.apify_api_key);
+        let gemini_api_key = self
+            .resolve(&self.gemini_api_key, "GEMINI_API_KEY", &config.gemini_api_key)
+            .unwrap_or_default();
+        let groq_api_key = self
+            .resolve(&self.groq_api_key, "GROQ_API_KEY", &config.groq_api_key)
+            .unwrap_or_default();
+
+        let source = match (&self.source, self.source_kind) {
+            (Some(source), _) => source.clone(),
+            (None, kind) => {
+                let kind = match kind {
+                    Some(kind) => kind,
+                    None => SourceKind::from_setting(self.resolve(&None, "TRANSCRIPT_SOURCE", &config.transcript_source))?,
+                };
+                // yt-dlp and YouTube need no key; Apify is then only used for playlists, channels, and `runs`
+                if kind == SourceKind::Apify && apify_api_key.is_none() {
+                    return Err(Error::MissingApiKey("APIFY_API_KEY").into());
+                }
+                Arc::from(kind.backend())
+            }
+        };
+
+        // Determine which provider to use
+        let llm_provider = match self.provider {
+            Some(provider) => provider,
+            None => {
+                let provider_str = self
+                    .resolve(&None, "LLM_PROVIDER", &config.llm_provider)
+                    .unwrap_or_else(|| "groq".to_string());
+                match provider_str.to_lowercase().as_str() {
+                    "gemini" => LlmProvider::Gemini,
+                    "groq" => LlmProvider::Groq,
+                    _ => {
+                        status!("⚠️  Unknown LLM_PROVIDER '{}', defaulting to Groq", provider_str);
+                        LlmProvider::Groq
+                    }
+                }
+            }
+        };
+
+        // Validate that the selected provider has an API key
+        match llm_provider {
+            LlmProvider::Gemini if gemini_api_key.is_empty() => {
+                return Err(Error::ProviderKey { key: "GEMINI_API_KEY", provider: "gemini" }.into());
+            }
+            LlmProvider::Groq if groq_api_key.is_empty() => {
+                return Err(Error::ProviderKey { key: "GROQ_API_KEY", provider: "groq" }.into());
+            }
+            _ => {}
+        }
+
+        let groq_model = self
+            .resolve(&self.groq_model, "GROQ_MODEL", &config.groq_model)
+            .unwrap_or_else(|| DEFAULT_GROQ_MODEL.to_string());
+        let gemini_model = self
+            .resolve(&self.gemini_model, "GEMINI_MODEL", &config.gemini_model)
+            .unwrap_or_else(|| DEFAULT_GEMINI_MODEL.to_string());
+
+        let client = match self.client {
+            Some(client) => client,
+            None => http::build_client(&config.http.clone().unwrap_or_default(), Duration::from_secs(300))?,
+        };
+
+        let (cache, data_dir) = match &self.store_dir {
+            Some(dir) => {
+                fs::create_dir_all(dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+                (TranscriptCache::open_at(dir.join("transcripts"))?, dir.clone())
+            }
+            None if self.ignore_environment => {
+                (TranscriptCache::open_at(TranscriptCache::platform_dir()?)?, config::platform_data_dir()?)
+            }
+            None => (TranscriptCache::open()?, config::data_dir()?),
+        };
+        let registry: Arc<dyn IndexStore> = match (self.index_store, &self.store_dir) {
+            (Some(store), _) => store,
+            (None, Some(dir)) => Arc::new(Registry::open_at(&dir.join("index.db"))?),
+            (None, None) => Arc::new(Registry::open_in(&data_dir)?),
+        };
+
+        status!("🤖 Using LLM provider: {:?}", llm_provider);
+
+        Ok(VideoTranscriber {
+            apify_api_key: apify_api_key.unwrap_or_default(),
+            gemini_api_key,
+            groq_api_key,
+            llm_provider,
+            groq_model,
+            gemini_model,
+            client,
+            endpoints: self.endpoints.unwrap_or_default(),
+            apify_poll_interval: self.apify_poll_interval.unwrap_or(APIFY_POLL_INTERVAL),
+            credits_checked_at: Arc::default(),
+            cancel: self.cancel.unwrap_or_default(),
+            source,
+            cache,
+            registry,
+            config,
+            data_dir,
+            ignore_environment: self.ignore_environment,
+            answer_style: None,
+            max_words: None,
+            answer_language: None,
+            strategy: None,
+            tier: None,
+            progress: None,
+            subscribers: self.subscribers,
+        })
+    }
+}
@@ -0,0 +1,329 @@
+use anyhow::{Context, Result};
+use std::env;
+use std::fs::{self, File};
+use std::io::{BufReader, BufWriter, Write};
+use sha2::{Digest, Sha256};
+use std::path::PathBuf;
+
+use crate::audio::AudioUpload;
+use crate::notes::Notes;
+use crate::chat::ChatLog;
+use crate::chunking::ChunkMap;
+use crate::context_cache::CachedContext;
+use crate::transcript::Transcript;
+
+/// On-disk cache of fetched transcripts, one JSON file per video ID
+#[derive(Clone)]
+pub struct TranscriptCache {
+    dir: PathBuf,
+}
+
+impl TranscriptCache {
+    /// Open the cache directory (`CVT_CACHE_DIR` overrides the platform default)
+    pub fn open() -> Result<Self> {
+        let dir = match env::var("CVT_CACHE_DIR") {
+            Ok(dir) => PathBuf::from(dir),
+            Err(_) => Self::platform_dir()?,
+        };
+        Self::open_at(dir)
+    }
+
+    /// The platform's cache directory for transcripts, whatever `CVT_CACHE_DIR` says
+    pub fn platform_dir() -> Result<PathBuf> {
+        Ok(dirs::cache_dir()
+            .context("Could not determine the cache directory")?
+            .join("claude-video-transcribe")
+            .join("transcripts"))
+    }
+
+    /// Open a cache directory of the caller's choosing, creating it if needed
+    pub fn open_at(dir: PathBuf) -> Result<Self> {
+        fs::create_dir_all(&dir)
+            .with_context(|| format!("Failed to create cache directory {}", dir.display()))?;
+
+        Ok(Self { dir })
+    }
+
+    fn path_for(&self, video_id: &str) -> PathBuf {
+        self.dir.join(format!("{}.json", video_id))
+    }
+
+    /// Whether a transcript is cached for the video, without reading it
+    pub fn contains(&self, video_id: &str) -> bool {
+        self.path_for(video_id).exists()
+    }
+
+    /// Look up a cached transcript by video ID
+    pub fn get(&self, video_id: &str) -> Result<Option<Transcript>> {
+        let path = self.path_for(video_id);
+        if !path.exists() {
+            return Ok(None);
+        }
+
+        Ok(Some(read_transcript(&path)?))
+    }
+
+    /// Store (or replace) a transcript in the cache
+    pub fn put(&self, transcript: &Transcript) -> Result<()> {
+        let path = self.path_for(&transcript.video_id);
+        let file = File::create(&path)
+            .with_context(|| format!("Failed to write cached transcript {}", path.display()))?;
+        let mut writer = BufWriter::new(file);
+        serde_json::to_writer_pretty(&mut writer, transcript)?;
+        writer
+            .flush()
+            .with_context(|| format!("Failed to write cached transcript {}", path.display()))?;
+
+        self.write_text(transcript)?;
+        Ok(())
+    }
+
+    /// Plain-text copy of the transcript on disk, so uploads can stream it instead of copying it.
+    /// Rewritten whenever its contents differ from `transcript`, even at the same length.
+    pub fn text_file(&self, transcript: &Transcript) -> Result<PathBuf> {
+        let path = self.dir.join(format!("{}.txt", transcript.video_id));
+        let current = fs::read(&path).ok().map(|bytes| hex::encode(Sha256::digest(bytes)));
+        if current.as_deref() != Some(transcript.text_sha256().as_str()) {
+            self.write_text(transcript)?;
+        }
+        Ok(path)
+    }
+
+    fn write_text(&self, transcript: &Transcript) -> Result<PathBuf> {
+        let path = self.dir.join(format!("{}.txt", transcript.video_id));
+        fs::write(&path, transcript.text.as_bytes())
+            .with_context(|| format!("Failed to write {}", path.display()))?;
+        Ok(path)
+    }
+
+    /// Chat replays live in their own directory so `list` never mistakes one for a transcript
+    fn chat_path(&self, video_id: &str) -> PathBuf {
+        self.dir.join("chat").join(format!("{}.json", video_id))
+    }
+
+    /// Look up a video's stored chat replay
+    pub fn get_chat(&self, video_id: &str) -> Result<Option<ChatLog>> {
+        let path = self.chat_path(video_id);
+        if !path.exists() {
+            return Ok(None);
+        }
+        let file = File::open(&path).with_context(|| format!("Failed to read chat replay {}", path.display()))?;
+        serde_json::from_reader(BufReader::new(file))
+            .map(Some)
+            .with_context(|| format!("Failed to parse chat replay {}", path.display()))
+    }
+
+    /// Store (or replace) a video's chat replay
+    pub fn put_chat(&self, log: &ChatLog) -> Result<()> {
+        let path = self.chat_path(&log.video_id);
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+        }
+        let file = File::create(&path).with_context(|| format!("Failed to write chat replay {}", path.display()))?;
+        let mut writer = BufWriter::new(file);
+        serde_json::to_writer(&mut writer, log)?;
+        writer
+            .flush()
+            .with_context(|| format!("Failed to write chat replay {}", path.display()))
+    }
+
+    fn audio_path(&self, video_id: &str) -> PathBuf {
+        self.dir.join("audio").join(format!("{}.json", video_id))
+    }
+
+    /// Look up a video's audio upload from an earlier `ask --audio`
+    pub fn get_audio_upload(&self, video_id: &str) -> Result<Option<AudioUpload>> {
+        let path = self.audio_path(video_id);
+        if !path.exists() {
+            return Ok(None);
+        }
+        let contents =
+            fs::read_to_string(&path).with_context(|| format!("Failed to read audio upload {}", path.display()))?;
+        serde_json::from_str(&contents)
+            .map(Some)
+            .with_context(|| format!("Failed to parse audio upload {}", path.display()))
+    }
+
+    /// Remember where a video's audio was uploaded so later questions can reuse it
+    pub fn put_audio_upload(&self, upload: &AudioUpload) -> Result<()> {
+        let path = self.audio_path(&upload.video_id);
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+        }
+        fs::write(&path, serde_json::to_string_pretty(upload)?)
+            .with_context(|| format!("Failed to write audio upload {}", path.display()))
+    }
+
+    fn context_path(&self, video_id: &str) -> PathBuf {
+        self.dir.join("contexts").join(format!("{}.json", video_id))
+    }
+
+    /// Look up the Gemini cached context holding a video's transcript
+    pub fn get_context(&self, video_id: &str) -> Result<Option<CachedContext>> {
+        let path = self.context_path(video_id);
+        if !path.exists() {
+            return Ok(None);
+        }
+        let contents =
+            fs::read_to_string(&path).with_context(|| format!("Failed to read cached context {}", path.display()))?;
+        serde_json::from_str(&contents)
+            .map(Some)
+            .with_context(|| format!("Failed to parse cached context {}", path.display()))
+    }
+
+    /// Remember a video's Gemini cached context so later questions can reuse it
+    pub fn put_context(&self, context: &CachedContext) -> Result<()> {
+        let path = self.context_path(&context.video_id);
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+        }
+        fs::write(&path, serde_json::to_string_pretty(context)?)
+            .with_context(|| format!("Failed to write cached context {}", path.display()))
+    }
+
+    /// Forget a video's cached context once Gemini no longer has it
+    pub fn remove_context(&self, video_id: &str) -> Result<()> {
+        match fs::remove_file(self.context_path(video_id)) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e).context("Failed to remove cached context"),
+            _ => Ok(()),
+        }
+    }
+
+    fn chunks_path(&self, video_id: &str) -> PathBuf {
+        self.dir.join("chunks").join(format!("{}.json", video_id))
+    }
+
+    /// Look up the chunk boundaries stored for a video's transcript
+    pub fn get_chunk_map(&self, video_id: &str) -> Result<Option<ChunkMap>> {
+        let path = self.chunks_path(video_id);
+        if !path.exists() {
+            return Ok(None);
+        }
+        let contents =
+            fs::read_to_string(&path).with_context(|| format!("Failed to read chunk boundaries {}", path.display()))?;
+        serde_json::from_str(&contents)
+            .map(Some)
+            .with_context(|| format!("Failed to parse chunk boundaries {}", path.display()))
+    }
+
+    /// Store a video's chunk boundaries so later questions retrieve from the same chunks
+    pub fn put_chunk_map(&self, map: &ChunkMap) -> Result<()> {
+        let path = self.chunks_path(&map.video_id);
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+        }
+        fs::write(&path, serde_json::to_string(map)?)
+            .with_context(|| format!("Failed to write chunk boundaries {}", path.display()))
+    }
+
+    fn notes_path(&self, video_id: &str) -> PathBuf {
+        self.dir.join("notes").join(format!("{}.json", video_id))
+    }
+
+    /// Look up the notes last written for a video by `notes`
+    pub fn get_notes(&self, video_id: &str) -> Result<Option<Notes>> {
+        let path = self.notes_path(video_id);
+        if !path.exists() {
+            return Ok(None);
+        }
+        let contents =
+            fs::read_to_string(&path).with_context(|| format!("Failed to read notes {}", path.display()))?;
+        serde_json::from_str(&contents)
+            .map(Some)
+            .with_context(|| format!("Failed to parse notes {}", path.display()))
+    }
+
+    /// Keep a video's notes so the next `notes` run can update them
+    pub fn put_notes(&self, notes: &Notes) -> Result<()> {
+        let path = self.notes_path(&notes.video_id);
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+        }
+        fs::write(&path, serde_json::to_string_pretty(notes)?)
+            .with_context(|| format!("Failed to write notes {}", path.display()))
+    }
+
+    /// Remove a video's cached transcript, text copy, chat replay, audio upload record, and
+    /// notes; false when nothing was cached
+    pub fn remove(&self, video_id: &str) -> Result<bool> {
+        let mut removed = false;
+        for path in [
+            self.path_for(video_id),
+            self.dir.join(format!("{}.txt", video_id)),
+            self.chat_path(video_id),
+            self.audio_path(video_id),
+            self.notes_path(video_id),
+            self.context_path(video_id),
+            self.chunks_path(video_id),
+        ] {
+            match fs::remove_file(&path) {
+                Ok(()) => removed = true,
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+                Err(e) => return Err(e).with_context(|| format!("Failed to remove {}", path.display())),
+            }
+        }
+        Ok(removed)
+    }
+
+    /// Every cached transcript, ordered by title
+    pub fn list(&self) -> Result<Vec<Transcript>> {
+        let mut transcripts = Vec::new();
+
+        for entry in fs::read_dir(&self.dir)
+            .with_context(|| format!("Failed to read cache directory {}", self.dir.display()))?
+        {
+            let path = entry?.path();
+            if path.extension().and_then(|ext| ext.to_str()) != Some("json") {
+                continue;
+            }
+
+            match read_transcript(&path) {
+                Ok(transcript) => transcripts.push(transcript),
+                Err(e) => status!("⚠️  Skipping unreadable cache entry {}: {}", path.display(), e),
+            }
+        }
+
+        transcripts.sort_by(|a, b| a.label().cmp(b.label()));
+        Ok(transcripts)
+    }
+}
+
+/// Parse a cached transcript straight from the file, without an intermediate string
+fn read_transcript(path: &std::path::Path) -> Result<Transcript> {
+    let file = File::open(path)
+        .with_context(|| format!("Failed to read cached transcript {}", path.display()))?;
+    serde_json::from_reader(BufReader::new(file))
+        .with_context(|| format!("Failed to parse cached transcript {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    fn transcript(text: &str) -> Transcript {
+        Transcript {
+            video_id: "dQw4w9WgXcQ".to_string(),
+            url: "https://www.youtube.com/watch?v=dQw4w9WgXcQ".to_string(),
+            title: None,
+            channel: None,
+            text: text.into(),
+            segments: Vec::new(),
+            published_at: None,
+            duration_secs: None,
+            fetched_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn text_file_follows_an_edit_of_the_same_length() {
+        let dir = env::temp_dir().join(format!("cvt-cache-text-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let cache = TranscriptCache::open_at(dir).unwrap();
+
+        let path = cache.text_file(&transcript("never gonna give you up")).unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "never gonna give you up");
+        let path = cache.text_file(&transcript("never gonna say goodbye")).unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "never gonna say goodbye");
+    }
+}
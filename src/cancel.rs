@@ -0,0 +1,185 @@
+use anyhow::Result;
+use std::fmt;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc;
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use reqwest::blocking::{RequestBuilder, Response};
+
+/// How often waits wake up to check for cancellation
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Why an operation stopped early
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Cancelled {
+    /// Ctrl-C
+    Interrupted,
+    /// The `--timeout` deadline passed
+    TimedOut(Duration),
+}
+
+impl fmt::Display for Cancelled {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Cancelled::Interrupted => write!(f, "interrupted"),
+            Cancelled::TimedOut(limit) => write!(f, "timed out after {}s (--timeout)", limit.as_secs()),
+        }
+    }
+}
+
+impl std::error::Error for Cancelled {}
+
+/// Whether an error chain ends in a cancellation, and which kind
+pub fn cancelled(error: &anyhow::Error) -> Option<Cancelled> {
+    error.chain().find_map(|cause| cause.downcast_ref::<Cancelled>().copied())
+}
+
+#[derive(Default)]
+struct Inner {
+    cancelled: AtomicBool,
+    deadline: Option<(Instant, Duration)>,
+    /// Cancelling the parent also cancels this token, but not the other way around
+    parent: Option<CancellationToken>,
+}
+
+/// Shared cancellation state for one command: set by Ctrl-C or an overall deadline, and
+/// checked by every network call and wait so they stop as soon as either happens.
+///
+/// What stops: connecting, sending a request, waiting for the response, reading its body, and
+/// waits between retries and status polls, all within `POLL_INTERVAL`. What doesn't: work the
+/// server already accepted (Apify runs are aborted separately, a Gemini upload that completed
+/// stays), local work such as whisper transcription, which only stops at its next check, and
+/// the abandoned request itself, which finishes on its own thread and is torn down at the
+/// deadline when there is one.
+#[derive(Clone, Default)]
+pub struct CancellationToken {
+    inner: Arc<Inner>,
+}
+
+impl CancellationToken {
+    /// A token that also expires `timeout` from now, if given
+    pub fn new(timeout: Option<Duration>) -> Self {
+        Self {
+            inner: Arc::new(Inner {
+                cancelled: AtomicBool::new(false),
+                deadline: timeout.map(|limit| (Instant::now() + limit, limit)),
+                parent: None,
+            }),
+        }
+    }
+
+    /// A token for one operation: cancelled along with this one, and also once its own
+    /// `timeout` passes. Cancelling the child leaves this token running.
+    pub fn child(&self, timeout: Option<Duration>) -> Self {
+        Self {
+            inner: Arc::new(Inner {
+                cancelled: AtomicBool::new(false),
+                deadline: timeout.map(|limit| (Instant::now() + limit, limit)),
+                parent: Some(self.clone()),
+            }),
+        }
+    }
+
+    pub fn cancel(&self) {
+        self.inner.cancelled.store(true, Ordering::SeqCst);
+    }
+
+    /// `Err(Cancelled)` once the token was cancelled or its deadline passed
+    pub fn check(&self) -> Result<(), Cancelled> {
+        if self.inner.cancelled.load(Ordering::SeqCst) {
+            return Err(Cancelled::Interrupted);
+        }
+        match self.inner.deadline {
+            Some((deadline, limit)) if Instant::now() >= deadline => Err(Cancelled::TimedOut(limit)),
+            _ => match &self.inner.parent {
+                Some(parent) => parent.check(),
+                None => Ok(()),
+            },
+        }
+    }
+
+    /// Time left before the nearest deadline of this token or its parents, if there is one
+    pub fn remaining(&self) -> Option<Duration> {
+        let own = self
+            .inner
+            .deadline
+            .map(|(deadline, _)| deadline.saturating_duration_since(Instant::now()));
+        match (own, self.inner.parent.as_ref().and_then(|parent| parent.remaining())) {
+            (Some(own), Some(parent)) => Some(own.min(parent)),
+            (own, parent) => own.or(parent),
+        }
+    }
+
+    /// Sleep for `duration`, returning early with an error if cancelled meanwhile
+    pub fn sleep(&self, duration: Duration) -> Result<()> {
+        let until = Instant::now() + duration;
+        loop {
+            self.check()?;
+            let left = until.saturating_duration_since(Instant::now());
+            if left.is_zero() {
+                return Ok(());
+            }
+            thread::sleep(left.min(POLL_INTERVAL));
+        }
+    }
+
+    /// Send a request and read its response, abandoning both as soon as the token is
+    /// cancelled. The returned response is already read, so reading it again cannot block.
+    ///
+    /// Whenever there is a deadline, the time left before it becomes the request's timeout,
+    /// which reqwest applies to the body as well as the headers, so the connection is torn down
+    /// at the deadline rather than left waiting out the client's full timeout.
+    fn send(&self, request: RequestBuilder) -> Result<Response> {
+        self.check()?;
+        let request = match self.remaining() {
+            Some(left) => request.timeout(left.max(Duration::from_millis(1))),
+            None => request,
+        };
+
+        let (tx, rx) = mpsc::channel();
+        thread::spawn(move || {
+            let _ = tx.send(exchange(request));
+        });
+        loop {
+            match rx.recv_timeout(POLL_INTERVAL) {
+                Ok(response) => {
+                    // A deadline hit inside reqwest reads better as the deadline it was
+                    if let (Err(e), Err(cancelled)) = (&response, self.check()) {
+                        if e.is_timeout() {
+                            return Err(cancelled.into());
+                        }
+                    }
+                    return Ok(response?);
+                }
+                Err(mpsc::RecvTimeoutError::Timeout) => self.check()?,
+                Err(mpsc::RecvTimeoutError::Disconnected) => {
+                    anyhow::bail!("HTTP request thread exited without a response")
+                }
+            }
+        }
+    }
+}
+
+/// Send `request` and read its whole body, returning a response that replays it
+fn exchange(request: RequestBuilder) -> reqwest::Result<Response> {
+    let response = request.send()?;
+    let mut head = http::Response::builder().status(response.status()).version(response.version());
+    if let Some(headers) = head.headers_mut() {
+        *headers = response.headers().clone();
+    }
+    let body = response.bytes()?;
+    Ok(Response::from(head.body(body).expect("status and headers taken from a response")))
+}
+
+/// `send` that honours a cancellation token
+pub trait SendCancellable {
+    fn send_with(self, cancel: &CancellationToken) -> Result<Response>;
+}
+
+impl SendCancellable for RequestBuilder {
+    fn send_with(self, cancel: &CancellationToken) -> Result<Response> {
+        cancel.send(self)
+    }
+}
@@ -0,0 +1,119 @@
+use crate::transcript::{CaptionSource, Segment};
+
+/// How much a creator-uploaded caption is trusted over auto-generated speech recognition
+const CREATOR_WEIGHT: f64 = 1.0;
+const AUTO_WEIGHT: f64 = 0.6;
+/// Creator cues that are only a sound tag (`[Music]`, `♪`) say little about the speech under them
+const SOUND_TAG_WEIGHT: f64 = 0.2;
+
+/// One caption track of a video, as the scraper returned it
+pub struct CaptionTrack {
+    pub source: CaptionSource,
+    pub language: Option<String>,
+    pub segments: Vec<Segment>,
+}
+
+/// `en-US` and `en` are the same track language for merging purposes
+fn base_language(language: &Option<String>) -> Option<String> {
+    language
+        .as_deref()
+        .map(|l| l.split(['-', '_']).next().unwrap_or(l).to_lowercase())
+}
+
+/// Whether a cue is only a bracketed sound description or music notes
+fn is_sound_tag(text: &str) -> bool {
+    let text = text.trim();
+    text.is_empty()
+        || text.chars().all(|c| c == '♪' || c == '♫' || c.is_whitespace())
+        || ((text.starts_with('[') && text.ends_with(']')) || (text.starts_with('(') && text.ends_with(')')))
+            && !text[1..text.len() - 1].contains([']', ')'])
+}
+
+fn weight(segment: &Segment) -> f64 {
+    if is_sound_tag(&segment.text) {
+        SOUND_TAG_WEIGHT
+    } else {
+        CREATOR_WEIGHT
+    }
+}
+
+fn overlap(a: &Segment, b: &Segment) -> f64 {
+    (a.end.min(b.end) - a.start.max(b.start)).max(0.0)
+}
+
+fn covered(track: &[Segment]) -> f64 {
+    track.iter().map(|s| (s.end - s.start).max(0.0)).sum()
+}
+
+fn tagged(mut segments: Vec<Segment>, source: CaptionSource) -> Vec<Segment> {
+    for segment in &mut segments {
+        segment.source = Some(source);
+    }
+    segments
+}
+
+/// Combine a video's caption tracks into one timeline, recording on each segment which track
+/// it came from.
+///
+/// The creator's track is preferred. An auto-generated cue is patched in only where the creator
+/// track is silent or just a sound tag: when the auto cue's weight over the part of it the
+/// creator leaves uncovered beats the creator's weighted coverage of it. Creator sound tags the
+/// patched speech lands on are dropped. Tracks in another language than the spoken (auto) one
+/// are only used when nothing else is available.
+pub fn merge_tracks(tracks: Vec<CaptionTrack>) -> Vec<Segment> {
+    let mut tracks: Vec<CaptionTrack> = tracks.into_iter().filter(|t| !t.segments.is_empty()).collect();
+    let best = |tracks: &mut Vec<CaptionTrack>, pick: &dyn Fn(&CaptionTrack) -> bool| {
+        let index = tracks
+            .iter()
+            .enumerate()
+            .filter(|(_, t)| pick(t))
+            .max_by(|(_, a), (_, b)| covered(&a.segments).total_cmp(&covered(&b.segments)))
+            .map(|(i, _)| i)?;
+        Some(tracks.swap_remove(index))
+    };
+
+    let auto = best(&mut tracks, &|t| t.source == CaptionSource::Auto);
+    let spoken = auto.as_ref().and_then(|t| base_language(&t.language));
+    let creator = best(&mut tracks, &|t| {
+        t.source == CaptionSource::Creator
+            && (spoken.is_none() || base_language(&t.language).is_none() || base_language(&t.language) == spoken)
+    });
+
+    let (creator, auto) = match (creator, auto) {
+        (Some(creator), Some(auto)) => (creator, auto),
+        (Some(only), None) | (None, Some(only)) => return tagged(only.segments, only.source),
+        (None, None) => {
+            return tracks
+                .into_iter()
+                .next()
+                .map(|t| tagged(t.segments, t.source))
+                .unwrap_or_default()
+        }
+    };
+
+    let creator = tagged(creator.segments, CaptionSource::Creator);
+    let mut patches = Vec::new();
+    for segment in tagged(auto.segments, CaptionSource::Auto) {
+        let duration = (segment.end - segment.start).max(f64::EPSILON);
+        let coverage: f64 = creator
+            .iter()
+            .map(|c| overlap(c, &segment) * weight(c))
+            .sum::<f64>()
+            / duration;
+        if AUTO_WEIGHT * (1.0 - coverage.min(1.0)) > coverage && !is_sound_tag(&segment.text) {
+            patches.push(segment);
+        }
+    }
+    if patches.is_empty() {
+        return creator;
+    }
+
+    status!("🔀 Filled {} gap(s) in the creator's captions from the auto-generated track", patches.len());
+    let mut merged: Vec<Segment> = creator
+        .into_iter()
+        .filter(|c| !(is_sound_tag(&c.text) && patches.iter().any(|p| overlap(p, c) > 0.0)))
+        .collect();
+    merged.extend(patches);
+    merged.sort_by(|a, b| a.start.total_cmp(&b.start));
+    merged
+}
@@ -0,0 +1,147 @@
+use anyhow::Result;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use crate::companion::parse_timestamp;
+use crate::guardrail::fence;
+use crate::llm::extract_json;
+use crate::transcript::{format_timestamp, Transcript};
+use crate::VideoTranscriber;
+
+const CHAPTERS_SYSTEM_PROMPT: &str =
+    "You split video transcripts into chapters for a YouTube description. Reply with JSON only.";
+
+/// YouTube ignores chapter lists with fewer chapters than this
+pub const MIN_CHAPTERS: usize = 3;
+/// YouTube's shortest allowed chapter
+const MIN_CHAPTER_SECS: f64 = 10.0;
+/// Longest chapter title kept; longer ones are cut at a word
+const MAX_TITLE_CHARS: usize = 60;
+
+/// One chapter marker
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct Chapter {
+    /// Start in seconds
+    pub start: f64,
+    pub title: String,
+}
+
+#[derive(Deserialize)]
+struct Reply {
+    chapters: Vec<ReplyChapter>,
+}
+
+#[derive(Deserialize)]
+struct ReplyChapter {
+    start: String,
+    title: String,
+}
+
+/// `[MM:SS] Title`-style lines, for replies that ignore the JSON format
+fn parse_lines(reply: &str) -> Vec<(String, String)> {
+    let line = Regex::new(r"(?m)^\s*(?:[-*•]|\d+\.)?\s*\[?(\d{1,2}:\d{2}(?::\d{2})?)\]?\s*[-–—:|]?\s*(.+?)\s*$")
+        .expect("valid regex");
+    line.captures_iter(reply)
+        .map(|caps| (caps[1].to_string(), caps[2].to_string()))
+        .collect()
+}
+
+/// Chapters from a model reply: JSON `{"chapters": [{"start": "MM:SS", "title": ...}]}`, or one
+/// `MM:SS Title` per line. The result follows YouTube's rules for description chapters: it
+/// starts at 0:00, runs in order, ends before `duration`, and has no chapter under 10 seconds.
+pub fn parse_chapters(reply: &str, duration: f64) -> Vec<Chapter> {
+    let raw: Vec<(String, String)> = match serde_json::from_str::<Reply>(extract_json(reply)) {
+        Ok(parsed) => parsed.chapters.into_iter().map(|c| (c.start, c.title)).collect(),
+        Err(_) => parse_lines(reply),
+    };
+
+    let mut candidates: Vec<Chapter> = raw
+        .into_iter()
+        .filter_map(|(start, title)| {
+            let start = parse_timestamp(start.trim().trim_matches(['[', ']'])).ok()?;
+            let title = clean_title(&title);
+            (!title.is_empty() && start < duration).then_some(Chapter { start, title })
+        })
+        .collect();
+    candidates.sort_by(|a, b| a.start.total_cmp(&b.start));
+
+    let mut chapters: Vec<Chapter> = Vec::with_capacity(candidates.len());
+    for mut chapter in candidates {
+        match chapters.last() {
+            None => chapter.start = 0.0,
+            Some(previous) if chapter.start - previous.start < MIN_CHAPTER_SECS => continue,
+            Some(_) => {}
+        }
+        chapters.push(chapter);
+    }
+    // The last chapter must also be long enough
+    while chapters.len() > 1 && duration - chapters[chapters.len() - 1].start < MIN_CHAPTER_SECS {
+        chapters.pop();
+    }
+    chapters
+}
+
+/// A title without quotes, trailing punctuation, or excess length
+fn clean_title(title: &str) -> String {
+    let title = title.trim().trim_matches(['"', '\'', '*']).trim_end_matches(['.', ',', ';']).trim();
+    if title.chars().count() <= MAX_TITLE_CHARS {
+        return title.to_string();
+    }
+    let cut: String = title.chars().take(MAX_TITLE_CHARS).collect();
+    match cut.rfind(' ') {
+        Some(space) => cut[..space].trim_end_matches([',', ':', '-']).to_string(),
+        None => cut,
+    }
+}
+
+/// Chapter lines ready to paste into a YouTube description
+pub fn render_chapters(chapters: &[Chapter]) -> String {
+    chapters
+        .iter()
+        .map(|chapter| format!("{} {}", format_timestamp(chapter.start), chapter.title))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+impl VideoTranscriber {
+    /// Chapter markers for a video, from about `count` topics the model finds in the transcript
+    /// (it picks the number when `None`). Needs a transcript with timed segments.
+    pub fn generate_chapters(&self, transcript: &Transcript, count: Option<usize>) -> Result<Vec<Chapter>> {
+        let Some(duration) = transcript.segments.last().map(|segment| segment.end) else {
+            anyhow::bail!(
+                "\"{}\" has no timed segments, so there is nothing to place chapters at",
+                transcript.label()
+            );
+        };
+
+        status!("📑 Finding the chapters of \"{}\"...", transcript.label());
+        let count = match count {
+            Some(count) => format!("about {} chapters", count),
+            None => format!("one chapter per major topic ({} or more)", MIN_CHAPTERS),
+        };
+        // Long videos get coarser markers so the whole transcript fits in one prompt
+        let every_secs = if duration > 2.0 * 3600.0 { 60.0 } else { 30.0 };
+        let prompt = format!(
+            "Split the video \"{}\" ({} long) into {}. Each chapter starts where a new topic begins; \
+             take its start from the nearest [MM:SS] marker in the transcript. The first chapter starts \
+             at 00:00. Titles are short (2 to 6 words), specific to the content, and in the video's \
+             language. Return JSON: {{\"chapters\": [{{\"start\": \"MM:SS\", \"title\": \"...\"}}]}}\n\n\
+             Transcript:\n{}",
+            transcript.label(),
+            format_timestamp(duration),
+            count,
+            fence(&transcript.timestamped_text(every_secs))
+        );
+        let reply = self.complete(CHAPTERS_SYSTEM_PROMPT, &prompt)?;
+        let chapters = parse_chapters(&reply, duration);
+        if chapters.len() < MIN_CHAPTERS {
+            anyhow::bail!(
+                "The model found only {} usable chapter(s); YouTube needs at least {}. Reply was:\n{}",
+                chapters.len(),
+                MIN_CHAPTERS,
+                reply
+            );
+        }
+        Ok(chapters)
+    }
+}
@@ -0,0 +1,416 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+
+use crate::cancel::SendCancellable;
+use crate::guardrail::fence;
+use crate::retrieval::{bm25_rank, chunk_transcript, Chunk, DEFAULT_CHUNK_CHARS};
+use crate::transcript::{format_timestamp, CaptionSource, Transcript};
+use crate::youtube::embedded_json;
+use crate::VideoTranscriber;
+
+const CHAT_SYSTEM_PROMPT: &str = "You answer questions about a livestream using its transcript and its chat replay. \
+     The chat is what viewers typed, not what the streamer said; keep the two apart and cite times as [MM:SS]. \
+     If the material does not answer the question, say so.";
+
+const REPLAY_URL: &str = "https://www.youtube.com/youtubei/v1/live_chat/get_live_chat_replay";
+/// Width of the bins chat activity is counted in
+const BIN_SECS: f64 = 60.0;
+/// Character budget for chat excerpts in one question
+const CHAT_CONTEXT_CHARS: usize = 40_000;
+
+/// One chat message, `at` seconds into the video
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ChatMessage {
+    pub at: f64,
+    pub author: String,
+    pub text: String,
+}
+
+/// A video's chat replay, stored next to its cached transcript
+#[derive(Serialize, Deserialize)]
+pub struct ChatLog {
+    pub video_id: String,
+    /// Where the messages came from: `youtube`, `yt-dlp`, or `twitch`
+    pub source: String,
+    pub fetched_at: DateTime<Utc>,
+    pub messages: Vec<ChatMessage>,
+}
+
+/// A stretch of unusually busy chat
+pub struct Peak {
+    pub start: f64,
+    pub end: f64,
+    pub messages: usize,
+    /// How many times the usual per-minute rate this stretch ran at
+    pub intensity: f64,
+}
+
+/// Busiest stretches of chat, busiest first: runs of one-minute bins at well over the
+/// typical (median) rate, merged when adjacent
+pub fn peaks(messages: &[ChatMessage], top: usize) -> Vec<Peak> {
+    let Some(last) = messages.iter().map(|m| m.at).reduce(f64::max) else {
+        return Vec::new();
+    };
+    let mut bins = vec![0usize; (last / BIN_SECS) as usize + 1];
+    for message in messages {
+        bins[(message.at.max(0.0) / BIN_SECS) as usize] += 1;
+    }
+    let mut sorted = bins.clone();
+    sorted.sort_unstable();
+    let usual = (sorted[sorted.len() / 2] as f64).max(1.0);
+    let hot = |count: usize| count as f64 >= (usual * 2.0).max(usual + 5.0);
+
+    let mut found = Vec::new();
+    let mut i = 0;
+    while i < bins.len() {
+        if !hot(bins[i]) {
+            i += 1;
+            continue;
+        }
+        let start = i;
+        while i < bins.len() && hot(bins[i]) {
+            i += 1;
+        }
+        let count: usize = bins[start..i].iter().sum();
+        found.push(Peak {
+            start: start as f64 * BIN_SECS,
+            end: i as f64 * BIN_SECS,
+            messages: count,
+            intensity: count as f64 / (i - start) as f64 / usual,
+        });
+    }
+    found.sort_by(|a, b| b.intensity.total_cmp(&a.intensity));
+    found.truncate(top);
+    found
+}
+
+/// Most repeated messages in a time range, as `(text, count)`, which is usually what chat
+/// was spamming when it went wild
+pub fn top_messages(messages: &[ChatMessage], start: f64, end: f64, n: usize) -> Vec<(String, usize)> {
+    let mut counts: HashMap<String, (String, usize)> = HashMap::new();
+    for message in messages.iter().filter(|m| m.at >= start && m.at < end) {
+        let entry = counts
+            .entry(message.text.trim().to_lowercase())
+            .or_insert_with(|| (message.text.trim().to_string(), 0));
+        entry.1 += 1;
+    }
+    let mut ranked: Vec<(String, usize)> = counts.into_values().collect();
+    ranked.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    ranked.truncate(n);
+    ranked
+}
+
+/// What the video was saying during a time range, shortened to about `max_chars`
+fn said_during(transcript: &Transcript, start: f64, end: f64, max_chars: usize) -> String {
+    let text = transcript
+        .segments
+        .iter()
+        .filter(|s| s.end >= start && s.start <= end)
+        .map(|s| s.text.as_str())
+        .collect::<Vec<_>>()
+        .join(" ");
+    if text.chars().count() <= max_chars {
+        return text;
+    }
+    let mut short: String = text.chars().take(max_chars).collect();
+    short.push('…');
+    short
+}
+
+fn runs_text(runs: &Value) -> String {
+    runs.as_array()
+        .map(|runs| {
+            runs.iter()
+                .map(|run| {
+                    run["text"]
+                        .as_str()
+                        .or_else(|| run["emoji"]["shortcuts"][0].as_str())
+                        .unwrap_or_default()
+                })
+                .collect::<String>()
+        })
+        .unwrap_or_default()
+}
+
+/// Messages in one `replayChatItemAction`, the unit of both YouTube's replay API and the
+/// `live_chat.json` files yt-dlp writes
+pub fn parse_replay_action(action: &Value) -> Vec<ChatMessage> {
+    let replay = &action["replayChatItemAction"];
+    let Some(offset) = replay["videoOffsetTimeMsec"]
+        .as_str()
+        .and_then(|ms| ms.parse::<f64>().ok())
+    else {
+        return Vec::new();
+    };
+    let Some(actions) = replay["actions"].as_array() else {
+        return Vec::new();
+    };
+
+    actions
+        .iter()
+        .filter_map(|action| {
+            let item = &action["addChatItemAction"]["item"];
+            let (renderer, prefix) = if item["liveChatTextMessageRenderer"].is_object() {
+                (&item["liveChatTextMessageRenderer"], String::new())
+            } else if item["liveChatPaidMessageRenderer"].is_object() {
+                let renderer = &item["liveChatPaidMessageRenderer"];
+                let amount = renderer["purchaseAmountText"]["simpleText"].as_str().unwrap_or_default();
+                (renderer, format!("[Super Chat {}] ", amount))
+            } else {
+                return None;
+            };
+            let text = runs_text(&renderer["message"]["runs"]);
+            if text.trim().is_empty() && prefix.is_empty() {
+                return None;
+            }
+            Some(ChatMessage {
+                at: offset / 1000.0,
+                author: renderer["authorName"]["simpleText"].as_str().unwrap_or("?").to_string(),
+                text: format!("{}{}", prefix, text.trim()),
+            })
+        })
+        .collect()
+}
+
+/// Parse a saved chat replay: yt-dlp's `live_chat.json` (one action per line) or a Twitch
+/// VOD chat export with a `comments` array. `offset` shifts every message, for chat recorded
+/// against a stream whose upload was trimmed. Returns the source name and the messages.
+pub fn parse_chat_file(contents: &str, offset: f64) -> Result<(&'static str, Vec<ChatMessage>)> {
+    let (source, mut messages) = match serde_json::from_str::<Value>(contents) {
+        Ok(value) if value["comments"].is_array() => {
+            let messages = value["comments"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .filter_map(|comment| {
+                    Some(ChatMessage {
+                        at: comment["content_offset_seconds"].as_f64()?,
+                        author: comment["commenter"]["display_name"].as_str().unwrap_or("?").to_string(),
+                        text: comment["message"]["body"].as_str()?.trim().to_string(),
+                    })
+                })
+                .collect();
+            ("twitch", messages)
+        }
+        _ => {
+            let mut messages = Vec::new();
+            for line in contents.lines().filter(|line| !line.trim().is_empty()) {
+                let action: Value = serde_json::from_str(line).context("Unrecognised chat file format")?;
+                messages.extend(parse_replay_action(&action));
+            }
+            ("yt-dlp", messages)
+        }
+    };
+    if messages.is_empty() {
+        anyhow::bail!("No chat messages found; expected yt-dlp live_chat.json or a Twitch chat export");
+    }
+    for message in &mut messages {
+        message.at = (message.at + offset).max(0.0);
+    }
+    messages.sort_by(|a, b| a.at.total_cmp(&b.at));
+    Ok((source, messages))
+}
+
+/// The busiest stretches of chat, each with what chat repeated and what the video was saying
+pub fn render_peaks(transcript: &Transcript, log: &ChatLog, top: usize) -> String {
+    let mut out = String::new();
+    for (rank, peak) in peaks(&log.messages, top).iter().enumerate() {
+        out.push_str(&format!(
+            "{}. [{}–{}] {} messages, {:.1}× the usual rate\n   {}\n",
+            rank + 1,
+            format_timestamp(peak.start),
+            format_timestamp(peak.end),
+            peak.messages,
+            peak.intensity,
+            transcript.link_at(peak.start)
+        ));
+        for (text, count) in top_messages(&log.messages, peak.start, peak.end, 3) {
+            out.push_str(&format!("   💬 {} ×{}\n", text, count));
+        }
+        let said = said_during(transcript, peak.start, peak.end, 200);
+        if !said.is_empty() {
+            out.push_str(&format!("   🎙️  {}\n", said));
+        }
+    }
+    out
+}
+
+/// The first match of `pattern`'s group in the watch page
+fn page_value(page: &str, pattern: &str) -> Option<String> {
+    Regex::new(pattern).ok()?.captures(page).map(|c| c[1].to_string())
+}
+
+impl VideoTranscriber {
+    /// A video's stored chat replay, or a hint on how to get one
+    pub fn chat_log(&self, video_id: &str) -> Result<ChatLog> {
+        self.cache
+            .get_chat(video_id)?
+            .with_context(|| format!("No chat replay stored for {}; run `chat fetch` or `chat import` first", video_id))
+    }
+
+    /// Download a YouTube livestream's chat replay, page by page, up to `max_messages`
+    pub fn fetch_chat_replay(&self, video_id: &str, max_messages: usize) -> Result<Vec<ChatMessage>> {
+        let page = self.watch_page(video_id)?;
+
+        let api_key = page_value(&page, r#""INNERTUBE_API_KEY":"([^"]+)""#)
+            .context("Could not find YouTube's API key on the video page")?;
+        let client_version = page_value(&page, r#""INNERTUBE_CLIENT_VERSION":"([^"]+)""#)
+            .unwrap_or_else(|| "2.20240101.00.00".to_string());
+        let initial_data = embedded_json(&page, "ytInitialData").context("Could not read the video page's data")?;
+        let chat = initial_data
+            .pointer("/contents/twoColumnWatchNextResults/conversationBar/liveChatRenderer")
+            .context("This video has no chat replay (it was not streamed live, or replay is turned off)")?;
+        // The last view is "Live chat replay" (every message) rather than the filtered "Top chat"
+        let mut continuation = chat
+            .pointer("/header/liveChatHeaderRenderer/viewSelector/sortFilterSubMenuRenderer/subMenuItems")
+            .and_then(Value::as_array)
+            .and_then(|items| items.last())
+            .and_then(|item| item.pointer("/continuation/reloadContinuationData/continuation"))
+            .or_else(|| chat.pointer("/continuations/0/reloadContinuationData/continuation"))
+            .and_then(Value::as_str)
+            .map(str::to_string)
+            .context("Could not find the chat replay on the video page")?;
+
+        let url = format!("{}?key={}&prettyPrint=false", REPLAY_URL, api_key);
+        let mut messages: Vec<ChatMessage> = Vec::new();
+        loop {
+            let body = serde_json::json!({
+                "context": { "client": { "clientName": "WEB", "clientVersion": client_version, "hl": "en" } },
+                "continuation": continuation,
+            });
+            let response = self
+                .client
+                .post(&url)
+                .json(&body)
+                .send_with(&self.cancel)
+                .context("Failed to fetch chat replay")?;
+            if !response.status().is_success() {
+                let status = response.status();
+                let body = response.text().unwrap_or_default();
+                return Err(self.http_failure("YouTube chat replay", "POST", REPLAY_URL, status, &body));
+            }
+            let page: Value = response.json().context("Failed to parse chat replay")?;
+            let replay = &page["continuationContents"]["liveChatContinuation"];
+            let actions = replay["actions"].as_array().map(Vec::as_slice).unwrap_or_default();
+            if actions.is_empty() {
+                break;
+            }
+            messages.extend(actions.iter().flat_map(parse_replay_action));
+            if let Some(last) = messages.last() {
+                status!("💬 {} messages, up to {}", messages.len(), format_timestamp(last.at));
+            }
+            if messages.len() >= max_messages {
+                status!("✂️  Stopping at --max-messages {}", max_messages);
+                messages.truncate(max_messages);
+                break;
+            }
+            match replay
+                .pointer("/continuations/0/liveChatReplayContinuationData/continuation")
+                .and_then(Value::as_str)
+            {
+                Some(next) => continuation = next.to_string(),
+                None => break,
+            }
+        }
+
+        if messages.is_empty() {
+            anyhow::bail!("The chat replay is empty");
+        }
+        messages.sort_by(|a, b| a.at.total_cmp(&b.at));
+        Ok(messages)
+    }
+
+    /// Answer a question from the transcript and the chat replay together
+    pub fn ask_chat(&self, transcript: &Transcript, log: &ChatLog, question: &str) -> Result<String> {
+        let messages = &log.messages;
+        let line = |m: &ChatMessage| format!("[{}] {}: {}", format_timestamp(m.at), m.author, m.text);
+        let mut context = format!(
+            "Chat replay: {} messages from {} viewers.\n\nBusiest moments:\n",
+            messages.len(),
+            messages.iter().map(|m| m.author.as_str()).collect::<std::collections::HashSet<_>>().len()
+        );
+        for peak in peaks(messages, 8) {
+            let repeated: Vec<String> = top_messages(messages, peak.start, peak.end, 5)
+                .into_iter()
+                .map(|(text, count)| format!("\"{}\" ×{}", text, count))
+                .collect();
+            context.push_str(&format!(
+                "- [{}–{}] {} messages ({:.1}× the usual rate). Chat: {}. Video said: \"{}\"\n",
+                format_timestamp(peak.start),
+                format_timestamp(peak.end),
+                peak.messages,
+                peak.intensity,
+                repeated.join(", "),
+                said_during(transcript, peak.start, peak.end, 300)
+            ));
+        }
+
+        // Messages about the question first, then viewer questions, within the budget
+        let chunks: Vec<Chunk> = messages
+            .iter()
+            .map(|m| Chunk {
+                video_id: transcript.video_id.clone(),
+                title: transcript.label().to_string(),
+                url: transcript.url.clone(),
+                start: Some(m.at),
+                text: m.text.clone(),
+                sources: vec![CaptionSource::Chat],
+            })
+            .collect();
+        let mut related: Vec<usize> = bm25_rank(question, &chunks, 150).into_iter().map(|(i, _)| i).collect();
+        related.sort_unstable();
+        let mut budget = CHAT_CONTEXT_CHARS.saturating_sub(context.len());
+        let mut take = |title: &str, lines: Vec<String>, context: &mut String| {
+            context.push_str(&format!("\n{}:\n", title));
+            for line in lines {
+                if line.len() + 1 > budget {
+                    break;
+                }
+                budget -= line.len() + 1;
+                context.push_str(&line);
+                context.push('\n');
+            }
+        };
+        take(
+            "Messages related to the question",
+            related.iter().map(|&i| line(&messages[i])).collect(),
+            &mut context,
+        );
+        take(
+            "Questions viewers asked",
+            messages.iter().filter(|m| m.text.contains('?')).map(line).collect(),
+            &mut context,
+        );
+
+        let excerpts: Vec<String> = {
+            let transcript_chunks = chunk_transcript(transcript, DEFAULT_CHUNK_CHARS, 0);
+            let mut best: Vec<usize> = bm25_rank(question, &transcript_chunks, 6).into_iter().map(|(i, _)| i).collect();
+            best.sort_unstable();
+            best.into_iter()
+                .map(|i| {
+                    let chunk = &transcript_chunks[i];
+                    match chunk.start {
+                        Some(start) => format!("[{}] {}", format_timestamp(start), chunk.text),
+                        None => chunk.text.clone(),
+                    }
+                })
+                .collect()
+        };
+
+        let prompt = self.instructed_prompt(
+            &transcript.video_id,
+            format!(
+                "Livestream: {}\n\nTranscript excerpts:\n{}\n\nChat:\n{}\n\nQuestion: {}",
+                transcript.label(),
+                fence(&excerpts.join("\n")),
+                fence(&context),
+                question
+            ),
+        )?;
+        self.complete(CHAT_SYSTEM_PROMPT, &prompt)
+    }
+}
@@ -0,0 +1,349 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+
+use crate::retrieval::{Chunk, RetrievalParams};
+use crate::transcript::{format_timestamp, CaptionSource, Transcript};
+use crate::VideoTranscriber;
+
+/// Characters of each chunk shown by `chunks`
+const PREVIEW_CHARS: usize = 60;
+
+/// Where one chunk starts and ends in a transcript
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct ChunkBoundary {
+    /// First segment of the chunk, or first word for transcripts without timed segments
+    pub first: usize,
+    /// One past the chunk's last segment or word
+    pub end: usize,
+    /// Start and end in seconds, when the transcript has timed segments
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub start_secs: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub end_secs: Option<f64>,
+}
+
+/// A transcript's chunk boundaries for one chunk size and overlap, stored in the cache so
+/// every question about the video retrieves from the same chunks
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ChunkMap {
+    pub video_id: String,
+    /// Hash of the transcript text the boundaries were computed from
+    pub transcript_sha256: String,
+    pub chunk_chars: usize,
+    pub chunk_overlap: usize,
+    pub boundaries: Vec<ChunkBoundary>,
+}
+
+impl ChunkMap {
+    pub fn build(transcript: &Transcript, size: usize, overlap: usize) -> Self {
+        Self {
+            video_id: transcript.video_id.clone(),
+            transcript_sha256: transcript.text_sha256(),
+            chunk_chars: size,
+            chunk_overlap: overlap,
+            boundaries: chunk_boundaries(transcript, size, overlap),
+        }
+    }
+
+    /// Whether the boundaries still describe `transcript` chunked with `params`
+    fn matches(&self, transcript: &Transcript, params: &RetrievalParams) -> bool {
+        self.chunk_chars == params.chunk_chars
+            && self.chunk_overlap == params.chunk_overlap
+            && self.transcript_sha256 == transcript.text_sha256()
+    }
+
+    /// The chunks' text and citations, read from the transcript
+    pub fn chunks(&self, transcript: &Transcript) -> Vec<Chunk> {
+        chunks_at(transcript, &self.boundaries)
+    }
+}
+
+/// The text and citation of each chunk at `boundaries`
+pub fn chunks_at(transcript: &Transcript, boundaries: &[ChunkBoundary]) -> Vec<Chunk> {
+    let words: Vec<&str> = if transcript.segments.is_empty() {
+        transcript.text.split_whitespace().collect()
+    } else {
+        Vec::new()
+    };
+    boundaries
+        .iter()
+        .map(|boundary| {
+            let (text, sources) = if transcript.segments.is_empty() {
+                (words[boundary.first..boundary.end].join(" "), Vec::new())
+            } else {
+                let segments = &transcript.segments[boundary.first..boundary.end];
+                let text = segments.iter().map(|s| s.text.as_str()).collect::<Vec<_>>().join(" ");
+                let mut sources: Vec<CaptionSource> = Vec::new();
+                for source in segments.iter().filter_map(|s| s.source) {
+                    if !sources.contains(&source) {
+                        sources.push(source);
+                    }
+                }
+                (text, sources)
+            };
+            Chunk {
+                video_id: transcript.video_id.clone(),
+                title: transcript.label().to_string(),
+                url: transcript.url.clone(),
+                start: boundary.start_secs,
+                text,
+                sources,
+            }
+        })
+        .collect()
+}
+
+/// Split a transcript into overlapping chunks of roughly `size` characters, each repeating
+/// about `overlap` characters from the end of the one before.
+///
+/// Timed transcripts are split on segment boundaries so every chunk keeps a start time;
+/// others are split between words.
+pub fn chunk_boundaries(transcript: &Transcript, size: usize, overlap: usize) -> Vec<ChunkBoundary> {
+    if transcript.segments.is_empty() {
+        let lengths: Vec<usize> = transcript.text.split_whitespace().map(str::len).collect();
+        return split(&lengths, size, overlap)
+            .into_iter()
+            .map(|(first, end)| ChunkBoundary {
+                first,
+                end,
+                start_secs: None,
+                end_secs: None,
+            })
+            .collect();
+    }
+
+    let segments = &transcript.segments;
+    let lengths: Vec<usize> = segments.iter().map(|s| s.text.len()).collect();
+    split(&lengths, size, overlap)
+        .into_iter()
+        .map(|(first, end)| ChunkBoundary {
+            first,
+            end,
+            start_secs: Some(segments[first].start),
+            end_secs: Some(segments[end - 1].end),
+        })
+        .collect()
+}
+
+/// Consecutive sections of roughly `size` characters that don't overlap, each with its
+/// boundary and its text, marked with `[MM:SS]` timestamps when the transcript has timed
+/// segments. For working through transcripts too long for one prompt.
+pub fn timestamped_sections(transcript: &Transcript, size: usize) -> Vec<(ChunkBoundary, String)> {
+    let boundaries = chunk_boundaries(transcript, size, 0);
+    let chunks = chunks_at(transcript, &boundaries);
+    boundaries
+        .into_iter()
+        .zip(chunks)
+        .map(|(boundary, chunk)| {
+            if transcript.segments.is_empty() {
+                return (boundary, chunk.text);
+            }
+            let section = Transcript {
+                segments: transcript.segments[boundary.first..boundary.end].to_vec(),
+                ..transcript.clone()
+            };
+            let text = section.timestamped_text(30.0).into_owned();
+            (boundary, text)
+        })
+        .collect()
+}
+
+/// `(first, end)` ranges over units of the given lengths, each joined with one separator.
+/// Every range holds at least one unit, so a `size` of 0 gives one range per unit.
+fn split(lengths: &[usize], size: usize, overlap: usize) -> Vec<(usize, usize)> {
+    let size = size.max(1);
+    let mut ranges = Vec::new();
+    let mut i = 0;
+    while i < lengths.len() {
+        let mut len = 0;
+        let mut j = i;
+        while j < lengths.len() && len < size {
+            len += lengths[j] + 1;
+            j += 1;
+        }
+        ranges.push((i, j));
+        if j >= lengths.len() {
+            break;
+        }
+        // Step back far enough to repeat roughly `overlap` characters
+        let mut back = 0;
+        let mut k = j;
+        while k > i + 1 && back < overlap {
+            k -= 1;
+            back += lengths[k] + 1;
+        }
+        i = k;
+    }
+    ranges
+}
+
+/// One line per chunk at `indexes`: its number, time span, length, and opening words
+pub fn render_chunks(map: &ChunkMap, chunks: &[Chunk], indexes: &[usize]) -> String {
+    let mut out = String::new();
+    for &index in indexes {
+        let boundary = &map.boundaries[index];
+        let chunk = &chunks[index];
+        let span = match (boundary.start_secs, boundary.end_secs) {
+            (Some(start), Some(end)) => format!("{}–{}", format_timestamp(start), format_timestamp(end)),
+            _ => format!("words {}–{}", boundary.first + 1, boundary.end),
+        };
+        let preview: String = chunk.text.chars().take(PREVIEW_CHARS).collect();
+        let ellipsis = if chunk.text.chars().count() > PREVIEW_CHARS { "…" } else { "" };
+        out.push_str(&format!(
+            "#{:<4} {:<15} {:>6} chars  {}{}\n",
+            index + 1,
+            span,
+            chunk.text.len(),
+            preview,
+            ellipsis
+        ));
+    }
+    out
+}
+
+impl VideoTranscriber {
+    /// The transcript's chunks for the configured size and overlap, from the boundaries stored
+    /// for it when they are still current, otherwise computed and stored
+    pub fn transcript_chunks(&self, transcript: &Transcript) -> Result<(ChunkMap, Vec<Chunk>)> {
+        let params = self.retrieval_params();
+        let map = match self.cache.get_chunk_map(&transcript.video_id)? {
+            Some(map) if map.matches(transcript, &params) => map,
+            _ => {
+                let map = ChunkMap::build(transcript, params.chunk_chars, params.chunk_overlap);
+                if let Err(e) = self.cache.put_chunk_map(&map) {
+                    status!("⚠️  Could not store the chunk boundaries: {}", e);
+                }
+                map
+            }
+        };
+        let chunks = map.chunks(transcript);
+        Ok((map, chunks))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transcript::Segment;
+    use chrono::Utc;
+
+    fn transcript(text: &str, segments: Vec<Segment>) -> Transcript {
+        Transcript {
+            video_id: "dQw4w9WgXcQ".to_string(),
+            url: "https://www.youtube.com/watch?v=dQw4w9WgXcQ".to_string(),
+            title: Some("Chunking".to_string()),
+            channel: None,
+            text: text.into(),
+            segments,
+            published_at: None,
+            duration_secs: None,
+            fetched_at: Utc::now(),
+        }
+    }
+
+    /// Ten timed segments of ten seconds, "one" to "ten"
+    fn timed() -> Transcript {
+        let words = ["one", "two", "three", "four", "five", "six", "seven", "eight", "nine", "ten"];
+        let segments = words
+            .iter()
+            .enumerate()
+            .map(|(i, word)| Segment {
+                start: i as f64 * 10.0,
+                end: (i + 1) as f64 * 10.0,
+                text: word.to_string(),
+                source: None,
+            })
+            .collect();
+        transcript(&words.join(" "), segments)
+    }
+
+    #[test]
+    fn split_without_overlap_covers_every_unit_once() {
+        assert_eq!(split(&[4; 6], 10, 0), vec![(0, 2), (2, 4), (4, 6)]);
+    }
+
+    #[test]
+    fn split_repeats_the_overlap() {
+        // Each unit is 5 characters with its separator, so an overlap of 5 repeats one unit
+        assert_eq!(split(&[4; 5], 10, 5), vec![(0, 2), (1, 3), (2, 4), (3, 5)]);
+    }
+
+    #[test]
+    fn split_overlap_never_stalls() {
+        // An overlap as large as a chunk still moves forward by at least one unit
+        assert_eq!(split(&[4; 4], 10, 100), vec![(0, 2), (1, 3), (2, 4)]);
+    }
+
+    #[test]
+    fn split_last_chunk_ends_at_the_last_unit() {
+        let ranges = split(&[3, 8, 2, 9, 1, 7, 4], 12, 3);
+        assert_eq!(ranges.first().map(|range| range.0), Some(0));
+        assert_eq!(ranges.last().map(|range| range.1), Some(7));
+        assert!(ranges.iter().all(|(first, end)| first < end));
+        assert!(ranges.windows(2).all(|pair| pair[0].0 < pair[1].0 && pair[1].0 <= pair[0].1));
+    }
+
+    #[test]
+    fn split_of_size_zero_gives_one_unit_per_range() {
+        assert_eq!(split(&[4; 3], 0, 0), vec![(0, 1), (1, 2), (2, 3)]);
+        assert_eq!(split(&[4; 3], 0, 10), vec![(0, 1), (1, 2), (2, 3)]);
+    }
+
+    #[test]
+    fn split_of_nothing_is_empty() {
+        assert!(split(&[], 10, 2).is_empty());
+    }
+
+    #[test]
+    fn timed_boundaries_carry_segment_times() {
+        let boundaries = chunk_boundaries(&timed(), 12, 0);
+        assert_eq!(boundaries.first().unwrap().start_secs, Some(0.0));
+        assert_eq!(boundaries.last().unwrap().end, 10);
+        assert_eq!(boundaries.last().unwrap().end_secs, Some(100.0));
+        for pair in boundaries.windows(2) {
+            assert_eq!(pair[0].end, pair[1].first);
+            assert_eq!(pair[0].end_secs, pair[1].start_secs);
+        }
+    }
+
+    #[test]
+    fn untimed_boundaries_split_between_words() {
+        let untimed = transcript("alpha beta gamma delta epsilon zeta", Vec::new());
+        let boundaries = chunk_boundaries(&untimed, 11, 0);
+        assert!(boundaries.iter().all(|boundary| boundary.start_secs.is_none() && boundary.end_secs.is_none()));
+        let chunks = chunks_at(&untimed, &boundaries);
+        let rejoined: Vec<&str> = chunks.iter().map(|chunk| chunk.text.as_str()).collect();
+        assert_eq!(rejoined.join(" "), untimed.text.as_ref());
+        assert!(chunks.iter().all(|chunk| chunk.start.is_none()));
+    }
+
+    #[test]
+    fn chunk_map_matches_only_the_same_text_and_settings() {
+        let transcript = timed();
+        let params = RetrievalParams {
+            chunk_chars: 12,
+            chunk_overlap: 4,
+            ..RetrievalParams::default()
+        };
+        let map = ChunkMap::build(&transcript, params.chunk_chars, params.chunk_overlap);
+        assert!(map.matches(&transcript, &params));
+
+        let resized = RetrievalParams { chunk_chars: 20, ..params };
+        assert!(!map.matches(&transcript, &resized));
+        let less_overlap = RetrievalParams { chunk_overlap: 0, ..params };
+        assert!(!map.matches(&transcript, &less_overlap));
+
+        let mut edited = transcript.clone();
+        edited.text = "one two three".into();
+        assert!(!map.matches(&edited, &params));
+    }
+
+    #[test]
+    fn retrieval_settings_that_cannot_chunk_are_rejected() {
+        let zero = RetrievalParams { chunk_chars: 0, ..RetrievalParams::default() };
+        assert!(zero.validate().is_err());
+        let overlap = RetrievalParams { chunk_chars: 200, chunk_overlap: 200, ..RetrievalParams::default() };
+        assert!(overlap.validate().is_err());
+        assert!(RetrievalParams::default().validate().is_ok());
+    }
+}
@@ -0,0 +1,87 @@
+use chrono::{DateTime, Datelike, Utc};
+
+use crate::transcript::{find_mentions, format_timestamp, Transcript};
+
+/// Supported citation styles
+#[derive(Clone, Copy, clap::ValueEnum)]
+pub enum CitationStyle {
+    Bibtex,
+    Apa,
+}
+
+/// Full reference for the video in the given style
+pub fn reference(transcript: &Transcript, style: CitationStyle, accessed: DateTime<Utc>) -> String {
+    let author = transcript.channel.as_deref().unwrap_or("Unknown");
+    let title = transcript.label();
+
+    match style {
+        CitationStyle::Bibtex => {
+            let mut fields = vec![
+                format!("  author = {{{{{}}}}}", author),
+                format!("  title = {{{}}}", title),
+            ];
+            if let Some(date) = transcript.published_at {
+                fields.push(format!("  year = {{{}}}", date.year()));
+                fields.push(format!("  month = {}", date.format("%b").to_string().to_lowercase()));
+            }
+            fields.push("  howpublished = {YouTube}".to_string());
+            fields.push(format!("  url = {{{}}}", transcript.url));
+            fields.push(format!("  note = {{Accessed: {}}}", accessed.format("%Y-%m-%d")));
+            format!("@misc{{{},\n{}\n}}", bibtex_key(transcript), fields.join(",\n"))
+        }
+        CitationStyle::Apa => {
+            let date = transcript
+                .published_at
+                .map(|date| date.format("%Y, %B %-d").to_string())
+                .unwrap_or_else(|| "n.d.".to_string());
+            format!("{}. ({}). *{}* [Video]. YouTube. {}", author, date, title, transcript.url)
+        }
+    }
+}
+
+/// Inline citation pointing at a moment in the video
+pub fn inline_citation(transcript: &Transcript, seconds: f64, style: CitationStyle) -> String {
+    match style {
+        CitationStyle::Bibtex => format!("\\cite[{}]{{{}}}", format_timestamp(seconds), bibtex_key(transcript)),
+        CitationStyle::Apa => format!(
+            "({}, {}, {})",
+            transcript.channel.as_deref().unwrap_or("Unknown"),
+            transcript
+                .published_at
+                .map(|date| date.year().to_string())
+                .unwrap_or_else(|| "n.d.".to_string()),
+            format_timestamp(seconds)
+        ),
+    }
+}
+
+/// Where a quoted passage is spoken, ignoring case and extra whitespace
+pub fn locate_quote(transcript: &Transcript, quote: &str) -> Option<f64> {
+    let normalized = quote.split_whitespace().collect::<Vec<_>>().join(" ");
+    let offset = *find_mentions(&transcript.text, &normalized).first()?;
+    transcript.time_at_offset(offset)
+}
+
+/// Citation key such as `rustychannel2024async`
+fn bibtex_key(transcript: &Transcript) -> String {
+    let word = |text: &str| -> String {
+        text.split_whitespace()
+            .map(|w| w.chars().filter(|c| c.is_ascii_alphanumeric()).collect::<String>())
+            .find(|w| w.len() > 3)
+            .unwrap_or_default()
+            .to_lowercase()
+    };
+    let author: String = transcript
+        .channel
+        .as_deref()
+        .unwrap_or("video")
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .collect::<String>()
+        .to_lowercase();
+    let year = transcript
+        .published_at
+        .map(|date| date.year().to_string())
+        .unwrap_or_default();
+    format!("{}{}{}", author, year, word(transcript.label()))
+}
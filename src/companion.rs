@@ -0,0 +1,107 @@
+use anyhow::{Context, Result};
+
+use crate::guardrail::fence;
+use crate::transcript::{format_timestamp, Transcript};
+use crate::VideoTranscriber;
+
+const COMPANION_SYSTEM_PROMPT: &str = "You are a second-screen companion for someone watching a video. \
+     Answer briefly and conversationally, using only the excerpt of what was just said. \
+     If the excerpt does not cover the question, say so in one sentence.";
+
+/// Seconds of video after the playhead included for context
+const LOOKAHEAD_SECS: f64 = 15.0;
+
+/// Parse a playhead position such as `42:10`, `1:02:03`, or `2530` (seconds)
+pub fn parse_timestamp(value: &str) -> Result<f64> {
+    let mut seconds = 0.0;
+    for part in value.trim().split(':') {
+        let n: f64 = part
+            .parse()
+            .ok()
+            .filter(|n: &f64| n.is_finite() && *n >= 0.0)
+            .with_context(|| format!("Invalid timestamp '{}' (use MM:SS or H:MM:SS)", value))?;
+        seconds = seconds * 60.0 + n;
+    }
+    Ok(seconds)
+}
+
+/// Text spoken from `window` seconds before the playhead to just after it
+fn recent_text(transcript: &Transcript, at: f64, window: f64) -> Option<(f64, String)> {
+    let segments: Vec<&str> = transcript
+        .segments
+        .iter()
+        .filter(|s| s.end >= at - window && s.start <= at + LOOKAHEAD_SECS)
+        .map(|s| s.text.as_str())
+        .collect();
+    if segments.is_empty() {
+        return None;
+    }
+    Some(((at - window).max(0.0), segments.join(" ")))
+}
+
+impl VideoTranscriber {
+    /// Answer a question from what was said in the `window` seconds before the playhead
+    pub fn ask_companion(&self, transcript: &Transcript, at: f64, window: f64, question: &str) -> Result<String> {
+        if transcript.segments.is_empty() {
+            anyhow::bail!("This transcript has no timings, so it cannot be scoped to a playhead position");
+        }
+        let Some((from, excerpt)) = recent_text(transcript, at, window) else {
+            anyhow::bail!(
+                "Nothing is said around {} in this video",
+                format_timestamp(at)
+            );
+        };
+
+        let prompt = self.instructed_prompt(
+            &transcript.video_id,
+            format!(
+                "Video: {}\nThe viewer is at {}. This is what was said from {} until now:\n{}\n\nQuestion: {}",
+                transcript.label(),
+                format_timestamp(at),
+                format_timestamp(from),
+                fence(&excerpt),
+                question
+            ),
+        )?;
+        self.complete(COMPANION_SYSTEM_PROMPT, &prompt)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transcript::format_timestamp;
+    use proptest::prelude::*;
+
+    proptest! {
+        #[test]
+        fn formatted_timestamps_parse_back(seconds in 0.0..400_000.0f64) {
+            let parsed = parse_timestamp(&format_timestamp(seconds)).unwrap();
+            prop_assert_eq!(parsed, seconds.floor());
+        }
+
+        #[test]
+        fn accepts_every_written_form(hours in 0..100u64, minutes in 0..60u64, secs in 0..60u64) {
+            let total = (hours * 3600 + minutes * 60 + secs) as f64;
+            prop_assert_eq!(parse_timestamp(&format!("{}:{:02}:{:02}", hours, minutes, secs)).unwrap(), total);
+            prop_assert_eq!(parse_timestamp(&format!(" {} ", total)).unwrap(), total);
+            if hours == 0 {
+                prop_assert_eq!(parse_timestamp(&format!("{}:{:02}", minutes, secs)).unwrap(), total);
+            }
+        }
+
+        #[test]
+        fn results_are_finite_and_not_negative(value in any::<String>()) {
+            if let Ok(seconds) = parse_timestamp(&value) {
+                prop_assert!(seconds.is_finite() && seconds >= 0.0, "{:?} parsed to {}", value, seconds);
+            }
+        }
+    }
+
+    #[test]
+    fn rejects_non_numbers() {
+        for value in ["", "12:", "ab:cd", "inf", "NaN", "-5", "1:-30"] {
+            assert!(parse_timestamp(value).is_err(), "{:?} should not parse", value);
+        }
+    }
+}
@@ -0,0 +1,224 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::ensemble::EnsembleConfig;
+use crate::http::HttpConfig;
+use crate::moderation::ModerationConfig;
+use crate::rate_limit::FreeTierLimits;
+use crate::retrieval::RetrievalParams;
+use crate::VideoTranscriber;
+
+/// Persistent settings written by `init` and read on every run.
+///
+/// Environment variables (and `.env`) always take precedence over the file, so
+/// existing setups keep working unchanged.
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+pub struct Config {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub apify_api_key: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub gemini_api_key: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub groq_api_key: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub llm_provider: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub groq_model: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub gemini_model: Option<String>,
+    /// Where watch-mode alerts are delivered
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub notify: Option<NotifyConfig>,
+    /// Keyword alert rules evaluated against newly watched videos
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub alerts: Vec<AlertRule>,
+    /// Excerpt retrieval parameters, usually written by `eval tune`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retrieval: Option<RetrievalParams>,
+    /// Content-safety check applied to generated answers; off when absent
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub moderation: Option<ModerationConfig>,
+    /// Opt-in local usage counts; see `telemetry status`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub telemetry: Option<bool>,
+    /// Connection pool, keep-alive, and HTTP/2 settings for API calls
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub http: Option<HttpConfig>,
+    /// Answer `ask` through a background daemon that stays warm between runs
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub daemon: Option<bool>,
+    /// File-name rules giving `ingest-dir` recordings a title, channel, and date
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub ingest_rules: Vec<IngestRule>,
+    /// Transcript backend for single videos: `apify` (default), `ytdlp`, or `youtube`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub transcript_source: Option<String>,
+    /// Seconds to wait for an uploaded Gemini file to finish processing
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub gemini_file_timeout_secs: Option<u64>,
+    /// Seconds a transcript stays in Gemini's context cache between questions; 0 turns it off
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub gemini_cache_ttl_secs: Option<u64>,
+    /// whisper.cpp ggml model used to transcribe videos without captions (`local-whisper` builds)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub whisper_model_path: Option<String>,
+    /// Models that answer together, merged by a judge model
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ensemble: Option<EnsembleConfig>,
+    /// How `ask` answers by default: `auto`, `full`, `retrieval`, `files`, or `cached`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub answer_strategy: Option<String>,
+    /// Longest transcript `ask` sends whole before switching to retrieval (40,000 by default)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub full_transcript_max_chars: Option<usize>,
+    /// Brave Search API key that gives `--agent` a web_search tool
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub web_search_api_key: Option<String>,
+    /// Gemini quota tier: `free` keeps under the free tier's limits, `paid` (default) does not
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub gemini_tier: Option<String>,
+    /// Limits kept to with `gemini_tier = "free"`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub free_tier: Option<FreeTierLimits>,
+}
+
+/// Alert delivery targets; alerts are always printed, these are additional
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+pub struct NotifyConfig {
+    /// URL that receives a JSON POST per alert (Slack/Discord-compatible `text` field)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub webhook_url: Option<String>,
+    /// Shell command run per alert, with details in `CVT_ALERT_*` environment variables
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub command: Option<String>,
+}
+
+/// A named set of keywords that triggers a notification when a new video mentions any of them
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct AlertRule {
+    pub name: String,
+    pub keywords: Vec<String>,
+}
+
+/// Metadata for recordings whose file name (without extension) matches `pattern`, via the
+/// named groups `title`, `channel`, and `date`
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct IngestRule {
+    pub pattern: String,
+    /// Channel for matching recordings when the pattern has no `channel` group
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub channel: Option<String>,
+}
+
+impl Config {
+    /// Location of the config file (`CVT_CONFIG` overrides the platform default)
+    pub fn path() -> Result<PathBuf> {
+        if let Ok(path) = env::var("CVT_CONFIG") {
+            return Ok(PathBuf::from(path));
+        }
+        Self::platform_path()
+    }
+
+    /// The platform's location for the config file, whatever `CVT_CONFIG` says
+    pub fn platform_path() -> Result<PathBuf> {
+        let dir = dirs::config_dir().context("Could not determine the config directory")?;
+        Ok(dir.join("claude-video-transcribe").join("config.toml"))
+    }
+
+    /// Load the config file, returning defaults when it does not exist yet
+    pub fn load() -> Result<Self> {
+        Self::load_from(&Self::path()?)
+    }
+
+    /// Load the config file at `path`, returning defaults when it does not exist
+    pub fn load_from(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let contents = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read config file {}", path.display()))?;
+        let config: Self = toml::from_str(&contents)
+            .with_context(|| format!("Failed to parse config file {}", path.display()))?;
+        if let Some(retrieval) = &config.retrieval {
+            retrieval
+                .validate()
+                .with_context(|| format!("Invalid [retrieval] settings in {}", path.display()))?;
+        }
+        Ok(config)
+    }
+
+    /// Write the config file, readable only by the current user
+    pub fn save(&self) -> Result<PathBuf> {
+        let path = Self::path()?;
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create {}", parent.display()))?;
+        }
+
+        let contents = toml::to_string_pretty(self).context("Failed to serialize config")?;
+        fs::write(&path, contents)
+            .with_context(|| format!("Failed to write config file {}", path.display()))?;
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(&path, fs::Permissions::from_mode(0o600))?;
+        }
+
+        Ok(path)
+    }
+}
+
+/// Directory for persistent user data such as the library layout (`CVT_DATA_DIR` overrides it)
+pub fn data_dir() -> Result<PathBuf> {
+    match env::var("CVT_DATA_DIR") {
+        Ok(dir) => create_data_dir(PathBuf::from(dir)),
+        Err(_) => platform_data_dir(),
+    }
+}
+
+/// The platform's data directory for this tool, whatever `CVT_DATA_DIR` says
+pub fn platform_data_dir() -> Result<PathBuf> {
+    let dir = dirs::data_dir()
+        .context("Could not determine the data directory")?
+        .join("claude-video-transcribe");
+    create_data_dir(dir)
+}
+
+fn create_data_dir(dir: PathBuf) -> Result<PathBuf> {
+    fs::create_dir_all(&dir)
+        .with_context(|| format!("Failed to create data directory {}", dir.display()))?;
+    Ok(dir)
+}
+
+/// Read a setting from the environment first, then from the config file
+pub fn setting(env_name: &str, file_value: &Option<String>) -> Option<String> {
+    env::var(env_name)
+        .ok()
+        .filter(|value| !value.is_empty())
+        .or_else(|| file_value.clone().filter(|value| !value.is_empty()))
+}
+
+impl VideoTranscriber {
+    /// Like `setting`, but only the config file's value when built with `ignore_environment`
+    pub(crate) fn setting(&self, env_name: &str, file_value: &Option<String>) -> Option<String> {
+        if self.ignore_environment {
+            file_value.clone().filter(|value| !value.is_empty())
+        } else {
+            setting(env_name, file_value)
+        }
+    }
+
+    /// An environment variable that tunes the transcriber, unset when built with
+    /// `ignore_environment`
+    pub(crate) fn env_var(&self, name: &str) -> Option<String> {
+        if self.ignore_environment {
+            return None;
+        }
+        env::var(name).ok()
+    }
+}
@@ -0,0 +1,180 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+
+use crate::guardrail::fence;
+use crate::parallel::map_bounded;
+use crate::transcript::Transcript;
+use crate::VideoTranscriber;
+
+/// Reviewers whose score is at least this far from the mean are flagged as dissenting
+const DISSENT_THRESHOLD: f64 = 2.0;
+
+const REVIEW_SYSTEM_PROMPT: &str =
+    "You extract structured product-review data from video transcripts. Reply with JSON only.";
+
+/// What one reviewer thinks of the product
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ReviewerVerdict {
+    pub reviewer: String,
+    #[serde(default)]
+    pub video: String,
+    pub pros: Vec<String>,
+    pub cons: Vec<String>,
+    /// Overall score normalised to 0–10, when the reviewer gives one or it can be inferred
+    pub score: Option<f64>,
+    pub verdict: String,
+}
+
+/// The full consensus analysis across reviewers
+#[derive(Serialize, Debug)]
+pub struct ConsensusReport {
+    pub product: String,
+    pub reviewers: Vec<ReviewerVerdict>,
+    pub mean_score: Option<f64>,
+    pub dissenters: Vec<String>,
+    pub synthesis: String,
+}
+
+impl VideoTranscriber {
+    /// Extract each reviewer's verdict and synthesize a consensus report
+    pub fn consensus(
+        &self,
+        product: &str,
+        urls: &[String],
+        concurrency: usize,
+    ) -> Result<ConsensusReport> {
+        let reviews = self.review_transcripts(product, urls)?;
+        status!("🔍 Extracting verdicts from {} review(s)...", reviews.len());
+
+        let extracted = map_bounded(&reviews, concurrency, |transcript| {
+            self.extract_verdict(product, transcript)
+        });
+
+        let mut reviewers = Vec::new();
+        for (transcript, verdict) in reviews.iter().zip(extracted) {
+            match verdict {
+                Ok(verdict) => reviewers.push(verdict),
+                Err(e) => status!("⚠️  Skipping {}: {}", transcript.label(), e),
+            }
+        }
+        if reviewers.is_empty() {
+            anyhow::bail!("Could not extract a verdict from any of the reviews");
+        }
+
+        let scores: Vec<f64> = reviewers.iter().filter_map(|r| r.score).collect();
+        let mean_score = if scores.is_empty() {
+            None
+        } else {
+            Some(scores.iter().sum::<f64>() / scores.len() as f64)
+        };
+        let dissenters: Vec<String> = reviewers
+            .iter()
+            .filter(|r| match (r.score, mean_score) {
+                (Some(score), Some(mean)) => (score - mean).abs() >= DISSENT_THRESHOLD,
+                _ => false,
+            })
+            .map(|r| r.reviewer.clone())
+            .collect();
+
+        status!("🧮 Synthesizing consensus...");
+        let prompt = format!(
+            "Here are structured verdicts from several reviewers of {}:\n{}\n\n\
+             Reviewers flagged as dissenting by score: {}\n\n\
+             Write a Markdown consensus report with these sections: Overall verdict, \
+             Consensus pros, Consensus cons, Points of disagreement (name the dissenting reviewers \
+             and what they disagree about), Bottom line. Only use information from the verdicts.",
+            product,
+            serde_json::to_string_pretty(&reviewers)?,
+            if dissenters.is_empty() {
+                "none".to_string()
+            } else {
+                dissenters.join(", ")
+            }
+        );
+        let synthesis = self.complete(crate::ANSWER_SYSTEM_PROMPT, &prompt)?;
+
+        Ok(ConsensusReport {
+            product: product.to_string(),
+            reviewers,
+            mean_score,
+            dissenters,
+            synthesis,
+        })
+    }
+
+    fn extract_verdict(&self, product: &str, transcript: &Transcript) -> Result<ReviewerVerdict> {
+        let reviewer = transcript.channel.as_deref().unwrap_or("Unknown reviewer");
+        let prompt = format!(
+            "This is a review of {} by {}. Extract the reviewer's opinion as JSON with exactly these keys:\n\
+             {{\"reviewer\": string, \"pros\": [string], \"cons\": [string], \
+             \"score\": number from 0 to 10 or null, \"verdict\": one-sentence string}}\n\
+             Normalise any score the reviewer gives to a 0-10 scale; use null if they give none.\n\n\
+             Transcript:\n{}",
+            product,
+            reviewer,
+            fence(&transcript.text)
+        );
+
+        let mut verdict: ReviewerVerdict = self.complete_json(REVIEW_SYSTEM_PROMPT, &prompt)?;
+        verdict.video = transcript.label().to_string();
+        Ok(verdict)
+    }
+
+    /// The requested review videos, or every library video whose title mentions the product
+    fn review_transcripts(&self, product: &str, urls: &[String]) -> Result<Vec<Transcript>> {
+        let reviews = if urls.is_empty() {
+            let needle = product.to_lowercase();
+            self.cache
+                .list()?
+                .into_iter()
+                .filter(|t| t.label().to_lowercase().contains(&needle))
+                .collect()
+        } else {
+            urls.iter()
+                .map(|url| self.load_transcript(url, false))
+                .collect::<Result<Vec<_>>>()?
+        };
+
+        if reviews.len() < 2 {
+            anyhow::bail!(
+                "Need at least two reviews of \"{}\" (found {}). Pass them with --url or index more reviews.",
+                product,
+                reviews.len()
+            );
+        }
+        Ok(reviews)
+    }
+}
+
+/// Render the per-reviewer breakdown followed by the synthesized report
+pub fn render_report(report: &ConsensusReport) -> String {
+    let mut out = format!("# Review consensus: {}\n\n", report.product);
+
+    if let Some(mean) = report.mean_score {
+        out.push_str(&format!("**Average score:** {:.1}/10\n\n", mean));
+    }
+
+    out.push_str("| Reviewer | Score | Verdict |\n|---|---|---|\n");
+    for reviewer in &report.reviewers {
+        let flag = if report.dissenters.contains(&reviewer.reviewer) {
+            " ⚠️ dissent"
+        } else {
+            ""
+        };
+        out.push_str(&format!(
+            "| {}{} | {} | {} |\n",
+            reviewer.reviewer,
+            flag,
+            reviewer
+                .score
+                .map(|score| format!("{:.1}", score))
+                .unwrap_or_else(|| "-".to_string()),
+            reviewer.verdict.replace('|', "\\|")
+        ));
+    }
+
+    out.push('\n');
+    out.push_str(&report.synthesis);
+    out.push('\n');
+    out
+}
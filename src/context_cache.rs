@@ -0,0 +1,216 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use reqwest::StatusCode;
+use serde::{Deserialize, Serialize};
+
+use crate::cancel::{self, SendCancellable};
+use crate::prompts;
+use crate::rate_limit::{estimate_tokens, GeminiCall};
+use crate::timestamps::with_timestamp_links;
+use crate::transcript::Transcript;
+use crate::{guardrail, Error, VideoTranscriber, ANSWER_SYSTEM_PROMPT};
+
+/// How long a cached context lives unless `GEMINI_CACHE_TTL` says otherwise
+const DEFAULT_CACHE_TTL_SECS: u64 = 3600;
+/// Gemini refuses to cache less than this many tokens; shorter transcripts are sent inline
+const MIN_CACHED_TOKENS: usize = 1024;
+/// Contexts this close to expiring are replaced rather than reused
+const EXPIRY_MARGIN_SECS: i64 = 60;
+
+/// A transcript held in Gemini's context cache, so follow-up questions send only the question
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CachedContext {
+    pub video_id: String,
+    /// The `cachedContents/...` resource name
+    pub name: String,
+    /// Cached contexts only work with the model they were created for
+    pub model: String,
+    /// Hash of the transcript text that was cached
+    pub transcript_sha256: String,
+    pub expires_at: DateTime<Utc>,
+}
+
+impl CachedContext {
+    fn usable(&self, model: &str, hash: &str) -> bool {
+        self.model == model
+            && self.transcript_sha256 == hash
+            && self.expires_at > Utc::now() + chrono::Duration::seconds(EXPIRY_MARGIN_SECS)
+    }
+}
+
+#[derive(Serialize)]
+struct CreateCachedContent {
+    model: String,
+    system_instruction: CachedContent,
+    contents: Vec<CachedContent>,
+    ttl: String,
+}
+
+#[derive(Serialize)]
+struct CachedContent {
+    role: String,
+    parts: Vec<CachedPart>,
+}
+
+#[derive(Serialize)]
+struct CachedPart {
+    text: String,
+}
+
+#[derive(Deserialize)]
+struct CachedContentResponse {
+    name: String,
+    #[serde(rename = "expireTime")]
+    expire_time: DateTime<Utc>,
+}
+
+/// Whether Gemini no longer has the cached context a request named
+fn context_gone(err: &anyhow::Error) -> bool {
+    matches!(
+        err.downcast_ref::<Error>(),
+        Some(Error::Http { status, .. }) if *status == StatusCode::NOT_FOUND || *status == StatusCode::FORBIDDEN
+    )
+}
+
+impl VideoTranscriber {
+    /// Seconds a new cached context lives; 0 turns context caching off
+    pub fn cache_ttl_secs(&self) -> u64 {
+        self.setting("GEMINI_CACHE_TTL", &self.config.gemini_cache_ttl_secs.map(|s| s.to_string()))
+            .and_then(|secs| secs.parse().ok())
+            .unwrap_or(DEFAULT_CACHE_TTL_SECS)
+    }
+
+    /// Whether caching is on and the transcript is long enough for Gemini to cache
+    pub fn cacheable(&self, transcript: &Transcript) -> bool {
+        self.cache_ttl_secs() > 0 && transcript.text.len() / 4 >= MIN_CACHED_TOKENS
+    }
+
+    /// The transcript's cached context, while it is live and matches the transcript and model
+    pub fn live_context(&self, transcript: &Transcript) -> Result<Option<CachedContext>> {
+        Ok(self
+            .cache
+            .get_context(&transcript.video_id)?
+            .filter(|context| context.usable(&self.gemini_model, &transcript.text_sha256())))
+    }
+
+    /// Answer from a whole transcript held in Gemini's context cache. The first question
+    /// caches the transcript; later ones within the cache's TTL reuse it and are billed the
+    /// cached-token rate for it. Returns `None` when the transcript is too short to cache,
+    /// caching is turned off, or Gemini would not cache it, so the caller sends it inline.
+    pub fn ask_cached_context(&self, transcript: &Transcript, question: &str) -> Result<Option<String>> {
+        if !self.cacheable(transcript) {
+            return Ok(None);
+        }
+        let Some(context) = self.cached_context(transcript, self.cache_ttl_secs())? else {
+            return Ok(None);
+        };
+
+        status!("🤔 Asking question with Gemini: \"{}\"", question);
+        let prompt = prompts::cached(transcript, question, &self.video_prompt_options(&transcript.video_id)?);
+        match self.generate_gemini_cached(&context, &prompt) {
+            Err(e) if context_gone(&e) => {
+                status!("♻️  Gemini no longer has the cached transcript; sending it inline");
+                self.cache.remove_context(&transcript.video_id)?;
+                Ok(None)
+            }
+            answer => answer.map(|answer| Some(with_timestamp_links(transcript, answer))),
+        }
+    }
+
+    /// The transcript's live cached context, otherwise a new one
+    fn cached_context(&self, transcript: &Transcript, ttl: u64) -> Result<Option<CachedContext>> {
+        if let Some(context) = self.live_context(transcript)? {
+            status!("📦 Reusing the transcript cached in Gemini until {}", context.expires_at.format("%Y-%m-%d %H:%M UTC"));
+            return Ok(Some(context));
+        }
+
+        status!("🗄️  Caching the transcript in Gemini for {} minute(s)...", ttl.div_ceil(60));
+        match self.create_cached_context(transcript, transcript.text_sha256(), ttl) {
+            Ok(context) => {
+                if let Err(e) = self.cache.put_context(&context) {
+                    status!("⚠️  Could not remember the cached context: {}", e);
+                }
+                Ok(Some(context))
+            }
+            Err(e) if cancel::cancelled(&e).is_some() => Err(e),
+            Err(e) => {
+                status!("⚠️  Gemini would not cache the transcript ({}); sending it inline", e);
+                Ok(None)
+            }
+        }
+    }
+
+    fn create_cached_context(&self, transcript: &Transcript, hash: String, ttl: u64) -> Result<CachedContext> {
+        let url = format!(
+            "{}/v1beta/cachedContents?key={}",
+            self.endpoints.gemini, self.gemini_api_key
+        );
+        let request = CreateCachedContent {
+            model: format!("models/{}", self.gemini_model),
+            system_instruction: CachedContent {
+                role: "system".to_string(),
+                parts: vec![CachedPart {
+                    text: guardrail::harden_system_prompt(ANSWER_SYSTEM_PROMPT),
+                }],
+            },
+            contents: vec![CachedContent {
+                role: "user".to_string(),
+                parts: vec![CachedPart {
+                    text: format!(
+                        "Transcript of the YouTube video \"{}\":\n{}",
+                        transcript.label(),
+                        guardrail::fence(&transcript.timestamped_text(30.0))
+                    ),
+                }],
+            }],
+            ttl: format!("{}s", ttl),
+        };
+        self.throttle_gemini(GeminiCall::Generate, estimate_tokens(serde_json::to_string(&request)?.len()))?;
+
+        let response = self
+            .client
+            .post(&url)
+            .json(&request)
+            .send_with(&self.cancel)
+            .context("Failed to create Gemini cached context")?;
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().unwrap_or_default();
+            return Err(self.http_failure("Gemini cache", "POST", &url, status, &body));
+        }
+        let created: CachedContentResponse = response
+            .json()
+            .context("Failed to parse Gemini cached context response")?;
+
+        Ok(CachedContext {
+            video_id: transcript.video_id.clone(),
+            name: created.name,
+            model: self.gemini_model.clone(),
+            transcript_sha256: hash,
+            expires_at: created.expire_time,
+        })
+    }
+
+    /// Delete a cached context before its TTL runs out; false when Gemini had already dropped it
+    pub fn delete_cached_context(&self, context: &CachedContext) -> Result<bool> {
+        let url = format!(
+            "{}/v1beta/{}?key={}",
+            self.endpoints.gemini, context.name, self.gemini_api_key
+        );
+        let response = self
+            .client
+            .delete(&url)
+            .send_with(&self.cancel)
+            .context("Failed to delete Gemini cached context")?;
+
+        let status = response.status();
+        if status == StatusCode::NOT_FOUND || status == StatusCode::FORBIDDEN {
+            return Ok(false);
+        }
+        if !status.is_success() {
+            let body = response.text().unwrap_or_default();
+            return Err(self.http_failure("Gemini cache delete", "DELETE", &url, status, &body));
+        }
+        Ok(true)
+    }
+}
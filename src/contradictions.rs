@@ -0,0 +1,151 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+
+use crate::guardrail::fence;
+use crate::parallel::map_bounded;
+use crate::transcript::{format_timestamp, Transcript};
+use crate::VideoTranscriber;
+
+/// Stance claims extracted per video before cross-checking
+const CLAIMS_PER_VIDEO: usize = 15;
+
+const CLAIMS_SYSTEM_PROMPT: &str =
+    "You extract the opinions and factual stances a creator commits to in a video. Reply with JSON only.";
+
+const CONTRADICTIONS_SYSTEM_PROMPT: &str =
+    "You compare statements a creator made in different videos and identify genuine contradictions, \
+     not mere changes of emphasis or topic. Reply with JSON only.";
+
+/// A stance the creator takes in one video
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct StanceClaim {
+    pub topic: String,
+    pub claim: String,
+    pub quote: String,
+    pub seconds: Option<f64>,
+    #[serde(skip_deserializing)]
+    pub video_index: usize,
+}
+
+/// Two claims from different videos that conflict
+pub struct Contradiction {
+    pub topic: String,
+    pub explanation: String,
+    pub first: StanceClaim,
+    pub second: StanceClaim,
+}
+
+#[derive(Deserialize)]
+struct ConflictReply {
+    first: usize,
+    second: usize,
+    topic: String,
+    explanation: String,
+}
+
+impl VideoTranscriber {
+    /// Find statements in a channel's videos that contradict each other
+    pub fn find_contradictions(
+        &self,
+        channel: &str,
+        concurrency: usize,
+    ) -> Result<(Vec<Transcript>, Vec<Contradiction>)> {
+        let videos = self.channel_transcripts(channel)?;
+        if videos.len() < 2 {
+            anyhow::bail!("Need at least two indexed videos from {} to compare", channel);
+        }
+        status!("🔎 Extracting stances from {} video(s)...", videos.len());
+
+        let extracted = map_bounded(&videos, concurrency, |transcript| self.extract_stances(transcript));
+        let mut claims = Vec::new();
+        for (index, result) in extracted.into_iter().enumerate() {
+            match result {
+                Ok(video_claims) => claims.extend(video_claims.into_iter().map(|mut claim| {
+                    claim.video_index = index;
+                    claim
+                })),
+                Err(e) => status!("⚠️  Skipping {}: {}", videos[index].label(), e),
+            }
+        }
+
+        status!("⚖️  Cross-checking {} claims...", claims.len());
+        let listing: String = claims
+            .iter()
+            .enumerate()
+            .map(|(i, claim)| {
+                format!(
+                    "{}. [video {}] ({}) {}\n",
+                    i,
+                    claim.video_index,
+                    claim.topic,
+                    claim.claim
+                )
+            })
+            .collect();
+        let prompt = format!(
+            "Below are numbered claims a creator made, tagged with the video they come from. \
+             Find pairs from DIFFERENT videos that directly contradict each other.\n\
+             Return a JSON array: [{{\"first\": claim number, \"second\": claim number, \
+             \"topic\": string, \"explanation\": string}}]. Return [] if there are none.\n\n{}",
+            listing
+        );
+        let conflicts: Vec<ConflictReply> = self.complete_json(CONTRADICTIONS_SYSTEM_PROMPT, &prompt)?;
+
+        let contradictions = conflicts
+            .into_iter()
+            .filter_map(|conflict| {
+                let first = claims.get(conflict.first)?.clone();
+                let second = claims.get(conflict.second)?.clone();
+                (first.video_index != second.video_index).then_some(Contradiction {
+                    topic: conflict.topic,
+                    explanation: conflict.explanation,
+                    first,
+                    second,
+                })
+            })
+            .collect();
+
+        Ok((videos, contradictions))
+    }
+
+    fn extract_stances(&self, transcript: &Transcript) -> Result<Vec<StanceClaim>> {
+        let prompt = format!(
+            "List up to {} clear opinions or factual stances the speaker commits to in this video. \
+             Return a JSON array: [{{\"topic\": short string, \"claim\": one-sentence paraphrase, \
+             \"quote\": verbatim words from the transcript, \"seconds\": number or null}}]. \
+             The transcript has [MM:SS] markers; use them for \"seconds\".\n\nTranscript:\n{}",
+            CLAIMS_PER_VIDEO,
+            fence(&transcript.timestamped_text(30.0))
+        );
+        self.complete_json(CLAIMS_SYSTEM_PROMPT, &prompt)
+    }
+}
+
+/// Render contradictions as Markdown with paired quotes and timestamp links
+pub fn render_contradictions(videos: &[Transcript], contradictions: &[Contradiction]) -> String {
+    if contradictions.is_empty() {
+        return "No contradictions found.".to_string();
+    }
+
+    let quote = |claim: &StanceClaim| {
+        let video = &videos[claim.video_index];
+        let (time, link) = match claim.seconds {
+            Some(seconds) => (format_timestamp(seconds), video.link_at(seconds)),
+            None => ("?".to_string(), video.url.clone()),
+        };
+        format!("> \"{}\"\n> — *{}* @ [{}]({})\n", claim.quote, video.label(), time, link)
+    };
+
+    let mut out = String::from("# Contradictions\n");
+    for (i, contradiction) in contradictions.iter().enumerate() {
+        out.push_str(&format!(
+            "\n## {}. {}\n\n{}\n{}\n{}\n",
+            i + 1,
+            contradiction.topic,
+            quote(&contradiction.first),
+            quote(&contradiction.second),
+            contradiction.explanation
+        ));
+    }
+    out
+}
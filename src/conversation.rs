@@ -0,0 +1,96 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+
+use crate::timestamps::with_timestamp_links;
+use crate::transcript::Transcript;
+use crate::VideoTranscriber;
+
+/// Previous exchanges sent as chat history with each question
+const HISTORY_TURNS: usize = 10;
+
+/// One question and the answer it got
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Turn {
+    pub question: String,
+    pub answer: String,
+}
+
+/// A conversation about one video, where each question is sent with the earlier turns as the
+/// model's chat history so follow-ups like "can you elaborate on that?" work
+pub struct VideoChat<'a> {
+    transcriber: &'a VideoTranscriber,
+    transcript: Transcript,
+    pub history: Vec<Turn>,
+    /// Saved session each turn is appended to, so the conversation can go on in a later run
+    session: Option<String>,
+}
+
+impl<'a> VideoChat<'a> {
+    pub fn new(transcriber: &'a VideoTranscriber, transcript: Transcript) -> Self {
+        Self {
+            transcriber,
+            transcript,
+            history: Vec::new(),
+            session: None,
+        }
+    }
+
+    /// Continue the saved session `session_id`, or start it when it does not exist yet
+    pub fn resume(transcriber: &'a VideoTranscriber, transcript: Transcript, session_id: &str) -> Result<Self> {
+        if let Some(session) = transcriber.registry.session(session_id)? {
+            if session.video_id != transcript.video_id {
+                anyhow::bail!(
+                    "Session '{}' is about {}, not {}; pick another session name for this video",
+                    session_id,
+                    session.url,
+                    transcript.url
+                );
+            }
+            status!("🧵 Continuing session '{}' ({} earlier question(s))", session_id, session.turns);
+        }
+        Ok(Self {
+            history: transcriber.registry.session_turns(session_id)?,
+            session: Some(session_id.to_string()),
+            ..Self::new(transcriber, transcript)
+        })
+    }
+
+    /// Answer one question in the context of the conversation so far, and remember it
+    pub fn ask(&mut self, question: &str) -> Result<String> {
+        let history = &self.history[self.history.len().saturating_sub(HISTORY_TURNS)..];
+        // Fold the previous question in so follow-ups still retrieve the right excerpts
+        let query = match history.last() {
+            Some(previous) => format!("{} {}", previous.question, question),
+            None => question.to_string(),
+        };
+        let context = self.transcriber.answer_context_for(&self.transcript, question, &query, None)?;
+        let answer = self.transcriber.complete_conversation(context.system, history, &context.prompt)?;
+        let answer = if context.excerpts.is_empty() {
+            with_timestamp_links(&self.transcript, answer)
+        } else {
+            context.with_sources(answer)
+        };
+
+        let turn = Turn {
+            question: question.to_string(),
+            answer: answer.clone(),
+        };
+        if let Some(session) = &self.session {
+            self.transcriber
+                .registry
+                .add_session_turn(session, &self.transcript.video_id, &self.transcript.url, &turn)?;
+        }
+        self.history.push(turn);
+        Ok(answer)
+    }
+}
+
+impl VideoTranscriber {
+    /// The URL a saved session started with, for questions that continue it without one
+    pub fn session_url(&self, session_id: &str) -> Result<String> {
+        match self.registry.session(session_id)? {
+            Some(session) => Ok(session.url),
+            None => anyhow::bail!("There is no session '{}' yet; start it by passing the video URL", session_id),
+        }
+    }
+}
@@ -0,0 +1,331 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::env;
+use std::fs;
+use std::io::{BufRead, BufReader, Write};
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use crate::cache::TranscriptCache;
+use crate::config::{data_dir, setting, Config};
+use crate::rate_limit::Tier;
+use crate::sources::SourceKind;
+use crate::{youtube, VideoTranscriber};
+
+/// The daemon exits after this long without a request
+const IDLE_TIMEOUT: Duration = Duration::from_secs(30 * 60);
+/// How long a client waits for a freshly spawned daemon to start listening
+const SPAWN_WAIT: Duration = Duration::from_secs(5);
+/// How long a client waits for an answer before giving up on the daemon and answering itself
+const ANSWER_WAIT: Duration = Duration::from_secs(120);
+/// How long a client waits for the reply to `status` or `stop`
+const CONTROL_WAIT: Duration = Duration::from_secs(5);
+
+/// One line of JSON sent by the CLI
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+enum Request {
+    Ask {
+        fingerprint: String,
+        url: String,
+        question: String,
+    },
+    Status,
+    Stop,
+}
+
+/// One line of JSON sent back by the daemon
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+enum Response {
+    Answer { answer: String, answer_id: String },
+    Error { message: String },
+    /// The daemon was started with different settings or a different build and is exiting
+    Stale,
+    Running { pid: u32, uptime_secs: u64, answered: u64 },
+    Stopping,
+}
+
+/// Where the daemon listens
+pub fn socket_path() -> Result<PathBuf> {
+    Ok(data_dir()?.join("daemon.sock"))
+}
+
+/// Whether `ask` should go through the daemon (`CVT_DAEMON=1`/`0` overrides the config)
+pub fn enabled() -> bool {
+    match env::var("CVT_DAEMON") {
+        Ok(value) => value == "1" || value.eq_ignore_ascii_case("true"),
+        Err(_) => Config::load().ok().and_then(|c| c.daemon).unwrap_or(false),
+    }
+}
+
+/// Hash of the build, the config file, the keys and models, and the settings an answer depends
+/// on as `VideoTranscriber::new` resolves them (retrieval parameters, transcript source, and
+/// tier), so a daemon started under other settings or by another build is replaced instead of
+/// answering with them
+fn fingerprint() -> String {
+    dotenv::dotenv().ok();
+    let mut hasher = Sha256::new();
+    hasher.update(env!("CARGO_PKG_VERSION"));
+    if let Ok(exe) = env::current_exe().and_then(fs::metadata) {
+        if let Ok(modified) = exe.modified() {
+            hasher.update(format!("{:?}", modified));
+        }
+    }
+    if let Ok(contents) = Config::path().and_then(|p| Ok(fs::read(p)?)) {
+        hasher.update(contents);
+    }
+    let config = Config::load().unwrap_or_default();
+    let retrieval = config
+        .retrieval
+        .unwrap_or_default()
+        .with_chunk_overrides(env::var("CHUNK_CHARS").ok(), env::var("CHUNK_OVERLAP").ok());
+    let source = SourceKind::from_setting(setting("TRANSCRIPT_SOURCE", &config.transcript_source)).ok();
+    let tier = Tier::from_setting(setting("GEMINI_TIER", &config.gemini_tier)).ok();
+    hasher.update(format!("{:?}\n{:?}\n{:?}\n", retrieval, source, tier));
+    let mut vars: Vec<(String, String)> = env::vars()
+        .filter(|(name, _)| {
+            name.ends_with("_API_KEY")
+                || name.ends_with("_MODEL")
+                || name.starts_with("CVT_")
+                || name == "LLM_PROVIDER"
+        })
+        .collect();
+    vars.sort();
+    for (name, value) in vars {
+        hasher.update(format!("{}={}\n", name, value));
+    }
+    format!("{:x}", hasher.finalize())
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+/// Serve requests until stopped or idle; holds one transcriber for every request
+pub fn serve(transcriber: VideoTranscriber) -> Result<()> {
+    let path = socket_path()?;
+    if UnixStream::connect(&path).is_ok() {
+        anyhow::bail!("A daemon is already listening on {}", path.display());
+    }
+    let _ = fs::remove_file(&path);
+    let listener =
+        UnixListener::bind(&path).with_context(|| format!("Failed to listen on {}", path.display()))?;
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o600))?;
+    }
+    status!("🛰️  Daemon listening on {}", path.display());
+
+    let fingerprint = fingerprint();
+    let started = Instant::now();
+    let last_request = AtomicU64::new(now_secs());
+    let answered = AtomicU64::new(0);
+    let path = &path;
+    let shutdown = || {
+        let _ = fs::remove_file(path);
+        std::process::exit(0);
+    };
+
+    thread::scope(|scope| {
+        scope.spawn(|| loop {
+            thread::sleep(Duration::from_secs(30));
+            if now_secs().saturating_sub(last_request.load(Ordering::Relaxed)) >= IDLE_TIMEOUT.as_secs() {
+                shutdown();
+            }
+        });
+
+        for stream in listener.incoming() {
+            let Ok(stream) = stream else { continue };
+            last_request.store(now_secs(), Ordering::Relaxed);
+            let (transcriber, fingerprint, answered) = (&transcriber, &fingerprint, &answered);
+            scope.spawn(move || {
+                let mut reader = BufReader::new(&stream);
+                let mut line = String::new();
+                if reader.read_line(&mut line).is_err() {
+                    return;
+                }
+                let response = match serde_json::from_str(&line) {
+                    Ok(Request::Ask {
+                        fingerprint: theirs,
+                        url,
+                        question,
+                    }) => {
+                        if &theirs != fingerprint {
+                            // Free the socket first so the replacement can bind it right away
+                            let _ = fs::remove_file(path);
+                            reply(&stream, &Response::Stale);
+                            std::process::exit(0);
+                        }
+                        let answer = transcriber.load_transcript(&url, false).and_then(|transcript| {
+                            let logged = transcriber.answer_with_feedback(&transcript, &question)?;
+                            Ok((transcriber.moderate(&logged.answer)?, logged.id))
+                        });
+                        answered.fetch_add(1, Ordering::Relaxed);
+                        match answer {
+                            Ok((answer, answer_id)) => Response::Answer { answer, answer_id },
+                            Err(e) => Response::Error {
+                                message: format!("{:#}", e),
+                            },
+                        }
+                    }
+                    Ok(Request::Status) => Response::Running {
+                        pid: std::process::id(),
+                        uptime_secs: started.elapsed().as_secs(),
+                        answered: answered.load(Ordering::Relaxed),
+                    },
+                    Ok(Request::Stop) => {
+                        reply(&stream, &Response::Stopping);
+                        shutdown();
+                        unreachable!()
+                    }
+                    Err(e) => Response::Error {
+                        message: format!("Bad request: {}", e),
+                    },
+                };
+                reply(&stream, &response);
+            });
+        }
+    });
+    Ok(())
+}
+
+fn reply(mut stream: &UnixStream, response: &Response) {
+    if let Ok(mut line) = serde_json::to_string(response) {
+        line.push('\n');
+        let _ = stream.write_all(line.as_bytes());
+    }
+}
+
+/// Send one request and read the reply, waiting at most `wait` for it; `None` when no daemon
+/// is listening
+fn send(request: &Request, wait: Duration) -> Result<Option<Response>> {
+    let Ok(mut stream) = UnixStream::connect(socket_path()?) else {
+        return Ok(None);
+    };
+    stream.set_read_timeout(Some(wait))?;
+    let mut line = serde_json::to_string(request)?;
+    line.push('\n');
+    stream.write_all(line.as_bytes()).context("Failed to write to the daemon")?;
+
+    let mut reply = String::new();
+    BufReader::new(&stream).read_line(&mut reply).map_err(|e| match e.kind() {
+        std::io::ErrorKind::WouldBlock | std::io::ErrorKind::TimedOut => {
+            anyhow::anyhow!("The daemon did not reply within {}s", wait.as_secs())
+        }
+        _ => anyhow::Error::new(e).context("Failed to read from the daemon"),
+    })?;
+    if reply.is_empty() {
+        anyhow::bail!("The daemon closed the connection without replying");
+    }
+    Ok(Some(serde_json::from_str(&reply).context("Failed to parse the daemon's reply")?))
+}
+
+/// Launch a detached daemon without waiting for it
+fn launch() -> Result<()> {
+    use std::os::unix::process::CommandExt;
+
+    let exe = env::current_exe().context("Failed to locate the current executable")?;
+    Command::new(exe)
+        .args(["daemon", "run"])
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        // Own process group, so Ctrl-C in the terminal that spawned it does not reach it
+        .process_group(0)
+        .spawn()
+        .context("Failed to start the daemon")?;
+    Ok(())
+}
+
+/// Start a detached daemon and wait until it accepts connections
+fn spawn() -> Result<()> {
+    launch()?;
+    let path = socket_path()?;
+    let deadline = Instant::now() + SPAWN_WAIT;
+    while Instant::now() < deadline {
+        if UnixStream::connect(&path).is_ok() {
+            return Ok(());
+        }
+        thread::sleep(Duration::from_millis(50));
+    }
+    anyhow::bail!("The daemon did not start within {}s", SPAWN_WAIT.as_secs())
+}
+
+/// Whether the video's transcript is in the cache the daemon reads, so it can answer without
+/// a scrape that might outlast `ANSWER_WAIT`
+pub fn is_cached(url: &str) -> bool {
+    youtube::video_id_from_url(url).is_some_and(|id| TranscriptCache::open().is_ok_and(|cache| cache.contains(id)))
+}
+
+/// Answer through the daemon, starting it if needed.
+///
+/// Returns `Ok(None)` when the daemon cannot be used and the caller should answer in-process,
+/// including when it takes longer than `ANSWER_WAIT` to reply; errors are the daemon's own
+/// failures, which retrying locally would only repeat.
+pub fn ask(url: &str, question: &str) -> Result<Option<(String, String)>> {
+    let request = Request::Ask {
+        fingerprint: fingerprint(),
+        url: url.to_string(),
+        question: question.to_string(),
+    };
+
+    let response = match send(&request, ANSWER_WAIT) {
+        Ok(None) => {
+            if let Err(e) = spawn() {
+                status!("⚠️  {}; answering without it", e);
+                return Ok(None);
+            }
+            send(&request, ANSWER_WAIT)
+        }
+        sent => sent,
+    };
+    let response = match response {
+        Ok(response) => response,
+        Err(e) => {
+            status!("⚠️  {:#}; answering without the daemon", e);
+            return Ok(None);
+        }
+    };
+
+    match response {
+        Some(Response::Answer { answer, answer_id }) => Ok(Some((answer, answer_id))),
+        Some(Response::Error { message }) => Err(anyhow::anyhow!(message)),
+        Some(Response::Stale) => {
+            status!("🔄 Settings changed; restarting the daemon in the background");
+            let _ = launch();
+            Ok(None)
+        }
+        _ => Ok(None),
+    }
+}
+
+/// Describe the running daemon, if any
+pub fn status() -> Result<Option<String>> {
+    match send(&Request::Status, CONTROL_WAIT)? {
+        Some(Response::Running {
+            pid,
+            uptime_secs,
+            answered,
+        }) => Ok(Some(format!(
+            "pid {}, up {}m, {} question(s) answered",
+            pid,
+            uptime_secs / 60,
+            answered
+        ))),
+        _ => Ok(None),
+    }
+}
+
+/// Ask a running daemon to exit; false when none was running
+pub fn stop() -> Result<bool> {
+    Ok(matches!(send(&Request::Stop, CONTROL_WAIT)?, Some(Response::Stopping)))
+}
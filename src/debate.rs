@@ -0,0 +1,131 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+
+use crate::guardrail::fence;
+use crate::VideoTranscriber;
+
+const DEBATE_SYSTEM_PROMPT: &str =
+    "You are a neutral debate analyst. You attribute arguments to speakers, track rebuttals, \
+     and judge whether each claim is backed by evidence presented in the debate. Reply with JSON only.";
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct DebateAnalysis {
+    pub speakers: Vec<SpeakerArguments>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct SpeakerArguments {
+    pub name: String,
+    pub arguments: Vec<Claim>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Claim {
+    pub claim: String,
+    /// One of `supported`, `partial`, or `unsupported`
+    pub evidence: String,
+    #[serde(default)]
+    pub evidence_note: String,
+    #[serde(default)]
+    pub rebuttals: Vec<Rebuttal>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Rebuttal {
+    pub by: String,
+    pub text: String,
+}
+
+impl SpeakerArguments {
+    /// Share of this speaker's claims judged as supported (partial counts as half)
+    pub fn evidence_score(&self) -> f64 {
+        if self.arguments.is_empty() {
+            return 0.0;
+        }
+        let points: f64 = self
+            .arguments
+            .iter()
+            .map(|claim| match claim.evidence.as_str() {
+                "supported" => 1.0,
+                "partial" => 0.5,
+                _ => 0.0,
+            })
+            .sum();
+        points / self.arguments.len() as f64
+    }
+}
+
+impl VideoTranscriber {
+    /// Separate a debate into per-speaker claims, rebuttals, and evidence judgements
+    pub fn analyze_debate(&self, url: &str, speakers: &[String]) -> Result<DebateAnalysis> {
+        let transcript = self.load_transcript(url, false)?;
+        let turns = speaker_turns(&transcript.text);
+        status!("🎙️  Found {} speaker turn(s), analyzing arguments...", turns.len());
+
+        let numbered: String = turns
+            .iter()
+            .enumerate()
+            .map(|(i, turn)| format!("[Turn {}] {}\n", i + 1, turn))
+            .collect();
+        let speaker_hint = if speakers.is_empty() {
+            "Infer the speakers' names from how they introduce or address each other.".to_string()
+        } else {
+            format!("The participants are: {}.", speakers.join(", "))
+        };
+
+        let prompt = format!(
+            "Below is a debate transcript split into speaker turns (a new turn starts whenever the speaker changes). {}\n\n\
+             Return JSON with this shape:\n\
+             {{\"speakers\": [{{\"name\": string, \"arguments\": [{{\"claim\": string, \
+             \"evidence\": \"supported\" | \"partial\" | \"unsupported\", \"evidence_note\": string, \
+             \"rebuttals\": [{{\"by\": string, \"text\": string}}]}}]}}]}}\n\
+             A claim is \"supported\" only if the speaker cites data, sources, or verifiable facts in the debate.\n\n{}",
+            speaker_hint,
+            fence(&numbered)
+        );
+
+        self.complete_json(DEBATE_SYSTEM_PROMPT, &prompt)
+    }
+}
+
+/// Split caption text into speaker turns using the `>>` speaker-change markers YouTube captions carry.
+///
+/// Captions without markers come back as a single turn.
+pub fn speaker_turns(text: &str) -> Vec<String> {
+    text.split(">>")
+        .map(|turn| turn.split_whitespace().collect::<Vec<_>>().join(" "))
+        .filter(|turn| !turn.is_empty())
+        .collect()
+}
+
+/// Render the analysis as a Markdown report
+pub fn render_debate(analysis: &DebateAnalysis) -> String {
+    let mut out = String::from("# Debate analysis\n\n| Speaker | Claims | Evidence score |\n|---|---|---|\n");
+    for speaker in &analysis.speakers {
+        out.push_str(&format!(
+            "| {} | {} | {:.0}% |\n",
+            speaker.name,
+            speaker.arguments.len(),
+            speaker.evidence_score() * 100.0
+        ));
+    }
+
+    for speaker in &analysis.speakers {
+        out.push_str(&format!("\n## {}\n", speaker.name));
+        for claim in &speaker.arguments {
+            let badge = match claim.evidence.as_str() {
+                "supported" => "✅",
+                "partial" => "🟡",
+                _ => "❌",
+            };
+            out.push_str(&format!("\n- {} **{}**\n", badge, claim.claim));
+            if !claim.evidence_note.is_empty() {
+                out.push_str(&format!("  - Evidence: {}\n", claim.evidence_note));
+            }
+            for rebuttal in &claim.rebuttals {
+                out.push_str(&format!("  - ↩️ {}: {}\n", rebuttal.by, rebuttal.text));
+            }
+        }
+    }
+    out
+}
@@ -0,0 +1,128 @@
+use chrono::Utc;
+use regex::Regex;
+use reqwest::StatusCode;
+use serde::{Deserialize, Serialize};
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+use crate::config::Config;
+use crate::VideoTranscriber;
+
+/// Response bodies kept in the failure log are cut to this many bytes
+const MAX_BODY_BYTES: usize = 64 * 1024;
+
+/// The most recent API call that returned an error status
+#[derive(Serialize, Deserialize)]
+pub struct HttpFailure {
+    pub at: String,
+    pub service: String,
+    pub method: String,
+    /// Request URL with credentials removed
+    pub url: String,
+    pub status: u16,
+    pub body: String,
+}
+
+pub fn last_error_path(data_dir: &Path) -> PathBuf {
+    data_dir.join("last_error.log")
+}
+
+pub fn last_http_failure_path(data_dir: &Path) -> PathBuf {
+    data_dir.join("last_http_failure.json")
+}
+
+/// API keys currently in use, from the environment and the config file
+fn known_secrets() -> Vec<String> {
+    let config = Config::load().unwrap_or_default();
+    let from_env = ["APIFY_API_KEY", "GEMINI_API_KEY", "GROQ_API_KEY"]
+        .into_iter()
+        .filter_map(|name| env::var(name).ok());
+    let from_config = [config.apify_api_key, config.gemini_api_key, config.groq_api_key]
+        .into_iter()
+        .flatten();
+    from_env
+        .chain(from_config)
+        .filter(|secret| secret.len() >= 8)
+        .collect()
+}
+
+fn credential_pattern() -> &'static Regex {
+    static PATTERN: OnceLock<Regex> = OnceLock::new();
+    PATTERN.get_or_init(|| {
+        Regex::new(r#"(?i)((?:key|token|api_key|apikey)=|bearer\s+|"(?:api_key|token|authorization)"\s*:\s*")[^&\s"]+"#)
+            .expect("valid credential pattern")
+    })
+}
+
+/// Remove API keys and anything that looks like a credential parameter or header
+pub fn scrub(text: &str) -> String {
+    let mut scrubbed = text.to_string();
+    for secret in known_secrets() {
+        scrubbed = scrubbed.replace(&secret, "[REDACTED]");
+    }
+    credential_pattern()
+        .replace_all(&scrubbed, "${1}[REDACTED]")
+        .into_owned()
+}
+
+impl VideoTranscriber {
+    /// Log an API error response in the data directory for `report-bug` and turn it into an
+    /// error
+    pub(crate) fn http_failure(&self, service: &str, method: &str, url: &str, status: StatusCode, body: &str) -> anyhow::Error {
+        http_failure(&self.data_dir, service, method, url, status, body)
+    }
+}
+
+/// Log an API error response in `data_dir` for `report-bug` and turn it into an error
+pub fn http_failure(
+    data_dir: &Path,
+    service: &str,
+    method: &str,
+    url: &str,
+    status: StatusCode,
+    body: &str,
+) -> anyhow::Error {
+    let mut kept = body;
+    if kept.len() > MAX_BODY_BYTES {
+        let mut end = MAX_BODY_BYTES;
+        while !kept.is_char_boundary(end) {
+            end -= 1;
+        }
+        kept = &kept[..end];
+    }
+
+    let failure = HttpFailure {
+        at: Utc::now().to_rfc3339(),
+        service: service.to_string(),
+        method: method.to_string(),
+        url: scrub(url),
+        status: status.as_u16(),
+        body: scrub(kept),
+    };
+    // Logging is best effort; the original error matters more than the log
+    if let Ok(json) = serde_json::to_string_pretty(&failure) {
+        let _ = fs::write(last_http_failure_path(data_dir), json);
+    }
+
+    crate::Error::Http {
+        service: service.to_string(),
+        status,
+        body: body.to_string(),
+    }
+    .into()
+}
+
+/// Log a failed command run (arguments and full error chain, scrubbed) in `data_dir` for
+/// `report-bug`
+pub fn record_error(data_dir: &Path, args: &[String], error: &anyhow::Error) {
+    let contents = format!(
+        "time: {}\nversion: {}\ncommand: {}\n\nerror:\n{:?}\n",
+        Utc::now().to_rfc3339(),
+        env!("CARGO_PKG_VERSION"),
+        args.join(" "),
+        error
+    );
+    let _ = fs::write(last_error_path(data_dir), scrub(&contents));
+}
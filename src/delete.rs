@@ -0,0 +1,86 @@
+use anyhow::{Context, Result};
+
+use crate::cancel::SendCancellable;
+use crate::keyword_index::KeywordIndex;
+use crate::VideoTranscriber;
+
+/// What `delete` removed for one video
+pub struct Deleted {
+    pub label: String,
+    pub files: usize,
+    pub cached: bool,
+    pub registered: bool,
+}
+
+impl VideoTranscriber {
+    /// Delete an uploaded Gemini file; false when Gemini had already deleted it
+    pub fn delete_gemini_file(&self, file_uri: &str) -> Result<bool> {
+        let url = format!("{}?key={}", file_uri, self.gemini_api_key);
+        let response = self
+            .client
+            .delete(&url)
+            .send_with(&self.cancel)
+            .context("Failed to delete Gemini file")?;
+
+        let status = response.status();
+        // Gemini answers 403 rather than 404 for files that no longer exist
+        if status == reqwest::StatusCode::NOT_FOUND || status == reqwest::StatusCode::FORBIDDEN {
+            return Ok(false);
+        }
+        if !status.is_success() {
+            let body = response.text().unwrap_or_default();
+            return Err(self.http_failure("Gemini file delete", "DELETE", &url, status, &body));
+        }
+        Ok(true)
+    }
+
+    /// Remove a video everywhere this tool stored it: its Gemini files and cached context, the
+    /// index registry, and the transcript cache.
+    ///
+    /// Remote files go first, so a failed delete leaves the registry entry in place for a retry.
+    /// The similarity index drops the video on its next rebuild, once it is out of the cache.
+    pub fn delete_video(&self, url: &str) -> Result<Deleted> {
+        let video_id = self.extract_video_id(url)?;
+        let label = self
+            .cache
+            .get(&video_id)?
+            .map_or_else(|| video_id.clone(), |transcript| transcript.label().to_string());
+
+        let parts = self.registry.parts(&video_id)?;
+        let mut files = 0;
+        if !parts.is_empty() {
+            if self.gemini_api_key.is_empty() {
+                status!("⚠️  GEMINI_API_KEY is not set, so uploaded files are left to expire within 48 hours");
+            } else {
+                status!("☁️  Deleting {} Gemini file(s)...", parts.len());
+                for part in &parts {
+                    if self.delete_gemini_file(&part.file_uri)? {
+                        files += 1;
+                    }
+                }
+            }
+        }
+
+        if let Some(context) = self.cache.get_context(&video_id)? {
+            if !self.gemini_api_key.is_empty() && context.expires_at > chrono::Utc::now() {
+                status!("☁️  Deleting the transcript cached in Gemini...");
+                self.delete_cached_context(&context)?;
+            }
+        }
+
+        let registered = self.registry.delete_video(&video_id)?;
+        if let Err(e) = KeywordIndex::open(&self.data_dir).and_then(|index| index.delete_video(&video_id)) {
+            status!("⚠️  Could not remove the video from the keyword index: {}", e);
+        }
+        let cached = self.cache.remove(&video_id)?;
+        if !registered && !cached {
+            anyhow::bail!("{} is not in the index or the transcript cache", video_id);
+        }
+        Ok(Deleted {
+            label,
+            files,
+            cached,
+            registered,
+        })
+    }
+}
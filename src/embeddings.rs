@@ -0,0 +1,479 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::cancel::SendCancellable;
+use crate::feedback::chunk_key;
+use crate::hnsw::Hnsw;
+use crate::hooks::PipelineEvent;
+use crate::progress::Progress;
+use crate::rate_limit::GeminiCall;
+use crate::retrieval::Chunk;
+use crate::simd::dot_and_norms;
+use crate::transcript::Transcript;
+use crate::vectorstore::VectorStore;
+use crate::VideoTranscriber;
+
+/// Gemini embedding model used for similarity features
+pub const EMBEDDING_MODEL: &str = "text-embedding-004";
+
+/// How much of a transcript goes into its video-level embedding (the model caps input at ~2k tokens)
+const VIDEO_EMBEDDING_CHARS: usize = 6_000;
+
+// ===== Gemini Embedding API Structures =====
+
+#[derive(Serialize)]
+struct EmbedRequest<'a> {
+    model: String,
+    content: EmbedContent<'a>,
+}
+
+#[derive(Serialize)]
+struct EmbedContent<'a> {
+    parts: Vec<EmbedPart<'a>>,
+}
+
+#[derive(Serialize)]
+struct EmbedPart<'a> {
+    text: &'a str,
+}
+
+#[derive(Serialize)]
+struct BatchEmbedRequest<'a> {
+    requests: Vec<EmbedRequest<'a>>,
+}
+
+#[derive(Deserialize)]
+struct BatchEmbedResponse {
+    embeddings: Vec<EmbedValues>,
+}
+
+#[derive(Deserialize)]
+struct EmbedResponse {
+    embedding: EmbedValues,
+}
+
+#[derive(Deserialize)]
+struct EmbedValues {
+    values: Vec<f32>,
+}
+
+/// Most texts the batch endpoint accepts in one request
+const MAX_BATCH_ITEMS: usize = 100;
+/// Total characters per batch request, kept well under the request payload limit
+const MAX_BATCH_CHARS: usize = 400_000;
+
+/// Group texts into batches that respect both the item and payload limits
+pub fn plan_batches(texts: &[&str]) -> Vec<std::ops::Range<usize>> {
+    let mut batches = Vec::new();
+    let mut start = 0;
+    let mut chars = 0;
+    for (i, text) in texts.iter().enumerate() {
+        let full = i - start >= MAX_BATCH_ITEMS || (chars + text.len() > MAX_BATCH_CHARS && i > start);
+        if full {
+            batches.push(start..i);
+            start = i;
+            chars = 0;
+        }
+        chars += text.len();
+    }
+    if start < texts.len() {
+        batches.push(start..texts.len());
+    }
+    batches
+}
+
+/// Libraries with at least this many videos are searched through the HNSW index
+pub const HNSW_MIN_VECTORS: usize = 2_000;
+
+/// HNSW graph over the video embeddings, persisted so it is built incrementally. Only the
+/// links are saved; the vectors are read from the video vector store.
+#[derive(Serialize, Deserialize, Default)]
+struct VideoIndex {
+    model: String,
+    index: Hnsw,
+}
+
+impl VideoIndex {
+    fn path(dir: &Path) -> PathBuf {
+        dir.join("video_index.json")
+    }
+
+    fn load(dir: &Path) -> Result<Self> {
+        let path = Self::path(dir);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let file = fs::File::open(&path).with_context(|| format!("Failed to read {}", path.display()))?;
+        serde_json::from_reader(std::io::BufReader::new(file))
+            .with_context(|| format!("Failed to parse {}", path.display()))
+    }
+
+    fn save(&self, dir: &Path) -> Result<()> {
+        let path = Self::path(dir);
+        fs::write(&path, serde_json::to_vec(self)?)
+            .with_context(|| format!("Failed to write {}", path.display()))
+    }
+}
+
+/// Name of the memory-mapped store holding one embedding per video
+const VIDEO_VECTORS: &str = "video_vectors";
+
+/// Video embeddings as written before the memory-mapped store, migrated on first open
+#[derive(Deserialize)]
+struct LegacyVideoEmbeddings {
+    model: String,
+    vectors: HashMap<String, Vec<f32>>,
+}
+
+/// Open the video vector store in `data_dir`, folding in a legacy `video_embeddings.json` if
+/// one is left over
+fn open_video_vectors(data_dir: &Path) -> Result<VectorStore> {
+    let mut store = VectorStore::open(data_dir, VIDEO_VECTORS, EMBEDDING_MODEL)?;
+    let legacy = data_dir.join("video_embeddings.json");
+    if legacy.exists() {
+        let contents = fs::read_to_string(&legacy)
+            .with_context(|| format!("Failed to read {}", legacy.display()))?;
+        let old: LegacyVideoEmbeddings = serde_json::from_str(&contents)
+            .with_context(|| format!("Failed to parse {}", legacy.display()))?;
+        if old.model == EMBEDDING_MODEL {
+            store.append(old.vectors.into_iter().collect())?;
+        }
+        fs::remove_file(&legacy).with_context(|| format!("Failed to remove {}", legacy.display()))?;
+    }
+    Ok(store)
+}
+
+impl VideoTranscriber {
+    /// Embed a piece of text with the Gemini embeddings API
+    pub fn embed_text(&self, text: &str) -> Result<Vec<f32>> {
+        if self.gemini_api_key.is_empty() {
+            anyhow::bail!("GEMINI_API_KEY is required for embedding-based features");
+        }
+
+        let url = format!(
+            "{}/v1beta/models/{}:embedContent?key={}",
+            self.endpoints.gemini, EMBEDDING_MODEL, self.gemini_api_key
+        );
+        let request = EmbedRequest {
+            model: format!("models/{}", EMBEDDING_MODEL),
+            content: EmbedContent {
+                parts: vec![EmbedPart { text }],
+            },
+        };
+
+        self.throttle_gemini(GeminiCall::Embed, 0)?;
+        let response = self
+            .client
+            .post(&url)
+            .json(&request)
+            .send_with(&self.cancel)
+            .context("Failed to request embedding from Gemini")?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().unwrap_or_default();
+            return Err(self.http_failure("Gemini embedding", "POST", &url, status, &body));
+        }
+
+        let embedding: EmbedResponse = response
+            .json()
+            .context("Failed to parse Gemini embedding response")?;
+        Ok(embedding.embedding.values)
+    }
+
+    /// Embed many texts with `batchEmbedContents`, in as few requests as the limits allow
+    pub fn embed_texts(&self, texts: &[&str]) -> Result<Vec<Vec<f32>>> {
+        if self.gemini_api_key.is_empty() {
+            anyhow::bail!("GEMINI_API_KEY is required for embedding-based features");
+        }
+
+        let mut vectors = Vec::with_capacity(texts.len());
+        for range in plan_batches(texts) {
+            vectors.extend(self.embed_batch(&texts[range])?);
+            self.report(Progress::Embedding {
+                done: vectors.len(),
+                total: texts.len(),
+            });
+        }
+        Ok(vectors)
+    }
+
+    /// One batch request, split in half and retried when the API rejects it as too large
+    fn embed_batch(&self, texts: &[&str]) -> Result<Vec<Vec<f32>>> {
+        let url = format!(
+            "{}/v1beta/models/{}:batchEmbedContents?key={}",
+            self.endpoints.gemini, EMBEDDING_MODEL, self.gemini_api_key
+        );
+        let request = BatchEmbedRequest {
+            requests: texts
+                .iter()
+                .map(|text| EmbedRequest {
+                    model: format!("models/{}", EMBEDDING_MODEL),
+                    content: EmbedContent {
+                        parts: vec![EmbedPart { text }],
+                    },
+                })
+                .collect(),
+        };
+
+        self.throttle_gemini(GeminiCall::Embed, 0)?;
+        let response = self
+            .client
+            .post(&url)
+            .json(&request)
+            .send_with(&self.cancel)
+            .context("Failed to request batch embeddings from Gemini")?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().unwrap_or_default();
+            let too_large = status == reqwest::StatusCode::PAYLOAD_TOO_LARGE
+                || (status == reqwest::StatusCode::BAD_REQUEST
+                    && (body.contains("payload size") || body.contains("at most")));
+            if too_large && texts.len() > 1 {
+                let (left, right) = texts.split_at(texts.len() / 2);
+                let mut vectors = self.embed_batch(left)?;
+                vectors.extend(self.embed_batch(right)?);
+                return Ok(vectors);
+            }
+            return Err(self.http_failure(
+                "Gemini batch embedding",
+                "POST",
+                &url,
+                status,
+                &body,
+            ));
+        }
+
+        let batch: BatchEmbedResponse = response
+            .json()
+            .context("Failed to parse Gemini batch embedding response")?;
+        if batch.embeddings.len() != texts.len() {
+            anyhow::bail!(
+                "Gemini returned {} embeddings for {} texts",
+                batch.embeddings.len(),
+                texts.len()
+            );
+        }
+        Ok(batch.embeddings.into_iter().map(|e| e.values).collect())
+    }
+
+    /// Indices of the `k` videos most similar to `query`, best first, with their similarity.
+    ///
+    /// Small libraries are scanned exactly; from `HNSW_MIN_VECTORS` videos up, a persisted
+    /// HNSW index answers in milliseconds at a small cost in recall.
+    pub fn nearest_videos(
+        &self,
+        transcripts: &[Transcript],
+        vectors: &[&[f32]],
+        query: &[f32],
+        k: usize,
+    ) -> Result<Vec<(usize, f32)>> {
+        if transcripts.len() < HNSW_MIN_VECTORS {
+            let mut scored: Vec<(usize, f32)> = vectors
+                .iter()
+                .enumerate()
+                .map(|(i, v)| (i, cosine_similarity(query, v)))
+                .collect();
+            scored.sort_by(|a, b| b.1.total_cmp(&a.1));
+            scored.truncate(k);
+            return Ok(scored);
+        }
+
+        let mut stored = VideoIndex::load(&self.data_dir)?;
+        let positions: HashMap<&str, usize> = transcripts
+            .iter()
+            .enumerate()
+            .map(|(i, t)| (t.video_id.as_str(), i))
+            .collect();
+        // HNSW graphs do not support removal, so rebuild when videos have left the library
+        let stale = stored.model != EMBEDDING_MODEL
+            || stored.index.ids().iter().any(|id| !positions.contains_key(id.as_str()));
+        if stale {
+            stored = VideoIndex {
+                model: EMBEDDING_MODEL.to_string(),
+                index: Hnsw::default(),
+            };
+        }
+
+        // The graph holds no vectors; each node's is borrowed from the memory-mapped store
+        let mut node_vectors: Vec<&[f32]> = stored.index.ids().iter().map(|id| vectors[positions[id.as_str()]]).collect();
+        let before = stored.index.len();
+        for (transcript, vector) in transcripts.iter().zip(vectors) {
+            if !stored.index.contains(&transcript.video_id) {
+                node_vectors.push(vector);
+                stored.index.insert(&transcript.video_id, &node_vectors);
+            }
+        }
+        if stored.index.len() != before || stale {
+            status!("🧭 Updated the similarity index ({} videos)", stored.index.len());
+            stored.save(&self.data_dir)?;
+        }
+
+        let ids = stored.index.ids();
+        Ok(stored
+            .index
+            .search(query, &node_vectors, k, (k * 4).max(64))
+            .into_iter()
+            .filter_map(|(node, score)| positions.get(ids[node].as_str()).map(|&i| (i, score)))
+            .collect())
+    }
+
+    /// Video-level embeddings for the given transcripts, computing and saving any that are
+    /// missing. Look vectors up with `VectorStore::get` or `VectorStore::rows_for`.
+    pub fn video_embeddings(&self, transcripts: &[Transcript]) -> Result<VectorStore> {
+        let mut store = open_video_vectors(&self.data_dir)?;
+
+        let missing: Vec<&Transcript> = transcripts
+            .iter()
+            .filter(|t| !store.contains(&t.video_id))
+            .collect();
+        if missing.is_empty() {
+            return Ok(store);
+        }
+
+        status!("🧬 Embedding {} video(s)...", missing.len());
+        let documents: Vec<String> = missing
+            .iter()
+            .map(|transcript| {
+                format!("{}\n\n{}", transcript.label(), transcript.text)
+                    .chars()
+                    .take(VIDEO_EMBEDDING_CHARS)
+                    .collect()
+            })
+            .collect();
+        let documents: Vec<&str> = documents.iter().map(String::as_str).collect();
+        let vectors = self.embed_texts(&documents)?;
+        store.append(
+            missing
+                .iter()
+                .map(|t| t.video_id.clone())
+                .zip(vectors)
+                .collect(),
+        )?;
+        Ok(store)
+    }
+
+    /// Embed a transcript's retrieval chunks into the registry's vector table, so questions about
+    /// it can be answered by similarity search. A re-chunked transcript replaces its stored
+    /// embeddings. Does nothing without a Gemini key or with embedding retrieval off.
+    pub fn embed_chunks(&self, transcript: &Transcript) -> Result<()> {
+        if self.gemini_api_key.is_empty() || !self.retrieval_params().embeddings {
+            return Ok(());
+        }
+        let (_, chunks) = self.transcript_chunks(transcript)?;
+        let current: HashSet<String> = chunks.iter().map(chunk_key).collect();
+        if !self.registry.chunk_vector_keys(&transcript.video_id)?.is_subset(&current) {
+            self.registry.delete_chunk_vectors(&transcript.video_id)?;
+        }
+        self.store_chunk_embeddings(&chunks)
+    }
+
+    /// Embed and store any of the given chunks the registry has no embedding for
+    fn store_chunk_embeddings(&self, chunks: &[Chunk]) -> Result<()> {
+        let mut stored: HashMap<&str, HashSet<String>> = HashMap::new();
+        let mut missing: Vec<(&Chunk, String)> = Vec::new();
+        for chunk in chunks {
+            let keys = match stored.entry(&chunk.video_id) {
+                Entry::Occupied(entry) => entry.into_mut(),
+                Entry::Vacant(entry) => entry.insert(self.registry.chunk_vector_keys(&chunk.video_id)?),
+            };
+            let key = chunk_key(chunk);
+            // Marked stored as soon as it is queued, so repeated chunks are embedded once
+            if keys.insert(key.clone()) {
+                missing.push((chunk, key));
+            }
+        }
+        if missing.is_empty() {
+            return Ok(());
+        }
+
+        status!("🧬 Embedding {} transcript chunk(s)...", missing.len());
+        let texts: Vec<&str> = missing.iter().map(|(chunk, _)| chunk.text.as_str()).collect();
+        let vectors = self.embed_texts(&texts)?;
+        let mut by_video: HashMap<&str, Vec<(String, Vec<f32>)>> = HashMap::new();
+        for ((chunk, key), vector) in missing.into_iter().zip(vectors) {
+            by_video.entry(&chunk.video_id).or_default().push((key, vector));
+        }
+        for (video_id, vectors) in by_video {
+            self.registry.add_chunk_vectors(video_id, &vectors)?;
+            self.emit(PipelineEvent::ChunksEmbedded {
+                video_id,
+                chunks: vectors.len(),
+            })?;
+        }
+        Ok(())
+    }
+
+    /// Cosine similarity of each chunk to `query`, in chunk order, embedding any chunks not
+    /// embedded at index time. Similarities come from a search of each video's stored
+    /// embeddings; chunks past the nearest `MAX_NEAREST_CHUNKS` of their video score 0.
+    pub fn embedding_scores(&self, query: &str, chunks: &[Chunk]) -> Result<Vec<f32>> {
+        self.store_chunk_embeddings(chunks)?;
+        let query = self.embed_text(query)?;
+        let mut similarities: HashMap<(String, String), f32> = HashMap::new();
+        let videos: HashSet<&str> = chunks.iter().map(|chunk| chunk.video_id.as_str()).collect();
+        for video_id in videos {
+            let stored = self.registry.chunk_vector_keys(video_id)?.len();
+            for found in self.registry.nearest_chunks(&query, stored, Some(video_id))? {
+                similarities.insert((found.video_id, found.chunk_key), found.similarity);
+            }
+        }
+        Ok(chunks
+            .iter()
+            .map(|chunk| {
+                similarities
+                    .get(&(chunk.video_id.clone(), chunk_key(chunk)))
+                    .copied()
+                    .unwrap_or(0.0)
+            })
+            .collect())
+    }
+}
+
+/// Cosine similarity between two vectors (0.0 when either is all zeros)
+pub fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
+    let (dot, norm_a, norm_b) = dot_and_norms(a, b);
+    if norm_a == 0.0 || norm_b == 0.0 {
+        0.0
+    } else {
+        dot / (norm_a.sqrt() * norm_b.sqrt())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn batches_fill_up_to_the_payload_limit() {
+        let text = "x".repeat(MAX_BATCH_CHARS / 4);
+        let texts = vec![text.as_str(); 9];
+        assert_eq!(plan_batches(&texts), [0..4, 4..8, 8..9]);
+    }
+
+    #[test]
+    fn batches_stop_at_the_item_limit() {
+        let texts = vec!["short"; MAX_BATCH_ITEMS * 2 + 1];
+        assert_eq!(
+            plan_batches(&texts),
+            [0..MAX_BATCH_ITEMS, MAX_BATCH_ITEMS..MAX_BATCH_ITEMS * 2, MAX_BATCH_ITEMS * 2..texts.len()]
+        );
+    }
+
+    #[test]
+    fn oversize_text_gets_a_batch_of_its_own() {
+        let huge = "x".repeat(MAX_BATCH_CHARS + 1);
+        assert_eq!(plan_batches(&[huge.as_str()]).len(), 1);
+        assert_eq!(plan_batches(&["before", huge.as_str(), "after"]), [0..1, 1..2, 2..3]);
+    }
+
+    #[test]
+    fn no_texts_means_no_batches() {
+        assert!(plan_batches(&[]).is_empty());
+    }
+}
@@ -0,0 +1,208 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use crate::config::Config;
+use crate::feedback::FeedbackStore;
+use crate::guardrail::fence;
+use crate::llm::ModelChoice;
+use crate::transcript::Transcript;
+use crate::VideoTranscriber;
+
+/// Fewest and most models an ensemble asks
+const MIN_MODELS: usize = 2;
+const MAX_MODELS: usize = 3;
+
+const JUDGE_SYSTEM_PROMPT: &str =
+    "You merge several models' answers to the same question into one answer. Keep what they agree \
+     on, prefer claims the source material supports, and never hide a substantive disagreement: \
+     list each one with every model's position. Reply with JSON only.";
+
+/// `[ensemble]` in the config file: which models answer together and which one merges them
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+pub struct EnsembleConfig {
+    /// Models asked in parallel (2 or 3)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub models: Vec<ModelChoice>,
+    /// Model that merges the answers; defaults to the configured provider and model
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub judge: Option<ModelChoice>,
+    /// Settings for one command (`ask`, `query`, `ask-library`), e.g. `[ensemble.command.ask]`
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub command: HashMap<String, CommandEnsemble>,
+}
+
+/// Per-command overrides of the ensemble
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+pub struct CommandEnsemble {
+    /// Answer with the ensemble even without `--ensemble`
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub models: Vec<ModelChoice>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub judge: Option<ModelChoice>,
+}
+
+/// The models one command answers with
+#[derive(Debug, Clone)]
+pub struct Panel {
+    pub models: Vec<ModelChoice>,
+    pub judge: ModelChoice,
+}
+
+/// One point the models did not agree on
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Disagreement {
+    pub topic: String,
+    /// Each model's position, prefixed with the model's name
+    #[serde(default)]
+    pub positions: Vec<String>,
+    /// Which position the merged answer took, and why
+    #[serde(default)]
+    pub resolution: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct Judgement {
+    answer: String,
+    #[serde(default)]
+    disagreements: Vec<Disagreement>,
+}
+
+/// A merged ensemble answer
+#[derive(Serialize, Debug)]
+pub struct EnsembleAnswer {
+    pub answer: String,
+    pub disagreements: Vec<Disagreement>,
+    /// Models whose answers were merged
+    pub models: Vec<String>,
+    /// `None` when only one model answered
+    pub judge: Option<String>,
+}
+
+impl EnsembleAnswer {
+    pub fn render(&self) -> String {
+        let mut out = self.answer.trim().to_string();
+        if !self.disagreements.is_empty() {
+            out.push_str("\n\n⚠️ Where the models disagreed:\n");
+            for disagreement in &self.disagreements {
+                out.push_str(&format!("- {}\n", disagreement.topic));
+                for position in &disagreement.positions {
+                    out.push_str(&format!("  - {}\n", position));
+                }
+                if let Some(resolution) = &disagreement.resolution {
+                    out.push_str(&format!("  → {}\n", resolution));
+                }
+            }
+        }
+        out.push_str(&format!("\n\nAnswered by {}", self.models.join(", ")));
+        if let Some(judge) = &self.judge {
+            out.push_str(&format!("; merged by {}", judge));
+        }
+        out
+    }
+}
+
+/// Whether `command` is set to answer with the ensemble by default, read before the
+/// transcriber is built (the daemon cannot answer ensemble questions)
+pub fn enabled_for(command: &str) -> bool {
+    Config::load()
+        .ok()
+        .and_then(|config| config.ensemble)
+        .and_then(|ensemble| ensemble.command.get(command).map(|c| c.enabled))
+        .unwrap_or(false)
+}
+
+impl VideoTranscriber {
+    /// The ensemble `command` should answer with, if any. `requested` is `--ensemble`: `None`
+    /// when it wasn't passed, or the models listed with it (empty to use the configured ones).
+    pub fn ensemble_panel(&self, command: &str, requested: Option<Vec<ModelChoice>>) -> Result<Option<Panel>> {
+        let ensemble = self.config.ensemble.clone().unwrap_or_default();
+        let settings = ensemble.command.get(command).cloned().unwrap_or_default();
+        let models = match requested {
+            None if !settings.enabled => return Ok(None),
+            Some(models) if !models.is_empty() => models,
+            _ if !settings.models.is_empty() => settings.models,
+            _ => ensemble.models,
+        };
+        if !(MIN_MODELS..=MAX_MODELS).contains(&models.len()) {
+            anyhow::bail!(
+                "An ensemble needs {} or {} models, got {}; list them with --ensemble or in [ensemble] models",
+                MIN_MODELS,
+                MAX_MODELS,
+                models.len()
+            );
+        }
+        let judge = settings
+            .judge
+            .or(ensemble.judge)
+            .unwrap_or_else(|| self.primary_model());
+        for model in models.iter().chain([&judge]) {
+            self.check_model_key(model)?;
+        }
+        Ok(Some(Panel { models, judge }))
+    }
+
+    /// Ask every model in `panel` the same prompt and have the judge merge the answers
+    pub fn ensemble_complete(&self, panel: &Panel, system: &str, prompt: &str, question: &str) -> Result<EnsembleAnswer> {
+        let names: Vec<String> = panel.models.iter().map(ModelChoice::to_string).collect();
+        status!("🎻 Asking {} models: {}", names.len(), names.join(", "));
+
+        let mut answers = Vec::new();
+        for (name, answer) in names.into_iter().zip(self.complete_each(&panel.models, system, prompt)) {
+            match answer {
+                Ok(answer) => answers.push((name, answer)),
+                Err(e) => status!("⚠️  {} failed: {}", name, e),
+            }
+        }
+        match answers.len() {
+            0 => anyhow::bail!("Every model in the ensemble failed"),
+            1 => {
+                let (name, answer) = answers.remove(0);
+                status!("⚠️  Only {} answered, so there was nothing to merge", name);
+                return Ok(EnsembleAnswer {
+                    answer,
+                    disagreements: Vec::new(),
+                    models: vec![name],
+                    judge: None,
+                });
+            }
+            _ => {}
+        }
+
+        status!("⚖️  Merging answers with {}...", panel.judge);
+        let listed: String = answers
+            .iter()
+            .map(|(name, answer)| format!("=== Answer from {} ===\n{}\n\n", name, fence(answer)))
+            .collect();
+        let judge_prompt = format!(
+            "Question: {}\n\n{}\
+             Merge these into one answer to the question. Return JSON: {{\"answer\": \"the merged \
+             answer\", \"disagreements\": [{{\"topic\": \"what they disagree on\", \"positions\": \
+             [\"<model>: its position\"], \"resolution\": \"which position the answer takes and why, \
+             or that it is unresolved\"}}]}}. Differences of wording or detail are not disagreements.",
+            question, listed
+        );
+        let reply = self.complete_with(&panel.judge, JUDGE_SYSTEM_PROMPT, &judge_prompt)?;
+        let judgement: Judgement = serde_json::from_str(crate::llm::extract_json(&reply))
+            .with_context(|| format!("The judge did not return the expected JSON. Reply was:\n{}", reply))?;
+
+        Ok(EnsembleAnswer {
+            answer: judgement.answer,
+            disagreements: judgement.disagreements,
+            models: answers.into_iter().map(|(name, _)| name).collect(),
+            judge: Some(panel.judge.to_string()),
+        })
+    }
+
+    /// Answer a question about one video with an ensemble, from the same context `ask` uses
+    pub fn ensemble_answer(&self, transcript: &Transcript, question: &str, panel: &Panel) -> Result<EnsembleAnswer> {
+        self.warn_if_unreliable(transcript);
+        let store = FeedbackStore::load(&self.data_dir)?;
+        let context = self.answer_context(transcript, question, store.videos.get(&transcript.video_id))?;
+        let mut merged = self.ensemble_complete(panel, context.system, &context.prompt, question)?;
+        merged.answer = context.with_sources(merged.answer);
+        Ok(merged)
+    }
+}
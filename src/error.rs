@@ -0,0 +1,55 @@
+use reqwest::StatusCode;
+
+/// Failures a caller of the library may want to tell apart.
+///
+/// Errors travel as `anyhow::Error` with context added on the way up; [`kind`] finds the
+/// typed cause underneath.
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    /// A required key is in neither the environment, `.env`, nor the config file
+    #[error("{0} not set (run `claude-video-transcribe init` or add it to .env)")]
+    MissingApiKey(&'static str),
+    /// The configured LLM provider has no key
+    #[error("{key} is required when LLM_PROVIDER={provider}")]
+    ProviderKey { key: &'static str, provider: &'static str },
+    /// Not a YouTube or `file://` URL
+    #[error("Could not extract video ID from URL: {0}")]
+    InvalidUrl(String),
+    /// The scraper returned nothing for the video, usually because it has no captions
+    #[error("No transcript found for {0}. The video might not have captions.")]
+    NoTranscript(String),
+    /// The Apify run ended without succeeding (`FAILED`, `ABORTED`, or `TIMED-OUT`)
+    #[error("Apify run failed with status: {0}")]
+    ApifyRun(String),
+    /// Gemini could not process an uploaded file
+    #[error("Gemini failed to process {name}: {message}")]
+    GeminiFileFailed { name: String, message: String },
+    /// An uploaded file was still not ready when the wait ran out
+    #[error("{name} was still {state} after {secs}s (raise GEMINI_FILE_TIMEOUT to wait longer)")]
+    GeminiFileTimeout { name: String, state: String, secs: u64 },
+    /// A video marked private was asked for where private videos are left out
+    #[error(
+        "{label} is private{}",
+        note.as_deref().map(|note| format!(" ({})", note)).unwrap_or_default()
+    )]
+    PrivateVideo { label: String, note: Option<String> },
+    /// A `hooks::Subscriber` refused a pipeline stage
+    #[error("Stopped after {stage}: {reason}")]
+    Vetoed { stage: &'static str, reason: String },
+    /// The model refused to answer, e.g. for a safety or recitation block. Retrying the same
+    /// prompt gets the same refusal.
+    #[error("{service} declined to answer ({reason}); try rephrasing the question or another provider")]
+    Blocked { service: &'static str, reason: String },
+    /// An API answered with an error status
+    #[error("{service} failed with status {status}: {body}")]
+    Http {
+        service: String,
+        status: StatusCode,
+        body: String,
+    },
+}
+
+/// The typed cause of an error, if it has one
+pub fn kind(error: &anyhow::Error) -> Option<&Error> {
+    error.chain().find_map(|cause| cause.downcast_ref::<Error>())
+}
@@ -0,0 +1,166 @@
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::cmp::Ordering;
+use std::fs;
+use std::path::Path;
+
+use crate::feedback::weighted_rank;
+use crate::grep::find_verbatim;
+use crate::retrieval::{chunk_transcript, Chunk, RetrievalParams};
+use crate::transcript::Transcript;
+use crate::VideoTranscriber;
+
+const CHUNK_SIZES: &[usize] = &[800, 1_500, 2_500];
+const CHUNK_OVERLAPS: &[usize] = &[0, 200, 400];
+const TOP_KS: &[usize] = &[4, 6, 8, 12];
+
+/// One labeled question: retrieval succeeds when an excerpt contains any of the evidence phrases
+#[derive(Deserialize)]
+pub struct EvalCase {
+    pub url: String,
+    pub question: String,
+    pub evidence: Vec<String>,
+}
+
+/// Retrieval quality for one parameter set
+#[derive(Debug, Clone, Copy)]
+pub struct EvalScore {
+    pub params: RetrievalParams,
+    /// Fraction of questions with evidence in the retrieved excerpts
+    pub hit_rate: f64,
+    /// Mean reciprocal rank of the first excerpt containing evidence
+    pub mrr: f64,
+}
+
+impl EvalScore {
+    /// Ordering best first: higher hit rate, then higher MRR, then the smaller prompt
+    fn rank_against(&self, other: &EvalScore) -> Ordering {
+        let budget = |p: &RetrievalParams| p.top_k * p.chunk_chars;
+        other
+            .hit_rate
+            .total_cmp(&self.hit_rate)
+            .then(other.mrr.total_cmp(&self.mrr))
+            .then(budget(&self.params).cmp(&budget(&other.params)))
+    }
+}
+
+/// Read a labeled question set: a JSON array of `{url, question, evidence}`
+pub fn load_cases(path: &Path) -> Result<Vec<EvalCase>> {
+    let contents =
+        fs::read_to_string(path).with_context(|| format!("Failed to read {}", path.display()))?;
+    let cases: Vec<EvalCase> = serde_json::from_str(&contents)
+        .with_context(|| format!("Failed to parse {}", path.display()))?;
+    if cases.is_empty() {
+        anyhow::bail!("{} contains no questions", path.display());
+    }
+    Ok(cases)
+}
+
+/// Score retrieval with `params` over cases whose transcripts are already loaded
+fn score(cases: &[(EvalCase, Transcript)], params: RetrievalParams, chunked: &[Vec<Chunk>]) -> EvalScore {
+    let mut hits = 0;
+    let mut reciprocal_ranks = 0.0;
+
+    for ((case, _), chunks) in cases.iter().zip(chunked) {
+        let ranked = weighted_rank(&case.question, chunks, params.top_k, params.rerank, None);
+        let first = ranked.iter().position(|(i, _)| {
+            case.evidence
+                .iter()
+                .any(|phrase| find_verbatim(&chunks[*i].text, phrase).is_some())
+        });
+        if let Some(rank) = first {
+            hits += 1;
+            reciprocal_ranks += 1.0 / (rank + 1) as f64;
+        }
+    }
+
+    EvalScore {
+        params,
+        hit_rate: hits as f64 / cases.len() as f64,
+        mrr: reciprocal_ranks / cases.len() as f64,
+    }
+}
+
+impl VideoTranscriber {
+    fn load_eval_transcripts(&self, cases: Vec<EvalCase>) -> Result<Vec<(EvalCase, Transcript)>> {
+        cases
+            .into_iter()
+            .map(|case| {
+                let transcript = self.load_transcript(&case.url, false)?;
+                Ok((case, transcript))
+            })
+            .collect()
+    }
+
+    /// Measure retrieval with the current parameters, listing questions whose evidence was missed
+    pub fn eval_run(&self, set: &Path) -> Result<EvalScore> {
+        let cases = self.load_eval_transcripts(load_cases(set)?)?;
+        let params = self.retrieval_params();
+        let chunked: Vec<Vec<Chunk>> = cases
+            .iter()
+            .map(|(_, t)| chunk_transcript(t, params.chunk_chars, params.chunk_overlap))
+            .collect();
+
+        for ((case, transcript), chunks) in cases.iter().zip(&chunked) {
+            let ranked = weighted_rank(&case.question, chunks, params.top_k, params.rerank, None);
+            let found = ranked.iter().any(|(i, _)| {
+                case.evidence
+                    .iter()
+                    .any(|phrase| find_verbatim(&chunks[*i].text, phrase).is_some())
+            });
+            if !found {
+                status!("❌ {} — {}", transcript.label(), case.question);
+            }
+        }
+
+        Ok(score(&cases, params, &chunked))
+    }
+
+    /// Sweep chunk size, overlap, k, and reranking, returning every score best first
+    pub fn eval_tune(&self, set: &Path) -> Result<Vec<EvalScore>> {
+        let cases = self.load_eval_transcripts(load_cases(set)?)?;
+        let mut scores = Vec::new();
+
+        for &chunk_chars in CHUNK_SIZES {
+            for &chunk_overlap in CHUNK_OVERLAPS {
+                if chunk_overlap * 2 >= chunk_chars {
+                    continue;
+                }
+                let chunked: Vec<Vec<Chunk>> = cases
+                    .iter()
+                    .map(|(_, t)| chunk_transcript(t, chunk_chars, chunk_overlap))
+                    .collect();
+                for &top_k in TOP_KS {
+                    for rerank in [false, true] {
+                        let params = RetrievalParams {
+                            chunk_chars,
+                            chunk_overlap,
+                            top_k,
+                            rerank,
+                            ..self.retrieval_params()
+                        };
+                        scores.push(score(&cases, params, &chunked));
+                    }
+                }
+            }
+        }
+        status!("🧪 Tried {} parameter sets on {} question(s)", scores.len(), cases.len());
+
+        scores.sort_by(EvalScore::rank_against);
+        Ok(scores)
+    }
+}
+
+/// One-line summary of a score
+pub fn describe(score: &EvalScore) -> String {
+    let p = score.params;
+    format!(
+        "hit rate {:>5.1}%  MRR {:.3}  (chunk {} chars, overlap {}, k {}, rerank {})",
+        score.hit_rate * 100.0,
+        score.mrr,
+        p.chunk_chars,
+        p.chunk_overlap,
+        p.top_k,
+        if p.rerank { "on" } else { "off" }
+    )
+}
@@ -0,0 +1,114 @@
+use anyhow::Result;
+use clap::ValueEnum;
+use std::path::Path;
+
+use crate::transcript::{format_timestamp, render_srt, render_vtt, Transcript};
+
+/// Seconds of video per paragraph of a Markdown export
+const PARAGRAPH_SECS: f64 = 60.0;
+
+/// File formats a transcript can be exported in
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TranscriptFormat {
+    /// Plain text
+    #[default]
+    Txt,
+    /// A heading with the video's details, then paragraphs that link to their moment in the video
+    Markdown,
+    /// SubRip subtitles
+    Srt,
+    /// WebVTT captions
+    Vtt,
+    /// The transcript with its segments, as `translate --json` prints a translation
+    Json,
+}
+
+impl TranscriptFormat {
+    /// The usual file extension
+    pub fn extension(self) -> &'static str {
+        match self {
+            TranscriptFormat::Txt => "txt",
+            TranscriptFormat::Markdown => "md",
+            TranscriptFormat::Srt => "srt",
+            TranscriptFormat::Vtt => "vtt",
+            TranscriptFormat::Json => "json",
+        }
+    }
+
+    /// The format a file name's extension stands for, if it is one of them
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let extension = path.extension()?.to_str()?.to_ascii_lowercase();
+        match extension.as_str() {
+            "txt" | "text" => Some(TranscriptFormat::Txt),
+            "md" | "markdown" => Some(TranscriptFormat::Markdown),
+            "srt" => Some(TranscriptFormat::Srt),
+            "vtt" | "webvtt" => Some(TranscriptFormat::Vtt),
+            "json" => Some(TranscriptFormat::Json),
+            _ => None,
+        }
+    }
+
+    /// Whether the format carries cue timings, which only timed transcripts have
+    pub fn needs_timing(self) -> bool {
+        matches!(self, TranscriptFormat::Srt | TranscriptFormat::Vtt)
+    }
+}
+
+/// Write a transcript in `format`. Subtitle formats take their cues from the caption
+/// segments, so they fail for transcripts that only have plain text.
+pub fn render_transcript(transcript: &Transcript, format: TranscriptFormat) -> Result<String> {
+    if format.needs_timing() && transcript.segments.is_empty() {
+        anyhow::bail!(
+            "\"{}\" has no timed captions to build {} cues from; export it as txt, markdown, or json instead",
+            transcript.label(),
+            format.extension()
+        );
+    }
+    Ok(match format {
+        TranscriptFormat::Txt => format!("{}\n", transcript.text.trim()),
+        TranscriptFormat::Markdown => to_markdown(transcript),
+        TranscriptFormat::Srt => render_srt(&transcript.segments),
+        TranscriptFormat::Vtt => render_vtt(&transcript.segments),
+        TranscriptFormat::Json => format!("{}\n", serde_json::to_string_pretty(transcript)?),
+    })
+}
+
+/// The transcript as Markdown: the video's details, then a paragraph per minute of video that
+/// starts with a link to its moment, or the plain text when untimed
+fn to_markdown(transcript: &Transcript) -> String {
+    let mut out = format!("# {}\n\n", transcript.label());
+    if let Some(channel) = &transcript.channel {
+        out.push_str(&format!("- **Channel:** {}\n", channel));
+    }
+    if let Some(published) = transcript.published_at {
+        out.push_str(&format!("- **Published:** {}\n", published.format("%Y-%m-%d")));
+    }
+    out.push_str(&format!("- **Video:** {}\n", transcript.url));
+
+    if transcript.segments.is_empty() {
+        out.push_str(&format!("\n{}\n", transcript.text.trim()));
+        return out;
+    }
+    let mut paragraphs: Vec<String> = Vec::new();
+    let mut next_paragraph = 0.0;
+    for segment in &transcript.segments {
+        let text = segment.text.split_whitespace().collect::<Vec<_>>().join(" ");
+        match paragraphs.last_mut() {
+            Some(paragraph) if segment.start < next_paragraph => {
+                paragraph.push(' ');
+                paragraph.push_str(&text);
+            }
+            _ => {
+                paragraphs.push(format!(
+                    "[{}]({}) {}",
+                    format_timestamp(segment.start),
+                    transcript.link_at(segment.start),
+                    text
+                ));
+                next_paragraph = segment.start + PARAGRAPH_SECS;
+            }
+        }
+    }
+    out.push_str(&format!("\n{}\n", paragraphs.join("\n\n")));
+    out
+}
@@ -0,0 +1,75 @@
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Browser origins allowed to call the server: extension pages and YouTube itself
+const ALLOWED_ORIGIN_PREFIXES: &[&str] = &[
+    "chrome-extension://",
+    "moz-extension://",
+    "safari-web-extension://",
+];
+const ALLOWED_ORIGINS: &[&str] = &["https://www.youtube.com", "https://m.youtube.com"];
+
+/// Body of `POST /extension/ask`
+#[derive(Deserialize)]
+pub struct ExtensionAsk {
+    /// URL of the tab's current video
+    pub url: String,
+    pub question: String,
+    /// Playhead position in seconds; when set the answer is scoped to what was just said
+    pub at: Option<f64>,
+    /// Seconds before the playhead to draw on
+    #[serde(default = "default_window")]
+    pub window: f64,
+}
+
+fn default_window() -> f64 {
+    180.0
+}
+
+pub fn token_path(data_dir: &Path) -> PathBuf {
+    data_dir.join("extension_token")
+}
+
+/// The token the extension sends as `Authorization: Bearer <token>`, kept in `data_dir` and
+/// created on first use
+pub fn load_or_create_token(data_dir: &Path) -> Result<String> {
+    let path = token_path(data_dir);
+    if let Ok(token) = fs::read_to_string(&path) {
+        let token = token.trim();
+        if !token.is_empty() {
+            return Ok(token.to_string());
+        }
+    }
+
+    let mut bytes = [0u8; 24];
+    getrandom::getrandom(&mut bytes).context("Failed to generate an extension token")?;
+    let token = hex::encode(bytes);
+    fs::write(&path, &token).with_context(|| format!("Failed to write {}", path.display()))?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o600))?;
+    }
+    Ok(token)
+}
+
+pub fn is_allowed_origin(origin: &str) -> bool {
+    ALLOWED_ORIGINS.contains(&origin) || ALLOWED_ORIGIN_PREFIXES.iter().any(|prefix| origin.starts_with(prefix))
+}
+
+/// Compare a presented `Authorization` header against the token without an early exit,
+/// so response timing does not leak how much of a guess was right
+pub fn authorized(header: Option<&str>, token: &str) -> bool {
+    let Some(presented) = header.and_then(|value| value.strip_prefix("Bearer ")) else {
+        return false;
+    };
+    let presented = presented.trim().as_bytes();
+    presented.len() == token.len()
+        && presented
+            .iter()
+            .zip(token.as_bytes())
+            .fold(0u8, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
@@ -0,0 +1,522 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+use crate::cancel;
+use crate::retrieval::{
+    bm25_rank, cited_sources, millis, phrase_boost, reciprocal_rank_fusion, Chunk, RankedChunk,
+    Retrieval, RetrievalParams, RetrievalTimings,
+};
+use crate::prompts;
+use crate::strategy::Strategy;
+use crate::transcript::Transcript;
+use crate::VideoTranscriber;
+
+/// Transcripts longer than this are answered from retrieved excerpts instead of in full
+pub const RETRIEVAL_THRESHOLD_CHARS: usize = 40_000;
+/// Upper bound for a video's `k` after repeated bad feedback
+const MAX_TOP_K: usize = 16;
+/// How much one rating moves a chunk's weight
+const WEIGHT_STEP: f32 = 0.25;
+const MIN_WEIGHT: f32 = 0.25;
+const MAX_WEIGHT: f32 = 3.0;
+
+/// Whether an answer was helpful
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum Rating {
+    Good,
+    Bad,
+}
+
+/// An answer the user can rate later, with the excerpts it was built from
+#[derive(Serialize, Deserialize, Clone)]
+pub struct AnswerRecord {
+    pub id: String,
+    pub video_id: String,
+    pub question: String,
+    /// Keys of the retrieved chunks; empty when the whole transcript was used
+    pub chunks: Vec<String>,
+    pub asked_at: DateTime<Utc>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rating: Option<Rating>,
+}
+
+/// Learned retrieval adjustments for one video
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub struct VideoFeedback {
+    /// Multiplier applied to a chunk's retrieval score, keyed by `chunk_key`
+    #[serde(default)]
+    pub weights: HashMap<String, f32>,
+    /// Excerpts to retrieve for this video, when feedback has changed it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub top_k: Option<usize>,
+}
+
+/// Answer log and per-video retrieval adjustments, stored in the data directory
+#[derive(Serialize, Deserialize, Default)]
+pub struct FeedbackStore {
+    #[serde(default)]
+    pub answers: Vec<AnswerRecord>,
+    #[serde(default)]
+    pub videos: HashMap<String, VideoFeedback>,
+}
+
+impl FeedbackStore {
+    fn path(dir: &Path) -> PathBuf {
+        dir.join("feedback.json")
+    }
+
+    pub fn load(dir: &Path) -> Result<Self> {
+        let path = Self::path(dir);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let contents = fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        serde_json::from_str(&contents).with_context(|| format!("Failed to parse {}", path.display()))
+    }
+
+    pub fn save(&self, dir: &Path) -> Result<()> {
+        let path = Self::path(dir);
+        fs::write(&path, serde_json::to_string_pretty(self)?)
+            .with_context(|| format!("Failed to write {}", path.display()))
+    }
+
+    /// Apply a rating to an earlier answer, adjusting the weights of the chunks it used
+    pub fn rate(
+        &mut self,
+        answer_id: &str,
+        rating: Rating,
+        default_top_k: usize,
+    ) -> Result<&AnswerRecord> {
+        let index = self
+            .answers
+            .iter()
+            .position(|a| a.id == answer_id)
+            .with_context(|| format!("No answer with id {}", answer_id))?;
+        let previous = self.answers[index].rating.replace(rating);
+        let record = &self.answers[index];
+
+        let video = self.videos.entry(record.video_id.clone()).or_default();
+        // Re-rating undoes the earlier adjustment before applying the new one
+        let delta = signed(rating) - previous.map(signed).unwrap_or(0.0);
+        for key in &record.chunks {
+            let weight = video.weights.entry(key.clone()).or_insert(1.0);
+            *weight = (*weight + delta * WEIGHT_STEP).clamp(MIN_WEIGHT, MAX_WEIGHT);
+        }
+        // Bad answers from retrieval usually mean the right excerpt was just out of reach
+        if rating == Rating::Bad && previous != Some(Rating::Bad) && !record.chunks.is_empty() {
+            let k = video.top_k.unwrap_or(default_top_k);
+            video.top_k = Some((k + 2).min(MAX_TOP_K));
+        }
+
+        Ok(&self.answers[index])
+    }
+}
+
+fn signed(rating: Rating) -> f32 {
+    match rating {
+        Rating::Good => 1.0,
+        Rating::Bad => -1.0,
+    }
+}
+
+/// Stable identifier for a chunk, independent of how many chunks surround it
+pub fn chunk_key(chunk: &Chunk) -> String {
+    let digest = Sha256::digest(chunk.text.as_bytes());
+    hex::encode(&digest[..6])
+}
+
+/// BM25 ranking with optional phrase reranking and each chunk's score scaled by its feedback weight
+pub fn weighted_rank(
+    query: &str,
+    chunks: &[Chunk],
+    k: usize,
+    rerank: bool,
+    feedback: Option<&VideoFeedback>,
+) -> Vec<(usize, f32)> {
+    score_chunks(query, chunks, None, None, k, rerank, feedback)
+        .into_iter()
+        .map(|hit| (hit.index, hit.score))
+        .collect()
+}
+
+/// The `k` best chunks, with optional phrase reranking and feedback weights, keeping each
+/// factor for inspection.
+///
+/// Chunks are scored by reciprocal rank fusion of embedding `similarities` and `keyword` BM25
+/// scores (one of each per chunk) when both are given, by similarity alone when only those are,
+/// and otherwise by in-memory BM25.
+pub fn score_chunks(
+    query: &str,
+    chunks: &[Chunk],
+    similarities: Option<&[f32]>,
+    keyword: Option<&[Option<f32>]>,
+    k: usize,
+    rerank: bool,
+    feedback: Option<&VideoFeedback>,
+) -> Vec<RankedChunk> {
+    let hit = |index: usize, score: f32| RankedChunk {
+        index,
+        score,
+        similarity: None,
+        bm25: None,
+        rrf: None,
+        rerank: None,
+        feedback_weight: None,
+    };
+    let mut hits: Vec<RankedChunk> = match (similarities, keyword) {
+        (Some(similarities), Some(keyword)) => {
+            let ranking = |score: &dyn Fn(usize) -> Option<f32>| {
+                let mut ranked: Vec<(usize, f32)> = (0..chunks.len()).filter_map(|i| Some((i, score(i)?))).collect();
+                ranked.sort_by(|a, b| b.1.total_cmp(&a.1).then(a.0.cmp(&b.0)));
+                ranked.into_iter().map(|(i, _)| i).collect::<Vec<_>>()
+            };
+            let fused = reciprocal_rank_fusion(
+                &[ranking(&|i| Some(similarities[i])), ranking(&|i| keyword[i])],
+                chunks.len(),
+            );
+            fused
+                .into_iter()
+                .enumerate()
+                .map(|(index, rrf)| RankedChunk {
+                    similarity: Some(similarities[index]),
+                    bm25: keyword[index],
+                    rrf: Some(rrf),
+                    ..hit(index, rrf)
+                })
+                .collect()
+        }
+        (Some(similarities), None) => similarities
+            .iter()
+            .enumerate()
+            .map(|(index, &similarity)| RankedChunk {
+                similarity: Some(similarity),
+                ..hit(index, similarity)
+            })
+            .collect(),
+        _ => bm25_rank(query, chunks, chunks.len())
+            .into_iter()
+            .map(|(index, bm25)| RankedChunk {
+                bm25: Some(bm25),
+                ..hit(index, bm25)
+            })
+            .collect(),
+    };
+    for hit in &mut hits {
+        if rerank {
+            let boost = phrase_boost(query, &chunks[hit.index]);
+            hit.rerank = Some(boost);
+            hit.score *= boost;
+        }
+        if let Some(&weight) = feedback.and_then(|f| f.weights.get(&chunk_key(&chunks[hit.index]))) {
+            hit.feedback_weight = Some(weight);
+            hit.score *= weight;
+        }
+    }
+    hits.sort_by(|a, b| b.score.total_cmp(&a.score));
+    hits.truncate(k);
+    hits
+}
+
+/// An answer logged for feedback, and how it was produced
+pub struct LoggedAnswer {
+    pub answer: String,
+    /// ID to rate the answer with
+    pub id: String,
+    pub strategy: Strategy,
+    /// The retrieved excerpts, empty unless the strategy was retrieval
+    pub excerpts: Vec<Chunk>,
+    /// How the excerpts were ranked, in the same order
+    pub retrieval: Option<Retrieval>,
+}
+
+/// Everything a model is sent to answer one question, so the same context can be put to
+/// several models
+pub struct AnswerContext {
+    pub system: &'static str,
+    pub prompt: String,
+    /// The retrieved excerpts, empty when the whole transcript is sent
+    pub excerpts: Vec<Chunk>,
+    /// How the excerpts were ranked, in the same order
+    pub retrieval: Option<Retrieval>,
+}
+
+impl AnswerContext {
+    /// `answer` with a list of the excerpts it cites, and where their text came from
+    pub fn with_sources(&self, mut answer: String) -> String {
+        let hits: Vec<&Chunk> = self.excerpts.iter().collect();
+        let cited = cited_sources(&answer, &hits);
+        if !cited.is_empty() {
+            answer.push_str(&format!("\n\nSources:\n{}", cited.trim_end()));
+        }
+        answer
+    }
+}
+
+impl VideoTranscriber {
+    /// Retrieval parameters from the config, falling back to the defaults. `CHUNK_CHARS` and
+    /// `CHUNK_OVERLAP` override the chunk size and overlap, unless together they can't chunk
+    /// a transcript.
+    pub fn retrieval_params(&self) -> RetrievalParams {
+        self.config
+            .retrieval
+            .unwrap_or_default()
+            .with_chunk_overrides(self.env_var("CHUNK_CHARS"), self.env_var("CHUNK_OVERLAP"))
+    }
+
+    /// The `k` chunks most relevant to `query`, best first, scaled by any feedback weights.
+    ///
+    /// Ranked by embedding similarity fused with BM25 from the keyword index when a Gemini key
+    /// is set and `embeddings` and `hybrid` are on in the retrieval settings, by similarity
+    /// alone with `hybrid` off, and by in-memory BM25 otherwise or when embedding fails.
+    pub fn rank_chunks(
+        &self,
+        query: &str,
+        chunks: &[Chunk],
+        k: usize,
+        feedback: Option<&VideoFeedback>,
+    ) -> Result<Retrieval> {
+        let params = self.retrieval_params();
+        let mut timings = RetrievalTimings::default();
+        let similarities = if params.embeddings && !self.gemini_api_key.is_empty() {
+            let started = Instant::now();
+            match self.embedding_scores(query, chunks) {
+                Ok(similarities) => {
+                    timings.embedding_ms = Some(millis(started.elapsed()));
+                    Some(similarities)
+                }
+                Err(e) if cancel::cancelled(&e).is_some() => return Err(e),
+                Err(e) => {
+                    status!("⚠️  Could not rank by embeddings ({}); ranking by keywords", e);
+                    None
+                }
+            }
+        } else {
+            None
+        };
+        let keyword = if similarities.is_some() && params.hybrid {
+            let started = Instant::now();
+            match self.keyword_scores(query, chunks) {
+                Ok(scores) => {
+                    timings.keyword_ms = Some(millis(started.elapsed()));
+                    Some(scores)
+                }
+                Err(e) if cancel::cancelled(&e).is_some() => return Err(e),
+                Err(e) => {
+                    status!("⚠️  Could not search the keyword index ({}); ranking by embeddings alone", e);
+                    None
+                }
+            }
+        } else {
+            None
+        };
+
+        let started = Instant::now();
+        let hits = score_chunks(query, chunks, similarities.as_deref(), keyword.as_deref(), k, params.rerank, feedback);
+        timings.ranking_ms = millis(started.elapsed());
+        Ok(Retrieval {
+            method: match (&similarities, &keyword) {
+                (Some(_), Some(_)) => "hybrid",
+                (Some(_), None) => "embeddings",
+                _ => "bm25",
+            },
+            candidates: chunks.len(),
+            hits,
+            timings,
+        })
+    }
+
+    /// The prompt for a question: the best excerpts of long transcripts, ranked with any
+    /// feedback recorded for the video, or the whole of shorter ones
+    pub fn answer_context(
+        &self,
+        transcript: &Transcript,
+        question: &str,
+        feedback: Option<&VideoFeedback>,
+    ) -> Result<AnswerContext> {
+        self.answer_context_for(transcript, question, question, feedback)
+    }
+
+    /// Like `answer_context`, ranking excerpts by `query` rather than the question itself
+    pub fn answer_context_for(
+        &self,
+        transcript: &Transcript,
+        question: &str,
+        query: &str,
+        feedback: Option<&VideoFeedback>,
+    ) -> Result<AnswerContext> {
+        if !self.retrieves(transcript)? {
+            return Ok(AnswerContext {
+                system: prompts::system_prompt(Strategy::Full),
+                prompt: self.direct_prompt(transcript, question)?,
+                excerpts: Vec::new(),
+                retrieval: None,
+            });
+        }
+
+        let params = self.retrieval_params();
+        let k = feedback.and_then(|v| v.top_k).unwrap_or(params.top_k);
+        let started = Instant::now();
+        let (_, chunks) = self.transcript_chunks(transcript)?;
+        let chunking_ms = millis(started.elapsed());
+        let mut retrieval = self.rank_chunks(query, &chunks, k, feedback)?;
+        retrieval.timings.chunking_ms = chunking_ms;
+        let hits: Vec<&Chunk> = retrieval.hits.iter().map(|hit| &chunks[hit.index]).collect();
+        status!("🔎 Answering from {} of {} excerpts", hits.len(), chunks.len());
+
+        let prompt = prompts::retrieval(transcript, &hits, question, &self.video_prompt_options(&transcript.video_id)?);
+        Ok(AnswerContext {
+            system: prompts::system_prompt(Strategy::Retrieval),
+            prompt,
+            excerpts: hits.into_iter().cloned().collect(),
+            retrieval: Some(retrieval),
+        })
+    }
+
+    /// Answer a question about one video and log it so it can be rated with `feedback`.
+    ///
+    /// The strategy is picked per question (see [`VideoTranscriber::choose_strategy`]);
+    /// retrieval ranks excerpts with any feedback recorded for the video.
+    pub fn answer_with_feedback(&self, transcript: &Transcript, question: &str) -> Result<LoggedAnswer> {
+        let mut store = FeedbackStore::load(&self.data_dir)?;
+        self.warn_if_unreliable(transcript);
+
+        let strategy = self.choose_strategy(transcript, question, &store)?;
+        let (answer, context) = match strategy {
+            Strategy::Files => (self.answer_from_files(transcript, question)?, None),
+            Strategy::Retrieval => {
+                let context = self.answer_context(transcript, question, store.videos.get(&transcript.video_id))?;
+                let answer = context.with_sources(self.complete(context.system, &context.prompt)?);
+                (answer, Some(context))
+            }
+            Strategy::Cached => match self.ask_cached_context(transcript, question)? {
+                Some(answer) => (answer, None),
+                None => {
+                    if !self.cacheable(transcript) {
+                        status!("⚠️  Too short for Gemini's context cache (or GEMINI_CACHE_TTL is 0); sending it whole");
+                    }
+                    (self.ask_question_direct(transcript, question)?, None)
+                }
+            },
+            Strategy::Full | Strategy::Auto => (self.ask_question_direct(transcript, question)?, None),
+        };
+        let (excerpts, retrieval) = context.map_or_else(Default::default, |context| (context.excerpts, context.retrieval));
+
+        let asked_at = Utc::now();
+        let id = hex::encode(
+            &Sha256::digest(format!("{}\n{}\n{}", transcript.video_id, question, asked_at))[..4],
+        );
+        store.answers.push(AnswerRecord {
+            id: id.clone(),
+            video_id: transcript.video_id.clone(),
+            question: question.to_string(),
+            chunks: excerpts.iter().map(chunk_key).collect(),
+            asked_at,
+            rating: None,
+        });
+        store.save(&self.data_dir)?;
+
+        Ok(LoggedAnswer {
+            answer,
+            id,
+            strategy,
+            excerpts,
+            retrieval,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chunks(texts: &[&str]) -> Vec<Chunk> {
+        texts
+            .iter()
+            .map(|text| Chunk {
+                video_id: "dQw4w9WgXcQ".to_string(),
+                title: "Never Gonna Give You Up".to_string(),
+                url: "https://www.youtube.com/watch?v=dQw4w9WgXcQ".to_string(),
+                start: None,
+                text: text.to_string(),
+                sources: Vec::new(),
+            })
+            .collect()
+    }
+
+    fn order(hits: &[RankedChunk]) -> Vec<usize> {
+        hits.iter().map(|hit| hit.index).collect()
+    }
+
+    #[test]
+    fn fusion_puts_chunks_both_rankings_agree_on_first() {
+        let chunks = chunks(&["one", "two", "three"]);
+        // Similarity ranks 1, 2, 0; BM25 ranks 1, 2 and has no score for 0
+        let hits = score_chunks(
+            "query",
+            &chunks,
+            Some(&[0.2, 0.9, 0.5]),
+            Some(&[None, Some(2.0), Some(1.0)]),
+            3,
+            false,
+            None,
+        );
+        assert_eq!(order(&hits), [1, 2, 0]);
+        assert_eq!(hits[0].rrf, Some(2.0 / 61.0));
+        assert_eq!(hits[2].rrf, Some(1.0 / 63.0));
+        assert_eq!(hits[2].bm25, None);
+        assert_eq!(hits[0].similarity, Some(0.9));
+    }
+
+    #[test]
+    fn fusion_ties_keep_chunk_order() {
+        let chunks = chunks(&["one", "two", "three"]);
+        // 0 and 1 swap places between the rankings, so they fuse to the same score
+        let hits = score_chunks(
+            "query",
+            &chunks,
+            Some(&[0.9, 0.8, 0.1]),
+            Some(&[Some(1.0), Some(3.0), None]),
+            3,
+            false,
+            None,
+        );
+        assert_eq!(hits[0].score, hits[1].score);
+        assert_eq!(order(&hits), [0, 1, 2]);
+
+        // Equal similarities rank by position too
+        let hits = score_chunks("query", &chunks, Some(&[0.5, 0.5, 0.5]), Some(&[None; 3]), 3, false, None);
+        assert_eq!(order(&hits), [0, 1, 2]);
+    }
+
+    #[test]
+    fn empty_keyword_ranking_leaves_the_similarity_order() {
+        let chunks = chunks(&["one", "two", "three"]);
+        let hits = score_chunks("query", &chunks, Some(&[0.1, 0.7, 0.4]), Some(&[None; 3]), 2, false, None);
+        assert_eq!(order(&hits), [1, 2]);
+        assert!(hits.iter().all(|hit| hit.bm25.is_none() && hit.rrf.is_some()));
+    }
+
+    #[test]
+    fn bm25_alone_ranks_by_matching_terms() {
+        let chunks = chunks(&[
+            "we talk about the weather",
+            "the borrow checker rejects this borrow",
+            "a borrow here",
+            "nothing relevant at all",
+        ]);
+        let hits = score_chunks("borrow checker", &chunks, None, None, 4, false, None);
+        assert_eq!(order(&hits), [1, 2]);
+        assert!(hits.iter().all(|hit| hit.bm25.is_some() && hit.similarity.is_none() && hit.rrf.is_none()));
+
+        assert!(score_chunks("", &chunks, None, None, 4, false, None).is_empty());
+        assert!(score_chunks("borrow", &[], None, None, 4, false, None).is_empty());
+    }
+}
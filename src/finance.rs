@@ -0,0 +1,120 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+
+use crate::guardrail::fence;
+use crate::VideoTranscriber;
+
+const FINANCE_SYSTEM_PROMPT: &str =
+    "You are a financial analyst extracting facts from earnings-call transcripts. \
+     Only report numbers that are stated in the transcript, verbatim. Reply with JSON only.";
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct EarningsCall {
+    pub company: String,
+    pub period: String,
+    #[serde(default)]
+    pub guidance: Vec<Guidance>,
+    #[serde(default)]
+    pub kpis: Vec<KpiMention>,
+    #[serde(default)]
+    pub analyst_qa: Vec<AnalystQuestion>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Guidance {
+    pub metric: String,
+    pub value: String,
+    pub period: String,
+    /// `raised`, `lowered`, `maintained`, `initiated`, or empty when not stated
+    #[serde(default)]
+    pub change: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct KpiMention {
+    pub name: String,
+    pub value: String,
+    #[serde(default)]
+    pub context: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct AnalystQuestion {
+    pub analyst: String,
+    #[serde(default)]
+    pub firm: String,
+    pub question: String,
+    pub answer: String,
+    #[serde(default)]
+    pub answered_by: String,
+}
+
+impl VideoTranscriber {
+    /// Extract guidance, KPI mentions, and analyst Q&A from an earnings call
+    pub fn analyze_earnings_call(&self, url: &str) -> Result<EarningsCall> {
+        let transcript = self.load_transcript(url, false)?;
+        status!("📈 Extracting guidance, KPIs, and analyst Q&A...");
+
+        let prompt = format!(
+            "Extract the following from this earnings call as JSON:\n\
+             {{\"company\": string, \"period\": string (e.g. \"Q3 FY2024\"),\n\
+             \"guidance\": [{{\"metric\": string, \"value\": string, \"period\": string, \
+             \"change\": \"raised\" | \"lowered\" | \"maintained\" | \"initiated\" | \"\"}}],\n\
+             \"kpis\": [{{\"name\": string, \"value\": string, \"context\": string}}],\n\
+             \"analyst_qa\": [{{\"analyst\": string, \"firm\": string, \"question\": string, \
+             \"answer\": string (summary), \"answered_by\": string}}]}}\n\
+             Keep values exactly as spoken, including units and currency.\n\nTranscript:\n{}",
+            fence(&transcript.text)
+        );
+
+        self.complete_json(FINANCE_SYSTEM_PROMPT, &prompt)
+    }
+}
+
+/// Flatten the extraction into one CSV table with a `section` column
+pub fn to_csv(call: &EarningsCall) -> String {
+    let mut out = String::from("section,company,period,name,value,detail\n");
+    let mut row = |section: &str, name: &str, value: &str, detail: &str| {
+        out.push_str(&format!(
+            "{},{},{},{},{},{}\n",
+            section,
+            csv_field(&call.company),
+            csv_field(&call.period),
+            csv_field(name),
+            csv_field(value),
+            csv_field(detail)
+        ));
+    };
+
+    for guidance in &call.guidance {
+        let detail = format!("{} {}", guidance.period, guidance.change);
+        row("guidance", &guidance.metric, &guidance.value, detail.trim());
+    }
+    for kpi in &call.kpis {
+        row("kpi", &kpi.name, &kpi.value, &kpi.context);
+    }
+    for qa in &call.analyst_qa {
+        let analyst = if qa.firm.is_empty() {
+            qa.analyst.clone()
+        } else {
+            format!("{} ({})", qa.analyst, qa.firm)
+        };
+        let answer = if qa.answered_by.is_empty() {
+            qa.answer.clone()
+        } else {
+            format!("{}: {}", qa.answered_by, qa.answer)
+        };
+        row("analyst_qa", &analyst, &qa.question, &answer);
+    }
+
+    out
+}
+
+/// Quote a CSV field when it contains separators, quotes, or newlines
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
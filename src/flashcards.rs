@@ -0,0 +1,94 @@
+use anyhow::Result;
+
+use crate::quiz::Card;
+use crate::transcript::{format_timestamp, Transcript};
+
+/// File formats flashcards can be written in
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum FlashcardFormat {
+    /// Tab-separated notes for Anki's File > Import
+    #[default]
+    Anki,
+    /// A numbered list of questions and answers
+    Markdown,
+    Json,
+}
+
+/// Write cards in `format`
+pub fn render_flashcards(cards: &[Card], transcript: &Transcript, format: FlashcardFormat) -> Result<String> {
+    Ok(match format {
+        FlashcardFormat::Anki => to_anki(cards, transcript),
+        FlashcardFormat::Markdown => to_markdown(cards, transcript),
+        FlashcardFormat::Json => serde_json::to_string_pretty(cards)?,
+    })
+}
+
+/// Anki's text import format: file headers that pick the separator, deck, and note type, then
+/// one Basic note per line with Front, Back, and Tags columns. Fields are HTML, so the answer
+/// can carry a link to the moment in the video it comes from.
+pub fn to_anki(cards: &[Card], transcript: &Transcript) -> String {
+    // `::` nests decks in Anki, and a title shouldn't create a hierarchy by accident
+    let deck = transcript.label().replace("::", ":").replace(['\t', '\n', '\r'], " ");
+    let tag = format!("youtube::{}", anki_tag(&transcript.video_id));
+
+    let mut out = format!(
+        "#separator:tab\n#html:true\n#notetype:Basic\n#deck:{}\n#columns:Front\tBack\tTags\n",
+        deck.trim()
+    );
+    for card in cards {
+        let mut back = html_field(&card.answer);
+        if let Some(at) = card.at {
+            back.push_str(&format!(
+                "<br><a href=\"{}\">{}</a>",
+                html_escape(&transcript.link_at(at)),
+                format_timestamp(at)
+            ));
+        }
+        out.push_str(&format!("{}\t{}\t{}\n", html_field(&card.question), back, tag));
+    }
+    out
+}
+
+/// Cards as a Markdown list, for reading or pasting into notes
+pub fn to_markdown(cards: &[Card], transcript: &Transcript) -> String {
+    let mut out = format!("# Flashcards: {}\n\n_Source: {}_\n", transcript.label(), transcript.url);
+    for (i, card) in cards.iter().enumerate() {
+        out.push_str(&format!(
+            "\n{}. **Q:** {}\n   **A:** {}",
+            i + 1,
+            one_line(&card.question),
+            one_line(&card.answer)
+        ));
+        if let Some(at) = card.at {
+            out.push_str(&format!(" ([{}]({}))", format_timestamp(at), transcript.link_at(at)));
+        }
+        out.push('\n');
+    }
+    out
+}
+
+/// Text with line breaks and runs of whitespace collapsed, so it stays inside its list item
+fn one_line(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// A field with HTML special characters escaped and line breaks as `<br>`, since a raw tab or
+/// newline would end the field or note
+fn html_field(text: &str) -> String {
+    html_escape(text.trim())
+        .replace("\r\n", "\n")
+        .replace('\n', "<br>")
+        .replace('\t', " ")
+}
+
+fn html_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Anki tags are separated by spaces, so anything but letters, digits, `-`, and `_` is dropped
+fn anki_tag(text: &str) -> String {
+    text.chars().filter(|c| c.is_alphanumeric() || *c == '-' || *c == '_').collect()
+}
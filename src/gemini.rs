@@ -0,0 +1,550 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
+
+use crate::bench::model_cost_usd;
+use crate::cancel::SendCancellable;
+use crate::context_cache::CachedContext;
+use crate::conversation::Turn;
+use crate::hooks::PipelineEvent;
+use crate::progress::Progress;
+use crate::prompts;
+use crate::rate_limit::{estimate_tokens, GeminiCall};
+use crate::strategy::Strategy;
+use crate::transcript::Transcript;
+use crate::{guardrail, parts, Error, VideoTranscriber, ANSWER_SYSTEM_PROMPT};
+
+/// How long to wait for an upload to become ACTIVE unless `GEMINI_FILE_TIMEOUT` says otherwise
+const DEFAULT_FILE_TIMEOUT_SECS: u64 = 120;
+/// Longest pause between file-state checks
+const MAX_FILE_POLL_DELAY: Duration = Duration::from_secs(10);
+
+// ===== Gemini API Structures =====
+
+#[derive(Deserialize, Debug)]
+struct GeminiFileResponse {
+    file: GeminiFileInfo,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct GeminiFileInfo {
+    pub name: String,
+    pub uri: String,
+    pub state: String,
+    /// When Gemini will delete the file (48 hours after upload)
+    #[serde(rename = "expirationTime", default)]
+    pub expiration_time: Option<chrono::DateTime<chrono::Utc>>,
+    /// Why processing failed, when `state` is `FAILED`
+    #[serde(default)]
+    pub error: Option<GeminiFileError>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct GeminiFileError {
+    #[serde(default)]
+    pub message: String,
+}
+
+#[derive(Serialize)]
+struct GeminiGenerateRequest {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    system_instruction: Option<GeminiContent>,
+    contents: Vec<GeminiContent>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tools: Option<Vec<GeminiTool>>,
+    /// A `cachedContents/...` context to answer from; it carries its own system instruction
+    #[serde(skip_serializing_if = "Option::is_none")]
+    cached_content: Option<String>,
+}
+
+#[derive(Serialize)]
+struct GeminiContent {
+    parts: Vec<GeminiPart>,
+    role: String,
+}
+
+#[derive(Serialize)]
+struct GeminiPart {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    text: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    file_data: Option<GeminiFileDataRef>,
+}
+
+#[derive(Serialize)]
+struct GeminiFileDataRef {
+    file_uri: String,
+    mime_type: String,
+}
+
+#[derive(Serialize)]
+struct GeminiTool {
+    google_search: Option<GoogleSearch>,
+}
+
+#[derive(Serialize)]
+struct GoogleSearch {}
+
+#[derive(Deserialize, Debug)]
+struct GeminiGenerateResponse {
+    candidates: Option<Vec<GeminiCandidate>>,
+    #[serde(rename = "usageMetadata", default)]
+    usage_metadata: Option<GeminiUsage>,
+    /// Set instead of candidates when the prompt itself was blocked
+    #[serde(rename = "promptFeedback", default)]
+    prompt_feedback: Option<GeminiPromptFeedback>,
+}
+
+#[derive(Deserialize, Debug)]
+struct GeminiPromptFeedback {
+    #[serde(rename = "blockReason", default)]
+    block_reason: Option<String>,
+}
+
+#[derive(Deserialize, Debug)]
+struct GeminiUsage {
+    #[serde(rename = "promptTokenCount", default)]
+    prompt_token_count: u64,
+    #[serde(rename = "candidatesTokenCount", default)]
+    candidates_token_count: u64,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+struct GeminiCandidate {
+    /// Missing when the answer was blocked
+    #[serde(default)]
+    content: Option<GeminiResponseContent>,
+    #[serde(rename = "finishReason", default)]
+    finish_reason: Option<String>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+struct GeminiResponseContent {
+    parts: Vec<GeminiResponsePart>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+struct GeminiResponsePart {
+    text: Option<String>,
+}
+
+impl VideoTranscriber {
+    /// Upload transcript to Gemini File API using resumable upload
+    ///
+    /// Transcripts longer than an hour (or too large for one file) are uploaded in parts,
+    /// recorded in the index registry so questions can reuse them and attach just the relevant ones.
+    pub fn upload_to_gemini(&self, transcript: &Transcript) -> Result<Vec<parts::UploadedPart>> {
+        let spans = parts::split_parts(transcript);
+        if spans.len() > 1 {
+            status!("☁️  Uploading transcript to Gemini File API in {} parts...", spans.len());
+        } else {
+            status!("☁️  Uploading transcript to Gemini File API...");
+        }
+
+        // Stream the body from the cached text file rather than copying the transcript into memory
+        let text_path = self.cache.text_file(transcript)?;
+        let mut uploaded = Vec::with_capacity(spans.len());
+        let parts = spans.len();
+        for (index, span) in spans.into_iter().enumerate() {
+            self.report(Progress::Uploading {
+                video_id: transcript.video_id.clone(),
+                part: index + 1,
+                parts,
+            });
+            let file_name = if index == 0 && span.range.len() == transcript.text.len() {
+                format!("youtube_transcript_{}.txt", transcript.video_id)
+            } else {
+                format!("youtube_transcript_{}_part{}.txt", transcript.video_id, index + 1)
+            };
+            let file = self.upload_text_file(&file_name, &text_path, span.range.clone())?;
+            uploaded.push(parts::UploadedPart {
+                index,
+                start: span.start,
+                end: span.end,
+                range: span.range,
+                file_uri: file.uri,
+                uploaded_at: chrono::Utc::now(),
+                expires_at: file.expiration_time,
+            });
+        }
+
+        self.registry.replace_parts(&transcript.video_id, &uploaded)?;
+        self.registry.record_video(transcript)?;
+        Ok(uploaded)
+    }
+
+    /// Upload a byte range of a text file with the resumable upload protocol
+    pub fn upload_text_file(
+        &self,
+        file_name: &str,
+        text_path: &std::path::Path,
+        range: std::ops::Range<usize>,
+    ) -> Result<GeminiFileInfo> {
+        self.upload_file(file_name, text_path, range, "text/plain")
+    }
+
+    /// Upload a byte range of any file with the resumable upload protocol and wait until
+    /// Gemini has processed it
+    pub fn upload_file(
+        &self,
+        file_name: &str,
+        path: &std::path::Path,
+        range: std::ops::Range<usize>,
+        mime_type: &str,
+    ) -> Result<GeminiFileInfo> {
+        let num_bytes = range.len();
+
+        // Step 1: Start the resumable upload
+        let init_url = format!(
+            "{}/upload/v1beta/files?key={}",
+            self.endpoints.gemini, self.gemini_api_key
+        );
+
+        let metadata = serde_json::json!({
+            "file": {
+                "display_name": file_name,
+            }
+        });
+
+        let init_response = self
+            .client
+            .post(&init_url)
+            .header("X-Goog-Upload-Protocol", "resumable")
+            .header("X-Goog-Upload-Command", "start")
+            .header("X-Goog-Upload-Header-Content-Length", num_bytes.to_string())
+            .header("X-Goog-Upload-Header-Content-Type", mime_type)
+            .header("Content-Type", "application/json")
+            .json(&metadata)
+            .send_with(&self.cancel)
+            .context("Failed to initiate file upload to Gemini")?;
+
+        if !init_response.status().is_success() {
+            let status = init_response.status();
+            let body = init_response.text().unwrap_or_default();
+            return Err(self.http_failure(
+                "Gemini upload init",
+                "POST",
+                &init_url,
+                status,
+                &body,
+            ));
+        }
+
+        // Get the upload URL from the response header
+        let upload_url = init_response
+            .headers()
+            .get("x-goog-upload-url")
+            .context("No upload URL in response headers")?
+            .to_str()
+            .context("Invalid upload URL header")?;
+
+        status!("   Upload session created, sending file data...");
+
+        // Step 2: Upload the actual file bytes
+        let upload_response = self
+            .client
+            .post(upload_url)
+            .header("Content-Length", num_bytes.to_string())
+            .header("X-Goog-Upload-Offset", "0")
+            .header("X-Goog-Upload-Command", "upload, finalize")
+            .body(reqwest::blocking::Body::sized(
+                open_range(path, &range)?,
+                num_bytes as u64,
+            ))
+            .send_with(&self.cancel)
+            .context("Failed to upload file bytes to Gemini")?;
+
+        if !upload_response.status().is_success() {
+            let status = upload_response.status();
+            let body = upload_response.text().unwrap_or_default();
+            return Err(self.http_failure(
+                "Gemini file upload",
+                "POST",
+                upload_url,
+                status,
+                &body,
+            ));
+        }
+
+        let file_response: GeminiFileResponse = upload_response
+            .json()
+            .context("Failed to parse Gemini file upload response")?;
+
+        status!("✅ File uploaded: {}", file_response.file.name);
+        status!("   URI: {}", file_response.file.uri);
+        status!("   State: {}", file_response.file.state);
+
+        self.wait_for_file(file_response.file)
+    }
+
+    /// Look up an uploaded file; `None` once Gemini has deleted it
+    pub fn gemini_file(&self, file_uri: &str) -> Result<Option<GeminiFileInfo>> {
+        let url = format!("{}?key={}", file_uri, self.gemini_api_key);
+        let response = self
+            .client
+            .get(&url)
+            .send_with(&self.cancel)
+            .context("Failed to look up Gemini file")?;
+
+        let status = response.status();
+        // Gemini answers 403 rather than 404 for files that no longer exist
+        if status == reqwest::StatusCode::NOT_FOUND || status == reqwest::StatusCode::FORBIDDEN {
+            return Ok(None);
+        }
+        if !status.is_success() {
+            let body = response.text().unwrap_or_default();
+            return Err(self.http_failure("Gemini file lookup", "GET", &url, status, &body));
+        }
+        response.json().map(Some).context("Failed to parse Gemini file")
+    }
+
+    /// Poll an uploaded file until Gemini has processed it, backing off from one second to ten
+    /// between checks, for up to `GEMINI_FILE_TIMEOUT` seconds (120 by default)
+    fn wait_for_file(&self, mut file: GeminiFileInfo) -> Result<GeminiFileInfo> {
+        let timeout = Duration::from_secs(
+            self.setting("GEMINI_FILE_TIMEOUT", &self.config.gemini_file_timeout_secs.map(|s| s.to_string()))
+                .and_then(|secs| secs.parse().ok())
+                .unwrap_or(DEFAULT_FILE_TIMEOUT_SECS),
+        );
+        let started = Instant::now();
+        let mut delay = Duration::from_secs(1);
+        loop {
+            match file.state.as_str() {
+                "ACTIVE" => return Ok(file),
+                "FAILED" => {
+                    return Err(Error::GeminiFileFailed {
+                        name: file.name,
+                        message: file.error.map(|e| e.message).unwrap_or_default(),
+                    }
+                    .into())
+                }
+                _ => {}
+            }
+            let waited = started.elapsed();
+            if waited >= timeout {
+                return Err(Error::GeminiFileTimeout {
+                    name: file.name,
+                    state: file.state,
+                    secs: timeout.as_secs(),
+                }
+                .into());
+            }
+            status!("⏳ Waiting for Gemini to process the file ({})...", file.state);
+            self.cancel.sleep(delay.min(timeout - waited))?;
+            delay = (delay * 2).min(MAX_FILE_POLL_DELAY);
+            file = self
+                .gemini_file(&file.uri)?
+                .ok_or_else(|| anyhow::anyhow!("Gemini deleted {} while it was being processed", file.name))?;
+        }
+    }
+
+    /// Ask a question about a video using its uploaded Gemini files instead of inline text.
+    ///
+    /// A pre-flight check first re-uploads the cached transcript if the files have expired or
+    /// Gemini no longer has them, so stale URIs never reach the model.
+    pub fn ask_question(&self, transcript: &Transcript, parts: Vec<parts::UploadedPart>, question: &str) -> Result<String> {
+        let (attached, total) = self.attachable_parts(transcript, parts, question)?;
+        if total > 1 {
+            let labels: Vec<String> = attached.iter().map(|p| p.label()).collect();
+            status!("📎 Attaching {} of {} parts: {}", attached.len(), total, labels.join(", "));
+        }
+        let file_uris: Vec<String> = attached.into_iter().map(|p| p.file_uri).collect();
+        status!("🤔 Asking question: \"{}\"", question);
+
+        let prompt = prompts::files(transcript, question, &self.video_prompt_options(&transcript.video_id)?);
+        self.complete_gemini_with_files(prompts::system_prompt(Strategy::Files), &prompt, &file_uris)
+    }
+
+    /// Ask a question with transcript directly using Gemini
+    pub fn ask_question_gemini(&self, transcript: &Transcript, question: &str) -> Result<String> {
+        status!("🤔 Asking question with Gemini: \"{}\"", question);
+
+        let prompt = self.direct_prompt(transcript, question)?;
+        self.complete_gemini(ANSWER_SYSTEM_PROMPT, &prompt)
+    }
+
+    /// Run a generateContent call against Gemini
+    pub fn complete_gemini(&self, system: &str, prompt: &str) -> Result<String> {
+        self.complete_gemini_with_files(system, prompt, &[])
+    }
+
+    /// Run a Gemini completion with uploaded files attached after the prompt
+    pub fn complete_gemini_with_files(&self, system: &str, prompt: &str, file_uris: &[String]) -> Result<String> {
+        self.generate_gemini(&self.gemini_model, system, prompt, file_uris)
+    }
+
+    /// Run a generateContent call against a specific Gemini model
+    pub fn generate_gemini(&self, model: &str, system: &str, prompt: &str, file_uris: &[String]) -> Result<String> {
+        let files: Vec<(&str, &str)> = file_uris.iter().map(|uri| (uri.as_str(), "text/plain")).collect();
+        self.generate_gemini_media(model, system, prompt, &files)
+    }
+
+    /// Run a Gemini completion on the configured model with an uploaded audio or video file
+    pub fn complete_gemini_with_media(&self, system: &str, prompt: &str, file_uri: &str, mime_type: &str) -> Result<String> {
+        self.generate_gemini_media(&self.gemini_model, system, prompt, &[(file_uri, mime_type)])
+    }
+
+    /// generateContent with `(uri, MIME type)` file attachments after the prompt
+    fn generate_gemini_media(&self, model: &str, system: &str, prompt: &str, files: &[(&str, &str)]) -> Result<String> {
+        let request = GeminiGenerateRequest {
+            system_instruction: Some(GeminiContent {
+                parts: vec![GeminiPart {
+                    text: Some(guardrail::harden_system_prompt(system)),
+                    file_data: None,
+                }],
+                role: "system".to_string(),
+            }),
+            contents: vec![GeminiContent {
+                parts: std::iter::once(GeminiPart {
+                    text: Some(prompt.to_string()),
+                    file_data: None,
+                })
+                .chain(files.iter().map(|(uri, mime_type)| GeminiPart {
+                    text: None,
+                    file_data: Some(GeminiFileDataRef {
+                        file_uri: uri.to_string(),
+                        mime_type: mime_type.to_string(),
+                    }),
+                }))
+                .collect(),
+                role: "user".to_string(),
+            }],
+            tools: None,
+            cached_content: None,
+        };
+        self.send_generate(model, &request)
+    }
+
+    /// generateContent on the configured model with earlier turns of a conversation before the
+    /// prompt, as the model's chat history
+    pub fn complete_gemini_conversation(&self, system: &str, history: &[Turn], prompt: &str) -> Result<String> {
+        let content = |role: &str, text: &str| GeminiContent {
+            parts: vec![GeminiPart {
+                text: Some(text.to_string()),
+                file_data: None,
+            }],
+            role: role.to_string(),
+        };
+        let mut contents = Vec::with_capacity(history.len() * 2 + 1);
+        for turn in history {
+            contents.push(content("user", &turn.question));
+            contents.push(content("model", &turn.answer));
+        }
+        contents.push(content("user", prompt));
+        let request = GeminiGenerateRequest {
+            system_instruction: Some(content("system", &guardrail::harden_system_prompt(system))),
+            contents,
+            tools: None,
+            cached_content: None,
+        };
+        self.send_generate(&self.gemini_model, &request)
+    }
+
+    /// Ask on top of a transcript held in Gemini's context cache, sending only `prompt`
+    pub fn generate_gemini_cached(&self, context: &CachedContext, prompt: &str) -> Result<String> {
+        let request = GeminiGenerateRequest {
+            system_instruction: None,
+            contents: vec![GeminiContent {
+                parts: vec![GeminiPart {
+                    text: Some(prompt.to_string()),
+                    file_data: None,
+                }],
+                role: "user".to_string(),
+            }],
+            tools: None,
+            cached_content: Some(context.name.clone()),
+        };
+        self.send_generate(&context.model, &request)
+    }
+
+    fn send_generate(&self, model: &str, request: &GeminiGenerateRequest) -> Result<String> {
+        let generate_url = format!(
+            "{}/v1beta/models/{}:generateContent?key={}",
+            self.endpoints.gemini, model, self.gemini_api_key
+        );
+        // Attached files are not counted; only the inline text is known here
+        let prompt_chars = serde_json::to_string(request)?.len();
+        self.throttle_gemini(GeminiCall::Generate, estimate_tokens(prompt_chars))?;
+        self.report(Progress::Generating {
+            model: model.to_string(),
+        });
+        let response = self
+            .send_retrying("Gemini", || self.client.post(&generate_url).json(request))
+            .context("Failed to generate answer from Gemini")?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().unwrap_or_default();
+            return Err(self.http_failure(
+                "Gemini generate",
+                "POST",
+                &generate_url,
+                status,
+                &body,
+            ));
+        }
+
+        let generate_response: GeminiGenerateResponse = response
+            .json()
+            .context("Failed to parse Gemini generate response")?;
+
+        if let Some(reason) = generate_response
+            .prompt_feedback
+            .as_ref()
+            .and_then(|feedback| feedback.block_reason.clone())
+        {
+            return Err(Error::Blocked { service: "Gemini", reason }.into());
+        }
+        let candidate = generate_response
+            .candidates
+            .as_ref()
+            .and_then(|candidates| candidates.first())
+            .context("No answer generated by Gemini")?;
+        let answer = match candidate
+            .content
+            .as_ref()
+            .and_then(|content| content.parts.first())
+            .and_then(|part| part.text.clone())
+        {
+            Some(answer) => answer,
+            None => match candidate.finish_reason.as_deref() {
+                Some(reason @ ("SAFETY" | "RECITATION" | "BLOCKLIST" | "PROHIBITED_CONTENT" | "SPII")) => {
+                    return Err(Error::Blocked {
+                        service: "Gemini",
+                        reason: reason.to_string(),
+                    }
+                    .into());
+                }
+                _ => anyhow::bail!("No answer generated by Gemini"),
+            },
+        };
+
+        let (input_tokens, output_tokens) = match &generate_response.usage_metadata {
+            Some(usage) => (usage.prompt_token_count, usage.candidates_token_count),
+            None => (estimate_tokens(prompt_chars), estimate_tokens(answer.len())),
+        };
+        self.emit(PipelineEvent::CostIncurred {
+            service: "gemini",
+            model: Some(model),
+            input_tokens,
+            output_tokens,
+            usd: model_cost_usd(model, input_tokens, output_tokens),
+        })?;
+        self.emit(PipelineEvent::AnswerGenerated {
+            model,
+            prompt_chars,
+            answer: &answer,
+        })?;
+        Ok(answer)
+    }
+}
+
+/// Open `path` positioned at the start of `range`, reading no further than its end
+fn open_range(path: &std::path::Path, range: &std::ops::Range<usize>) -> Result<impl std::io::Read> {
+    use std::io::{Read, Seek, SeekFrom};
+
+    let mut file =
+        std::fs::File::open(path).with_context(|| format!("Failed to open {}", path.display()))?;
+    file.seek(SeekFrom::Start(range.start as u64))?;
+    Ok(file.take(range.len() as u64))
+}
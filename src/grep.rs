@@ -0,0 +1,97 @@
+use anyhow::{Context, Result};
+use regex::{Regex, RegexBuilder};
+use std::ops::Range;
+
+use crate::transcript::{format_timestamp, snippet_around, Transcript};
+
+/// One match in a stored transcript
+pub struct GrepHit {
+    pub snippet: String,
+    pub seconds: Option<f64>,
+}
+
+/// Build the search pattern: a literal phrase by default, or a regular expression
+pub fn build_pattern(pattern: &str, regex: bool, ignore_case: bool) -> Result<Regex> {
+    let source = if regex {
+        pattern.to_string()
+    } else {
+        regex::escape(pattern)
+    };
+    RegexBuilder::new(&source)
+        .case_insensitive(ignore_case)
+        .build()
+        .with_context(|| format!("Invalid pattern: {}", pattern))
+}
+
+/// Every match of `pattern` in the transcript, with surrounding context and timestamp
+pub fn grep_transcript(transcript: &Transcript, pattern: &Regex) -> Vec<GrepHit> {
+    pattern
+        .find_iter(&transcript.text)
+        .map(|m| GrepHit {
+            snippet: snippet_around(&transcript.text, m.start(), 120),
+            seconds: transcript.time_at_offset(m.start()),
+        })
+        .collect()
+}
+
+/// Byte offset in `text` where `quote` appears verbatim, ignoring case, punctuation, and spacing
+pub fn find_verbatim(text: &str, quote: &str) -> Option<usize> {
+    find_verbatim_range(text, quote).map(|range| range.start)
+}
+
+/// Byte range of `text` that `quote` matches verbatim, ignoring case, punctuation, and spacing;
+/// from the first to the last alphanumeric character matched
+pub fn find_verbatim_range(text: &str, quote: &str) -> Option<Range<usize>> {
+    let (haystack, offsets) = normalize_with_offsets(text);
+    let (needle, _) = normalize_with_offsets(quote);
+    if needle.is_empty() {
+        return None;
+    }
+    let at = haystack.find(&needle)?;
+    let last = offsets[at + needle.len() - 1];
+    let end = last + text[last..].chars().next().map_or(0, char::len_utf8);
+    Some(offsets[at]..end)
+}
+
+/// Lowercase alphanumerics separated by single spaces, plus the original byte offset of each output byte
+fn normalize_with_offsets(text: &str) -> (String, Vec<usize>) {
+    let mut normalized = String::with_capacity(text.len());
+    let mut offsets = Vec::with_capacity(text.len());
+    let mut pending_space = false;
+
+    for (at, c) in text.char_indices() {
+        if c.is_alphanumeric() {
+            if pending_space && !normalized.is_empty() {
+                normalized.push(' ');
+                offsets.push(at);
+            }
+            pending_space = false;
+            for lower in c.to_lowercase() {
+                let before = normalized.len();
+                normalized.push(lower);
+                offsets.extend(std::iter::repeat_n(at, normalized.len() - before));
+            }
+        } else {
+            pending_space = true;
+        }
+    }
+
+    (normalized, offsets)
+}
+
+/// Hits as `[MM:SS] snippet` lines with links
+pub fn render_hits(transcript: &Transcript, hits: &[GrepHit]) -> String {
+    let mut out = String::new();
+    for hit in hits {
+        match hit.seconds {
+            Some(seconds) => out.push_str(&format!(
+                "[{}] {}\n        {}\n",
+                format_timestamp(seconds),
+                hit.snippet,
+                transcript.link_at(seconds)
+            )),
+            None => out.push_str(&format!("{}\n", hit.snippet)),
+        }
+    }
+    out
+}
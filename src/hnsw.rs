@@ -0,0 +1,336 @@
+use serde::{Deserialize, Serialize};
+use std::cmp::{Ordering, Reverse};
+use std::collections::{BinaryHeap, HashMap, HashSet};
+
+use crate::simd::dot_and_norms;
+
+/// Neighbors kept per node on upper layers (twice this on the bottom layer)
+const DEFAULT_M: usize = 16;
+/// Candidate list size while inserting
+const DEFAULT_EF_CONSTRUCTION: usize = 100;
+
+/// Approximate nearest-neighbor index (Hierarchical Navigable Small World graph) ranking by
+/// cosine similarity.
+///
+/// Only the graph is kept: ids and neighbor lists. The vectors stay wherever the caller keeps
+/// them (the memory-mapped `VectorStore` for video embeddings) and are passed to `insert` and
+/// `search` as `vectors[node]`, in the order the nodes were inserted.
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(from = "Graph")]
+pub struct Hnsw {
+    m: usize,
+    ef_construction: usize,
+    ids: Vec<String>,
+    /// `links[node][layer]` are the node's neighbors on that layer
+    links: Vec<Vec<Vec<u32>>>,
+    entry: Option<u32>,
+    rng_state: u64,
+    /// Node of each id, rebuilt from `ids` on load
+    #[serde(skip)]
+    nodes: HashMap<String, u32>,
+}
+
+/// `Hnsw` as stored, without the id lookup
+#[derive(Deserialize)]
+struct Graph {
+    m: usize,
+    ef_construction: usize,
+    ids: Vec<String>,
+    links: Vec<Vec<Vec<u32>>>,
+    entry: Option<u32>,
+    rng_state: u64,
+}
+
+impl From<Graph> for Hnsw {
+    fn from(graph: Graph) -> Self {
+        Self {
+            nodes: graph.ids.iter().enumerate().map(|(node, id)| (id.clone(), node as u32)).collect(),
+            m: graph.m,
+            ef_construction: graph.ef_construction,
+            ids: graph.ids,
+            links: graph.links,
+            entry: graph.entry,
+            rng_state: graph.rng_state,
+        }
+    }
+}
+
+impl Default for Hnsw {
+    fn default() -> Self {
+        Self {
+            m: DEFAULT_M,
+            ef_construction: DEFAULT_EF_CONSTRUCTION,
+            ids: Vec::new(),
+            links: Vec::new(),
+            entry: None,
+            rng_state: 0x9E37_79B9_7F4A_7C15,
+            nodes: HashMap::new(),
+        }
+    }
+}
+
+/// Similarity paired with a node, ordered by similarity
+#[derive(Clone, Copy, PartialEq)]
+struct Scored(f32, u32);
+
+impl Eq for Scored {}
+
+impl PartialOrd for Scored {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Scored {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.total_cmp(&other.0).then(self.1.cmp(&other.1))
+    }
+}
+
+fn cosine(a: &[f32], b: &[f32]) -> f32 {
+    let (dot, norm_a, norm_b) = dot_and_norms(a, b);
+    if norm_a == 0.0 || norm_b == 0.0 {
+        return 0.0;
+    }
+    dot / (norm_a.sqrt() * norm_b.sqrt())
+}
+
+impl Hnsw {
+    pub fn len(&self) -> usize {
+        self.ids.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ids.is_empty()
+    }
+
+    pub fn contains(&self, id: &str) -> bool {
+        self.nodes.contains_key(id)
+    }
+
+    pub fn ids(&self) -> &[String] {
+        &self.ids
+    }
+
+    fn top_layer(&self, node: u32) -> usize {
+        self.links[node as usize].len() - 1
+    }
+
+    fn max_links(&self, layer: usize) -> usize {
+        if layer == 0 {
+            self.m * 2
+        } else {
+            self.m
+        }
+    }
+
+    /// Geometric level draw with a deterministic xorshift generator
+    fn random_level(&mut self) -> usize {
+        let mut x = self.rng_state;
+        x ^= x << 13;
+        x ^= x >> 7;
+        x ^= x << 17;
+        self.rng_state = x;
+        let uniform = (x >> 11) as f64 / (1u64 << 53) as f64;
+        let scale = 1.0 / (self.m as f64).ln();
+        (-(uniform.max(f64::MIN_POSITIVE)).ln() * scale) as usize
+    }
+
+    /// Best-first search on one layer, returning up to `ef` nodes best first
+    fn search_layer(&self, query: &[f32], vectors: &[&[f32]], entries: &[u32], ef: usize, layer: usize) -> Vec<Scored> {
+        let similarity = |node: u32| cosine(query, vectors[node as usize]);
+        let mut visited: HashSet<u32> = entries.iter().copied().collect();
+        let mut candidates: BinaryHeap<Scored> = BinaryHeap::new();
+        let mut best: BinaryHeap<Reverse<Scored>> = BinaryHeap::new();
+
+        for &entry in entries {
+            let scored = Scored(similarity(entry), entry);
+            candidates.push(scored);
+            best.push(Reverse(scored));
+        }
+
+        while let Some(current) = candidates.pop() {
+            let worst = best.peek().map(|Reverse(s)| s.0).unwrap_or(f32::MIN);
+            if current.0 < worst && best.len() >= ef {
+                break;
+            }
+            let Some(neighbors) = self.links[current.1 as usize].get(layer) else {
+                continue;
+            };
+            for &neighbor in neighbors {
+                if !visited.insert(neighbor) {
+                    continue;
+                }
+                let scored = Scored(similarity(neighbor), neighbor);
+                let worst = best.peek().map(|Reverse(s)| s.0).unwrap_or(f32::MIN);
+                if best.len() < ef || scored.0 > worst {
+                    candidates.push(scored);
+                    best.push(Reverse(scored));
+                    if best.len() > ef {
+                        best.pop();
+                    }
+                }
+            }
+        }
+
+        let mut found: Vec<Scored> = best.into_iter().map(|Reverse(s)| s).collect();
+        found.sort_by(|a, b| b.cmp(a));
+        found
+    }
+
+    /// Add `id` as the next node, whose vector is `vectors[self.len()]`; the vectors before it
+    /// are those of the nodes already in the graph
+    pub fn insert(&mut self, id: &str, vectors: &[&[f32]]) {
+        let node = self.ids.len() as u32;
+        let level = self.random_level();
+        self.ids.push(id.to_string());
+        self.nodes.insert(id.to_string(), node);
+        self.links.push(vec![Vec::new(); level + 1]);
+
+        let Some(entry) = self.entry else {
+            self.entry = Some(node);
+            return;
+        };
+
+        let query = vectors[node as usize];
+        let top = self.top_layer(entry);
+        let mut nearest = entry;
+        for layer in (level + 1..=top).rev() {
+            nearest = self.search_layer(query, vectors, &[nearest], 1, layer)[0].1;
+        }
+
+        for layer in (0..=level.min(top)).rev() {
+            let found = self.search_layer(query, vectors, &[nearest], self.ef_construction, layer);
+            let chosen: Vec<u32> = found.iter().take(self.max_links(layer)).map(|s| s.1).collect();
+            self.links[node as usize][layer] = chosen.clone();
+
+            for neighbor in chosen {
+                self.links[neighbor as usize][layer].push(node);
+                if self.links[neighbor as usize][layer].len() > self.max_links(layer) {
+                    self.prune(neighbor, vectors, layer);
+                }
+            }
+            nearest = found[0].1;
+        }
+
+        if level > top {
+            self.entry = Some(node);
+        }
+    }
+
+    /// Keep only a node's closest links on a layer
+    fn prune(&mut self, node: u32, vectors: &[&[f32]], layer: usize) {
+        let base = vectors[node as usize];
+        let mut scored: Vec<Scored> = self.links[node as usize][layer]
+            .iter()
+            .map(|&n| Scored(cosine(base, vectors[n as usize]), n))
+            .collect();
+        scored.sort_by(|a, b| b.cmp(a));
+        scored.truncate(self.max_links(layer));
+        self.links[node as usize][layer] = scored.into_iter().map(|s| s.1).collect();
+    }
+
+    /// Approximate `k` most similar entries as `(index into ids, cosine similarity)`, best first.
+    ///
+    /// `vectors[node]` is each node's vector, as for `insert`. Larger `ef` trades speed for recall.
+    pub fn search(&self, query: &[f32], vectors: &[&[f32]], k: usize, ef: usize) -> Vec<(usize, f32)> {
+        let Some(entry) = self.entry else {
+            return Vec::new();
+        };
+
+        let mut nearest = entry;
+        for layer in (1..=self.top_layer(entry)).rev() {
+            nearest = self.search_layer(query, vectors, &[nearest], 1, layer)[0].1;
+        }
+
+        self.search_layer(query, vectors, &[nearest], ef.max(k), 0)
+            .into_iter()
+            .take(k)
+            .map(|s| (s.1 as usize, s.0))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Deterministic random vectors with values in [-1, 1)
+    fn random_vectors(count: usize, dim: usize, seed: u64) -> Vec<Vec<f32>> {
+        let mut x = seed | 1;
+        (0..count)
+            .map(|_| {
+                (0..dim)
+                    .map(|_| {
+                        x ^= x << 13;
+                        x ^= x >> 7;
+                        x ^= x << 17;
+                        (x >> 40) as f32 / (1u64 << 23) as f32 - 1.0
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    fn build(vectors: &[Vec<f32>]) -> Hnsw {
+        let vectors: Vec<&[f32]> = vectors.iter().map(Vec::as_slice).collect();
+        let mut index = Hnsw::default();
+        for node in 0..vectors.len() {
+            index.insert(&format!("v{}", node), &vectors[..=node]);
+        }
+        index
+    }
+
+    fn brute_force(vectors: &[&[f32]], query: &[f32], k: usize) -> Vec<usize> {
+        let mut scored: Vec<(usize, f32)> = vectors.iter().enumerate().map(|(i, v)| (i, cosine(query, v))).collect();
+        scored.sort_by(|a, b| b.1.total_cmp(&a.1));
+        scored.into_iter().take(k).map(|(i, _)| i).collect()
+    }
+
+    #[test]
+    fn recall_against_brute_force() {
+        let vectors = random_vectors(1_500, 32, 7);
+        let index = build(&vectors);
+        let slices: Vec<&[f32]> = vectors.iter().map(Vec::as_slice).collect();
+
+        let k = 10;
+        let queries = random_vectors(50, 32, 99);
+        let mut found = 0;
+        for query in &queries {
+            let exact = brute_force(&slices, query, k);
+            let approximate: HashSet<usize> =
+                index.search(query, &slices, k, 64).into_iter().map(|(node, _)| node).collect();
+            found += exact.iter().filter(|node| approximate.contains(node)).count();
+        }
+        let recall = found as f64 / (queries.len() * k) as f64;
+        assert!(recall >= 0.9, "recall {:.3}", recall);
+    }
+
+    #[test]
+    fn an_indexed_vector_finds_itself_first() {
+        let vectors = random_vectors(300, 16, 3);
+        let index = build(&vectors);
+        let slices: Vec<&[f32]> = vectors.iter().map(Vec::as_slice).collect();
+        for node in [0, 150, 299] {
+            let (best, similarity) = index.search(&vectors[node], &slices, 1, 32)[0];
+            assert_eq!(best, node);
+            assert!((similarity - 1.0).abs() < 1e-5);
+        }
+    }
+
+    #[test]
+    fn saved_graph_keeps_membership_and_no_vectors() {
+        let index = build(&random_vectors(20, 8, 5));
+        let json = serde_json::to_string(&index).unwrap();
+        assert!(!json.contains("vectors"));
+        let loaded: Hnsw = serde_json::from_str(&json).unwrap();
+        assert_eq!(loaded.len(), 20);
+        assert!(loaded.contains("v0") && loaded.contains("v19"));
+        assert!(!loaded.contains("v20"));
+    }
+
+    #[test]
+    fn empty_index_finds_nothing() {
+        assert!(Hnsw::default().search(&[1.0, 0.0], &[], 5, 16).is_empty());
+    }
+}
@@ -0,0 +1,83 @@
+use anyhow::Result;
+use std::sync::Arc;
+
+use crate::transcript::Transcript;
+use crate::{Error, VideoTranscriber};
+
+/// A pipeline stage that has just finished, passed to every `Subscriber`. New variants may be
+/// added.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum PipelineEvent<'a> {
+    /// A transcript was loaded from the cache or fetched from its source
+    TranscriptFetched { transcript: &'a Transcript, cached: bool },
+    /// Newly embedded chunks of a video were stored in the index
+    ChunksEmbedded { video_id: &'a str, chunks: usize },
+    /// A model replied to a prompt: an answer, or an intermediate step such as notes or a plan
+    AnswerGenerated { model: &'a str, prompt_chars: usize, answer: &'a str },
+    /// A paid API call finished. Token counts are those the API reported; `usd` is estimated
+    /// from list prices for models, reported by Apify for runs, and `None` when unknown.
+    CostIncurred {
+        service: &'static str,
+        model: Option<&'a str>,
+        input_tokens: u64,
+        output_tokens: u64,
+        usd: Option<f64>,
+    },
+}
+
+impl PipelineEvent<'_> {
+    /// Short name of the stage, used in veto errors
+    pub fn stage(&self) -> &'static str {
+        match self {
+            PipelineEvent::TranscriptFetched { .. } => "transcript fetched",
+            PipelineEvent::ChunksEmbedded { .. } => "chunks embedded",
+            PipelineEvent::AnswerGenerated { .. } => "answer generated",
+            PipelineEvent::CostIncurred { .. } => "cost incurred",
+        }
+    }
+}
+
+/// Observes pipeline stages to log, meter, or veto them.
+///
+/// Subscribers are called in the order they were added, on the thread doing the work. An
+/// error vetoes the stage: the operation stops with `Error::Vetoed` carrying the error's
+/// message, and later subscribers are not called.
+pub trait Subscriber: Send + Sync {
+    fn on_event(&self, event: &PipelineEvent) -> Result<()>;
+}
+
+impl<F> Subscriber for F
+where
+    F: Fn(&PipelineEvent) -> Result<()> + Send + Sync,
+{
+    fn on_event(&self, event: &PipelineEvent) -> Result<()> {
+        self(event)
+    }
+}
+
+impl VideoTranscriber {
+    /// Add a subscriber to every later call of this transcriber and its clones made from now on
+    pub fn subscribe(&mut self, subscriber: impl Subscriber + 'static) {
+        self.subscribers.push(Arc::new(subscriber));
+    }
+
+    /// Pass an event to every subscriber, stopping with `Error::Vetoed` at the first that refuses it
+    pub fn emit(&self, event: PipelineEvent) -> Result<()> {
+        for subscriber in &self.subscribers {
+            if let Err(e) = subscriber.on_event(&event) {
+                return Err(Error::Vetoed {
+                    stage: event.stage(),
+                    reason: format!("{:#}", e),
+                }
+                .into());
+            }
+        }
+        Ok(())
+    }
+
+    /// Whether anyone is listening, so events that cost a request to build can be skipped
+    pub fn has_subscribers(&self) -> bool {
+        !self.subscribers.is_empty()
+    }
+}
@@ -0,0 +1,202 @@
+use anyhow::{Context, Result};
+use reqwest::blocking::{Client, RequestBuilder, Response};
+use reqwest::StatusCode;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+use crate::cancel::{self, SendCancellable};
+use crate::progress::Progress;
+use crate::VideoTranscriber;
+
+/// Whether to speak HTTP/2 to the APIs
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum Http2Mode {
+    /// Negotiate HTTP/2 over TLS when the server offers it, otherwise HTTP/1.1
+    #[default]
+    Auto,
+    /// Always use HTTP/1.1
+    Off,
+    /// Assume HTTP/2 without negotiating (only for servers known to support it)
+    PriorKnowledge,
+}
+
+/// `[http]` config section tuning the client shared by every API call
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(default)]
+pub struct HttpConfig {
+    /// Whole-request timeout in seconds
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timeout_secs: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub connect_timeout_secs: Option<u64>,
+    /// Idle connections kept open per host for reuse
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pool_max_idle_per_host: Option<usize>,
+    /// How long an idle pooled connection is kept before closing
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pool_idle_timeout_secs: Option<u64>,
+    /// TCP keep-alive probe interval; 0 disables it
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tcp_keepalive_secs: Option<u64>,
+    pub http2: Http2Mode,
+    /// Let HTTP/2 flow-control windows grow with throughput (helps large uploads)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub http2_adaptive_window: Option<bool>,
+}
+
+const DEFAULT_POOL_IDLE_TIMEOUT: Duration = Duration::from_secs(90);
+const DEFAULT_TCP_KEEPALIVE: Duration = Duration::from_secs(60);
+
+/// Build the HTTP client. One client is created per run and shared (including across
+/// worker threads) so pooled connections and TLS sessions are reused.
+pub fn build_client(config: &HttpConfig, default_timeout: Duration) -> Result<Client> {
+    let mut builder = Client::builder()
+        .timeout(config.timeout_secs.map(Duration::from_secs).unwrap_or(default_timeout))
+        .pool_idle_timeout(
+            config
+                .pool_idle_timeout_secs
+                .map(Duration::from_secs)
+                .unwrap_or(DEFAULT_POOL_IDLE_TIMEOUT),
+        );
+
+    builder = match config.tcp_keepalive_secs {
+        Some(0) => builder.tcp_keepalive(None),
+        Some(secs) => builder.tcp_keepalive(Duration::from_secs(secs)),
+        None => builder.tcp_keepalive(DEFAULT_TCP_KEEPALIVE),
+    };
+    if let Some(secs) = config.connect_timeout_secs {
+        builder = builder.connect_timeout(Duration::from_secs(secs));
+    }
+    if let Some(max) = config.pool_max_idle_per_host {
+        builder = builder.pool_max_idle_per_host(max);
+    }
+    builder = match config.http2 {
+        Http2Mode::Auto => builder,
+        Http2Mode::Off => builder.http1_only(),
+        Http2Mode::PriorKnowledge => builder.http2_prior_knowledge(),
+    };
+    if let Some(adaptive) = config.http2_adaptive_window {
+        builder = builder.http2_adaptive_window(adaptive);
+    }
+
+    builder.build().context("Failed to build the HTTP client")
+}
+
+/// Base URLs of the APIs the crate calls. The defaults are the public services; the fault
+/// injection tests point them at a local server that plays back failures, and a gateway in
+/// front of the APIs can be set the same way.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Endpoints {
+    pub apify: String,
+    pub gemini: String,
+    pub groq: String,
+    /// Brave Search, used by the research agent's web search
+    pub brave: String,
+}
+
+impl Default for Endpoints {
+    fn default() -> Self {
+        Self {
+            apify: "https://api.apify.com".to_string(),
+            gemini: "https://generativelanguage.googleapis.com".to_string(),
+            groq: "https://api.groq.com".to_string(),
+            brave: "https://api.search.brave.com".to_string(),
+        }
+    }
+}
+
+impl Endpoints {
+    /// Every API served from one base URL, such as a local test server
+    pub fn all(base: &str) -> Self {
+        let base = base.trim_end_matches('/').to_string();
+        Self {
+            apify: base.clone(),
+            gemini: base.clone(),
+            groq: base.clone(),
+            brave: base,
+        }
+    }
+}
+
+/// Attempts made at a request that keeps hitting rate limits or transient failures
+const MAX_ATTEMPTS: u32 = 4;
+/// Wait before the first retry when the server doesn't say how long; doubled for each one after
+const FIRST_BACKOFF: Duration = Duration::from_secs(2);
+/// Longest wait between attempts, whatever the server asks for
+const MAX_BACKOFF: Duration = Duration::from_secs(60);
+
+/// Rate limits and server errors that usually pass
+fn retryable(status: StatusCode) -> bool {
+    matches!(status.as_u16(), 429 | 500 | 502 | 503 | 504)
+}
+
+/// A request that timed out or could not connect, as opposed to one that was cancelled or
+/// could not be built
+fn transient(error: &anyhow::Error) -> bool {
+    cancel::cancelled(error).is_none()
+        && error
+            .chain()
+            .filter_map(|cause| cause.downcast_ref::<reqwest::Error>())
+            .any(|e| e.is_timeout() || e.is_connect())
+}
+
+/// How long the server asked to wait: a `Retry-After` header in seconds, or the `retryDelay`
+/// (like `"21s"`) that Gemini puts in the details of a 429's body
+fn retry_after(response: Response) -> Option<Duration> {
+    let header = response
+        .headers()
+        .get(reqwest::header::RETRY_AFTER)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.trim().parse::<u64>().ok());
+    if let Some(secs) = header {
+        return Some(Duration::from_secs(secs));
+    }
+    let body: serde_json::Value = response.json().ok()?;
+    body["error"]["details"]
+        .as_array()?
+        .iter()
+        .find_map(|detail| detail["retryDelay"].as_str())
+        .and_then(|delay| delay.trim_end_matches('s').parse::<f64>().ok())
+        .filter(|secs| secs.is_finite() && *secs >= 0.0)
+        .map(Duration::from_secs_f64)
+}
+
+impl VideoTranscriber {
+    /// Send the request `build` makes, retrying rate limits (429), server errors that usually
+    /// pass (500, 502, 503, 504), and requests that timed out or could not connect, up to
+    /// four attempts in all. Waits as long as the server asks, else 2s doubling each time.
+    /// The last response is returned whatever its status, for the caller to turn into an
+    /// error; only idempotent requests, or ones worth paying for twice, should go through here.
+    pub fn send_retrying(&self, service: &str, build: impl Fn() -> RequestBuilder) -> Result<Response> {
+        let mut attempt = 1;
+        loop {
+            let backoff = (FIRST_BACKOFF * 2u32.pow(attempt - 1)).min(MAX_BACKOFF);
+            let (wait, reason) = match build().send_with(&self.cancel) {
+                Ok(response) if attempt == MAX_ATTEMPTS || !retryable(response.status()) => return Ok(response),
+                Ok(response) => {
+                    let status = response.status();
+                    let wait = retry_after(response).unwrap_or(backoff).min(MAX_BACKOFF);
+                    if status == StatusCode::TOO_MANY_REQUESTS {
+                        self.report(Progress::RateLimited {
+                            wait_secs: wait.as_secs_f64().ceil() as u64,
+                        });
+                    }
+                    (wait, format!("answered {}", status))
+                }
+                Err(e) if attempt < MAX_ATTEMPTS && transient(&e) => (backoff, format!("failed ({:#})", e)),
+                Err(e) => return Err(e),
+            };
+            status!(
+                "⏳ {} {}; retrying in {}s (attempt {} of {})",
+                service,
+                reason,
+                wait.as_secs_f64().ceil(),
+                attempt + 1,
+                MAX_ATTEMPTS
+            );
+            self.cancel.sleep(wait)?;
+            attempt += 1;
+        }
+    }
+}
@@ -0,0 +1,56 @@
+use anyhow::Result;
+
+use crate::hooks::PipelineEvent;
+use crate::parts;
+use crate::progress::Progress;
+use crate::transcript::Transcript;
+use crate::VideoTranscriber;
+
+impl VideoTranscriber {
+    /// Load a transcript from the cache, fetching it from Apify when missing or when `reindex` is set
+    pub fn load_transcript(&self, url: &str, reindex: bool) -> Result<Transcript> {
+        if !reindex {
+            let video_id = self.extract_video_id(url)?;
+            if let Some(transcript) = self.cache.get(&video_id)? {
+                status!(
+                    "📦 Using cached transcript from {} (pass --reindex to refresh)",
+                    transcript.fetched_at.format("%Y-%m-%d %H:%M UTC")
+                );
+                self.report(Progress::TranscriptReady {
+                    video_id: transcript.video_id.clone(),
+                    cached: true,
+                    chars: transcript.text.len(),
+                });
+                self.emit(PipelineEvent::TranscriptFetched {
+                    transcript: &transcript,
+                    cached: true,
+                })?;
+                return Ok(transcript);
+            }
+        }
+
+        if let Some(path) = url.strip_prefix("file://") {
+            return self.ingest_file(std::path::Path::new(path));
+        }
+        self.fetch_transcript(url)
+    }
+
+    /// Index a video (load the cached transcript or fetch it, then upload to Gemini)
+    pub fn index_video(&self, url: &str, reindex: bool) -> Result<Vec<parts::UploadedPart>> {
+        let transcript = self.load_transcript(url, reindex)?;
+        self.report_caption_quality(&transcript);
+        let parts = self.upload_to_gemini(&transcript)?;
+        if let Err(e) = self.index_chunks(&transcript) {
+            status!("⚠️  Could not index the transcript's chunks: {}", e);
+        }
+        Ok(parts)
+    }
+
+    /// Query a video (index + ask question) - uses direct embedding
+    pub fn query_video(&self, url: &str, question: &str, reindex: bool) -> Result<String> {
+        let transcript = self.load_transcript(url, reindex)?;
+        self.warn_if_unreliable(&transcript);
+        let answer = self.ask_question_direct(&transcript, question)?;
+        Ok(answer)
+    }
+}
@@ -0,0 +1,369 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, NaiveDate, Utc};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::{Duration, SystemTime};
+
+use crate::cancel::{self, SendCancellable};
+use crate::config::IngestRule;
+use crate::meetings;
+use crate::transcript::{join_segments, CaptionSource, Segment, Transcript};
+use crate::VideoTranscriber;
+
+const WHISPER_MODEL: &str = "whisper-large-v3-turbo";
+/// Largest file the transcription endpoint accepts
+const MAX_AUDIO_BYTES: u64 = 25 * 1024 * 1024;
+/// Containers the endpoint reads directly; anything else goes through ffmpeg first
+const DIRECT_EXTENSIONS: &[&str] = &["flac", "m4a", "mp3", "mp4", "mpeg", "mpga", "ogg", "opus", "wav", "webm"];
+const CONVERTED_EXTENSIONS: &[&str] = &["aac", "avi", "mkv", "mov", "wma"];
+/// Files modified more recently than this may still be being written, so they wait a pass
+const SETTLE_SECS: u64 = 10;
+
+/// Files already ingested, keyed by path, so a file is only transcribed again when it changes
+#[derive(Serialize, Deserialize, Default)]
+struct IngestState {
+    files: HashMap<String, IngestedFile>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct IngestedFile {
+    size: u64,
+    modified: u64,
+    video_id: String,
+    ingested_at: DateTime<Utc>,
+}
+
+impl IngestState {
+    fn path(dir: &Path) -> PathBuf {
+        dir.join("ingested.json")
+    }
+
+    fn load(dir: &Path) -> Result<Self> {
+        let path = Self::path(dir);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let contents = fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        serde_json::from_str(&contents).with_context(|| format!("Failed to parse {}", path.display()))
+    }
+
+    fn save(&self, dir: &Path) -> Result<()> {
+        let path = Self::path(dir);
+        fs::write(&path, serde_json::to_string_pretty(self)?)
+            .with_context(|| format!("Failed to write {}", path.display()))
+    }
+}
+
+#[derive(Deserialize)]
+struct WhisperResponse {
+    text: String,
+    #[serde(default)]
+    segments: Vec<WhisperSegment>,
+}
+
+#[derive(Deserialize)]
+struct WhisperSegment {
+    start: f64,
+    end: f64,
+    text: String,
+}
+
+/// Stable ID for a local recording, derived from its path so `file://` URLs resolve to it
+pub fn local_video_id(path: &Path) -> String {
+    let path = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+    let digest = hex::encode(Sha256::digest(path.to_string_lossy().as_bytes()));
+    format!("local-{}", &digest[..12])
+}
+
+fn is_media(path: &Path) -> bool {
+    let Some(extension) = path.extension().and_then(|e| e.to_str()) else {
+        return false;
+    };
+    let extension = extension.to_ascii_lowercase();
+    DIRECT_EXTENSIONS.contains(&extension.as_str()) || CONVERTED_EXTENSIONS.contains(&extension.as_str())
+}
+
+fn unix_secs(time: SystemTime) -> u64 {
+    time.duration_since(SystemTime::UNIX_EPOCH).map_or(0, |d| d.as_secs())
+}
+
+/// Metadata a rule gives a recording; fields the rule does not set are `None`
+#[derive(Default)]
+pub struct RuleMatch {
+    pub title: Option<String>,
+    pub channel: Option<String>,
+    pub date: Option<DateTime<Utc>>,
+}
+
+/// Title, channel, and date for a recording from the first rule whose pattern matches its
+/// file name (without extension). Named groups `title`, `channel`, and `date` (YYYY-MM-DD)
+/// fill those fields; a rule's fixed `channel` applies when the pattern has no such group.
+pub fn apply_rules(stem: &str, rules: &[IngestRule]) -> Result<RuleMatch> {
+    for rule in rules {
+        let pattern = Regex::new(&rule.pattern)
+            .with_context(|| format!("Invalid ingest rule pattern: {}", rule.pattern))?;
+        let Some(captures) = pattern.captures(stem) else {
+            continue;
+        };
+        let group = |name: &str| captures.name(name).map(|m| m.as_str().trim().to_string());
+        return Ok(RuleMatch {
+            title: group("title"),
+            channel: group("channel").or_else(|| rule.channel.clone()),
+            date: group("date")
+                .and_then(|date| NaiveDate::parse_from_str(&date, "%Y-%m-%d").ok())
+                .and_then(|date| date.and_hms_opt(0, 0, 0))
+                .map(|date| date.and_utc()),
+        });
+    }
+    Ok(RuleMatch::default())
+}
+
+/// Media files under `dir`, including meeting folders below it, skipping hidden entries
+fn media_files(dir: &Path, files: &mut Vec<PathBuf>) -> Result<()> {
+    for entry in fs::read_dir(dir).with_context(|| format!("Failed to read {}", dir.display()))? {
+        let path = entry?.path();
+        if path.file_name().is_some_and(|name| name.to_string_lossy().starts_with('.')) {
+            continue;
+        }
+        if path.is_dir() {
+            media_files(&path, files)?;
+        } else if is_media(&path) && !meetings::is_secondary(&path) {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+impl VideoTranscriber {
+    /// Watch a folder and index every media file that appears in it, or changes
+    pub fn ingest_dir(&self, dir: &Path, interval: Duration, once: bool) -> Result<()> {
+        if self.groq_api_key.is_empty() {
+            anyhow::bail!("GROQ_API_KEY is required to transcribe recordings");
+        }
+        if !dir.is_dir() {
+            anyhow::bail!("{} is not a directory", dir.display());
+        }
+        status!(
+            "📂 Watching {} for recordings with {} metadata rule(s)",
+            dir.display(),
+            self.config.ingest_rules.len()
+        );
+
+        loop {
+            if let Err(e) = self.ingest_pass(dir) {
+                if cancel::cancelled(&e).is_some() {
+                    return Err(e);
+                }
+                status!("⚠️  Ingest pass failed: {:#}", e);
+            }
+            if once {
+                return Ok(());
+            }
+            self.cancel.sleep(interval)?;
+        }
+    }
+
+    /// Ingest every new or changed, settled media file in `dir` once
+    fn ingest_pass(&self, dir: &Path) -> Result<()> {
+        let mut state = IngestState::load(&self.data_dir)?;
+        let mut entries = Vec::new();
+        media_files(dir, &mut entries)?;
+        entries.sort();
+
+        let now = unix_secs(SystemTime::now());
+        for path in entries {
+            let metadata = fs::metadata(&path)?;
+            let (size, modified) = (metadata.len(), unix_secs(metadata.modified()?));
+            let key = path.to_string_lossy().to_string();
+            if state.files.get(&key).is_some_and(|f| f.size == size && f.modified == modified) {
+                continue;
+            }
+            if now.saturating_sub(modified) < SETTLE_SECS {
+                status!("⏳ {} is still being written; waiting", path.display());
+                continue;
+            }
+
+            match self.ingest_file(&path) {
+                Ok(transcript) => {
+                    status!("📥 Indexed {} as {}", transcript.label(), transcript.video_id);
+                    state.files.insert(
+                        key,
+                        IngestedFile {
+                            size,
+                            modified,
+                            video_id: transcript.video_id,
+                            ingested_at: Utc::now(),
+                        },
+                    );
+                    state.save(&self.data_dir)?;
+                }
+                Err(e) if cancel::cancelled(&e).is_some() => return Err(e),
+                Err(e) => status!("⚠️  Could not ingest {}: {:#}", path.display(), e),
+            }
+        }
+        Ok(())
+    }
+
+    /// Transcribe one recording and index it like a video: cache, registry, Gemini, embeddings.
+    ///
+    /// Zoom, Teams, and Meet recordings use the captions saved with them when there are any,
+    /// and get their participant list and chat log folded into the transcript.
+    pub fn ingest_file(&self, path: &Path) -> Result<Transcript> {
+        let meeting = meetings::detect(path)?;
+        let (mut segments, mut text) = match &meeting {
+            Some(meeting) if !meeting.captions.is_empty() => {
+                status!("📝 Using the {} captions saved with {}", meeting.platform.name(), path.display());
+                let captions: Vec<Segment> = meeting
+                    .captions
+                    .iter()
+                    .cloned()
+                    .map(|segment| Segment {
+                        source: Some(CaptionSource::Meeting),
+                        ..segment
+                    })
+                    .collect();
+                (captions, String::new())
+            }
+            _ => {
+                status!("🎙️  Transcribing {}...", path.display());
+                let response = self.transcribe_audio(path)?;
+                let segments = response
+                    .segments
+                    .into_iter()
+                    .map(|s| Segment {
+                        start: s.start,
+                        end: s.end,
+                        text: s.text.trim().to_string(),
+                        source: Some(CaptionSource::Whisper),
+                    })
+                    .filter(|s| !s.text.is_empty())
+                    .collect();
+                (segments, response.text.trim().to_string())
+            }
+        };
+        if let Some(meeting) = &meeting {
+            status!(
+                "👥 {} meeting: {} participant(s), {} chat message(s)",
+                meeting.platform.name(),
+                meeting.participants.len(),
+                meeting.chat.len()
+            );
+            if segments.is_empty() {
+                // Untimed text: the chat can only follow it
+                for message in &meeting.chat {
+                    text.push_str(&format!("\n[Chat] {}: {}", message.sender, message.text));
+                }
+            } else {
+                segments = meetings::fold_into(meeting, segments);
+            }
+        }
+        if !segments.is_empty() {
+            text = join_segments(&segments);
+        }
+        if text.trim().is_empty() {
+            anyhow::bail!("No speech found");
+        }
+
+        let stem = path.file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or_default();
+        let rule = apply_rules(&stem, &self.config.ingest_rules)?;
+        let modified = fs::metadata(path)?.modified().ok().map(DateTime::<Utc>::from);
+        let absolute = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+        let transcript = Transcript {
+            video_id: local_video_id(path),
+            url: format!("file://{}", absolute.display()),
+            title: rule
+                .title
+                .or_else(|| meeting.as_ref().and_then(|m| m.title.clone()))
+                .or(Some(stem)),
+            channel: rule
+                .channel
+                .or_else(|| meeting.as_ref().map(|m| format!("{} meetings", m.platform.name()))),
+            text: text.into(),
+            segments,
+            published_at: rule
+                .date
+                .or_else(|| meeting.as_ref().and_then(|m| m.started_at))
+                .or(modified),
+            duration_secs: None,
+            fetched_at: Utc::now(),
+        };
+
+        self.cache.put(&transcript)?;
+        if self.gemini_api_key.is_empty() {
+            self.registry.record_video(&transcript)?;
+        } else {
+            self.upload_to_gemini(&transcript)?;
+            if let Err(e) = self
+                .video_embeddings(std::slice::from_ref(&transcript))
+                .and_then(|_| self.index_chunks(&transcript))
+            {
+                status!("⚠️  Could not embed {}: {}", transcript.label(), e);
+            }
+        }
+        Ok(transcript)
+    }
+
+    /// Send a recording to Groq's Whisper endpoint, converting it with ffmpeg first when the
+    /// container is not accepted directly or the file is over the size limit
+    fn transcribe_audio(&self, path: &Path) -> Result<WhisperResponse> {
+        let extension = path.extension().and_then(|e| e.to_str()).unwrap_or_default().to_ascii_lowercase();
+        let size = fs::metadata(path)?.len();
+        let converted = if size > MAX_AUDIO_BYTES || !DIRECT_EXTENSIONS.contains(&extension.as_str()) {
+            Some(extract_audio(path)?)
+        } else {
+            None
+        };
+        let upload = converted.as_deref().unwrap_or(path);
+        if fs::metadata(upload)?.len() > MAX_AUDIO_BYTES {
+            anyhow::bail!("The audio is over 25 MB even after compression; split the recording first");
+        }
+
+        let form = reqwest::blocking::multipart::Form::new()
+            .text("model", WHISPER_MODEL)
+            .text("response_format", "verbose_json")
+            .file("file", upload)
+            .with_context(|| format!("Failed to read {}", upload.display()))?;
+        let url = format!("{}/openai/v1/audio/transcriptions", self.endpoints.groq);
+        let result = self
+            .client
+            .post(&url)
+            .header("Authorization", format!("Bearer {}", self.groq_api_key))
+            .multipart(form)
+            .send_with(&self.cancel)
+            .context("Failed to send the recording to Groq");
+        if let Some(converted) = &converted {
+            let _ = fs::remove_file(converted);
+        }
+        let response = result?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().unwrap_or_default();
+            return Err(self.http_failure("Groq transcription", "POST", &url, status, &body));
+        }
+        response.json().context("Failed to parse Groq transcription response")
+    }
+}
+
+/// Compress a recording to low-bitrate mono MP3 in the temp directory, enough for speech
+fn extract_audio(path: &Path) -> Result<PathBuf> {
+    let out = std::env::temp_dir().join(format!("cvt-ingest-{}.mp3", std::process::id()));
+    status!("🎚️  Extracting audio with ffmpeg...");
+    let output = Command::new("ffmpeg")
+        .args(["-y", "-loglevel", "error", "-i"])
+        .arg(path)
+        .args(["-vn", "-ac", "1", "-ar", "16000", "-b:a", "32k"])
+        .arg(&out)
+        .output()
+        .context("This recording needs ffmpeg to convert it, and ffmpeg was not found on PATH")?;
+    if !output.status.success() {
+        anyhow::bail!("ffmpeg failed: {}", String::from_utf8_lossy(&output.stderr).trim());
+    }
+    Ok(out)
+}
@@ -0,0 +1,194 @@
+use anyhow::{Context, Result};
+use clap::CommandFactory;
+use clap_complete::Shell;
+use std::fs;
+use std::io::{self, BufRead, Write};
+use std::path::PathBuf;
+use std::time::Duration;
+
+use crate::config::Config;
+use crate::http;
+use crate::{Cli, DEFAULT_GEMINI_MODEL, DEFAULT_GROQ_MODEL};
+
+/// Interactive first-run setup: collect keys, test them, pick defaults, save the config
+pub fn run_wizard() -> Result<()> {
+    let mut config = Config::load()?;
+    let client = http::build_client(
+        &config.http.clone().unwrap_or_default(),
+        Duration::from_secs(30),
+    )?;
+
+    println!("👋 Welcome to claude-video-transcribe! Let's get you set up.");
+    println!("   Press Enter to keep the value shown in [brackets].\n");
+
+    println!("Apify fetches YouTube transcripts: https://console.apify.com/account/integrations");
+    config.apify_api_key = prompt_key("Apify API key", &config.apify_api_key)?;
+    if let Some(key) = &config.apify_api_key {
+        report_check(
+            "Apify",
+            client
+                .get(format!("https://api.apify.com/v2/users/me?token={}", key))
+                .send(),
+        );
+    }
+
+    println!("\nGroq answers questions on a free tier: https://console.groq.com/keys");
+    config.groq_api_key = prompt_key("Groq API key (optional)", &config.groq_api_key)?;
+    if let Some(key) = &config.groq_api_key {
+        report_check(
+            "Groq",
+            client
+                .get("https://api.groq.com/openai/v1/models")
+                .header("Authorization", format!("Bearer {}", key))
+                .send(),
+        );
+    }
+
+    println!("\nGemini powers file uploads and answers: https://aistudio.google.com/app/apikey");
+    config.gemini_api_key = prompt_key("Gemini API key (optional)", &config.gemini_api_key)?;
+    if let Some(key) = &config.gemini_api_key {
+        report_check(
+            "Gemini",
+            client
+                .get(format!(
+                    "https://generativelanguage.googleapis.com/v1beta/models?key={}",
+                    key
+                ))
+                .send(),
+        );
+    }
+
+    let default_provider = config.llm_provider.clone().unwrap_or_else(|| {
+        if config.groq_api_key.is_none() && config.gemini_api_key.is_some() {
+            "gemini".to_string()
+        } else {
+            "groq".to_string()
+        }
+    });
+    let provider = loop {
+        let answer = prompt("\nDefault LLM provider (groq/gemini)", &default_provider)?;
+        match answer.to_lowercase().as_str() {
+            "groq" | "gemini" => break answer.to_lowercase(),
+            _ => println!("   Please enter either 'groq' or 'gemini'."),
+        }
+    };
+
+    if provider == "groq" {
+        let current = config
+            .groq_model
+            .clone()
+            .unwrap_or_else(|| DEFAULT_GROQ_MODEL.to_string());
+        config.groq_model = Some(prompt("Groq model", &current)?);
+    } else {
+        let current = config
+            .gemini_model
+            .clone()
+            .unwrap_or_else(|| DEFAULT_GEMINI_MODEL.to_string());
+        config.gemini_model = Some(prompt("Gemini model", &current)?);
+    }
+    config.llm_provider = Some(provider);
+
+    let path = config.save()?;
+    println!("\n💾 Configuration written to {}", path.display());
+
+    let install = prompt("\nInstall shell completions? (y/N)", "n")?;
+    if install.eq_ignore_ascii_case("y") || install.eq_ignore_ascii_case("yes") {
+        match install_completions() {
+            Ok(path) => println!("✅ Completions installed to {}", path.display()),
+            Err(e) => println!("⚠️  Could not install completions: {}", e),
+        }
+    }
+
+    println!("\n✨ All set! Try:");
+    println!("  claude-video-transcribe query --url \"https://www.youtube.com/watch?v=VIDEO_ID\" --question \"What is this about?\"");
+
+    Ok(())
+}
+
+/// Write the completion script for `shell` to stdout
+pub fn print_completions(shell: Shell) {
+    let mut command = Cli::command();
+    let name = command.get_name().to_string();
+    clap_complete::generate(shell, &mut command, name, &mut io::stdout());
+}
+
+/// Install completions for the user's login shell into its standard location
+fn install_completions() -> Result<PathBuf> {
+    let shell = Shell::from_env().context("Could not detect your shell from $SHELL")?;
+    let home = dirs::home_dir().context("Could not determine the home directory")?;
+
+    let path = match shell {
+        Shell::Bash => home.join(".local/share/bash-completion/completions/claude-video-transcribe"),
+        Shell::Zsh => home.join(".zfunc/_claude-video-transcribe"),
+        Shell::Fish => home.join(".config/fish/completions/claude-video-transcribe.fish"),
+        other => anyhow::bail!(
+            "Automatic install is not supported for {}; use `completions {}` instead",
+            other,
+            other
+        ),
+    };
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    let mut command = Cli::command();
+    let name = command.get_name().to_string();
+    let mut file = fs::File::create(&path)
+        .with_context(|| format!("Failed to create {}", path.display()))?;
+    clap_complete::generate(shell, &mut command, name, &mut file);
+
+    if shell == Shell::Zsh {
+        println!("   Make sure ~/.zfunc is on your fpath: add `fpath+=~/.zfunc` to ~/.zshrc");
+    }
+
+    Ok(path)
+}
+
+/// Prompt for a line of input, falling back to `default` on an empty answer
+fn prompt(label: &str, default: &str) -> Result<String> {
+    if default.is_empty() {
+        print!("{}: ", label);
+    } else {
+        print!("{} [{}]: ", label, default);
+    }
+    io::stdout().flush()?;
+
+    let mut line = String::new();
+    io::stdin().lock().read_line(&mut line)?;
+    let answer = line.trim();
+
+    Ok(if answer.is_empty() {
+        default.to_string()
+    } else {
+        answer.to_string()
+    })
+}
+
+/// Prompt for an API key, showing only a masked version of the existing value
+fn prompt_key(label: &str, current: &Option<String>) -> Result<Option<String>> {
+    let masked = current.as_deref().map(mask_key).unwrap_or_default();
+    let answer = prompt(label, &masked)?;
+
+    if answer.is_empty() {
+        Ok(None)
+    } else if answer == masked {
+        Ok(current.clone())
+    } else {
+        Ok(Some(answer))
+    }
+}
+
+/// Show the last four characters of a key so users can recognise it
+fn mask_key(key: &str) -> String {
+    let visible: String = key.chars().rev().take(4).collect::<Vec<_>>().into_iter().rev().collect();
+    format!("****{}", visible)
+}
+
+fn report_check(service: &str, result: reqwest::Result<reqwest::blocking::Response>) {
+    match result {
+        Ok(response) if response.status().is_success() => println!("   ✅ {} key works", service),
+        Ok(response) => println!("   ❌ {} rejected the key (status {})", service, response.status()),
+        Err(e) => println!("   ⚠️  Could not reach {}: {}", service, e),
+    }
+}
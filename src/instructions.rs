@@ -0,0 +1,72 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::organize::Categories;
+use crate::prompts::PromptOptions;
+use crate::VideoTranscriber;
+
+/// Standing instructions attached to videos and collections, persisted in the data directory
+#[derive(Serialize, Deserialize, Default)]
+pub struct Instructions {
+    /// Keyed by video ID
+    #[serde(default)]
+    pub videos: BTreeMap<String, String>,
+    /// Keyed by collection path; a collection's instructions also cover its sub-collections
+    #[serde(default)]
+    pub collections: BTreeMap<String, String>,
+}
+
+impl Instructions {
+    fn path(dir: &Path) -> PathBuf {
+        dir.join("instructions.json")
+    }
+
+    pub fn load(dir: &Path) -> Result<Self> {
+        let path = Self::path(dir);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let contents = fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        serde_json::from_str(&contents).with_context(|| format!("Failed to parse {}", path.display()))
+    }
+
+    pub fn save(&self, dir: &Path) -> Result<()> {
+        let path = Self::path(dir);
+        fs::write(&path, serde_json::to_string_pretty(self)?)
+            .with_context(|| format!("Failed to write {}", path.display()))
+    }
+
+    /// Every instruction that applies to a video: its collections' (outermost first), then its own
+    pub fn for_video(&self, video_id: &str, categories: &Categories) -> Vec<String> {
+        let mut applicable = Vec::new();
+        for (collection, text) in &self.collections {
+            if categories.covers(collection, video_id) {
+                applicable.push(text.clone());
+            }
+        }
+        if let Some(text) = self.videos.get(video_id) {
+            applicable.push(text.clone());
+        }
+        applicable
+    }
+}
+
+impl VideoTranscriber {
+    /// Prepend any standing instructions for the video to a prompt, and add the `--style` instruction
+    pub fn instructed_prompt(&self, video_id: &str, prompt: String) -> Result<String> {
+        Ok(self.video_prompt_options(video_id)?.finish(prompt))
+    }
+
+    /// The `--style` and `--max-words` settings and the standing instructions for the video
+    pub(crate) fn video_prompt_options(&self, video_id: &str) -> Result<PromptOptions> {
+        let applicable = Instructions::load(&self.data_dir)?.for_video(video_id, &Categories::load(&self.data_dir)?);
+        if !applicable.is_empty() {
+            status!("📌 Applying {} standing instruction(s)", applicable.len());
+        }
+        Ok(self.prompt_options(applicable))
+    }
+}
@@ -0,0 +1,148 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+
+/// How long an idempotency key keeps pointing at its job
+const IDEMPOTENCY_TTL_HOURS: i64 = 24;
+/// Finished jobs older than this are dropped from the store
+const JOB_RETENTION_DAYS: i64 = 7;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum JobStatus {
+    Queued,
+    Running,
+    Succeeded,
+    Failed,
+}
+
+/// One video handled by a job
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct JobVideo {
+    pub video_id: String,
+    pub title: String,
+    /// One Gemini file per uploaded part; empty when the video was already cached
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub file_uris: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// An indexing request accepted by the server
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Job {
+    pub id: String,
+    pub url: String,
+    pub status: JobStatus,
+    pub created_at: DateTime<Utc>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub finished_at: Option<DateTime<Utc>>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub videos: Vec<JobVideo>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// A client-supplied `Idempotency-Key` and the job it created
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct IdempotencyKey {
+    pub key: String,
+    /// Hash of the request body, so a key reused for a different request is caught
+    pub request_hash: String,
+    pub job_id: String,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Server jobs, persisted so idempotency keys survive a restart
+#[derive(Serialize, Deserialize, Default)]
+pub struct JobStore {
+    jobs: Vec<Job>,
+    #[serde(default)]
+    keys: Vec<IdempotencyKey>,
+}
+
+impl JobStore {
+    fn path(dir: &Path) -> PathBuf {
+        dir.join("jobs.json")
+    }
+
+    pub fn load(dir: &Path) -> Result<Self> {
+        let path = Self::path(dir);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let contents = fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        serde_json::from_str(&contents).with_context(|| format!("Failed to parse {}", path.display()))
+    }
+
+    pub fn save(&self, dir: &Path) -> Result<()> {
+        let path = Self::path(dir);
+        fs::write(&path, serde_json::to_string_pretty(self)?)
+            .with_context(|| format!("Failed to write {}", path.display()))
+    }
+
+    /// Fail jobs a previous server process left unfinished and drop old ones
+    pub fn recover(&mut self) {
+        let now = Utc::now();
+        for job in &mut self.jobs {
+            if matches!(job.status, JobStatus::Queued | JobStatus::Running) {
+                job.status = JobStatus::Failed;
+                job.error = Some("The server stopped before this job finished".to_string());
+                job.finished_at = Some(now);
+            }
+        }
+        let cutoff = now - Duration::days(JOB_RETENTION_DAYS);
+        self.jobs.retain(|job| job.finished_at.is_none_or(|at| at > cutoff));
+        let key_cutoff = now - Duration::hours(IDEMPOTENCY_TTL_HOURS);
+        self.keys.retain(|key| key.created_at > key_cutoff);
+    }
+
+    pub fn get(&self, id: &str) -> Option<&Job> {
+        self.jobs.iter().find(|job| job.id == id)
+    }
+
+    /// The key record and job for an idempotency key, while the key is still live
+    pub fn find_by_key(&self, key: &str) -> Option<(&IdempotencyKey, &Job)> {
+        let cutoff = Utc::now() - Duration::hours(IDEMPOTENCY_TTL_HOURS);
+        let record = self
+            .keys
+            .iter()
+            .find(|record| record.key == key && record.created_at > cutoff)?;
+        Some((record, self.get(&record.job_id)?))
+    }
+
+    /// Queue a new job, remembering the idempotency key (and request hash) it was created under
+    pub fn create(&mut self, url: &str, idempotency_key: Option<(String, String)>) -> Job {
+        let now = Utc::now();
+        let job = Job {
+            id: format!("job_{:x}{:03x}", now.timestamp_millis(), self.jobs.len() % 0x1000),
+            url: url.to_string(),
+            status: JobStatus::Queued,
+            created_at: now,
+            finished_at: None,
+            videos: Vec::new(),
+            error: None,
+        };
+        if let Some((key, request_hash)) = idempotency_key {
+            self.keys.retain(|record| record.key != key);
+            self.keys.push(IdempotencyKey {
+                key,
+                request_hash,
+                job_id: job.id.clone(),
+                created_at: now,
+            });
+        }
+        self.jobs.push(job.clone());
+        job
+    }
+
+    pub fn update(&mut self, id: &str, change: impl FnOnce(&mut Job)) {
+        if let Some(job) = self.jobs.iter_mut().find(|job| job.id == id) {
+            change(job);
+        }
+    }
+}
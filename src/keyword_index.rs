@@ -0,0 +1,196 @@
+use anyhow::{Context, Result};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::Path;
+use tantivy::collector::{DocSetCollector, TopDocs};
+use tantivy::directory::MmapDirectory;
+use tantivy::query::{BooleanQuery, ConstScoreQuery, Occur, Query, QueryParser, TermQuery};
+use tantivy::schema::{Field, IndexRecordOption, Schema, Value, STORED, STRING, TEXT};
+use tantivy::{Index, IndexWriter, TantivyDocument, Term};
+
+use crate::feedback::chunk_key;
+use crate::retrieval::Chunk;
+use crate::transcript::Transcript;
+use crate::VideoTranscriber;
+
+/// Memory the writer buffers before writing a segment
+const WRITER_MEMORY_BYTES: usize = 15_000_000;
+
+/// A chunk matching a keyword search
+#[derive(Debug, Clone)]
+pub struct KeywordHit {
+    pub video_id: String,
+    /// The chunk's `feedback::chunk_key`
+    pub chunk_key: String,
+    /// BM25 score
+    pub score: f32,
+}
+
+/// Full-text index of transcript chunks (tantivy, in `keyword_index/` in the data directory),
+/// one document per chunk, for BM25 scores over exact terms such as product names and error
+/// codes that embeddings blur
+pub struct KeywordIndex {
+    index: Index,
+    video_id: Field,
+    chunk_key: Field,
+    text: Field,
+}
+
+impl KeywordIndex {
+    /// Open (or create) the index in `keyword_index/` under `data_dir`
+    pub fn open(data_dir: &Path) -> Result<Self> {
+        let dir = data_dir.join("keyword_index");
+        fs::create_dir_all(&dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+        let mut schema = Schema::builder();
+        schema.add_text_field("video_id", STRING | STORED);
+        schema.add_text_field("chunk_key", STRING | STORED);
+        schema.add_text_field("text", TEXT);
+        let index = MmapDirectory::open(&dir)
+            .map_err(anyhow::Error::from)
+            .and_then(|directory| Ok(Index::open_or_create(directory, schema.build())?))
+            .with_context(|| format!("Failed to open the keyword index in {}", dir.display()))?;
+
+        let schema = index.schema();
+        Ok(Self {
+            video_id: schema.get_field("video_id")?,
+            chunk_key: schema.get_field("chunk_key")?,
+            text: schema.get_field("text")?,
+            index,
+        })
+    }
+
+    /// Replace a video's documents with the given chunks
+    pub fn replace_video(&self, video_id: &str, chunks: &[&Chunk]) -> Result<()> {
+        let mut writer = self.writer()?;
+        writer.delete_term(Term::from_field_text(self.video_id, video_id));
+        for chunk in chunks {
+            let mut document = TantivyDocument::default();
+            document.add_text(self.video_id, video_id);
+            document.add_text(self.chunk_key, chunk_key(chunk));
+            document.add_text(self.text, &chunk.text);
+            writer.add_document(document)?;
+        }
+        writer.commit().context("Failed to update the keyword index")?;
+        Ok(())
+    }
+
+    /// Forget a video's documents
+    pub fn delete_video(&self, video_id: &str) -> Result<()> {
+        let mut writer = self.writer()?;
+        writer.delete_term(Term::from_field_text(self.video_id, video_id));
+        writer.commit().context("Failed to update the keyword index")?;
+        Ok(())
+    }
+
+    /// Keys of the chunks of a video in the index
+    pub fn video_keys(&self, video_id: &str) -> Result<HashSet<String>> {
+        let searcher = self.index.reader()?.searcher();
+        let documents = searcher.search(&self.video_filter(&[video_id]), &DocSetCollector)?;
+        let mut keys = HashSet::new();
+        for address in documents {
+            let document: TantivyDocument = searcher.doc(address)?;
+            if let Some(key) = document.get_first(self.chunk_key).and_then(|value| value.as_str()) {
+                keys.insert(key.to_string());
+            }
+        }
+        Ok(keys)
+    }
+
+    /// The `limit` chunks of the given videos that best match `query`, best first. Query syntax
+    /// the parser cannot read is dropped rather than rejected.
+    pub fn search(&self, query: &str, video_ids: &[&str], limit: usize) -> Result<Vec<KeywordHit>> {
+        let (terms, _) = QueryParser::for_index(&self.index, vec![self.text]).parse_query_lenient(query);
+        // The filter scores nothing, so hits keep their plain BM25 scores
+        let query = BooleanQuery::new(vec![
+            (Occur::Must, terms),
+            (Occur::Must, Box::new(ConstScoreQuery::new(Box::new(self.video_filter(video_ids)), 0.0))),
+        ]);
+
+        let searcher = self.index.reader()?.searcher();
+        let mut hits = Vec::new();
+        for (score, address) in searcher.search(&query, &TopDocs::with_limit(limit.max(1)))? {
+            let document: TantivyDocument = searcher.doc(address)?;
+            let field = |field: Field| {
+                document
+                    .get_first(field)
+                    .and_then(|value| value.as_str())
+                    .unwrap_or_default()
+                    .to_string()
+            };
+            hits.push(KeywordHit {
+                video_id: field(self.video_id),
+                chunk_key: field(self.chunk_key),
+                score,
+            });
+        }
+        Ok(hits)
+    }
+
+    /// Documents belonging to any of the given videos
+    fn video_filter(&self, video_ids: &[&str]) -> BooleanQuery {
+        BooleanQuery::new(
+            video_ids
+                .iter()
+                .map(|video_id| -> (Occur, Box<dyn Query>) {
+                    let term = Term::from_field_text(self.video_id, video_id);
+                    (Occur::Should, Box::new(TermQuery::new(term, IndexRecordOption::Basic)))
+                })
+                .collect(),
+        )
+    }
+
+    fn writer(&self) -> Result<IndexWriter> {
+        self.index
+            .writer(WRITER_MEMORY_BYTES)
+            .context("Failed to open the keyword index for writing (is another indexing run going?)")
+    }
+}
+
+impl VideoTranscriber {
+    /// Make sure the keyword index holds exactly the given chunks for each of their videos,
+    /// re-indexing any video whose chunks changed
+    pub fn index_keywords(&self, index: &KeywordIndex, chunks: &[Chunk]) -> Result<()> {
+        let mut by_video: HashMap<&str, Vec<&Chunk>> = HashMap::new();
+        for chunk in chunks {
+            by_video.entry(&chunk.video_id).or_default().push(chunk);
+        }
+        for (video_id, chunks) in by_video {
+            let current: HashSet<String> = chunks.iter().map(|chunk| chunk_key(chunk)).collect();
+            if index.video_keys(video_id)? != current {
+                index.replace_video(video_id, &chunks)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// BM25 score of each chunk for `query` from the keyword index, in chunk order; `None`
+    /// for chunks that do not match. Chunks not indexed yet are indexed first.
+    pub fn keyword_scores(&self, query: &str, chunks: &[Chunk]) -> Result<Vec<Option<f32>>> {
+        let index = KeywordIndex::open(&self.data_dir)?;
+        self.index_keywords(&index, chunks)?;
+        let videos: HashSet<&str> = chunks.iter().map(|chunk| chunk.video_id.as_str()).collect();
+        let scores: HashMap<(String, String), f32> = index
+            .search(query, &videos.into_iter().collect::<Vec<_>>(), chunks.len())?
+            .into_iter()
+            .map(|hit| ((hit.video_id, hit.chunk_key), hit.score))
+            .collect();
+        Ok(chunks
+            .iter()
+            .map(|chunk| scores.get(&(chunk.video_id.clone(), chunk_key(chunk))).copied())
+            .collect())
+    }
+
+    /// Prepare a transcript's chunks for hybrid retrieval: add them to the keyword index and
+    /// embed them. Does nothing without a Gemini key or with embedding retrieval off, since
+    /// keyword-only ranking needs no index.
+    pub fn index_chunks(&self, transcript: &Transcript) -> Result<()> {
+        if self.gemini_api_key.is_empty() || !self.retrieval_params().embeddings {
+            return Ok(());
+        }
+        if self.retrieval_params().hybrid {
+            let (_, chunks) = self.transcript_chunks(transcript)?;
+            self.index_keywords(&KeywordIndex::open(&self.data_dir)?, &chunks)?;
+        }
+        self.embed_chunks(transcript)
+    }
+}
@@ -0,0 +1,160 @@
+use clap::ValueEnum;
+use serde::Serialize;
+
+use crate::debuglog;
+use crate::grep::GrepHit;
+use crate::schema::Answer;
+use crate::transcript::{format_timestamp, Transcript};
+
+/// How answers and search results are printed on stdout
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// Human-readable text
+    #[default]
+    Text,
+    /// Single-line results for Raycast script commands (any output mode)
+    Raycast,
+    /// Script Filter JSON, as read by Alfred and compatible launchers
+    Launcher,
+    /// Pretty-printed JSON for scripts, with retrieval scores and timings where there are any
+    Json,
+}
+
+/// Script Filter output: `{"items": [...]}`
+#[derive(Serialize)]
+struct Items {
+    items: Vec<Item>,
+}
+
+#[derive(Serialize)]
+struct Item {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    uid: Option<String>,
+    title: String,
+    subtitle: String,
+    /// Passed on when the item is actioned: a link to open, or the text to copy
+    arg: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    quicklookurl: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    text: Option<ItemText>,
+    valid: bool,
+}
+
+/// Text for the launcher's copy and large-type actions
+#[derive(Serialize)]
+struct ItemText {
+    copy: String,
+    largetype: String,
+}
+
+fn render_json(value: &serde_json::Value) -> String {
+    format!("{}\n", serde_json::to_string_pretty(value).unwrap_or_default())
+}
+
+fn render_items(items: Vec<Item>) -> String {
+    format!("{}\n", serde_json::to_string(&Items { items }).unwrap_or_default())
+}
+
+/// Whitespace collapsed so a paragraph fits on one launcher line
+fn one_line(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// An answer for a launcher; empty for `Text` output, which is left to the caller
+pub fn render_answer(format: OutputFormat, answer: &str, question: &str, url: Option<&str>) -> String {
+    match format {
+        OutputFormat::Text => String::new(),
+        OutputFormat::Raycast => format!("{}\n", one_line(answer)),
+        OutputFormat::Launcher => render_items(vec![Item {
+            uid: None,
+            title: one_line(answer),
+            subtitle: question.to_string(),
+            arg: answer.to_string(),
+            quicklookurl: url.map(str::to_string),
+            text: Some(ItemText {
+                copy: answer.to_string(),
+                largetype: answer.to_string(),
+            }),
+            valid: true,
+        }]),
+        OutputFormat::Json => {
+            let mut output = Answer::new(question, answer);
+            output.url = url.map(str::to_string);
+            render_json(&serde_json::to_value(output).unwrap_or_default())
+        }
+    }
+}
+
+/// Search hits across videos for a launcher: one line or item per hit, opening the video at
+/// that moment; empty for `Text` output
+pub fn render_hits(format: OutputFormat, results: &[(&Transcript, Vec<GrepHit>)]) -> String {
+    let hits = results
+        .iter()
+        .flat_map(|(transcript, hits)| hits.iter().map(move |hit| (*transcript, hit)));
+    match format {
+        OutputFormat::Text => String::new(),
+        OutputFormat::Raycast => hits
+            .map(|(transcript, hit)| match hit.seconds {
+                Some(seconds) => format!(
+                    "{} [{}] {} {}\n",
+                    transcript.label(),
+                    format_timestamp(seconds),
+                    one_line(&hit.snippet),
+                    transcript.link_at(seconds)
+                ),
+                None => format!("{} {} {}\n", transcript.label(), one_line(&hit.snippet), transcript.url),
+            })
+            .collect(),
+        OutputFormat::Launcher => render_items(
+            hits.map(|(transcript, hit)| {
+                let (link, at) = match hit.seconds {
+                    Some(seconds) => (transcript.link_at(seconds), format!(" · {}", format_timestamp(seconds))),
+                    None => (transcript.url.clone(), String::new()),
+                };
+                Item {
+                    uid: Some(format!("{}:{}", transcript.video_id, hit.seconds.unwrap_or(0.0) as u64)),
+                    title: one_line(&hit.snippet),
+                    subtitle: format!("{}{}", transcript.label(), at),
+                    arg: link.clone(),
+                    quicklookurl: Some(link),
+                    text: None,
+                    valid: true,
+                }
+            })
+            .collect(),
+        ),
+        OutputFormat::Json => render_json(&serde_json::Value::Array(
+            hits.map(|(transcript, hit)| {
+                serde_json::json!({
+                    "video_id": transcript.video_id,
+                    "title": transcript.label(),
+                    "start": hit.seconds,
+                    "link": hit.seconds.map_or_else(|| transcript.url.clone(), |seconds| transcript.link_at(seconds)),
+                    "snippet": hit.snippet,
+                })
+            })
+            .collect(),
+        )),
+    }
+}
+
+/// A failure as the launcher will show it, since launchers do not display stderr; empty for
+/// `Text` output
+pub fn render_error(format: OutputFormat, error: &anyhow::Error) -> String {
+    let message = debuglog::scrub(&format!("{:#}", error));
+    match format {
+        OutputFormat::Text => String::new(),
+        OutputFormat::Raycast => format!("Error: {}\n", one_line(&message)),
+        OutputFormat::Launcher => render_items(vec![Item {
+            uid: None,
+            title: format!("Error: {}", debuglog::scrub(&error.to_string())),
+            subtitle: one_line(&message),
+            arg: String::new(),
+            quicklookurl: None,
+            text: None,
+            valid: false,
+        }]),
+        OutputFormat::Json => render_json(&serde_json::json!({ "error": message })),
+    }
+}
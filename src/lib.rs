@@ -0,0 +1,275 @@
+//! Transcribe YouTube videos and local recordings and ask questions about them with Groq or Gemini.
+//!
+//! The `claude-video-transcribe` binary is built on this crate. The entry point is
+//! [`VideoTranscriber`], configured from the environment by [`VideoTranscriber::new`] or in
+//! code with [`VideoTranscriber::builder`]; the main building blocks are [`transcript`] (the
+//! transcript model and caption parsers), [`apify`] (fetching transcripts), [`gemini`] (file
+//! uploads and generation), and [`index`] (cached lookup, indexing, and querying).
+//!
+//! Functions return [`anyhow::Result`]. Failures callers are likely to handle are typed: find
+//! them with [`error::kind`] (or [`cancel::cancelled`] for Ctrl-C and timeouts).
+//!
+//! [`VideoTranscriber::with_deadline`] and [`VideoTranscriber::with_cancellation`] scope a
+//! timeout or cancellation token to one operation, and [`VideoTranscriber::with_progress`] or
+//! [`VideoTranscriber::progress_channel`] report what it is doing as [`progress::Progress`] events.
+//! The human-readable status lines the CLI prints are not written anywhere unless a handler
+//! is set with [`progress::set_status_handler`].
+//! A [`hooks::Subscriber`] sees each finished stage and its cost, and may veto it.
+//!
+//! Results that leave the process (`--format json`, the HTTP API) use the types in [`schema`],
+//! which only change compatibly within a major version.
+//!
+//! All network calls are blocking and there is no async runtime; concurrent work runs on
+//! threads via [`parallel::map_bounded`], so the API can be called from any thread, or from
+//! `tokio::task::spawn_blocking` in an async service.
+
+use anyhow::Result;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Send a status line to the handler set with `progress::set_status_handler`; the CLI prints
+/// them to stderr so stdout stays clean for piping, and the library is silent without one
+#[macro_export]
+macro_rules! status {
+    ($($arg:tt)*) => {
+        $crate::progress::status(format_args!($($arg)*))
+    };
+}
+
+pub mod access;
+pub mod agent;
+pub mod apify;
+pub mod archive;
+pub mod audio;
+pub mod batch;
+pub mod bench;
+pub mod bugreport;
+pub mod builder;
+pub mod cache;
+pub mod captions;
+pub mod cancel;
+pub mod chapters;
+pub mod chat;
+pub mod chunking;
+pub mod cite;
+pub mod companion;
+pub mod config;
+pub mod consensus;
+pub mod context_cache;
+pub mod contradictions;
+pub mod conversation;
+#[cfg(unix)]
+pub mod daemon;
+pub mod debate;
+pub mod debuglog;
+pub mod delete;
+pub mod embeddings;
+pub mod ensemble;
+pub mod error;
+pub mod eval;
+pub mod export;
+pub mod extension;
+pub mod feedback;
+pub mod finance;
+pub mod flashcards;
+pub mod gemini;
+pub mod grep;
+pub mod guardrail;
+pub mod hnsw;
+pub mod hooks;
+pub mod http;
+pub mod index;
+pub mod ingest;
+pub mod instructions;
+pub mod jobs;
+pub mod keyword_index;
+pub mod launcher;
+pub mod library;
+pub mod listing;
+pub mod llm;
+pub mod meetings;
+pub mod mentions;
+pub mod moderation;
+pub mod notes;
+pub mod organize;
+pub mod parallel;
+pub mod parts;
+pub mod persona;
+pub mod progress;
+mod prompts;
+pub mod pipeline;
+pub mod playlist;
+pub mod quality;
+pub mod quiz;
+pub mod quotes;
+pub mod rate_limit;
+pub mod registry;
+pub mod related;
+pub mod report;
+pub mod retrieval;
+pub mod schema;
+pub mod second_opinion;
+pub mod server;
+pub mod simd;
+pub mod slides;
+pub mod sources;
+pub mod store;
+pub mod strategy;
+pub mod style;
+pub mod summary;
+pub mod telemetry;
+pub mod timestamps;
+pub mod transcript;
+pub mod translate;
+pub mod trends;
+pub mod vectorstore;
+pub mod watch;
+pub mod whisper;
+pub mod youtube;
+
+use cache::TranscriptCache;
+use cancel::CancellationToken;
+use config::Config;
+use hooks::Subscriber;
+use http::Endpoints;
+pub use error::Error;
+use llm::LlmProvider;
+use progress::ProgressFn;
+use sources::{SourceKind, TranscriptSource};
+use rate_limit::Tier;
+use strategy::Strategy;
+use store::IndexStore;
+use style::AnswerStyle;
+
+/// Model used for Groq answers unless overridden
+pub const DEFAULT_GROQ_MODEL: &str = "llama-3.3-70b-versatile";
+/// Model used for Gemini answers unless overridden
+pub const DEFAULT_GEMINI_MODEL: &str = "gemini-1.5-flash";
+
+/// System prompt for answering questions about a transcript
+pub const ANSWER_SYSTEM_PROMPT: &str =
+    "You are a helpful assistant that answers questions about YouTube video transcripts accurately and concisely.";
+
+/// Fetches, caches, indexes, and answers questions about video transcripts.
+///
+/// Every command of the CLI is a method on this type, spread over the modules that implement it.
+/// Clones share the caches, registry, and HTTP connection pool, so `with_deadline`,
+/// `with_cancellation`, and `with_progress` are cheap ways to scope one operation.
+#[derive(Clone)]
+pub struct VideoTranscriber {
+    apify_api_key: String,
+    gemini_api_key: String,
+    groq_api_key: String,
+    llm_provider: LlmProvider,
+    groq_model: String,
+    gemini_model: String,
+    client: reqwest::blocking::Client,
+    /// Where API requests are sent; see `http::Endpoints`
+    endpoints: Endpoints,
+    /// Time between Apify run status checks
+    apify_poll_interval: Duration,
+    /// When Apify credits were last checked before a run, shared by clones
+    credits_checked_at: Arc<Mutex<Option<Instant>>>,
+    cancel: CancellationToken,
+    /// Where single-video transcripts are fetched from
+    source: Arc<dyn TranscriptSource>,
+    pub cache: TranscriptCache,
+    /// The index: uploads, library records, sessions, and chunk embeddings
+    pub registry: Arc<dyn IndexStore>,
+    pub config: Config,
+    /// Where feedback, instructions, categories, search indexes, and job and usage records are
+    /// kept; see `data_dir`
+    data_dir: PathBuf,
+    /// Read settings only from `config`, never from environment variables
+    ignore_environment: bool,
+    /// Shape answers must take (`--style`); free-form when unset
+    pub answer_style: Option<AnswerStyle>,
+    /// Longest answer in words (`--max-words`); unlimited when unset
+    pub max_words: Option<usize>,
+    /// Language answers are written in (`--answer-lang`), as a code like `es` or a name; the
+    /// model's choice, usually the question's language, when unset
+    pub answer_language: Option<String>,
+    /// How questions about one video are put to the model (`--strategy`); the configured
+    /// strategy when unset
+    pub strategy: Option<Strategy>,
+    /// Gemini quota tier (`--tier`); the configured tier when unset
+    pub tier: Option<Tier>,
+    /// Receives progress events; see `with_progress`
+    progress: Option<ProgressFn>,
+    /// Observe and may veto pipeline stages; see `subscribe`
+    subscribers: Vec<Arc<dyn Subscriber>>,
+}
+
+impl VideoTranscriber {
+    /// Read keys and settings from the environment, `.env`, and the config file. Use
+    /// `builder` to set them in code instead.
+    pub fn new(cancel: CancellationToken) -> Result<Self> {
+        Self::builder().cancel_token(cancel).build()
+    }
+
+    /// Like `new`, with the transcript backend chosen by the caller rather than by
+    /// `TRANSCRIPT_SOURCE` or the config file
+    pub fn with_source(cancel: CancellationToken, source: Option<SourceKind>) -> Result<Self> {
+        let builder = Self::builder().cancel_token(cancel);
+        match source {
+            Some(source) => builder.source(source).build(),
+            None => builder.build(),
+        }
+    }
+
+    /// Like `new`, with single-video transcripts fetched by a backend of the caller's own,
+    /// such as an in-house captioning service. Apify is then only needed for playlists,
+    /// channels, and `runs`.
+    pub fn with_transcript_source(cancel: CancellationToken, source: impl TranscriptSource + 'static) -> Result<Self> {
+        Self::builder().cancel_token(cancel).transcript_source(source).build()
+    }
+
+    /// The backend single-video transcripts are fetched with
+    pub fn transcript_source(&self) -> &dyn TranscriptSource {
+        self.source.as_ref()
+    }
+
+    /// The HTTP client shared by every API call, for custom transcript sources to reuse
+    pub fn http_client(&self) -> &reqwest::blocking::Client {
+        &self.client
+    }
+
+    /// The directory the transcriber keeps its own state in: the `store_dir` it was built
+    /// with, else the platform data directory (`CVT_DATA_DIR` overrides it)
+    pub fn data_dir(&self) -> &Path {
+        &self.data_dir
+    }
+
+    /// The token that Ctrl-C and `--timeout` cancel; pass it to `cancel::SendCancellable::send_with`
+    pub fn cancel_token(&self) -> &CancellationToken {
+        &self.cancel
+    }
+
+    /// A transcriber whose calls stop when `token` is cancelled, e.g. from another thread
+    pub fn with_cancellation(&self, token: CancellationToken) -> Self {
+        Self {
+            cancel: token,
+            ..self.clone()
+        }
+    }
+
+    /// A transcriber whose calls fail with `Cancelled::TimedOut` once `timeout` has passed,
+    /// and still stop on anything that cancels this one
+    pub fn with_deadline(&self, timeout: Duration) -> Self {
+        self.with_cancellation(self.cancel.child(Some(timeout)))
+    }
+
+    /// Extract video ID from YouTube URL
+    pub fn extract_video_id(&self, url: &str) -> Result<String> {
+        // Recordings added with `ingest-dir`
+        if let Some(path) = url.strip_prefix("file://") {
+            return Ok(ingest::local_video_id(std::path::Path::new(path)));
+        }
+
+        match youtube::video_id_from_url(url) {
+            Some(id) => Ok(id.to_string()),
+            None => Err(Error::InvalidUrl(url.to_string()).into()),
+        }
+    }
+}
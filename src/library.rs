@@ -0,0 +1,229 @@
+use anyhow::Result;
+use std::collections::HashMap;
+
+use crate::feedback::AnswerContext;
+use crate::guardrail::fence;
+use crate::organize::Categories;
+use crate::parallel::map_bounded;
+use crate::playlist::playlist_id;
+use crate::retrieval::{bm25_rank, chunk_transcript, numbered_context, rerank_phrases, Chunk};
+use crate::transcript::Transcript;
+use crate::VideoTranscriber;
+
+/// Character budget for the combined transcript context in whole-library questions
+const LIBRARY_CONTEXT_CHARS: usize = 120_000;
+/// Excerpts a cross-video answer is grounded in, and the most taken from any one video
+const CROSS_VIDEO_EXCERPTS: usize = 12;
+const CROSS_VIDEO_PER_VIDEO: usize = 4;
+
+const CROSS_VIDEO_SYSTEM_PROMPT: &str =
+    "You answer questions from numbered transcript excerpts of several videos. Attribute every point \
+     to the video it comes from by name, cite the excerpts you rely on as [n], and say where the \
+     videos agree or differ. If the excerpts do not contain the answer, say so.";
+
+/// One video's answer in a per-video fan-out
+pub struct VideoAnswer {
+    pub title: String,
+    pub channel: String,
+    pub answer: Result<String>,
+}
+
+impl VideoTranscriber {
+    /// Ask the same question of every cached video independently, in parallel
+    pub fn ask_library_per_video(
+        &self,
+        question: &str,
+        concurrency: usize,
+    ) -> Result<Vec<VideoAnswer>> {
+        let transcripts = self.library_transcripts()?;
+        status!(
+            "🔀 Asking {} video(s) in parallel (concurrency {})...",
+            transcripts.len(),
+            concurrency
+        );
+
+        let answers = map_bounded(&transcripts, concurrency, |transcript| {
+            let prompt = format!(
+                "Answer the question below using only this video transcript. Reply in at most two sentences. \
+                 If the transcript does not address the question, reply exactly \"Not covered.\"\n\n\
+                 Question: {}\n\nTranscript:\n{}",
+                question,
+                fence(&transcript.text)
+            );
+
+            VideoAnswer {
+                title: transcript.label().to_string(),
+                channel: transcript.channel.clone().unwrap_or_default(),
+                answer: self.complete(crate::ANSWER_SYSTEM_PROMPT, &prompt),
+            }
+        });
+
+        Ok(answers)
+    }
+
+    /// Ask one question grounded in every cached video at once
+    pub fn ask_library_combined(&self, question: &str) -> Result<String> {
+        let prompt = self.library_prompt(question)?;
+        self.complete(crate::ANSWER_SYSTEM_PROMPT, &prompt)
+    }
+
+    /// The prompt for a question across the library: a share of every transcript
+    pub fn library_prompt(&self, question: &str) -> Result<String> {
+        let transcripts = self.library_transcripts()?;
+        let per_video_budget = LIBRARY_CONTEXT_CHARS / transcripts.len();
+
+        let mut context = String::new();
+        for transcript in &transcripts {
+            let excerpt: String = transcript.text.chars().take(per_video_budget).collect();
+            context.push_str(&format!(
+                "=== Video: {} ({}) ===\n{}\n\n",
+                transcript.label(),
+                transcript.channel.as_deref().unwrap_or("unknown channel"),
+                fence(&excerpt)
+            ));
+        }
+
+        status!("🤔 Asking across {} video(s): \"{}\"", transcripts.len(), question);
+        Ok(self.styled_prompt(format!(
+            "Answer the question using the video transcripts below. Attribute each point to the video it came from.\n\n\
+             Question: {}\n\n{}",
+            question, context
+        )))
+    }
+
+    /// The best excerpts for a question from every video in a collection (or the whole library),
+    /// with no one video crowding out the others
+    pub fn cross_video_context(&self, question: &str, collection: Option<&str>) -> Result<AnswerContext> {
+        let transcripts = self.collection_transcripts(collection)?;
+        let params = self.retrieval_params();
+        let chunks: Vec<Chunk> = transcripts
+            .iter()
+            .flat_map(|t| chunk_transcript(t, params.chunk_chars, params.chunk_overlap))
+            .collect();
+        let mut ranked = bm25_rank(question, &chunks, chunks.len());
+        if params.rerank {
+            rerank_phrases(question, &chunks, &mut ranked);
+        }
+
+        let mut per_video: HashMap<&str, usize> = HashMap::new();
+        let mut hits: Vec<&Chunk> = Vec::new();
+        for (i, _) in ranked {
+            let taken = per_video.entry(chunks[i].video_id.as_str()).or_default();
+            if *taken < CROSS_VIDEO_PER_VIDEO {
+                *taken += 1;
+                hits.push(&chunks[i]);
+            }
+            if hits.len() == CROSS_VIDEO_EXCERPTS {
+                break;
+            }
+        }
+        if hits.is_empty() {
+            anyhow::bail!("No excerpt in {} video(s) matches the question", transcripts.len());
+        }
+        // Keep each video's excerpts together, videos in order of their best excerpt
+        let order: Vec<&str> = hits.iter().fold(Vec::new(), |mut order, hit| {
+            if !order.contains(&hit.video_id.as_str()) {
+                order.push(&hit.video_id);
+            }
+            order
+        });
+        hits.sort_by_key(|hit| order.iter().position(|id| *id == hit.video_id));
+        status!(
+            "🔎 Answering from {} excerpt(s) across {} of {} video(s)",
+            hits.len(),
+            order.len(),
+            transcripts.len()
+        );
+
+        let prompt = self.styled_prompt(format!(
+            "Excerpts from {} videos:\n{}\n\nQuestion: {}",
+            order.len(),
+            numbered_context(&hits),
+            question
+        ));
+        Ok(AnswerContext {
+            system: CROSS_VIDEO_SYSTEM_PROMPT,
+            prompt,
+            excerpts: hits.into_iter().cloned().collect(),
+            retrieval: None,
+        })
+    }
+
+    /// Answer one question grounded in excerpts from many videos, attributed per video
+    pub fn ask_across(&self, question: &str, collection: Option<&str>) -> Result<String> {
+        let context = self.cross_video_context(question, collection)?;
+        Ok(context.with_sources(self.complete(context.system, &context.prompt)?))
+    }
+
+    /// Cached transcripts in a collection, or the whole library. A collection is a category
+    /// from `organize` (by path such as `Programming/Rust`, sub-categories included, or by any
+    /// one level of it such as `rust`) or an indexed playlist's URL or ID.
+    pub fn collection_transcripts(&self, collection: Option<&str>) -> Result<Vec<Transcript>> {
+        let Some(collection) = collection else {
+            return self.library_transcripts();
+        };
+        let transcripts = self.cache.list()?;
+
+        let categories = Categories::load(&self.data_dir)?;
+        let matching: Vec<&Vec<String>> = categories
+            .categories
+            .iter()
+            .filter(|category| {
+                category.path == collection
+                    || category.path.starts_with(&format!("{}/", collection))
+                    || category.path.split('/').any(|level| level.eq_ignore_ascii_case(collection))
+            })
+            .map(|category| &category.video_ids)
+            .collect();
+        let in_collection: Vec<Transcript> = if !matching.is_empty() {
+            transcripts
+                .into_iter()
+                .filter(|transcript| matching.iter().any(|ids| ids.contains(&transcript.video_id)))
+                .collect()
+        } else if let Some(video_ids) = self.registry.playlist_videos(playlist_id(collection))? {
+            transcripts
+                .into_iter()
+                .filter(|transcript| video_ids.contains(&transcript.video_id))
+                .collect()
+        } else {
+            anyhow::bail!(
+                "No collection or indexed playlist named \"{}\"; see `organize --show` and `list --playlists`",
+                collection
+            );
+        };
+        if in_collection.is_empty() {
+            anyhow::bail!("No cached transcripts in \"{}\"", collection);
+        }
+        Ok(in_collection)
+    }
+
+    fn library_transcripts(&self) -> Result<Vec<Transcript>> {
+        let transcripts = self.cache.list()?;
+        if transcripts.is_empty() {
+            anyhow::bail!("The library is empty. Index some videos first with `index`.");
+        }
+        Ok(transcripts)
+    }
+}
+
+/// Render per-video answers as a Markdown comparison table
+pub fn comparison_table(answers: &[VideoAnswer]) -> String {
+    let mut table = String::from("| Video | Channel | Answer |\n|---|---|---|\n");
+    for answer in answers {
+        let text = match &answer.answer {
+            Ok(text) => text.clone(),
+            Err(e) => format!("⚠️ {}", e),
+        };
+        table.push_str(&format!(
+            "| {} | {} | {} |\n",
+            table_cell(&answer.title),
+            table_cell(&answer.channel),
+            table_cell(&text)
+        ));
+    }
+    table
+}
+
+fn table_cell(text: &str) -> String {
+    text.trim().replace('|', "\\|").replace('\n', " ")
+}
@@ -0,0 +1,210 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+use crate::debuglog;
+use crate::parallel::map_bounded;
+use crate::playlist::playlist_id;
+use crate::registry::{PlaylistRecord, VideoRecord};
+use crate::transcript::format_timestamp;
+use crate::youtube::VideoMetadata;
+use crate::VideoTranscriber;
+
+/// Videos whose files are checked at the same time
+const CHECK_CONCURRENCY: usize = 4;
+/// Lengths within this many seconds of each other are the same cut of a video
+const DURATION_TOLERANCE_SECS: f64 = 2.0;
+
+/// An indexed video and the combined state of its Gemini files
+#[derive(Serialize)]
+pub struct ListedVideo {
+    #[serde(flatten)]
+    pub video: VideoRecord,
+    pub parts: usize,
+    /// `ACTIVE` when every part is, otherwise the first other state: `PROCESSING`, `FAILED`,
+    /// `MISSING` (deleted or expired), `NOT UPLOADED`, or `UNKNOWN` when not checked
+    pub file_state: String,
+    /// How the video changed on YouTube since it was indexed, when it looks re-uploaded or edited
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stale: Option<String>,
+}
+
+/// How a video changed between indexing (its length and publish date then) and `now`, or
+/// `None` when nothing that can be compared differs
+pub fn staleness(
+    duration_secs: Option<f64>,
+    published_at: Option<DateTime<Utc>>,
+    now: &VideoMetadata,
+) -> Option<String> {
+    let mut changes = Vec::new();
+    if let (Some(then), Some(now)) = (duration_secs, now.duration_secs) {
+        if (then - now).abs() > DURATION_TOLERANCE_SECS {
+            changes.push(format!("length {} → {}", format_timestamp(then), format_timestamp(now)));
+        }
+    }
+    if let (Some(then), Some(now)) = (published_at, now.published_at) {
+        if then.date_naive() != now.date_naive() {
+            changes.push(format!("published {} → {}", then.format("%Y-%m-%d"), now.format("%Y-%m-%d")));
+        }
+    }
+    (!changes.is_empty()).then(|| changes.join(", "))
+}
+
+impl VideoTranscriber {
+    /// State of one uploaded Gemini file; `MISSING` once Gemini has deleted it
+    pub fn gemini_file_state(&self, file_uri: &str) -> Result<String> {
+        Ok(self
+            .gemini_file(file_uri)?
+            .map_or_else(|| "MISSING".to_string(), |file| file.state))
+    }
+
+    /// Every indexed video from the registry, with its files' state and whether it changed on
+    /// YouTube checked when `check` is set
+    pub fn list_videos(&self, check: bool) -> Result<Vec<ListedVideo>> {
+        self.list_records(self.registry.videos()?, check)
+    }
+
+    /// The indexed videos of a playlist (by URL or ID), in playlist order
+    pub fn list_playlist(&self, playlist: &str, check: bool) -> Result<Vec<ListedVideo>> {
+        let playlist_id = playlist_id(playlist);
+        let Some(video_ids) = self.registry.playlist_videos(playlist_id)? else {
+            anyhow::bail!("No playlist {} has been indexed; index it with `index --playlist`", playlist_id);
+        };
+        let mut videos = self.registry.videos()?;
+        videos.retain(|video| video_ids.contains(&video.video_id));
+        videos.sort_by_key(|video| video_ids.iter().position(|id| *id == video.video_id));
+        self.list_records(videos, check)
+    }
+
+    fn list_records(&self, videos: Vec<VideoRecord>, check: bool) -> Result<Vec<ListedVideo>> {
+        let check_files = check && !self.gemini_api_key.is_empty();
+        if check && !videos.is_empty() {
+            if check_files {
+                status!("🔎 Checking Gemini files and YouTube for {} video(s)...", videos.len());
+            } else {
+                status!("🔎 Checking YouTube for {} video(s)...", videos.len());
+            }
+        }
+
+        let listed = map_bounded(&videos, CHECK_CONCURRENCY, |video| {
+            let parts = self.registry.parts(&video.video_id)?;
+            let file_state = if parts.is_empty() {
+                "NOT UPLOADED".to_string()
+            } else if !check_files {
+                "UNKNOWN".to_string()
+            } else {
+                let mut state = "ACTIVE".to_string();
+                for part in &parts {
+                    let part_state = self.gemini_file_state(&part.file_uri).unwrap_or_else(|e| {
+                        status!(
+                            "⚠️  Could not check {}: {}",
+                            video.video_id,
+                            debuglog::scrub(&format!("{:#}", e))
+                        );
+                        "UNKNOWN".to_string()
+                    });
+                    if part_state != "ACTIVE" {
+                        state = part_state;
+                        break;
+                    }
+                }
+                state
+            };
+            let mut video = video.clone();
+            // Videos indexed before metadata was recorded take it from the transcript cache
+            if video.title.is_none() || video.duration_secs.is_none() || video.published_at.is_none() {
+                if let Ok(Some(transcript)) = self.cache.get(&video.video_id) {
+                    if video.title.is_none() {
+                        video.title = transcript.title;
+                        video.channel = transcript.channel;
+                    }
+                    video.duration_secs = video.duration_secs.or(transcript.duration_secs);
+                    video.published_at = video.published_at.or(transcript.published_at);
+                }
+            }
+            let stale = if check { self.check_staleness(&video) } else { None };
+            Ok(ListedVideo {
+                video,
+                parts: parts.len(),
+                file_state,
+                stale,
+            })
+        });
+        listed.into_iter().collect()
+    }
+
+    /// Whether a YouTube video changed since it was indexed; local recordings and videos with
+    /// nothing recorded to compare are never stale
+    fn check_staleness(&self, video: &VideoRecord) -> Option<String> {
+        if video.url.starts_with("file://") || (video.duration_secs.is_none() && video.published_at.is_none()) {
+            return None;
+        }
+        match self.video_metadata(&video.video_id) {
+            Ok(now) => staleness(video.duration_secs, video.published_at, &now),
+            Err(e) => {
+                status!(
+                    "⚠️  Could not check {} on YouTube: {}",
+                    video.video_id,
+                    debuglog::scrub(&format!("{:#}", e))
+                );
+                None
+            }
+        }
+    }
+}
+
+/// Aligned table of indexed videos for `list`
+pub fn render_list(videos: &[ListedVideo]) -> String {
+    let mut out = format!(
+        "{:<40} {:<24} {:<13} {:<17} {}\n",
+        "TITLE", "CHANNEL", "VIDEO ID", "INDEXED", "GEMINI FILE"
+    );
+    for listed in videos {
+        let video = &listed.video;
+        let mut state = if listed.parts > 1 {
+            format!("{} ({} parts)", listed.file_state, listed.parts)
+        } else {
+            listed.file_state.clone()
+        };
+        if video.private {
+            state.push_str("  🔒 PRIVATE");
+        }
+        if let Some(change) = &listed.stale {
+            state.push_str(&format!("  ⚠️ STALE: {}", change));
+        }
+        out.push_str(&format!(
+            "{:<40} {:<24} {:<13} {:<17} {}\n",
+            truncate(video.title.as_deref().unwrap_or("-"), 40),
+            truncate(video.channel.as_deref().unwrap_or("-"), 24),
+            video.video_id,
+            video.indexed_at.format("%Y-%m-%d %H:%M"),
+            state
+        ));
+    }
+    out
+}
+
+/// Aligned table of indexed playlists for `list --playlists`
+pub fn render_playlists(playlists: &[(PlaylistRecord, usize)]) -> String {
+    let mut out = format!("{:<40} {:<24} {:<36} {:<7} {}\n", "TITLE", "CHANNEL", "PLAYLIST ID", "VIDEOS", "INDEXED");
+    for (playlist, videos) in playlists {
+        out.push_str(&format!(
+            "{:<40} {:<24} {:<36} {:<7} {}\n",
+            truncate(playlist.title.as_deref().unwrap_or("-"), 40),
+            truncate(playlist.channel.as_deref().unwrap_or("-"), 24),
+            playlist.playlist_id,
+            videos,
+            playlist.indexed_at.format("%Y-%m-%d %H:%M")
+        ));
+    }
+    out
+}
+
+fn truncate(text: &str, width: usize) -> String {
+    if text.chars().count() <= width {
+        return text.to_string();
+    }
+    let mut short: String = text.chars().take(width - 1).collect();
+    short.push('…');
+    short
+}
@@ -0,0 +1,298 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::bench::model_cost_usd;
+use crate::conversation::Turn;
+use crate::hooks::PipelineEvent;
+use crate::parallel::map_bounded;
+use crate::progress::Progress;
+use crate::prompts;
+use crate::rate_limit::estimate_tokens;
+use crate::timestamps::with_timestamp_links;
+use crate::transcript::Transcript;
+use crate::{guardrail, Error, VideoTranscriber, ANSWER_SYSTEM_PROMPT};
+
+// ===== Groq API Structures =====
+
+#[derive(Serialize)]
+struct GroqRequest {
+    model: String,
+    messages: Vec<GroqMessage>,
+    temperature: f32,
+}
+
+#[derive(Serialize)]
+struct GroqMessage {
+    role: String,
+    content: String,
+}
+
+#[derive(Deserialize, Debug)]
+struct GroqResponse {
+    choices: Vec<GroqChoice>,
+    #[serde(default)]
+    usage: Option<GroqUsage>,
+}
+
+#[derive(Deserialize, Debug)]
+struct GroqUsage {
+    prompt_tokens: u64,
+    completion_tokens: u64,
+}
+
+#[derive(Deserialize, Debug)]
+struct GroqChoice {
+    message: GroqResponseMessage,
+}
+
+#[derive(Deserialize, Debug)]
+struct GroqResponseMessage {
+    content: String,
+}
+
+// ===== Provider Selection =====
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LlmProvider {
+    Groq,
+    Gemini,
+}
+
+/// One model on one provider, written `groq:<model>`, `gemini:<model>`, or just the model name
+/// (names starting with `gemini` run on Gemini, anything else on Groq)
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(try_from = "String", into = "String")]
+pub struct ModelChoice {
+    pub provider: LlmProvider,
+    pub model: String,
+}
+
+impl std::str::FromStr for ModelChoice {
+    type Err = String;
+
+    fn from_str(value: &str) -> std::result::Result<Self, Self::Err> {
+        let (provider, model) = match value.split_once(':') {
+            Some(("groq", model)) => (LlmProvider::Groq, model),
+            Some(("gemini", model)) => (LlmProvider::Gemini, model),
+            Some((provider, _)) => return Err(format!("unknown provider '{}' (expected groq or gemini)", provider)),
+            None if value.starts_with("gemini") => (LlmProvider::Gemini, value),
+            None => (LlmProvider::Groq, value),
+        };
+        if model.trim().is_empty() {
+            return Err("missing model name".to_string());
+        }
+        Ok(Self {
+            provider,
+            model: model.trim().to_string(),
+        })
+    }
+}
+
+impl TryFrom<String> for ModelChoice {
+    type Error = String;
+
+    fn try_from(value: String) -> std::result::Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl From<ModelChoice> for String {
+    fn from(choice: ModelChoice) -> Self {
+        choice.to_string()
+    }
+}
+
+impl std::fmt::Display for ModelChoice {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let provider = match self.provider {
+            LlmProvider::Groq => "groq",
+            LlmProvider::Gemini => "gemini",
+        };
+        write!(f, "{}:{}", provider, self.model)
+    }
+}
+
+impl VideoTranscriber {
+    /// Send a system + user prompt to the configured LLM provider and return the reply text
+    pub fn complete(&self, system: &str, prompt: &str) -> Result<String> {
+        match self.llm_provider {
+            LlmProvider::Groq => self.complete_groq(system, prompt),
+            LlmProvider::Gemini => self.complete_gemini(system, prompt),
+        }
+    }
+
+    /// Like `complete`, with earlier turns of a conversation sent as the model's chat history
+    pub fn complete_conversation(&self, system: &str, history: &[Turn], prompt: &str) -> Result<String> {
+        match self.llm_provider {
+            LlmProvider::Groq => self.complete_groq_conversation(&self.groq_model, system, history, prompt),
+            LlmProvider::Gemini => self.complete_gemini_conversation(system, history, prompt),
+        }
+    }
+
+    /// The configured provider and model that `complete` uses
+    pub fn primary_model(&self) -> ModelChoice {
+        ModelChoice {
+            provider: self.llm_provider,
+            model: match self.llm_provider {
+                LlmProvider::Groq => self.groq_model.clone(),
+                LlmProvider::Gemini => self.gemini_model.clone(),
+            },
+        }
+    }
+
+    /// Like `complete`, on a model chosen by the caller
+    pub fn complete_with(&self, model: &ModelChoice, system: &str, prompt: &str) -> Result<String> {
+        self.check_model_key(model)?;
+        match model.provider {
+            LlmProvider::Groq => self.complete_groq_model(&model.model, system, prompt),
+            LlmProvider::Gemini => self.generate_gemini(&model.model, system, prompt, &[]),
+        }
+    }
+
+    /// Complete the same prompt on every model at once, in the order given
+    pub fn complete_each(&self, models: &[ModelChoice], system: &str, prompt: &str) -> Vec<Result<String>> {
+        map_bounded(models, models.len(), |model| self.complete_with(model, system, prompt))
+    }
+
+    /// Fail early when `model`'s provider has no API key configured
+    pub fn check_model_key(&self, model: &ModelChoice) -> Result<()> {
+        match model.provider {
+            LlmProvider::Groq if self.groq_api_key.is_empty() => Err(Error::MissingApiKey("GROQ_API_KEY").into()),
+            LlmProvider::Gemini if self.gemini_api_key.is_empty() => Err(Error::MissingApiKey("GEMINI_API_KEY").into()),
+            _ => Ok(()),
+        }
+    }
+
+    /// Complete a prompt whose reply must be JSON, parsing it into `T`. A reply that doesn't
+    /// parse is sent back once with the parse error, since models usually fix it when told.
+    pub fn complete_json<T: serde::de::DeserializeOwned>(&self, system: &str, prompt: &str) -> Result<T> {
+        let reply = self.complete(system, prompt)?;
+        let error = match serde_json::from_str(extract_json(&reply)) {
+            Ok(parsed) => return Ok(parsed),
+            Err(e) => e,
+        };
+        status!("⚠️  The model's reply wasn't the JSON asked for ({}); asking again...", error);
+        let retry = format!(
+            "{}\n\nYour previous reply could not be parsed ({}). Reply with the JSON asked for and nothing else.",
+            prompt, error
+        );
+        let reply = self.complete(system, &retry)?;
+        serde_json::from_str(extract_json(&reply)).with_context(|| {
+            format!("The model did not return the expected JSON. Reply was:\n{}", reply)
+        })
+    }
+
+    /// Run a chat completion against Groq
+    pub fn complete_groq(&self, system: &str, prompt: &str) -> Result<String> {
+        self.complete_groq_model(&self.groq_model, system, prompt)
+    }
+
+    /// Run a chat completion against a specific Groq model
+    pub fn complete_groq_model(&self, model: &str, system: &str, prompt: &str) -> Result<String> {
+        self.complete_groq_conversation(model, system, &[], prompt)
+    }
+
+    /// Run a chat completion against Groq with earlier turns of the conversation before the prompt
+    pub fn complete_groq_conversation(&self, model: &str, system: &str, history: &[Turn], prompt: &str) -> Result<String> {
+        let message = |role: &str, content: &str| GroqMessage {
+            role: role.to_string(),
+            content: content.to_string(),
+        };
+        let mut messages = vec![message("system", &guardrail::harden_system_prompt(system))];
+        for turn in history {
+            messages.push(message("user", &turn.question));
+            messages.push(message("assistant", &turn.answer));
+        }
+        messages.push(message("user", prompt));
+        let request = GroqRequest {
+            model: model.to_string(),
+            messages,
+            temperature: 0.3,
+        };
+
+        self.report(Progress::Generating {
+            model: model.to_string(),
+        });
+        let url = format!("{}/openai/v1/chat/completions", self.endpoints.groq);
+        let response = self
+            .send_retrying("Groq", || {
+                self.client
+                    .post(&url)
+                    .header("Authorization", format!("Bearer {}", self.groq_api_key))
+                    .header("Content-Type", "application/json")
+                    .json(&request)
+            })
+            .context("Failed to generate answer from Groq")?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().unwrap_or_default();
+            return Err(self.http_failure("Groq generate", "POST", &url, status, &body));
+        }
+
+        let groq_response: GroqResponse = response
+            .json()
+            .context("Failed to parse Groq response")?;
+
+        let answer = groq_response
+            .choices
+            .first()
+            .map(|choice| choice.message.content.clone())
+            .context("No answer generated by Groq")?;
+
+        let prompt_chars = request.messages.iter().map(|message| message.content.len()).sum();
+        let (input_tokens, output_tokens) = match &groq_response.usage {
+            Some(usage) => (usage.prompt_tokens, usage.completion_tokens),
+            None => (estimate_tokens(prompt_chars), estimate_tokens(answer.len())),
+        };
+        self.emit(PipelineEvent::CostIncurred {
+            service: "groq",
+            model: Some(model),
+            input_tokens,
+            output_tokens,
+            usd: model_cost_usd(model, input_tokens, output_tokens),
+        })?;
+        self.emit(PipelineEvent::AnswerGenerated {
+            model,
+            prompt_chars,
+            answer: &answer,
+        })?;
+        Ok(answer)
+    }
+
+    /// Ask a question with transcript directly using Groq
+    pub fn ask_question_groq(&self, transcript: &Transcript, question: &str) -> Result<String> {
+        status!("🤔 Asking question with Groq: \"{}\"", question);
+
+        let prompt = self.direct_prompt(transcript, question)?;
+        self.complete_groq(ANSWER_SYSTEM_PROMPT, &prompt)
+    }
+
+    /// The prompt for answering from a whole transcript sent inline
+    ///
+    /// Timed transcripts are sent with `[MM:SS]` markers for the answer to cite.
+    pub fn direct_prompt(&self, transcript: &Transcript, question: &str) -> Result<String> {
+        let options = self.video_prompt_options(&transcript.video_id)?;
+        Ok(prompts::full_transcript(transcript, question, &options))
+    }
+
+    /// Ask a question with transcript directly (no file upload needed), linking each moment
+    /// the answer cites
+    pub fn ask_question_direct(&self, transcript: &Transcript, question: &str) -> Result<String> {
+        let answer = match self.llm_provider {
+            LlmProvider::Groq => self.ask_question_groq(transcript, question)?,
+            LlmProvider::Gemini => self.ask_question_gemini(transcript, question)?,
+        };
+        Ok(with_timestamp_links(transcript, answer))
+    }
+}
+
+/// Pull the JSON payload out of a model reply that may wrap it in prose or code fences
+pub fn extract_json(reply: &str) -> &str {
+    let start = reply.find(['{', '[']);
+    let end = reply.rfind(['}', ']']);
+    match (start, end) {
+        (Some(start), Some(end)) if end > start => &reply[start..=end],
+        _ => reply.trim(),
+    }
+}
@@ -1,8 +1,30 @@
 use anyhow::{Context, Result};
-use clap::{Parser, Subcommand};
-use serde::{Deserialize, Serialize};
-use std::env;
-use std::time::Duration;
+use clap::{Args, CommandFactory, FromArgMatches, Parser, Subcommand};
+use std::io::{self, BufRead, IsTerminal, Write};
+use std::time::{Duration, Instant};
+
+mod init;
+
+use claude_video_transcribe::{
+    apify, archive, batch, bench, bugreport, cancel, chapters, chat, chunking, cite, companion, config, consensus, contradictions, conversation, debate, debuglog, ensemble, eval, export, feedback, finance, flashcards, grep, http, instructions, launcher, library, listing, llm, mentions, notes, organize, persona, pipeline, playlist, progress, quotes, rate_limit, related, retrieval, schema, server, slides, sources, strategy, style, summary, telemetry, transcript, trends, watch,
+};
+#[cfg(unix)]
+use claude_video_transcribe::daemon;
+use claude_video_transcribe::{status, VideoTranscriber, ANSWER_SYSTEM_PROMPT, DEFAULT_GEMINI_MODEL, DEFAULT_GROQ_MODEL};
+
+use cancel::CancellationToken;
+use launcher::OutputFormat;
+use llm::ModelChoice;
+use notes::NotesOutcome;
+use playlist::PlaylistOutcome;
+use rate_limit::Tier;
+use sources::SourceKind;
+use strategy::Strategy;
+use style::AnswerStyle;
+use summary::SummaryLength;
+use retrieval::Chunk;
+use schema::{Answer, Citation, UsageMeter};
+use transcript::CaptionSource;
 
 /// CLI application for transcribing YouTube videos and asking questions using RAG
 #[derive(Parser)]
@@ -11,674 +33,2351 @@ use std::time::Duration;
 struct Cli {
     #[command(subcommand)]
     command: Commands,
+    /// Give up on the whole command after this many seconds, aborting requests in flight
+    #[arg(long, global = true, value_name = "SECS")]
+    timeout: Option<u64>,
+    /// Where to fetch single-video transcripts from (overrides TRANSCRIPT_SOURCE)
+    #[arg(long, global = true, value_enum)]
+    source: Option<SourceKind>,
+    /// Gemini quota tier; `free` waits to stay under the free tier's limits (overrides GEMINI_TIER)
+    #[arg(long, global = true, value_enum)]
+    tier: Option<Tier>,
 }
 
+/// `ask` options that need a single video, which `--all` and `--tag` answer without
+const CROSS_VIDEO_CONFLICTS: [&str; 10] = [
+    "url", "positional_url", "stdin_url", "reindex", "related", "second_opinion", "audio", "agent", "strategy", "chunked",
+];
+
 #[derive(Subcommand)]
 enum Commands {
     /// Fetch and index a YouTube video transcript
     Index {
-        /// YouTube video URL
-        #[arg(short, long)]
-        url: String,
+        #[command(flatten)]
+        video: VideoArg,
+        /// For playlist or channel URLs: maximum number of videos to index from each
+        #[arg(long, default_value_t = 50)]
+        max_videos: i32,
+        /// For playlists, channels, and --from-file: videos fetched, uploaded, and embedded in
+        /// parallel
+        #[arg(long, visible_alias = "concurrency", default_value_t = 4)]
+        workers: usize,
+        /// Fetch a fresh transcript instead of using the cached one
+        #[arg(long)]
+        reindex: bool,
+        /// Index every video of a YouTube playlist with the configured transcript source and
+        /// group them in the index (videos already indexed are skipped unless --reindex)
+        #[arg(long, value_name = "URL", conflicts_with_all = ["url", "positional_url", "stdin_url"])]
+        playlist: Option<String>,
+        /// Index a channel's back catalog (a URL or @handle); repeat to index several channels
+        #[arg(long, value_name = "URL", conflicts_with_all = ["url", "positional_url", "stdin_url", "playlist"])]
+        channel: Vec<String>,
+        /// For playlist or channel URLs: only videos published since this date (2024-01-31) or
+        /// for this long (30d, 2w)
+        #[arg(long, conflicts_with = "playlist")]
+        since: Option<String>,
+        /// Index every video URL in this file, one per line (`#` starts a comment); videos
+        /// already indexed are skipped unless --reindex
+        #[arg(long, value_name = "PATH", conflicts_with_all = ["url", "positional_url", "stdin_url", "playlist", "channel", "since"])]
+        from_file: Option<std::path::PathBuf>,
+        /// With --from-file: where to write the JSON results summary (`<FILE>.results.json` by
+        /// default)
+        #[arg(long, value_name = "PATH", requires = "from_file")]
+        results: Option<std::path::PathBuf>,
+        /// With --from-file: also print the results summary to stdout as JSON
+        #[arg(long, requires = "from_file")]
+        json: bool,
     },
     /// Ask a question about an indexed video
     Ask {
-        /// YouTube video URL (must be indexed first)
-        #[arg(short, long)]
-        url: String,
+        #[command(flatten)]
+        video: VideoArg,
         /// Question to ask about the video
         #[arg(short, long)]
         question: String,
+        /// Fetch a fresh transcript instead of using the cached one
+        #[arg(long)]
+        reindex: bool,
+        /// Suggest other library videos on the same topic after answering
+        #[arg(long)]
+        related: bool,
+        /// Also answer with this model (`groq:<model>`, `gemini:<model>`, or a model name) from
+        /// the same context, and compare the two answers
+        #[arg(long, value_name = "MODEL", conflicts_with = "ensemble")]
+        second_opinion: Option<ModelChoice>,
+        /// Answer with 2–3 models and merge their answers with a judge, flagging disagreements.
+        /// Takes a comma-separated model list, or uses `[ensemble]` from the config file
+        #[arg(long, value_name = "MODELS", num_args = 0..=1, value_delimiter = ',')]
+        ensemble: Option<Vec<ModelChoice>>,
+        /// Shape of the answer, checked and reformatted if the model ignores it
+        #[arg(long, value_enum)]
+        style: Option<AnswerStyle>,
+        /// Longest answer in words; longer answers are sent back to be shortened
+        #[arg(long, value_name = "N", value_parser = clap::value_parser!(u32).range(1..))]
+        max_words: Option<u32>,
+        /// Language to answer in (a code like `es` or a name), whatever language the video is in
+        #[arg(long, value_name = "LANG")]
+        answer_lang: Option<String>,
+        /// Print the answer as text, for a launcher (`raycast`, `launcher`), or as JSON with its
+        /// excerpts and usage
+        #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+        format: OutputFormat,
+        /// Let Gemini answer from the video's audio instead of a transcript (works for videos
+        /// without captions; needs GEMINI_API_KEY, yt-dlp, and ffmpeg)
+        #[arg(long, conflicts_with_all = ["reindex", "related", "second_opinion", "ensemble"])]
+        audio: bool,
+        /// For complex questions: let the model search the library, read transcript windows, and
+        /// search the web over several steps before answering
+        #[arg(long, conflicts_with_all = ["second_opinion", "ensemble", "audio"])]
+        agent: bool,
+        /// Print each step of --agent: the tool called, why, and what it returned
+        #[arg(long, requires = "agent")]
+        show_steps: bool,
+        /// How to put the question to the model; `auto` picks per question from the transcript's
+        /// length, the question, and what is already uploaded or cached
+        #[arg(long, value_enum, conflicts_with_all = ["audio", "agent"])]
+        strategy: Option<Strategy>,
+        /// Shorthand for --strategy retrieval: answer from the most relevant transcript chunks
+        #[arg(long, conflicts_with_all = ["audio", "agent", "strategy"])]
+        chunked: bool,
+        /// Answer from the most relevant excerpts of every cached video instead of one video,
+        /// attributing each point to its video
+        #[arg(long, conflicts_with_all = CROSS_VIDEO_CONFLICTS)]
+        all: bool,
+        /// Like --all, but only videos in this collection: a category (`Programming/Rust`, or
+        /// one level of it such as `rust`) or an indexed playlist
+        #[arg(long, conflicts_with_all = CROSS_VIDEO_CONFLICTS, conflicts_with = "all")]
+        tag: Option<String>,
+        /// Continue a saved conversation, sending its earlier turns as chat history and saving
+        /// this one; the video URL can be left out once the session exists
+        #[arg(
+            long,
+            value_name = "NAME",
+            conflicts_with_all = ["related", "second_opinion", "ensemble", "audio", "agent", "strategy", "chunked", "all", "tag"]
+        )]
+        session: Option<String>,
+    },
+    /// Answer questions about what was just said, for use as a second screen while watching
+    Companion {
+        #[command(flatten)]
+        video: VideoArg,
+        /// Playhead position, e.g. 42:10 or 1:02:03
+        #[arg(long, value_parser = companion::parse_timestamp)]
+        at: f64,
+        /// Question to ask; without it, questions are read interactively and the playhead
+        /// advances in real time
+        #[arg(short, long)]
+        question: Option<String>,
+        /// Seconds before the playhead the answer may draw on
+        #[arg(long, default_value_t = 180.0)]
+        window: f64,
     },
     /// Index a video and immediately ask a question
     Query {
-        /// YouTube video URL
-        #[arg(short, long)]
-        url: String,
+        #[command(flatten)]
+        video: VideoArg,
         /// Question to ask about the video
         #[arg(short, long)]
         question: String,
+        /// Fetch a fresh transcript instead of using the cached one
+        #[arg(long)]
+        reindex: bool,
+        /// Answer with 2–3 models and merge their answers with a judge, flagging disagreements.
+        /// Takes a comma-separated model list, or uses `[ensemble]` from the config file
+        #[arg(long, value_name = "MODELS", num_args = 0..=1, value_delimiter = ',')]
+        ensemble: Option<Vec<ModelChoice>>,
+        /// Shape of the answer, checked and reformatted if the model ignores it
+        #[arg(long, value_enum)]
+        style: Option<AnswerStyle>,
+        /// Longest answer in words; longer answers are sent back to be shortened
+        #[arg(long, value_name = "N", value_parser = clap::value_parser!(u32).range(1..))]
+        max_words: Option<u32>,
+        /// Language to answer in (a code like `es` or a name), whatever language the video is in
+        #[arg(long, value_name = "LANG")]
+        answer_lang: Option<String>,
+        /// Print the answer as text, for a launcher (`raycast`, `launcher`), or as JSON
+        #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+        format: OutputFormat,
+    },
+    /// List every indexed video, whether its Gemini files are still active, and whether it
+    /// changed on YouTube since it was indexed
+    List {
+        /// Print JSON instead of a table
+        #[arg(long)]
+        json: bool,
+        /// Skip asking Gemini for each file's state and YouTube whether each video changed
+        #[arg(long)]
+        no_check: bool,
+        /// Re-index the videos that changed on YouTube since they were indexed
+        #[arg(long, conflicts_with_all = ["no_check", "playlists"])]
+        refresh: bool,
+        /// Only the videos of this indexed playlist (URL or ID), in playlist order
+        #[arg(long, value_name = "PLAYLIST")]
+        playlist: Option<String>,
+        /// List the indexed playlists instead of videos
+        #[arg(long, conflicts_with = "playlist")]
+        playlists: bool,
+    },
+    /// Remove a video from the index, the transcript cache, and Gemini
+    Delete {
+        #[command(flatten)]
+        video: VideoArg,
+    },
+    /// Ask a question across every video in the local library
+    AskLibrary {
+        /// Question to ask
+        #[arg(short, long)]
+        question: String,
+        /// Ask each video separately and compare the answers in a table
+        #[arg(long)]
+        per_video: bool,
+        /// Maximum number of videos asked at the same time with --per-video
+        #[arg(long, default_value_t = 4)]
+        concurrency: usize,
+        /// Answer with 2–3 models and merge their answers with a judge, flagging disagreements.
+        /// Takes a comma-separated model list, or uses `[ensemble]` from the config file
+        #[arg(long, conflicts_with = "per_video", value_name = "MODELS", num_args = 0..=1, value_delimiter = ',')]
+        ensemble: Option<Vec<ModelChoice>>,
+        /// Shape of the answer, checked and reformatted if the model ignores it
+        #[arg(long, value_enum, conflicts_with = "per_video")]
+        style: Option<AnswerStyle>,
+        /// Longest answer in words; longer answers are sent back to be shortened
+        #[arg(long, value_name = "N", value_parser = clap::value_parser!(u32).range(1..), conflicts_with = "per_video")]
+        max_words: Option<u32>,
+        /// Language to answer in (a code like `es` or a name), whatever language the video is in
+        #[arg(long, value_name = "LANG", conflicts_with = "per_video")]
+        answer_lang: Option<String>,
+        /// Print the answer as text, for a launcher (`raycast`, `launcher`), or as JSON
+        #[arg(long, value_enum, default_value_t = OutputFormat::Text, conflicts_with = "per_video")]
+        format: OutputFormat,
+        /// For complex questions: let the model search the library, read transcript windows, and
+        /// search the web over several steps before answering
+        #[arg(long, conflicts_with_all = ["per_video", "ensemble"])]
+        agent: bool,
+        /// Print each step of --agent: the tool called, why, and what it returned
+        #[arg(long, requires = "agent")]
+        show_steps: bool,
+    },
+    /// Synthesize a consensus report from several review videos of one product
+    Consensus {
+        /// Product being reviewed
+        #[arg(short, long)]
+        product: String,
+        /// Review video URLs (defaults to library videos whose title mentions the product)
+        #[arg(short, long)]
+        url: Vec<String>,
+        /// Print the structured analysis as JSON
+        #[arg(long)]
+        json: bool,
+        /// Maximum number of reviews analyzed at the same time
+        #[arg(long, default_value_t = 4)]
+        concurrency: usize,
+    },
+    /// Break down a debate or panel into per-speaker claims, rebuttals, and evidence
+    Debate {
+        #[command(flatten)]
+        video: VideoArg,
+        /// Participant names, to help attribute turns (comma separated)
+        #[arg(long, value_delimiter = ',')]
+        speakers: Vec<String>,
+        /// Print the structured analysis as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Extract guidance, KPIs, and analyst Q&A from an earnings call
+    Finance {
+        #[command(flatten)]
+        video: VideoArg,
+        /// Output format
+        #[arg(long, value_enum, default_value_t = FinanceFormat::Json)]
+        format: FinanceFormat,
+        /// Write the output to a file instead of stdout
+        #[arg(short, long)]
+        out: Option<std::path::PathBuf>,
+    },
+    /// Turn a lecture into a Marp or reveal.js Markdown slide deck
+    Slides {
+        #[command(flatten)]
+        video: VideoArg,
+        /// Slide dialect to generate
+        #[arg(long, value_enum, default_value_t = slides::SlideFormat::Marp)]
+        format: slides::SlideFormat,
+        /// Maximum number of content slides
+        #[arg(long, default_value_t = 15)]
+        max_slides: usize,
+        /// Write the deck to a file instead of stdout
+        #[arg(short, long)]
+        out: Option<std::path::PathBuf>,
+    },
+    /// Show where a video's transcript is split into overlapping chunks for retrieval
+    Chunks {
+        #[command(flatten)]
+        video: VideoArg,
+        /// Only the chunks that would be sent to the model for this question, best first
+        #[arg(short, long)]
+        question: Option<String>,
+        /// Print the chunk boundaries as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Write study notes for a video; running it again updates them with a changelog
+    Notes {
+        #[command(flatten)]
+        video: VideoArg,
+        /// Fetch a fresh transcript (e.g. once better captions are available) before updating
+        #[arg(long)]
+        reindex: bool,
+        /// Write new notes from scratch instead of updating the earlier ones
+        #[arg(long)]
+        fresh: bool,
+        /// Write the notes to a file instead of stdout
+        #[arg(short, long)]
+        out: Option<std::path::PathBuf>,
+    },
+    /// Summarize a video in a sentence, a paragraph, or in detail
+    Summarize {
+        #[command(flatten)]
+        video: VideoArg,
+        /// How long the summary should be
+        #[arg(short, long, value_enum, default_value_t = SummaryLength::Paragraph)]
+        length: SummaryLength,
+        /// Fetch a fresh transcript instead of using the cached one
+        #[arg(long)]
+        reindex: bool,
+        /// Write the summary to a file instead of stdout
+        #[arg(short, long)]
+        out: Option<std::path::PathBuf>,
+    },
+    /// Generate timestamped chapter markers to paste into a YouTube description
+    Chapters {
+        #[command(flatten)]
+        video: VideoArg,
+        /// Roughly how many chapters to make; by default one per major topic
+        #[arg(long, value_name = "N", value_parser = clap::value_parser!(u32).range(3..))]
+        count: Option<u32>,
+        /// Fetch a fresh transcript instead of using the cached one
+        #[arg(long)]
+        reindex: bool,
+        /// Print the chapters as JSON
+        #[arg(long)]
+        json: bool,
+        /// Write the chapters to a file instead of stdout
+        #[arg(short, long)]
+        out: Option<std::path::PathBuf>,
+    },
+    /// Write question-and-answer flashcards from a video, ready to import into Anki
+    Quiz {
+        #[command(flatten)]
+        video: VideoArg,
+        /// How many cards to write
+        #[arg(long, default_value_t = 10, value_parser = clap::value_parser!(u32).range(1..))]
+        count: u32,
+        /// File format to write
+        #[arg(long, value_enum, default_value_t = flashcards::FlashcardFormat::Anki)]
+        format: flashcards::FlashcardFormat,
+        /// Fetch a fresh transcript instead of using the cached one
+        #[arg(long)]
+        reindex: bool,
+        /// Write the cards to a file instead of stdout
+        #[arg(short, long)]
+        out: Option<std::path::PathBuf>,
+    },
+    /// Write a video's transcript to a file as plain text, Markdown, SRT or WebVTT subtitles,
+    /// or JSON
+    Export {
+        #[command(flatten)]
+        video: VideoArg,
+        /// File format; taken from the `-o` extension when not given, else txt
+        #[arg(long, value_enum)]
+        format: Option<export::TranscriptFormat>,
+        /// Fetch a fresh transcript instead of using the cached one
+        #[arg(long)]
+        reindex: bool,
+        /// File to write, or `-` for stdout; `<VIDEO_ID>.<extension>` by default
+        #[arg(short, long)]
+        out: Option<std::path::PathBuf>,
+        /// Export the video even if it is private
+        #[arg(long)]
+        include_private: bool,
+    },
+    /// Translate a video's transcript, keeping its timestamps
+    Translate {
+        #[command(flatten)]
+        video: VideoArg,
+        /// Language to translate into (a code like `es` or a name)
+        #[arg(long, value_name = "LANG")]
+        to: String,
+        /// Fetch a fresh transcript instead of using the cached one
+        #[arg(long)]
+        reindex: bool,
+        /// Print the translated transcript as JSON, segments included
+        #[arg(long)]
+        json: bool,
+        /// Write the translation to a file instead of stdout
+        #[arg(short, long)]
+        out: Option<std::path::PathBuf>,
+    },
+    /// Write a multi-section research report with citations from the videos in the library
+    Report {
+        /// Research question the report answers
+        #[arg(short, long)]
+        query: String,
+        /// Only use videos in this collection (a category path from `organize`, or an indexed
+        /// playlist's URL or ID)
+        #[arg(long)]
+        collection: Option<String>,
+        /// Maximum number of sections
+        #[arg(long, default_value_t = 6, value_parser = clap::value_parser!(u32).range(1..=12))]
+        sections: u32,
+        /// Write the report to a file instead of stdout
+        #[arg(short, long)]
+        out: Option<std::path::PathBuf>,
+        /// Draw on private videos too
+        #[arg(long)]
+        include_private: bool,
+    },
+    /// Group the library into categories by topic similarity
+    Organize {
+        /// Minimum average similarity for videos to share a category (0.0-1.0)
+        #[arg(long, default_value_t = 0.75)]
+        threshold: f32,
+        /// Accept every proposed category without prompting
+        #[arg(short, long)]
+        yes: bool,
+        /// Print the saved hierarchy without re-clustering
+        #[arg(long)]
+        show: bool,
+    },
+    /// Suggest library videos that cover the same topic as a video
+    Related {
+        #[command(flatten)]
+        video: VideoArg,
+        /// Maximum number of suggestions
+        #[arg(short, long, default_value_t = 5)]
+        limit: usize,
+    },
+    /// Chart how often and how positively a channel talks about a topic over time
+    Trends {
+        /// Channel name (matched against indexed videos)
+        #[arg(short, long)]
+        channel: String,
+        /// Topic to track
+        #[arg(short, long)]
+        topic: String,
+        /// Print CSV instead of an ASCII chart
+        #[arg(long)]
+        csv: bool,
+        /// Count private videos too
+        #[arg(long)]
+        include_private: bool,
+    },
+    /// Chat about what a creator thinks, grounded in all of their indexed videos
+    Persona {
+        /// Channel name (matched against indexed videos)
+        #[arg(short, long)]
+        channel: String,
+        /// Ask a single question instead of starting an interactive chat
+        #[arg(short, long)]
+        question: Option<String>,
+    },
+    /// Find statements that conflict across a creator's indexed videos
+    Contradictions {
+        /// Channel name (matched against indexed videos)
+        #[arg(short, long)]
+        channel: String,
+        /// Maximum number of videos analyzed at the same time
+        #[arg(long, default_value_t = 4)]
+        concurrency: usize,
+    },
+    /// Watch channels for new uploads, index them, and alert on keyword matches
+    Watch {
+        /// Channel URLs to watch
+        #[arg(short, long, required = true)]
+        channel: Vec<String>,
+        /// Extra alert keywords on top of the `[[alerts]]` rules in the config file
+        #[arg(short, long)]
+        alert: Vec<String>,
+        /// Seconds between checks
+        #[arg(long, default_value_t = 3600)]
+        interval: u64,
+        /// How many of the latest videos to look at per check
+        #[arg(long, default_value_t = 5)]
+        max_videos: i32,
+        /// Check once and exit (for cron)
+        #[arg(long)]
+        once: bool,
+    },
+    /// Transcribe and index every recording dropped into a folder
+    IngestDir {
+        /// Folder to watch
+        dir: std::path::PathBuf,
+        /// Seconds between scans
+        #[arg(long, default_value_t = 30)]
+        interval: u64,
+        /// Scan once and exit (for cron)
+        #[arg(long)]
+        once: bool,
+    },
+    /// Chat about a video with `--url`, remembering earlier questions; or index a livestream's
+    /// chat replay alongside its transcript and ask about it
+    #[command(args_conflicts_with_subcommands = true, subcommand_negates_reqs = true)]
+    Chat {
+        #[command(subcommand)]
+        action: Option<ChatCommand>,
+        /// YouTube video URL to chat about
+        #[arg(short, long, required_unless_present = "session")]
+        url: Option<String>,
+        /// Save the conversation under this name, or continue it if it exists (see `ask --session`)
+        #[arg(long, value_name = "NAME")]
+        session: Option<String>,
+    },
+    /// Report brand or keyword mentions across the library with context and sentiment
+    Mentions {
+        /// Brand or phrase to look for
+        #[arg(short, long)]
+        query: String,
+        /// Only videos published since this point (e.g. 30d, 12h, 2w, 2024-01-31)
+        #[arg(long, default_value = "30d")]
+        since: String,
+        /// Check watched channels for new uploads first
+        #[arg(long)]
+        watched: bool,
+        /// Write the report to a file instead of stdout
+        #[arg(short, long)]
+        out: Option<std::path::PathBuf>,
+        /// Report mentions in private videos too
+        #[arg(long)]
+        include_private: bool,
+    },
+    /// Create or verify tamper-evident compliance archives
+    Archive {
+        #[command(subcommand)]
+        action: ArchiveCommand,
+    },
+    /// Print an academic citation for a video, with optional timestamped quote citations
+    Cite {
+        #[command(flatten)]
+        video: VideoArg,
+        /// Citation style
+        #[arg(long, value_enum, default_value_t = cite::CitationStyle::Apa)]
+        style: cite::CitationStyle,
+        /// Quoted passages to cite inline with their timestamp
+        #[arg(long)]
+        quote: Vec<String>,
+    },
+    /// Find a video's most notable verbatim quotes, or those about a topic, with speaker,
+    /// timestamp, and confidence
+    #[command(visible_alias = "quotes")]
+    Quote {
+        #[command(flatten)]
+        video: VideoArg,
+        /// Topic the quotes should be about; without it, the video's most notable quotes
+        #[arg(short, long)]
+        about: Option<String>,
+        /// Maximum number of quotes
+        #[arg(long, default_value_t = 5)]
+        count: usize,
+        /// Print the quotes as JSON
+        #[arg(long)]
+        json: bool,
+        /// Keep quotes that could not be found verbatim in the transcript (flagged)
+        #[arg(long)]
+        include_unverified: bool,
+    },
+    /// Rate an earlier answer so retrieval for that video improves
+    Feedback {
+        /// Whether the answer was helpful
+        #[arg(value_enum)]
+        rating: feedback::Rating,
+        /// Answer ID printed by `ask`
+        answer_id: String,
+    },
+    /// Attach standing instructions to a video or collection, prepended to every answer prompt
+    Instructions {
+        #[command(subcommand)]
+        action: InstructionsCommand,
+    },
+    /// Mark videos private, keeping them out of the shared server, archives, and reports
+    Access {
+        #[command(subcommand)]
+        action: AccessCommand,
+    },
+    /// Measure and tune excerpt retrieval against a labeled question set
+    Eval {
+        #[command(subcommand)]
+        action: EvalCommand,
+    },
+    /// Time each pipeline stage (fetch, chunk, embed, upload, generate) on a sample video
+    Bench {
+        #[command(flatten)]
+        video: VideoArg,
+        /// Run a fresh Apify fetch instead of using the cache (costs credits)
+        #[arg(long)]
+        fetch: bool,
+        /// How many chunks to embed when timing the embedding stage
+        #[arg(long, default_value_t = 8)]
+        embed_chunks: usize,
+    },
+    /// Search a stored transcript for an exact phrase or regular expression
+    Grep {
+        /// Phrase (or regex with --regex) to search for
+        pattern: String,
+        /// YouTube video URL (searches the whole library when omitted)
+        #[arg(short, long)]
+        url: Option<String>,
+        /// Treat the pattern as a regular expression
+        #[arg(short = 'E', long)]
+        regex: bool,
+        /// Match case exactly
+        #[arg(long)]
+        case_sensitive: bool,
+        /// Print the matches as text, for a launcher (`raycast`, `launcher`), or as JSON
+        #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+        format: OutputFormat,
+    },
+    /// Find the transcript passages most relevant to a query, with the scores they ranked by
+    Search {
+        /// What to look for
+        #[arg(value_name = "QUERY", required_unless_present = "query", conflicts_with = "query")]
+        positional_query: Option<String>,
+        /// What to look for
+        #[arg(short, long)]
+        query: Option<String>,
+        /// YouTube video URL (searches the whole library when omitted)
+        #[arg(short, long)]
+        url: Option<String>,
+        /// Passages to show
+        #[arg(short = 'k', long, default_value_t = 10)]
+        top_k: usize,
+        /// `json` prints the ranking with every hit's scores and each stage's timings
+        #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+        format: OutputFormat,
+    },
+    /// List or delete conversations saved with `ask --session` or `chat --session`
+    Sessions {
+        #[command(subcommand)]
+        action: SessionsCommand,
+    },
+    /// Inspect and manage Apify actor runs
+    Runs {
+        #[command(subcommand)]
+        action: RunsCommand,
+    },
+    /// Show Apify credit usage for the current monthly cycle
+    ApifyUsage,
+    /// Interactive first-run setup: API keys, default provider and model
+    Init,
+    /// Print a shell completion script to stdout
+    Completions {
+        /// Shell to generate completions for
+        shell: clap_complete::Shell,
+    },
+    /// Bundle the last error, redacted config, and versions into a zip for a GitHub issue
+    ReportBug {
+        /// Where to write the zip (default: ./cvt-bug-report-<time>.zip)
+        #[arg(short, long)]
+        out: Option<std::path::PathBuf>,
+    },
+    /// Opt in to (or out of) local usage counts that help prioritize fixes
+    Telemetry {
+        #[command(subcommand)]
+        action: TelemetryCommand,
+    },
+    /// Run a local HTTP server that indexes videos as background jobs
+    Serve {
+        /// Address to listen on
+        #[arg(long, default_value = "127.0.0.1")]
+        bind: String,
+        /// Port to listen on
+        #[arg(short, long, default_value_t = 8787)]
+        port: u16,
+        /// Parallel upload/embedding workers for playlist and channel jobs
+        #[arg(long, default_value_t = 4)]
+        workers: usize,
+        /// Answer questions about private videos too
+        #[arg(long)]
+        include_private: bool,
+    },
+    /// Manage the background daemon that keeps `ask` warm between runs
+    Daemon {
+        #[command(subcommand)]
+        action: DaemonCommand,
     },
 }
 
-// ===== Apify API Structures =====
-
-#[derive(Serialize)]
-struct ApifyRunInput {
-    #[serde(rename = "startUrls")]
-    start_urls: Vec<ApifyUrl>,
-    #[serde(rename = "maxResults")]
-    max_results: i32,
+impl Commands {
+    /// The `--format` a command prints its results in, which its errors are printed in too
+    fn output_format(&self) -> OutputFormat {
+        match self {
+            Commands::Ask { format, .. }
+            | Commands::Query { format, .. }
+            | Commands::AskLibrary { format, .. }
+            | Commands::Grep { format, .. }
+            | Commands::Search { format, .. } => *format,
+            _ => OutputFormat::Text,
+        }
+    }
 }
 
-#[derive(Serialize)]
-struct ApifyUrl {
-    url: String,
+#[derive(Subcommand)]
+enum DaemonCommand {
+    /// Run the daemon in the foreground (normally started automatically by `ask`)
+    Run,
+    /// Show whether a daemon is running
+    Status,
+    /// Stop the running daemon
+    Stop,
 }
 
-#[derive(Deserialize, Debug)]
-struct ApifyDatasetItem {
-    text: Option<String>,
-    #[serde(rename = "channelName")]
-    channel_name: Option<String>,
-    title: Option<String>,
+#[derive(Subcommand)]
+enum TelemetryCommand {
+    /// Start counting command runs and error categories on this machine
+    Enable,
+    /// Stop counting and delete everything recorded
+    Disable,
+    /// Show whether telemetry is on and what has been recorded
+    Status,
+    /// Write the recorded counts as JSON so you can review and share them
+    Export {
+        /// Write to this file instead of stdout
+        #[arg(short, long)]
+        out: Option<std::path::PathBuf>,
+    },
 }
 
-// ===== Gemini API Structures =====
-
-#[derive(Serialize)]
-struct GeminiFile {
-    file: GeminiFileData,
+#[derive(Clone, Copy, clap::ValueEnum)]
+enum FinanceFormat {
+    Json,
+    Csv,
 }
 
-#[derive(Serialize)]
-struct GeminiFileData {
-    #[serde(rename = "mimeType")]
-    mime_type: String,
-    #[serde(rename = "displayName")]
-    display_name: String,
+#[derive(Subcommand)]
+enum InstructionsCommand {
+    /// Set (or replace) the instructions for a video or collection
+    Set {
+        #[command(flatten)]
+        target: InstructionTarget,
+        /// Instructions such as "always answer with code examples"
+        text: String,
+    },
+    /// Remove the instructions for a video or collection
+    Clear {
+        #[command(flatten)]
+        target: InstructionTarget,
+    },
+    /// List all instructions, or those that apply to one video
+    Show {
+        /// Only show instructions that apply to this video
+        #[arg(short, long)]
+        url: Option<String>,
+    },
 }
 
-#[derive(Deserialize, Debug)]
-struct GeminiFileResponse {
-    file: GeminiFileInfo,
+#[derive(Subcommand)]
+enum AccessCommand {
+    /// Mark an indexed video private
+    Private {
+        #[command(flatten)]
+        video: VideoArg,
+        /// Who may see the video, or why it is private
+        #[arg(short, long)]
+        note: Option<String>,
+    },
+    /// Make a private video public again
+    Public {
+        #[command(flatten)]
+        video: VideoArg,
+    },
+    /// List private videos and their access notes
+    List,
 }
 
-#[derive(Deserialize, Debug)]
-struct GeminiFileInfo {
-    name: String,
-    uri: String,
-    state: String,
+/// A video URL or a collection path created by `organize`
+#[derive(Args)]
+#[group(required = true, multiple = false)]
+struct InstructionTarget {
+    /// YouTube video URL
+    #[arg(short, long)]
+    url: Option<String>,
+    /// Collection path such as `Programming/Rust` (covers its sub-collections too)
+    #[arg(short, long)]
+    collection: Option<String>,
 }
 
-#[derive(Serialize)]
-struct GeminiGenerateRequest {
-    contents: Vec<GeminiContent>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    tools: Option<Vec<GeminiTool>>,
+#[derive(Subcommand)]
+enum EvalCommand {
+    /// Score retrieval with the current parameters
+    Run {
+        /// JSON array of {"url", "question", "evidence": [phrases]}
+        set: std::path::PathBuf,
+    },
+    /// Sweep chunk size, overlap, k, and reranking and save the best parameters to the config
+    Tune {
+        /// JSON array of {"url", "question", "evidence": [phrases]}
+        set: std::path::PathBuf,
+        /// Show the results without writing the config
+        #[arg(long)]
+        dry_run: bool,
+    },
 }
 
-#[derive(Serialize)]
-struct GeminiContent {
-    parts: Vec<GeminiPart>,
-    role: String,
+/// Video URL given as a positional argument, with `--url`, or on stdin
+#[derive(Args)]
+struct VideoArg {
+    /// YouTube video URL (use `-` to read it from stdin)
+    #[arg(value_name = "URL", conflicts_with = "url")]
+    positional_url: Option<String>,
+    /// YouTube video URL
+    #[arg(short, long)]
+    url: Option<String>,
+    /// Read the video URL from stdin
+    #[arg(long, conflicts_with_all = ["url", "positional_url"])]
+    stdin_url: bool,
 }
 
-#[derive(Serialize)]
-struct GeminiPart {
-    #[serde(skip_serializing_if = "Option::is_none")]
-    text: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    file_data: Option<GeminiFileDataRef>,
-}
+impl VideoArg {
+    /// Whether a URL was given in any form, including as a request to read stdin
+    fn given(&self) -> bool {
+        self.url.is_some() || self.positional_url.is_some() || self.stdin_url
+    }
 
-#[derive(Serialize)]
-struct GeminiFileDataRef {
-    file_uri: String,
-    mime_type: String,
-}
+    /// An argument that already holds its URL, so resolving it again never touches stdin
+    fn resolved(url: String) -> Self {
+        Self {
+            positional_url: None,
+            url: Some(url),
+            stdin_url: false,
+        }
+    }
 
-#[derive(Serialize)]
-struct GeminiTool {
-    google_search: Option<GoogleSearch>,
-}
+    /// Resolve the URL, reading the first non-empty stdin line when it was piped in
+    fn resolve(&self) -> Result<String> {
+        let explicit = self
+            .url
+            .clone()
+            .or_else(|| self.positional_url.clone())
+            .filter(|url| url != "-");
+        if let Some(url) = explicit {
+            return Ok(url.trim().to_string());
+        }
 
-#[derive(Serialize)]
-struct GoogleSearch {}
+        let wants_stdin = self.stdin_url || self.positional_url.as_deref() == Some("-");
+        if !wants_stdin && std::io::stdin().is_terminal() {
+            anyhow::bail!("A video URL is required: pass it as an argument, with --url, or on stdin");
+        }
 
-#[derive(Deserialize, Debug)]
-struct GeminiGenerateResponse {
-    candidates: Option<Vec<GeminiCandidate>>,
-}
+        for line in std::io::stdin().lock().lines() {
+            let line = line.context("Failed to read URL from stdin")?;
+            if !line.trim().is_empty() {
+                return Ok(line.trim().to_string());
+            }
+        }
 
-#[derive(Deserialize, Debug, Clone)]
-struct GeminiCandidate {
-    content: GeminiResponseContent,
+        anyhow::bail!("No video URL received on stdin");
+    }
 }
 
-#[derive(Deserialize, Debug, Clone)]
-struct GeminiResponseContent {
-    parts: Vec<GeminiResponsePart>,
+#[derive(Subcommand)]
+enum ArchiveCommand {
+    /// Bundle transcript, metadata, and answers with SHA-256 hashes into a directory
+    Create {
+        #[command(flatten)]
+        video: VideoArg,
+        /// Questions to answer and archive alongside the transcript
+        #[arg(short, long)]
+        question: Vec<String>,
+        /// Directory to create (must not exist or be empty)
+        #[arg(short, long)]
+        out: std::path::PathBuf,
+        /// Archive the video even if it is private
+        #[arg(long)]
+        include_private: bool,
+    },
+    /// Check an archive's hashes and report any tampering
+    Verify {
+        /// Archive directory
+        dir: std::path::PathBuf,
+    },
 }
 
-#[derive(Deserialize, Debug, Clone)]
-struct GeminiResponsePart {
-    text: Option<String>,
+#[derive(Subcommand)]
+enum SessionsCommand {
+    /// List saved conversations, most recently continued first
+    List,
+    /// Delete a saved conversation
+    Delete {
+        /// Session name
+        name: String,
+    },
 }
 
-// ===== Groq API Structures =====
-
-#[derive(Serialize)]
-struct GroqRequest {
-    model: String,
-    messages: Vec<GroqMessage>,
-    temperature: f32,
+#[derive(Subcommand)]
+enum RunsCommand {
+    /// List recent Apify runs and what they cost
+    List {
+        /// Maximum number of runs to show
+        #[arg(short, long, default_value_t = 10)]
+        limit: u32,
+    },
+    /// Show the details of a single run
+    Show {
+        /// Apify run ID
+        id: String,
+    },
+    /// Abort a run that is still in progress
+    Abort {
+        /// Apify run ID
+        id: String,
+    },
 }
 
-#[derive(Serialize)]
-struct GroqMessage {
-    role: String,
-    content: String,
+#[derive(Subcommand)]
+enum ChatCommand {
+    /// Download a YouTube live VOD's chat replay
+    Fetch {
+        #[command(flatten)]
+        video: VideoArg,
+        /// Stop after this many messages
+        #[arg(long, default_value_t = 50_000)]
+        max_messages: usize,
+    },
+    /// Load a saved chat replay: yt-dlp live_chat.json or a Twitch chat export
+    Import {
+        #[command(flatten)]
+        video: VideoArg,
+        /// Chat file to import
+        #[arg(short, long)]
+        file: std::path::PathBuf,
+        /// Seconds to add to every message time (negative when the upload was trimmed)
+        #[arg(long, default_value_t = 0.0, allow_hyphen_values = true)]
+        offset: f64,
+    },
+    /// Show when chat was busiest and what it was saying
+    Peaks {
+        #[command(flatten)]
+        video: VideoArg,
+        /// Number of moments to show
+        #[arg(long, default_value_t = 5)]
+        top: usize,
+    },
+    /// Ask a question about the stream and its chat
+    Ask {
+        #[command(flatten)]
+        video: VideoArg,
+        /// Question, e.g. "when did chat go wild?" or "what did viewers ask?"
+        #[arg(short, long)]
+        question: String,
+    },
 }
 
-#[derive(Deserialize, Debug)]
-struct GroqResponse {
-    choices: Vec<GroqChoice>,
+/// One line per video of a collection: its label and file URIs, or what went wrong
+fn print_indexed(indexed: &[pipeline::IndexedVideo]) {
+    for video in indexed {
+        match &video.parts {
+            Some(Ok(parts)) => {
+                let uris: Vec<&str> = parts.iter().map(|p| p.file_uri.as_str()).collect();
+                println!("{}\t{}", video.transcript.label(), uris.join(" "));
+            }
+            Some(Err(e)) => println!("{}\tupload failed: {}", video.transcript.label(), e),
+            None => println!("{}\tcached", video.transcript.label()),
+        }
+    }
 }
 
-#[derive(Deserialize, Debug)]
-struct GroqChoice {
-    message: GroqResponseMessage,
+/// Write command output to a file when `--out` is given, otherwise to stdout
+fn write_output(out: Option<&std::path::Path>, contents: &str) -> Result<()> {
+    match out {
+        Some(path) => {
+            std::fs::write(path, contents)
+                .with_context(|| format!("Failed to write {}", path.display()))?;
+            status!("💾 Written to {}", path.display());
+        }
+        None => println!("{}", contents),
+    }
+    Ok(())
 }
 
-#[derive(Deserialize, Debug)]
-struct GroqResponseMessage {
-    content: String,
+/// Walk the user through the proposed categories, letting them accept, rename, or skip each one
+fn review_proposals(proposals: Vec<(String, Vec<transcript::Transcript>)>, accept_all: bool) -> Result<organize::Categories> {
+    let mut categories = organize::Categories::default();
+    for (name, members) in proposals {
+        let mut path = name;
+        if !accept_all {
+            println!("\n📁 {} ({} video(s))", path, members.len());
+            for transcript in &members {
+                println!("   - {}", transcript.label());
+            }
+            print!("   Enter to accept, type a new name, or 's' to skip: ");
+            io::stdout().flush()?;
+
+            let mut line = String::new();
+            io::stdin().lock().read_line(&mut line)?;
+            match line.trim() {
+                "" => {}
+                "s" | "S" => continue,
+                renamed => path = renamed.to_string(),
+            }
+        }
+        categories.add(path, members.into_iter().map(|t| t.video_id).collect());
+    }
+    Ok(categories)
 }
 
-// ===== Provider Selection =====
-
-#[derive(Debug, Clone)]
-enum LlmProvider {
-    Groq,
-    Gemini,
+/// A line typed after `prompt`, trimmed; `None` once stdin closes
+fn read_prompted(prompt: &str) -> Result<Option<String>> {
+    eprint!("{}", prompt);
+    io::stderr().flush()?;
+    let mut line = String::new();
+    if io::stdin().lock().read_line(&mut line)? == 0 {
+        return Ok(None);
+    }
+    Ok(Some(line.trim().to_string()))
 }
 
-// ===== Main Application Logic =====
-
-struct VideoTranscriber {
-    apify_api_key: String,
-    gemini_api_key: String,
-    groq_api_key: String,
-    llm_provider: LlmProvider,
-    client: reqwest::blocking::Client,
+/// Chat about a video until the user types `exit` or closes stdin
+fn chat_repl(transcriber: &VideoTranscriber, label: &str, mut chat: conversation::VideoChat) -> Result<()> {
+    status!(
+        "💬 Chatting about \"{}\". Follow-ups remember the conversation; 'exit' or Ctrl-D quits.",
+        label
+    );
+    while let Some(question) = read_prompted("\nyou> ")? {
+        match question.as_str() {
+            "" => continue,
+            "exit" | "quit" => break,
+            _ => {}
+        }
+        match chat.ask(&question).and_then(|answer| transcriber.moderate(&answer)) {
+            Ok(answer) => println!("\n{}", answer),
+            Err(e) => status!("⚠️  {:#}", e),
+        }
+    }
+    Ok(())
 }
 
-impl VideoTranscriber {
-    fn new() -> Result<Self> {
-        dotenv::dotenv().ok(); // Load .env file if it exists
-
-        let apify_api_key = env::var("APIFY_API_KEY")
-            .context("APIFY_API_KEY environment variable not set")?;
-
-        let gemini_api_key = env::var("GEMINI_API_KEY").unwrap_or_default();
-        let groq_api_key = env::var("GROQ_API_KEY").unwrap_or_default();
-
-        // Determine which provider to use
-        let provider_str = env::var("LLM_PROVIDER").unwrap_or_else(|_| "groq".to_string());
-        let llm_provider = match provider_str.to_lowercase().as_str() {
-            "gemini" => LlmProvider::Gemini,
-            "groq" => LlmProvider::Groq,
-            _ => {
-                println!("⚠️  Unknown LLM_PROVIDER '{}', defaulting to Groq", provider_str);
-                LlmProvider::Groq
-            }
-        };
+/// Ask a creator's persona questions until the user types `exit` or closes stdin
+fn persona_repl(channel: &str, mut chat: persona::PersonaChat) -> Result<()> {
+    status!("Ask what {} thinks about anything. Type 'exit' to quit.", channel);
+    while let Some(question) = read_prompted("\nyou> ")? {
+        match question.as_str() {
+            "" => continue,
+            "exit" | "quit" => break,
+            _ => {}
+        }
+        match chat.ask(&question) {
+            Ok((answer, sources)) => print!("{}", persona::render_answer(&answer, &sources)),
+            Err(e) => status!("⚠️  {}", e),
+        }
+    }
+    Ok(())
+}
 
-        // Validate that the selected provider has an API key
-        match llm_provider {
-            LlmProvider::Gemini if gemini_api_key.is_empty() => {
-                anyhow::bail!("GEMINI_API_KEY is required when LLM_PROVIDER=gemini");
-            }
-            LlmProvider::Groq if groq_api_key.is_empty() => {
-                anyhow::bail!("GROQ_API_KEY is required when LLM_PROVIDER=groq");
+/// Watch along with a video: each line is a question, and the playhead advances in real time
+/// from `at`, so the window slides with the video. `@MM:SS` resyncs it.
+fn companion_repl(transcriber: &VideoTranscriber, transcript: &transcript::Transcript, at: f64, window: f64) -> Result<()> {
+    status!(
+        "🎧 Companion for \"{}\" from {}. Ask away; `@MM:SS` resyncs the playhead, Ctrl-D quits.",
+        transcript.label(),
+        transcript::format_timestamp(at)
+    );
+    let (mut synced_at, mut synced) = (at, Instant::now());
+    while let Some(line) = read_prompted("❓ ")? {
+        if line.is_empty() {
+            continue;
+        }
+        if let Some(position) = line.strip_prefix('@') {
+            match companion::parse_timestamp(position) {
+                Ok(position) => (synced_at, synced) = (position, Instant::now()),
+                Err(e) => status!("⚠️  {}", e),
             }
-            _ => {}
+            continue;
         }
 
-        let client = reqwest::blocking::Client::builder()
-            .timeout(Duration::from_secs(300))
-            .build()?;
+        let playhead = synced_at + synced.elapsed().as_secs_f64();
+        status!("⏱️  {}", transcript::format_timestamp(playhead));
+        match transcriber.ask_companion(transcript, playhead, window, &line) {
+            Ok(answer) => println!("{}\n", transcriber.moderate(&answer)?),
+            Err(e) => status!("⚠️  {:#}", e),
+        }
+    }
+    Ok(())
+}
 
-        println!("🤖 Using LLM provider: {:?}", llm_provider);
+fn main() -> Result<()> {
+    // Progress goes to stderr so stdout stays clean for piping
+    progress::set_status_handler(|line| eprintln!("{}", line));
+    let matches = Cli::command().get_matches();
+    let command_name = matches.subcommand_name().unwrap_or_default().to_string();
+    let cli = Cli::from_arg_matches(&matches)?;
+
+    let cancel = CancellationToken::new(cli.timeout.map(Duration::from_secs));
+    let on_interrupt = cancel.clone();
+    ctrlc::set_handler(move || {
+        // A second Ctrl-C stops waiting for cleanup
+        if on_interrupt.check().is_err() {
+            std::process::exit(130);
+        }
+        status!("\n🛑 Cancelling...");
+        on_interrupt.cancel();
+    })
+    .context("Failed to install the Ctrl-C handler")?;
+
+    let format = cli.command.output_format();
+    let result = run(cli, cancel);
+    // The CLI's transcriber keeps its state in the default data directory, and so do its logs
+    let data_dir = config::data_dir();
+    if let Err(error) = &result {
+        if let Ok(data_dir) = &data_dir {
+            debuglog::record_error(data_dir, &std::env::args().collect::<Vec<_>>(), error);
+        }
+        print!("{}", launcher::render_error(format, error));
+    }
+    if let Ok(data_dir) = &data_dir {
+        telemetry::record(data_dir, &command_name, result.as_ref().err());
+    }
+    if let Err(error) = &result {
+        if cancel::cancelled(error) == Some(cancel::Cancelled::Interrupted) {
+            status!("Error: {:#}", error);
+            std::process::exit(130);
+        }
+    }
+    result
+}
 
-        Ok(Self {
-            apify_api_key,
-            gemini_api_key,
-            groq_api_key,
-            llm_provider,
-            client,
-        })
+fn run(mut cli: Cli, cancel: CancellationToken) -> Result<()> {
+    // Setup commands must work before any API keys are configured
+    match cli.command {
+        Commands::Init => return init::run_wizard(),
+        Commands::Completions { shell } => {
+            init::print_completions(shell);
+            return Ok(());
+        }
+        Commands::Telemetry { action } => return run_telemetry(action),
+        Commands::ReportBug { out } => {
+            let path = bugreport::create_bundle(&config::data_dir()?, out)?;
+            status!("🐞 Wrote {}", path.display());
+            status!("   Review its contents, then attach it to an issue on GitHub.");
+            return Ok(());
+        }
+        Commands::Daemon { action } => return run_daemon(action),
+        _ => {}
     }
 
-    /// Fetch transcript from YouTube using Apify YouTube Scraper
-    fn fetch_transcript(&self, youtube_url: &str) -> Result<String> {
-        println!("📥 Fetching transcript from YouTube using Apify...");
-
-        // Step 1: Start the Apify actor run
-        let run_input = ApifyRunInput {
-            start_urls: vec![ApifyUrl {
-                url: youtube_url.to_string(),
-            }],
-            max_results: 1,
-        };
-
-        let run_url = format!(
-            "https://api.apify.com/v2/acts/streamers~youtube-scraper/runs?token={}",
-            self.apify_api_key
-        );
-
-        let run_response = self
-            .client
-            .post(&run_url)
-            .json(&run_input)
-            .send()
-            .context("Failed to start Apify actor run")?;
-
-        if !run_response.status().is_success() {
-            let status = run_response.status();
-            let body = run_response.text().unwrap_or_default();
-            anyhow::bail!("Apify run failed with status {}: {}", status, body);
-        }
-
-        let run_data: serde_json::Value = run_response
-            .json()
-            .context("Failed to parse Apify run response")?;
-
-        let run_id = run_data["data"]["id"]
-            .as_str()
-            .context("Failed to get run ID from Apify response")?;
-
-        println!("⏳ Waiting for Apify to process the video (run ID: {})...", run_id);
-
-        // Step 2: Wait for the run to complete
-        let mut attempts = 0;
-        let max_attempts = 60; // 5 minutes max wait time
-        loop {
-            std::thread::sleep(Duration::from_secs(5));
-            attempts += 1;
-
-            let status_url = format!(
-                "https://api.apify.com/v2/acts/streamers~youtube-scraper/runs/{}?token={}",
-                run_id, self.apify_api_key
-            );
+    // Cached-video questions can skip client and store setup entirely via the daemon. Others
+    // stay in-process: a scrape can outlast the daemon wait, and answering locally after it
+    // gives up would start a second one.
+    #[cfg(unix)]
+    if let Commands::Ask {
+        video,
+        question,
+        reindex: false,
+        related: false,
+        second_opinion: None,
+        ensemble: None,
+        style: None,
+        max_words: None,
+        answer_lang: None,
+        format,
+        audio: false,
+        agent: false,
+        show_steps: false,
+        strategy: None,
+        chunked: false,
+        all: false,
+        tag: None,
+        session: None,
+    } = &mut cli.command
+    {
+        // The daemon answers without retrieval details, which JSON output includes, with its
+        // own tier and transcript source, and without this command's deadline
+        if daemon::enabled()
+            && !ensemble::enabled_for("ask")
+            && *format != OutputFormat::Json
+            && cli.tier.is_none()
+            && cli.source.is_none()
+            && cli.timeout.is_none()
+        {
+            let url = video.resolve()?;
+            if daemon::is_cached(&url) {
+                if let Some((answer, answer_id)) = daemon::ask(&url, question)? {
+                    status!("⚡ Answered by the daemon");
+                    print_rated_answer(*format, &answer, &answer_id, question, &url);
+                    return Ok(());
+                }
+            }
+            *video = VideoArg::resolved(url);
+        }
+    }
 
-            let status_response = self
-                .client
-                .get(&status_url)
-                .send()
-                .context("Failed to check Apify run status")?;
+    // A dot per Apify status check, so a long scrape visibly isn't stuck
+    let mut transcriber = VideoTranscriber::with_source(cancel, cli.source)?.with_progress(|event| {
+        if let progress::Progress::ApifyRunning { .. } = event {
+            eprint!(".");
+            let _ = io::stderr().flush();
+        }
+    });
+    transcriber.tier = cli.tier;
 
-            let status_data: serde_json::Value = status_response
-                .json()
-                .context("Failed to parse Apify status response")?;
+    match cli.command {
+        Commands::Index {
+            video,
+            max_videos,
+            workers,
+            reindex,
+            playlist,
+            channel,
+            since,
+            from_file,
+            results,
+            json,
+        } => {
+            if let Some(path) = from_file {
+                let urls = batch::read_url_list(&path)?;
+                let summary = transcriber.index_batch(&urls, reindex, workers);
+                let results = results.unwrap_or_else(|| path.with_extension("results.json"));
+                let summary_json = serde_json::to_string_pretty(&summary)?;
+                std::fs::write(&results, &summary_json)
+                    .with_context(|| format!("Failed to write {}", results.display()))?;
+                if json {
+                    println!("{}", summary_json);
+                } else {
+                    for item in &summary.videos {
+                        let detail = match (&item.error, &item.title) {
+                            (Some(error), _) => format!("{}: {}", item.status.label(), error),
+                            (None, Some(title)) => format!("{}\t{}", item.status.label(), title),
+                            (None, None) => item.status.label().to_string(),
+                        };
+                        println!("{}\t{}", item.url, detail);
+                    }
+                }
+                status!(
+                    "\n✨ Indexed {}, {} already indexed, {} duplicate(s), {} failed, of {} in {:.0}s",
+                    summary.indexed,
+                    summary.already_indexed,
+                    summary.duplicates,
+                    summary.failed,
+                    summary.total,
+                    (summary.finished_at - summary.started_at).num_milliseconds() as f64 / 1000.0
+                );
+                status!("📄 Results written to {}", results.display());
+                transcriber.cancel_token().check()?;
+                if summary.failed > 0 {
+                    anyhow::bail!("{} of {} video(s) could not be indexed", summary.failed, summary.total);
+                }
+                return Ok(());
+            }
+            let since = since.as_deref().map(mentions::parse_since).transpose()?;
+            if !channel.is_empty() {
+                let channels = channel.iter().map(|c| pipeline::channel_url(c)).collect::<Result<Vec<_>>>()?;
+                let results = transcriber.index_channels(&channels, max_videos, workers, since)?;
+                let mut failed = 0;
+                let mut total = 0;
+                for (_, indexed) in &results {
+                    match indexed {
+                        Ok(indexed) => {
+                            total += indexed.len();
+                            print_indexed(indexed);
+                        }
+                        Err(_) => failed += 1,
+                    }
+                }
+                status!("\n✨ Indexed {} video(s) from {} channel(s)", total, results.len() - failed);
+                if failed > 0 {
+                    anyhow::bail!("{} of {} channel(s) could not be indexed", failed, results.len());
+                }
+                return Ok(());
+            }
+            if let Some(url) = playlist {
+                let indexed = transcriber.index_playlist(&url, reindex, workers)?;
+                let failed = indexed
+                    .videos
+                    .iter()
+                    .filter(|(_, outcome)| matches!(outcome, PlaylistOutcome::Failed(_)))
+                    .count();
+                status!(
+                    "\n✨ Indexed {} of {} video(s) from \"{}\"",
+                    indexed.videos.len() - failed,
+                    indexed.videos.len(),
+                    indexed.playlist.title.as_deref().unwrap_or(&indexed.playlist.playlist_id)
+                );
+                for (entry, outcome) in &indexed.videos {
+                    match outcome {
+                        PlaylistOutcome::Indexed(parts) => {
+                            let uris: Vec<&str> = parts.iter().map(|p| p.file_uri.as_str()).collect();
+                            println!("{}\t{}", entry.label(), uris.join(" "));
+                        }
+                        PlaylistOutcome::AlreadyIndexed => println!("{}\talready indexed", entry.label()),
+                        PlaylistOutcome::Failed(e) => println!("{}\tfailed: {}", entry.label(), e),
+                    }
+                }
+                status!("\nList them with: cargo run -- list --playlist {}", indexed.playlist.playlist_id);
+                return Ok(());
+            }
 
-            let status = status_data["data"]["status"]
-                .as_str()
-                .context("Failed to get status from Apify response")?;
+            let url = video.resolve()?;
+            if pipeline::is_collection_url(&url) {
+                status!("🚀 Indexing playlist/channel: {}", url);
+                let indexed = transcriber.index_collection(&url, max_videos, workers, since)?;
+                status!("\n✨ Indexed {} video(s)", indexed.len());
+                print_indexed(&indexed);
+                return Ok(());
+            }
 
-            match status {
-                "SUCCEEDED" => break,
-                "FAILED" | "ABORTED" | "TIMED-OUT" => {
-                    anyhow::bail!("Apify run failed with status: {}", status);
-                }
+            status!("🚀 Indexing video: {}", url);
+            let parts = transcriber.index_video(&url, reindex)?;
+            status!("\n✨ Video successfully indexed!");
+            match parts.as_slice() {
+                [single] => println!("File URI: {}", single.file_uri),
                 _ => {
-                    if attempts >= max_attempts {
-                        anyhow::bail!("Apify run timed out after {} attempts", max_attempts);
+                    for part in &parts {
+                        println!("File URI ({}): {}", part.label(), part.file_uri);
                     }
-                    print!(".");
-                    std::io::Write::flush(&mut std::io::stdout())?;
                 }
             }
+            status!("\nYou can now ask questions using:");
+            status!("  cargo run -- ask --url \"{}\" --question \"Your question here\"", url);
         }
-
-        println!("\n✅ Apify processing complete!");
-
-        // Step 3: Get the dataset items
-        let dataset_url = format!(
-            "https://api.apify.com/v2/actor-runs/{}/dataset/items?token={}",
-            run_id, self.apify_api_key
-        );
-
-        let dataset_response = self
-            .client
-            .get(&dataset_url)
-            .send()
-            .context("Failed to fetch Apify dataset")?;
-
-        let items: Vec<ApifyDatasetItem> = dataset_response
-            .json()
-            .context("Failed to parse Apify dataset items")?;
-
-        if items.is_empty() {
-            anyhow::bail!("No transcript found for the video. The video might not have captions.");
+        Commands::Ask {
+            video,
+            question,
+            reindex,
+            related,
+            second_opinion,
+            ensemble,
+            style,
+            max_words,
+            answer_lang,
+            format,
+            audio,
+            agent,
+            show_steps,
+            strategy,
+            chunked,
+            all,
+            tag,
+            session,
+        } => {
+            if all || tag.is_some() {
+                transcriber.answer_style = style;
+                transcriber.max_words = max_words.map(|n| n as usize);
+                transcriber.answer_language = answer_lang;
+                let answer = match transcriber.ensemble_panel("ask", ensemble)? {
+                    Some(panel) => {
+                        let context = transcriber.cross_video_context(&question, tag.as_deref())?;
+                        let mut merged = transcriber.ensemble_complete(&panel, context.system, &context.prompt, &question)?;
+                        merged.answer = transcriber.enforce_style(context.with_sources(merged.answer))?;
+                        merged.render()
+                    }
+                    None => transcriber.enforce_style(transcriber.ask_across(&question, tag.as_deref())?)?,
+                };
+                print_answer(format, &transcriber.moderate(&answer)?, &question, None);
+                return Ok(());
+            }
+            let url = match &session {
+                Some(session) if !video.given() => transcriber.session_url(session)?,
+                _ => video.resolve()?,
+            };
+            status!("🚀 Processing question for video: {}", url);
+            let usage = (format == OutputFormat::Json).then(|| {
+                let meter = UsageMeter::default();
+                transcriber.subscribe(meter.clone());
+                meter
+            });
+            transcriber.answer_style = style;
+            transcriber.max_words = max_words.map(|n| n as usize);
+            transcriber.answer_language = answer_lang;
+            transcriber.strategy = if chunked { Some(Strategy::Retrieval) } else { strategy };
+            if let Some(session) = session {
+                let transcript = transcriber.load_transcript(&url, reindex)?;
+                let answer = conversation::VideoChat::resume(&transcriber, transcript, &session)?.ask(&question)?;
+                let answer = transcriber.enforce_style(answer)?;
+                print_answer(format, &transcriber.moderate(&answer)?, &question, Some(&url));
+                return Ok(());
+            }
+            if audio {
+                let answer = transcriber.enforce_style(transcriber.ask_audio(&url, &question)?)?;
+                print_answer(format, &transcriber.moderate(&answer)?, &question, Some(&url));
+                return Ok(());
+            }
+            let panel = if agent { None } else { transcriber.ensemble_panel("ask", ensemble)? };
+            let transcript = transcriber.load_transcript(&url, reindex)?;
+            let answer = if agent {
+                let answer = transcriber.agent_answer(&question, Some(&transcript), show_steps)?;
+                let answer = transcriber.enforce_style(answer.render())?;
+                print_answer(format, &transcriber.moderate(&answer)?, &question, Some(&url));
+                answer
+            } else if let Some(panel) = panel {
+                let mut merged = transcriber.ensemble_answer(&transcript, &question, &panel)?;
+                merged.answer = transcriber.enforce_style(merged.answer)?;
+                print_answer(format, &transcriber.moderate(&merged.render())?, &question, Some(&url));
+                merged.answer
+            } else if let Some(model) = second_opinion {
+                let opinion = transcriber.second_opinion(&transcript, &question, &model)?;
+                print_answer(format, &transcriber.moderate(&opinion.render())?, &question, Some(&url));
+                opinion.first.answer
+            } else {
+                let started = Instant::now();
+                let logged = transcriber.answer_with_feedback(&transcript, &question)?;
+                let answer = transcriber.enforce_style(logged.answer)?;
+                let moderated = transcriber.moderate(&answer)?;
+                if format == OutputFormat::Json {
+                    let excerpts: Vec<&Chunk> = logged.excerpts.iter().collect();
+                    let mut output = Answer::new(&question, moderated).for_video(&transcript);
+                    output.answer_id = Some(logged.id.clone());
+                    output.strategy = Some(logged.strategy);
+                    output.citations = logged.excerpts.iter().map(Citation::from).collect();
+                    output.retrieval = logged.retrieval.map(|retrieval| retrieval.to_json(&excerpts));
+                    output.usage = usage.as_ref().map(UsageMeter::report);
+                    output.total_ms = Some(retrieval::millis(started.elapsed()));
+                    println!("{}", serde_json::to_string_pretty(&output)?);
+                } else {
+                    print_rated_answer(format, &moderated, &logged.id, &question, &url);
+                }
+                answer
+            };
+
+            if related {
+                let suggestions =
+                    transcriber.related_to_answer(&transcript.video_id, &question, &answer, 3)?;
+                status!("\n📚 Related videos in your library:");
+                print!("{}", related::render_related(&suggestions));
+            }
         }
-
-        let item = &items[0];
-        let transcript = item
-            .text
-            .as_ref()
-            .context("No transcript text found in the video data")?;
-
-        if let Some(title) = &item.title {
-            println!("📺 Video Title: {}", title);
+        Commands::Companion {
+            video,
+            at,
+            question,
+            window,
+        } => {
+            let url = video.resolve()?;
+            let transcript = transcriber.load_transcript(&url, false)?;
+            match question {
+                Some(question) => {
+                    let answer = transcriber.ask_companion(&transcript, at, window, &question)?;
+                    status!("\n💡 Answer:");
+                    println!("{}", transcriber.moderate(&answer)?);
+                }
+                None => companion_repl(&transcriber, &transcript, at, window)?,
+            }
         }
-        if let Some(channel) = &item.channel_name {
-            println!("👤 Channel: {}", channel);
+        Commands::Query {
+            video,
+            question,
+            reindex,
+            ensemble,
+            style,
+            max_words,
+            answer_lang,
+            format,
+        } => {
+            let url = video.resolve()?;
+            status!("🚀 Querying video: {}", url);
+            transcriber.answer_style = style;
+            transcriber.max_words = max_words.map(|n| n as usize);
+            transcriber.answer_language = answer_lang;
+            let answer = match transcriber.ensemble_panel("query", ensemble)? {
+                Some(panel) => {
+                    let transcript = transcriber.load_transcript(&url, reindex)?;
+                    let mut merged = transcriber.ensemble_answer(&transcript, &question, &panel)?;
+                    merged.answer = transcriber.enforce_style(merged.answer)?;
+                    merged.render()
+                }
+                None => transcriber.enforce_style(transcriber.query_video(&url, &question, reindex)?)?,
+            };
+            let answer = transcriber.moderate(&answer)?;
+            if format == OutputFormat::Text {
+                status!("\n💡 Answer:");
+            }
+            print_answer(format, &answer, &question, Some(&url));
         }
-        println!("📝 Transcript length: {} characters", transcript.len());
-
-        Ok(transcript.clone())
-    }
+        Commands::List {
+            json,
+            no_check,
+            refresh,
+            playlist,
+            playlists,
+        } => {
+            if playlists {
+                let playlists = transcriber.registry.playlists()?;
+                if json {
+                    let playlists: Vec<_> = playlists
+                        .into_iter()
+                        .map(|(playlist, videos)| serde_json::json!({ "playlist": playlist, "videos": videos }))
+                        .collect();
+                    println!("{}", serde_json::to_string_pretty(&playlists)?);
+                } else if playlists.is_empty() {
+                    println!("No indexed playlists yet. Index one with `index --playlist ...`.");
+                } else {
+                    print!("{}", listing::render_playlists(&playlists));
+                }
+                return Ok(());
+            }
+            let videos = match playlist {
+                Some(playlist) => transcriber.list_playlist(&playlist, !no_check)?,
+                None => transcriber.list_videos(!no_check)?,
+            };
+            if json {
+                println!("{}", serde_json::to_string_pretty(&videos)?);
+            } else if videos.is_empty() {
+                println!("No indexed videos yet. Index one with `index --url ...`.");
+            } else {
+                print!("{}", listing::render_list(&videos));
+            }
 
-    /// Upload transcript to Gemini File API using resumable upload
-    fn upload_to_gemini(&self, transcript: &str, video_url: &str) -> Result<String> {
-        println!("☁️  Uploading transcript to Gemini File API...");
-
-        let video_id = self.extract_video_id(video_url)?;
-        let file_name = format!("youtube_transcript_{}.txt", video_id);
-        let transcript_bytes = transcript.as_bytes();
-        let num_bytes = transcript_bytes.len();
-
-        // Step 1: Start the resumable upload
-        let init_url = format!(
-            "https://generativelanguage.googleapis.com/upload/v1beta/files?key={}",
-            self.gemini_api_key
-        );
-
-        let metadata = serde_json::json!({
-            "file": {
-                "display_name": file_name,
-            }
-        });
-
-        let init_response = self
-            .client
-            .post(&init_url)
-            .header("X-Goog-Upload-Protocol", "resumable")
-            .header("X-Goog-Upload-Command", "start")
-            .header("X-Goog-Upload-Header-Content-Length", num_bytes.to_string())
-            .header("X-Goog-Upload-Header-Content-Type", "text/plain")
-            .header("Content-Type", "application/json")
-            .json(&metadata)
-            .send()
-            .context("Failed to initiate file upload to Gemini")?;
-
-        if !init_response.status().is_success() {
-            let status = init_response.status();
-            let body = init_response.text().unwrap_or_default();
-            anyhow::bail!("Gemini upload init failed with status {}: {}", status, body);
-        }
-
-        // Get the upload URL from the response header
-        let upload_url = init_response
-            .headers()
-            .get("x-goog-upload-url")
-            .context("No upload URL in response headers")?
-            .to_str()
-            .context("Invalid upload URL header")?;
-
-        println!("   Upload session created, sending file data...");
-
-        // Step 2: Upload the actual file bytes
-        let upload_response = self
-            .client
-            .post(upload_url)
-            .header("Content-Length", num_bytes.to_string())
-            .header("X-Goog-Upload-Offset", "0")
-            .header("X-Goog-Upload-Command", "upload, finalize")
-            .body(transcript_bytes.to_vec())
-            .send()
-            .context("Failed to upload file bytes to Gemini")?;
-
-        if !upload_response.status().is_success() {
-            let status = upload_response.status();
-            let body = upload_response.text().unwrap_or_default();
-            anyhow::bail!("Gemini file upload failed with status {}: {}", status, body);
-        }
-
-        let file_response: GeminiFileResponse = upload_response
-            .json()
-            .context("Failed to parse Gemini file upload response")?;
-
-        println!("✅ File uploaded: {}", file_response.file.name);
-        println!("   URI: {}", file_response.file.uri);
-        println!("   State: {}", file_response.file.state);
-
-        // Wait for file to be processed (state should be ACTIVE)
-        if file_response.file.state != "ACTIVE" {
-            println!("⏳ Waiting for file to be processed...");
-            std::thread::sleep(Duration::from_secs(3));
-        }
-
-        Ok(file_response.file.uri)
-    }
+            let stale: Vec<_> = videos.iter().filter(|listed| listed.stale.is_some()).collect();
+            if stale.is_empty() {
+                return Ok(());
+            }
+            if !refresh {
+                status!(
+                    "\n⚠️  {} video(s) changed on YouTube since they were indexed; re-index them with `list --refresh`",
+                    stale.len()
+                );
+                return Ok(());
+            }
+            let mut failed = 0;
+            for listed in stale {
+                let label = listed.video.title.as_deref().unwrap_or(&listed.video.video_id);
+                status!("\n🔄 Re-indexing {}...", label);
+                if let Err(e) = transcriber.index_video(&listed.video.url, true) {
+                    if cancel::cancelled(&e).is_some() {
+                        return Err(e);
+                    }
+                    status!("❌ Could not re-index {}: {:#}", label, e);
+                    failed += 1;
+                }
+            }
+            if failed > 0 {
+                anyhow::bail!("{} video(s) could not be re-indexed", failed);
+            }
+        }
+        Commands::Delete { video } => {
+            let url = video.resolve()?;
+            let deleted = transcriber.delete_video(&url)?;
+            let mut removed = Vec::new();
+            if deleted.files > 0 {
+                removed.push(format!("{} Gemini file(s)", deleted.files));
+            }
+            if deleted.registered {
+                removed.push("registry entry".to_string());
+            }
+            if deleted.cached {
+                removed.push("cached transcript".to_string());
+            }
+            println!("🗑️  Deleted {}: {}", deleted.label, removed.join(", "));
+        }
+        Commands::AskLibrary {
+            question,
+            per_video,
+            concurrency,
+            ensemble,
+            style,
+            max_words,
+            answer_lang,
+            format,
+            agent,
+            show_steps,
+        } => {
+            transcriber.answer_style = style;
+            transcriber.max_words = max_words.map(|n| n as usize);
+            transcriber.answer_language = answer_lang;
+            if per_video {
+                let answers = transcriber.ask_library_per_video(&question, concurrency)?;
+                status!("\n📊 Per-video answers:");
+                println!("{}", library::comparison_table(&answers));
+            } else {
+                let panel = if agent { None } else { transcriber.ensemble_panel("ask-library", ensemble)? };
+                let answer = match panel {
+                    None if agent => {
+                        let answer = transcriber.agent_answer(&question, None, show_steps)?;
+                        transcriber.enforce_style(answer.render())?
+                    }
+                    Some(panel) => {
+                        let prompt = transcriber.library_prompt(&question)?;
+                        let mut merged = transcriber.ensemble_complete(&panel, ANSWER_SYSTEM_PROMPT, &prompt, &question)?;
+                        merged.answer = transcriber.enforce_style(merged.answer)?;
+                        merged.render()
+                    }
+                    None => transcriber.enforce_style(transcriber.ask_library_combined(&question)?)?,
+                };
+                let answer = transcriber.moderate(&answer)?;
+                if format == OutputFormat::Text {
+                    status!("\n💡 Answer:");
+                }
+                print_answer(format, &answer, &question, None);
+            }
+        }
+        Commands::Consensus {
+            product,
+            url,
+            json,
+            concurrency,
+        } => {
+            let report = transcriber.consensus(&product, &url, concurrency)?;
+            if json {
+                println!("{}", serde_json::to_string_pretty(&report)?);
+            } else {
+                println!("{}", consensus::render_report(&report));
+            }
+        }
+        Commands::Debate {
+            video,
+            speakers,
+            json,
+        } => {
+            let url = video.resolve()?;
+            let analysis = transcriber.analyze_debate(&url, &speakers)?;
+            if json {
+                println!("{}", serde_json::to_string_pretty(&analysis)?);
+            } else {
+                println!("{}", debate::render_debate(&analysis));
+            }
+        }
+        Commands::Finance { video, format, out } => {
+            let url = video.resolve()?;
+            let call = transcriber.analyze_earnings_call(&url)?;
+            let rendered = match format {
+                FinanceFormat::Json => serde_json::to_string_pretty(&call)?,
+                FinanceFormat::Csv => finance::to_csv(&call),
+            };
+            write_output(out.as_deref(), &rendered)?;
+        }
+        Commands::Slides {
+            video,
+            format,
+            max_slides,
+            out,
+        } => {
+            let url = video.resolve()?;
+            let (transcript, deck) = transcriber.generate_slides(&url, max_slides)?;
+            write_output(out.as_deref(), &slides::render_deck(&deck, &transcript, format))?;
+        }
+        Commands::Chunks { video, question, json } => {
+            let url = video.resolve()?;
+            let transcript = transcriber.load_transcript(&url, false)?;
+            let (map, chunks) = transcriber.transcript_chunks(&transcript)?;
+            let shown: Vec<usize> = match question {
+                Some(question) => {
+                    let top_k = transcriber.retrieval_params().top_k;
+                    let ranked = transcriber.rank_chunks(&question, &chunks, top_k, None)?;
+                    ranked.hits.into_iter().map(|hit| hit.index).collect()
+                }
+                None => (0..chunks.len()).collect(),
+            };
+            status!(
+                "🧩 {} chunk(s) of about {} characters, overlapping by about {}",
+                chunks.len(),
+                map.chunk_chars,
+                map.chunk_overlap
+            );
+            if json {
+                let boundaries: Vec<_> = shown.iter().map(|&index| map.boundaries[index]).collect();
+                println!("{}", serde_json::to_string_pretty(&boundaries)?);
+            } else {
+                print!("{}", chunking::render_chunks(&map, &chunks, &shown));
+            }
+        }
+        Commands::Notes {
+            video,
+            reindex,
+            fresh,
+            out,
+        } => {
+            let url = video.resolve()?;
+            let (transcript, notes, outcome) = transcriber.generate_notes(&url, reindex, fresh)?;
+            match outcome {
+                NotesOutcome::Written | NotesOutcome::Unchanged => {}
+                NotesOutcome::Updated { added, removed } => {
+                    status!("✏️  Updated the notes: {} line(s) added, {} removed", added, removed)
+                }
+            }
+            write_output(out.as_deref(), &notes.render(&transcript))?;
+        }
+        Commands::Summarize {
+            video,
+            length,
+            reindex,
+            out,
+        } => {
+            let url = video.resolve()?;
+            let transcript = transcriber.load_transcript(&url, reindex)?;
+            let summary = transcriber.moderate(&transcriber.summarize(&transcript, length)?)?;
+            write_output(out.as_deref(), &summary)?;
+        }
+        Commands::Chapters {
+            video,
+            count,
+            reindex,
+            json,
+            out,
+        } => {
+            let url = video.resolve()?;
+            let transcript = transcriber.load_transcript(&url, reindex)?;
+            let chapters = transcriber.generate_chapters(&transcript, count.map(|n| n as usize))?;
+            let rendered = if json {
+                serde_json::to_string_pretty(&chapters)?
+            } else {
+                transcriber.moderate(&chapters::render_chapters(&chapters))?
+            };
+            write_output(out.as_deref(), &rendered)?;
+        }
+        Commands::Quiz {
+            video,
+            count,
+            format,
+            reindex,
+            out,
+        } => {
+            let url = video.resolve()?;
+            let transcript = transcriber.load_transcript(&url, reindex)?;
+            let cards = transcriber.generate_quiz(&transcript, count as usize)?;
+            let rendered = flashcards::render_flashcards(&cards, &transcript, format)?;
+            write_output(out.as_deref(), &transcriber.moderate(&rendered)?)?;
+        }
+        Commands::Export {
+            video,
+            format,
+            reindex,
+            out,
+            include_private,
+        } => {
+            let format = format
+                .or_else(|| out.as_deref().and_then(export::TranscriptFormat::from_path))
+                .unwrap_or_default();
+            let url = video.resolve()?;
+            let transcript = transcriber.load_transcript(&url, reindex)?;
+            transcriber
+                .check_shareable(&transcript, include_private)
+                .context("Private videos are only exported with --include-private")?;
+            let rendered = export::render_transcript(&transcript, format)?;
+            match out {
+                Some(path) if path.as_os_str() == "-" => print!("{}", rendered),
+                out => {
+                    let path = out.unwrap_or_else(|| {
+                        std::path::PathBuf::from(format!("{}.{}", transcript.video_id, format.extension()))
+                    });
+                    std::fs::write(&path, rendered).with_context(|| format!("Failed to write {}", path.display()))?;
+                    status!("💾 Wrote the transcript as {} to {}", format.extension(), path.display());
+                }
+            }
+        }
+        Commands::Translate {
+            video,
+            to,
+            reindex,
+            json,
+            out,
+        } => {
+            let url = video.resolve()?;
+            let transcript = transcriber.load_transcript(&url, reindex)?;
+            let translated = transcriber.translate_transcript(&transcript, &to)?;
+            let rendered = if json {
+                serde_json::to_string_pretty(&translated)?
+            } else {
+                translated.timestamped_text(30.0).into_owned()
+            };
+            write_output(out.as_deref(), &transcriber.moderate(&rendered)?)?;
+        }
+        Commands::Report {
+            query,
+            collection,
+            sections,
+            out,
+            include_private,
+        } => {
+            let report =
+                transcriber.research_report(&query, collection.as_deref(), sections as usize, include_private)?;
+            write_output(out.as_deref(), &report.render(&query))?;
+        }
+        Commands::Organize {
+            threshold,
+            yes,
+            show,
+        } => {
+            if !show {
+                let proposals = transcriber.propose_categories(threshold)?;
+                let categories = review_proposals(proposals, yes)?;
+                categories.save(transcriber.data_dir())?;
+                status!("\n💾 Saved {} categories", categories.categories.len());
+            }
+            let categories = organize::Categories::load(transcriber.data_dir())?;
+            println!("{}", organize::render_tree(&categories, &transcriber.cache.list()?));
+        }
+        Commands::Related { video, limit } => {
+            let url = video.resolve()?;
+            let suggestions = transcriber.related_videos(&url, limit)?;
+            print!("{}", related::render_related(&suggestions));
+        }
+        Commands::Trends {
+            channel,
+            topic,
+            csv,
+            include_private,
+        } => {
+            let points = transcriber.topic_trend(&channel, &topic, include_private)?;
+            if csv {
+                print!("{}", trends::render_csv(&points));
+            } else {
+                println!("{}", trends::render_ascii(&points, &topic));
+            }
+        }
+        Commands::Persona { channel, question } => {
+            let mut chat = persona::PersonaChat::new(&transcriber, &channel)?;
+            match question {
+                Some(question) => {
+                    let (answer, sources) = chat.ask(&question)?;
+                    print!("{}", persona::render_answer(&answer, &sources));
+                }
+                None => persona_repl(&channel, chat)?,
+            }
+        }
+        Commands::Contradictions {
+            channel,
+            concurrency,
+        } => {
+            let (videos, found) = transcriber.find_contradictions(&channel, concurrency)?;
+            println!("{}", contradictions::render_contradictions(&videos, &found));
+        }
+        Commands::Watch {
+            channel,
+            alert,
+            interval,
+            max_videos,
+            once,
+        } => {
+            let extra_rules: Vec<config::AlertRule> = if alert.is_empty() {
+                Vec::new()
+            } else {
+                vec![config::AlertRule {
+                    name: "cli".to_string(),
+                    keywords: alert,
+                }]
+            };
+            transcriber.watch(
+                &channel,
+                &extra_rules,
+                Duration::from_secs(interval),
+                max_videos,
+                once,
+            )?;
+        }
+        Commands::Mentions {
+            query,
+            since,
+            watched,
+            out,
+            include_private,
+        } => {
+            let since = mentions::parse_since(&since)?;
+            if watched {
+                for channel in watch::watched_channels(transcriber.data_dir())? {
+                    if let Err(e) = transcriber.watch_pass(&channel, &[], 5) {
+                        status!("⚠️  Could not refresh {}: {}", channel, e);
+                    }
+                }
+            }
+            let found = transcriber.find_brand_mentions(&query, since, include_private)?;
+            write_output(out.as_deref(), &mentions::render_report(&query, since, &found))?;
+        }
+        Commands::Archive { action } => match action {
+            ArchiveCommand::Create {
+                video,
+                question,
+                out,
+                include_private,
+            } => {
+                let url = video.resolve()?;
+                let manifest = transcriber.create_archive(&url, &question, &out, include_private)?;
+                println!(
+                    "🔒 Archived {} file(s) to {} (manifest chain head {})",
+                    manifest.entries.len(),
+                    out.display(),
+                    manifest
+                        .entries
+                        .last()
+                        .map(|entry| entry.chain_sha256.as_str())
+                        .unwrap_or("-")
+                );
+            }
+            ArchiveCommand::Verify { dir } => {
+                let problems = archive::verify_archive(&dir)?;
+                if problems.is_empty() {
+                    println!("✅ Archive intact: every hash matches");
+                } else {
+                    for problem in &problems {
+                        println!("❌ {}", problem);
+                    }
+                    anyhow::bail!("Archive verification failed with {} problem(s)", problems.len());
+                }
+            }
+        },
+        Commands::Cite {
+            video,
+            style,
+            quote,
+        } => {
+            let url = video.resolve()?;
+            let transcript = transcriber.load_transcript(&url, false)?;
+            println!("{}", cite::reference(&transcript, style, chrono::Utc::now()));
+            for passage in &quote {
+                match cite::locate_quote(&transcript, passage) {
+                    Some(seconds) => {
+                        println!(
+                            "\n\"{}\" {}",
+                            passage,
+                            cite::inline_citation(&transcript, seconds, style)
+                        );
+                        if let Some(source @ (CaptionSource::Auto | CaptionSource::Whisper)) =
+                            transcript.source_at(seconds)
+                        {
+                            status!("⚠️  \"{}\" comes from {}; check the wording against the video", passage, source.describe());
+                        }
+                    }
+                    None => status!("⚠️  Passage not found verbatim in the transcript: \"{}\"", passage),
+                }
+            }
+        }
+        Commands::Quote {
+            video,
+            about,
+            count,
+            json,
+            include_unverified,
+        } => {
+            let url = video.resolve()?;
+            let (transcript, found) =
+                transcriber.find_quotes(&url, about.as_deref(), count, include_unverified)?;
+            if json {
+                println!("{}", serde_json::to_string_pretty(&found)?);
+            } else {
+                print!("{}", quotes::render_quotes(&transcript, &found));
+            }
+        }
+        Commands::Feedback { rating, answer_id } => {
+            let mut store = feedback::FeedbackStore::load(transcriber.data_dir())?;
+            let top_k = transcriber.retrieval_params().top_k;
+            let record = store.rate(&answer_id, rating, top_k)?.clone();
+            store.save(transcriber.data_dir())?;
+            if record.chunks.is_empty() {
+                status!(
+                    "✅ Recorded. That answer used the whole transcript, so there are no excerpts to reweight."
+                );
+            } else {
+                status!(
+                    "✅ Recorded {:?} feedback on {} excerpt(s) for \"{}\"",
+                    rating,
+                    record.chunks.len(),
+                    record.question
+                );
+            }
+        }
+        Commands::Instructions { action } => {
+            let mut store = instructions::Instructions::load(transcriber.data_dir())?;
+            match action {
+                InstructionsCommand::Set { target, text } => {
+                    match (target.url, target.collection) {
+                        (Some(url), _) => {
+                            let video_id = transcriber.extract_video_id(&url)?;
+                            store.videos.insert(video_id, text);
+                        }
+                        (None, Some(collection)) => {
+                            store.collections.insert(collection, text);
+                        }
+                        (None, None) => unreachable!("clap requires a target"),
+                    }
+                    store.save(transcriber.data_dir())?;
+                    status!("✅ Instructions saved");
+                }
+                InstructionsCommand::Clear { target } => {
+                    let removed = match (target.url, target.collection) {
+                        (Some(url), _) => {
+                            let video_id = transcriber.extract_video_id(&url)?;
+                            store.videos.remove(&video_id)
+                        }
+                        (None, Some(collection)) => store.collections.remove(&collection),
+                        (None, None) => unreachable!("clap requires a target"),
+                    };
+                    if removed.is_none() {
+                        anyhow::bail!("No instructions were set for that target");
+                    }
+                    store.save(transcriber.data_dir())?;
+                    status!("🗑️  Instructions removed");
+                }
+                InstructionsCommand::Show { url: Some(url) } => {
+                    let video_id = transcriber.extract_video_id(&url)?;
+                    let categories = organize::Categories::load(transcriber.data_dir())?;
+                    for text in store.for_video(&video_id, &categories) {
+                        println!("- {}", text);
+                    }
+                }
+                InstructionsCommand::Show { url: None } => {
+                    for (collection, text) in &store.collections {
+                        println!("[collection {}] {}", collection, text);
+                    }
+                    for (video_id, text) in &store.videos {
+                        let label = transcriber
+                            .cache
+                            .get(video_id)?
+                            .map(|t| t.label().to_string())
+                            .unwrap_or_else(|| video_id.clone());
+                        println!("[video {}] {}", label, text);
+                    }
+                }
+            }
+        }
+        Commands::Access { action } => match action {
+            AccessCommand::Private { video, note } => {
+                let video_id = transcriber.set_private(&video.resolve()?, true, note.as_deref())?;
+                status!("🔒 {} is private", video_id);
+            }
+            AccessCommand::Public { video } => {
+                let video_id = transcriber.set_private(&video.resolve()?, false, None)?;
+                status!("🔓 {} is public", video_id);
+            }
+            AccessCommand::List => {
+                let private: Vec<_> = transcriber.registry.videos()?.into_iter().filter(|video| video.private).collect();
+                if private.is_empty() {
+                    println!("No private videos");
+                }
+                for video in private {
+                    println!(
+                        "{}  {}{}",
+                        video.video_id,
+                        video.title.as_deref().unwrap_or("-"),
+                        video.access_note.map(|note| format!(" — {}", note)).unwrap_or_default()
+                    );
+                }
+            }
+        },
+        Commands::Eval { action } => match action {
+            EvalCommand::Run { set } => {
+                let score = transcriber.eval_run(&set)?;
+                println!("{}", eval::describe(&score));
+            }
+            EvalCommand::Tune { set, dry_run } => {
+                let ranked = transcriber.eval_tune(&set)?;
+                let current = transcriber.retrieval_params();
+                for score in ranked.iter().take(5) {
+                    println!("{}", eval::describe(score));
+                }
+                if let Some(baseline) = ranked.iter().find(|s| s.params == current) {
+                    status!("\nCurrent: {}", eval::describe(baseline));
+                }
 
-    /// Ask a question using Gemini API with the uploaded file
-    fn ask_question(&self, file_uri: &str, question: &str) -> Result<String> {
-        println!("🤔 Asking question: \"{}\"", question);
-
-        let generate_url = format!(
-            "https://generativelanguage.googleapis.com/v1beta/models/gemini-1.5-flash:generateContent?key={}",
-            self.gemini_api_key
-        );
-
-        let request = GeminiGenerateRequest {
-            contents: vec![GeminiContent {
-                parts: vec![
-                    GeminiPart {
-                        text: Some(format!(
-                            "Based on the content of this video transcript, please answer the following question: {}\n\nProvide a detailed and accurate answer based solely on the information in the transcript.",
-                            question
-                        )),
-                        file_data: None,
-                    },
-                    GeminiPart {
-                        text: None,
-                        file_data: Some(GeminiFileDataRef {
-                            file_uri: file_uri.to_string(),
-                            mime_type: "text/plain".to_string(),
-                        }),
-                    },
-                ],
-                role: "user".to_string(),
-            }],
-            tools: None,
-        };
-
-        let response = self
-            .client
-            .post(&generate_url)
-            .json(&request)
-            .send()
-            .context("Failed to generate answer from Gemini")?;
-
-        if !response.status().is_success() {
-            let status = response.status();
-            let body = response.text().unwrap_or_default();
-            anyhow::bail!("Gemini generate failed with status {}: {}", status, body);
-        }
-
-        let generate_response: GeminiGenerateResponse = response
-            .json()
-            .context("Failed to parse Gemini generate response")?;
-
-        let answer = generate_response
-            .candidates
-            .and_then(|candidates| candidates.first().cloned())
-            .and_then(|candidate| candidate.content.parts.first().cloned())
-            .and_then(|part| part.text)
-            .context("No answer generated by Gemini")?;
-
-        Ok(answer)
+                let best = ranked[0].params;
+                if dry_run {
+                    status!("\n(dry run, config not changed)");
+                } else if best == current {
+                    status!("\n✅ Current retrieval parameters are already the best found");
+                } else {
+                    let mut config = config::Config::load()?;
+                    config.retrieval = Some(best);
+                    let path = config.save()?;
+                    status!("\n💾 Saved the best parameters to {}", path.display());
+                }
+            }
+        },
+        Commands::Bench {
+            video,
+            fetch,
+            embed_chunks,
+        } => {
+            let url = video.resolve()?;
+            status!("⏱️  Benchmarking pipeline stages on {}", url);
+            let results = transcriber.bench(&url, fetch, embed_chunks)?;
+            println!("{}", bench::render_bench(&results));
+        }
+        Commands::IngestDir { dir, interval, once } => {
+            transcriber.ingest_dir(&dir, Duration::from_secs(interval), once)?;
+        }
+        Commands::Grep {
+            pattern,
+            url,
+            regex,
+            case_sensitive,
+            format,
+        } => {
+            let pattern = grep::build_pattern(&pattern, regex, !case_sensitive)?;
+            let transcripts = match url {
+                Some(url) => vec![transcriber.load_transcript(&url, false)?],
+                None => transcriber.cache.list()?,
+            };
+            let results: Vec<_> = transcripts
+                .iter()
+                .map(|transcript| (transcript, grep::grep_transcript(transcript, &pattern)))
+                .filter(|(_, hits)| !hits.is_empty())
+                .collect();
+            if results.is_empty() {
+                anyhow::bail!("No matches found");
+            }
+            if format == OutputFormat::Text {
+                for (transcript, hits) in &results {
+                    println!("== {} ({} match(es))", transcript.label(), hits.len());
+                    print!("{}", grep::render_hits(transcript, hits));
+                }
+            } else {
+                print!("{}", launcher::render_hits(format, &results));
+            }
+        }
+        Commands::Search {
+            positional_query,
+            query,
+            url,
+            top_k,
+            format,
+        } => {
+            let query = query.or(positional_query).expect("clap requires a query");
+            let transcripts = match url {
+                Some(url) => vec![transcriber.load_transcript(&url, false)?],
+                None => transcriber.cache.list()?,
+            };
+            let started = Instant::now();
+            let mut chunks = Vec::new();
+            for transcript in &transcripts {
+                chunks.extend(transcriber.transcript_chunks(transcript)?.1);
+            }
+            let chunking_ms = retrieval::millis(started.elapsed());
+            if chunks.is_empty() {
+                anyhow::bail!("No transcripts to search; index a video first");
+            }
+            status!("🔎 Searching {} passage(s) from {} video(s)", chunks.len(), transcripts.len());
+            let mut ranked = transcriber.rank_chunks(&query, &chunks, top_k, None)?;
+            ranked.timings.chunking_ms = chunking_ms;
+            let hits: Vec<&Chunk> = ranked.hits.iter().map(|hit| &chunks[hit.index]).collect();
+            if format == OutputFormat::Json {
+                let mut output = ranked.to_json(&hits);
+                output["query"] = serde_json::json!(query);
+                println!("{}", serde_json::to_string_pretty(&output)?);
+            } else if hits.is_empty() {
+                anyhow::bail!("No passages match \"{}\"", query);
+            } else {
+                for (hit, chunk) in ranked.hits.iter().zip(&hits) {
+                    println!("{:.3}  {}\n        {}\n        {}", hit.score, chunk.citation(), chunk.link(), chunk.snippet(&query, 100));
+                }
+            }
+        }
+        Commands::Chat { action: None, url, session } => {
+            let url = match (url, &session) {
+                (Some(url), _) => url,
+                (None, Some(session)) => transcriber.session_url(session)?,
+                (None, None) => unreachable!("clap requires --url or --session"),
+            };
+            let transcript = transcriber.load_transcript(&url, false)?;
+            let label = transcript.label().to_string();
+            match session {
+                Some(session) => chat_repl(&transcriber, &label, conversation::VideoChat::resume(&transcriber, transcript, &session)?)?,
+                None => chat_repl(&transcriber, &label, conversation::VideoChat::new(&transcriber, transcript))?,
+            }
+        }
+        Commands::Chat { action: Some(action), .. } => match action {
+            ChatCommand::Fetch { video, max_messages } => {
+                let url = video.resolve()?;
+                let video_id = transcriber.extract_video_id(&url)?;
+                status!("💬 Fetching chat replay for {}...", video_id);
+                let messages = transcriber.fetch_chat_replay(&video_id, max_messages)?;
+                let log = chat::ChatLog {
+                    video_id,
+                    source: "youtube".to_string(),
+                    fetched_at: chrono::Utc::now(),
+                    messages,
+                };
+                transcriber.cache.put_chat(&log)?;
+                println!("💬 Stored {} chat messages for {}", log.messages.len(), log.video_id);
+            }
+            ChatCommand::Import { video, file, offset } => {
+                let url = video.resolve()?;
+                let video_id = transcriber.extract_video_id(&url)?;
+                let contents = std::fs::read_to_string(&file)
+                    .with_context(|| format!("Failed to read {}", file.display()))?;
+                let (source, messages) = chat::parse_chat_file(&contents, offset)?;
+                let log = chat::ChatLog {
+                    video_id,
+                    source: source.to_string(),
+                    fetched_at: chrono::Utc::now(),
+                    messages,
+                };
+                transcriber.cache.put_chat(&log)?;
+                println!("💬 Imported {} {} chat messages for {}", log.messages.len(), source, log.video_id);
+            }
+            ChatCommand::Peaks { video, top } => {
+                let url = video.resolve()?;
+                let transcript = transcriber.load_transcript(&url, false)?;
+                let log = transcriber.chat_log(&transcript.video_id)?;
+                let peaks = chat::render_peaks(&transcript, &log, top);
+                if peaks.is_empty() {
+                    println!("Chat never rose much above its usual rate.");
+                } else {
+                    print!("{}", peaks);
+                }
+            }
+            ChatCommand::Ask { video, question } => {
+                let url = video.resolve()?;
+                let transcript = transcriber.load_transcript(&url, false)?;
+                let log = transcriber.chat_log(&transcript.video_id)?;
+                let answer = transcriber.moderate(&transcriber.ask_chat(&transcript, &log, &question)?)?;
+                status!("\n💡 Answer:");
+                println!("{}", answer);
+            }
+        },
+        Commands::Sessions { action } => match action {
+            SessionsCommand::List => {
+                let sessions = transcriber.registry.sessions()?;
+                if sessions.is_empty() {
+                    println!("No saved sessions");
+                }
+                for session in sessions {
+                    println!(
+                        "{}  {}  {} turn(s)  last {}",
+                        session.session_id,
+                        session.url,
+                        session.turns,
+                        session.updated_at.format("%Y-%m-%d %H:%M UTC")
+                    );
+                }
+            }
+            SessionsCommand::Delete { name } => {
+                if !transcriber.registry.delete_session(&name)? {
+                    anyhow::bail!("There is no session '{}'", name);
+                }
+                status!("🗑️  Deleted session '{}'", name);
+            }
+        },
+        Commands::Runs { action } => match action {
+            RunsCommand::List { limit } => {
+                let runs = transcriber.list_runs(limit)?;
+                if runs.is_empty() {
+                    println!("No Apify runs found.");
+                } else {
+                    println!("{:<20} {:<10} {:<26} COST", "RUN ID", "STATUS", "STARTED");
+                    for run in &runs {
+                        println!("{}", apify::render_run_line(run));
+                    }
+                }
+            }
+            RunsCommand::Show { id } => {
+                let run = transcriber.get_run(&id)?;
+                print!("{}", apify::render_run_details(&run));
+            }
+            RunsCommand::Abort { id } => {
+                let run = transcriber.abort_run(&id)?;
+                println!("🛑 Abort requested for run {} (status: {})", run.id, run.status);
+            }
+        },
+        Commands::Serve {
+            bind,
+            port,
+            workers,
+            include_private,
+        } => {
+            server::serve(&transcriber, &format!("{}:{}", bind, port), workers, include_private)?;
+        }
+        Commands::ApifyUsage => {
+            let limits = transcriber.get_account_limits()?;
+            print!("{}", apify::render_account_usage(&limits));
+        }
+        Commands::Init
+        | Commands::Completions { .. }
+        | Commands::Telemetry { .. }
+        | Commands::ReportBug { .. }
+        | Commands::Daemon { .. } => unreachable!("handled above"),
     }
 
-    /// Extract video ID from YouTube URL
-    fn extract_video_id(&self, url: &str) -> Result<String> {
-        // Handle various YouTube URL formats
-        if let Some(v_pos) = url.find("v=") {
-            let id_start = v_pos + 2;
-            let id_end = url[id_start..]
-                .find('&')
-                .map(|pos| id_start + pos)
-                .unwrap_or(url.len());
-            return Ok(url[id_start..id_end].to_string());
-        } else if url.contains("youtu.be/") {
-            if let Some(id_pos) = url.find("youtu.be/") {
-                let id_start = id_pos + 9;
-                let id_end = url[id_start..]
-                    .find('?')
-                    .map(|pos| id_start + pos)
-                    .unwrap_or(url.len());
-                return Ok(url[id_start..id_end].to_string());
-            }
-        }
-
-        anyhow::bail!("Could not extract video ID from URL: {}", url);
-    }
+    Ok(())
+}
 
-    /// Ask a question with transcript directly using Groq
-    fn ask_question_groq(&self, transcript: &str, question: &str) -> Result<String> {
-        println!("🤔 Asking question with Groq: \"{}\"", question);
-
-        let prompt = format!(
-            "Based on the following YouTube video transcript, please answer this question: {}\n\nTranscript:\n{}",
-            question, transcript
-        );
-
-        let request = GroqRequest {
-            model: "llama-3.3-70b-versatile".to_string(), // Fast and capable model
-            messages: vec![
-                GroqMessage {
-                    role: "system".to_string(),
-                    content: "You are a helpful assistant that answers questions about YouTube video transcripts accurately and concisely.".to_string(),
-                },
-                GroqMessage {
-                    role: "user".to_string(),
-                    content: prompt,
-                },
-            ],
-            temperature: 0.3,
-        };
-
-        let response = self
-            .client
-            .post("https://api.groq.com/openai/v1/chat/completions")
-            .header("Authorization", format!("Bearer {}", self.groq_api_key))
-            .header("Content-Type", "application/json")
-            .json(&request)
-            .send()
-            .context("Failed to generate answer from Groq")?;
-
-        if !response.status().is_success() {
-            let status = response.status();
-            let body = response.text().unwrap_or_default();
-            anyhow::bail!("Groq generate failed with status {}: {}", status, body);
-        }
-
-        let groq_response: GroqResponse = response
-            .json()
-            .context("Failed to parse Groq response")?;
-
-        let answer = groq_response
-            .choices
-            .first()
-            .map(|choice| choice.message.content.clone())
-            .context("No answer generated by Groq")?;
-
-        Ok(answer)
+/// Print an answer as text or in the launcher format asked for
+fn print_answer(format: OutputFormat, answer: &str, question: &str, url: Option<&str>) {
+    match format {
+        OutputFormat::Text => println!("{}", answer),
+        format => print!("{}", launcher::render_answer(format, answer, question, url)),
     }
+}
 
-    /// Ask a question with transcript directly using Gemini
-    fn ask_question_gemini(&self, transcript: &str, question: &str) -> Result<String> {
-        println!("🤔 Asking question with Gemini: \"{}\"", question);
-
-        let generate_url = format!(
-            "https://generativelanguage.googleapis.com/v1beta/models/gemini-1.5-flash:generateContent?key={}",
-            self.gemini_api_key
-        );
-
-        let prompt = format!(
-            "Based on the following YouTube video transcript, please answer this question: {}\n\nTranscript:\n{}",
-            question, transcript
-        );
-
-        let request = GeminiGenerateRequest {
-            contents: vec![GeminiContent {
-                parts: vec![GeminiPart {
-                    text: Some(prompt),
-                    file_data: None,
-                }],
-                role: "user".to_string(),
-            }],
-            tools: None,
-        };
-
-        let response = self
-            .client
-            .post(&generate_url)
-            .json(&request)
-            .send()
-            .context("Failed to generate answer from Gemini")?;
-
-        if !response.status().is_success() {
-            let status = response.status();
-            let body = response.text().unwrap_or_default();
-            anyhow::bail!("Gemini generate failed with status {}: {}", status, body);
-        }
-
-        let generate_response: GeminiGenerateResponse = response
-            .json()
-            .context("Failed to parse Gemini generate response")?;
-
-        let answer = generate_response
-            .candidates
-            .and_then(|candidates| candidates.first().cloned())
-            .and_then(|candidate| candidate.content.parts.first().cloned())
-            .and_then(|part| part.text)
-            .context("No answer generated by Gemini")?;
-
-        Ok(answer)
+fn print_rated_answer(format: OutputFormat, answer: &str, answer_id: &str, question: &str, url: &str) {
+    if format != OutputFormat::Text {
+        print!("{}", launcher::render_answer(format, answer, question, Some(url)));
+        return;
     }
+    status!("\n💡 Answer:");
+    println!("{}", answer);
+    status!(
+        "\n🆔 Answer {} — rate it with `feedback good {}` or `feedback bad {}`",
+        answer_id,
+        answer_id,
+        answer_id
+    );
+}
 
-    /// Ask a question with transcript directly (no file upload needed)
-    fn ask_question_direct(&self, transcript: &str, question: &str) -> Result<String> {
-        match self.llm_provider {
-            LlmProvider::Groq => self.ask_question_groq(transcript, question),
-            LlmProvider::Gemini => self.ask_question_gemini(transcript, question),
+#[cfg(unix)]
+fn run_daemon(action: DaemonCommand) -> Result<()> {
+    match action {
+        DaemonCommand::Run => daemon::serve(VideoTranscriber::new(CancellationToken::default())?)?,
+        DaemonCommand::Status => match daemon::status()? {
+            Some(details) => println!("Daemon running: {}", details),
+            None => println!("Daemon not running"),
+        },
+        DaemonCommand::Stop => {
+            if daemon::stop()? {
+                status!("🛑 Daemon stopped");
+            } else {
+                status!("No daemon was running");
+            }
         }
     }
+    Ok(())
+}
 
-    /// Index a video (fetch transcript and upload to Gemini)
-    fn index_video(&self, url: &str) -> Result<String> {
-        let transcript = self.fetch_transcript(url)?;
-        let file_uri = self.upload_to_gemini(&transcript, url)?;
-        Ok(file_uri)
-    }
+#[cfg(not(unix))]
+fn run_daemon(_action: DaemonCommand) -> Result<()> {
+    anyhow::bail!("The daemon uses Unix sockets and is not available on this platform")
+}
 
-    /// Query a video (index + ask question) - uses direct embedding
-    fn query_video(&self, url: &str, question: &str) -> Result<String> {
-        let transcript = self.fetch_transcript(url)?;
-        let answer = self.ask_question_direct(&transcript, question)?;
-        Ok(answer)
+fn run_telemetry(action: TelemetryCommand) -> Result<()> {
+    match action {
+        TelemetryCommand::Enable => {
+            let path = telemetry::set_enabled(true)?;
+            status!("📊 Telemetry enabled (saved in {})", path.display());
+            status!("   Only command names and error categories are counted, locally.");
+            status!("   Nothing is sent anywhere; use `telemetry export` to review and share.");
+        }
+        TelemetryCommand::Disable => {
+            telemetry::set_enabled(false)?;
+            telemetry::Telemetry::clear(&config::data_dir()?)?;
+            status!("🔕 Telemetry disabled and local data deleted");
+        }
+        TelemetryCommand::Status => {
+            println!("Telemetry: {}", if telemetry::enabled() { "enabled" } else { "disabled" });
+            let recorded = telemetry::Telemetry::load(&config::data_dir()?)?;
+            let total: u64 = recorded.commands.values().sum();
+            println!("Recorded since {}: {} run(s)", recorded.since.format("%Y-%m-%d"), total);
+            for (command, count) in &recorded.commands {
+                let failed = recorded.failures.get(command).copied().unwrap_or(0);
+                println!("  {:<20} {:>5} run(s), {} failed", command, count, failed);
+            }
+            for (category, count) in &recorded.errors {
+                println!("  error: {:<13} {:>5}", category, count);
+            }
+        }
+        TelemetryCommand::Export { out } => {
+            let recorded = telemetry::Telemetry::load(&config::data_dir()?)?;
+            write_output(out.as_deref(), &serde_json::to_string_pretty(&recorded)?)?;
+        }
     }
+    Ok(())
 }
 
-fn main() -> Result<()> {
-    let cli = Cli::parse();
-    let transcriber = VideoTranscriber::new()?;
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    match cli.command {
-        Commands::Index { url } => {
-            println!("🚀 Indexing video: {}", url);
-            let file_uri = transcriber.index_video(&url)?;
-            println!("\n✨ Video successfully indexed!");
-            println!("File URI: {}", file_uri);
-            println!("\nYou can now ask questions using:");
-            println!("  cargo run -- ask --url \"{}\" --question \"Your question here\"", url);
-        }
-        Commands::Ask { url, question } => {
-            println!("🚀 Processing question for video: {}", url);
-            let transcript = transcriber.fetch_transcript(&url)?;
-            let answer = transcriber.ask_question_direct(&transcript, &question)?;
-            println!("\n💡 Answer:\n{}", answer);
-        }
-        Commands::Query { url, question } => {
-            println!("🚀 Querying video: {}", url);
-            let answer = transcriber.query_video(&url, &question)?;
-            println!("\n💡 Answer:\n{}", answer);
-        }
+    #[test]
+    fn cli_is_valid() {
+        Cli::command().debug_assert();
     }
 
-    Ok(())
+    #[test]
+    fn format_belongs_to_the_command() {
+        let cli = Cli::try_parse_from(["cvt", "grep", "rust", "--format", "launcher"]).unwrap();
+        assert_eq!(cli.command.output_format(), OutputFormat::Launcher);
+        assert!(Cli::try_parse_from(["cvt", "--format", "json", "list"]).is_err());
+    }
 }
@@ -0,0 +1,339 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Local, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Utc};
+use regex::Regex;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::transcript::{parse_vtt, CaptionSource, Segment};
+
+/// Meeting platforms whose recording folders are understood
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Platform {
+    Zoom,
+    Teams,
+    Meet,
+}
+
+impl Platform {
+    pub fn name(self) -> &'static str {
+        match self {
+            Platform::Zoom => "Zoom",
+            Platform::Teams => "Microsoft Teams",
+            Platform::Meet => "Google Meet",
+        }
+    }
+}
+
+/// One chat message, with its offset into the recording when the log has times
+pub struct ChatMessage {
+    pub at: Option<f64>,
+    pub sender: String,
+    pub text: String,
+}
+
+/// What a meeting platform saved alongside a recording
+pub struct Meeting {
+    pub platform: Platform,
+    pub title: Option<String>,
+    pub started_at: Option<DateTime<Utc>>,
+    pub participants: Vec<String>,
+    pub chat: Vec<ChatMessage>,
+    /// Captions the platform already produced, so the recording need not be transcribed
+    pub captions: Vec<Segment>,
+}
+
+/// Zoom names each local recording folder `YYYY-MM-DD HH.MM.SS Topic [meeting ID]`
+fn zoom_folder(name: &str) -> Option<(NaiveDateTime, String)> {
+    let pattern = Regex::new(r"^(\d{4}-\d{2}-\d{2} \d{2}\.\d{2}\.\d{2}) (.+?)(?: \d{9,11})?$").expect("valid regex");
+    let captures = pattern.captures(name)?;
+    let started = NaiveDateTime::parse_from_str(&captures[1], "%Y-%m-%d %H.%M.%S").ok()?;
+    Some((started, captures[2].to_string()))
+}
+
+fn is_zoom_media(stem: &str) -> bool {
+    Regex::new(r"^(?:zoom_\d+|video\d+|audio\d+|audio_only|GMT\d{8}-\d{6}_Recording.*)$")
+        .expect("valid regex")
+        .is_match(stem)
+}
+
+fn local_to_utc(naive: NaiveDateTime) -> Option<DateTime<Utc>> {
+    Local.from_local_datetime(&naive).single().map(|date| date.with_timezone(&Utc))
+}
+
+fn stem(path: &Path) -> String {
+    path.file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or_default()
+}
+
+fn has_extension(path: &Path, extension: &str) -> bool {
+    path.extension().and_then(|e| e.to_str()).is_some_and(|e| e.eq_ignore_ascii_case(extension))
+}
+
+/// Files in `dir` with the given extension, sorted
+fn sidecars(dir: &Path, extension: &str) -> Vec<PathBuf> {
+    let mut files: Vec<PathBuf> = fs::read_dir(dir)
+        .map(|entries| {
+            entries
+                .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+                .filter(|path| path.is_file() && has_extension(path, extension))
+                .collect()
+        })
+        .unwrap_or_default();
+    files.sort();
+    files
+}
+
+fn read(path: &Path) -> Result<String> {
+    fs::read_to_string(path).with_context(|| format!("Failed to read {}", path.display()))
+}
+
+/// Recognise a recording saved by Zoom, Teams, or Meet and read the files saved with it
+pub fn detect(media: &Path) -> Result<Option<Meeting>> {
+    let dir = media.parent().unwrap_or(Path::new("."));
+    let name = stem(media);
+    let folder = dir.file_name().map(|f| f.to_string_lossy().to_string()).unwrap_or_default();
+
+    if let Some((started, topic)) = zoom_folder(&folder) {
+        return detect_zoom(dir, Some(started), Some(topic)).map(Some);
+    }
+    if is_zoom_media(&name) && dir.join("chat.txt").exists() {
+        return detect_zoom(dir, None, None).map(Some);
+    }
+
+    // Teams: `Title-20240501_100000-Meeting Recording.mp4`
+    let teams = Regex::new(r"^(.+?)-(\d{8}_\d{6})-Meeting Recording$").expect("valid regex");
+    if let Some(captures) = teams.captures(&name) {
+        let started = NaiveDateTime::parse_from_str(&captures[2], "%Y%m%d_%H%M%S").ok();
+        return detect_captioned(Platform::Teams, media, &captures[1], started).map(Some);
+    }
+
+    // Meet: `Title (2024-05-01 10:00 GMT-7).mp4`, with `at` and `.` in some locales and downloads
+    let meet = Regex::new(r"^(.+?) \((\d{4}-\d{2}-\d{2})(?: at)? (\d{1,2})[:.](\d{2})[^)]*\)$").expect("valid regex");
+    if let Some(captures) = meet.captures(&name) {
+        let started = NaiveDate::parse_from_str(&captures[2], "%Y-%m-%d").ok().and_then(|date| {
+            date.and_hms_opt(captures[3].parse().ok()?, captures[4].parse().ok()?, 0)
+        });
+        let mut meeting = detect_captioned(Platform::Meet, media, &captures[1], started)?;
+        for log in sidecars(dir, "sbv").iter().filter(|log| stem(log).starts_with(&name)) {
+            meeting.chat.extend(parse_sbv_chat(&read(log)?));
+        }
+        add_participants(&mut meeting.participants, meeting.chat.iter().map(|m| m.sender.as_str()));
+        return Ok(Some(meeting));
+    }
+
+    Ok(None)
+}
+
+/// Zoom keeps one meeting per folder: the recording, `chat.txt` (or `meeting_saved_chat.txt`),
+/// cloud captions as `.vtt`, and a `participants_*.csv` export when downloaded from the web
+fn detect_zoom(dir: &Path, started: Option<NaiveDateTime>, topic: Option<String>) -> Result<Meeting> {
+    let mut meeting = Meeting {
+        platform: Platform::Zoom,
+        title: topic,
+        started_at: started.and_then(local_to_utc),
+        participants: Vec::new(),
+        chat: Vec::new(),
+        captions: Vec::new(),
+    };
+
+    for name in ["chat.txt", "meeting_saved_chat.txt"] {
+        let path = dir.join(name);
+        if path.exists() {
+            meeting.chat = parse_zoom_chat(&read(&path)?, started.map(|s| s.time()));
+            break;
+        }
+    }
+    let mut captions = sidecars(dir, "vtt");
+    captions.sort_by_key(|path| !stem(path).to_lowercase().contains("transcript"));
+    if let Some(path) = captions.first() {
+        meeting.captions = parse_vtt(&read(path)?);
+        add_participants(&mut meeting.participants, speakers(&read(path)?).iter().map(String::as_str));
+    }
+    for csv in sidecars(dir, "csv").iter().filter(|csv| stem(csv).starts_with("participants")) {
+        let names = parse_participants_csv(&read(csv)?);
+        add_participants(&mut meeting.participants, names.iter().map(String::as_str));
+    }
+    add_participants(&mut meeting.participants, meeting.chat.iter().map(|m| m.sender.as_str()));
+    Ok(meeting)
+}
+
+/// Teams and Meet save captions as a `.vtt` named after the recording or the meeting title
+fn detect_captioned(platform: Platform, media: &Path, title: &str, started: Option<NaiveDateTime>) -> Result<Meeting> {
+    let dir = media.parent().unwrap_or(Path::new("."));
+    let name = stem(media);
+    let mut meeting = Meeting {
+        platform,
+        title: Some(title.trim().to_string()),
+        started_at: started.and_then(local_to_utc),
+        participants: Vec::new(),
+        chat: Vec::new(),
+        captions: Vec::new(),
+    };
+    if let Some(path) = sidecars(dir, "vtt")
+        .into_iter()
+        .find(|path| stem(path).starts_with(&name) || stem(path).starts_with(title))
+    {
+        let vtt = read(&path)?;
+        meeting.captions = parse_vtt(&vtt);
+        add_participants(&mut meeting.participants, speakers(&vtt).iter().map(String::as_str));
+    }
+    Ok(meeting)
+}
+
+/// Zoom records video and an audio-only copy side by side; only the audio is ingested
+pub fn is_secondary(media: &Path) -> bool {
+    let dir = media.parent().unwrap_or(Path::new("."));
+    let in_zoom_folder = dir.file_name().is_some_and(|f| zoom_folder(&f.to_string_lossy()).is_some());
+    in_zoom_folder
+        && has_extension(media, "mp4")
+        && !sidecars(dir, "m4a").is_empty()
+}
+
+fn add_participants<'a>(participants: &mut Vec<String>, names: impl Iterator<Item = &'a str>) {
+    for name in names {
+        let name = name.trim();
+        if !name.is_empty() && !participants.iter().any(|p| p == name) {
+            participants.push(name.to_string());
+        }
+    }
+}
+
+/// Speaker names from WebVTT voice tags
+fn speakers(vtt: &str) -> Vec<String> {
+    let voice = Regex::new(r"<v(?:\.[^ >]*)? ([^>]+)>").expect("valid regex");
+    let mut names = Vec::new();
+    add_participants(&mut names, voice.captures_iter(vtt).map(|c| c.get(1).map_or("", |m| m.as_str())));
+    names
+}
+
+/// Parse Zoom's `chat.txt`, in both the current layout
+/// (`10:02:15 From Alice to Everyone:` then tab-indented lines) and the older one-line layout
+/// (`10:02:15\t From Alice : Hello`). Direct messages are left out.
+pub fn parse_zoom_chat(log: &str, started: Option<NaiveTime>) -> Vec<ChatMessage> {
+    let header = Regex::new(r"^(\d{2}:\d{2}:\d{2})\s+From\s+(.+?)\s+to\s+(.+?)\s*:\s*$").expect("valid regex");
+    let one_line = Regex::new(r"^(\d{2}:\d{2}:\d{2})\s+From\s+(.+?)\s*:\s*(.*)$").expect("valid regex");
+    let offset = |clock: &str| {
+        let (started, clock) = (started?, NaiveTime::parse_from_str(clock, "%H:%M:%S").ok()?);
+        Some((clock - started).num_seconds().rem_euclid(86_400) as f64)
+    };
+
+    let mut messages: Vec<ChatMessage> = Vec::new();
+    // Whether continuation lines belong to a message being kept
+    let mut keeping = false;
+    for line in log.replace("\r\n", "\n").lines() {
+        if let Some(captures) = header.captures(line) {
+            let recipient = captures[3].to_lowercase();
+            keeping = !recipient.contains("direct message") && !recipient.contains("privately")
+                && recipient.starts_with("everyone");
+            if keeping {
+                messages.push(ChatMessage {
+                    at: offset(&captures[1]),
+                    sender: captures[2].trim().to_string(),
+                    text: String::new(),
+                });
+            }
+        } else if let Some(captures) = one_line.captures(line) {
+            keeping = !captures[2].contains("(Privately)") && !captures[2].contains("(Direct Message)");
+            if keeping {
+                messages.push(ChatMessage {
+                    at: offset(&captures[1]),
+                    sender: captures[2].trim().to_string(),
+                    text: captures[3].trim().to_string(),
+                });
+            }
+        } else if keeping && !line.trim().is_empty() {
+            if let Some(message) = messages.last_mut() {
+                if !message.text.is_empty() {
+                    message.text.push(' ');
+                }
+                message.text.push_str(line.trim());
+            }
+        }
+    }
+    messages.retain(|m| !m.text.is_empty());
+    messages
+}
+
+/// Parse a Google Meet chat log (`.sbv`): a time range line, then `Name: message`
+pub fn parse_sbv_chat(log: &str) -> Vec<ChatMessage> {
+    let mut messages = Vec::new();
+    for block in log.replace("\r\n", "\n").split("\n\n") {
+        let mut lines = block.lines().filter(|line| !line.trim().is_empty());
+        let Some(timing) = lines.next() else {
+            continue;
+        };
+        let at = timing.split(',').next().and_then(|start| crate::companion::parse_timestamp(start).ok());
+        let body = lines.collect::<Vec<_>>().join(" ");
+        let Some((sender, text)) = body.split_once(':') else {
+            continue;
+        };
+        messages.push(ChatMessage {
+            at,
+            sender: sender.trim().to_string(),
+            text: text.trim().to_string(),
+        });
+    }
+    messages
+}
+
+/// Names from a Zoom participants export: the `Name (Original Name)` or `Name` column
+fn parse_participants_csv(csv: &str) -> Vec<String> {
+    let mut lines = csv.lines().filter(|line| !line.trim().is_empty());
+    let Some(header) = lines.next() else {
+        return Vec::new();
+    };
+    let columns = split_csv_line(header);
+    let Some(column) = columns
+        .iter()
+        .position(|c| c.trim_start_matches('\u{feff}').starts_with("Name"))
+    else {
+        return Vec::new();
+    };
+    lines
+        .filter_map(|line| split_csv_line(line).into_iter().nth(column))
+        // `Alice Smith (Alice)` keeps the display name
+        .map(|name| name.split(" (").next().unwrap_or_default().trim().to_string())
+        .filter(|name| !name.is_empty())
+        .collect()
+}
+
+fn split_csv_line(line: &str) -> Vec<String> {
+    let mut fields = vec![String::new()];
+    let mut quoted = false;
+    for c in line.chars() {
+        match c {
+            '"' => quoted = !quoted,
+            ',' if !quoted => fields.push(String::new()),
+            _ => fields.last_mut().expect("fields is never empty").push(c),
+        }
+    }
+    fields
+}
+
+/// The recording's segments with the participant list up front and chat messages
+/// interleaved at the moment they were sent (untimed messages go at the end)
+pub fn fold_into(meeting: &Meeting, mut segments: Vec<Segment>) -> Vec<Segment> {
+    let end = segments.last().map_or(0.0, |s| s.end);
+    for message in &meeting.chat {
+        let at = message.at.unwrap_or(end);
+        segments.push(Segment {
+            start: at,
+            end: at,
+            text: format!("[Chat] {}: {}", message.sender, message.text),
+            source: Some(CaptionSource::Chat),
+        });
+    }
+    // Stable, so chat sent during a caption lands after it
+    segments.sort_by(|a, b| a.start.total_cmp(&b.start));
+    if !meeting.participants.is_empty() {
+        segments.insert(
+            0,
+            Segment {
+                start: 0.0,
+                end: 0.0,
+                text: format!("[Participants] {}", meeting.participants.join(", ")),
+                source: Some(CaptionSource::Chat),
+            },
+        );
+    }
+    segments
+}
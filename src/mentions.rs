@@ -0,0 +1,154 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use serde::Deserialize;
+
+use crate::guardrail::fence;
+use crate::transcript::{find_mentions, format_timestamp, snippet_around, Transcript};
+use crate::VideoTranscriber;
+
+/// Mentions per video sent for sentiment scoring
+const MAX_MENTIONS_PER_VIDEO: usize = 20;
+
+const MENTIONS_SYSTEM_PROMPT: &str =
+    "You classify the sentiment of brand mentions in video transcripts. Reply with JSON only.";
+
+/// One mention of the query term in a video
+pub struct Mention {
+    pub transcript: Transcript,
+    pub snippet: String,
+    pub seconds: Option<f64>,
+    pub sentiment: String,
+}
+
+#[derive(Deserialize)]
+struct SentimentLabel {
+    sentiment: String,
+}
+
+/// Parse `--since` values: relative (`30d`, `12h`, `2w`) or a date (`2024-01-31`)
+pub fn parse_since(value: &str) -> Result<DateTime<Utc>> {
+    let value = value.trim();
+    if let Ok(date) = NaiveDate::parse_from_str(value, "%Y-%m-%d") {
+        return Ok(date.and_hms_opt(0, 0, 0).unwrap().and_utc());
+    }
+
+    let (amount, unit) = value.split_at(value.len().saturating_sub(1));
+    let amount: i64 = amount
+        .parse()
+        .with_context(|| format!("Invalid --since value '{}': use e.g. 30d, 12h, 2w, or 2024-01-31", value))?;
+    let span = match unit {
+        "h" => Duration::hours(amount),
+        "d" => Duration::days(amount),
+        "w" => Duration::weeks(amount),
+        _ => anyhow::bail!("Invalid --since unit in '{}': use h, d, or w", value),
+    };
+    Ok(Utc::now() - span)
+}
+
+impl VideoTranscriber {
+    /// Find and score mentions of `query` in library videos published (or indexed) since
+    /// `since`, leaving out private videos unless `include_private`
+    pub fn find_brand_mentions(&self, query: &str, since: DateTime<Utc>, include_private: bool) -> Result<Vec<Mention>> {
+        let mut videos: Vec<Transcript> = self
+            .cache
+            .list()?
+            .into_iter()
+            .filter(|t| t.published_at.unwrap_or(t.fetched_at) >= since)
+            .collect();
+        if !videos.is_empty() {
+            videos = self.shareable_transcripts(videos, include_private)?;
+        }
+        status!("🔎 Searching {} video(s) for \"{}\"...", videos.len(), query);
+
+        let mut mentions = Vec::new();
+        for transcript in videos {
+            let offsets: Vec<usize> = find_mentions(&transcript.text, query)
+                .into_iter()
+                .take(MAX_MENTIONS_PER_VIDEO)
+                .collect();
+            if offsets.is_empty() {
+                continue;
+            }
+
+            let snippets: Vec<String> = offsets
+                .iter()
+                .map(|&at| snippet_around(&transcript.text, at, 200))
+                .collect();
+            let numbered: String = snippets
+                .iter()
+                .enumerate()
+                .map(|(i, snippet)| format!("{}. {}\n", i + 1, snippet))
+                .collect();
+            let prompt = format!(
+                "Classify how each excerpt talks about \"{}\". Return a JSON array with one \
+                 {{\"sentiment\": \"positive\" | \"neutral\" | \"negative\"}} per excerpt, in order.\n\n{}",
+                query,
+                fence(&numbered)
+            );
+            let labels: Vec<SentimentLabel> = self
+                .complete_json(MENTIONS_SYSTEM_PROMPT, &prompt)
+                .unwrap_or_else(|e| {
+                    status!("⚠️  Could not score sentiment for {}: {}", transcript.label(), e);
+                    Vec::new()
+                });
+
+            for (i, (offset, snippet)) in offsets.iter().zip(snippets).enumerate() {
+                mentions.push(Mention {
+                    seconds: transcript.time_at_offset(*offset),
+                    sentiment: labels
+                        .get(i)
+                        .map(|label| label.sentiment.to_lowercase())
+                        .unwrap_or_else(|| "unknown".to_string()),
+                    snippet,
+                    transcript: transcript.clone(),
+                });
+            }
+        }
+
+        Ok(mentions)
+    }
+}
+
+/// Render the mentions as a Markdown report grouped by video
+pub fn render_report(query: &str, since: DateTime<Utc>, mentions: &[Mention]) -> String {
+    let count = |label: &str| mentions.iter().filter(|m| m.sentiment == label).count();
+    let mut out = format!(
+        "# Mentions of \"{}\" since {}\n\n**{}** mention(s): {} positive, {} neutral, {} negative\n",
+        query,
+        since.format("%Y-%m-%d"),
+        mentions.len(),
+        count("positive"),
+        count("neutral"),
+        count("negative")
+    );
+
+    let mut current_video = "";
+    for mention in mentions {
+        if mention.transcript.video_id != current_video {
+            current_video = &mention.transcript.video_id;
+            out.push_str(&format!(
+                "\n## {} ({})\n{}\n\n",
+                mention.transcript.label(),
+                mention.transcript.channel.as_deref().unwrap_or("unknown channel"),
+                mention.transcript.url
+            ));
+        }
+
+        let badge = match mention.sentiment.as_str() {
+            "positive" => "🟢",
+            "negative" => "🔴",
+            "neutral" => "⚪",
+            _ => "❔",
+        };
+        let location = match mention.seconds {
+            Some(seconds) => format!(
+                "[{}]({})",
+                format_timestamp(seconds),
+                mention.transcript.link_at(seconds)
+            ),
+            None => "-".to_string(),
+        };
+        out.push_str(&format!("- {} {} — {}\n", badge, location, mention.snippet));
+    }
+    out
+}
@@ -0,0 +1,155 @@
+use anyhow::Result;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::OnceLock;
+
+use crate::VideoTranscriber;
+
+/// What to do with an output that trips the content-safety check
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum ModerationMode {
+    /// Return the output with a content warning in front of it
+    #[default]
+    Annotate,
+    /// Replace the output with a notice naming the flagged categories
+    Block,
+}
+
+/// How outputs are scored
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum Classifier {
+    /// Phrase lists checked locally; free and instant, but coarse
+    #[default]
+    Local,
+    /// Ask the configured LLM provider for per-category safety scores
+    Provider,
+}
+
+/// `[moderation]` config section; moderation is off unless the section exists
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(default)]
+pub struct ModerationConfig {
+    pub mode: ModerationMode,
+    pub classifier: Classifier,
+    /// Category score (0.0-1.0) at or above which an output is flagged
+    pub threshold: Option<f32>,
+}
+
+const DEFAULT_THRESHOLD: f32 = 0.5;
+
+const MODERATION_SYSTEM_PROMPT: &str =
+    "You are a content-safety classifier. Score text for harmful content. Reply with JSON only.";
+
+const CATEGORIES: &[&str] = &["self-harm", "violence", "hate", "sexual", "illicit"];
+
+/// Phrases that indicate each category for the local classifier
+fn local_lexicon() -> &'static [(&'static str, Regex)] {
+    static LEXICON: OnceLock<Vec<(&'static str, Regex)>> = OnceLock::new();
+    LEXICON.get_or_init(|| {
+        [
+            (
+                "self-harm",
+                r"(?i)\b(kill (myself|yourself)|suicide (method|note)s?|self[- ]harm(ing)?|cut(ting)? (myself|yourself)|end (my|your) life)\b",
+            ),
+            (
+                "violence",
+                r"(?i)\b((build|make) an? (bomb|pipe bomb|explosive)|mass shooting|shoot (them|everyone) up|kill (them|everyone|him|her)|torture (them|him|her))\b",
+            ),
+            (
+                "hate",
+                r"(?i)\b(subhumans?|inferior races?|ethnic cleansing|racial purity|go back to (your|their) country)\b",
+            ),
+            ("sexual", r"(?i)\b(sexually explicit|explicit sex|porn(ography)?|nude photos? of)\b"),
+            (
+                "illicit",
+                r"(?i)\b((cook|synthesi[sz]e|make) (meth|fentanyl)|stolen credit cards?|launder(ing)? money|untraceable (gun|firearm)s?)\b",
+            ),
+        ]
+        .into_iter()
+        .map(|(category, pattern)| (category, Regex::new(pattern).expect("valid lexicon pattern")))
+        .collect()
+    })
+}
+
+/// Category scores from the local lexicon: each distinct match adds a third, capped at 1.0
+pub fn local_scores(text: &str) -> BTreeMap<String, f32> {
+    local_lexicon()
+        .iter()
+        .map(|(category, pattern)| {
+            let hits = pattern.find_iter(text).count();
+            (category.to_string(), (hits as f32 / 3.0).min(1.0))
+        })
+        .collect()
+}
+
+/// Result of moderating one output
+pub struct Verdict {
+    /// Categories at or above the threshold, highest score first
+    pub flagged: Vec<(String, f32)>,
+}
+
+impl VideoTranscriber {
+    fn moderation_scores(&self, classifier: Classifier, text: &str) -> Result<BTreeMap<String, f32>> {
+        match classifier {
+            Classifier::Local => Ok(local_scores(text)),
+            Classifier::Provider => {
+                let prompt = format!(
+                    "Score the text below from 0.0 (none) to 1.0 (severe) for each category: {}. \
+                     Discussing or reporting on a topic is not harmful by itself; score what the text \
+                     itself promotes or instructs. Return a JSON object mapping each category to its score.\n\n\
+                     Text:\n{}",
+                    CATEGORIES.join(", "),
+                    text
+                );
+                self.complete_json(MODERATION_SYSTEM_PROMPT, &prompt)
+            }
+        }
+    }
+
+    /// Score an output against the configured categories
+    pub fn check_output(&self, config: &ModerationConfig, text: &str) -> Result<Verdict> {
+        let threshold = config.threshold.unwrap_or(DEFAULT_THRESHOLD);
+        let mut flagged: Vec<(String, f32)> = self
+            .moderation_scores(config.classifier, text)?
+            .into_iter()
+            .filter(|(_, score)| *score >= threshold)
+            .collect();
+        flagged.sort_by(|a, b| b.1.total_cmp(&a.1));
+        Ok(Verdict { flagged })
+    }
+
+    /// Apply the configured moderation to a generated output before it is returned.
+    ///
+    /// Returns the output unchanged when moderation is not configured or nothing is flagged.
+    pub fn moderate(&self, output: &str) -> Result<String> {
+        let Some(config) = &self.config.moderation else {
+            return Ok(output.to_string());
+        };
+
+        let verdict = self.check_output(config, output)?;
+        if verdict.flagged.is_empty() {
+            return Ok(output.to_string());
+        }
+
+        let categories = verdict
+            .flagged
+            .iter()
+            .map(|(category, score)| format!("{} {:.2}", category, score))
+            .collect::<Vec<_>>()
+            .join(", ");
+        status!("🚩 Output flagged by content-safety check ({})", categories);
+
+        Ok(match config.mode {
+            ModerationMode::Annotate => {
+                format!("⚠️ Content warning ({}):\n\n{}", categories, output)
+            }
+            ModerationMode::Block => format!(
+                "⚠️ This output was withheld by the content-safety filter ({}).",
+                categories
+            ),
+        })
+    }
+}
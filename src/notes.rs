@@ -0,0 +1,171 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+use crate::guardrail::fence;
+use crate::transcript::Transcript;
+use crate::VideoTranscriber;
+
+const NOTES_SYSTEM_PROMPT: &str =
+    "You write clear, well-organized Markdown study notes from video transcripts. Reply with JSON only.";
+
+const UPDATE_SYSTEM_PROMPT: &str =
+    "You revise existing Markdown notes against a newer transcript of the same video. Keep the notes' \
+     structure and wording wherever the transcript still supports them, and change only what the new \
+     transcript says differently or adds. Reply with JSON only.";
+
+/// The notes last written for a video, kept so a later run updates them instead of starting over
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Notes {
+    pub video_id: String,
+    pub title: Option<String>,
+    /// The notes themselves, without the changelog
+    pub markdown: String,
+    /// Hash of the transcript text the notes were written from
+    pub transcript_sha256: String,
+    /// Newest first
+    #[serde(default)]
+    pub changelog: Vec<ChangelogEntry>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// What one update changed in the notes
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ChangelogEntry {
+    pub at: DateTime<Utc>,
+    pub changes: Vec<String>,
+}
+
+/// How a `notes` run changed the stored notes
+pub enum NotesOutcome {
+    /// No earlier notes (or `--fresh`): written from scratch
+    Written,
+    /// The transcript is the one the notes were written from, so they were kept as they are
+    Unchanged,
+    /// Revised against a newer transcript; counts of lines added and removed
+    Updated { added: usize, removed: usize },
+}
+
+#[derive(Deserialize)]
+struct Draft {
+    notes: String,
+}
+
+#[derive(Deserialize)]
+struct Revision {
+    notes: String,
+    #[serde(default)]
+    changes: Vec<String>,
+}
+
+impl Notes {
+    /// The notes followed by their changelog, when they have one
+    pub fn render(&self, transcript: &Transcript) -> String {
+        let mut out = format!(
+            "{}\n\n_Source: [{}]({})_\n",
+            self.markdown.trim(),
+            transcript.label(),
+            transcript.url
+        );
+        if !self.changelog.is_empty() {
+            out.push_str("\n## Changelog\n");
+            for entry in &self.changelog {
+                out.push_str(&format!("\n### {}\n\n", entry.at.format("%Y-%m-%d %H:%M UTC")));
+                for change in &entry.changes {
+                    out.push_str(&format!("- {}\n", change));
+                }
+            }
+        }
+        out
+    }
+}
+
+/// Lines added to and removed from the notes, ignoring blank lines and order
+fn line_diff(before: &str, after: &str) -> (usize, usize) {
+    let lines = |text: &str| -> HashSet<String> {
+        text.lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .map(str::to_string)
+            .collect()
+    };
+    let (before, after) = (lines(before), lines(after));
+    (after.difference(&before).count(), before.difference(&after).count())
+}
+
+impl VideoTranscriber {
+    /// Write study notes for a video, or bring the earlier notes up to date when the transcript
+    /// has changed since (e.g. after `--reindex` picked up better captions). `fresh` ignores the
+    /// earlier notes and starts over.
+    pub fn generate_notes(&self, url: &str, reindex: bool, fresh: bool) -> Result<(Transcript, Notes, NotesOutcome)> {
+        let transcript = self.load_transcript(url, reindex)?;
+        let hash = transcript.text_sha256();
+        let previous = if fresh { None } else { self.cache.get_notes(&transcript.video_id)? };
+
+        let (notes, outcome) = match previous {
+            Some(previous) if previous.transcript_sha256 == hash => {
+                status!("📦 The transcript hasn't changed since the notes were written; keeping them");
+                return Ok((transcript, previous, NotesOutcome::Unchanged));
+            }
+            Some(previous) => {
+                status!("📝 Updating the notes from {} against the new transcript...", previous.updated_at.format("%Y-%m-%d"));
+                let prompt = format!(
+                    "These notes were written from an earlier transcript of the video \"{}\". Revise them \
+                     so they match the current transcript below. Return JSON: {{\"notes\": \"the full \
+                     updated Markdown notes\", \"changes\": [\"one short line per change, e.g. Corrected \
+                     the speaker's name in Setup\"]}}. Leave \"changes\" empty when nothing needed to change.\n\n\
+                     Current notes:\n{}\n\nCurrent transcript:\n{}",
+                    transcript.label(),
+                    fence(&previous.markdown),
+                    fence(&transcript.timestamped_text(60.0))
+                );
+                let revision: Revision = self.complete_json(UPDATE_SYSTEM_PROMPT, &prompt)?;
+                let (added, removed) = line_diff(&previous.markdown, &revision.notes);
+
+                let mut changes = revision.changes;
+                if changes.is_empty() && added + removed > 0 {
+                    changes.push(format!("Revised against a newer transcript ({} line(s) added, {} removed)", added, removed));
+                }
+                let mut changelog = previous.changelog;
+                if !changes.is_empty() {
+                    changelog.insert(0, ChangelogEntry { at: Utc::now(), changes });
+                }
+                let notes = Notes {
+                    markdown: revision.notes.trim().to_string(),
+                    changelog,
+                    ..previous
+                };
+                (notes, NotesOutcome::Updated { added, removed })
+            }
+            None => {
+                status!("📝 Writing notes...");
+                let prompt = format!(
+                    "Write study notes for the video \"{}\": a short overview, then sections with headings \
+                     and bullet points covering every main idea, with [MM:SS] times from the transcript for \
+                     key moments. Return JSON: {{\"notes\": \"the Markdown notes\"}}.\n\nTranscript:\n{}",
+                    transcript.label(),
+                    fence(&transcript.timestamped_text(60.0))
+                );
+                let draft: Draft = self.complete_json(NOTES_SYSTEM_PROMPT, &prompt)?;
+                let notes = Notes {
+                    video_id: transcript.video_id.clone(),
+                    title: transcript.title.clone(),
+                    markdown: draft.notes.trim().to_string(),
+                    transcript_sha256: String::new(),
+                    changelog: Vec::new(),
+                    updated_at: Utc::now(),
+                };
+                (notes, NotesOutcome::Written)
+            }
+        };
+
+        let notes = Notes {
+            transcript_sha256: hash,
+            updated_at: Utc::now(),
+            ..notes
+        };
+        self.cache.put_notes(&notes)?;
+        Ok((transcript, notes, outcome))
+    }
+}
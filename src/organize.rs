@@ -0,0 +1,174 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::embeddings::cosine_similarity;
+use crate::transcript::Transcript;
+use crate::VideoTranscriber;
+
+const ORGANIZE_SYSTEM_PROMPT: &str =
+    "You name groups of videos for a personal video library. Reply with JSON only.";
+
+/// The library's folder-like category hierarchy, persisted in the data directory
+#[derive(Serialize, Deserialize, Default, Debug)]
+pub struct Categories {
+    pub categories: Vec<Category>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Category {
+    /// Slash-separated path such as `Programming/Rust`
+    pub path: String,
+    pub video_ids: Vec<String>,
+}
+
+impl Categories {
+    fn path(dir: &Path) -> PathBuf {
+        dir.join("categories.json")
+    }
+
+    pub fn load(dir: &Path) -> Result<Self> {
+        let path = Self::path(dir);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let contents = fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        serde_json::from_str(&contents).with_context(|| format!("Failed to parse {}", path.display()))
+    }
+
+    pub fn save(&self, dir: &Path) -> Result<()> {
+        let path = Self::path(dir);
+        fs::write(&path, serde_json::to_string_pretty(self)?)
+            .with_context(|| format!("Failed to write {}", path.display()))
+    }
+
+    /// File videos under `path`, adding to the category of that path when there is one
+    pub fn add(&mut self, path: String, video_ids: Vec<String>) {
+        match self.categories.iter_mut().find(|c| c.path == path) {
+            Some(existing) => existing.video_ids.extend(video_ids),
+            None => self.categories.push(Category { path, video_ids }),
+        }
+    }
+
+    /// Whether a video is in a collection or one of its sub-collections
+    pub fn covers(&self, collection: &str, video_id: &str) -> bool {
+        self.categories.iter().any(|category| {
+            category.video_ids.iter().any(|id| id == video_id)
+                && (category.path == collection || category.path.starts_with(&format!("{}/", collection)))
+        })
+    }
+}
+
+impl VideoTranscriber {
+    /// Cluster the library by embedding similarity and propose a name for each cluster
+    pub fn propose_categories(&self, threshold: f32) -> Result<Vec<(String, Vec<Transcript>)>> {
+        let transcripts = self.cache.list()?;
+        if transcripts.len() < 2 {
+            anyhow::bail!("Need at least two videos in the library to organize it");
+        }
+
+        let store = self.video_embeddings(&transcripts)?;
+        let vectors = store.rows_for(&transcripts)?;
+        let clusters = cluster(&vectors, threshold);
+        status!("🗂️  Grouped {} video(s) into {} cluster(s)", transcripts.len(), clusters.len());
+
+        let listing: String = clusters
+            .iter()
+            .enumerate()
+            .map(|(i, members)| {
+                let titles: Vec<&str> = members.iter().map(|&m| transcripts[m].label()).collect();
+                format!("Group {}: {}\n", i + 1, titles.join(" | "))
+            })
+            .collect();
+        let prompt = format!(
+            "Propose a short category path for each group of videos below, using '/' for at most two levels \
+             (e.g. \"Programming/Rust\"). Reuse the same top-level names where groups are related.\n\
+             Return a JSON array of strings, one per group, in order.\n\n{}",
+            listing
+        );
+        let names: Vec<String> = self.complete_json(ORGANIZE_SYSTEM_PROMPT, &prompt)?;
+
+        Ok(clusters
+            .into_iter()
+            .enumerate()
+            .map(|(i, members)| {
+                let name = names
+                    .get(i)
+                    .cloned()
+                    .unwrap_or_else(|| format!("Uncategorized/Group {}", i + 1));
+                (name, members.into_iter().map(|m| transcripts[m].clone()).collect())
+            })
+            .collect())
+    }
+}
+
+/// Average-linkage agglomerative clustering: keep merging the most similar pair of
+/// clusters while their average similarity is at least `threshold`.
+fn cluster(vectors: &[&[f32]], threshold: f32) -> Vec<Vec<usize>> {
+    let mut clusters: Vec<Vec<usize>> = (0..vectors.len()).map(|i| vec![i]).collect();
+
+    loop {
+        let mut best: Option<(usize, usize, f32)> = None;
+        for i in 0..clusters.len() {
+            for j in (i + 1)..clusters.len() {
+                let mut total = 0.0;
+                for &a in &clusters[i] {
+                    for &b in &clusters[j] {
+                        total += cosine_similarity(vectors[a], vectors[b]);
+                    }
+                }
+                let average = total / (clusters[i].len() * clusters[j].len()) as f32;
+                if average >= threshold && best.is_none_or(|(_, _, s)| average > s) {
+                    best = Some((i, j, average));
+                }
+            }
+        }
+
+        match best {
+            Some((i, j, _)) => {
+                let merged = clusters.remove(j);
+                clusters[i].extend(merged);
+            }
+            None => return clusters,
+        }
+    }
+}
+
+/// Render the hierarchy as an indented tree, resolving video IDs to titles
+pub fn render_tree(categories: &Categories, transcripts: &[Transcript]) -> String {
+    let mut tree: BTreeMap<String, BTreeMap<String, Vec<String>>> = BTreeMap::new();
+    for category in &categories.categories {
+        let (top, sub) = match category.path.split_once('/') {
+            Some((top, sub)) => (top.trim().to_string(), sub.trim().to_string()),
+            None => (category.path.trim().to_string(), String::new()),
+        };
+        let titles = category.video_ids.iter().map(|id| {
+            transcripts
+                .iter()
+                .find(|t| &t.video_id == id)
+                .map(|t| t.label().to_string())
+                .unwrap_or_else(|| id.clone())
+        });
+        tree.entry(top).or_default().entry(sub).or_default().extend(titles);
+    }
+
+    let mut out = String::new();
+    for (top, subs) in tree {
+        out.push_str(&format!("📁 {}\n", top));
+        for (sub, titles) in subs {
+            let indent = if sub.is_empty() {
+                "   "
+            } else {
+                out.push_str(&format!("   📂 {}\n", sub));
+                "      "
+            };
+            for title in titles {
+                out.push_str(&format!("{}- {}\n", indent, title));
+            }
+        }
+    }
+    out
+}
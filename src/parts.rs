@@ -0,0 +1,327 @@
+use anyhow::Result;
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::ops::Range;
+
+use crate::retrieval::{bm25_rank, chunk_transcript, DEFAULT_CHUNK_CHARS};
+use crate::transcript::{format_timestamp, Transcript};
+use crate::VideoTranscriber;
+
+/// Transcripts are uploaded as one file per this many seconds of video
+const PART_SECONDS: f64 = 3600.0;
+/// Byte cap per uploaded file, for very dense hours and transcripts without timings
+const MAX_PART_BYTES: usize = 400_000;
+/// Most parts attached to a single question
+const MAX_ATTACHED_PARTS: usize = 2;
+/// Gemini deletes uploaded files after 48 hours
+const FILE_LIFETIME_HOURS: i64 = 48;
+/// Files this close to expiry are re-uploaded rather than risked mid-question
+const EXPIRY_MARGIN_MINUTES: i64 = 60;
+
+/// One uploaded slice of a transcript
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct UploadedPart {
+    pub index: usize,
+    /// Time range covered, when the transcript has timed segments
+    pub start: Option<f64>,
+    pub end: Option<f64>,
+    /// Byte range of the transcript text in this part
+    pub range: Range<usize>,
+    pub file_uri: String,
+    pub uploaded_at: DateTime<Utc>,
+    /// Expiry reported by Gemini at upload time
+    #[serde(default)]
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+impl UploadedPart {
+    /// Label such as `part 2 (1:00:00–2:00:00)`
+    pub fn label(&self) -> String {
+        match (self.start, self.end) {
+            (Some(start), Some(end)) => format!(
+                "part {} ({}–{})",
+                self.index + 1,
+                format_timestamp(start),
+                format_timestamp(end)
+            ),
+            _ => format!("part {}", self.index + 1),
+        }
+    }
+
+    /// When the file expires, estimated from the upload time for parts recorded without one
+    pub fn expiry(&self) -> DateTime<Utc> {
+        self.expires_at
+            .unwrap_or(self.uploaded_at + Duration::hours(FILE_LIFETIME_HOURS))
+    }
+
+    /// Whether Gemini will still have the file for a while
+    pub fn is_live(&self) -> bool {
+        Utc::now() + Duration::minutes(EXPIRY_MARGIN_MINUTES) < self.expiry()
+    }
+}
+
+/// A planned part: its byte range of the text and the time span it covers
+pub struct PartSpan {
+    pub range: Range<usize>,
+    pub start: Option<f64>,
+    pub end: Option<f64>,
+}
+
+/// Split a transcript into hour-long parts that also respect the per-file byte cap.
+///
+/// Short transcripts come back as a single part covering the whole text.
+pub fn split_parts(transcript: &Transcript) -> Vec<PartSpan> {
+    let text = &transcript.text;
+    let joined_len = transcript.segments.iter().map(|s| s.text.len() + 1).sum::<usize>();
+    // Segment offsets only line up when the text is the segments joined with spaces
+    if transcript.segments.is_empty() || joined_len.saturating_sub(1) != text.len() {
+        return split_by_bytes(text);
+    }
+
+    let mut parts = Vec::new();
+    let (mut part_start, mut part_time) = (0, transcript.segments[0].start);
+    let mut offset = 0;
+    for (i, segment) in transcript.segments.iter().enumerate() {
+        let starts_new = segment.start >= part_time + PART_SECONDS
+            || (offset + segment.text.len() - part_start > MAX_PART_BYTES && offset > part_start);
+        if starts_new {
+            parts.push(PartSpan {
+                range: part_start..offset - 1,
+                start: Some(part_time),
+                end: Some(transcript.segments[i - 1].end),
+            });
+            part_start = offset;
+            part_time = segment.start;
+        }
+        offset += segment.text.len() + 1;
+    }
+    parts.push(PartSpan {
+        range: part_start..text.len(),
+        start: Some(part_time),
+        end: transcript.segments.last().map(|s| s.end),
+    });
+    parts
+}
+
+fn split_by_bytes(text: &str) -> Vec<PartSpan> {
+    let mut parts = Vec::new();
+    let mut start = 0;
+    while text.len() - start > MAX_PART_BYTES {
+        // Break on the last space before the cap so no word is cut in half
+        let mut end = start + MAX_PART_BYTES;
+        while !text.is_char_boundary(end) {
+            end -= 1;
+        }
+        let end = text[start..end].rfind(' ').map_or(end, |i| start + i);
+        parts.push(PartSpan {
+            range: start..end,
+            start: None,
+            end: None,
+        });
+        start = end + usize::from(text[end..].starts_with(' '));
+    }
+    parts.push(PartSpan {
+        range: start..text.len(),
+        start: None,
+        end: None,
+    });
+    parts
+}
+
+/// Parts most relevant to a question: the transcript's best-matching excerpts are
+/// tallied by the part they fall in, and the top-scoring parts are returned in order
+fn relevant_parts<'a>(transcript: &Transcript, parts: &'a [UploadedPart], question: &str) -> Vec<&'a UploadedPart> {
+    let chunks = chunk_transcript(transcript, DEFAULT_CHUNK_CHARS, 0);
+    let mut scores = vec![0.0f32; parts.len()];
+    let mut offset = 0;
+    let offsets: Vec<usize> = chunks
+        .iter()
+        .map(|chunk| {
+            let at = transcript.text[offset..].find(&chunk.text).map_or(offset, |i| offset + i);
+            offset = at;
+            at
+        })
+        .collect();
+    for (i, score) in bm25_rank(question, &chunks, 8) {
+        if let Some(part) = parts.iter().position(|p| p.range.contains(&offsets[i])) {
+            scores[part] += score;
+        }
+    }
+
+    let mut ranked: Vec<usize> = (0..parts.len()).filter(|&i| scores[i] > 0.0).collect();
+    ranked.sort_by(|&a, &b| scores[b].total_cmp(&scores[a]));
+    ranked.truncate(MAX_ATTACHED_PARTS);
+    if ranked.is_empty() {
+        ranked.push(0);
+    }
+    ranked.sort_unstable();
+    ranked.into_iter().map(|i| &parts[i]).collect()
+}
+
+impl VideoTranscriber {
+    /// Answer from the video's uploaded Gemini files, attaching only the relevant parts.
+    ///
+    /// Files are looked up in the index registry; a video never uploaded is uploaded first.
+    pub fn answer_from_files(&self, transcript: &Transcript, question: &str) -> Result<String> {
+        let mut parts = self.registry.parts(&transcript.video_id)?;
+        if parts.is_empty() {
+            status!("☁️  Uploading the transcript to Gemini first");
+            parts = self.upload_to_gemini(transcript)?;
+        }
+        self.ask_question(transcript, parts, question)
+    }
+
+    /// Pre-flight for asking from uploaded files: parts past their recorded expiry are
+    /// re-uploaded, then Gemini is asked whether the parts to attach still exist, and the
+    /// video is re-uploaded once more if one has gone. Returns the parts to attach and the
+    /// video's total number of parts.
+    pub fn attachable_parts(
+        &self,
+        transcript: &Transcript,
+        mut parts: Vec<UploadedPart>,
+        question: &str,
+    ) -> Result<(Vec<UploadedPart>, usize)> {
+        if parts.is_empty() || !parts.iter().all(UploadedPart::is_live) {
+            status!("♻️  Uploaded files for this video have expired; re-uploading");
+            parts = self.upload_to_gemini(transcript)?;
+        } else {
+            for part in relevant_parts(transcript, &parts, question) {
+                let state = self.gemini_file_state(&part.file_uri)?;
+                if state == "MISSING" || state == "FAILED" {
+                    status!("♻️  Gemini no longer has {} ({}); re-uploading", part.label(), state);
+                    parts = self.upload_to_gemini(transcript)?;
+                    break;
+                }
+            }
+        }
+
+        let attached = relevant_parts(transcript, &parts, question).into_iter().cloned().collect();
+        Ok((attached, parts.len()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transcript::Segment;
+
+    /// A transcript with one ten-minute segment per entry of `texts`, and no duration, as for
+    /// a live stream's recording
+    fn timed(texts: &[&str]) -> Transcript {
+        let segments: Vec<Segment> = texts
+            .iter()
+            .enumerate()
+            .map(|(i, text)| Segment {
+                start: i as f64 * 600.0,
+                end: (i + 1) as f64 * 600.0,
+                text: text.to_string(),
+                source: None,
+            })
+            .collect();
+        Transcript {
+            text: texts.join(" ").into(),
+            segments,
+            ..untimed("")
+        }
+    }
+
+    fn untimed(text: &str) -> Transcript {
+        Transcript {
+            video_id: "dQw4w9WgXcQ".to_string(),
+            url: "https://www.youtube.com/watch?v=dQw4w9WgXcQ".to_string(),
+            title: None,
+            channel: None,
+            text: text.into(),
+            segments: Vec::new(),
+            published_at: None,
+            duration_secs: None,
+            fetched_at: Utc::now(),
+        }
+    }
+
+    fn uploaded(index: usize, span: &PartSpan, uploaded_at: DateTime<Utc>) -> UploadedPart {
+        UploadedPart {
+            index,
+            start: span.start,
+            end: span.end,
+            range: span.range.clone(),
+            file_uri: format!("files/part-{}", index),
+            uploaded_at,
+            expires_at: None,
+        }
+    }
+
+    #[test]
+    fn timed_transcripts_split_on_the_hour() {
+        let texts: Vec<String> = (0..14).map(|i| format!("segment{}", i)).collect();
+        let transcript = timed(&texts.iter().map(String::as_str).collect::<Vec<_>>());
+        let parts = split_parts(&transcript);
+
+        let spans: Vec<(Option<f64>, Option<f64>)> = parts.iter().map(|p| (p.start, p.end)).collect();
+        assert_eq!(
+            spans,
+            [(Some(0.0), Some(3600.0)), (Some(3600.0), Some(7200.0)), (Some(7200.0), Some(8400.0))]
+        );
+        assert_eq!(&transcript.text[parts[0].range.clone()], texts[..6].join(" "));
+        assert_eq!(&transcript.text[parts[1].range.clone()], texts[6..12].join(" "));
+        assert_eq!(&transcript.text[parts[2].range.clone()], texts[12..].join(" "));
+    }
+
+    #[test]
+    fn dense_hours_split_at_the_byte_cap() {
+        let big = "word ".repeat(MAX_PART_BYTES / 5 * 3 / 4);
+        let big = big.trim_end();
+        let transcript = timed(&[big, big, big]);
+        let parts = split_parts(&transcript);
+        assert_eq!(parts.len(), 3);
+        assert!(parts.iter().all(|p| p.range.len() <= MAX_PART_BYTES));
+        assert_eq!(parts[1].start, Some(600.0));
+    }
+
+    #[test]
+    fn live_stream_without_timings_or_duration_splits_by_bytes() {
+        assert_eq!(split_parts(&untimed("a short live chat")).len(), 1);
+
+        let text = "word ".repeat(MAX_PART_BYTES / 2);
+        let transcript = untimed(text.trim_end());
+        let parts = split_parts(&transcript);
+        assert_eq!(parts.len(), 3);
+        assert!(parts.iter().all(|p| p.start.is_none() && p.end.is_none()));
+        assert!(parts.iter().all(|p| p.range.len() <= MAX_PART_BYTES));
+        // Parts break between words and together cover every one of them
+        let rejoined: Vec<&str> = parts.iter().map(|p| &transcript.text[p.range.clone()]).collect();
+        assert_eq!(rejoined.join(" "), &*transcript.text);
+    }
+
+    #[test]
+    fn questions_attach_the_parts_that_match() {
+        // Segments long enough to be excerpts of their own
+        let filler = "small talk about nothing much ".repeat(60);
+        let forecast = format!("{}the quarterly revenue forecast", filler);
+        let mut texts = vec![filler.trim_end(); 14];
+        texts[8] = &forecast;
+        let transcript = timed(&texts);
+        let parts: Vec<UploadedPart> = split_parts(&transcript)
+            .iter()
+            .enumerate()
+            .map(|(i, span)| uploaded(i, span, Utc::now()))
+            .collect();
+
+        let attached = relevant_parts(&transcript, &parts, "What is the revenue forecast?");
+        assert_eq!(attached.iter().map(|p| p.index).collect::<Vec<_>>(), [1]);
+        // With nothing to go on, the first part is attached
+        let attached = relevant_parts(&transcript, &parts, "zebra");
+        assert_eq!(attached.iter().map(|p| p.index).collect::<Vec<_>>(), [0]);
+    }
+
+    #[test]
+    fn parts_near_expiry_are_not_live() {
+        let span = &split_parts(&untimed("text"))[0];
+        assert!(uploaded(0, span, Utc::now()).is_live());
+        assert!(!uploaded(0, span, Utc::now() - Duration::hours(FILE_LIFETIME_HOURS)).is_live());
+
+        let mut part = uploaded(0, span, Utc::now());
+        part.expires_at = Some(Utc::now() + Duration::minutes(EXPIRY_MARGIN_MINUTES / 2));
+        assert!(!part.is_live());
+    }
+}
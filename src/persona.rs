@@ -0,0 +1,93 @@
+use anyhow::Result;
+
+use crate::retrieval::{
+    bm25_rank, chunk_transcript, cited_sources, numbered_context, Chunk, DEFAULT_CHUNK_CHARS,
+    DEFAULT_CHUNK_OVERLAP,
+};
+use crate::VideoTranscriber;
+
+/// Excerpts retrieved per question
+const PERSONA_TOP_K: usize = 8;
+/// Previous exchanges carried into each prompt
+const PERSONA_HISTORY_TURNS: usize = 4;
+
+const PERSONA_SYSTEM_PROMPT: &str =
+    "You explain what a specific video creator thinks, based only on excerpts from their videos. \
+     Every claim must cite the excerpt it comes from as [n]. If the excerpts do not cover the \
+     question, say so rather than guessing. Speak about the creator in the third person.";
+
+/// A retrieval-grounded conversation about one creator's views
+pub struct PersonaChat<'a> {
+    transcriber: &'a VideoTranscriber,
+    channel: String,
+    chunks: Vec<Chunk>,
+    history: Vec<(String, String)>,
+}
+
+impl<'a> PersonaChat<'a> {
+    /// Chunk every indexed video from the channel so questions can be answered across them
+    pub fn new(transcriber: &'a VideoTranscriber, channel: &str) -> Result<Self> {
+        let videos = transcriber.channel_transcripts(channel)?;
+        let chunks: Vec<Chunk> = videos
+            .iter()
+            .flat_map(|t| chunk_transcript(t, DEFAULT_CHUNK_CHARS, DEFAULT_CHUNK_OVERLAP))
+            .collect();
+        status!(
+            "🧑 Loaded {} video(s) ({} excerpts) from {}",
+            videos.len(),
+            chunks.len(),
+            channel
+        );
+
+        Ok(Self {
+            transcriber,
+            channel: channel.to_string(),
+            chunks,
+            history: Vec::new(),
+        })
+    }
+
+    /// Answer one question, returning the answer and the excerpts it may cite
+    pub fn ask(&mut self, question: &str) -> Result<(String, Vec<Chunk>)> {
+        // Fold the previous question in so follow-ups like "why?" still retrieve well
+        let retrieval_query = match self.history.last() {
+            Some((previous, _)) => format!("{} {}", previous, question),
+            None => question.to_string(),
+        };
+        let hits: Vec<Chunk> = bm25_rank(&retrieval_query, &self.chunks, PERSONA_TOP_K)
+            .into_iter()
+            .map(|(i, _)| self.chunks[i].clone())
+            .collect();
+
+        let history: String = self
+            .history
+            .iter()
+            .map(|(q, a)| format!("User: {}\nAssistant: {}\n", q, a))
+            .collect();
+        let prompt = format!(
+            "Creator: {}\n\nExcerpts from their videos:\n{}\n\nConversation so far:\n{}\nUser: {}",
+            self.channel,
+            numbered_context(&hits.iter().collect::<Vec<_>>()),
+            if history.is_empty() { "(none)\n" } else { &history },
+            question
+        );
+
+        let answer = self.transcriber.complete(PERSONA_SYSTEM_PROMPT, &prompt)?;
+        self.history.push((question.to_string(), answer.clone()));
+        if self.history.len() > PERSONA_HISTORY_TURNS {
+            self.history.remove(0);
+        }
+
+        Ok((answer, hits))
+    }
+}
+
+/// An answer followed by the excerpts it cites
+pub fn render_answer(answer: &str, sources: &[Chunk]) -> String {
+    let mut out = format!("\n{}\n", answer);
+    let cited = cited_sources(answer, &sources.iter().collect::<Vec<_>>());
+    if !cited.is_empty() {
+        out.push_str(&format!("\nSources:\n{}", cited));
+    }
+    out
+}
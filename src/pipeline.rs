@@ -0,0 +1,200 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc::{sync_channel, Receiver};
+use std::sync::Mutex;
+use std::thread;
+
+use crate::cancel;
+use crate::parts::UploadedPart;
+use crate::transcript::Transcript;
+use crate::apify::ApifyDatasetItem;
+use crate::VideoTranscriber;
+
+/// Dataset items requested per page while the scraper is still running
+const PAGE_SIZE: usize = 10;
+/// Items buffered between the fetch stage and the upload workers
+const STAGE_BUFFER: usize = 32;
+
+/// Whether a URL points at many videos (playlist or channel) rather than one
+pub fn is_collection_url(url: &str) -> bool {
+    url.contains("list=")
+        || url.contains("youtube.com/@")
+        || url.contains("/channel/")
+        || url.contains("/c/")
+        || url.contains("/user/")
+}
+
+/// A channel URL from a URL or a bare `@handle`
+pub fn channel_url(channel: &str) -> Result<String> {
+    let channel = channel.trim();
+    if channel.starts_with('@') {
+        return Ok(format!("https://www.youtube.com/{}", channel));
+    }
+    if !is_collection_url(channel) || channel.contains("list=") {
+        anyhow::bail!("{} is not a YouTube channel URL (https://www.youtube.com/@handle or /channel/ID)", channel);
+    }
+    Ok(channel.to_string())
+}
+
+/// Videos a collection run leaves out
+struct Filter<'a> {
+    since: Option<DateTime<Utc>>,
+    /// How many were skipped for being published before `since`
+    too_old: &'a AtomicUsize,
+}
+
+/// What happened to one video in a collection
+pub struct IndexedVideo {
+    pub transcript: Transcript,
+    /// Uploaded parts, or `None` for videos that were already cached
+    pub parts: Option<Result<Vec<UploadedPart>>>,
+}
+
+impl VideoTranscriber {
+    /// Index every video in a playlist or channel as a staged pipeline.
+    ///
+    /// Stage 1 polls the Apify run and reads dataset pages as soon as items appear;
+    /// stage 2 workers convert, cache, upload, and embed each video while later pages
+    /// are still being scraped. With `since`, videos published before it are skipped.
+    pub fn index_collection(
+        &self,
+        url: &str,
+        max_videos: i32,
+        workers: usize,
+        since: Option<DateTime<Utc>>,
+    ) -> Result<Vec<IndexedVideo>> {
+        let run_id = self.start_scraper(&[url.to_string()], max_videos, since)?;
+        status!("⏳ Scraping {} (run ID: {}); videos are processed as they arrive", url, run_id);
+
+        let (sender, receiver) = sync_channel::<ApifyDatasetItem>(STAGE_BUFFER);
+        let receiver = Mutex::new(receiver);
+        let indexed = Mutex::new(Vec::new());
+        // Video embeddings share one file on disk, so updates are serialized
+        let embeddings = Mutex::new(());
+        let upload = !self.gemini_api_key.is_empty();
+        let too_old = AtomicUsize::new(0);
+        let filter = Filter { since, too_old: &too_old };
+
+        let fetched = thread::scope(|scope| -> Result<usize> {
+            for _ in 0..workers.max(1) {
+                scope.spawn(|| self.process_items(&receiver, url, upload, &filter, &embeddings, &indexed));
+            }
+
+            let mut offset = 0;
+            let drain = |offset: &mut usize| -> Result<()> {
+                loop {
+                    let page = self.dataset_page(&run_id, *offset, Some(PAGE_SIZE))?;
+                    if page.is_empty() {
+                        return Ok(());
+                    }
+                    *offset += page.len();
+                    for item in page {
+                        // Workers only stop when the sender is dropped, so this cannot fail early
+                        let _ = sender.send(item);
+                    }
+                }
+            };
+
+            let result = self
+                .wait_for_run(&run_id, &mut || drain(&mut offset))
+                .and_then(|()| drain(&mut offset));
+            drop(sender);
+            result.map(|()| offset)
+        })?;
+
+        status!("✅ Scraper returned {} item(s)", fetched);
+        if let Some(since) = since {
+            let skipped = too_old.into_inner();
+            if skipped > 0 {
+                status!("⏭️  Skipped {} video(s) published before {}", skipped, since.format("%Y-%m-%d"));
+            }
+        }
+        let mut indexed = indexed.into_inner().unwrap_or_else(|e| e.into_inner());
+        indexed.sort_by(|a: &IndexedVideo, b| a.transcript.label().cmp(b.transcript.label()));
+        Ok(indexed)
+    }
+
+    /// Index several channels one after another, each with its own scraper run, reporting
+    /// progress across them. A channel that fails is reported and the rest still run.
+    pub fn index_channels(
+        &self,
+        channels: &[String],
+        max_videos: i32,
+        workers: usize,
+        since: Option<DateTime<Utc>>,
+    ) -> Result<Vec<(String, Result<Vec<IndexedVideo>>)>> {
+        let mut results = Vec::with_capacity(channels.len());
+        let mut total = 0;
+        for (i, channel) in channels.iter().enumerate() {
+            status!("\n📺 Channel {}/{}: {}", i + 1, channels.len(), channel);
+            let indexed = match self.index_collection(channel, max_videos, workers, since) {
+                // Stop here rather than starting more runs the user asked to cancel
+                Err(e) if cancel::cancelled(&e).is_some() => return Err(e),
+                indexed => indexed,
+            };
+            match &indexed {
+                Ok(videos) => {
+                    total += videos.len();
+                    status!("✨ {} video(s) from {} ({} so far)", videos.len(), channel, total);
+                }
+                Err(e) => status!("⚠️  Could not index {}: {}", channel, e),
+            }
+            results.push((channel.clone(), indexed));
+        }
+        Ok(results)
+    }
+
+    fn process_items(
+        &self,
+        receiver: &Mutex<Receiver<ApifyDatasetItem>>,
+        source_url: &str,
+        upload: bool,
+        filter: &Filter,
+        embeddings: &Mutex<()>,
+        indexed: &Mutex<Vec<IndexedVideo>>,
+    ) {
+        loop {
+            let item = match receiver.lock() {
+                Ok(receiver) => receiver.recv(),
+                Err(_) => return,
+            };
+            let Ok(item) = item else {
+                return;
+            };
+
+            let transcript = match self.transcript_from_item(&item, source_url) {
+                Ok(transcript) => transcript,
+                Err(e) => {
+                    status!("⚠️  Skipping a video without a usable transcript: {}", e);
+                    continue;
+                }
+            };
+            if let (Some(since), Some(published)) = (filter.since, transcript.published_at) {
+                if published < since {
+                    filter.too_old.fetch_add(1, Ordering::Relaxed);
+                    continue;
+                }
+            }
+            if let Err(e) = self.cache.put(&transcript) {
+                status!("⚠️  Could not cache {}: {}", transcript.label(), e);
+            }
+
+            let parts = upload.then(|| self.upload_to_gemini(&transcript));
+            if upload {
+                let _guard = embeddings.lock();
+                if let Err(e) = self
+                    .video_embeddings(std::slice::from_ref(&transcript))
+                    .and_then(|_| self.index_chunks(&transcript))
+                {
+                    status!("⚠️  Could not embed {}: {}", transcript.label(), e);
+                }
+            }
+            status!("📥 Indexed {}", transcript.label());
+
+            if let Ok(mut indexed) = indexed.lock() {
+                indexed.push(IndexedVideo { transcript, parts });
+            }
+        }
+    }
+}
@@ -0,0 +1,134 @@
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::process::Command;
+
+use crate::parallel::map_bounded;
+use crate::parts::UploadedPart;
+use crate::registry::PlaylistRecord;
+use crate::VideoTranscriber;
+
+/// A playlist's videos as yt-dlp lists them, without fetching each one
+#[derive(Deserialize)]
+struct PlaylistInfo {
+    id: String,
+    title: Option<String>,
+    channel: Option<String>,
+    uploader: Option<String>,
+    #[serde(default)]
+    entries: Vec<PlaylistEntry>,
+}
+
+#[derive(Deserialize, Clone)]
+pub struct PlaylistEntry {
+    pub id: String,
+    pub title: Option<String>,
+}
+
+impl PlaylistEntry {
+    pub fn url(&self) -> String {
+        format!("https://www.youtube.com/watch?v={}", self.id)
+    }
+
+    pub fn label(&self) -> &str {
+        self.title.as_deref().unwrap_or(&self.id)
+    }
+}
+
+/// What happened to one video of an indexed playlist or batch
+pub enum PlaylistOutcome {
+    /// Uploaded now
+    Indexed(Vec<UploadedPart>),
+    /// Its Gemini files were still live from an earlier run
+    AlreadyIndexed,
+    Failed(anyhow::Error),
+}
+
+/// An indexed playlist, with each video's outcome in playlist order
+pub struct IndexedPlaylist {
+    pub playlist: PlaylistRecord,
+    pub videos: Vec<(PlaylistEntry, PlaylistOutcome)>,
+}
+
+/// The `list=` ID of a playlist URL, or the argument itself when it is already an ID
+pub fn playlist_id(url_or_id: &str) -> &str {
+    url_or_id
+        .split(['?', '&'])
+        .find_map(|param| param.strip_prefix("list="))
+        .unwrap_or(url_or_id)
+}
+
+impl VideoTranscriber {
+    /// Every video in a playlist, in order, listed with yt-dlp
+    fn playlist_entries(&self, url: &str) -> Result<PlaylistInfo> {
+        status!("📜 Listing the playlist's videos with yt-dlp...");
+        self.cancel.check()?;
+        let output = Command::new("yt-dlp")
+            .args(["--flat-playlist", "--dump-single-json", "--no-warnings", url])
+            .output()
+            .context("Failed to run yt-dlp, which lists the playlist (https://github.com/yt-dlp/yt-dlp)")?;
+        if !output.status.success() {
+            anyhow::bail!("yt-dlp failed: {}", String::from_utf8_lossy(&output.stderr).trim());
+        }
+        serde_json::from_slice(&output.stdout).context("Failed to parse yt-dlp's playlist information")
+    }
+
+    /// Index every video of a playlist with the configured transcript source, `workers` at a
+    /// time, and record them as one group in the index. Videos whose files Gemini still has are
+    /// skipped unless `reindex` is set, so running it again picks up just the new videos.
+    pub fn index_playlist(&self, url: &str, reindex: bool, workers: usize) -> Result<IndexedPlaylist> {
+        let info = self.playlist_entries(url)?;
+        if info.entries.is_empty() {
+            anyhow::bail!("The playlist {} has no videos", url);
+        }
+        status!(
+            "🚀 Indexing {} video(s) from \"{}\"",
+            info.entries.len(),
+            info.title.as_deref().unwrap_or(&info.id)
+        );
+
+        let outcomes = map_bounded(&info.entries, workers, |entry| {
+            self.index_unless_live(&entry.id, &entry.url(), entry.label(), reindex)
+        });
+        self.cancel.check()?;
+
+        let videos: Vec<(PlaylistEntry, PlaylistOutcome)> = info.entries.into_iter().zip(outcomes).collect();
+        let playlist = PlaylistRecord {
+            playlist_id: info.id,
+            url: url.to_string(),
+            title: info.title,
+            channel: info.channel.or(info.uploader),
+            indexed_at: chrono::Utc::now(),
+        };
+        let indexed: Vec<String> = videos
+            .iter()
+            .filter(|(_, outcome)| !matches!(outcome, PlaylistOutcome::Failed(_)))
+            .map(|(entry, _)| entry.id.clone())
+            .collect();
+        self.registry.record_playlist(&playlist, &indexed)?;
+        Ok(IndexedPlaylist { playlist, videos })
+    }
+
+    /// Index one video, unless its Gemini files are still live from an earlier run and
+    /// `reindex` is not set. Failures are returned as an outcome so the other videos go on.
+    pub fn index_unless_live(&self, video_id: &str, url: &str, label: &str, reindex: bool) -> PlaylistOutcome {
+        let live = !reindex
+            && self
+                .registry
+                .parts(video_id)
+                .is_ok_and(|parts| !parts.is_empty() && parts.iter().all(UploadedPart::is_live));
+        if live {
+            status!("📦 {} is already indexed", label);
+            return PlaylistOutcome::AlreadyIndexed;
+        }
+        match self.index_video(url, reindex) {
+            Ok(parts) => {
+                status!("📥 Indexed {}", label);
+                PlaylistOutcome::Indexed(parts)
+            }
+            Err(e) => {
+                status!("⚠️  Could not index {}: {}", label, e);
+                PlaylistOutcome::Failed(e)
+            }
+        }
+    }
+}
@@ -0,0 +1,80 @@
+use serde::Serialize;
+use std::fmt;
+use std::sync::mpsc::{self, Receiver};
+use std::sync::{Arc, RwLock};
+
+use crate::VideoTranscriber;
+
+/// Callback that receives progress events; called on whichever thread the work runs on
+pub type ProgressFn = Arc<dyn Fn(&Progress) + Send + Sync>;
+
+/// Callback that receives status lines; called on whichever thread wrote them
+type StatusFn = Box<dyn Fn(&str) + Send + Sync>;
+
+static STATUS_HANDLER: RwLock<Option<StatusFn>> = RwLock::new(None);
+
+/// Send the status lines every call in this process writes as it goes ("🔍 Loading
+/// transcript...", warnings, and the like) to `on_status`, in place of any earlier handler.
+/// Without one they are dropped; the CLI prints them to stderr.
+pub fn set_status_handler(on_status: impl Fn(&str) + Send + Sync + 'static) {
+    *STATUS_HANDLER.write().unwrap_or_else(|poisoned| poisoned.into_inner()) = Some(Box::new(on_status));
+}
+
+/// Where `status!` sends its line
+#[doc(hidden)]
+pub fn status(line: fmt::Arguments) {
+    if let Some(on_status) = STATUS_HANDLER.read().unwrap_or_else(|poisoned| poisoned.into_inner()).as_ref() {
+        on_status(&line.to_string());
+    }
+}
+
+/// What a long-running call is doing, for library users who want to show more than the
+/// messages the CLI prints to stderr. New variants may be added.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+#[non_exhaustive]
+pub enum Progress {
+    /// A transcript is being fetched from a transcript source
+    FetchingTranscript { url: String, source: &'static str },
+    /// A transcript was loaded, from the cache or freshly fetched
+    TranscriptReady { video_id: String, cached: bool, chars: usize },
+    /// An Apify run is still going after `polls` status checks
+    ApifyRunning { run_id: String, polls: u32 },
+    /// Part `part` (1-based) of `parts` of a transcript is being uploaded to Gemini
+    Uploading { video_id: String, part: usize, parts: usize },
+    /// `done` of `total` texts have been embedded
+    Embedding { done: usize, total: usize },
+    /// A request was sent to a language model and is waiting for its answer
+    Generating { model: String },
+    /// A call is waiting for room under the Gemini free-tier limits, or waiting out a rate
+    /// limit an API answered with
+    RateLimited { wait_secs: u64 },
+}
+
+impl VideoTranscriber {
+    /// A transcriber that calls `on_progress` with every progress event of its calls, in place
+    /// of any earlier callback
+    pub fn with_progress(&self, on_progress: impl Fn(&Progress) + Send + Sync + 'static) -> Self {
+        Self {
+            progress: Some(Arc::new(on_progress)),
+            ..self.clone()
+        }
+    }
+
+    /// A transcriber that sends its progress events to the returned channel. Events sent after
+    /// the receiver is dropped are discarded.
+    pub fn progress_channel(&self) -> (Self, Receiver<Progress>) {
+        let (tx, rx) = mpsc::channel();
+        let transcriber = self.with_progress(move |event| {
+            let _ = tx.send(event.clone());
+        });
+        (transcriber, rx)
+    }
+
+    /// Pass an event to the progress callback, if there is one
+    pub fn report(&self, event: Progress) {
+        if let Some(on_progress) = &self.progress {
+            on_progress(&event);
+        }
+    }
+}
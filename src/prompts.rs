@@ -0,0 +1,219 @@
+use crate::guardrail::fence;
+use crate::retrieval::{numbered_context, Chunk};
+use crate::strategy::Strategy;
+use crate::style::AnswerStyle;
+use crate::timestamps::TIMESTAMP_INSTRUCTION;
+use crate::transcript::Transcript;
+use crate::ANSWER_SYSTEM_PROMPT;
+
+/// System prompt for answering from numbered excerpts
+pub const RETRIEVAL_SYSTEM_PROMPT: &str =
+    "You answer questions about a YouTube video using only the numbered transcript excerpts \
+     provided. Cite the excerpts you rely on as [n]. Each excerpt notes where its text came from; \
+     when a quote comes from auto-generated captions or speech recognition, say it may be inexact. \
+     If they do not contain the answer, say so.";
+
+/// What is added to every answer prompt: the `--style`, `--max-words`, and `--answer-lang`
+/// instructions and the standing instructions that apply to the video
+#[derive(Debug, Clone, Default)]
+pub struct PromptOptions {
+    pub style: Option<AnswerStyle>,
+    pub max_words: Option<usize>,
+    /// Name of the language to answer in, whatever language the video is in
+    pub language: Option<String>,
+    pub instructions: Vec<String>,
+}
+
+impl PromptOptions {
+    /// `prompt` with the style, length, and language instructions after it and the standing
+    /// instructions before it
+    pub fn finish(&self, mut prompt: String) -> String {
+        if let Some(style) = self.style {
+            prompt.push_str(&format!("\n\nAnswer format: {}", style.instruction()));
+        }
+        if let Some(limit) = self.max_words {
+            prompt.push_str(&format!("\n\nLength: at most {} words.", limit));
+        }
+        if let Some(language) = &self.language {
+            prompt.push_str(&format!(
+                "\n\nLanguage: answer in {}, whatever language the transcript is in. Keep quotes from the \
+                 transcript in their original language and add a translation after each.",
+                language
+            ));
+        }
+        if self.instructions.is_empty() {
+            return prompt;
+        }
+        let list: String = self.instructions.iter().map(|text| format!("- {}\n", text)).collect();
+        format!("Standing instructions for this video (always follow them):\n{}\n{}", list, prompt)
+    }
+}
+
+/// The system prompt a question is sent with under `strategy`
+pub fn system_prompt(strategy: Strategy) -> &'static str {
+    match strategy {
+        Strategy::Retrieval => RETRIEVAL_SYSTEM_PROMPT,
+        Strategy::Auto | Strategy::Full | Strategy::Files | Strategy::Cached => ANSWER_SYSTEM_PROMPT,
+    }
+}
+
+/// The timestamp instruction, for transcripts that have timed segments
+fn citing(transcript: &Transcript) -> String {
+    if transcript.segments.is_empty() {
+        String::new()
+    } else {
+        format!("\n\n{}", TIMESTAMP_INSTRUCTION)
+    }
+}
+
+/// A question about a whole transcript sent inline, with `[MM:SS]` markers to cite when the
+/// transcript is timed
+pub fn full_transcript(transcript: &Transcript, question: &str, options: &PromptOptions) -> String {
+    options.finish(format!(
+        "Based on the following YouTube video transcript, please answer this question: {}{}\n\nTranscript:\n{}",
+        question,
+        citing(transcript),
+        fence(&transcript.timestamped_text(30.0))
+    ))
+}
+
+/// A question about the retrieved excerpts of a transcript, numbered for the answer to cite
+pub fn retrieval(transcript: &Transcript, excerpts: &[&Chunk], question: &str, options: &PromptOptions) -> String {
+    options.finish(format!(
+        "Video: {}\n\nExcerpts:\n{}\n\nQuestion: {}",
+        transcript.label(),
+        numbered_context(excerpts),
+        question
+    ))
+}
+
+/// A question about the transcript files attached after the prompt
+pub fn files(transcript: &Transcript, question: &str, options: &PromptOptions) -> String {
+    options.finish(format!(
+        "Based on the attached transcript of the video \"{}\", please answer the following question: {}\n\n\
+         Provide a detailed and accurate answer based solely on the information in the transcript.",
+        transcript.label(),
+        question
+    ))
+}
+
+/// A question about the transcript held in Gemini's context cache, which comes before the prompt
+pub fn cached(transcript: &Transcript, question: &str, options: &PromptOptions) -> String {
+    options.finish(format!(
+        "Based on the transcript of the video \"{}\" above, please answer this question: {}{}",
+        transcript.label(),
+        question,
+        citing(transcript)
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::retrieval::chunk_transcript;
+
+    const TRANSCRIPT: &str = include_str!("../tests/fixtures/prompts/transcript.json");
+    const QUESTION: &str = "Why does the speaker prefer work-stealing executors?";
+
+    const STRATEGIES: [Strategy; 4] = [Strategy::Full, Strategy::Retrieval, Strategy::Files, Strategy::Cached];
+    const STYLES: [Option<AnswerStyle>; 5] = [
+        None,
+        Some(AnswerStyle::Bullets),
+        Some(AnswerStyle::Table),
+        Some(AnswerStyle::Essay),
+        Some(AnswerStyle::OneLiner),
+    ];
+
+    fn transcript() -> Transcript {
+        serde_json::from_str(TRANSCRIPT).expect("valid fixture")
+    }
+
+    /// The prompt `strategy` builds, with the system prompt first so a change to either shows
+    fn prompt(strategy: Strategy, transcript: &Transcript, options: &PromptOptions) -> String {
+        let user = match strategy {
+            Strategy::Full | Strategy::Auto => full_transcript(transcript, QUESTION, options),
+            Strategy::Retrieval => {
+                let chunks = chunk_transcript(transcript, 160, 0);
+                let excerpts: Vec<&Chunk> = chunks.iter().rev().take(2).collect();
+                retrieval(transcript, &excerpts, QUESTION, options)
+            }
+            Strategy::Files => files(transcript, QUESTION, options),
+            Strategy::Cached => cached(transcript, QUESTION, options),
+        };
+        format!("[system]\n{}\n\n[user]\n{}", system_prompt(strategy), user)
+    }
+
+    fn style_name(style: Option<AnswerStyle>) -> &'static str {
+        match style {
+            None => "plain",
+            Some(AnswerStyle::Bullets) => "bullets",
+            Some(AnswerStyle::Table) => "table",
+            Some(AnswerStyle::Essay) => "essay",
+            Some(AnswerStyle::OneLiner) => "one_liner",
+        }
+    }
+
+    #[test]
+    fn every_strategy_and_style() {
+        let transcript = transcript();
+        for strategy in STRATEGIES {
+            for style in STYLES {
+                let options = PromptOptions {
+                    style,
+                    ..PromptOptions::default()
+                };
+                insta::assert_snapshot!(
+                    format!("{}_{}", strategy.name().replace(' ', "_"), style_name(style)),
+                    prompt(strategy, &transcript, &options)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn length_limit_and_standing_instructions() {
+        let transcript = transcript();
+        let options = PromptOptions {
+            style: Some(AnswerStyle::Bullets),
+            max_words: Some(120),
+            language: None,
+            instructions: vec![
+                "Always answer with code examples".to_string(),
+                "Use British spelling".to_string(),
+            ],
+        };
+        for strategy in STRATEGIES {
+            insta::assert_snapshot!(
+                format!("{}_instructed", strategy.name().replace(' ', "_")),
+                prompt(strategy, &transcript, &options)
+            );
+        }
+    }
+
+    #[test]
+    fn answer_language() {
+        let transcript = transcript();
+        let options = PromptOptions {
+            language: Some("Spanish".to_string()),
+            ..PromptOptions::default()
+        };
+        for strategy in STRATEGIES {
+            insta::assert_snapshot!(
+                format!("{}_in_spanish", strategy.name().replace(' ', "_")),
+                prompt(strategy, &transcript, &options)
+            );
+        }
+    }
+
+    #[test]
+    fn untimed_transcripts_are_not_asked_for_timestamps() {
+        let mut transcript = transcript();
+        transcript.segments.clear();
+        for strategy in STRATEGIES {
+            insta::assert_snapshot!(
+                format!("{}_untimed", strategy.name().replace(' ', "_")),
+                prompt(strategy, &transcript, &PromptOptions::default())
+            );
+        }
+    }
+}
@@ -0,0 +1,177 @@
+use serde::Serialize;
+
+use crate::transcript::{CaptionSource, Transcript};
+use crate::VideoTranscriber;
+
+/// Silences longer than this count as a hole in the captions
+const GAP_SECS: f64 = 10.0;
+/// Scores below this get a warning next to answers
+const UNRELIABLE_BELOW: f64 = 0.6;
+
+/// Heuristic estimate of how trustworthy a transcript's text is
+#[derive(Serialize, Debug, Clone)]
+pub struct CaptionQuality {
+    /// 0 (unusable) to 1 (clean creator captions)
+    pub score: f64,
+    /// Share of the captioned time from YouTube's speech recognition
+    pub auto_share: f64,
+    /// Share of the video's timeline inside silences longer than 10 seconds
+    pub gap_share: f64,
+    /// What lowered the score, worst first
+    pub issues: Vec<String>,
+}
+
+impl CaptionQuality {
+    pub fn grade(&self) -> &'static str {
+        if self.score >= 0.8 {
+            "good"
+        } else if self.score >= UNRELIABLE_BELOW {
+            "fair"
+        } else {
+            "poor"
+        }
+    }
+
+    pub fn is_unreliable(&self) -> bool {
+        self.score < UNRELIABLE_BELOW
+    }
+}
+
+/// Score a transcript from its caption type, signs of speech-recognition errors, and holes in
+/// its timeline. `None` for plain-text transcripts, which have nothing to measure.
+pub fn assess(transcript: &Transcript) -> Option<CaptionQuality> {
+    let segments = &transcript.segments;
+    let last = segments.iter().map(|s| s.end).reduce(f64::max)?;
+    if last <= 0.0 {
+        return None;
+    }
+    let mut penalties: Vec<(f64, String)> = Vec::new();
+
+    // Caption type
+    let duration = |s: &crate::transcript::Segment| (s.end - s.start).max(0.0);
+    let captioned: f64 = segments.iter().map(duration).sum();
+    let auto: f64 = segments
+        .iter()
+        .filter(|s| s.source == Some(CaptionSource::Auto))
+        .map(duration)
+        .sum();
+    let auto_share = if captioned > 0.0 { auto / captioned } else { 0.0 };
+    if auto_share > 0.0 {
+        penalties.push((
+            0.15 * auto_share,
+            format!("{:.0}% auto-generated captions", auto_share * 100.0),
+        ));
+    }
+
+    // Word-error heuristics: recognisers leave out punctuation, stutter on repeated words,
+    // bleep profanity as `[ __ ]`, and fall back to sound tags where they hear no words
+    let words: Vec<&str> = transcript.text.split_whitespace().collect();
+    if words.len() >= 50 {
+        let per_100 = |count: usize| count as f64 * 100.0 / words.len() as f64;
+        let sentences = words.iter().filter(|w| w.ends_with(['.', '?', '!'])).count();
+        if per_100(sentences) < 1.0 {
+            penalties.push((0.15, "almost no punctuation".to_string()));
+        }
+        let repeats = words
+            .windows(2)
+            .filter(|pair| pair[0].eq_ignore_ascii_case(pair[1]) && pair[0].chars().any(char::is_alphabetic))
+            .count();
+        if per_100(repeats) > 1.5 {
+            penalties.push((
+                (per_100(repeats) / 10.0).min(0.2),
+                format!("{:.1} repeated words per 100", per_100(repeats)),
+            ));
+        }
+        let bleeps = transcript.text.matches("[ __ ]").count();
+        let tags = segments
+            .iter()
+            .filter(|s| {
+                let text = s.text.trim();
+                text.starts_with('[') && text.ends_with(']')
+            })
+            .count();
+        if tags + bleeps > 0 && (tags + bleeps) * 10 > segments.len() {
+            penalties.push((0.1, format!("{} cue(s) with no words ([Music], [ __ ])", tags + bleeps)));
+        }
+        let minutes = captioned / 60.0;
+        if minutes > 1.0 && (words.len() as f64 / minutes) < 60.0 {
+            penalties.push((0.15, format!("only {:.0} words per minute", words.len() as f64 / minutes)));
+        }
+    }
+
+    // Coverage gaps
+    let mut gaps = segments.first().map_or(0.0, |s| s.start).max(0.0);
+    let mut covered_to = segments.first().map_or(0.0, |s| s.end);
+    for segment in segments.iter().skip(1) {
+        let gap = segment.start - covered_to;
+        if gap > GAP_SECS {
+            gaps += gap;
+        }
+        covered_to = covered_to.max(segment.end);
+    }
+    if gaps < GAP_SECS {
+        gaps = 0.0;
+    }
+    let gap_share = (gaps / last).min(1.0);
+    if gap_share > 0.05 {
+        penalties.push((
+            (gap_share * 0.8).min(0.4),
+            format!("{:.0}% of the video has no captions", gap_share * 100.0),
+        ));
+    }
+
+    penalties.sort_by(|a, b| b.0.total_cmp(&a.0));
+    let score = (1.0 - penalties.iter().map(|(p, _)| p).sum::<f64>()).clamp(0.0, 1.0);
+    Some(CaptionQuality {
+        score,
+        auto_share,
+        gap_share,
+        issues: penalties.into_iter().map(|(_, issue)| issue).collect(),
+    })
+}
+
+/// Where to point users whose captions are too poor to answer from
+pub fn whisper_hint(transcript: &Transcript) -> String {
+    if transcript.url.starts_with("file://") {
+        return "check the recording's audio".to_string();
+    }
+    format!(
+        "for a Whisper transcript, download the audio (e.g. `yt-dlp -x {}`) and index it with a file:// URL or `ingest-dir`",
+        transcript.url
+    )
+}
+
+impl VideoTranscriber {
+    /// Report caption quality when a video is indexed
+    pub fn report_caption_quality(&self, transcript: &Transcript) {
+        let Some(quality) = assess(transcript) else {
+            return;
+        };
+        if quality.issues.is_empty() {
+            status!("📏 Caption quality: {:.2} ({})", quality.score, quality.grade());
+        } else {
+            status!(
+                "📏 Caption quality: {:.2} ({}): {}",
+                quality.score,
+                quality.grade(),
+                quality.issues.join(", ")
+            );
+        }
+        if quality.is_unreliable() {
+            status!("⚠️  Answers about this video may be unreliable; {}", whisper_hint(transcript));
+        }
+    }
+
+    /// Warn before answering from captions that scored poorly
+    pub fn warn_if_unreliable(&self, transcript: &Transcript) {
+        if let Some(quality) = assess(transcript).filter(CaptionQuality::is_unreliable) {
+            status!(
+                "⚠️  {} has poor captions ({:.2}: {}), so this answer may be unreliable; {}",
+                transcript.label(),
+                quality.score,
+                quality.issues.join(", "),
+                whisper_hint(transcript)
+            );
+        }
+    }
+}
@@ -0,0 +1,130 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+use crate::chunking::timestamped_sections;
+use crate::companion::parse_timestamp;
+use crate::feedback::RETRIEVAL_THRESHOLD_CHARS;
+use crate::guardrail::fence;
+use crate::parallel::map_bounded;
+use crate::transcript::Transcript;
+use crate::VideoTranscriber;
+
+const QUIZ_SYSTEM_PROMPT: &str =
+    "You write flashcards that help students remember lectures. Every card is answerable from the \
+     transcript alone. Reply with JSON only.";
+
+/// Characters of transcript per section when cards for a long transcript are written in sections
+const SECTION_CHARS: usize = 30_000;
+/// Sections worked on at once
+const SECTION_CONCURRENCY: usize = 4;
+
+/// One flashcard
+#[derive(Serialize, Debug, Clone)]
+pub struct Card {
+    pub question: String,
+    pub answer: String,
+    /// Where in the video the answer is given, in seconds, when the model cited a time
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub at: Option<f64>,
+}
+
+#[derive(Deserialize)]
+struct Reply {
+    cards: Vec<ReplyCard>,
+}
+
+#[derive(Deserialize)]
+struct ReplyCard {
+    question: String,
+    answer: String,
+    #[serde(default)]
+    at: Option<String>,
+}
+
+impl ReplyCard {
+    fn into_card(self) -> Option<Card> {
+        let question = self.question.trim().to_string();
+        let answer = self.answer.trim().to_string();
+        if question.is_empty() || answer.is_empty() {
+            return None;
+        }
+        let at = self
+            .at
+            .and_then(|at| parse_timestamp(at.trim().trim_matches(['[', ']'])).ok());
+        Some(Card { question, answer, at })
+    }
+}
+
+impl VideoTranscriber {
+    /// Write `count` question-and-answer flashcards from a video's transcript. Long transcripts
+    /// are split into sections, each asked for its share of the cards, so the cards cover the
+    /// whole video.
+    pub fn generate_quiz(&self, transcript: &Transcript, count: usize) -> Result<Vec<Card>> {
+        status!("🎓 Writing {} flashcard(s) for \"{}\"...", count, transcript.label());
+        let mut cards = if transcript.text.len() <= RETRIEVAL_THRESHOLD_CHARS {
+            self.quiz_cards(transcript, &transcript.timestamped_text(30.0), count, None)?
+        } else {
+            let sections: Vec<String> = timestamped_sections(transcript, SECTION_CHARS)
+                .into_iter()
+                .map(|(_, text)| text)
+                .collect();
+            status!("🧩 The transcript is long; writing cards for its {} sections...", sections.len());
+
+            let total_chars: usize = sections.iter().map(String::len).sum();
+            let shares: Vec<(usize, &String)> = sections
+                .iter()
+                .map(|text| ((count * text.len()).div_ceil(total_chars.max(1)).max(1), text))
+                .collect();
+            let parts = shares.len();
+            let indexed: Vec<(usize, (usize, &String))> = shares.into_iter().enumerate().collect();
+            map_bounded(&indexed, SECTION_CONCURRENCY, |(index, (share, text))| {
+                self.quiz_cards(transcript, text, *share, Some((index + 1, parts)))
+            })
+            .into_iter()
+            .collect::<Result<Vec<_>>>()?
+            .into_iter()
+            .flatten()
+            .collect()
+        };
+
+        // Sections can repeat a point, and the model sometimes repeats a card
+        let mut seen = HashSet::new();
+        cards.retain(|card| seen.insert(card.question.to_lowercase()));
+        cards.truncate(count);
+        if cards.is_empty() {
+            anyhow::bail!("The model returned no usable flashcards");
+        }
+        if cards.len() < count {
+            status!("⚠️  Only {} of the {} cards asked for were usable", cards.len(), count);
+        }
+        Ok(cards)
+    }
+
+    /// Cards for one transcript or section of it
+    fn quiz_cards(
+        &self,
+        transcript: &Transcript,
+        text: &str,
+        count: usize,
+        section: Option<(usize, usize)>,
+    ) -> Result<Vec<Card>> {
+        let scope = match section {
+            Some((index, total)) => format!("section {} of {} of the transcript", index, total),
+            None => "the transcript".to_string(),
+        };
+        let prompt = format!(
+            "Write {} flashcards from {} of the video \"{}\". Each question tests one idea, definition, \
+             fact, or step the speaker explains, and makes sense without seeing the video. Each answer \
+             is one or two sentences. Cover the material in order and do not repeat a point. Where the \
+             transcript has [MM:SS] markers, give the nearest one before the answer is explained as \"at\". \
+             Return JSON: {{\"cards\": [{{\"question\": \"...\", \"answer\": \"...\", \"at\": \"MM:SS\"}}]}}\n\n{}",
+            count,
+            scope,
+            transcript.label(),
+            fence(text)
+        );
+        let reply: Reply = self.complete_json(QUIZ_SYSTEM_PROMPT, &prompt)?;
+        Ok(reply.cards.into_iter().filter_map(ReplyCard::into_card).collect())
+    }
+}
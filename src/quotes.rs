@@ -0,0 +1,164 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+use crate::chunking::timestamped_sections;
+use crate::feedback::RETRIEVAL_THRESHOLD_CHARS;
+use crate::grep::find_verbatim_range;
+use crate::guardrail::fence;
+use crate::parallel::map_bounded;
+use crate::transcript::{format_timestamp, Transcript};
+use crate::VideoTranscriber;
+
+const QUOTE_SYSTEM_PROMPT: &str =
+    "You find exact quotes in transcripts for journalists. Quotes must be copied character for \
+     character from the transcript, never paraphrased or cleaned up. Reply with JSON only.";
+
+/// Characters of transcript per section when a long transcript is searched in sections
+const SECTION_CHARS: usize = 30_000;
+/// Sections searched at once
+const SECTION_CONCURRENCY: usize = 4;
+
+/// A verbatim quote pulled from a transcript
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Quote {
+    pub text: String,
+    #[serde(default)]
+    pub speaker: Option<String>,
+    #[serde(default)]
+    pub seconds: Option<f64>,
+    /// How confident the model is that the quote is on-topic and exact (0.0-1.0)
+    pub confidence: f64,
+    /// Whether the quote was found word for word in the stored transcript
+    #[serde(default)]
+    pub verified: bool,
+}
+
+impl VideoTranscriber {
+    /// Find verbatim quotes about a topic, or the most notable quotes of the video when `about`
+    /// is `None`. Long transcripts are searched section by section, and the quotes the model
+    /// is most confident in are kept.
+    pub fn find_quotes(
+        &self,
+        url: &str,
+        about: Option<&str>,
+        count: usize,
+        include_unverified: bool,
+    ) -> Result<(Transcript, Vec<Quote>)> {
+        let transcript = self.load_transcript(url, false)?;
+        match about {
+            Some(about) => status!("🔍 Looking for quotes about \"{}\"...", about),
+            None => status!("🔍 Looking for the most notable quotes in \"{}\"...", transcript.label()),
+        }
+
+        let mut quotes = if transcript.text.len() <= RETRIEVAL_THRESHOLD_CHARS {
+            self.quote_candidates(&transcript.timestamped_text(30.0), about, count)?
+        } else {
+            let sections: Vec<String> = timestamped_sections(&transcript, SECTION_CHARS)
+                .into_iter()
+                .map(|(_, text)| text)
+                .collect();
+            status!("🧩 The transcript is long; searching its {} sections...", sections.len());
+            let mut quotes: Vec<Quote> = map_bounded(&sections, SECTION_CONCURRENCY, |text| {
+                self.quote_candidates(text, about, count)
+            })
+            .into_iter()
+            .collect::<Result<Vec<_>>>()?
+            .into_iter()
+            .flatten()
+            .collect();
+            quotes.sort_by(|a, b| b.confidence.total_cmp(&a.confidence));
+            quotes
+        };
+        verify_quotes(&transcript, &mut quotes);
+        // Sections overlap nothing, but the model can return one passage twice
+        let mut seen = HashSet::new();
+        quotes.retain(|quote| seen.insert(quote.text.to_lowercase()));
+
+        let unverified = quotes.iter().filter(|q| !q.verified).count();
+        if unverified > 0 && !include_unverified {
+            status!(
+                "🚫 Dropped {} quote(s) that do not appear verbatim in the transcript",
+                unverified
+            );
+            quotes.retain(|q| q.verified);
+        }
+
+        quotes.truncate(count);
+        Ok((transcript, quotes))
+    }
+
+    /// Quotes the model proposes from one transcript or section of it, before verification
+    fn quote_candidates(&self, text: &str, about: Option<&str>, count: usize) -> Result<Vec<Quote>> {
+        let wanted = match about {
+            Some(about) => format!("verbatim quotes about \"{}\"", about),
+            None => "of the most notable verbatim quotes: memorable lines, strong claims, figures, \
+                     predictions, and admissions a reporter would quote"
+                .to_string(),
+        };
+        let prompt = format!(
+            "Find up to {} {} in this transcript. Each quote is one to three complete sentences. \
+             Return a JSON array: [{{\"text\": exact words from the transcript, \
+             \"speaker\": name or null if unknown, \"seconds\": number or null, \
+             \"confidence\": number 0-1}}]. The transcript has [MM:SS] markers; use them for \
+             \"seconds\" but do not include them in the quote text. Lines starting with >> mark a \
+             new speaker.\n\nTranscript:\n{}",
+            count,
+            wanted,
+            fence(text)
+        );
+        self.complete_json(QUOTE_SYSTEM_PROMPT, &prompt)
+    }
+}
+
+/// Check each quote against the transcript text. A quote found there takes the transcript's
+/// exact wording, in case the model changed its case or punctuation, and the timestamp of
+/// where it actually occurs.
+pub fn verify_quotes(transcript: &Transcript, quotes: &mut [Quote]) {
+    for quote in quotes {
+        match find_verbatim_range(&transcript.text, &quote.text) {
+            Some(range) => {
+                quote.verified = true;
+                if let Some(seconds) = transcript.time_at_offset(range.start) {
+                    quote.seconds = Some(seconds);
+                }
+                quote.text = transcript.text[range].to_string();
+            }
+            None => quote.verified = false,
+        }
+    }
+}
+
+/// Render quotes as copy-ready Markdown blockquotes
+pub fn render_quotes(transcript: &Transcript, quotes: &[Quote]) -> String {
+    if quotes.is_empty() {
+        return "No matching quotes found.".to_string();
+    }
+
+    let mut out = String::new();
+    for quote in quotes {
+        let attribution = match quote.speaker.as_deref() {
+            Some(speaker) => format!("{}, ", speaker),
+            None => String::new(),
+        };
+        let location = match quote.seconds {
+            Some(seconds) => format!("[{}]({})", format_timestamp(seconds), transcript.link_at(seconds)),
+            None => transcript.url.clone(),
+        };
+        let source = quote
+            .seconds
+            .and_then(|seconds| transcript.source_at(seconds))
+            .map_or_else(String::new, |source| format!(", from {}", source.describe()));
+        out.push_str(&format!(
+            "> \"{}\"\n> — {}*{}*, {} (confidence {:.0}%{}{})\n\n",
+            quote.text.trim(),
+            attribution,
+            transcript.label(),
+            location,
+            quote.confidence * 100.0,
+            if quote.verified { ", verified" } else { ", ⚠️ NOT verified" },
+            source
+        ));
+    }
+    out
+}
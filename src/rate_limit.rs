@@ -0,0 +1,185 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use crate::progress::Progress;
+use crate::VideoTranscriber;
+
+/// Held while a Gemini call waits for room under the free-tier limits, so threads queue up
+/// behind each other instead of racing for the same slot
+static QUEUE: Mutex<()> = Mutex::new(());
+
+/// Which Gemini quota the API key is on
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Tier {
+    /// Keep under the free tier's limits, waiting for room rather than hitting 429s
+    Free,
+    /// No client-side limits
+    Paid,
+}
+
+impl Tier {
+    /// The tier named by `GEMINI_TIER` or the config file, `paid` when neither is set
+    pub fn from_setting(name: Option<String>) -> Result<Self> {
+        match name {
+            Some(name) => Tier::from_str(&name, true)
+                .map_err(|_| anyhow::anyhow!("Unknown GEMINI_TIER '{}' (expected free or paid)", name)),
+            None => Ok(Tier::Paid),
+        }
+    }
+}
+
+/// `[free_tier]` config section: the limits `--tier free` keeps to. The defaults are the free
+/// tier's published limits for Flash models and `text-embedding-004`.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct FreeTierLimits {
+    /// Generate and context-cache requests per minute
+    pub requests_per_minute: usize,
+    /// Embedding requests per minute (a batch counts as one)
+    pub embed_requests_per_minute: usize,
+    /// Tokens sent to the model over any 24 hours, estimated from request size
+    pub tokens_per_day: u64,
+}
+
+impl Default for FreeTierLimits {
+    fn default() -> Self {
+        Self {
+            requests_per_minute: 15,
+            embed_requests_per_minute: 1500,
+            tokens_per_day: 1_000_000,
+        }
+    }
+}
+
+/// The quota a Gemini request counts against
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GeminiCall {
+    /// `generateContent` and `cachedContents`, which spend tokens
+    Generate,
+    /// `embedContent` and `batchEmbedContents`
+    Embed,
+}
+
+/// Free-tier requests made over the last minute and tokens over the last day, kept in the data
+/// directory so the limits hold across runs
+#[derive(Serialize, Deserialize, Default)]
+struct Usage {
+    generate: Vec<DateTime<Utc>>,
+    embed: Vec<DateTime<Utc>>,
+    tokens: Vec<(DateTime<Utc>, u64)>,
+}
+
+impl Usage {
+    fn path(dir: &Path) -> PathBuf {
+        dir.join("free_tier_usage.json")
+    }
+
+    fn load(dir: &Path) -> Result<Self> {
+        let path = Self::path(dir);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let contents = fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        serde_json::from_str(&contents).with_context(|| format!("Failed to parse {}", path.display()))
+    }
+
+    fn save(&self, dir: &Path) -> Result<()> {
+        let path = Self::path(dir);
+        fs::write(&path, serde_json::to_string(self)?)
+            .with_context(|| format!("Failed to write {}", path.display()))
+    }
+
+    /// Forget requests older than a minute and tokens older than a day
+    fn prune(&mut self, now: DateTime<Utc>) {
+        let minute_ago = now - chrono::Duration::minutes(1);
+        let day_ago = now - chrono::Duration::days(1);
+        self.generate.retain(|at| *at > minute_ago);
+        self.embed.retain(|at| *at > minute_ago);
+        self.tokens.retain(|(at, _)| *at > day_ago);
+    }
+}
+
+/// Rough token count of a request body: about four characters per token
+pub fn estimate_tokens(chars: usize) -> u64 {
+    chars.div_ceil(4) as u64
+}
+
+impl VideoTranscriber {
+    /// The tier asked for with `--tier`, else `GEMINI_TIER` or `gemini_tier` in the config
+    /// file, else `paid`
+    pub fn configured_tier(&self) -> Result<Tier> {
+        if let Some(tier) = self.tier {
+            return Ok(tier);
+        }
+        Tier::from_setting(self.setting("GEMINI_TIER", &self.config.gemini_tier))
+    }
+
+    /// On the free tier, wait until a Gemini request of about `tokens` tokens fits the
+    /// per-minute limit, then count it. Fails rather than waiting when it would go over the
+    /// daily token limit, since that frees up only hours later.
+    pub fn throttle_gemini(&self, call: GeminiCall, tokens: u64) -> Result<()> {
+        if self.configured_tier()? != Tier::Free {
+            return Ok(());
+        }
+        let limits = self.config.free_tier.clone().unwrap_or_default();
+        let _queue = QUEUE.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        loop {
+            let now = Utc::now();
+            let mut usage = Usage::load(&self.data_dir)?;
+            usage.prune(now);
+
+            let spent: u64 = usage.tokens.iter().map(|(_, tokens)| tokens).sum();
+            if tokens > 0 && spent + tokens > limits.tokens_per_day {
+                let frees_at = usage
+                    .tokens
+                    .first()
+                    .map(|(at, _)| *at + chrono::Duration::days(1))
+                    .unwrap_or(now);
+                anyhow::bail!(
+                    "This request (~{} tokens) would go over the free tier's {} tokens a day: {} were \
+                     used in the last 24 hours. Room starts freeing up at {}; use a paid key with `--tier paid` \
+                     to go on now.",
+                    tokens,
+                    limits.tokens_per_day,
+                    spent,
+                    frees_at.format("%Y-%m-%d %H:%M UTC")
+                );
+            }
+
+            let (recent, per_minute, kind) = match call {
+                GeminiCall::Generate => (&mut usage.generate, limits.requests_per_minute, "generate"),
+                GeminiCall::Embed => (&mut usage.embed, limits.embed_requests_per_minute, "embedding"),
+            };
+            if recent.len() < per_minute.max(1) {
+                recent.push(now);
+                if tokens > 0 {
+                    usage.tokens.push((now, tokens));
+                }
+                return usage.save(&self.data_dir);
+            }
+
+            let wait = (recent[0] + chrono::Duration::minutes(1) - now)
+                .to_std()
+                .unwrap_or_default()
+                .max(std::time::Duration::from_millis(100));
+            status!(
+                "⏳ Free tier: {} {} requests in the last minute (limit {}); waiting {}s",
+                recent.len(),
+                kind,
+                per_minute,
+                wait.as_secs_f64().ceil()
+            );
+            self.report(Progress::RateLimited {
+                wait_secs: wait.as_secs_f64().ceil() as u64,
+            });
+            self.cancel.sleep(wait)?;
+        }
+    }
+}
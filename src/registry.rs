@@ -0,0 +1,696 @@
+use anyhow::{Context, Result};
+use rusqlite::{params, Connection, OptionalExtension};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs;
+use std::path::Path;
+use std::sync::{Mutex, Once};
+
+use crate::conversation::Turn;
+use crate::parts::UploadedPart;
+use crate::store::IndexStore;
+use crate::transcript::Transcript;
+
+/// Schema changes, applied in order; `PRAGMA user_version` records how many have run
+const MIGRATIONS: &[&str] = &[
+    "CREATE TABLE uploads (
+        video_id    TEXT NOT NULL,
+        part        INTEGER NOT NULL,
+        start_secs  REAL,
+        end_secs    REAL,
+        byte_start  INTEGER NOT NULL,
+        byte_end    INTEGER NOT NULL,
+        file_uri    TEXT NOT NULL,
+        uploaded_at TEXT NOT NULL,
+        PRIMARY KEY (video_id, part)
+    )",
+    // Videos indexed before this table existed get their first upload time and no title
+    "CREATE TABLE videos (
+        video_id   TEXT PRIMARY KEY,
+        url        TEXT NOT NULL,
+        title      TEXT,
+        channel    TEXT,
+        indexed_at TEXT NOT NULL
+    );
+    INSERT INTO videos (video_id, url, indexed_at)
+        SELECT video_id, 'https://www.youtube.com/watch?v=' || video_id, MIN(uploaded_at)
+        FROM uploads GROUP BY video_id",
+    // Gemini reports each file's expiry; older rows get the documented 48 hours
+    "ALTER TABLE uploads ADD COLUMN expires_at TEXT;
+    UPDATE uploads SET expires_at = datetime(substr(uploaded_at, 1, 19), '+48 hours') || '+00:00'",
+    // Playlists indexed with `index --playlist`, and their videos in playlist order
+    "CREATE TABLE playlists (
+        playlist_id TEXT PRIMARY KEY,
+        url         TEXT NOT NULL,
+        title       TEXT,
+        channel     TEXT,
+        indexed_at  TEXT NOT NULL
+    );
+    CREATE TABLE playlist_videos (
+        playlist_id TEXT NOT NULL,
+        video_id    TEXT NOT NULL,
+        position    INTEGER NOT NULL,
+        PRIMARY KEY (playlist_id, video_id)
+    )",
+    // Transcript chunk embeddings (768-dimensional `text-embedding-004` vectors) in a sqlite-vec
+    // table, each sharing its rowid with the chunk's row in `chunks`
+    "CREATE TABLE chunks (
+        id        INTEGER PRIMARY KEY,
+        video_id  TEXT NOT NULL,
+        chunk_key TEXT NOT NULL,
+        UNIQUE (video_id, chunk_key)
+    );
+    CREATE VIRTUAL TABLE chunk_vectors USING vec0(
+        video_id  TEXT PARTITION KEY,
+        embedding float[768] distance_metric=cosine
+    )",
+    // What YouTube reported about a video when it was indexed, to notice re-uploads and edits
+    "ALTER TABLE videos ADD COLUMN duration_secs REAL;
+    ALTER TABLE videos ADD COLUMN published_at TEXT",
+    // Videos kept out of the shared server, archives, and reports, and why
+    "ALTER TABLE videos ADD COLUMN private INTEGER NOT NULL DEFAULT 0;
+    ALTER TABLE videos ADD COLUMN access_note TEXT",
+    // Conversations continued across runs with `ask --session`
+    "CREATE TABLE sessions (
+        session_id  TEXT PRIMARY KEY,
+        video_id    TEXT NOT NULL,
+        url         TEXT NOT NULL,
+        created_at  TEXT NOT NULL,
+        updated_at  TEXT NOT NULL
+    );
+    CREATE TABLE session_turns (
+        session_id  TEXT NOT NULL,
+        turn        INTEGER NOT NULL,
+        question    TEXT NOT NULL,
+        answer      TEXT NOT NULL,
+        asked_at    TEXT NOT NULL,
+        PRIMARY KEY (session_id, turn)
+    )",
+];
+
+/// Most neighbors sqlite-vec returns from one search
+pub const MAX_NEAREST_CHUNKS: usize = 4096;
+
+/// A stored chunk embedding close to a search vector
+#[derive(Debug, Clone)]
+pub struct ChunkMatch {
+    pub video_id: String,
+    /// The chunk's `feedback::chunk_key`
+    pub chunk_key: String,
+    /// Cosine similarity to the search vector
+    pub similarity: f32,
+}
+
+/// A video as recorded when it was last indexed
+#[derive(Serialize, Debug, Clone)]
+pub struct VideoRecord {
+    pub video_id: String,
+    pub url: String,
+    pub title: Option<String>,
+    pub channel: Option<String>,
+    pub indexed_at: DateTime<Utc>,
+    /// Length and publish date as the source reported them at indexing
+    pub duration_secs: Option<f64>,
+    pub published_at: Option<DateTime<Utc>>,
+    /// Kept out of the shared server, archives, and reports unless asked for
+    pub private: bool,
+    /// Who may see a private video, or why it is private
+    pub access_note: Option<String>,
+}
+
+/// A conversation saved with `ask --session`
+#[derive(Serialize, Debug, Clone)]
+pub struct SessionRecord {
+    pub session_id: String,
+    pub video_id: String,
+    /// The URL the conversation started with, used when a later question gives none
+    pub url: String,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    pub turns: usize,
+}
+
+/// A playlist as recorded when it was last indexed
+#[derive(Serialize, Debug, Clone)]
+pub struct PlaylistRecord {
+    pub playlist_id: String,
+    pub url: String,
+    pub title: Option<String>,
+    pub channel: Option<String>,
+    pub indexed_at: DateTime<Utc>,
+}
+
+/// `uploads.json` as written before the registry existed, imported once
+#[derive(Deserialize)]
+struct LegacyUploads {
+    videos: BTreeMap<String, Vec<UploadedPart>>,
+}
+
+/// Local index registry: which Gemini files hold each video's transcript, and since when, plus
+/// the embeddings of each video's transcript chunks.
+///
+/// Backed by SQLite in the data directory so lookups stay cheap as the library grows; chunk
+/// embeddings are searched in place with the sqlite-vec extension, so similarity search needs
+/// no network access beyond embedding the query.
+pub struct Registry {
+    conn: Mutex<Connection>,
+}
+
+impl Registry {
+    /// Open the registry database (`index.db`) in `data_dir`, importing the `uploads.json`
+    /// that versions before the registry left there
+    pub fn open_in(data_dir: &Path) -> Result<Self> {
+        let registry = Self::open_at(&data_dir.join("index.db"))?;
+        registry.import_legacy(data_dir)?;
+        Ok(registry)
+    }
+
+    /// Open the registry database at `path` rather than in the data directory. Nothing is
+    /// imported, since legacy files only ever lived in the data directory.
+    pub fn open_at(path: &Path) -> Result<Self> {
+        register_sqlite_vec();
+        let conn =
+            Connection::open(path).with_context(|| format!("Failed to open {}", path.display()))?;
+        Self::from_connection(conn).with_context(|| format!("Failed to migrate {}", path.display()))
+    }
+
+    fn from_connection(mut conn: Connection) -> Result<Self> {
+        migrate(&mut conn)?;
+        Ok(Self {
+            conn: Mutex::new(conn),
+        })
+    }
+
+    fn import_legacy(&self, data_dir: &Path) -> Result<()> {
+        let legacy = data_dir.join("uploads.json");
+        if !legacy.exists() {
+            return Ok(());
+        }
+        let contents = fs::read_to_string(&legacy)
+            .with_context(|| format!("Failed to read {}", legacy.display()))?;
+        let uploads: LegacyUploads = serde_json::from_str(&contents)
+            .with_context(|| format!("Failed to parse {}", legacy.display()))?;
+        for (video_id, parts) in &uploads.videos {
+            self.replace_parts(video_id, parts)?;
+        }
+        fs::remove_file(&legacy).with_context(|| format!("Failed to remove {}", legacy.display()))
+    }
+}
+
+impl IndexStore for Registry {
+    fn parts(&self, video_id: &str) -> Result<Vec<UploadedPart>> {
+        let conn = self.conn.lock().unwrap();
+        let mut statement = conn.prepare_cached(
+            "SELECT part, start_secs, end_secs, byte_start, byte_end, file_uri, uploaded_at, expires_at
+             FROM uploads WHERE video_id = ?1 ORDER BY part",
+        )?;
+        let parts = statement
+            .query_map(params![video_id], |row| {
+                Ok(UploadedPart {
+                    index: row.get(0)?,
+                    start: row.get(1)?,
+                    end: row.get(2)?,
+                    range: row.get(3)?..row.get(4)?,
+                    file_uri: row.get(5)?,
+                    uploaded_at: row.get(6)?,
+                    expires_at: row.get(7)?,
+                })
+            })?
+            .collect::<rusqlite::Result<_>>()?;
+        Ok(parts)
+    }
+
+    fn replace_parts(&self, video_id: &str, parts: &[UploadedPart]) -> Result<()> {
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction()?;
+        tx.execute("DELETE FROM uploads WHERE video_id = ?1", params![video_id])?;
+        for part in parts {
+            tx.execute(
+                "INSERT INTO uploads
+                 (video_id, part, start_secs, end_secs, byte_start, byte_end, file_uri, uploaded_at, expires_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+                params![
+                    video_id,
+                    part.index,
+                    part.start,
+                    part.end,
+                    part.range.start,
+                    part.range.end,
+                    part.file_uri,
+                    part.uploaded_at,
+                    part.expires_at,
+                ],
+            )?;
+        }
+        tx.commit().context("Failed to update the index registry")
+    }
+
+    fn record_video(&self, transcript: &Transcript) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "INSERT INTO videos (video_id, url, title, channel, indexed_at, duration_secs, published_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
+             ON CONFLICT (video_id) DO UPDATE SET
+                url = excluded.url,
+                title = COALESCE(excluded.title, videos.title),
+                channel = COALESCE(excluded.channel, videos.channel),
+                indexed_at = excluded.indexed_at,
+                duration_secs = excluded.duration_secs,
+                published_at = excluded.published_at",
+            params![
+                transcript.video_id,
+                transcript.url,
+                transcript.title,
+                transcript.channel,
+                Utc::now(),
+                transcript.duration_secs,
+                transcript.published_at,
+            ],
+        )
+        .context("Failed to update the index registry")?;
+        Ok(())
+    }
+
+    fn record_playlist(&self, playlist: &PlaylistRecord, video_ids: &[String]) -> Result<()> {
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction()?;
+        tx.execute(
+            "INSERT INTO playlists (playlist_id, url, title, channel, indexed_at)
+             VALUES (?1, ?2, ?3, ?4, ?5)
+             ON CONFLICT (playlist_id) DO UPDATE SET
+                url = excluded.url,
+                title = COALESCE(excluded.title, playlists.title),
+                channel = COALESCE(excluded.channel, playlists.channel),
+                indexed_at = excluded.indexed_at",
+            params![
+                playlist.playlist_id,
+                playlist.url,
+                playlist.title,
+                playlist.channel,
+                playlist.indexed_at,
+            ],
+        )?;
+        tx.execute("DELETE FROM playlist_videos WHERE playlist_id = ?1", params![playlist.playlist_id])?;
+        for (position, video_id) in video_ids.iter().enumerate() {
+            tx.execute(
+                "INSERT OR IGNORE INTO playlist_videos (playlist_id, video_id, position) VALUES (?1, ?2, ?3)",
+                params![playlist.playlist_id, video_id, position],
+            )?;
+        }
+        tx.commit().context("Failed to update the index registry")
+    }
+
+    fn playlist_videos(&self, playlist_id: &str) -> Result<Option<Vec<String>>> {
+        let conn = self.conn.lock().unwrap();
+        let known: bool = conn.query_row(
+            "SELECT EXISTS (SELECT 1 FROM playlists WHERE playlist_id = ?1)",
+            params![playlist_id],
+            |row| row.get(0),
+        )?;
+        if !known {
+            return Ok(None);
+        }
+        let mut statement = conn.prepare_cached(
+            "SELECT video_id FROM playlist_videos WHERE playlist_id = ?1 ORDER BY position",
+        )?;
+        let video_ids = statement
+            .query_map(params![playlist_id], |row| row.get(0))?
+            .collect::<rusqlite::Result<_>>()?;
+        Ok(Some(video_ids))
+    }
+
+    fn playlists(&self) -> Result<Vec<(PlaylistRecord, usize)>> {
+        let conn = self.conn.lock().unwrap();
+        let mut statement = conn.prepare_cached(
+            "SELECT p.playlist_id, p.url, p.title, p.channel, p.indexed_at, COUNT(v.video_id)
+             FROM playlists p LEFT JOIN playlist_videos v ON v.playlist_id = p.playlist_id
+             GROUP BY p.playlist_id ORDER BY p.indexed_at DESC",
+        )?;
+        let playlists = statement
+            .query_map([], |row| {
+                Ok((
+                    PlaylistRecord {
+                        playlist_id: row.get(0)?,
+                        url: row.get(1)?,
+                        title: row.get(2)?,
+                        channel: row.get(3)?,
+                        indexed_at: row.get(4)?,
+                    },
+                    row.get(5)?,
+                ))
+            })?
+            .collect::<rusqlite::Result<_>>()?;
+        Ok(playlists)
+    }
+
+    fn delete_video(&self, video_id: &str) -> Result<bool> {
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction()?;
+        let uploads = tx.execute("DELETE FROM uploads WHERE video_id = ?1", params![video_id])?;
+        let videos = tx.execute("DELETE FROM videos WHERE video_id = ?1", params![video_id])?;
+        tx.execute("DELETE FROM playlist_videos WHERE video_id = ?1", params![video_id])?;
+        delete_chunk_rows(&tx, video_id)?;
+        tx.commit().context("Failed to update the index registry")?;
+        Ok(uploads + videos > 0)
+    }
+
+    fn videos(&self) -> Result<Vec<VideoRecord>> {
+        let conn = self.conn.lock().unwrap();
+        let mut statement = conn.prepare_cached(
+            "SELECT video_id, url, title, channel, indexed_at, duration_secs, published_at, private, access_note
+             FROM videos ORDER BY indexed_at DESC",
+        )?;
+        let videos = statement
+            .query_map([], |row| {
+                Ok(VideoRecord {
+                    video_id: row.get(0)?,
+                    url: row.get(1)?,
+                    title: row.get(2)?,
+                    channel: row.get(3)?,
+                    indexed_at: row.get(4)?,
+                    duration_secs: row.get(5)?,
+                    published_at: row.get(6)?,
+                    private: row.get(7)?,
+                    access_note: row.get(8)?,
+                })
+            })?
+            .collect::<rusqlite::Result<_>>()?;
+        Ok(videos)
+    }
+
+    fn set_private(&self, video_id: &str, private: bool, note: Option<&str>) -> Result<bool> {
+        let conn = self.conn.lock().unwrap();
+        let updated = conn
+            .execute(
+                "UPDATE videos SET private = ?2, access_note = ?3 WHERE video_id = ?1",
+                params![video_id, private, note.filter(|_| private)],
+            )
+            .context("Failed to update the index registry")?;
+        Ok(updated > 0)
+    }
+
+    fn private_videos(&self) -> Result<HashMap<String, Option<String>>> {
+        let conn = self.conn.lock().unwrap();
+        let mut statement = conn.prepare_cached("SELECT video_id, access_note FROM videos WHERE private")?;
+        let videos = statement
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+            .collect::<rusqlite::Result<_>>()?;
+        Ok(videos)
+    }
+
+    fn sessions(&self) -> Result<Vec<SessionRecord>> {
+        let conn = self.conn.lock().unwrap();
+        let mut statement = conn.prepare_cached(
+            "SELECT s.session_id, s.video_id, s.url, s.created_at, s.updated_at, COUNT(t.turn)
+             FROM sessions s LEFT JOIN session_turns t ON t.session_id = s.session_id
+             GROUP BY s.session_id ORDER BY s.updated_at DESC",
+        )?;
+        let sessions = statement
+            .query_map([], |row| {
+                Ok(SessionRecord {
+                    session_id: row.get(0)?,
+                    video_id: row.get(1)?,
+                    url: row.get(2)?,
+                    created_at: row.get(3)?,
+                    updated_at: row.get(4)?,
+                    turns: row.get(5)?,
+                })
+            })?
+            .collect::<rusqlite::Result<_>>()?;
+        Ok(sessions)
+    }
+
+    fn session_turns(&self, session_id: &str) -> Result<Vec<Turn>> {
+        let conn = self.conn.lock().unwrap();
+        let mut statement = conn.prepare_cached(
+            "SELECT question, answer FROM session_turns WHERE session_id = ?1 ORDER BY turn",
+        )?;
+        let turns = statement
+            .query_map(params![session_id], |row| {
+                Ok(Turn {
+                    question: row.get(0)?,
+                    answer: row.get(1)?,
+                })
+            })?
+            .collect::<rusqlite::Result<_>>()?;
+        Ok(turns)
+    }
+
+    fn add_session_turn(&self, session_id: &str, video_id: &str, url: &str, turn: &Turn) -> Result<()> {
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction()?;
+        let started_with: Option<String> = tx
+            .query_row("SELECT video_id FROM sessions WHERE session_id = ?1", params![session_id], |row| row.get(0))
+            .optional()?;
+        if let Some(started_with) = started_with.filter(|started_with| started_with != video_id) {
+            anyhow::bail!("Session '{}' is about video {}, not {}", session_id, started_with, video_id);
+        }
+        let now = Utc::now();
+        tx.execute(
+            "INSERT INTO sessions (session_id, video_id, url, created_at, updated_at)
+             VALUES (?1, ?2, ?3, ?4, ?4)
+             ON CONFLICT (session_id) DO UPDATE SET updated_at = excluded.updated_at",
+            params![session_id, video_id, url, now],
+        )?;
+        tx.execute(
+            "INSERT INTO session_turns (session_id, turn, question, answer, asked_at)
+             VALUES (?1, (SELECT COUNT(*) FROM session_turns WHERE session_id = ?1), ?2, ?3, ?4)",
+            params![session_id, turn.question, turn.answer, now],
+        )?;
+        tx.commit().context("Failed to save the conversation")
+    }
+
+    fn delete_session(&self, session_id: &str) -> Result<bool> {
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction()?;
+        tx.execute("DELETE FROM session_turns WHERE session_id = ?1", params![session_id])?;
+        let deleted = tx.execute("DELETE FROM sessions WHERE session_id = ?1", params![session_id])?;
+        tx.commit().context("Failed to delete the conversation")?;
+        Ok(deleted > 0)
+    }
+
+    fn add_chunk_vectors(&self, video_id: &str, vectors: &[(String, Vec<f32>)]) -> Result<()> {
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction()?;
+        for (chunk_key, vector) in vectors {
+            let added = tx.execute(
+                "INSERT OR IGNORE INTO chunks (video_id, chunk_key) VALUES (?1, ?2)",
+                params![video_id, chunk_key],
+            )?;
+            if added > 0 {
+                tx.execute(
+                    "INSERT INTO chunk_vectors (rowid, video_id, embedding) VALUES (?1, ?2, ?3)",
+                    params![tx.last_insert_rowid(), video_id, vector_blob(vector)],
+                )?;
+            }
+        }
+        tx.commit().context("Failed to store chunk embeddings")
+    }
+
+    fn delete_chunk_vectors(&self, video_id: &str) -> Result<usize> {
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction()?;
+        let deleted = delete_chunk_rows(&tx, video_id)?;
+        tx.commit().context("Failed to delete chunk embeddings")?;
+        Ok(deleted)
+    }
+
+    fn chunk_vector_keys(&self, video_id: &str) -> Result<HashSet<String>> {
+        let conn = self.conn.lock().unwrap();
+        let mut statement = conn.prepare_cached("SELECT chunk_key FROM chunks WHERE video_id = ?1")?;
+        let keys = statement
+            .query_map(params![video_id], |row| row.get(0))?
+            .collect::<rusqlite::Result<_>>()?;
+        Ok(keys)
+    }
+
+    fn nearest_chunks(&self, query: &[f32], k: usize, video_id: Option<&str>) -> Result<Vec<ChunkMatch>> {
+        let conn = self.conn.lock().unwrap();
+        // The partition key must be a plain constraint for sqlite-vec to search only that video
+        let mut statement = conn.prepare_cached(if video_id.is_some() {
+            "SELECT c.video_id, c.chunk_key, v.distance
+             FROM chunk_vectors v JOIN chunks c ON c.id = v.rowid
+             WHERE v.embedding MATCH ?1 AND v.k = ?2 AND v.video_id = ?3
+             ORDER BY v.distance"
+        } else {
+            "SELECT c.video_id, c.chunk_key, v.distance
+             FROM chunk_vectors v JOIN chunks c ON c.id = v.rowid
+             WHERE v.embedding MATCH ?1 AND v.k = ?2 AND ?3 IS NULL
+             ORDER BY v.distance"
+        })?;
+        let matches = statement
+            .query_map(params![vector_blob(query), k.min(MAX_NEAREST_CHUNKS), video_id], |row| {
+                let distance: f64 = row.get(2)?;
+                Ok(ChunkMatch {
+                    video_id: row.get(0)?,
+                    chunk_key: row.get(1)?,
+                    similarity: (1.0 - distance) as f32,
+                })
+            })?
+            .collect::<rusqlite::Result<_>>()
+            .context("Failed to search chunk embeddings")?;
+        Ok(matches)
+    }
+}
+
+/// Load sqlite-vec into every connection opened from here on
+fn register_sqlite_vec() {
+    static REGISTER: Once = Once::new();
+    REGISTER.call_once(|| {
+        // SAFETY: `sqlite3_vec_init` is an SQLite extension entry point, which is the signature
+        // `sqlite3_auto_extension` expects; it is only declared without arguments
+        unsafe {
+            rusqlite::ffi::sqlite3_auto_extension(Some(std::mem::transmute::<
+                *const (),
+                unsafe extern "C" fn(
+                    *mut rusqlite::ffi::sqlite3,
+                    *mut *mut std::os::raw::c_char,
+                    *const rusqlite::ffi::sqlite3_api_routines,
+                ) -> std::os::raw::c_int,
+            >(sqlite_vec::sqlite3_vec_init as *const ())));
+        }
+    });
+}
+
+/// Delete a video's rows from `chunks` and `chunk_vectors`
+fn delete_chunk_rows(tx: &rusqlite::Transaction, video_id: &str) -> Result<usize> {
+    tx.execute("DELETE FROM chunk_vectors WHERE video_id = ?1", params![video_id])?;
+    Ok(tx.execute("DELETE FROM chunks WHERE video_id = ?1", params![video_id])?)
+}
+
+/// A vector in sqlite-vec's format: little-endian `f32`s back to back
+fn vector_blob(vector: &[f32]) -> Vec<u8> {
+    vector.iter().flat_map(|x| x.to_le_bytes()).collect()
+}
+
+fn migrate(conn: &mut Connection) -> Result<()> {
+    let version: usize = conn.query_row("PRAGMA user_version", [], |row| row.get(0))?;
+    let tx = conn.transaction()?;
+    for (i, migration) in MIGRATIONS.iter().enumerate().skip(version) {
+        tx.execute_batch(migration)?;
+        tx.pragma_update(None, "user_version", i + 1)?;
+    }
+    tx.commit()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn registry() -> Registry {
+        register_sqlite_vec();
+        Registry::from_connection(Connection::open_in_memory().unwrap()).unwrap()
+    }
+
+    fn turn(question: &str, answer: &str) -> Turn {
+        Turn {
+            question: question.to_string(),
+            answer: answer.to_string(),
+        }
+    }
+
+    fn scratch_dir(name: &str) -> std::path::PathBuf {
+        let dir = std::env::temp_dir().join(format!("cvt-registry-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn version_1_database_is_migrated() {
+        register_sqlite_vec();
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(MIGRATIONS[0]).unwrap();
+        conn.pragma_update(None, "user_version", 1).unwrap();
+        conn.execute(
+            "INSERT INTO uploads (video_id, part, start_secs, end_secs, byte_start, byte_end, file_uri, uploaded_at)
+             VALUES ('dQw4w9WgXcQ', 0, NULL, NULL, 0, 120, 'files/abc', '2024-01-01 00:00:00+00:00')",
+            [],
+        )
+        .unwrap();
+
+        let registry = Registry::from_connection(conn).unwrap();
+        let version: usize = registry
+            .conn
+            .lock()
+            .unwrap()
+            .query_row("PRAGMA user_version", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(version, MIGRATIONS.len());
+
+        let videos = registry.videos().unwrap();
+        assert_eq!(videos.len(), 1);
+        assert_eq!(videos[0].url, "https://www.youtube.com/watch?v=dQw4w9WgXcQ");
+        assert_eq!(videos[0].indexed_at, Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap());
+        assert!(!videos[0].private);
+
+        let parts = registry.parts("dQw4w9WgXcQ").unwrap();
+        assert_eq!(parts.len(), 1);
+        assert_eq!(parts[0].range, 0..120);
+        assert_eq!(parts[0].expires_at, Some(Utc.with_ymd_and_hms(2024, 1, 3, 0, 0, 0).unwrap()));
+    }
+
+    #[test]
+    fn migrating_twice_changes_nothing() {
+        let registry = registry();
+        let mut conn = registry.conn.lock().unwrap();
+        migrate(&mut conn).unwrap();
+    }
+
+    #[test]
+    fn session_turns_round_trip_in_order() {
+        let registry = registry();
+        let url = "https://www.youtube.com/watch?v=dQw4w9WgXcQ";
+        registry.add_session_turn("talk", "dQw4w9WgXcQ", url, &turn("Who sings?", "Rick.")).unwrap();
+        registry.add_session_turn("talk", "dQw4w9WgXcQ", url, &turn("When?", "1987.")).unwrap();
+
+        let turns = registry.session_turns("talk").unwrap();
+        assert_eq!(turns.len(), 2);
+        assert_eq!((turns[0].question.as_str(), turns[0].answer.as_str()), ("Who sings?", "Rick."));
+        assert_eq!((turns[1].question.as_str(), turns[1].answer.as_str()), ("When?", "1987."));
+
+        let session = registry.session("talk").unwrap().unwrap();
+        assert_eq!(session.video_id, "dQw4w9WgXcQ");
+        assert_eq!(session.url, url);
+        assert_eq!(session.turns, 2);
+
+        assert!(registry.delete_session("talk").unwrap());
+        assert!(registry.session_turns("talk").unwrap().is_empty());
+        assert!(registry.session("talk").unwrap().is_none());
+    }
+
+    #[test]
+    fn session_turn_for_another_video_is_rejected() {
+        let registry = registry();
+        registry
+            .add_session_turn("talk", "dQw4w9WgXcQ", "https://youtu.be/dQw4w9WgXcQ", &turn("Who sings?", "Rick."))
+            .unwrap();
+        let error = registry
+            .add_session_turn("talk", "9bZkp7q19f0", "https://youtu.be/9bZkp7q19f0", &turn("Who?", "PSY."))
+            .unwrap_err();
+        assert!(error.to_string().contains("is about video dQw4w9WgXcQ"), "{:#}", error);
+        assert_eq!(registry.session_turns("talk").unwrap().len(), 1);
+    }
+
+    #[test]
+    fn legacy_uploads_are_imported_from_the_data_dir_only() {
+        let legacy = r#"{"videos": {"dQw4w9WgXcQ": [{"index": 0, "start": null, "end": null,
+            "range": {"start": 0, "end": 120}, "file_uri": "files/abc",
+            "uploaded_at": "2024-01-01T00:00:00Z"}]}}"#;
+
+        let dir = scratch_dir("custom");
+        fs::write(dir.join("uploads.json"), legacy).unwrap();
+        let registry = Registry::open_at(&dir.join("custom.db")).unwrap();
+        assert!(registry.parts("dQw4w9WgXcQ").unwrap().is_empty());
+        assert!(dir.join("uploads.json").exists());
+
+        let dir = scratch_dir("data");
+        fs::write(dir.join("uploads.json"), legacy).unwrap();
+        let registry = Registry::open_in(&dir).unwrap();
+        assert_eq!(registry.parts("dQw4w9WgXcQ").unwrap()[0].file_uri, "files/abc");
+        assert!(!dir.join("uploads.json").exists());
+    }
+}
@@ -0,0 +1,76 @@
+use anyhow::Result;
+
+use crate::transcript::Transcript;
+use crate::VideoTranscriber;
+
+/// Suggestions below this similarity are not worth showing
+const MIN_RELATED_SIMILARITY: f32 = 0.55;
+
+impl VideoTranscriber {
+    /// Library videos most similar to the given video
+    pub fn related_videos(&self, url: &str, limit: usize) -> Result<Vec<(Transcript, f32)>> {
+        let video_id = self.extract_video_id(url)?;
+        let transcripts = self.cache.list()?;
+        let store = self.video_embeddings(&transcripts)?;
+        let vectors = store.rows_for(&transcripts)?;
+
+        let target = transcripts
+            .iter()
+            .position(|t| t.video_id == video_id)
+            .ok_or_else(|| anyhow::anyhow!("Video {} is not in the library; index it first", video_id))?;
+
+        self.rank(&transcripts, &vectors, vectors[target], &video_id, limit)
+    }
+
+    /// Library videos covering the same topic as a question and its answer
+    pub fn related_to_answer(
+        &self,
+        current_video_id: &str,
+        question: &str,
+        answer: &str,
+        limit: usize,
+    ) -> Result<Vec<(Transcript, f32)>> {
+        let transcripts = self.cache.list()?;
+        let store = self.video_embeddings(&transcripts)?;
+        let vectors = store.rows_for(&transcripts)?;
+        let query = self.embed_text(&format!("{}\n\n{}", question, answer))?;
+
+        self.rank(&transcripts, &vectors, &query, current_video_id, limit)
+    }
+
+    fn rank(
+        &self,
+        transcripts: &[Transcript],
+        vectors: &[&[f32]],
+        query: &[f32],
+        exclude_id: &str,
+        limit: usize,
+    ) -> Result<Vec<(Transcript, f32)>> {
+        // One extra in case the excluded video is among the nearest
+        let nearest = self.nearest_videos(transcripts, vectors, query, limit + 1)?;
+        Ok(nearest
+            .into_iter()
+            .filter(|&(i, score)| transcripts[i].video_id != exclude_id && score >= MIN_RELATED_SIMILARITY)
+            .take(limit)
+            .map(|(i, score)| (transcripts[i].clone(), score))
+            .collect())
+    }
+}
+
+/// Suggestions as a short list with similarity scores
+pub fn render_related(related: &[(Transcript, f32)]) -> String {
+    if related.is_empty() {
+        return "No related videos found in the library.\n".to_string();
+    }
+    let mut out = String::new();
+    for (transcript, score) in related {
+        out.push_str(&format!(
+            "- {} ({}) — {:.0}% similar\n  {}\n",
+            transcript.label(),
+            transcript.channel.as_deref().unwrap_or("unknown channel"),
+            score * 100.0,
+            transcript.url
+        ));
+    }
+    out
+}
@@ -0,0 +1,213 @@
+use anyhow::Result;
+use regex::Regex;
+use serde::Deserialize;
+use std::collections::HashMap;
+
+use crate::parallel::map_bounded;
+use crate::retrieval::{bm25_rank, chunk_transcript, numbered_context, Chunk, DEFAULT_CHUNK_CHARS, DEFAULT_CHUNK_OVERLAP};
+use crate::VideoTranscriber;
+
+/// Excerpts each section is written from
+const SECTION_TOP_K: usize = 10;
+/// Sections written at the same time
+const SECTION_CONCURRENCY: usize = 3;
+
+const PLAN_SYSTEM_PROMPT: &str =
+    "You plan research reports written from a personal library of video transcripts. Reply with JSON only.";
+
+const SECTION_SYSTEM_PROMPT: &str =
+    "You write one section of a research report from excerpts of video transcripts. Every claim must \
+     cite the excerpt it comes from as [n]. Compare what different videos say, note where they \
+     disagree, and say plainly when the excerpts leave part of the section's focus uncovered. Never \
+     use knowledge from outside the excerpts.";
+
+const SUMMARY_SYSTEM_PROMPT: &str =
+    "You write the executive summary of a research report from its sections. Keep their citations \
+     exactly as written and add no claims of your own.";
+
+#[derive(Deserialize)]
+struct Plan {
+    title: String,
+    sections: Vec<PlannedSection>,
+}
+
+#[derive(Deserialize)]
+struct PlannedSection {
+    heading: String,
+    /// What the section should establish
+    focus: String,
+    /// Search queries for the section's evidence
+    #[serde(default)]
+    queries: Vec<String>,
+}
+
+/// One written section, citing `Report::sources` by number
+pub struct ReportSection {
+    pub heading: String,
+    pub body: String,
+}
+
+/// A cited research report over the library
+pub struct Report {
+    pub title: String,
+    pub summary: String,
+    pub sections: Vec<ReportSection>,
+    /// Excerpts cited anywhere in the report; `[n]` refers to `sources[n - 1]`
+    pub sources: Vec<Chunk>,
+    /// Videos the report drew on
+    pub videos: usize,
+}
+
+impl Report {
+    /// The report as a Markdown document
+    pub fn render(&self, query: &str) -> String {
+        let mut out = format!(
+            "# {}\n\n_Research question: {}. Written from {} video(s) on {}._\n\n## Summary\n\n{}\n",
+            self.title,
+            query,
+            self.videos,
+            chrono::Utc::now().format("%Y-%m-%d"),
+            self.summary.trim()
+        );
+        for section in &self.sections {
+            out.push_str(&format!("\n## {}\n\n{}\n", section.heading, section.body.trim()));
+        }
+        if !self.sources.is_empty() {
+            out.push_str("\n## Sources\n\n");
+            for (i, chunk) in self.sources.iter().enumerate() {
+                out.push_str(&format!("{}. [{}]({})\n", i + 1, chunk.citation(), chunk.link()));
+            }
+        }
+        out
+    }
+}
+
+/// Rewrite a section's excerpt numbers `[n]` as numbers in the report's source list, adding the
+/// excerpts it cites for the first time. Citations of excerpts the section was not given are dropped.
+fn renumber(body: &str, excerpts: &[Chunk], sources: &mut Vec<Chunk>, numbers: &mut HashMap<(String, String), usize>) -> String {
+    let citation = Regex::new(r"\[(\d+)\]").expect("valid regex");
+    citation
+        .replace_all(body, |caps: &regex::Captures| {
+            let Some(chunk) = caps[1].parse::<usize>().ok().and_then(|n| excerpts.get(n.wrapping_sub(1))) else {
+                return String::new();
+            };
+            let key = (chunk.video_id.clone(), chunk.text.clone());
+            let number = *numbers.entry(key).or_insert_with(|| {
+                sources.push(chunk.clone());
+                sources.len()
+            });
+            format!("[{}]", number)
+        })
+        .into_owned()
+}
+
+impl VideoTranscriber {
+    /// Research a question across the library: plan sections, gather each section's evidence
+    /// from every video in the collection, write the sections with citations, and summarize them.
+    /// Private videos are left out unless `include_private`.
+    pub fn research_report(
+        &self,
+        query: &str,
+        collection: Option<&str>,
+        max_sections: usize,
+        include_private: bool,
+    ) -> Result<Report> {
+        let transcripts = self.shareable_transcripts(self.collection_transcripts(collection)?, include_private)?;
+        let chunks: Vec<Chunk> = transcripts
+            .iter()
+            .flat_map(|t| chunk_transcript(t, DEFAULT_CHUNK_CHARS, DEFAULT_CHUNK_OVERLAP))
+            .collect();
+        status!("📚 Researching across {} video(s) ({} excerpts)", transcripts.len(), chunks.len());
+
+        status!("🗺️  Planning up to {} sections...", max_sections);
+        let titles: String = transcripts
+            .iter()
+            .map(|t| format!("- {} ({})\n", t.label(), t.channel.as_deref().unwrap_or("unknown channel")))
+            .collect();
+        let plan_prompt = format!(
+            "Plan a research report answering: {}\n\nThe library holds these videos:\n{}\n\
+             Return JSON: {{\"title\": string, \"sections\": [{{\"heading\": string, \"focus\": \"what the \
+             section should establish\", \"queries\": [\"2-4 keyword search queries for its evidence\"]}}]}} \
+             with at most {} sections in reading order. Do not plan an introduction or conclusion; a \
+             summary is added separately.",
+            query, titles, max_sections
+        );
+        let mut plan: Plan = self.complete_json(PLAN_SYSTEM_PROMPT, &plan_prompt)?;
+        plan.sections.truncate(max_sections);
+        if plan.sections.is_empty() {
+            anyhow::bail!("The model planned no sections for \"{}\"", query);
+        }
+
+        let drafts = map_bounded(&plan.sections, SECTION_CONCURRENCY, |section| -> Result<(String, Vec<Chunk>)> {
+            let excerpts = section_evidence(section, &chunks);
+            if excerpts.is_empty() {
+                status!("⚠️  Nothing in the library covers \"{}\"", section.heading);
+                return Ok(("The library has nothing on this.".to_string(), excerpts));
+            }
+            status!("✍️  Writing \"{}\" from {} excerpt(s)...", section.heading, excerpts.len());
+            let prompt = format!(
+                "Report: {}\nResearch question: {}\n\nWrite the section \"{}\", which should establish: {}\n\
+                 Write Markdown paragraphs (sub-headings with ### are fine) citing excerpts as [n].\n\n\
+                 Excerpts:\n{}",
+                plan.title,
+                query,
+                section.heading,
+                section.focus,
+                numbered_context(&excerpts.iter().collect::<Vec<_>>())
+            );
+            Ok((self.complete(SECTION_SYSTEM_PROMPT, &prompt)?, excerpts))
+        });
+
+        let mut sources = Vec::new();
+        let mut numbers = HashMap::new();
+        let mut sections = Vec::new();
+        for (planned, draft) in plan.sections.iter().zip(drafts) {
+            let (body, excerpts) = draft?;
+            sections.push(ReportSection {
+                heading: planned.heading.clone(),
+                body: renumber(&body, &excerpts, &mut sources, &mut numbers),
+            });
+        }
+
+        status!("🧾 Summarizing {} section(s)...", sections.len());
+        let written: String = sections
+            .iter()
+            .map(|section| format!("## {}\n{}\n\n", section.heading, section.body))
+            .collect();
+        let summary = self.complete(
+            SUMMARY_SYSTEM_PROMPT,
+            &format!(
+                "Research question: {}\n\nWrite a summary of two or three paragraphs answering the question \
+                 from the report below, keeping its [n] citations.\n\n{}",
+                query, written
+            ),
+        )?;
+
+        Ok(Report {
+            title: plan.title,
+            summary,
+            sections,
+            sources,
+            videos: transcripts.len(),
+        })
+    }
+}
+
+/// The best excerpts for a section across all of its queries
+fn section_evidence(section: &PlannedSection, chunks: &[Chunk]) -> Vec<Chunk> {
+    let mut scores: HashMap<usize, f32> = HashMap::new();
+    let queries = if section.queries.is_empty() {
+        vec![format!("{} {}", section.heading, section.focus)]
+    } else {
+        section.queries.clone()
+    };
+    for query in &queries {
+        for (index, score) in bm25_rank(query, chunks, SECTION_TOP_K) {
+            let best = scores.entry(index).or_insert(score);
+            *best = best.max(score);
+        }
+    }
+    let mut ranked: Vec<(usize, f32)> = scores.into_iter().collect();
+    ranked.sort_by(|a, b| b.1.total_cmp(&a.1).then(a.0.cmp(&b.0)));
+    ranked.into_iter().take(SECTION_TOP_K).map(|(index, _)| chunks[index].clone()).collect()
+}
@@ -0,0 +1,353 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+use crate::chunking::{chunk_boundaries, chunks_at};
+use crate::guardrail::fence;
+use crate::transcript::{find_mentions, format_timestamp, snippet_around, CaptionSource, Transcript};
+
+/// Default chunk size in characters
+pub const DEFAULT_CHUNK_CHARS: usize = 1_500;
+/// Default overlap between consecutive chunks in characters
+pub const DEFAULT_CHUNK_OVERLAP: usize = 200;
+/// Default number of excerpts retrieved per question
+pub const DEFAULT_TOP_K: usize = 6;
+/// Reciprocal rank fusion constant; larger values flatten the gap between neighboring ranks
+pub const RRF_K: f32 = 60.0;
+
+/// How transcripts are chunked and how many excerpts are retrieved
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(default)]
+pub struct RetrievalParams {
+    pub chunk_chars: usize,
+    pub chunk_overlap: usize,
+    pub top_k: usize,
+    /// Re-order BM25 candidates by how many query phrases they contain
+    pub rerank: bool,
+    /// Rank chunks by Gemini embedding similarity to the question when `GEMINI_API_KEY` is
+    /// set, instead of by BM25 keyword score
+    pub embeddings: bool,
+    /// When ranking by embeddings, merge in BM25 ranks from the keyword index (reciprocal rank
+    /// fusion) so exact terms the embeddings blur still count
+    pub hybrid: bool,
+}
+
+impl Default for RetrievalParams {
+    fn default() -> Self {
+        Self {
+            chunk_chars: DEFAULT_CHUNK_CHARS,
+            chunk_overlap: DEFAULT_CHUNK_OVERLAP,
+            top_k: DEFAULT_TOP_K,
+            rerank: false,
+            embeddings: true,
+            hybrid: true,
+        }
+    }
+}
+
+impl RetrievalParams {
+    /// Fail on chunk settings that can't split a transcript: chunks must hold at least one
+    /// character and repeat less than a whole chunk of the one before
+    pub fn validate(&self) -> anyhow::Result<()> {
+        if self.chunk_chars == 0 {
+            anyhow::bail!("chunk_chars must be at least 1");
+        }
+        if self.chunk_overlap >= self.chunk_chars {
+            anyhow::bail!(
+                "chunk_overlap ({}) must be less than chunk_chars ({})",
+                self.chunk_overlap,
+                self.chunk_chars
+            );
+        }
+        Ok(())
+    }
+
+    /// These settings with the chunk size and overlap given as `CHUNK_CHARS` and
+    /// `CHUNK_OVERLAP`, unless together they can't chunk a transcript
+    pub(crate) fn with_chunk_overrides(mut self, chars: Option<String>, overlap: Option<String>) -> Self {
+        let chars = chars.and_then(|v| v.parse().ok()).unwrap_or(self.chunk_chars);
+        let overlap = overlap.and_then(|v| v.parse().ok()).unwrap_or(self.chunk_overlap);
+        if chars > 0 && overlap < chars {
+            self.chunk_chars = chars;
+            self.chunk_overlap = overlap;
+        }
+        self
+    }
+}
+
+/// A contiguous piece of one video's transcript
+#[derive(Debug, Clone)]
+pub struct Chunk {
+    pub video_id: String,
+    pub title: String,
+    pub url: String,
+    /// Start time in seconds, when the transcript has timed segments
+    pub start: Option<f64>,
+    pub text: String,
+    /// Where the chunk's segments came from, in order of first appearance
+    pub sources: Vec<CaptionSource>,
+}
+
+impl Chunk {
+    /// Short citation label such as `Video title @ 12:34`
+    pub fn citation(&self) -> String {
+        let mut citation = match self.start {
+            Some(start) => format!("{} @ {}", self.title, format_timestamp(start)),
+            None => self.title.clone(),
+        };
+        if !self.sources.is_empty() {
+            let sources: Vec<&str> = self.sources.iter().map(|s| s.describe()).collect();
+            citation.push_str(&format!(" ({})", sources.join(" + ")));
+        }
+        citation
+    }
+
+    /// Link to the chunk's position in the video, when known
+    pub fn link(&self) -> String {
+        match self.start {
+            Some(start) => format!("https://youtu.be/{}?t={}", self.video_id, start as u64),
+            None => self.url.clone(),
+        }
+    }
+
+    /// About `radius` bytes either side of the first place a word of `query` appears in the
+    /// chunk, or its opening when none does
+    pub fn snippet(&self, query: &str, radius: usize) -> String {
+        let at = query
+            .split(|c: char| !c.is_alphanumeric())
+            .filter(|word| word.len() >= 4)
+            .filter_map(|word| find_mentions(&self.text, word).first().copied())
+            .min();
+        match at {
+            Some(at) => snippet_around(&self.text, at, radius),
+            None => snippet_around(&self.text, 0, 2 * radius),
+        }
+    }
+}
+
+/// Split a transcript into overlapping chunks of roughly `size` characters.
+///
+/// Timed transcripts are split on segment boundaries so every chunk keeps a start time.
+pub fn chunk_transcript(transcript: &Transcript, size: usize, overlap: usize) -> Vec<Chunk> {
+    chunks_at(transcript, &chunk_boundaries(transcript, size, overlap))
+}
+
+const STOPWORDS: &[&str] = &[
+    "a", "an", "and", "are", "as", "at", "be", "but", "by", "did", "do", "does", "for", "from",
+    "has", "have", "he", "how", "i", "in", "is", "it", "its", "of", "on", "or", "she", "so",
+    "that", "the", "their", "they", "this", "to", "was", "what", "when", "where", "which", "who",
+    "why", "will", "with", "you",
+];
+
+/// Lowercased alphanumeric terms with stopwords removed
+pub fn tokenize(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .filter(|word| !STOPWORDS.contains(&word.as_str()))
+        .collect()
+}
+
+/// Rank chunks against a query with BM25, returning `(chunk index, score)` best first
+pub fn bm25_rank(query: &str, chunks: &[Chunk], k: usize) -> Vec<(usize, f32)> {
+    const K1: f32 = 1.2;
+    const B: f32 = 0.75;
+
+    let query_terms: HashSet<String> = tokenize(query).into_iter().collect();
+    if query_terms.is_empty() || chunks.is_empty() {
+        return Vec::new();
+    }
+
+    let docs: Vec<Vec<String>> = chunks.iter().map(|c| tokenize(&c.text)).collect();
+    let avg_len = docs.iter().map(Vec::len).sum::<usize>() as f32 / docs.len() as f32;
+
+    let mut doc_freq: HashMap<&str, usize> = HashMap::new();
+    for doc in &docs {
+        let unique: HashSet<&str> = doc.iter().map(String::as_str).collect();
+        for term in unique {
+            if query_terms.contains(term) {
+                *doc_freq.entry(term).or_default() += 1;
+            }
+        }
+    }
+
+    let n = docs.len() as f32;
+    let mut scores: Vec<(usize, f32)> = docs
+        .iter()
+        .enumerate()
+        .map(|(i, doc)| {
+            let mut term_freq: HashMap<&str, usize> = HashMap::new();
+            for term in doc {
+                if query_terms.contains(term) {
+                    *term_freq.entry(term).or_default() += 1;
+                }
+            }
+            let score = term_freq
+                .iter()
+                .map(|(term, &tf)| {
+                    let df = doc_freq[term] as f32;
+                    let idf = ((n - df + 0.5) / (df + 0.5) + 1.0).ln();
+                    let tf = tf as f32;
+                    idf * tf * (K1 + 1.0) / (tf + K1 * (1.0 - B + B * doc.len() as f32 / avg_len))
+                })
+                .sum();
+            (i, score)
+        })
+        .filter(|(_, score)| *score > 0.0)
+        .collect();
+
+    scores.sort_by(|a, b| b.1.total_cmp(&a.1));
+    scores.truncate(k);
+    scores
+}
+
+/// Boost candidates where consecutive query terms also appear next to each other, then re-sort
+pub fn rerank_phrases(query: &str, chunks: &[Chunk], ranked: &mut [(usize, f32)]) {
+    for (i, score) in ranked.iter_mut() {
+        *score *= phrase_boost(query, &chunks[*i]);
+    }
+    ranked.sort_by(|a, b| b.1.total_cmp(&a.1));
+}
+
+/// How much `rerank_phrases` scales a chunk's score: 1.5× per query bigram it contains, up to four
+pub fn phrase_boost(query: &str, chunk: &Chunk) -> f32 {
+    let terms = tokenize(query);
+    let bigrams: HashSet<(&str, &str)> = terms
+        .windows(2)
+        .map(|pair| (pair[0].as_str(), pair[1].as_str()))
+        .collect();
+    if bigrams.is_empty() {
+        return 1.0;
+    }
+    let words = tokenize(&chunk.text);
+    let hits = words
+        .windows(2)
+        .filter(|pair| bigrams.contains(&(pair[0].as_str(), pair[1].as_str())))
+        .count();
+    1.0 + 0.5 * hits.min(4) as f32
+}
+
+/// The excerpts an answer cites as `[n]`, one per line with provenance and a link; empty when
+/// it cites none
+pub fn cited_sources(answer: &str, chunks: &[&Chunk]) -> String {
+    chunks
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| answer.contains(&format!("[{}]", i + 1)))
+        .map(|(i, chunk)| format!("  [{}] {} — {}\n", i + 1, chunk.citation(), chunk.link()))
+        .collect()
+}
+
+/// Format retrieved chunks as numbered, fenced excerpts for a prompt
+pub fn numbered_context(chunks: &[&Chunk]) -> String {
+    chunks
+        .iter()
+        .enumerate()
+        .map(|(i, chunk)| format!("[{}] ({})\n{}\n", i + 1, chunk.citation(), fence(&chunk.text)))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// One retrieved chunk and the scores behind its rank
+#[derive(Serialize, Debug, Clone)]
+pub struct RankedChunk {
+    /// Position in the chunks that were ranked
+    #[serde(skip)]
+    pub index: usize,
+    /// What the chunks were ordered by: the fused, similarity, or BM25 score times any rerank
+    /// and feedback factors
+    pub score: f32,
+    /// Cosine similarity of the chunk's embedding to the query's
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub similarity: Option<f32>,
+    /// Absent for chunks with none of the query's terms in hybrid ranking
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bm25: Option<f32>,
+    /// Reciprocal rank fusion of the similarity and BM25 ranks, in hybrid ranking
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rrf: Option<f32>,
+    /// Phrase rerank factor, when reranking is on
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rerank: Option<f32>,
+    /// Weight learned from `feedback` ratings, when the chunk has one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub feedback_weight: Option<f32>,
+}
+
+/// Milliseconds spent in each retrieval stage
+#[derive(Serialize, Debug, Clone, Copy, Default)]
+pub struct RetrievalTimings {
+    pub chunking_ms: f64,
+    /// Embedding the query and any chunks not embedded yet; absent for keyword ranking
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub embedding_ms: Option<f64>,
+    /// Searching the keyword index; present for hybrid ranking only
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub keyword_ms: Option<f64>,
+    pub ranking_ms: f64,
+}
+
+/// How the chunks for a query were found, for `--format json` output
+#[derive(Serialize, Debug, Clone)]
+pub struct Retrieval {
+    /// `hybrid`, `embeddings`, or `bm25`
+    pub method: &'static str,
+    /// Chunks ranked
+    pub candidates: usize,
+    /// Best first
+    pub hits: Vec<RankedChunk>,
+    pub timings: RetrievalTimings,
+}
+
+impl Retrieval {
+    /// The hits as JSON, each with its chunk from `chunks`: the hits' chunks, in rank order
+    pub fn hits_json(&self, chunks: &[&Chunk]) -> Vec<serde_json::Value> {
+        self.hits
+            .iter()
+            .zip(chunks)
+            .enumerate()
+            .map(|(rank, (hit, chunk))| {
+                let mut value = serde_json::json!({
+                    "rank": rank + 1,
+                    "video_id": chunk.video_id,
+                    "title": chunk.title,
+                    "start": chunk.start,
+                    "link": chunk.link(),
+                    "text": chunk.text,
+                });
+                if let (Some(object), Ok(serde_json::Value::Object(scores))) =
+                    (value.as_object_mut(), serde_json::to_value(hit))
+                {
+                    object.extend(scores);
+                }
+                value
+            })
+            .collect()
+    }
+
+    /// The retrieval as JSON, with each hit's chunk taken from `chunks` as in `hits_json`
+    pub fn to_json(&self, chunks: &[&Chunk]) -> serde_json::Value {
+        serde_json::json!({
+            "method": self.method,
+            "candidates": self.candidates,
+            "timings": self.timings,
+            "hits": self.hits_json(chunks),
+        })
+    }
+}
+
+/// Fuse several rankings of `len` items (each a list of item indexes, best first) by reciprocal
+/// rank: each item scores the sum of `1 / (RRF_K + rank)` over the rankings it appears in
+pub fn reciprocal_rank_fusion(rankings: &[Vec<usize>], len: usize) -> Vec<f32> {
+    let mut fused = vec![0.0; len];
+    for ranking in rankings {
+        for (rank, &index) in ranking.iter().enumerate() {
+            fused[index] += 1.0 / (RRF_K + rank as f32 + 1.0);
+        }
+    }
+    fused
+}
+
+/// Milliseconds in a duration, for timings
+pub fn millis(elapsed: std::time::Duration) -> f64 {
+    elapsed.as_secs_f64() * 1000.0
+}
@@ -0,0 +1,282 @@
+//! Result types shared by `--format json` output, the HTTP API, and library callers.
+//!
+//! These types are the crate's stable schema. Within a major version, fields are only ever
+//! added, never renamed, removed, or given another type, and every added field is optional, so
+//! JSON written by an older version still deserializes. Unknown fields are ignored when
+//! reading, so newer output can be read by older code too. A breaking change bumps
+//! [`SCHEMA_VERSION`], which `Answer` carries.
+//!
+//! The structs are `#[non_exhaustive]`, so adding a field is not a breaking change for code
+//! that builds them either: start from their constructors and set fields from there.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, Mutex};
+
+use crate::hooks::{PipelineEvent, Subscriber};
+use crate::registry::VideoRecord;
+use crate::retrieval::Chunk;
+use crate::strategy::Strategy;
+
+pub use crate::transcript::{CaptionSource, Segment, Transcript};
+
+/// Version of the schema in this module, bumped only for breaking changes
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// What is known about a video apart from its transcript
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub struct VideoMetadata {
+    pub video_id: String,
+    pub url: String,
+    #[serde(default)]
+    pub title: Option<String>,
+    #[serde(default)]
+    pub channel: Option<String>,
+    #[serde(default)]
+    pub published_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub duration_secs: Option<f64>,
+}
+
+impl VideoMetadata {
+    /// A video with nothing known about it but where it is
+    pub fn new(video_id: impl Into<String>, url: impl Into<String>) -> Self {
+        Self {
+            video_id: video_id.into(),
+            url: url.into(),
+            title: None,
+            channel: None,
+            published_at: None,
+            duration_secs: None,
+        }
+    }
+}
+
+impl From<&Transcript> for VideoMetadata {
+    fn from(transcript: &Transcript) -> Self {
+        Self {
+            video_id: transcript.video_id.clone(),
+            url: transcript.url.clone(),
+            title: transcript.title.clone(),
+            channel: transcript.channel.clone(),
+            published_at: transcript.published_at,
+            duration_secs: transcript
+                .duration_secs
+                .or_else(|| transcript.segments.last().map(|segment| segment.end)),
+        }
+    }
+}
+
+impl From<&VideoRecord> for VideoMetadata {
+    fn from(record: &VideoRecord) -> Self {
+        Self {
+            video_id: record.video_id.clone(),
+            url: record.url.clone(),
+            title: record.title.clone(),
+            channel: record.channel.clone(),
+            published_at: record.published_at,
+            duration_secs: record.duration_secs,
+        }
+    }
+}
+
+/// A passage of a video that an answer drew on
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub struct Citation {
+    pub video_id: String,
+    pub title: String,
+    /// Start of the passage in seconds, when the transcript is timed
+    #[serde(default)]
+    pub start: Option<f64>,
+    /// The video at the start of the passage, or the video itself when untimed
+    pub link: String,
+    pub text: String,
+    /// Where the passage's text came from, in order of first appearance
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub sources: Vec<CaptionSource>,
+}
+
+impl Citation {
+    /// An untimed passage with no recorded provenance
+    pub fn new(video_id: impl Into<String>, title: impl Into<String>, link: impl Into<String>, text: impl Into<String>) -> Self {
+        Self {
+            video_id: video_id.into(),
+            title: title.into(),
+            start: None,
+            link: link.into(),
+            text: text.into(),
+            sources: Vec::new(),
+        }
+    }
+}
+
+impl From<&Chunk> for Citation {
+    fn from(chunk: &Chunk) -> Self {
+        Self {
+            video_id: chunk.video_id.clone(),
+            title: chunk.title.clone(),
+            start: chunk.start,
+            link: chunk.link(),
+            text: chunk.text.clone(),
+            sources: chunk.sources.clone(),
+        }
+    }
+}
+
+/// Tokens used and money spent by paid API calls; start from `UsageReport::default()`
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[non_exhaustive]
+pub struct UsageReport {
+    pub calls: u64,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    /// Estimated from list prices for models and as billed for Apify runs; `None` when no
+    /// call had a known price
+    #[serde(default)]
+    pub usd: Option<f64>,
+}
+
+impl UsageReport {
+    /// Count one `CostIncurred` event; other events are ignored
+    pub fn record(&mut self, event: &PipelineEvent) {
+        if let PipelineEvent::CostIncurred {
+            input_tokens,
+            output_tokens,
+            usd,
+            ..
+        } = event
+        {
+            self.calls += 1;
+            self.input_tokens += input_tokens;
+            self.output_tokens += output_tokens;
+            if let Some(usd) = usd {
+                *self.usd.get_or_insert(0.0) += usd;
+            }
+        }
+    }
+}
+
+/// A subscriber that adds up the cost of every call it sees. Clones share one total, so keep
+/// a clone to read it after handing the meter to `subscribe`.
+#[derive(Clone, Default)]
+pub struct UsageMeter(Arc<Mutex<UsageReport>>);
+
+impl UsageMeter {
+    /// The usage counted so far
+    pub fn report(&self) -> UsageReport {
+        self.0.lock().unwrap().clone()
+    }
+}
+
+impl Subscriber for UsageMeter {
+    fn on_event(&self, event: &PipelineEvent) -> anyhow::Result<()> {
+        self.0.lock().unwrap().record(event);
+        Ok(())
+    }
+}
+
+/// An answer to a question, with what it was based on
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[non_exhaustive]
+pub struct Answer {
+    pub schema_version: u32,
+    pub question: String,
+    pub answer: String,
+    /// The video asked about, absent for questions across the library
+    #[serde(default)]
+    pub url: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub video: Option<VideoMetadata>,
+    /// ID to rate the answer with `feedback`, when it was logged
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub answer_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub strategy: Option<Strategy>,
+    /// The excerpts the model was given, best first; empty when it had the whole transcript
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub citations: Vec<Citation>,
+    /// How the excerpts were ranked, with scores and timings. For diagnosis only: its layout
+    /// is not covered by the schema guarantee.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retrieval: Option<serde_json::Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub usage: Option<UsageReport>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub total_ms: Option<f64>,
+}
+
+impl Answer {
+    /// An answer with nothing known about how it was produced
+    pub fn new(question: impl Into<String>, answer: impl Into<String>) -> Self {
+        Self {
+            schema_version: SCHEMA_VERSION,
+            question: question.into(),
+            answer: answer.into(),
+            url: None,
+            video: None,
+            answer_id: None,
+            strategy: None,
+            citations: Vec::new(),
+            retrieval: None,
+            usage: None,
+            total_ms: None,
+        }
+    }
+
+    /// The answer about this video
+    pub fn for_video(mut self, transcript: &Transcript) -> Self {
+        self.url = Some(transcript.url.clone());
+        self.video = Some(VideoMetadata::from(transcript));
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `ask --format json` output as version 1 of the schema wrote it
+    const ANSWER_V1: &str = include_str!("../tests/fixtures/schema/answer_v1.json");
+
+    #[test]
+    fn version_1_answer_still_deserializes() {
+        let answer: Answer = serde_json::from_str(ANSWER_V1).unwrap();
+        assert_eq!(answer.schema_version, 1);
+        assert_eq!(answer.answer_id.as_deref(), Some("a1b2c3d4"));
+        assert_eq!(answer.strategy, Some(Strategy::Retrieval));
+
+        let video = answer.video.as_ref().unwrap();
+        assert_eq!(video.title.as_deref(), Some("Never Gonna Give You Up"));
+        assert_eq!(video.duration_secs, Some(212.0));
+        assert_eq!(video.published_at.unwrap().to_rfc3339(), "2009-10-25T06:57:33+00:00");
+
+        assert_eq!(answer.citations.len(), 2);
+        assert_eq!(answer.citations[0].start, Some(43.0));
+        assert_eq!(answer.citations[0].sources, vec![CaptionSource::Creator, CaptionSource::Auto]);
+        assert_eq!(answer.citations[1].start, None);
+        assert!(answer.citations[1].sources.is_empty());
+
+        let usage = answer.usage.as_ref().unwrap();
+        assert_eq!((usage.calls, usage.input_tokens, usage.output_tokens), (2, 1840, 96));
+        assert_eq!(answer.total_ms, Some(1532.4));
+    }
+
+    #[test]
+    fn version_1_answer_round_trips() {
+        let answer: Answer = serde_json::from_str(ANSWER_V1).unwrap();
+        let written: serde_json::Value = serde_json::to_value(&answer).unwrap();
+        let original: serde_json::Value = serde_json::from_str(ANSWER_V1).unwrap();
+        assert_eq!(written, original);
+    }
+
+    #[test]
+    fn minimal_answer_needs_only_the_required_fields() {
+        let answer: Answer =
+            serde_json::from_str(r#"{"schema_version": 1, "question": "q", "answer": "a", "added_later": true}"#).unwrap();
+        assert_eq!(answer.url, None);
+        assert!(answer.citations.is_empty());
+        assert!(answer.usage.is_none());
+    }
+}
@@ -0,0 +1,139 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+use crate::feedback::FeedbackStore;
+use crate::guardrail::fence;
+use crate::llm::ModelChoice;
+use crate::transcript::Transcript;
+use crate::VideoTranscriber;
+
+const COMPARE_SYSTEM_PROMPT: &str =
+    "You compare two answers to the same question about a video and report where they agree and \
+     where they differ in substance (facts, numbers, conclusions), ignoring wording and length. \
+     Reply with JSON only.";
+
+/// One model's answer
+#[derive(Serialize, Debug)]
+pub struct ModelAnswer {
+    pub model: String,
+    pub answer: String,
+}
+
+/// How far two answers agree
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Verdict {
+    Agree,
+    Partial,
+    Disagree,
+}
+
+/// The comparison of two answers
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Agreement {
+    pub verdict: Verdict,
+    pub summary: String,
+    /// Points both answers make
+    #[serde(default)]
+    pub shared: Vec<String>,
+    /// Points where the answers contradict each other or only one makes a claim
+    #[serde(default)]
+    pub differences: Vec<String>,
+}
+
+/// Two models' answers to the same question from the same context
+#[derive(Serialize, Debug)]
+pub struct SecondOpinion {
+    pub question: String,
+    pub first: ModelAnswer,
+    pub second: ModelAnswer,
+    /// Share of distinct words the answers have in common (Jaccard), as a rough check on
+    /// the model's own comparison
+    pub word_overlap: f64,
+    pub agreement: Agreement,
+}
+
+impl SecondOpinion {
+    pub fn render(&self) -> String {
+        let mut out = format!(
+            "## {}\n\n{}\n\n## {}\n\n{}\n\n## Agreement: {:?} ({:.0}% word overlap)\n\n{}\n",
+            self.first.model,
+            self.first.answer.trim(),
+            self.second.model,
+            self.second.answer.trim(),
+            self.agreement.verdict,
+            self.word_overlap * 100.0,
+            self.agreement.summary.trim()
+        );
+        for (heading, points) in [("Both say", &self.agreement.shared), ("Differences", &self.agreement.differences)] {
+            if !points.is_empty() {
+                out.push_str(&format!("\n{}:\n", heading));
+                for point in points {
+                    out.push_str(&format!("- {}\n", point));
+                }
+            }
+        }
+        out
+    }
+}
+
+/// Jaccard similarity of the answers' lower-cased words
+fn word_overlap(a: &str, b: &str) -> f64 {
+    let words = |text: &str| -> HashSet<String> {
+        text.split(|c: char| !c.is_alphanumeric())
+            .filter(|w| !w.is_empty())
+            .map(str::to_lowercase)
+            .collect()
+    };
+    let (a, b) = (words(a), words(b));
+    let union = a.union(&b).count();
+    if union == 0 {
+        return 1.0;
+    }
+    a.intersection(&b).count() as f64 / union as f64
+}
+
+impl VideoTranscriber {
+    /// Answer `question` with the configured model and with `second`, from identical context,
+    /// then compare the two answers
+    pub fn second_opinion(&self, transcript: &Transcript, question: &str, second: &ModelChoice) -> Result<SecondOpinion> {
+        self.check_model_key(second)?;
+        self.warn_if_unreliable(transcript);
+        let store = FeedbackStore::load(&self.data_dir)?;
+        let context = self.answer_context(transcript, question, store.videos.get(&transcript.video_id))?;
+        let first = self.primary_model();
+        status!("🤔 Asking {} and {}: \"{}\"", first, second, question);
+
+        let mut answers = self
+            .complete_each(&[first.clone(), second.clone()], context.system, &context.prompt)
+            .into_iter();
+        let first_answer = context.with_sources(answers.next().expect("one answer per model")?);
+        let second_answer = context.with_sources(answers.next().expect("one answer per model")?);
+
+        status!("⚖️  Comparing the answers...");
+        let prompt = format!(
+            "Question: {}\n\nAnswer A:\n{}\n\nAnswer B:\n{}\n\n\
+             Return JSON: {{\"verdict\": \"agree\" | \"partial\" | \"disagree\", \"summary\": \"one or two \
+             sentences\", \"shared\": [\"point both make\"], \"differences\": [\"A says ..., B says ...\"]}}",
+            question,
+            fence(&first_answer),
+            fence(&second_answer)
+        );
+        let agreement: Agreement = self.complete_json(COMPARE_SYSTEM_PROMPT, &prompt)?;
+
+        Ok(SecondOpinion {
+            question: question.to_string(),
+            word_overlap: word_overlap(&first_answer, &second_answer),
+            first: ModelAnswer {
+                model: first.to_string(),
+                answer: first_answer,
+            },
+            second: ModelAnswer {
+                model: second.to_string(),
+                answer: second_answer,
+            },
+            agreement,
+        })
+    }
+}
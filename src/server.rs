@@ -0,0 +1,424 @@
+use anyhow::{Context, Result};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::Mutex;
+use std::thread;
+use std::time::Duration;
+
+use crate::debuglog;
+use crate::error;
+use crate::extension::{self, ExtensionAsk};
+use crate::jobs::{Job, JobStatus, JobStore, JobVideo};
+use crate::pipeline::is_collection_url;
+use crate::schema::{Answer, Citation, UsageMeter};
+use crate::{Error, VideoTranscriber};
+
+/// Largest request body the server reads
+const MAX_BODY_BYTES: usize = 1 << 20;
+
+/// A parsed HTTP/1.1 request
+pub struct HttpRequest {
+    pub method: String,
+    pub path: String,
+    /// Header names are lowercased
+    pub headers: HashMap<String, String>,
+    pub body: Vec<u8>,
+}
+
+impl HttpRequest {
+    fn read(stream: &TcpStream) -> Result<Self> {
+        let mut reader = BufReader::new(stream);
+        let mut line = String::new();
+        reader.read_line(&mut line).context("Failed to read request line")?;
+        let mut parts = line.split_whitespace();
+        let (Some(method), Some(path)) = (parts.next(), parts.next()) else {
+            anyhow::bail!("Malformed request line");
+        };
+        let (method, path) = (method.to_string(), path.to_string());
+
+        let mut headers = HashMap::new();
+        loop {
+            line.clear();
+            reader.read_line(&mut line).context("Failed to read headers")?;
+            let header = line.trim_end();
+            if header.is_empty() {
+                break;
+            }
+            if let Some((name, value)) = header.split_once(':') {
+                headers.insert(name.trim().to_ascii_lowercase(), value.trim().to_string());
+            }
+        }
+
+        let length: usize = headers
+            .get("content-length")
+            .map(|value| value.parse())
+            .transpose()
+            .context("Invalid Content-Length")?
+            .unwrap_or(0);
+        if length > MAX_BODY_BYTES {
+            anyhow::bail!("Request body too large");
+        }
+        let mut body = vec![0; length];
+        reader.read_exact(&mut body).context("Failed to read request body")?;
+
+        Ok(Self {
+            method,
+            path,
+            headers,
+            body,
+        })
+    }
+
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers.get(name).map(String::as_str)
+    }
+}
+
+/// A JSON response
+pub struct HttpResponse {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: String,
+}
+
+impl HttpResponse {
+    pub fn json(status: u16, value: &impl Serialize) -> Self {
+        Self {
+            status,
+            headers: Vec::new(),
+            body: serde_json::to_string_pretty(value).unwrap_or_default(),
+        }
+    }
+
+    pub fn error(status: u16, message: impl Into<String>) -> Self {
+        Self::json(status, &serde_json::json!({ "error": message.into() }))
+    }
+
+    pub fn with_header(mut self, name: &str, value: impl Into<String>) -> Self {
+        self.headers.push((name.to_string(), value.into()));
+        self
+    }
+
+    fn write_to(&self, mut stream: &TcpStream) -> std::io::Result<()> {
+        let reason = match self.status {
+            200 => "OK",
+            202 => "Accepted",
+            204 => "No Content",
+            400 => "Bad Request",
+            401 => "Unauthorized",
+            403 => "Forbidden",
+            404 => "Not Found",
+            405 => "Method Not Allowed",
+            422 => "Unprocessable Entity",
+            500 => "Internal Server Error",
+            _ => "Error",
+        };
+        let mut head = format!(
+            "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n",
+            self.status,
+            reason,
+            self.body.len()
+        );
+        for (name, value) in &self.headers {
+            head.push_str(&format!("{}: {}\r\n", name, value));
+        }
+        head.push_str("\r\n");
+        stream.write_all(head.as_bytes())?;
+        stream.write_all(self.body.as_bytes())
+    }
+}
+
+/// Body of `POST /index`
+#[derive(Deserialize)]
+struct IndexRequest {
+    url: String,
+    /// Videos to take from a playlist or channel URL
+    #[serde(default = "default_max_videos")]
+    max_videos: i32,
+}
+
+fn default_max_videos() -> i32 {
+    50
+}
+
+struct Server<'a> {
+    transcriber: &'a VideoTranscriber,
+    jobs: Mutex<JobStore>,
+    workers: usize,
+    /// Bearer token required from browser callers
+    token: String,
+    /// Answer questions about private videos too
+    include_private: bool,
+}
+
+/// Serve the HTTP API on `addr` until Ctrl-C. Questions about private videos are refused
+/// unless `include_private`.
+pub fn serve(transcriber: &VideoTranscriber, addr: &str, workers: usize, include_private: bool) -> Result<()> {
+    let mut jobs = JobStore::load(transcriber.data_dir())?;
+    jobs.recover();
+    jobs.save(transcriber.data_dir())?;
+    let server = Server {
+        transcriber,
+        jobs: Mutex::new(jobs),
+        workers,
+        token: extension::load_or_create_token(transcriber.data_dir())?,
+        include_private,
+    };
+
+    let listener = TcpListener::bind(addr).with_context(|| format!("Failed to listen on {}", addr))?;
+    // Non-blocking accepts let the loop notice Ctrl-C between connections
+    listener.set_nonblocking(true)?;
+    status!("🌐 Serving on http://{}", addr);
+    status!("🔑 Browser extension token: {}", server.token);
+
+    thread::scope(|scope| {
+        while transcriber.cancel.check().is_ok() {
+            let stream = match listener.accept() {
+                Ok((stream, _)) => stream,
+                Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => {
+                    thread::sleep(Duration::from_millis(50));
+                    continue;
+                }
+                Err(e) => {
+                    status!("⚠️  Accept failed: {}", e);
+                    continue;
+                }
+            };
+            let server = &server;
+            scope.spawn(move || server.handle(stream, scope));
+        }
+    });
+    status!("👋 Server stopped");
+    Ok(())
+}
+
+impl Server<'_> {
+    fn handle<'scope, 'env>(&'env self, stream: TcpStream, scope: &'scope thread::Scope<'scope, 'env>) {
+        let _ = stream.set_nonblocking(false);
+        let _ = stream.set_read_timeout(Some(Duration::from_secs(30)));
+        let response = match HttpRequest::read(&stream) {
+            Ok(request) => {
+                let response = self.guard(&request, scope);
+                status!("{} {} -> {}", request.method, request.path, response.status);
+                response
+            }
+            Err(e) => HttpResponse::error(400, format!("{:#}", e)),
+        };
+        let _ = response.write_to(&stream);
+    }
+
+    /// CORS and token checks in front of the routes. Requests from a browser carry an
+    /// `Origin`; those must come from an extension or YouTube and present the token, so a
+    /// random web page cannot drive the server. Local clients such as curl send no `Origin`.
+    fn guard<'scope, 'env>(
+        &'env self,
+        request: &HttpRequest,
+        scope: &'scope thread::Scope<'scope, 'env>,
+    ) -> HttpResponse {
+        let origin = request.header("origin");
+        if origin.is_some_and(|origin| !extension::is_allowed_origin(origin)) {
+            return HttpResponse::error(403, "Origin not allowed");
+        }
+
+        let path = request.path.split('?').next().unwrap_or_default();
+        let response = if request.method == "OPTIONS" {
+            HttpResponse {
+                status: 204,
+                headers: Vec::new(),
+                body: String::new(),
+            }
+            .with_header("Access-Control-Allow-Methods", "GET, POST, OPTIONS")
+            .with_header("Access-Control-Allow-Headers", "Authorization, Content-Type, Idempotency-Key")
+            .with_header("Access-Control-Max-Age", "600")
+        } else if (origin.is_some() || path.starts_with("/extension/"))
+            && !extension::authorized(request.header("authorization"), &self.token)
+        {
+            HttpResponse::error(401, "Missing or wrong extension token")
+        } else {
+            self.route(request, scope)
+        };
+
+        match origin {
+            Some(origin) => response
+                .with_header("Access-Control-Allow-Origin", origin)
+                .with_header("Access-Control-Expose-Headers", "Location, Idempotent-Replayed")
+                .with_header("Vary", "Origin"),
+            None => response,
+        }
+    }
+
+    fn route<'scope, 'env>(
+        &'env self,
+        request: &HttpRequest,
+        scope: &'scope thread::Scope<'scope, 'env>,
+    ) -> HttpResponse {
+        let path = request.path.split('?').next().unwrap_or_default();
+        match (request.method.as_str(), path) {
+            ("GET", "/health") => HttpResponse::json(200, &serde_json::json!({ "status": "ok" })),
+            ("POST", "/index") => self.post_index(request, scope),
+            ("POST", "/extension/ask") => self.extension_ask(request),
+            ("GET", path) if path.starts_with("/jobs/") => {
+                let jobs = self.jobs.lock().unwrap();
+                match jobs.get(&path["/jobs/".len()..]) {
+                    Some(job) => HttpResponse::json(200, job),
+                    None => HttpResponse::error(404, "No such job"),
+                }
+            }
+            (_, "/index") | (_, "/health") | (_, "/extension/ask") => HttpResponse::error(405, "Method not allowed"),
+            _ => HttpResponse::error(404, "Not found"),
+        }
+    }
+
+    /// Queue an indexing job, or return the existing one for a repeated `Idempotency-Key`
+    fn post_index<'scope, 'env>(
+        &'env self,
+        request: &HttpRequest,
+        scope: &'scope thread::Scope<'scope, 'env>,
+    ) -> HttpResponse {
+        let body: IndexRequest = match serde_json::from_slice(&request.body) {
+            Ok(body) => body,
+            Err(e) => return HttpResponse::error(400, format!("Invalid request body: {}", e)),
+        };
+        if self.transcriber.extract_video_id(&body.url).is_err() && !is_collection_url(&body.url) {
+            return HttpResponse::error(400, "url must be a YouTube video, playlist, or channel URL");
+        }
+
+        let key = request.header("idempotency-key").map(str::to_string);
+        let request_hash = hex::encode(Sha256::digest(&request.body));
+
+        // Look up and create under one lock, so concurrent retries cannot both start a run
+        let job = {
+            let mut jobs = self.jobs.lock().unwrap();
+            if let Some(key) = &key {
+                if let Some((record, job)) = jobs.find_by_key(key) {
+                    if record.request_hash != request_hash {
+                        return HttpResponse::error(
+                            422,
+                            "Idempotency-Key was already used with a different request",
+                        );
+                    }
+                    return HttpResponse::json(200, job)
+                        .with_header("Idempotent-Replayed", "true")
+                        .with_header("Location", format!("/jobs/{}", job.id));
+                }
+            }
+            let job = jobs.create(&body.url, key.map(|key| (key, request_hash)));
+            if let Err(e) = jobs.save(self.transcriber.data_dir()) {
+                status!("⚠️  Could not save jobs: {}", e);
+            }
+            job
+        };
+
+        let id = job.id.clone();
+        scope.spawn(move || self.run_job(&id, &body));
+        HttpResponse::json(202, &job).with_header("Location", format!("/jobs/{}", job.id))
+    }
+
+    /// Answer a question from the extension, scoped to the playhead when it sends one
+    fn extension_ask(&self, request: &HttpRequest) -> HttpResponse {
+        let body: ExtensionAsk = match serde_json::from_slice(&request.body) {
+            Ok(body) => body,
+            Err(e) => return HttpResponse::error(400, format!("Invalid request body: {}", e)),
+        };
+        if self.transcriber.extract_video_id(&body.url).is_err() {
+            return HttpResponse::error(400, "url must be a YouTube video URL");
+        }
+
+        // A clone so the meter only counts this request
+        let mut transcriber = self.transcriber.clone();
+        let usage = UsageMeter::default();
+        transcriber.subscribe(usage.clone());
+        let answer = transcriber.load_transcript(&body.url, false).and_then(|transcript| {
+            transcriber.check_shareable(&transcript, self.include_private)?;
+            let mut answer = match body.at {
+                Some(at) => {
+                    let answer = transcriber.ask_companion(&transcript, at, body.window, &body.question)?;
+                    Answer::new(&body.question, transcriber.moderate(&answer)?)
+                }
+                None => {
+                    let logged = transcriber.answer_with_feedback(&transcript, &body.question)?;
+                    let mut answer = Answer::new(&body.question, transcriber.moderate(&logged.answer)?);
+                    answer.answer_id = Some(logged.id);
+                    answer.strategy = Some(logged.strategy);
+                    answer.citations = logged.excerpts.iter().map(Citation::from).collect();
+                    answer.retrieval = logged
+                        .retrieval
+                        .map(|retrieval| retrieval.to_json(&logged.excerpts.iter().collect::<Vec<_>>()));
+                    answer
+                }
+            }
+            .for_video(&transcript);
+            answer.usage = Some(usage.report());
+            Ok(answer)
+        });
+        match answer {
+            Ok(answer) => HttpResponse::json(200, &answer),
+            Err(e) if matches!(error::kind(&e), Some(Error::PrivateVideo { .. })) => HttpResponse::error(403, e.to_string()),
+            Err(e) => HttpResponse::error(500, debuglog::scrub(&format!("{:#}", e))),
+        }
+    }
+
+    fn run_job(&self, id: &str, request: &IndexRequest) {
+        self.update_job(id, |job| job.status = JobStatus::Running);
+
+        let result = if is_collection_url(&request.url) {
+            self.transcriber
+                .index_collection(&request.url, request.max_videos, self.workers, None)
+                .map(|indexed| {
+                    indexed
+                        .into_iter()
+                        .map(|video| {
+                            let (file_uris, error) = match video.parts {
+                                Some(Ok(parts)) => (parts.into_iter().map(|p| p.file_uri).collect(), None),
+                                Some(Err(e)) => (Vec::new(), Some(debuglog::scrub(&format!("{:#}", e)))),
+                                None => (Vec::new(), None),
+                            };
+                            JobVideo {
+                                video_id: video.transcript.video_id.clone(),
+                                title: video.transcript.label().to_string(),
+                                file_uris,
+                                error,
+                            }
+                        })
+                        .collect()
+                })
+        } else {
+            self.transcriber.load_transcript(&request.url, false).and_then(|transcript| {
+                let parts = self.transcriber.upload_to_gemini(&transcript)?;
+                Ok(vec![JobVideo {
+                    video_id: transcript.video_id.clone(),
+                    title: transcript.label().to_string(),
+                    file_uris: parts.into_iter().map(|p| p.file_uri).collect(),
+                    error: None,
+                }])
+            })
+        };
+
+        self.update_job(id, |job| {
+            job.finished_at = Some(Utc::now());
+            match result {
+                Ok(videos) => {
+                    job.status = JobStatus::Succeeded;
+                    job.videos = videos;
+                }
+                Err(e) => {
+                    job.status = JobStatus::Failed;
+                    // Errors can carry request URLs, and those carry API keys
+                    job.error = Some(debuglog::scrub(&format!("{:#}", e)));
+                }
+            }
+        });
+    }
+
+    fn update_job(&self, id: &str, change: impl FnOnce(&mut Job)) {
+        let mut jobs = self.jobs.lock().unwrap();
+        jobs.update(id, change);
+        if let Err(e) = jobs.save(self.transcriber.data_dir()) {
+            status!("⚠️  Could not save jobs: {}", e);
+        }
+    }
+}
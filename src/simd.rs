@@ -0,0 +1,142 @@
+//! Vector kernels for similarity search, using AVX2/FMA when the CPU has them.
+
+/// Dot product of `a` and `b` plus the squared norm of each, in one pass
+pub fn dot_and_norms(a: &[f32], b: &[f32]) -> (f32, f32, f32) {
+    let len = a.len().min(b.len());
+    let (a, b) = (&a[..len], &b[..len]);
+
+    #[cfg(target_arch = "x86_64")]
+    {
+        if is_x86_feature_detected!("avx2") && is_x86_feature_detected!("fma") {
+            // SAFETY: the required CPU features were detected at runtime
+            return unsafe { dot_and_norms_avx2(a, b) };
+        }
+    }
+
+    dot_and_norms_portable(a, b)
+}
+
+/// Dot product of two equal-length vectors
+pub fn dot(a: &[f32], b: &[f32]) -> f32 {
+    dot_and_norms(a, b).0
+}
+
+/// Eight independent accumulators so the compiler can vectorize without reassociating
+fn dot_and_norms_portable(a: &[f32], b: &[f32]) -> (f32, f32, f32) {
+    const LANES: usize = 8;
+    let mut dot = [0.0f32; LANES];
+    let mut norm_a = [0.0f32; LANES];
+    let mut norm_b = [0.0f32; LANES];
+
+    let chunks = a.len() / LANES;
+    for i in 0..chunks {
+        let x = &a[i * LANES..(i + 1) * LANES];
+        let y = &b[i * LANES..(i + 1) * LANES];
+        for lane in 0..LANES {
+            dot[lane] += x[lane] * y[lane];
+            norm_a[lane] += x[lane] * x[lane];
+            norm_b[lane] += y[lane] * y[lane];
+        }
+    }
+
+    let (mut d, mut na, mut nb) = (dot.iter().sum::<f32>(), norm_a.iter().sum::<f32>(), norm_b.iter().sum::<f32>());
+    for i in chunks * LANES..a.len() {
+        d += a[i] * b[i];
+        na += a[i] * a[i];
+        nb += b[i] * b[i];
+    }
+    (d, na, nb)
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "avx2,fma")]
+unsafe fn dot_and_norms_avx2(a: &[f32], b: &[f32]) -> (f32, f32, f32) {
+    use std::arch::x86_64::*;
+
+    #[target_feature(enable = "avx2")]
+    unsafe fn horizontal_sum(v: __m256) -> f32 {
+        let high = _mm256_extractf128_ps(v, 1);
+        let low = _mm256_castps256_ps128(v);
+        let sum = _mm_add_ps(high, low);
+        let sum = _mm_add_ps(sum, _mm_movehl_ps(sum, sum));
+        let sum = _mm_add_ss(sum, _mm_shuffle_ps(sum, sum, 0x55));
+        _mm_cvtss_f32(sum)
+    }
+
+    let mut dot = _mm256_setzero_ps();
+    let mut norm_a = _mm256_setzero_ps();
+    let mut norm_b = _mm256_setzero_ps();
+
+    let chunks = a.len() / 8;
+    for i in 0..chunks {
+        let x = _mm256_loadu_ps(a.as_ptr().add(i * 8));
+        let y = _mm256_loadu_ps(b.as_ptr().add(i * 8));
+        dot = _mm256_fmadd_ps(x, y, dot);
+        norm_a = _mm256_fmadd_ps(x, x, norm_a);
+        norm_b = _mm256_fmadd_ps(y, y, norm_b);
+    }
+
+    let (mut d, mut na, mut nb) = (horizontal_sum(dot), horizontal_sum(norm_a), horizontal_sum(norm_b));
+    for i in chunks * 8..a.len() {
+        d += a[i] * b[i];
+        na += a[i] * a[i];
+        nb += b[i] * b[i];
+    }
+    (d, na, nb)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Deterministic values in [-1, 1)
+    fn vector(seed: u64, len: usize) -> Vec<f32> {
+        let mut x = seed.wrapping_mul(0x9E37_79B9_7F4A_7C15) | 1;
+        (0..len)
+            .map(|_| {
+                x ^= x << 13;
+                x ^= x >> 7;
+                x ^= x << 17;
+                (x >> 40) as f32 / (1u64 << 23) as f32 - 1.0
+            })
+            .collect()
+    }
+
+    fn close(a: f32, b: f32) -> bool {
+        (a - b).abs() <= 1e-4 * a.abs().max(b.abs()).max(1.0)
+    }
+
+    #[test]
+    fn portable_kernel_matches_a_plain_loop() {
+        for len in [0, 1, 7, 8, 9, 768, 1001] {
+            let (a, b) = (vector(1, len), vector(2, len));
+            let (dot, norm_a, norm_b) = dot_and_norms_portable(&a, &b);
+            let expected: f32 = a.iter().zip(&b).map(|(x, y)| x * y).sum();
+            assert!(close(dot, expected), "len {}: {} vs {}", len, dot, expected);
+            assert!(close(norm_a, a.iter().map(|x| x * x).sum()));
+            assert!(close(norm_b, b.iter().map(|x| x * x).sum()));
+        }
+    }
+
+    #[cfg(target_arch = "x86_64")]
+    #[test]
+    fn avx2_kernel_matches_the_portable_one() {
+        if !(is_x86_feature_detected!("avx2") && is_x86_feature_detected!("fma")) {
+            return;
+        }
+        for len in [0, 1, 7, 8, 9, 15, 16, 17, 768, 1001] {
+            let (a, b) = (vector(3, len), vector(4, len));
+            // SAFETY: the required CPU features were detected above
+            let fast = unsafe { dot_and_norms_avx2(&a, &b) };
+            let portable = dot_and_norms_portable(&a, &b);
+            assert!(close(fast.0, portable.0), "len {}: dot {} vs {}", len, fast.0, portable.0);
+            assert!(close(fast.1, portable.1), "len {}: norm {} vs {}", len, fast.1, portable.1);
+            assert!(close(fast.2, portable.2), "len {}: norm {} vs {}", len, fast.2, portable.2);
+        }
+    }
+
+    #[test]
+    fn mismatched_lengths_use_the_shorter() {
+        assert_eq!(dot(&[1.0, 2.0, 3.0], &[4.0, 5.0]), 14.0);
+    }
+}
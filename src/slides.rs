@@ -0,0 +1,98 @@
+use anyhow::Result;
+use serde::Deserialize;
+
+use crate::guardrail::fence;
+use crate::transcript::{format_timestamp, Transcript};
+use crate::VideoTranscriber;
+
+const SLIDES_SYSTEM_PROMPT: &str =
+    "You turn lecture transcripts into clear, well-structured slide decks. Reply with JSON only.";
+
+#[derive(Deserialize, Debug)]
+pub struct Deck {
+    pub title: String,
+    #[serde(default)]
+    pub subtitle: String,
+    pub slides: Vec<Slide>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct Slide {
+    pub title: String,
+    pub bullets: Vec<String>,
+    #[serde(default)]
+    pub notes: String,
+    /// Where in the lecture this slide's material starts, in seconds
+    pub start_seconds: Option<f64>,
+}
+
+/// Markdown slide dialects we can emit
+#[derive(Clone, Copy, clap::ValueEnum)]
+pub enum SlideFormat {
+    Marp,
+    Reveal,
+}
+
+impl VideoTranscriber {
+    /// Generate a slide deck outline from a lecture transcript
+    pub fn generate_slides(&self, url: &str, max_slides: usize) -> Result<(Transcript, Deck)> {
+        let transcript = self.load_transcript(url, false)?;
+        status!("🖼️  Drafting up to {} slides...", max_slides);
+
+        let prompt = format!(
+            "Convert this lecture into a slide deck of at most {} content slides. Return JSON:\n\
+             {{\"title\": string, \"subtitle\": string, \"slides\": [{{\"title\": string, \
+             \"bullets\": [string] (3-5 short bullets), \"notes\": string (speaker notes), \
+             \"start_seconds\": number or null}}]}}\n\
+             The transcript has [MM:SS] markers; set start_seconds to where each slide's material begins.\n\n\
+             Lecture: {}\n\nTranscript:\n{}",
+            max_slides,
+            transcript.label(),
+            fence(&transcript.timestamped_text(30.0))
+        );
+
+        let deck = self.complete_json(SLIDES_SYSTEM_PROMPT, &prompt)?;
+        Ok((transcript, deck))
+    }
+}
+
+/// Render the deck as Marp or reveal.js Markdown
+pub fn render_deck(deck: &Deck, transcript: &Transcript, format: SlideFormat) -> String {
+    let mut out = String::new();
+    if let SlideFormat::Marp = format {
+        out.push_str("---\nmarp: true\ntheme: default\npaginate: true\n---\n\n");
+    }
+
+    out.push_str(&format!("# {}\n", deck.title));
+    if !deck.subtitle.is_empty() {
+        out.push_str(&format!("\n{}\n", deck.subtitle));
+    }
+    out.push_str(&format!("\n_Source: [{}]({})_\n", transcript.label(), transcript.url));
+
+    for slide in &deck.slides {
+        out.push_str(&format!("\n---\n\n## {}\n\n", slide.title));
+        for bullet in &slide.bullets {
+            out.push_str(&format!("- {}\n", bullet));
+        }
+
+        let mut notes = slide.notes.trim().to_string();
+        if let Some(start) = slide.start_seconds {
+            notes = format!(
+                "[{}] {} ({})",
+                format_timestamp(start),
+                notes,
+                transcript.link_at(start)
+            );
+        }
+        if notes.is_empty() {
+            continue;
+        }
+        match format {
+            // Marp treats HTML comments as presenter notes
+            SlideFormat::Marp => out.push_str(&format!("\n<!--\n{}\n-->\n", notes)),
+            SlideFormat::Reveal => out.push_str(&format!("\nNote: {}\n", notes)),
+        }
+    }
+
+    out
+}
@@ -0,0 +1,305 @@
+use anyhow::{Context, Result};
+use clap::ValueEnum;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::process::Command;
+
+use crate::captions::{self, CaptionTrack};
+use crate::cancel::SendCancellable;
+use crate::hooks::PipelineEvent;
+use crate::progress::Progress;
+use crate::transcript::{self, CaptionSource, Segment, Transcript};
+use crate::youtube::YouTubeSource;
+use crate::{error, whisper, Error, VideoTranscriber};
+
+/// A backend that turns a single video URL into a transcript.
+///
+/// The built-in backends are picked with [`SourceKind`]; others can be plugged in with
+/// [`VideoTranscriber::with_transcript_source`]. Everything downstream (caching, indexing,
+/// answering) works the same whichever backend fetched the transcript.
+pub trait TranscriptSource: Send + Sync {
+    /// Name shown in progress messages
+    fn name(&self) -> &'static str;
+    /// Fetch `url`'s transcript. `transcriber` gives access to the shared HTTP client,
+    /// cancellation token, and config; the returned `video_id` is replaced with the one
+    /// parsed from `url` so cache lookups stay consistent.
+    fn fetch(&self, transcriber: &VideoTranscriber, url: &str) -> Result<Transcript>;
+}
+
+/// Which `TranscriptSource` single videos are fetched with
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SourceKind {
+    /// Apify's YouTube scraper (needs APIFY_API_KEY, costs credits)
+    #[default]
+    Apify,
+    /// A local yt-dlp install: no key, no credits, no actor run to wait for
+    Ytdlp,
+    /// YouTube's own caption tracks, read from the watch page: nothing to install or pay for
+    Youtube,
+}
+
+impl SourceKind {
+    /// The source named by `TRANSCRIPT_SOURCE` or the config file, Apify when neither is set
+    pub fn from_setting(name: Option<String>) -> Result<Self> {
+        match name {
+            Some(name) => Self::from_str(&name, true)
+                .map_err(|_| anyhow::anyhow!("Unknown TRANSCRIPT_SOURCE '{}' (expected apify, ytdlp, or youtube)", name)),
+            None => Ok(SourceKind::Apify),
+        }
+    }
+
+    pub fn backend(self) -> Box<dyn TranscriptSource> {
+        match self {
+            SourceKind::Apify => Box::new(ApifySource),
+            SourceKind::Ytdlp => Box::new(YtDlpSource),
+            SourceKind::Youtube => Box::new(YouTubeSource),
+        }
+    }
+}
+
+pub struct ApifySource;
+
+impl TranscriptSource for ApifySource {
+    fn name(&self) -> &'static str {
+        "Apify"
+    }
+
+    fn fetch(&self, transcriber: &VideoTranscriber, url: &str) -> Result<Transcript> {
+        transcriber.fetch_from_apify(url)
+    }
+}
+
+pub struct YtDlpSource;
+
+/// The parts of `yt-dlp --dump-json` this backend reads
+#[derive(Deserialize)]
+struct YtDlpInfo {
+    id: String,
+    title: Option<String>,
+    channel: Option<String>,
+    uploader: Option<String>,
+    /// `YYYYMMDD`
+    upload_date: Option<String>,
+    /// Seconds
+    duration: Option<f64>,
+    /// Spoken language, when YouTube knows it
+    language: Option<String>,
+    #[serde(default)]
+    subtitles: HashMap<String, Vec<YtDlpFormat>>,
+    #[serde(default)]
+    automatic_captions: HashMap<String, Vec<YtDlpFormat>>,
+}
+
+#[derive(Deserialize)]
+struct YtDlpFormat {
+    ext: String,
+    url: String,
+}
+
+/// YouTube's `json3` caption format
+#[derive(Deserialize)]
+struct Json3 {
+    #[serde(default)]
+    events: Vec<Json3Event>,
+}
+
+#[derive(Deserialize)]
+struct Json3Event {
+    #[serde(rename = "tStartMs", default)]
+    start_ms: f64,
+    #[serde(rename = "dDurationMs", default)]
+    duration_ms: f64,
+    #[serde(default)]
+    segs: Vec<Json3Seg>,
+}
+
+#[derive(Deserialize)]
+struct Json3Seg {
+    #[serde(default)]
+    utf8: String,
+}
+
+/// Caption events as segments; auto-generated tracks split words into separate `segs`, and
+/// line breaks arrive as events of their own, which are skipped
+fn parse_json3(json: &str) -> Result<Vec<Segment>> {
+    let captions: Json3 = serde_json::from_str(json).context("Failed to parse YouTube captions")?;
+    Ok(captions
+        .events
+        .into_iter()
+        .filter_map(|event| {
+            let text = event
+                .segs
+                .iter()
+                .map(|seg| seg.utf8.as_str())
+                .collect::<String>()
+                .split_whitespace()
+                .collect::<Vec<_>>()
+                .join(" ");
+            (!text.is_empty()).then(|| Segment {
+                start: event.start_ms / 1000.0,
+                end: (event.start_ms + event.duration_ms) / 1000.0,
+                text,
+                source: None,
+            })
+        })
+        .collect())
+}
+
+/// The language to use out of `languages`: the spoken language, then English, then whatever
+/// there is. yt-dlp lists auto-generated captions in every language YouTube can translate to,
+/// with the original marked `-orig`. Livestreams list their chat replay as a `live_chat` "subtitle".
+pub fn pick_language<'a>(languages: impl IntoIterator<Item = &'a str>, spoken: Option<&str>) -> Option<&'a str> {
+    let mut keys: Vec<&str> = languages.into_iter().filter(|key| *key != "live_chat").collect();
+    keys.sort_unstable();
+    let mut preferred: Vec<String> = Vec::new();
+    if let Some(spoken) = spoken {
+        preferred.extend([format!("{}-orig", spoken), spoken.to_string()]);
+    }
+    preferred.extend(["en-orig".to_string(), "en".to_string()]);
+    preferred
+        .iter()
+        .find_map(|lang| keys.iter().find(|key| **key == lang.as_str()))
+        .or_else(|| keys.iter().find(|key| key.ends_with("-orig")))
+        .or_else(|| keys.iter().find(|key| key.starts_with("en")))
+        .or_else(|| keys.first())
+        .copied()
+}
+
+/// yt-dlp's `YYYYMMDD` upload date
+pub fn parse_upload_date(date: &str) -> Option<chrono::DateTime<chrono::Utc>> {
+    chrono::NaiveDate::parse_from_str(date, "%Y%m%d")
+        .ok()
+        .and_then(|date| date.and_hms_opt(0, 0, 0))
+        .map(|date| date.and_utc())
+}
+
+/// Download a caption track in YouTube's `json3` format
+pub fn fetch_json3(transcriber: &VideoTranscriber, url: &str) -> Result<Vec<Segment>> {
+    let json = transcriber
+        .client
+        .get(url)
+        .send_with(&transcriber.cancel)
+        .context("Failed to download captions")?
+        .error_for_status()
+        .context("YouTube refused the caption download")?
+        .text()
+        .context("Failed to read captions")?;
+    if json.trim().is_empty() {
+        return Ok(Vec::new());
+    }
+    parse_json3(&json)
+}
+
+impl YtDlpSource {
+    fn track(
+        &self,
+        transcriber: &VideoTranscriber,
+        tracks: &HashMap<String, Vec<YtDlpFormat>>,
+        spoken: Option<&str>,
+        source: CaptionSource,
+    ) -> Result<Option<CaptionTrack>> {
+        let Some(language) = pick_language(tracks.keys().map(String::as_str), spoken) else {
+            return Ok(None);
+        };
+        let Some(format) = tracks[language].iter().find(|f| f.ext == "json3") else {
+            return Ok(None);
+        };
+        Ok(Some(CaptionTrack {
+            source,
+            language: Some(language.trim_end_matches("-orig").to_string()),
+            segments: fetch_json3(transcriber, &format.url)?,
+        }))
+    }
+}
+
+impl TranscriptSource for YtDlpSource {
+    fn name(&self) -> &'static str {
+        "yt-dlp"
+    }
+
+    fn fetch(&self, transcriber: &VideoTranscriber, url: &str) -> Result<Transcript> {
+        status!("📥 Fetching captions with yt-dlp...");
+        transcriber.cancel.check()?;
+        let output = Command::new("yt-dlp")
+            .args(["--dump-json", "--skip-download", "--no-playlist", "--no-warnings", url])
+            .output()
+            .context("Failed to run yt-dlp; install it (https://github.com/yt-dlp/yt-dlp) or use --source apify")?;
+        if !output.status.success() {
+            anyhow::bail!(
+                "yt-dlp failed: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+        }
+        let info: YtDlpInfo =
+            serde_json::from_slice(&output.stdout).context("Failed to parse yt-dlp's video information")?;
+
+        let spoken = info.language.as_deref();
+        let tracks: Vec<CaptionTrack> = [
+            self.track(transcriber, &info.subtitles, spoken, CaptionSource::Creator)?,
+            self.track(transcriber, &info.automatic_captions, spoken, CaptionSource::Auto)?,
+        ]
+        .into_iter()
+        .flatten()
+        .collect();
+        let segments = captions::merge_tracks(tracks);
+        if segments.is_empty() {
+            return Err(Error::NoTranscript(url.to_string()).into());
+        }
+
+        Ok(Transcript {
+            video_id: info.id,
+            url: url.to_string(),
+            title: info.title,
+            channel: info.channel.or(info.uploader),
+            text: transcript::join_segments(&segments).into(),
+            segments,
+            published_at: info.upload_date.as_deref().and_then(parse_upload_date),
+            duration_secs: info.duration,
+            fetched_at: chrono::Utc::now(),
+        })
+    }
+}
+
+impl VideoTranscriber {
+    /// Fetch a video's transcript with the configured source and cache it. Builds with the
+    /// `local-whisper` feature transcribe videos that have no captions from their audio.
+    pub fn fetch_transcript(&self, youtube_url: &str) -> Result<Transcript> {
+        self.report(Progress::FetchingTranscript {
+            url: youtube_url.to_string(),
+            source: self.source.name(),
+        });
+        let mut transcript = match self.source.fetch(self, youtube_url) {
+            // Videos without captions can still be transcribed from their audio
+            Err(e) if whisper::ENABLED && matches!(error::kind(&e), Some(Error::NoTranscript(_))) => {
+                status!("🎙️  No captions available; transcribing the audio locally with whisper.cpp");
+                self.transcribe_locally(youtube_url)?
+            }
+            result => result?,
+        };
+        transcript.video_id = self.extract_video_id(youtube_url)?;
+
+        if let Some(title) = &transcript.title {
+            status!("📺 Video Title: {}", title);
+        }
+        if let Some(channel) = &transcript.channel {
+            status!("👤 Channel: {}", channel);
+        }
+        status!("📝 Transcript length: {} characters", transcript.text.len());
+
+        // Keep a copy so later questions don't need another fetch
+        if let Err(e) = self.cache.put(&transcript) {
+            status!("⚠️  Could not cache transcript: {}", e);
+        }
+
+        self.report(Progress::TranscriptReady {
+            video_id: transcript.video_id.clone(),
+            cached: false,
+            chars: transcript.text.len(),
+        });
+        self.emit(PipelineEvent::TranscriptFetched {
+            transcript: &transcript,
+            cached: false,
+        })?;
+        Ok(transcript)
+    }
+}
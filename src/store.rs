@@ -0,0 +1,79 @@
+use anyhow::Result;
+use std::collections::{HashMap, HashSet};
+
+use crate::conversation::Turn;
+use crate::parts::UploadedPart;
+use crate::registry::{ChunkMatch, PlaylistRecord, SessionRecord, VideoRecord};
+use crate::transcript::Transcript;
+
+/// Where the index is kept: which Gemini files hold each video's transcript, the videos and
+/// playlists of the library, saved sessions, and the embeddings of transcript chunks.
+///
+/// `registry::Registry`, an SQLite file in the data directory, is the default. An application
+/// with its own database can implement this trait and pass it to
+/// `VideoTranscriberBuilder::index_store`. Transcripts themselves stay in the transcript cache.
+/// Implementations are shared across threads, so they must handle concurrent calls.
+pub trait IndexStore: Send + Sync {
+    /// Uploaded parts of a video, in order; empty when it was never uploaded
+    fn parts(&self, video_id: &str) -> Result<Vec<UploadedPart>>;
+
+    /// Record a fresh upload of a video, replacing any earlier one
+    fn replace_parts(&self, video_id: &str, parts: &[UploadedPart]) -> Result<()>;
+
+    /// Record a video's metadata as of now, keeping earlier titles the new transcript lacks
+    fn record_video(&self, transcript: &Transcript) -> Result<()>;
+
+    /// Record a playlist and the videos of it that were indexed, replacing its earlier videos
+    fn record_playlist(&self, playlist: &PlaylistRecord, video_ids: &[String]) -> Result<()>;
+
+    /// Video IDs recorded for a playlist, in playlist order; `None` when it was never indexed
+    fn playlist_videos(&self, playlist_id: &str) -> Result<Option<Vec<String>>>;
+
+    /// Every indexed playlist with its number of indexed videos, most recently indexed first
+    fn playlists(&self) -> Result<Vec<(PlaylistRecord, usize)>>;
+
+    /// Forget a video, its uploads, its playlist memberships, and its chunk embeddings; false when
+    /// it was not registered
+    fn delete_video(&self, video_id: &str) -> Result<bool>;
+
+    /// Every indexed video, most recently indexed first
+    fn videos(&self) -> Result<Vec<VideoRecord>>;
+
+    /// Mark a video private with an access note, or public again (clearing its note); false when
+    /// it was not registered
+    fn set_private(&self, video_id: &str, private: bool, note: Option<&str>) -> Result<bool>;
+
+    /// Private videos by ID, with their access notes
+    fn private_videos(&self) -> Result<HashMap<String, Option<String>>>;
+
+    /// Saved conversations, most recently continued first
+    fn sessions(&self) -> Result<Vec<SessionRecord>>;
+
+    /// A saved conversation, if there is one by that ID
+    fn session(&self, session_id: &str) -> Result<Option<SessionRecord>> {
+        Ok(self.sessions()?.into_iter().find(|session| session.session_id == session_id))
+    }
+
+    /// A saved conversation's turns, oldest first
+    fn session_turns(&self, session_id: &str) -> Result<Vec<Turn>>;
+
+    /// Append a turn to a conversation, starting the session when it is new
+    fn add_session_turn(&self, session_id: &str, video_id: &str, url: &str, turn: &Turn) -> Result<()>;
+
+    /// Forget a saved conversation; false when there was none by that ID
+    fn delete_session(&self, session_id: &str) -> Result<bool>;
+
+    /// Store embeddings of a video's chunks by chunk key, skipping chunks already stored
+    fn add_chunk_vectors(&self, video_id: &str, vectors: &[(String, Vec<f32>)]) -> Result<()>;
+
+    /// Forget a video's chunk embeddings, returning how many there were
+    fn delete_chunk_vectors(&self, video_id: &str) -> Result<usize>;
+
+    /// Keys of the chunks of a video that have embeddings stored
+    fn chunk_vector_keys(&self, video_id: &str) -> Result<HashSet<String>>;
+
+    /// The `k` stored chunks most similar to `query` by cosine similarity, most similar first,
+    /// from one video or the whole library. Implementations may return fewer than `k`; the
+    /// SQLite registry caps it at `registry::MAX_NEAREST_CHUNKS`.
+    fn nearest_chunks(&self, query: &[f32], k: usize, video_id: Option<&str>) -> Result<Vec<ChunkMatch>>;
+}
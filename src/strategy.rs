@@ -0,0 +1,156 @@
+use anyhow::Result;
+use chrono::Utc;
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+
+use crate::feedback::{FeedbackStore, RETRIEVAL_THRESHOLD_CHARS};
+use crate::llm::LlmProvider;
+use crate::parts::split_parts;
+use crate::transcript::Transcript;
+use crate::VideoTranscriber;
+
+/// Phrases that ask about a video as a whole, which excerpts answer badly
+const BROAD_QUESTION_MARKERS: &[&str] = &[
+    "summar",
+    "overview",
+    "main point",
+    "key point",
+    "takeaway",
+    "outline",
+    "recap",
+    "tl;dr",
+    "tldr",
+    "overall",
+    "whole video",
+    "entire video",
+    "throughout",
+    "video about",
+    "talk about",
+    "main idea",
+    "main argument",
+    "structure of",
+];
+
+/// How a question about one video is put to the model
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Strategy {
+    /// Pick per question from the transcript's length, the question, and what is already uploaded
+    Auto,
+    /// Send the whole transcript inline
+    Full,
+    /// Send only the transcript chunks most relevant to the question
+    Retrieval,
+    /// Attach the video's uploaded Gemini files, uploading them first if needed
+    Files,
+    /// Ask on top of the transcript held in Gemini's context cache, creating it if needed
+    Cached,
+}
+
+impl Strategy {
+    pub fn name(self) -> &'static str {
+        match self {
+            Strategy::Auto => "auto",
+            Strategy::Full => "full transcript",
+            Strategy::Retrieval => "retrieval",
+            Strategy::Files => "file attach",
+            Strategy::Cached => "cached context",
+        }
+    }
+}
+
+/// Whether a question is about the video as a whole rather than a particular moment or detail
+pub fn is_broad(question: &str) -> bool {
+    let question = question.to_lowercase();
+    BROAD_QUESTION_MARKERS.iter().any(|marker| question.contains(marker))
+}
+
+impl VideoTranscriber {
+    /// The strategy asked for with `--strategy`, else `ANSWER_STRATEGY` or `answer_strategy`
+    /// in the config file, else `auto`
+    pub fn configured_strategy(&self) -> Result<Strategy> {
+        if let Some(strategy) = self.strategy {
+            return Ok(strategy);
+        }
+        match self.setting("ANSWER_STRATEGY", &self.config.answer_strategy) {
+            Some(name) => Strategy::from_str(&name, true).map_err(|_| {
+                anyhow::anyhow!(
+                    "Unknown ANSWER_STRATEGY '{}' (expected auto, full, retrieval, files, or cached)",
+                    name
+                )
+            }),
+            None => Ok(Strategy::Auto),
+        }
+    }
+
+    /// Longest transcript sent whole: `FULL_TRANSCRIPT_MAX_CHARS` or `full_transcript_max_chars`
+    /// in the config file. Lower it to spend fewer tokens per question on long videos.
+    pub fn full_transcript_limit(&self) -> usize {
+        self.setting(
+            "FULL_TRANSCRIPT_MAX_CHARS",
+            &self.config.full_transcript_max_chars.map(|n| n.to_string()),
+        )
+        .and_then(|n| n.parse().ok())
+        .unwrap_or(RETRIEVAL_THRESHOLD_CHARS)
+    }
+
+    /// Whether answers built from a prompt (ensembles and second opinions included) should use
+    /// retrieved chunks rather than the whole transcript
+    pub fn retrieves(&self, transcript: &Transcript) -> Result<bool> {
+        Ok(match self.configured_strategy()? {
+            Strategy::Retrieval => true,
+            Strategy::Auto => transcript.text.len() > self.full_transcript_limit(),
+            Strategy::Full | Strategy::Files | Strategy::Cached => false,
+        })
+    }
+
+    /// Pick how to answer `question`, reporting the choice and why. Never returns `Auto`.
+    ///
+    /// Videos already uploaded in parts (or needing several) attach their files; transcripts
+    /// over the full-transcript limit use retrieval, unless Gemini can take the whole thing for
+    /// a question about the video as a whole; repeated questions about a video use the context
+    /// cache; everything else is sent whole.
+    pub fn choose_strategy(&self, transcript: &Transcript, question: &str, store: &FeedbackStore) -> Result<Strategy> {
+        let gemini = matches!(self.llm_provider, LlmProvider::Gemini);
+        let chosen = self.configured_strategy()?;
+        if chosen != Strategy::Auto {
+            if matches!(chosen, Strategy::Files | Strategy::Cached) && !gemini {
+                let flag = if chosen == Strategy::Files { "files" } else { "cached" };
+                anyhow::bail!("--strategy {} needs LLM_PROVIDER=gemini", flag);
+            }
+            status!("🧭 Strategy: {} (chosen)", chosen.name());
+            return Ok(chosen);
+        }
+
+        let len = transcript.text.len();
+        let limit = self.full_transcript_limit();
+        let (strategy, reason) = if gemini
+            && (!self.registry.parts(&transcript.video_id)?.is_empty() || split_parts(transcript).len() > 1)
+        {
+            (Strategy::Files, "the video is uploaded to Gemini, or needs several parts".to_string())
+        } else if len > limit && !(gemini && is_broad(question)) {
+            (Strategy::Retrieval, format!("{} characters is over the {}-character full-transcript limit", len, limit))
+        } else if gemini && self.cacheable(transcript) && self.asked_recently(transcript, store)? {
+            (Strategy::Cached, "a follow-up question about this video".to_string())
+        } else if len > limit {
+            (Strategy::Full, "a question about the whole video".to_string())
+        } else {
+            (Strategy::Full, format!("{} characters fits in one prompt", len))
+        };
+        status!("🧭 Strategy: {} ({})", strategy.name(), reason);
+        Ok(strategy)
+    }
+
+    /// Whether the video was asked about within the context cache's lifetime, or still has a
+    /// live cached context
+    fn asked_recently(&self, transcript: &Transcript, store: &FeedbackStore) -> Result<bool> {
+        if self.live_context(transcript)?.is_some() {
+            return Ok(true);
+        }
+        let since = Utc::now() - chrono::Duration::seconds(self.cache_ttl_secs() as i64);
+        Ok(store
+            .answers
+            .iter()
+            .any(|answer| answer.video_id == transcript.video_id && answer.asked_at > since))
+    }
+}
@@ -0,0 +1,196 @@
+use anyhow::Result;
+
+use crate::prompts::PromptOptions;
+use crate::translate::language_name;
+use crate::VideoTranscriber;
+
+/// Longest answer `--style one-liner` accepts, in characters
+const ONE_LINER_MAX_CHARS: usize = 280;
+
+/// Most times an over-long answer is sent back to be shortened
+const TIGHTEN_ATTEMPTS: usize = 2;
+
+const RESHAPE_SYSTEM_PROMPT: &str =
+    "You reformat answers without changing what they say. Keep every fact and citation; change only the layout.";
+
+const TIGHTEN_SYSTEM_PROMPT: &str =
+    "You shorten answers to fit a word limit. Keep the most important facts and any citations, drop \
+     repetition and filler, and never add anything new.";
+
+/// Shape an answer must take so it can be pasted straight into a document
+#[derive(Clone, Copy, Debug, PartialEq, Eq, clap::ValueEnum)]
+pub enum AnswerStyle {
+    /// A Markdown bullet list and nothing else
+    Bullets,
+    /// A single Markdown table
+    Table,
+    /// Paragraphs of prose, no lists or tables
+    Essay,
+    /// One line of at most 280 characters
+    OneLiner,
+}
+
+impl AnswerStyle {
+    /// What the model is told to do
+    pub fn instruction(self) -> &'static str {
+        match self {
+            AnswerStyle::Bullets => {
+                "Format the answer as a Markdown bullet list: one point per `- ` item, with no \
+                 introduction, conclusion, or headings."
+            }
+            AnswerStyle::Table => {
+                "Format the answer as a single Markdown table with a header row, and put nothing \
+                 before or after the table."
+            }
+            AnswerStyle::Essay => {
+                "Write the answer as flowing prose of at least two paragraphs, with no lists, \
+                 tables, or headings."
+            }
+            AnswerStyle::OneLiner => "Answer in one sentence of at most 280 characters, on a single line.",
+        }
+    }
+
+    /// Why `answer` does not have this shape, if it doesn't
+    pub fn violation(self, answer: &str) -> Option<String> {
+        let lines: Vec<&str> = answer.lines().map(str::trim).filter(|line| !line.is_empty()).collect();
+        if lines.is_empty() {
+            return Some("the answer is empty".to_string());
+        }
+        let is_bullet = |line: &str| {
+            line.starts_with("- ")
+                || line.starts_with("* ")
+                || line.starts_with("• ")
+                || line.split_once(". ").is_some_and(|(n, _)| !n.is_empty() && n.chars().all(|c| c.is_ascii_digit()))
+        };
+        let is_row = |line: &str| line.starts_with('|');
+
+        match self {
+            AnswerStyle::Bullets => {
+                let stray = lines.iter().filter(|line| !is_bullet(line)).count();
+                (stray > 0).then(|| format!("{} line(s) are not list items", stray))
+            }
+            AnswerStyle::Table => {
+                let rows: Vec<&str> = lines.iter().copied().filter(|line| is_row(line)).collect();
+                let separator = rows.get(1).is_some_and(|row| {
+                    row.chars().all(|c| matches!(c, '|' | '-' | ':' | ' ')) && row.contains("---")
+                });
+                if rows.len() < 3 || !separator {
+                    Some("there is no Markdown table with a header, separator, and rows".to_string())
+                } else if rows.len() < lines.len() {
+                    Some(format!("{} line(s) are outside the table", lines.len() - rows.len()))
+                } else {
+                    None
+                }
+            }
+            AnswerStyle::Essay => {
+                let paragraphs = answer.split("\n\n").filter(|p| !p.trim().is_empty()).count();
+                if lines.iter().any(|line| is_bullet(line) || is_row(line) || line.starts_with('#')) {
+                    Some("it contains lists, tables, or headings".to_string())
+                } else if paragraphs < 2 {
+                    Some("it is a single paragraph".to_string())
+                } else {
+                    None
+                }
+            }
+            AnswerStyle::OneLiner => {
+                let length = answer.trim().chars().count();
+                if lines.len() > 1 {
+                    Some(format!("it spans {} lines", lines.len()))
+                } else if length > ONE_LINER_MAX_CHARS {
+                    Some(format!("it is {} characters long", length))
+                } else {
+                    None
+                }
+            }
+        }
+    }
+}
+
+/// Words in an answer, counted the way a word processor does
+pub fn word_count(text: &str) -> usize {
+    text.split_whitespace().count()
+}
+
+/// Split the `Sources:` list that retrieval answers end with from the answer itself
+fn split_sources(answer: &str) -> (&str, &str) {
+    match answer.rfind("\n\nSources:\n") {
+        Some(at) => answer.split_at(at),
+        None => (answer, ""),
+    }
+}
+
+impl VideoTranscriber {
+    /// Add the `--style`, `--max-words`, and `--answer-lang` instructions, if given, to a prompt
+    pub fn styled_prompt(&self, prompt: String) -> String {
+        self.prompt_options(Vec::new()).finish(prompt)
+    }
+
+    /// The `--style`, `--max-words`, and `--answer-lang` settings, with standing instructions
+    /// to add to prompts
+    pub(crate) fn prompt_options(&self, instructions: Vec<String>) -> PromptOptions {
+        PromptOptions {
+            style: self.answer_style,
+            max_words: self.max_words,
+            language: self.answer_language.as_deref().map(language_name),
+            instructions,
+        }
+    }
+
+    /// Check an answer against `--style` and `--max-words`, reformatting it once when it has
+    /// the wrong shape and shortening it when it is too long. A `Sources:` list is left out
+    /// of both checks and kept below the answer.
+    pub fn enforce_style(&self, answer: String) -> Result<String> {
+        if self.answer_style.is_none() && self.max_words.is_none() {
+            return Ok(answer);
+        }
+        let (body, sources) = split_sources(&answer);
+        let mut body = body.trim().to_string();
+
+        if let Some(style) = self.answer_style {
+            if let Some(problem) = style.violation(&body) {
+                status!("📐 Answer doesn't match --style ({}); reformatting...", problem);
+                body = self
+                    .complete(RESHAPE_SYSTEM_PROMPT, &format!("{}\n\nAnswer to reformat:\n{}", style.instruction(), body))?
+                    .trim()
+                    .to_string();
+                if let Some(problem) = style.violation(&body) {
+                    status!("⚠️  The reformatted answer still doesn't match --style: {}", problem);
+                }
+            }
+        }
+        if let Some(limit) = self.max_words {
+            body = self.tighten(body, limit)?;
+        }
+        Ok(format!("{}{}", body, sources))
+    }
+
+    /// Shorten an answer until it fits in `limit` words, keeping its `--style`
+    fn tighten(&self, mut body: String, limit: usize) -> Result<String> {
+        for _ in 0..TIGHTEN_ATTEMPTS {
+            let words = word_count(&body);
+            if words <= limit {
+                return Ok(body);
+            }
+            status!("✂️  Answer is {} words, over --max-words {}; tightening...", words, limit);
+            let format = self
+                .answer_style
+                .map(|style| format!(" {}", style.instruction()))
+                .unwrap_or_default();
+            body = self
+                .complete(
+                    TIGHTEN_SYSTEM_PROMPT,
+                    &format!(
+                        "Rewrite this answer in at most {} words (it is {} now).{}\n\nAnswer to shorten:\n{}",
+                        limit, words, format, body
+                    ),
+                )?
+                .trim()
+                .to_string();
+        }
+        let words = word_count(&body);
+        if words > limit {
+            status!("⚠️  The answer is still {} words, over --max-words {}", words, limit);
+        }
+        Ok(body)
+    }
+}
@@ -0,0 +1,138 @@
+use anyhow::Result;
+use clap::ValueEnum;
+use serde::Serialize;
+
+use crate::chunking::timestamped_sections;
+use crate::feedback::RETRIEVAL_THRESHOLD_CHARS;
+use crate::guardrail::fence;
+use crate::parallel::map_bounded;
+use crate::timestamps::{with_timestamp_links, TIMESTAMP_INSTRUCTION};
+use crate::transcript::{format_timestamp, Transcript};
+use crate::VideoTranscriber;
+
+const SUMMARY_SYSTEM_PROMPT: &str =
+    "You summarize YouTube video transcripts faithfully. State only what the video says, in the \
+     speaker's terms, without adding outside knowledge or opinions of your own.";
+
+/// Characters of transcript per section when a long transcript is summarized in sections
+const SECTION_CHARS: usize = 30_000;
+/// Sections summarized at once
+const SECTION_CONCURRENCY: usize = 4;
+
+/// How long a summary should be
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SummaryLength {
+    /// One or two sentences
+    Tldr,
+    /// One paragraph covering the main points
+    #[default]
+    Paragraph,
+    /// Sections with bullet points and timestamp links
+    Detailed,
+}
+
+impl SummaryLength {
+    /// What the summary must look like, added to the prompt
+    fn instruction(self) -> &'static str {
+        match self {
+            SummaryLength::Tldr => {
+                "a TL;DR: one or two plain sentences, at most 40 words, giving the video's main point \
+                 and conclusion. No heading, list, or preamble."
+            }
+            SummaryLength::Paragraph => {
+                "one paragraph of 4 to 6 sentences covering the main points in the order the video makes \
+                 them, ending with its conclusion or recommendation. No heading, list, or preamble."
+            }
+            SummaryLength::Detailed => {
+                "a detailed Markdown summary: a one-sentence overview, then a `##` section for each major \
+                 topic in the order the video covers it, with bullet points for the key points, examples, \
+                 and figures, and finally a `## Key Takeaways` list of 3 to 5 bullets."
+            }
+        }
+    }
+}
+
+impl VideoTranscriber {
+    /// Summarize a video at the given length. Transcripts too long to send at once are
+    /// summarized section by section first, and the section notes are then combined.
+    pub fn summarize(&self, transcript: &Transcript, length: SummaryLength) -> Result<String> {
+        let timestamped = !transcript.segments.is_empty() && length == SummaryLength::Detailed;
+        let citing = if timestamped {
+            format!(" {}", TIMESTAMP_INSTRUCTION)
+        } else {
+            String::new()
+        };
+
+        let summary = if transcript.text.len() <= RETRIEVAL_THRESHOLD_CHARS {
+            status!("📝 Summarizing \"{}\"...", transcript.label());
+            let prompt = format!(
+                "Write {}{}\n\nTranscript of the video \"{}\":\n{}",
+                length.instruction(),
+                citing,
+                transcript.label(),
+                fence(&transcript.timestamped_text(30.0))
+            );
+            self.complete(SUMMARY_SYSTEM_PROMPT, &prompt)?
+        } else {
+            let notes = self.section_notes(transcript)?;
+            status!("📝 Combining the notes on {} sections into one summary...", notes.len());
+            let prompt = format!(
+                "Below are notes on consecutive sections of the video \"{}\", in order. Combine them into \
+                 {} Treat the notes as one video: don't mention sections, and merge points that repeat.{}\n\n{}",
+                transcript.label(),
+                length.instruction(),
+                if timestamped {
+                    " Keep the [MM:SS] timestamps of the notes after the claims they support."
+                } else {
+                    ""
+                },
+                fence(&notes.join("\n\n"))
+            );
+            self.complete(SUMMARY_SYSTEM_PROMPT, &prompt)?
+        };
+
+        let summary = summary.trim().to_string();
+        Ok(if timestamped {
+            with_timestamp_links(transcript, summary)
+        } else {
+            summary
+        })
+    }
+
+    /// Notes on each section of a long transcript, in order (the map step of `summarize`)
+    fn section_notes(&self, transcript: &Transcript) -> Result<Vec<String>> {
+        let sections: Vec<(String, String)> = timestamped_sections(transcript, SECTION_CHARS)
+            .into_iter()
+            .map(|(boundary, text)| {
+                let span = match (boundary.start_secs, boundary.end_secs) {
+                    (Some(start), Some(end)) => format!(" ({}–{})", format_timestamp(start), format_timestamp(end)),
+                    _ => String::new(),
+                };
+                (span, text)
+            })
+            .collect();
+        status!(
+            "🧩 The transcript is long; summarizing it in {} sections first...",
+            sections.len()
+        );
+
+        let total = sections.len();
+        let indexed: Vec<(usize, &(String, String))> = sections.iter().enumerate().collect();
+        map_bounded(&indexed, SECTION_CONCURRENCY, |(index, (span, text))| {
+            let prompt = format!(
+                "This is section {} of {}{} of the transcript of the video \"{}\". Write concise bullet-point \
+                 notes of every main point, argument, example, and figure in it, in order. Keep the [MM:SS] \
+                 timestamp of the passage each note comes from, if the transcript has them.\n\n{}",
+                index + 1,
+                total,
+                span,
+                transcript.label(),
+                fence(text)
+            );
+            self.complete(SUMMARY_SYSTEM_PROMPT, &prompt)
+        })
+        .into_iter()
+        .collect()
+    }
+}
@@ -0,0 +1,151 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::config::Config;
+use crate::Error;
+
+/// Aggregate usage counts kept on this machine.
+///
+/// Only command names and error categories are counted: never arguments, URLs,
+/// questions, transcripts, or keys. Nothing leaves the machine unless the user
+/// runs `telemetry export` and shares the file themselves.
+#[derive(Serialize, Deserialize)]
+pub struct Telemetry {
+    pub version: String,
+    pub os: String,
+    pub since: DateTime<Utc>,
+    /// Runs per command
+    #[serde(default)]
+    pub commands: BTreeMap<String, u64>,
+    /// Failed runs per command
+    #[serde(default)]
+    pub failures: BTreeMap<String, u64>,
+    /// Failures per error category
+    #[serde(default)]
+    pub errors: BTreeMap<String, u64>,
+}
+
+impl Default for Telemetry {
+    fn default() -> Self {
+        Self {
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            os: env::consts::OS.to_string(),
+            since: Utc::now(),
+            commands: BTreeMap::new(),
+            failures: BTreeMap::new(),
+            errors: BTreeMap::new(),
+        }
+    }
+}
+
+impl Telemetry {
+    fn path(dir: &Path) -> PathBuf {
+        dir.join("telemetry.json")
+    }
+
+    pub fn load(dir: &Path) -> Result<Self> {
+        let path = Self::path(dir);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let contents = fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        serde_json::from_str(&contents).with_context(|| format!("Failed to parse {}", path.display()))
+    }
+
+    fn save(&self, dir: &Path) -> Result<()> {
+        let path = Self::path(dir);
+        fs::write(&path, serde_json::to_string_pretty(self)?)
+            .with_context(|| format!("Failed to write {}", path.display()))
+    }
+
+    /// Delete everything recorded so far
+    pub fn clear(dir: &Path) -> Result<()> {
+        let path = Self::path(dir);
+        if path.exists() {
+            fs::remove_file(&path).with_context(|| format!("Failed to delete {}", path.display()))?;
+        }
+        Ok(())
+    }
+}
+
+/// Whether the user has opted in; `CVT_TELEMETRY=0` always wins
+pub fn enabled() -> bool {
+    if env::var("CVT_TELEMETRY").is_ok_and(|v| v == "0" || v.eq_ignore_ascii_case("false")) {
+        return false;
+    }
+    Config::load().ok().and_then(|c| c.telemetry).unwrap_or(false)
+}
+
+/// Coarse bucket for an error, derived from its cause chain rather than its message
+pub fn error_category(error: &anyhow::Error) -> &'static str {
+    match crate::cancel::cancelled(error) {
+        Some(crate::cancel::Cancelled::Interrupted) => return "interrupted",
+        Some(crate::cancel::Cancelled::TimedOut(_)) => return "timeout",
+        None => {}
+    }
+    match crate::error::kind(error) {
+        Some(Error::MissingApiKey(_) | Error::ProviderKey { .. }) => return "config",
+        Some(Error::Http { .. } | Error::ApifyRun(_) | Error::GeminiFileFailed { .. }) => return "api",
+        Some(Error::GeminiFileTimeout { .. }) => return "timeout",
+        Some(Error::InvalidUrl(_) | Error::NoTranscript(_) | Error::PrivateVideo { .. }) => return "input",
+        Some(Error::Vetoed { .. }) => return "vetoed",
+        Some(Error::Blocked { .. }) => return "blocked",
+        None => {}
+    }
+    for cause in error.chain() {
+        if let Some(e) = cause.downcast_ref::<reqwest::Error>() {
+            return if e.is_timeout() { "timeout" } else { "network" };
+        }
+        if cause.is::<std::io::Error>() {
+            return "io";
+        }
+        if cause.is::<serde_json::Error>() || cause.is::<toml::de::Error>() {
+            return "parse";
+        }
+    }
+
+    let message = error.to_string();
+    if message.contains("_API_KEY") {
+        "config"
+    } else if message.contains("failed with status") {
+        "api"
+    } else if message.contains("timed out") {
+        "timeout"
+    } else if message.contains("URL") {
+        "input"
+    } else {
+        "other"
+    }
+}
+
+/// Count one run of a command in the counts kept in `data_dir`. Never fails the command:
+/// problems here are ignored.
+pub fn record(data_dir: &Path, command: &str, error: Option<&anyhow::Error>) {
+    if command.is_empty() || !enabled() {
+        return;
+    }
+    let Ok(mut telemetry) = Telemetry::load(data_dir) else {
+        return;
+    };
+
+    telemetry.version = env!("CARGO_PKG_VERSION").to_string();
+    *telemetry.commands.entry(command.to_string()).or_default() += 1;
+    if let Some(error) = error {
+        *telemetry.failures.entry(command.to_string()).or_default() += 1;
+        *telemetry.errors.entry(error_category(error).to_string()).or_default() += 1;
+    }
+    let _ = telemetry.save(data_dir);
+}
+
+/// Turn collection on or off, saving the choice in the config file
+pub fn set_enabled(on: bool) -> Result<PathBuf> {
+    let mut config = Config::load()?;
+    config.telemetry = Some(on);
+    config.save()
+}
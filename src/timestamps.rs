@@ -0,0 +1,40 @@
+use regex::Regex;
+
+use crate::companion::parse_timestamp;
+use crate::transcript::{format_timestamp, Transcript};
+
+/// Tells the model to cite the `[MM:SS]` markers of `Transcript::timestamped_text`
+pub const TIMESTAMP_INSTRUCTION: &str = "The transcript is marked with [MM:SS] timestamps. After each claim, \
+     cite the moment it comes from by copying the nearest earlier timestamp, e.g. [12:30].";
+
+/// Moments an answer cites as `[MM:SS]` or `[H:MM:SS]`, in order of first citation, leaving out
+/// any past the end of the transcript
+pub fn cited_times(answer: &str, transcript: &Transcript) -> Vec<f64> {
+    let Some(end) = transcript.segments.last().map(|segment| segment.end) else {
+        return Vec::new();
+    };
+    let marker = Regex::new(r"\[(\d{1,2}:\d{2}(?::\d{2})?)\]").expect("valid regex");
+    let mut times: Vec<f64> = Vec::new();
+    for caps in marker.captures_iter(answer) {
+        if let Ok(seconds) = parse_timestamp(&caps[1]) {
+            if seconds <= end && !times.contains(&seconds) {
+                times.push(seconds);
+            }
+        }
+    }
+    times
+}
+
+/// The answer followed by a Sources list linking each moment it cites into the video;
+/// unchanged when it cites none
+pub fn with_timestamp_links(transcript: &Transcript, mut answer: String) -> String {
+    let times = cited_times(&answer, transcript);
+    if times.is_empty() {
+        return answer;
+    }
+    answer.push_str("\n\nSources:");
+    for seconds in times {
+        answer.push_str(&format!("\n  [{}] {}", format_timestamp(seconds), transcript.link_at(seconds)));
+    }
+    answer
+}
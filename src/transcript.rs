@@ -0,0 +1,438 @@
+use chrono::{DateTime, Utc};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::borrow::Cow;
+use std::sync::Arc;
+
+/// A fetched video transcript together with the metadata we know about the video
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Transcript {
+    pub video_id: String,
+    pub url: String,
+    pub title: Option<String>,
+    pub channel: Option<String>,
+    /// Full transcript text, shared rather than copied when transcripts are cloned
+    pub text: Arc<str>,
+    /// Timed caption segments, empty when only plain text was available
+    #[serde(default)]
+    pub segments: Vec<Segment>,
+    /// When the video was published, if the scraper reported it
+    #[serde(default)]
+    pub published_at: Option<DateTime<Utc>>,
+    /// Length of the video in seconds, if the source reported it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub duration_secs: Option<f64>,
+    pub fetched_at: DateTime<Utc>,
+}
+
+/// One caption cue with its start and end time in seconds
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Segment {
+    pub start: f64,
+    pub end: f64,
+    pub text: String,
+    /// Where the cue's text came from; unset for transcripts cached before it was recorded
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<CaptionSource>,
+}
+
+/// Provenance of a transcript segment
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum CaptionSource {
+    /// YouTube captions uploaded by the creator
+    Creator,
+    /// YouTube's speech recognition
+    Auto,
+    /// Whisper transcription: of a local recording with Groq, or of a video without
+    /// captions with whisper.cpp
+    Whisper,
+    /// Captions saved with a Zoom, Teams, or Meet recording
+    Meeting,
+    /// Chat messages and participant lists, which were typed rather than said
+    Chat,
+}
+
+impl CaptionSource {
+    /// How a citation describes the source
+    pub fn describe(self) -> &'static str {
+        match self {
+            CaptionSource::Creator => "creator captions",
+            CaptionSource::Auto => "auto-generated captions",
+            CaptionSource::Whisper => "Whisper transcription",
+            CaptionSource::Meeting => "meeting captions",
+            CaptionSource::Chat => "chat",
+        }
+    }
+}
+
+impl Transcript {
+    /// Human-readable label for the video, falling back to its ID
+    pub fn label(&self) -> &str {
+        self.title.as_deref().unwrap_or(&self.video_id)
+    }
+
+    /// Hex SHA-256 of the transcript text, for noticing when a video's transcript has changed
+    pub fn text_sha256(&self) -> String {
+        hex::encode(Sha256::digest(self.text.as_bytes()))
+    }
+
+    /// Transcript text with a `[MM:SS]` marker roughly every `every_secs` seconds.
+    ///
+    /// Falls back to the plain text when no timed segments are available.
+    pub fn timestamped_text(&self, every_secs: f64) -> Cow<'_, str> {
+        if self.segments.is_empty() {
+            return Cow::Borrowed(&self.text);
+        }
+
+        let mut out = String::with_capacity(self.text.len() + self.text.len() / 10);
+        let mut next_marker = 0.0;
+        for segment in &self.segments {
+            if segment.start >= next_marker {
+                if !out.is_empty() {
+                    out.push('\n');
+                }
+                out.push_str(&format!("[{}] ", format_timestamp(segment.start)));
+                next_marker = segment.start + every_secs;
+            } else {
+                out.push(' ');
+            }
+            out.push_str(&segment.text);
+        }
+        Cow::Owned(out)
+    }
+
+    /// Start time of the segment containing byte offset `offset` of `text`.
+    ///
+    /// Relies on `text` being the segments joined with single spaces, as `join_segments` builds it.
+    pub fn time_at_offset(&self, offset: usize) -> Option<f64> {
+        let mut position = 0;
+        for segment in &self.segments {
+            position += segment.text.len() + 1;
+            if offset < position {
+                return Some(segment.start);
+            }
+        }
+        self.segments.last().map(|segment| segment.start)
+    }
+
+    /// Provenance of the segment playing at `seconds`
+    pub fn source_at(&self, seconds: f64) -> Option<CaptionSource> {
+        self.segments
+            .iter()
+            .take_while(|segment| segment.start <= seconds)
+            .last()
+            .and_then(|segment| segment.source)
+    }
+
+    /// Link to the video starting at `seconds`
+    pub fn link_at(&self, seconds: f64) -> String {
+        // Local recordings use a media fragment, which browsers and most players honour
+        if self.url.starts_with("file://") {
+            return format!("{}#t={}", self.url, seconds.max(0.0) as u64);
+        }
+        format!("https://youtu.be/{}?t={}", self.video_id, seconds.max(0.0) as u64)
+    }
+}
+
+/// Format seconds as `MM:SS`, or `H:MM:SS` for times past the first hour
+pub fn format_timestamp(seconds: f64) -> String {
+    let total = seconds.max(0.0) as u64;
+    let (hours, minutes, secs) = (total / 3600, (total % 3600) / 60, total % 60);
+    if hours > 0 {
+        format!("{}:{:02}:{:02}", hours, minutes, secs)
+    } else {
+        format!("{:02}:{:02}", minutes, secs)
+    }
+}
+
+/// Parse an SRT subtitle file into segments, skipping malformed cues
+pub fn parse_srt(srt: &str) -> Vec<Segment> {
+    let normalized = srt.replace("\r\n", "\n");
+    let mut segments = Vec::new();
+
+    for block in normalized.split("\n\n") {
+        let mut lines = block.lines().filter(|line| !line.trim().is_empty());
+        let mut timing = lines.next();
+        // The cue number line is optional in practice
+        if timing.is_some_and(|line| !line.contains("-->")) {
+            timing = lines.next();
+        }
+        let Some((start, end)) = timing.and_then(|line| line.split_once("-->")) else {
+            continue;
+        };
+        let (Some(start), Some(end)) = (parse_srt_time(start), parse_srt_time(end)) else {
+            continue;
+        };
+
+        let text = lines.map(str::trim).collect::<Vec<_>>().join(" ");
+        if !text.is_empty() {
+            segments.push(Segment {
+                start,
+                end,
+                text,
+                source: None,
+            });
+        }
+    }
+
+    segments
+}
+
+/// Parse a WebVTT caption file into segments. Voice tags (`<v Alice>`) become an `Alice:`
+/// prefix and other markup is dropped.
+pub fn parse_vtt(vtt: &str) -> Vec<Segment> {
+    let voice = Regex::new(r"<v(?:\.[^ >]*)? ([^>]+)>").expect("valid regex");
+    let markup = Regex::new(r"<[^>]*>").expect("valid regex");
+    let normalized = vtt.replace("\r\n", "\n");
+    let mut segments = Vec::new();
+
+    for block in normalized.split("\n\n") {
+        // Skips the header, NOTE and STYLE blocks, and optional cue identifiers
+        let mut lines = block
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .skip_while(|line| !line.contains("-->"));
+        let Some((start, end)) = lines.next().and_then(|line| line.split_once("-->")) else {
+            continue;
+        };
+        // Cue settings such as `align:start` follow the end time
+        let end = end.split_whitespace().next().unwrap_or_default();
+        let (Some(start), Some(end)) = (parse_srt_time(start), parse_srt_time(end)) else {
+            continue;
+        };
+
+        let mut speaker = None;
+        let text = lines
+            .map(|line| {
+                if let Some(captures) = voice.captures(line) {
+                    speaker.get_or_insert_with(|| captures[1].trim().to_string());
+                }
+                unescape_html(markup.replace_all(line, "").trim())
+            })
+            .collect::<Vec<_>>()
+            .join(" ");
+        if text.is_empty() {
+            continue;
+        }
+        let text = match speaker {
+            Some(speaker) => format!("{}: {}", speaker, text),
+            None => text,
+        };
+        segments.push(Segment {
+            start,
+            end,
+            text,
+            source: None,
+        });
+    }
+
+    segments
+}
+
+/// Parse an SRT timestamp such as `00:01:02,345` into seconds
+fn parse_srt_time(value: &str) -> Option<f64> {
+    let value = value.trim().replace(',', ".");
+    let mut parts = value.split(':').rev();
+    let seconds: f64 = parts.next()?.parse().ok()?;
+    let minutes: f64 = parts.next().unwrap_or("0").parse().ok()?;
+    let hours: f64 = parts.next().unwrap_or("0").parse().ok()?;
+    Some(hours * 3600.0 + minutes * 60.0 + seconds)
+}
+
+/// Segments as an SRT subtitle file
+pub fn render_srt(segments: &[Segment]) -> String {
+    segments
+        .iter()
+        .enumerate()
+        .map(|(i, segment)| {
+            format!(
+                "{}\n{} --> {}\n{}\n",
+                i + 1,
+                format_cue_time(segment.start, ','),
+                format_cue_time(segment.end, ','),
+                cue_text(&segment.text)
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Segments as a WebVTT caption file, with `&`, `<`, and `>` escaped as the format requires
+pub fn render_vtt(segments: &[Segment]) -> String {
+    let mut out = String::from("WEBVTT\n");
+    for segment in segments {
+        let text = cue_text(&segment.text)
+            .replace('&', "&amp;")
+            .replace('<', "&lt;")
+            .replace('>', "&gt;");
+        out.push_str(&format!(
+            "\n{} --> {}\n{}\n",
+            format_cue_time(segment.start, '.'),
+            format_cue_time(segment.end, '.'),
+            text
+        ));
+    }
+    out
+}
+
+/// A cue time such as `01:02:03,450`, with `separator` before the milliseconds (`,` for SRT
+/// and `.` for WebVTT)
+fn format_cue_time(seconds: f64, separator: char) -> String {
+    let millis = (seconds.max(0.0) * 1000.0).round() as u64;
+    format!(
+        "{:02}:{:02}:{:02}{}{:03}",
+        millis / 3_600_000,
+        (millis / 60_000) % 60,
+        (millis / 1000) % 60,
+        separator,
+        millis % 1000
+    )
+}
+
+/// Cue text on one line: a blank line would end the cue and an arrow would read as timing
+fn cue_text(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ").replace("-->", "->")
+}
+
+/// Decode the HTML entities caption formats use for markup characters
+pub fn unescape_html(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&nbsp;", " ")
+        .replace("&amp;", "&")
+}
+
+/// Join segment texts into a single plain transcript
+pub fn join_segments(segments: &[Segment]) -> String {
+    segments
+        .iter()
+        .map(|segment| segment.text.as_str())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Parse the publish date formats the scraper returns (RFC 3339 or a bare `YYYY-MM-DD`)
+pub fn parse_published_date(value: &str) -> Option<DateTime<Utc>> {
+    if let Ok(date) = DateTime::parse_from_rfc3339(value) {
+        return Some(date.with_timezone(&Utc));
+    }
+    chrono::NaiveDate::parse_from_str(value.get(..10)?, "%Y-%m-%d")
+        .ok()
+        .and_then(|date| date.and_hms_opt(0, 0, 0))
+        .map(|date| date.and_utc())
+}
+
+/// Byte offsets of every case-insensitive (ASCII) occurrence of `term` in `text`
+pub fn find_mentions(text: &str, term: &str) -> Vec<usize> {
+    let haystack = text.to_ascii_lowercase();
+    let needle = term.to_ascii_lowercase();
+    if needle.is_empty() {
+        return Vec::new();
+    }
+    haystack.match_indices(&needle).map(|(i, _)| i).collect()
+}
+
+/// The text surrounding byte offset `at`, roughly `radius` bytes either side
+pub fn snippet_around(text: &str, at: usize, radius: usize) -> String {
+    let mut start = at.saturating_sub(radius);
+    while !text.is_char_boundary(start) {
+        start -= 1;
+    }
+    let mut end = (at + radius).min(text.len());
+    while !text.is_char_boundary(end) {
+        end += 1;
+    }
+
+    let mut snippet = text[start..end].split_whitespace().collect::<Vec<_>>().join(" ");
+    if start > 0 {
+        snippet.insert(0, '…');
+    }
+    if end < text.len() {
+        snippet.push('…');
+    }
+    snippet
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    /// Cue text as it comes out of the parsers: one line of non-blank text. Includes the
+    /// characters WebVTT has to escape.
+    fn cue_text() -> impl Strategy<Value = String> {
+        "[A-Za-z0-9 ,.!?'\"&<>:;()é-]{1,60}"
+            .prop_filter("not blank", |text| !text.trim().is_empty())
+            .prop_map(|text| text.split_whitespace().collect::<Vec<_>>().join(" "))
+            .prop_filter("no arrows", |text| !text.contains("-->"))
+    }
+
+    /// Segments in order with millisecond times, as caption files store them
+    fn segments() -> impl Strategy<Value = Vec<Segment>> {
+        prop::collection::vec((0..5_000_000u64, 1..600_000u64, cue_text()), 0..20).prop_map(|cues| {
+            let mut at = 0;
+            cues.into_iter()
+                .map(|(gap, length, text)| {
+                    let start = at + gap;
+                    at = start + length;
+                    Segment {
+                        start: start as f64 / 1000.0,
+                        end: at as f64 / 1000.0,
+                        text,
+                        source: None,
+                    }
+                })
+                .collect()
+        })
+    }
+
+    fn assert_same(parsed: &[Segment], segments: &[Segment]) -> Result<(), TestCaseError> {
+        prop_assert_eq!(parsed.len(), segments.len());
+        for (parsed, segment) in parsed.iter().zip(segments) {
+            prop_assert!((parsed.start - segment.start).abs() < 5e-4, "start {} != {}", parsed.start, segment.start);
+            prop_assert!((parsed.end - segment.end).abs() < 5e-4, "end {} != {}", parsed.end, segment.end);
+            prop_assert_eq!(&parsed.text, &segment.text);
+        }
+        Ok(())
+    }
+
+    proptest! {
+        #[test]
+        fn srt_round_trips(segments in segments()) {
+            assert_same(&parse_srt(&render_srt(&segments)), &segments)?;
+        }
+
+        #[test]
+        fn vtt_round_trips(segments in segments()) {
+            assert_same(&parse_vtt(&render_vtt(&segments)), &segments)?;
+        }
+
+        #[test]
+        fn srt_survives_windows_line_endings(segments in segments()) {
+            assert_same(&parse_srt(&render_srt(&segments).replace('\n', "\r\n")), &segments)?;
+        }
+
+        #[test]
+        fn timestamps_are_minutes_and_seconds_or_hours(seconds in 0.0..400_000.0f64) {
+            let formatted = format_timestamp(seconds);
+            let parts: Vec<&str> = formatted.split(':').collect();
+            if seconds < 3600.0 {
+                prop_assert_eq!(parts.len(), 2);
+            } else {
+                prop_assert_eq!(parts.len(), 3);
+                prop_assert_eq!(parts[1].len(), 2);
+            }
+            prop_assert_eq!(parts.last().unwrap().len(), 2);
+        }
+
+        #[test]
+        fn parsers_never_panic(input in any::<String>()) {
+            parse_srt(&input);
+            parse_vtt(&input);
+        }
+    }
+}
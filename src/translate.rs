@@ -0,0 +1,211 @@
+use anyhow::Result;
+use serde::Deserialize;
+use std::sync::Arc;
+
+use crate::guardrail::fence;
+use crate::parallel::map_bounded;
+use crate::transcript::{join_segments, Segment, Transcript};
+use crate::VideoTranscriber;
+
+const TRANSLATE_SYSTEM_PROMPT: &str =
+    "You translate video transcripts. Translate faithfully and completely, keeping the speaker's \
+     tone, technical terms, names, and numbers. Never summarize, skip, merge, or explain lines.";
+
+/// Characters of transcript sent per translation request
+const BATCH_CHARS: usize = 6_000;
+/// Translation requests made at once
+const BATCH_CONCURRENCY: usize = 4;
+
+/// Common ISO 639-1 codes and the language names prompts use for them
+const LANGUAGES: &[(&str, &str)] = &[
+    ("ar", "Arabic"),
+    ("bn", "Bengali"),
+    ("cs", "Czech"),
+    ("da", "Danish"),
+    ("de", "German"),
+    ("el", "Greek"),
+    ("en", "English"),
+    ("es", "Spanish"),
+    ("fa", "Persian"),
+    ("fi", "Finnish"),
+    ("fr", "French"),
+    ("he", "Hebrew"),
+    ("hi", "Hindi"),
+    ("hu", "Hungarian"),
+    ("id", "Indonesian"),
+    ("it", "Italian"),
+    ("ja", "Japanese"),
+    ("ko", "Korean"),
+    ("nl", "Dutch"),
+    ("no", "Norwegian"),
+    ("pl", "Polish"),
+    ("pt", "Portuguese"),
+    ("ro", "Romanian"),
+    ("ru", "Russian"),
+    ("sv", "Swedish"),
+    ("sw", "Swahili"),
+    ("th", "Thai"),
+    ("tr", "Turkish"),
+    ("uk", "Ukrainian"),
+    ("ur", "Urdu"),
+    ("vi", "Vietnamese"),
+    ("zh", "Chinese"),
+];
+
+/// The name of a language given as an ISO 639-1 code (`es`) or a regional tag (`pt-BR`).
+/// Anything else, such as a name like "Brazilian Portuguese", is passed through as written,
+/// since the model understands it as well as we do.
+pub fn language_name(language: &str) -> String {
+    let language = language.trim();
+    let (code, region) = match language.split_once(['-', '_']) {
+        Some((code, region)) => (code, Some(region)),
+        None => (language, None),
+    };
+    match LANGUAGES.iter().find(|(known, _)| known.eq_ignore_ascii_case(code)) {
+        Some((_, name)) => match region {
+            Some(region) => format!("{} ({})", name, region.to_uppercase()),
+            None => name.to_string(),
+        },
+        None => language.to_string(),
+    }
+}
+
+#[derive(Deserialize)]
+struct Reply {
+    lines: Vec<String>,
+}
+
+impl VideoTranscriber {
+    /// Translate a transcript into `language` (a code like `es` or a language name). Timed
+    /// transcripts are translated segment by segment, so every segment keeps its timing and
+    /// the translation can be cited and exported like the original.
+    pub fn translate_transcript(&self, transcript: &Transcript, language: &str) -> Result<Transcript> {
+        let language = language_name(language);
+        let mut translated = transcript.clone();
+        if transcript.segments.is_empty() {
+            let batches = text_batches(&transcript.text, BATCH_CHARS);
+            status!(
+                "🌐 Translating \"{}\" into {} ({} part(s))...",
+                transcript.label(),
+                language,
+                batches.len()
+            );
+            let parts = map_bounded(&batches, BATCH_CONCURRENCY, |text| self.translate_text(text, &language))
+                .into_iter()
+                .collect::<Result<Vec<_>>>()?;
+            translated.text = Arc::from(parts.join(" "));
+        } else {
+            let batches = segment_batches(&transcript.segments, BATCH_CHARS);
+            status!(
+                "🌐 Translating {} segment(s) of \"{}\" into {}...",
+                transcript.segments.len(),
+                transcript.label(),
+                language
+            );
+            translated.segments = map_bounded(&batches, BATCH_CONCURRENCY, |batch| {
+                self.translate_segments(batch, &language)
+            })
+            .into_iter()
+            .collect::<Result<Vec<_>>>()?
+            .concat();
+            translated.text = Arc::from(join_segments(&translated.segments));
+        }
+        Ok(translated)
+    }
+
+    /// Translate a run of segments as numbered lines. When the model merges or drops lines, the
+    /// run is halved and each half translated again, down to single segments.
+    fn translate_segments(&self, segments: &[Segment], language: &str) -> Result<Vec<Segment>> {
+        let numbered: String = segments
+            .iter()
+            .enumerate()
+            .map(|(i, segment)| format!("{}. {}\n", i + 1, segment.text.trim()))
+            .collect();
+        let prompt = format!(
+            "Translate each of these {} numbered transcript lines into {}. Lines can end mid-sentence; \
+             translate each one on its own as best you can, and never move words between lines. \
+             Return JSON with exactly one translated line per input line, in order and without the \
+             numbers: {{\"lines\": [\"...\"]}}\n\n{}",
+            segments.len(),
+            language,
+            fence(&numbered)
+        );
+        let reply: Reply = self.complete_json(TRANSLATE_SYSTEM_PROMPT, &prompt)?;
+        if reply.lines.len() == segments.len() {
+            return Ok(segments
+                .iter()
+                .zip(reply.lines)
+                .map(|(segment, text)| Segment {
+                    text: text.trim().to_string(),
+                    ..segment.clone()
+                })
+                .collect());
+        }
+        if segments.len() == 1 {
+            anyhow::bail!(
+                "The model returned {} lines for one transcript segment at {:.0}s",
+                reply.lines.len(),
+                segments[0].start
+            );
+        }
+        status!(
+            "⚠️  Got {} translated lines for {} segments; retrying in smaller batches...",
+            reply.lines.len(),
+            segments.len()
+        );
+        let (first, second) = segments.split_at(segments.len() / 2);
+        let mut translated = self.translate_segments(first, language)?;
+        translated.extend(self.translate_segments(second, language)?);
+        Ok(translated)
+    }
+
+    /// Translate a passage of untimed transcript
+    fn translate_text(&self, text: &str, language: &str) -> Result<String> {
+        let prompt = format!(
+            "Translate this transcript passage into {}. Reply with the translation only.\n\n{}",
+            language,
+            fence(text)
+        );
+        Ok(self.complete(TRANSLATE_SYSTEM_PROMPT, &prompt)?.trim().to_string())
+    }
+}
+
+/// Consecutive runs of segments of about `budget` characters each
+fn segment_batches(segments: &[Segment], budget: usize) -> Vec<Vec<Segment>> {
+    let mut batches = Vec::new();
+    let mut batch: Vec<Segment> = Vec::new();
+    let mut chars = 0;
+    for segment in segments {
+        if !batch.is_empty() && chars + segment.text.len() > budget {
+            batches.push(std::mem::take(&mut batch));
+            chars = 0;
+        }
+        chars += segment.text.len();
+        batch.push(segment.clone());
+    }
+    if !batch.is_empty() {
+        batches.push(batch);
+    }
+    batches
+}
+
+/// Text split into passages of about `budget` characters, ending at a sentence where one ends
+/// close enough to the budget
+fn text_batches(text: &str, budget: usize) -> Vec<String> {
+    let mut batches = Vec::new();
+    let mut batch = String::new();
+    for word in text.split_whitespace() {
+        if !batch.is_empty() {
+            batch.push(' ');
+        }
+        batch.push_str(word);
+        let sentence_end = word.ends_with(['.', '?', '!']);
+        if (batch.len() >= budget && sentence_end) || batch.len() >= budget + budget / 2 {
+            batches.push(std::mem::take(&mut batch));
+        }
+    }
+    if !batch.is_empty() {
+        batches.push(batch);
+    }
+    batches
+}
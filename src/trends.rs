@@ -0,0 +1,135 @@
+use anyhow::Result;
+use serde::Deserialize;
+
+use crate::guardrail::fence;
+use crate::transcript::{find_mentions, snippet_around, Transcript};
+use crate::VideoTranscriber;
+
+/// How many mention snippets per video are sent for sentiment scoring
+const MAX_SNIPPETS_PER_VIDEO: usize = 10;
+
+const TRENDS_SYSTEM_PROMPT: &str =
+    "You rate how a speaker talks about a topic. Reply with JSON only.";
+
+/// How one video talks about the topic
+pub struct TopicPoint {
+    pub transcript: Transcript,
+    pub mentions: usize,
+    /// -1.0 (negative) to 1.0 (positive); `None` when the topic is not mentioned
+    pub sentiment: Option<f64>,
+}
+
+#[derive(Deserialize)]
+struct SentimentReply {
+    sentiment: f64,
+}
+
+impl VideoTranscriber {
+    /// Count topic mentions and score their sentiment across a channel's videos, oldest first,
+    /// leaving out private videos unless `include_private`
+    pub fn topic_trend(&self, channel: &str, topic: &str, include_private: bool) -> Result<Vec<TopicPoint>> {
+        let mut videos = self.shareable_transcripts(self.channel_transcripts(channel)?, include_private)?;
+        videos.sort_by_key(|t| t.published_at);
+
+        let mut points = Vec::new();
+        for transcript in videos {
+            let offsets = find_mentions(&transcript.text, topic);
+            let sentiment = if offsets.is_empty() {
+                None
+            } else {
+                status!("💬 Scoring {} mention(s) in {}", offsets.len(), transcript.label());
+                let snippets: Vec<String> = offsets
+                    .iter()
+                    .take(MAX_SNIPPETS_PER_VIDEO)
+                    .map(|&at| snippet_around(&transcript.text, at, 200))
+                    .collect();
+                let prompt = format!(
+                    "How positively does the speaker talk about \"{}\" in these excerpts? \
+                     Return {{\"sentiment\": number}} from -1 (very negative) to 1 (very positive).\n\n{}",
+                    topic,
+                    fence(&snippets.join("\n---\n"))
+                );
+                let reply: SentimentReply = self.complete_json(TRENDS_SYSTEM_PROMPT, &prompt)?;
+                Some(reply.sentiment.clamp(-1.0, 1.0))
+            };
+
+            points.push(TopicPoint {
+                mentions: offsets.len(),
+                sentiment,
+                transcript,
+            });
+        }
+
+        Ok(points)
+    }
+
+    /// Library videos from a channel (case-insensitive name match)
+    pub fn channel_transcripts(&self, channel: &str) -> Result<Vec<Transcript>> {
+        let needle = channel.to_lowercase();
+        let videos: Vec<Transcript> = self
+            .cache
+            .list()?
+            .into_iter()
+            .filter(|t| {
+                t.channel
+                    .as_deref()
+                    .is_some_and(|name| name.to_lowercase().contains(&needle))
+            })
+            .collect();
+
+        if videos.is_empty() {
+            anyhow::bail!("No indexed videos from a channel matching \"{}\"", channel);
+        }
+        Ok(videos)
+    }
+}
+
+/// Render the trend as an ASCII bar chart of mentions with a sentiment column
+pub fn render_ascii(points: &[TopicPoint], topic: &str) -> String {
+    const BAR_WIDTH: usize = 40;
+    let max = points.iter().map(|p| p.mentions).max().unwrap_or(0).max(1);
+
+    let mut out = format!("Mentions of \"{}\" over time\n\n", topic);
+    for point in points {
+        let bar = "█".repeat(point.mentions * BAR_WIDTH / max);
+        let sentiment = match point.sentiment {
+            Some(s) if s > 0.2 => format!("+{:.2} 🙂", s),
+            Some(s) if s < -0.2 => format!("{:.2} 🙁", s),
+            Some(s) => format!("{:+.2} 😐", s),
+            None => String::new(),
+        };
+        out.push_str(&format!(
+            "{:<10} {:<40.40} {:>4} {:<40} {}\n",
+            date_label(point),
+            point.transcript.label(),
+            point.mentions,
+            bar,
+            sentiment
+        ));
+    }
+    out
+}
+
+/// Render the trend as CSV for spreadsheets and plotting tools
+pub fn render_csv(points: &[TopicPoint]) -> String {
+    let mut out = String::from("date,video_id,title,mentions,sentiment\n");
+    for point in points {
+        out.push_str(&format!(
+            "{},{},\"{}\",{},{}\n",
+            date_label(point),
+            point.transcript.video_id,
+            point.transcript.label().replace('"', "\"\""),
+            point.mentions,
+            point.sentiment.map(|s| format!("{:.2}", s)).unwrap_or_default()
+        ));
+    }
+    out
+}
+
+fn date_label(point: &TopicPoint) -> String {
+    point
+        .transcript
+        .published_at
+        .map(|date| date.format("%Y-%m-%d").to_string())
+        .unwrap_or_else(|| "undated".to_string())
+}
@@ -0,0 +1,269 @@
+use anyhow::{Context, Result};
+use memmap2::Mmap;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs::{self, OpenOptions};
+use std::io::{Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+use crate::transcript::Transcript;
+
+/// Row ids and shape of a vector store; the rows themselves live in the `.f32` file
+#[derive(Serialize, Deserialize)]
+struct StoreMeta {
+    model: String,
+    dim: usize,
+    /// Byte order the rows were written in, so a copied store is never misread
+    endian: String,
+    ids: Vec<String>,
+}
+
+fn native_endian() -> &'static str {
+    if cfg!(target_endian = "little") {
+        "little"
+    } else {
+        "big"
+    }
+}
+
+impl StoreMeta {
+    fn empty(model: &str) -> Self {
+        Self {
+            model: model.to_string(),
+            dim: 0,
+            endian: native_endian().to_string(),
+            ids: Vec::new(),
+        }
+    }
+
+    fn load(path: &Path) -> Result<Option<Self>> {
+        if !path.exists() {
+            return Ok(None);
+        }
+        let contents = fs::read_to_string(path).with_context(|| format!("Failed to read {}", path.display()))?;
+        let meta = serde_json::from_str(&contents).with_context(|| format!("Failed to parse {}", path.display()))?;
+        Ok(Some(meta))
+    }
+
+    /// Whether the rows on disk can be read back under this metadata
+    fn usable(&self, model: &str, data_len: usize) -> bool {
+        self.model == model && self.endian == native_endian() && data_len >= self.ids.len() * self.dim * 4
+    }
+
+    /// Write through a temporary file, so a reader never sees half the ids
+    fn save(&self, path: &Path) -> Result<()> {
+        let temp = path.with_extension("json.tmp");
+        fs::write(&temp, serde_json::to_string(self)?).with_context(|| format!("Failed to write {}", temp.display()))?;
+        fs::rename(&temp, path).with_context(|| format!("Failed to write {}", path.display()))
+    }
+}
+
+/// Embedding matrix kept on disk and memory-mapped, so opening it costs the same for ten
+/// vectors or a hundred thousand and only the rows actually read are paged in.
+///
+/// `<name>.f32` holds the rows back to back as native-endian `f32`; `<name>.json` holds the
+/// model, dimension, and the id of each row. The `.f32` file is never shortened, since other
+/// processes may have it mapped: bytes past the last listed row (left by an interrupted write)
+/// are simply overwritten by the next append, which holds a lock on the file.
+pub struct VectorStore {
+    meta_path: PathBuf,
+    data_path: PathBuf,
+    meta: StoreMeta,
+    rows: HashMap<String, usize>,
+    mmap: Option<Mmap>,
+}
+
+impl VectorStore {
+    /// Open (or create) the store `name` in `dir`, starting over if it was built with a
+    /// different model
+    pub fn open(dir: &Path, name: &str, model: &str) -> Result<Self> {
+        let meta_path = dir.join(format!("{}.json", name));
+        let data_path = dir.join(format!("{}.f32", name));
+
+        let data_len = fs::metadata(&data_path).map(|m| m.len() as usize).unwrap_or(0);
+        // A store that can't be read back starts over empty; its file is replaced on the next
+        // append, leaving any mapping of the old one intact
+        let meta = StoreMeta::load(&meta_path)?
+            .filter(|meta| meta.usable(model, data_len))
+            .unwrap_or_else(|| StoreMeta::empty(model));
+
+        let mut store = Self {
+            meta_path,
+            data_path,
+            rows: meta.ids.iter().enumerate().map(|(i, id)| (id.clone(), i)).collect(),
+            meta,
+            mmap: None,
+        };
+        store.remap()?;
+        Ok(store)
+    }
+
+    fn remap(&mut self) -> Result<()> {
+        self.mmap = None;
+        if self.meta.ids.is_empty() {
+            return Ok(());
+        }
+        let file = fs::File::open(&self.data_path)
+            .with_context(|| format!("Failed to open {}", self.data_path.display()))?;
+        // SAFETY: the file never shrinks and listed rows are never rewritten, so bytes we read
+        // through the mapping never change underneath us
+        let mmap = unsafe { Mmap::map(&file) }
+            .with_context(|| format!("Failed to map {}", self.data_path.display()))?;
+        self.mmap = Some(mmap);
+        Ok(())
+    }
+
+    pub fn contains(&self, id: &str) -> bool {
+        self.rows.contains_key(id)
+    }
+
+    /// The vector stored for `id`, read straight from the mapping
+    pub fn get(&self, id: &str) -> Option<&[f32]> {
+        let row = *self.rows.get(id)?;
+        let mmap = self.mmap.as_ref()?;
+        let bytes = &mmap[row * self.meta.dim * 4..(row + 1) * self.meta.dim * 4];
+        // SAFETY: the bytes were written from f32 values in this byte order; align_to
+        // returns them unsplit only when the offset is suitably aligned
+        let (prefix, floats, suffix) = unsafe { bytes.align_to::<f32>() };
+        (prefix.is_empty() && suffix.is_empty()).then_some(floats)
+    }
+
+    /// Vectors for each transcript, in order; every one must already be stored
+    pub fn rows_for(&self, transcripts: &[Transcript]) -> Result<Vec<&[f32]>> {
+        transcripts
+            .iter()
+            .map(|t| {
+                self.get(&t.video_id)
+                    .with_context(|| format!("No embedding stored for {}", t.video_id))
+            })
+            .collect()
+    }
+
+    /// Add rows; ids already present, including ones another process added since this store
+    /// was opened, are skipped
+    pub fn append(&mut self, items: Vec<(String, Vec<f32>)>) -> Result<()> {
+        if items.iter().all(|(id, _)| self.contains(id)) {
+            return Ok(());
+        }
+
+        self.mmap = None;
+        let mut file = OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .read(true)
+            .write(true)
+            .open(&self.data_path)
+            .with_context(|| format!("Failed to open {}", self.data_path.display()))?;
+        file.lock()
+            .with_context(|| format!("Failed to lock {}", self.data_path.display()))?;
+
+        // Pick up rows appended elsewhere, or start over when the store on disk is unusable
+        let data_len = file.metadata()?.len() as usize;
+        self.meta = StoreMeta::load(&self.meta_path)?
+            .filter(|meta| meta.usable(&self.meta.model, data_len))
+            .unwrap_or_else(|| StoreMeta::empty(&self.meta.model));
+        if self.meta.ids.is_empty() && data_len > 0 {
+            // Replace rather than shorten the old file, which someone may still have mapped
+            fs::remove_file(&self.data_path)
+                .with_context(|| format!("Failed to reset {}", self.data_path.display()))?;
+            drop(file);
+            return self.append(items);
+        }
+        self.rows = self.meta.ids.iter().enumerate().map(|(i, id)| (id.clone(), i)).collect();
+
+        let items: Vec<(String, Vec<f32>)> =
+            items.into_iter().filter(|(id, _)| !self.contains(id)).collect();
+        let Some((_, first)) = items.first() else {
+            return self.remap();
+        };
+        if self.meta.dim == 0 {
+            self.meta.dim = first.len();
+        }
+
+        let mut bytes = Vec::with_capacity(items.len() * self.meta.dim * 4);
+        for (id, vector) in &items {
+            if vector.len() != self.meta.dim {
+                anyhow::bail!(
+                    "Embedding for {} has {} dimensions, expected {}",
+                    id,
+                    vector.len(),
+                    self.meta.dim
+                );
+            }
+            for value in vector {
+                bytes.extend_from_slice(&value.to_ne_bytes());
+            }
+        }
+        // Rows go right after the last listed one, over any orphans of an interrupted write
+        file.seek(SeekFrom::Start((self.meta.ids.len() * self.meta.dim * 4) as u64))?;
+        file.write_all(&bytes)
+            .with_context(|| format!("Failed to write {}", self.data_path.display()))?;
+        file.sync_data()?;
+
+        for (id, _) in items {
+            self.rows.insert(id.clone(), self.meta.ids.len());
+            self.meta.ids.push(id);
+        }
+        self.meta.save(&self.meta_path)?;
+        self.remap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scratch_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("cvt-vectorstore-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn row(value: f32) -> Vec<f32> {
+        vec![value; 4]
+    }
+
+    #[test]
+    fn rows_survive_reopening() {
+        let dir = scratch_dir("reopen");
+        let mut store = VectorStore::open(&dir, "v", "model").unwrap();
+        store.append(vec![("a".into(), row(1.0)), ("b".into(), row(2.0))]).unwrap();
+        store.append(vec![("a".into(), row(9.0)), ("c".into(), row(3.0))]).unwrap();
+
+        let store = VectorStore::open(&dir, "v", "model").unwrap();
+        assert_eq!(store.get("a"), Some(&row(1.0)[..]));
+        assert_eq!(store.get("c"), Some(&row(3.0)[..]));
+        assert!(VectorStore::open(&dir, "v", "another model").unwrap().get("a").is_none());
+    }
+
+    #[test]
+    fn orphaned_rows_are_overwritten_not_truncated() {
+        let dir = scratch_dir("orphans");
+        let mut store = VectorStore::open(&dir, "v", "model").unwrap();
+        store.append(vec![("a".into(), row(1.0))]).unwrap();
+        // Rows written by an append that never got to save its ids
+        let mut file = OpenOptions::new().append(true).open(dir.join("v.f32")).unwrap();
+        file.write_all(&[0xAB; 32]).unwrap();
+        let len = file.metadata().unwrap().len();
+
+        let mut store = VectorStore::open(&dir, "v", "model").unwrap();
+        assert_eq!(fs::metadata(dir.join("v.f32")).unwrap().len(), len);
+        store.append(vec![("b".into(), row(2.0))]).unwrap();
+        assert_eq!(store.get("b"), Some(&row(2.0)[..]));
+        assert_eq!(fs::metadata(dir.join("v.f32")).unwrap().len(), len);
+    }
+
+    #[test]
+    fn appends_from_another_handle_are_kept() {
+        let dir = scratch_dir("shared");
+        let mut first = VectorStore::open(&dir, "v", "model").unwrap();
+        let mut second = VectorStore::open(&dir, "v", "model").unwrap();
+        first.append(vec![("a".into(), row(1.0))]).unwrap();
+        second.append(vec![("b".into(), row(2.0))]).unwrap();
+
+        let store = VectorStore::open(&dir, "v", "model").unwrap();
+        assert_eq!(store.get("a"), Some(&row(1.0)[..]));
+        assert_eq!(store.get("b"), Some(&row(2.0)[..]));
+    }
+}
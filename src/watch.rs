@@ -0,0 +1,227 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::Duration;
+
+use crate::cancel::{self, SendCancellable};
+use crate::config::AlertRule;
+use crate::transcript::{find_mentions, format_timestamp, snippet_around, Transcript};
+use crate::VideoTranscriber;
+
+/// Video IDs already seen per watched channel, so only new uploads are processed
+#[derive(Serialize, Deserialize, Default)]
+struct WatchState {
+    seen: HashMap<String, HashSet<String>>,
+}
+
+impl WatchState {
+    fn path(dir: &Path) -> PathBuf {
+        dir.join("watch_state.json")
+    }
+
+    fn load(dir: &Path) -> Result<Self> {
+        let path = Self::path(dir);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let contents = fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        serde_json::from_str(&contents).with_context(|| format!("Failed to parse {}", path.display()))
+    }
+
+    fn save(&self, dir: &Path) -> Result<()> {
+        let path = Self::path(dir);
+        fs::write(&path, serde_json::to_string_pretty(self)?)
+            .with_context(|| format!("Failed to write {}", path.display()))
+    }
+}
+
+/// Channels that have been watched before, from the state kept in `data_dir`
+pub fn watched_channels(data_dir: &Path) -> Result<Vec<String>> {
+    let mut channels: Vec<String> = WatchState::load(data_dir)?.seen.into_keys().collect();
+    channels.sort();
+    Ok(channels)
+}
+
+/// A keyword match in a newly indexed video
+#[derive(Serialize, Debug)]
+pub struct Alert {
+    pub rule: String,
+    pub keyword: String,
+    pub title: String,
+    pub channel: String,
+    pub snippet: String,
+    pub timestamp: Option<String>,
+    pub link: String,
+}
+
+impl Alert {
+    fn message(&self) -> String {
+        format!(
+            "🚨 [{}] \"{}\" mentioned in {} ({}){}: {}\n{}",
+            self.rule,
+            self.keyword,
+            self.title,
+            self.channel,
+            self.timestamp
+                .as_deref()
+                .map(|t| format!(" at {}", t))
+                .unwrap_or_default(),
+            self.snippet,
+            self.link
+        )
+    }
+}
+
+impl VideoTranscriber {
+    /// Poll channels for new uploads, index them, and fire alerts on keyword matches
+    pub fn watch(
+        &self,
+        channels: &[String],
+        extra_rules: &[AlertRule],
+        interval: Duration,
+        max_videos: i32,
+        once: bool,
+    ) -> Result<()> {
+        let rules: Vec<AlertRule> = self
+            .config
+            .alerts
+            .iter()
+            .chain(extra_rules)
+            .cloned()
+            .collect();
+        status!(
+            "👀 Watching {} channel(s) with {} alert rule(s)",
+            channels.len(),
+            rules.len()
+        );
+
+        loop {
+            for channel in channels {
+                if let Err(e) = self.watch_pass(channel, &rules, max_videos) {
+                    if cancel::cancelled(&e).is_some() {
+                        return Err(e);
+                    }
+                    status!("⚠️  Watch pass failed for {}: {}", channel, e);
+                }
+            }
+            if once {
+                return Ok(());
+            }
+            status!("💤 Sleeping {}s until the next check...", interval.as_secs());
+            self.cancel.sleep(interval)?;
+        }
+    }
+
+    /// Check one channel once, returning the transcripts of videos not seen before
+    pub fn watch_pass(
+        &self,
+        channel: &str,
+        rules: &[AlertRule],
+        max_videos: i32,
+    ) -> Result<Vec<Transcript>> {
+        let mut state = WatchState::load(&self.data_dir)?;
+        let first_pass = !state.seen.contains_key(channel);
+
+        status!("🔄 Checking {} for new videos...", channel);
+        let items = self.run_scraper(&[channel.to_string()], max_videos)?;
+
+        let mut fresh = Vec::new();
+        for item in &items {
+            let transcript = match self.transcript_from_item(item, channel) {
+                Ok(transcript) => transcript,
+                Err(e) => {
+                    status!("⚠️  Skipping a video without a usable transcript: {}", e);
+                    continue;
+                }
+            };
+            let seen = state.seen.entry(channel.to_string()).or_default();
+            if !seen.insert(transcript.video_id.clone()) {
+                continue;
+            }
+
+            self.cache.put(&transcript)?;
+            status!("📥 Indexed new video: {}", transcript.label());
+            fresh.push(transcript);
+        }
+        state.seen.entry(channel.to_string()).or_default();
+        state.save(&self.data_dir)?;
+
+        if first_pass {
+            status!(
+                "📌 First check of {}: indexed {} existing video(s) without alerting",
+                channel,
+                fresh.len()
+            );
+            return Ok(fresh);
+        }
+
+        for transcript in &fresh {
+            for alert in match_alerts(transcript, rules) {
+                self.send_alert(&alert);
+            }
+        }
+        Ok(fresh)
+    }
+
+    /// Report the alert as a status line and deliver it to the configured webhook and/or command
+    fn send_alert(&self, alert: &Alert) {
+        status!("{}", alert.message());
+        let Some(notify) = &self.config.notify else {
+            return;
+        };
+
+        if let Some(webhook) = &notify.webhook_url {
+            let mut payload = serde_json::to_value(alert).unwrap_or_default();
+            payload["text"] = serde_json::Value::String(alert.message());
+            match self.client.post(webhook).json(&payload).send_with(&self.cancel) {
+                Ok(response) if response.status().is_success() => {}
+                Ok(response) => status!("⚠️  Alert webhook returned {}", response.status()),
+                Err(e) => status!("⚠️  Alert webhook failed: {}", e),
+            }
+        }
+
+        if let Some(command) = &notify.command {
+            let result = Command::new("sh")
+                .arg("-c")
+                .arg(command)
+                .env("CVT_ALERT_MESSAGE", alert.message())
+                .env("CVT_ALERT_RULE", &alert.rule)
+                .env("CVT_ALERT_KEYWORD", &alert.keyword)
+                .env("CVT_ALERT_TITLE", &alert.title)
+                .env("CVT_ALERT_LINK", &alert.link)
+                .status();
+            if let Err(e) = result {
+                status!("⚠️  Alert command failed: {}", e);
+            }
+        }
+    }
+}
+
+/// First match of each keyword of each rule in the transcript
+pub fn match_alerts(transcript: &Transcript, rules: &[AlertRule]) -> Vec<Alert> {
+    let mut alerts = Vec::new();
+    for rule in rules {
+        for keyword in &rule.keywords {
+            let Some(&offset) = find_mentions(&transcript.text, keyword).first() else {
+                continue;
+            };
+            let seconds = transcript.time_at_offset(offset);
+            alerts.push(Alert {
+                rule: rule.name.clone(),
+                keyword: keyword.clone(),
+                title: transcript.label().to_string(),
+                channel: transcript.channel.clone().unwrap_or_default(),
+                snippet: snippet_around(&transcript.text, offset, 150),
+                timestamp: seconds.map(format_timestamp),
+                link: seconds
+                    .map(|s| transcript.link_at(s))
+                    .unwrap_or_else(|| transcript.url.clone()),
+            });
+        }
+    }
+    alerts
+}
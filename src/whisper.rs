@@ -0,0 +1,153 @@
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::sources::parse_upload_date;
+use crate::transcript::{self, CaptionSource, Segment, Transcript};
+use crate::VideoTranscriber;
+
+/// Whether this build can transcribe audio locally (the `local-whisper` feature)
+pub const ENABLED: bool = cfg!(feature = "local-whisper");
+
+/// whisper.cpp expects 16 kHz mono samples
+const SAMPLE_RATE: u32 = 16_000;
+
+/// The parts of yt-dlp's video information kept with a local transcription
+#[derive(Deserialize)]
+struct AudioInfo {
+    title: Option<String>,
+    channel: Option<String>,
+    uploader: Option<String>,
+    upload_date: Option<String>,
+    duration: Option<f64>,
+}
+
+/// Removes the downloaded audio however transcription ends
+struct TempDir(PathBuf);
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
+
+impl VideoTranscriber {
+    /// The ggml model file whisper.cpp runs, from `WHISPER_MODEL_PATH` or the config file
+    fn whisper_model_path(&self) -> Result<PathBuf> {
+        let path = self.setting("WHISPER_MODEL_PATH", &self.config.whisper_model_path).context(
+            "Set WHISPER_MODEL_PATH (or whisper_model_path in the config file) to a whisper.cpp \
+             ggml model, e.g. ggml-base.en.bin from https://huggingface.co/ggerganov/whisper.cpp",
+        )?;
+        let path = PathBuf::from(path);
+        anyhow::ensure!(path.is_file(), "Whisper model {} does not exist", path.display());
+        Ok(path)
+    }
+
+    /// Download a video's audio with yt-dlp and transcribe it with whisper.cpp, for videos
+    /// that have no captions
+    pub fn transcribe_locally(&self, url: &str) -> Result<Transcript> {
+        anyhow::ensure!(ENABLED, "This build has no local transcription; rebuild with `--features local-whisper`");
+        let model = self.whisper_model_path()?;
+        let video_id = self.extract_video_id(url)?;
+        let dir = TempDir(std::env::temp_dir().join(format!("cvt-audio-{}", video_id)));
+        std::fs::create_dir_all(&dir.0)?;
+
+        status!("📥 Downloading audio with yt-dlp...");
+        self.cancel.check()?;
+        let output = Command::new("yt-dlp")
+            .args(["--extract-audio", "--audio-format", "wav", "--no-playlist", "--no-warnings"])
+            .args(["--postprocessor-args", &format!("ffmpeg:-ar {} -ac 1", SAMPLE_RATE)])
+            .args(["--dump-json", "--no-simulate", "--output"])
+            .arg(dir.0.join("audio.%(ext)s"))
+            .arg(url)
+            .output()
+            .context("Failed to run yt-dlp, which downloads the audio (https://github.com/yt-dlp/yt-dlp)")?;
+        if !output.status.success() {
+            anyhow::bail!("yt-dlp failed: {}", String::from_utf8_lossy(&output.stderr).trim());
+        }
+        let info: AudioInfo =
+            serde_json::from_slice(&output.stdout).context("Failed to parse yt-dlp's video information")?;
+
+        self.cancel.check()?;
+        status!("🎙️  Transcribing with {}...", model.display());
+        let segments: Vec<Segment> = run_whisper(&model, &dir.0.join("audio.wav"))?
+            .into_iter()
+            .map(|segment| Segment {
+                source: Some(CaptionSource::Whisper),
+                ..segment
+            })
+            .collect();
+        if segments.is_empty() {
+            anyhow::bail!("whisper.cpp heard no speech in {}", url);
+        }
+
+        Ok(Transcript {
+            video_id,
+            url: url.to_string(),
+            title: info.title,
+            channel: info.channel.or(info.uploader),
+            text: transcript::join_segments(&segments).into(),
+            segments,
+            published_at: info.upload_date.as_deref().and_then(parse_upload_date),
+            duration_secs: info.duration,
+            fetched_at: chrono::Utc::now(),
+        })
+    }
+}
+
+/// Transcribe a 16 kHz mono WAV file
+#[cfg(feature = "local-whisper")]
+fn run_whisper(model: &Path, wav: &Path) -> Result<Vec<Segment>> {
+    use whisper_rs::{FullParams, SamplingStrategy, WhisperContext, WhisperContextParameters};
+
+    let mut reader = hound::WavReader::open(wav).with_context(|| format!("Failed to open {}", wav.display()))?;
+    let spec = reader.spec();
+    anyhow::ensure!(
+        spec.sample_rate == SAMPLE_RATE && spec.channels == 1,
+        "Expected 16 kHz mono audio, got {} Hz with {} channel(s)",
+        spec.sample_rate,
+        spec.channels
+    );
+    let samples: Vec<f32> = match spec.sample_format {
+        hound::SampleFormat::Int => reader
+            .samples::<i16>()
+            .map(|sample| sample.map(|s| s as f32 / i16::MAX as f32))
+            .collect::<Result<_, _>>()?,
+        hound::SampleFormat::Float => reader.samples::<f32>().collect::<Result<_, _>>()?,
+    };
+
+    let model = model.to_str().context("The whisper model path is not valid UTF-8")?;
+    let context = WhisperContext::new_with_params(model, WhisperContextParameters::default())
+        .context("Failed to load the whisper model")?;
+    let mut state = context.create_state().context("Failed to start whisper.cpp")?;
+    let mut params = FullParams::new(SamplingStrategy::Greedy { best_of: 1 });
+    // Detect the spoken language instead of assuming English
+    params.set_language(Some("auto"));
+    params.set_print_progress(false);
+    params.set_print_realtime(false);
+    params.set_print_timestamps(false);
+    params.set_print_special(false);
+    state.full(params, &samples).context("whisper.cpp failed to transcribe the audio")?;
+
+    let mut segments = Vec::new();
+    for i in 0..state.full_n_segments()? {
+        let text = state.full_get_segment_text(i)?.trim().to_string();
+        if text.is_empty() {
+            continue;
+        }
+        // Timestamps are in centiseconds
+        segments.push(Segment {
+            start: state.full_get_segment_t0(i)? as f64 / 100.0,
+            end: state.full_get_segment_t1(i)? as f64 / 100.0,
+            text,
+            source: None,
+        });
+    }
+    Ok(segments)
+}
+
+#[cfg(not(feature = "local-whisper"))]
+fn run_whisper(_model: &Path, _wav: &Path) -> Result<Vec<Segment>> {
+    unreachable!("transcribe_locally checks ENABLED first")
+}
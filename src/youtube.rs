@@ -0,0 +1,287 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use regex::Regex;
+use serde_json::Value;
+
+use crate::cancel::SendCancellable;
+use crate::captions::{self, CaptionTrack};
+use crate::sources::{fetch_json3, pick_language, TranscriptSource};
+use crate::transcript::{self, CaptionSource, Segment, Transcript};
+use crate::{Error, VideoTranscriber};
+
+/// A JSON object the watch page assigns to `name` in an inline script, such as
+/// `ytInitialPlayerResponse`. Parsed as a stream so the object's end is found by the parser
+/// rather than by a pattern that could stop inside a string.
+pub fn embedded_json(page: &str, name: &str) -> Option<Value> {
+    let start = page.find(&format!("{} = ", name))? + name.len() + 3;
+    serde_json::Deserializer::from_str(&page[start..])
+        .into_iter::<Value>()
+        .next()?
+        .ok()
+}
+
+/// Parse YouTube's default timedtext XML (`<text start="1.2" dur="3.4">...</text>`), which some
+/// tracks return when `json3` comes back empty
+pub fn parse_timedtext_xml(xml: &str) -> Vec<Segment> {
+    let cue = Regex::new(r#"(?s)<text start="([\d.]+)"(?: dur="([\d.]+)")?[^>]*>(.*?)</text>"#).expect("valid regex");
+    let markup = Regex::new(r"<[^>]*>").expect("valid regex");
+    cue.captures_iter(xml)
+        .filter_map(|captures| {
+            let start: f64 = captures[1].parse().ok()?;
+            let duration: f64 = captures.get(2).and_then(|d| d.as_str().parse().ok()).unwrap_or(0.0);
+            // Entities are escaped twice when a cue contains markup of its own
+            let cue = transcript::unescape_html(&captures[3]);
+            let text = transcript::unescape_html(&markup.replace_all(&cue, ""));
+            let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+            (!text.is_empty()).then_some(Segment {
+                start,
+                end: start + duration,
+                text,
+                source: None,
+            })
+        })
+        .collect()
+}
+
+/// The video ID in a YouTube URL: `watch?v=ID` (the `v` parameter anywhere in the query),
+/// `youtu.be/ID`, or `/shorts/ID`, `/embed/ID`, `/live/ID`, and `/v/ID` paths, with or without
+/// a scheme. Fragments and other parameters such as `t` are ignored.
+pub fn video_id_from_url(url: &str) -> Option<&str> {
+    let url = url.trim();
+    let url = url.split('#').next().unwrap_or_default();
+    let (address, query) = match url.split_once('?') {
+        Some((address, query)) => (address, Some(query)),
+        None => (url, None),
+    };
+    let address = address.split_once("://").map_or(address, |(_, rest)| rest);
+    let (host, path) = address.split_once('/').unwrap_or((address, ""));
+
+    let id = if host == "youtu.be" || host.ends_with(".youtu.be") {
+        path.split('/').next()
+    } else if let Some(id) = query.and_then(|query| query.split('&').find_map(|param| param.strip_prefix("v="))) {
+        Some(id)
+    } else {
+        let mut segments = path.split('/');
+        match segments.next() {
+            Some("shorts" | "embed" | "live" | "v") => segments.next(),
+            _ => None,
+        }
+    };
+    id.filter(|id| !id.is_empty() && id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_'))
+}
+
+impl VideoTranscriber {
+    /// The HTML of a video's watch page, in English
+    pub fn watch_page(&self, video_id: &str) -> Result<String> {
+        let watch_url = format!("https://www.youtube.com/watch?v={}", video_id);
+        self.client
+            .get(&watch_url)
+            .header("Accept-Language", "en")
+            // Skips the cookie-consent interstitial served in some regions
+            .header("Cookie", "CONSENT=YES+1")
+            .send_with(&self.cancel)
+            .context("Failed to load the video page")?
+            .text()
+            .context("Failed to read the video page")
+    }
+
+    /// A video's current length and publish date, read from its watch page
+    pub fn video_metadata(&self, video_id: &str) -> Result<VideoMetadata> {
+        let page = self.watch_page(video_id)?;
+        let player = embedded_json(&page, "ytInitialPlayerResponse")
+            .context("Could not read the video page's player data")?;
+        let microformat = &player["microformat"]["playerMicroformatRenderer"];
+        Ok(VideoMetadata {
+            duration_secs: player["videoDetails"]["lengthSeconds"].as_str().and_then(|secs| secs.parse().ok()),
+            published_at: microformat["publishDate"]
+                .as_str()
+                .or_else(|| microformat["uploadDate"].as_str())
+                .and_then(transcript::parse_published_date),
+        })
+    }
+}
+
+/// What a video's watch page says about it now
+#[derive(Debug, Clone)]
+pub struct VideoMetadata {
+    pub duration_secs: Option<f64>,
+    pub published_at: Option<DateTime<Utc>>,
+}
+
+/// Captions read straight from YouTube: the caption tracks the watch page lists, downloaded
+/// in the `json3` format. No key, account, or extra tool needed, but only public captions.
+pub struct YouTubeSource;
+
+impl TranscriptSource for YouTubeSource {
+    fn name(&self) -> &'static str {
+        "YouTube"
+    }
+
+    fn fetch(&self, transcriber: &VideoTranscriber, url: &str) -> Result<Transcript> {
+        status!("📥 Fetching captions from YouTube...");
+        let video_id = transcriber.extract_video_id(url)?;
+        let page = transcriber.watch_page(&video_id)?;
+        let player = embedded_json(&page, "ytInitialPlayerResponse")
+            .context("Could not read the video page's player data")?;
+
+        let playability = &player["playabilityStatus"];
+        if playability["status"].as_str().is_some_and(|status| status != "OK") {
+            anyhow::bail!(
+                "YouTube will not play this video: {}",
+                playability["reason"].as_str().unwrap_or("unavailable")
+            );
+        }
+
+        let listed = player
+            .pointer("/captions/playerCaptionsTracklistRenderer/captionTracks")
+            .and_then(Value::as_array)
+            .ok_or_else(|| Error::NoTranscript(url.to_string()))?;
+        // `kind: asr` marks speech recognition, which is in the spoken language
+        let (auto, creator): (Vec<&Value>, Vec<&Value>) =
+            listed.iter().partition(|track| track["kind"].as_str() == Some("asr"));
+        let language = |track: &&Value| track["languageCode"].as_str().unwrap_or_default().to_string();
+        let spoken = auto.first().map(language);
+
+        let mut tracks = Vec::new();
+        for (candidates, source) in [(&creator, CaptionSource::Creator), (&auto, CaptionSource::Auto)] {
+            let languages: Vec<String> = candidates.iter().map(language).collect();
+            let Some(chosen) = pick_language(languages.iter().map(String::as_str), spoken.as_deref()) else {
+                continue;
+            };
+            let Some(base_url) = candidates
+                .iter()
+                .find(|track| language(track) == chosen)
+                .and_then(|track| track["baseUrl"].as_str())
+            else {
+                continue;
+            };
+            let mut segments = fetch_json3(transcriber, &format!("{}&fmt=json3", base_url))?;
+            if segments.is_empty() {
+                let xml = transcriber
+                    .client
+                    .get(base_url)
+                    .send_with(&transcriber.cancel)
+                    .context("Failed to download captions")?
+                    .text()
+                    .context("Failed to read captions")?;
+                segments = parse_timedtext_xml(&xml);
+            }
+            tracks.push(CaptionTrack {
+                source,
+                language: Some(chosen.to_string()),
+                segments,
+            });
+        }
+        if !listed.is_empty() && tracks.iter().all(|t| t.segments.is_empty()) {
+            anyhow::bail!("YouTube listed captions for this video but sent none back; try --source ytdlp or apify");
+        }
+        let segments = captions::merge_tracks(tracks);
+        if segments.is_empty() {
+            return Err(Error::NoTranscript(url.to_string()).into());
+        }
+
+        let details = &player["videoDetails"];
+        let microformat = &player["microformat"]["playerMicroformatRenderer"];
+        Ok(Transcript {
+            video_id,
+            url: url.to_string(),
+            title: details["title"].as_str().map(str::to_string),
+            channel: details["author"].as_str().map(str::to_string),
+            text: transcript::join_segments(&segments).into(),
+            segments,
+            published_at: microformat["publishDate"]
+                .as_str()
+                .or_else(|| microformat["uploadDate"].as_str())
+                .and_then(transcript::parse_published_date),
+            duration_secs: details["lengthSeconds"].as_str().and_then(|secs| secs.parse().ok()),
+            fetched_at: chrono::Utc::now(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    /// IDs as YouTube issues them
+    fn video_id() -> impl Strategy<Value = String> {
+        "[A-Za-z0-9_-]{11}"
+    }
+
+    /// Query parameters that are not `v`, including ones ending in `v` such as `dev`
+    fn other_param() -> impl Strategy<Value = String> {
+        ("(t|list|si|feature|index|dev|pp|ab_channel)", "[A-Za-z0-9_.-]{0,12}")
+            .prop_map(|(key, value)| format!("{}={}", key, value))
+    }
+
+    fn prefix() -> impl Strategy<Value = &'static str> {
+        prop::sample::select(vec!["https://", "http://", ""])
+    }
+
+    proptest! {
+        #[test]
+        fn finds_the_v_parameter_wherever_it_is(
+            id in video_id(),
+            prefix in prefix(),
+            host in prop::sample::select(vec!["www.youtube.com", "youtube.com", "m.youtube.com", "music.youtube.com"]),
+            before in prop::collection::vec(other_param(), 0..3),
+            after in prop::collection::vec(other_param(), 0..3),
+            fragment in prop::option::of("t=[0-9]{1,4}s?"),
+        ) {
+            let mut params = before;
+            params.push(format!("v={}", id));
+            params.extend(after);
+            let mut url = format!("{}{}/watch?{}", prefix, host, params.join("&"));
+            if let Some(fragment) = fragment {
+                url.push('#');
+                url.push_str(&fragment);
+            }
+            prop_assert_eq!(video_id_from_url(&url), Some(id.as_str()));
+        }
+
+        #[test]
+        fn finds_short_link_and_path_ids(
+            id in video_id(),
+            prefix in prefix(),
+            query in prop::option::of(other_param()),
+            kind in 0..5usize,
+        ) {
+            let address = match kind {
+                0 => format!("youtu.be/{}", id),
+                1 => format!("www.youtube.com/shorts/{}", id),
+                2 => format!("www.youtube.com/embed/{}", id),
+                3 => format!("www.youtube.com/live/{}", id),
+                _ => format!("www.youtube-nocookie.com/embed/{}", id),
+            };
+            let url = match query {
+                Some(query) => format!("{}{}?{}", prefix, address, query),
+                None => format!("{}{}", prefix, address),
+            };
+            prop_assert_eq!(video_id_from_url(&url), Some(id.as_str()));
+        }
+
+        #[test]
+        fn rejects_urls_without_a_video(
+            handle in "[A-Za-z0-9_]{1,20}",
+            list in "PL[A-Za-z0-9_-]{10,32}",
+            kind in 0..4usize,
+        ) {
+            let url = match kind {
+                0 => format!("https://www.youtube.com/@{}", handle),
+                1 => format!("https://www.youtube.com/playlist?list={}", list),
+                2 => format!("https://www.youtube.com/channel/UC{}", handle),
+                _ => format!("https://www.youtube.com/watch?dev={}", handle),
+            };
+            prop_assert_eq!(video_id_from_url(&url), None);
+        }
+
+        #[test]
+        fn never_panics(url in any::<String>()) {
+            if let Some(id) = video_id_from_url(&url) {
+                prop_assert!(!id.is_empty());
+                prop_assert!(url.contains(id));
+            }
+        }
+    }
+}
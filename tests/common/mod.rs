@@ -0,0 +1,245 @@
+//! A local HTTP server that stands in for Apify, Gemini, and Groq and plays back failures:
+//! slow or error responses, rate limits, and bodies that aren't what the API promises. Point
+//! a transcriber at it with `Endpoints::all(&server.url())`.
+
+use std::collections::VecDeque;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use claude_video_transcribe::config::Config;
+use claude_video_transcribe::http::Endpoints;
+use claude_video_transcribe::llm::LlmProvider;
+use claude_video_transcribe::builder::VideoTranscriberBuilder;
+use claude_video_transcribe::VideoTranscriber;
+
+/// Client timeout for transcribers under test, short so a stalled reply times out quickly
+pub const CLIENT_TIMEOUT: Duration = Duration::from_millis(500);
+
+/// One scripted response
+#[derive(Clone, Debug)]
+pub struct Reply {
+    status: u16,
+    headers: Vec<(String, String)>,
+    body: String,
+    delay: Duration,
+    body_delay: Duration,
+}
+
+impl Reply {
+    /// 200 with a JSON body
+    pub fn json(body: serde_json::Value) -> Self {
+        Self::status(200, body.to_string())
+    }
+
+    /// Any status with a body sent as is, which need not be valid JSON
+    pub fn status(status: u16, body: impl Into<String>) -> Self {
+        Self {
+            status,
+            headers: Vec::new(),
+            body: body.into(),
+            delay: Duration::ZERO,
+            body_delay: Duration::ZERO,
+        }
+    }
+
+    /// 429 as Gemini sends it, asking for a retry straight away
+    pub fn rate_limited() -> Self {
+        Self::status(
+            429,
+            r#"{"error": {"code": 429, "status": "RESOURCE_EXHAUSTED", "message": "Quota exceeded"}}"#,
+        )
+        .header("Retry-After", "0")
+    }
+
+    pub fn header(mut self, name: &str, value: &str) -> Self {
+        self.headers.push((name.to_string(), value.to_string()));
+        self
+    }
+
+    /// Hold the reply back this long, to outlast the client's timeout
+    pub fn after(mut self, delay: Duration) -> Self {
+        self.delay = delay;
+        self
+    }
+
+    /// Send the status and headers straight away but hold the body back this long
+    pub fn body_after(mut self, delay: Duration) -> Self {
+        self.body_delay = delay;
+        self
+    }
+}
+
+/// A request the server received
+#[derive(Clone, Debug)]
+pub struct Request {
+    pub method: String,
+    pub path: String,
+    pub body: String,
+}
+
+struct Route {
+    method: &'static str,
+    path: String,
+    replies: VecDeque<Reply>,
+}
+
+#[derive(Default)]
+struct State {
+    routes: Vec<Route>,
+    requests: Vec<Request>,
+}
+
+pub struct FaultServer {
+    base: String,
+    state: Arc<Mutex<State>>,
+}
+
+impl FaultServer {
+    /// Listen on a free local port until the test process exits
+    pub fn start() -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("bind a local port");
+        let base = format!("http://{}", listener.local_addr().unwrap());
+        let state = Arc::new(Mutex::new(State::default()));
+        let shared = state.clone();
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let state = shared.clone();
+                thread::spawn(move || serve(stream, &state));
+            }
+        });
+        Self { base, state }
+    }
+
+    pub fn url(&self) -> String {
+        self.base.clone()
+    }
+
+    /// Answer requests with this method and a path (query string included) starting with
+    /// `path` with `replies` in turn, repeating the last one once the others are used up
+    pub fn on(&self, method: &'static str, path: &str, replies: Vec<Reply>) -> &Self {
+        assert!(!replies.is_empty(), "a route needs at least one reply");
+        self.state.lock().unwrap().routes.push(Route {
+            method,
+            path: path.to_string(),
+            replies: replies.into(),
+        });
+        self
+    }
+
+    /// Requests received so far whose path starts with `path`, in order
+    pub fn requests(&self, path: &str) -> Vec<Request> {
+        let state = self.state.lock().unwrap();
+        state.requests.iter().filter(|request| request.path.starts_with(path)).cloned().collect()
+    }
+
+    pub fn hits(&self, path: &str) -> usize {
+        self.requests(path).len()
+    }
+}
+
+fn serve(stream: TcpStream, state: &Mutex<State>) {
+    let Some(request) = read_request(&stream) else {
+        return;
+    };
+    let reply = {
+        let mut state = state.lock().unwrap();
+        state.requests.push(request.clone());
+        state
+            .routes
+            .iter_mut()
+            .find(|route| route.method == request.method && request.path.starts_with(&route.path))
+            .map(|route| match route.replies.len() {
+                1 => route.replies[0].clone(),
+                _ => route.replies.pop_front().unwrap(),
+            })
+            .unwrap_or_else(|| Reply::status(404, format!(r#"{{"error": "no route for {}"}}"#, request.path)))
+    };
+
+    thread::sleep(reply.delay);
+    let mut head = format!(
+        "HTTP/1.1 {} Fault\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n",
+        reply.status,
+        reply.body.len()
+    );
+    for (name, value) in &reply.headers {
+        head.push_str(&format!("{}: {}\r\n", name, value));
+    }
+    head.push_str("\r\n");
+    // The client may have given up already; that's the point of a stalled reply
+    let mut stream = stream;
+    let _ = stream.write_all(head.as_bytes());
+    let _ = stream.flush();
+    thread::sleep(reply.body_delay);
+    let _ = stream.write_all(reply.body.as_bytes());
+}
+
+fn read_request(stream: &TcpStream) -> Option<Request> {
+    let mut reader = BufReader::new(stream);
+    let mut line = String::new();
+    reader.read_line(&mut line).ok()?;
+    let mut parts = line.split_whitespace();
+    let method = parts.next()?.to_string();
+    let path = parts.next()?.to_string();
+
+    let mut length = 0;
+    loop {
+        let mut header = String::new();
+        reader.read_line(&mut header).ok()?;
+        let header = header.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            if name.eq_ignore_ascii_case("content-length") {
+                length = value.trim().parse().ok()?;
+            }
+        }
+    }
+    let mut body = vec![0; length];
+    reader.read_exact(&mut body).ok()?;
+    Some(Request {
+        method,
+        path,
+        body: String::from_utf8_lossy(&body).into_owned(),
+    })
+}
+
+/// A fresh directory for one test's cache, index, and failure log
+fn scratch_dir() -> PathBuf {
+    static COUNTER: AtomicUsize = AtomicUsize::new(0);
+    let dir = std::env::temp_dir()
+        .join(format!("cvt-faults-{}", std::process::id()))
+        .join(COUNTER.fetch_add(1, Ordering::SeqCst).to_string());
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+/// A transcriber that sends every API call to `server`, polls Apify runs without waiting,
+/// and gives up on a response after `CLIENT_TIMEOUT`
+pub fn transcriber(server: &FaultServer, provider: LlmProvider) -> VideoTranscriber {
+    builder(server, provider).build().expect("transcriber for the fault server")
+}
+
+/// The builder `transcriber` builds from, for tests that change more of it
+pub fn builder(server: &FaultServer, provider: LlmProvider) -> VideoTranscriberBuilder {
+    let client = reqwest::blocking::Client::builder()
+        .timeout(CLIENT_TIMEOUT)
+        .build()
+        .unwrap();
+    VideoTranscriber::builder()
+        .ignore_environment()
+        .config(Config::default())
+        .apify_api_key("apify-test")
+        .gemini_api_key("gemini-test")
+        .groq_api_key("groq-test")
+        .provider(provider)
+        .store_dir(scratch_dir())
+        .http_client(client)
+        .endpoints(Endpoints::all(&server.url()))
+        .apify_poll_interval(Duration::from_millis(10))
+}
@@ -0,0 +1,279 @@
+//! Upstream failures played back by a local server, checking that each one is retried,
+//! recovered from, or surfaced as the typed error callers match on
+
+mod common;
+
+use std::time::Duration;
+
+use claude_video_transcribe::error::{kind, Error};
+use claude_video_transcribe::llm::LlmProvider;
+use serde::Deserialize;
+use serde_json::json;
+
+use common::{transcriber, FaultServer, Reply, CLIENT_TIMEOUT};
+
+const VIDEO: &str = "https://www.youtube.com/watch?v=dQw4w9WgXcQ";
+const START_RUN: &str = "/v2/acts/streamers~youtube-scraper/runs?";
+const RUN_STATUS: &str = "/v2/acts/streamers~youtube-scraper/runs/run-1?";
+const DATASET: &str = "/v2/actor-runs/run-1/dataset/items";
+const LIMITS: &str = "/v2/users/me/limits";
+const GEMINI: &str = "/v1beta/models/";
+const GROQ: &str = "/openai/v1/chat/completions";
+
+fn run_status(status: &str) -> Reply {
+    Reply::json(json!({"data": {"id": "run-1", "status": status}}))
+}
+
+/// An Apify server whose run reports `statuses` in turn and whose dataset is `dataset`
+fn apify(statuses: Vec<Reply>, dataset: Vec<Reply>) -> FaultServer {
+    let server = FaultServer::start();
+    server
+        .on("POST", START_RUN, vec![run_status("RUNNING")])
+        .on("GET", RUN_STATUS, statuses)
+        .on("GET", DATASET, dataset);
+    server
+}
+
+fn video_item() -> Reply {
+    Reply::json(json!([{
+        "id": "dQw4w9WgXcQ",
+        "title": "Never Gonna Give You Up",
+        "url": VIDEO,
+        "text": "We're no strangers to love. You know the rules and so do I."
+    }]))
+}
+
+fn gemini_answer(text: &str) -> Reply {
+    Reply::json(json!({"candidates": [{"content": {"parts": [{"text": text}]}, "finishReason": "STOP"}]}))
+}
+
+fn groq_answer(text: &str) -> Reply {
+    Reply::json(json!({"choices": [{"message": {"content": text}}]}))
+}
+
+#[test]
+fn apify_status_check_that_times_out_is_retried() {
+    let server = apify(
+        vec![run_status("RUNNING").after(CLIENT_TIMEOUT * 3), run_status("SUCCEEDED")],
+        vec![video_item()],
+    );
+    let transcript = transcriber(&server, LlmProvider::Groq).fetch_from_apify(VIDEO).unwrap();
+    assert_eq!(transcript.video_id, "dQw4w9WgXcQ");
+    assert!(transcript.text.starts_with("We're no strangers"));
+    assert_eq!(server.hits(RUN_STATUS), 2);
+}
+
+#[test]
+fn apify_run_that_times_out_is_reported_as_failed() {
+    let server = apify(vec![run_status("RUNNING"), run_status("TIMED-OUT")], vec![video_item()]);
+    let error = transcriber(&server, LlmProvider::Groq).fetch_from_apify(VIDEO).unwrap_err();
+    assert!(matches!(kind(&error), Some(Error::ApifyRun(status)) if status == "TIMED-OUT"), "{:#}", error);
+    assert_eq!(server.hits(DATASET), 0);
+}
+
+#[test]
+fn apify_server_errors_are_retried() {
+    let server = apify(
+        vec![Reply::status(502, "Bad Gateway"), Reply::status(503, "").header("Retry-After", "0"), run_status("SUCCEEDED")],
+        vec![Reply::status(500, "").header("Retry-After", "0"), video_item()],
+    );
+    transcriber(&server, LlmProvider::Groq).fetch_from_apify(VIDEO).unwrap();
+    assert_eq!(server.hits(RUN_STATUS), 3);
+    assert_eq!(server.hits(DATASET), 2);
+}
+
+#[test]
+fn empty_apify_dataset_means_no_transcript() {
+    let server = apify(vec![run_status("SUCCEEDED")], vec![Reply::json(json!([]))]);
+    let error = transcriber(&server, LlmProvider::Groq).fetch_from_apify(VIDEO).unwrap_err();
+    assert!(matches!(kind(&error), Some(Error::NoTranscript(_))), "{:#}", error);
+}
+
+#[test]
+fn malformed_apify_dataset_is_a_parse_error() {
+    let server = apify(vec![run_status("SUCCEEDED")], vec![Reply::status(200, "[{\"id\": \"dQw4")]);
+    let error = transcriber(&server, LlmProvider::Groq).fetch_from_apify(VIDEO).unwrap_err();
+    assert!(format!("{:#}", error).contains("Failed to parse Apify dataset items"), "{:#}", error);
+}
+
+#[test]
+fn apify_credits_are_checked_once_per_transcriber() {
+    let server = apify(vec![run_status("SUCCEEDED")], vec![video_item()]);
+    let first = transcriber(&server, LlmProvider::Groq);
+    first.fetch_from_apify(VIDEO).unwrap();
+    first.clone().fetch_from_apify(VIDEO).unwrap();
+    assert_eq!(server.hits(LIMITS), 1);
+
+    // Another transcriber, perhaps with another key, checks for itself
+    transcriber(&server, LlmProvider::Groq).fetch_from_apify(VIDEO).unwrap();
+    assert_eq!(server.hits(LIMITS), 2);
+}
+
+#[test]
+fn gemini_rate_limits_are_waited_out() {
+    let server = FaultServer::start();
+    let retry_delay = json!({"error": {"code": 429, "details": [
+        {"@type": "type.googleapis.com/google.rpc.RetryInfo", "retryDelay": "0s"}
+    ]}});
+    server.on(
+        "POST",
+        GEMINI,
+        vec![Reply::rate_limited(), Reply::status(429, retry_delay.to_string()), gemini_answer("Forty-two.")],
+    );
+    let answer = transcriber(&server, LlmProvider::Gemini).complete("system", "question").unwrap();
+    assert_eq!(answer, "Forty-two.");
+    assert_eq!(server.hits(GEMINI), 3);
+}
+
+#[test]
+fn gemini_rate_limit_that_never_lifts_is_an_http_error() {
+    let server = FaultServer::start();
+    server.on("POST", GEMINI, vec![Reply::rate_limited()]);
+    let error = transcriber(&server, LlmProvider::Gemini).complete("system", "question").unwrap_err();
+    assert!(
+        matches!(kind(&error), Some(Error::Http { status, .. }) if status.as_u16() == 429),
+        "{:#}",
+        error
+    );
+    assert_eq!(server.hits(GEMINI), 4);
+}
+
+#[test]
+fn gemini_blocked_prompt_is_not_retried() {
+    let server = FaultServer::start();
+    server.on("POST", GEMINI, vec![Reply::json(json!({"promptFeedback": {"blockReason": "SAFETY"}}))]);
+    let error = transcriber(&server, LlmProvider::Gemini).complete("system", "question").unwrap_err();
+    assert!(matches!(kind(&error), Some(Error::Blocked { reason, .. }) if reason == "SAFETY"), "{:#}", error);
+    assert_eq!(server.hits(GEMINI), 1);
+}
+
+#[test]
+fn gemini_blocked_answer_is_a_typed_error() {
+    let server = FaultServer::start();
+    server.on(
+        "POST",
+        GEMINI,
+        vec![Reply::json(json!({"candidates": [{"finishReason": "SAFETY", "safetyRatings": []}]}))],
+    );
+    let error = transcriber(&server, LlmProvider::Gemini).complete("system", "question").unwrap_err();
+    assert!(matches!(kind(&error), Some(Error::Blocked { reason, .. }) if reason == "SAFETY"), "{:#}", error);
+}
+
+#[test]
+fn malformed_gemini_response_is_a_parse_error() {
+    let server = FaultServer::start();
+    server.on("POST", GEMINI, vec![Reply::status(200, "{\"candidates\": [{\"content\": ")]);
+    let error = transcriber(&server, LlmProvider::Gemini).complete("system", "question").unwrap_err();
+    assert!(format!("{:#}", error).contains("Failed to parse Gemini generate response"), "{:#}", error);
+    assert_eq!(server.hits(GEMINI), 1);
+}
+
+#[test]
+fn groq_timeouts_and_outages_are_retried() {
+    let server = FaultServer::start();
+    server.on(
+        "POST",
+        GROQ,
+        vec![
+            groq_answer("too late").after(CLIENT_TIMEOUT * 3),
+            Reply::status(503, "").header("Retry-After", "0"),
+            groq_answer("On time."),
+        ],
+    );
+    let answer = transcriber(&server, LlmProvider::Groq).complete("system", "question").unwrap();
+    assert_eq!(answer, "On time.");
+    assert_eq!(server.hits(GROQ), 3);
+}
+
+#[test]
+fn client_errors_are_not_retried() {
+    let server = FaultServer::start();
+    server.on("POST", GROQ, vec![Reply::status(401, r#"{"error": "invalid api key"}"#)]);
+    let error = transcriber(&server, LlmProvider::Groq).complete("system", "question").unwrap_err();
+    assert!(
+        matches!(kind(&error), Some(Error::Http { status, .. }) if status.as_u16() == 401),
+        "{:#}",
+        error
+    );
+    assert_eq!(server.hits(GROQ), 1);
+}
+
+#[test]
+fn failed_requests_are_logged_in_the_store_dir() {
+    let server = FaultServer::start();
+    server.on("POST", GROQ, vec![Reply::status(401, r#"{"error": "invalid api key"}"#)]);
+    let transcriber = transcriber(&server, LlmProvider::Groq);
+    transcriber.complete("system", "question").unwrap_err();
+    let log = std::fs::read_to_string(transcriber.data_dir().join("last_http_failure.json")).unwrap();
+    assert!(log.contains("\"status\": 401"), "{}", log);
+}
+
+#[derive(Deserialize, Debug, PartialEq)]
+struct Verdict {
+    verdict: String,
+}
+
+#[test]
+fn malformed_json_from_the_model_is_asked_for_again() {
+    let server = FaultServer::start();
+    server.on(
+        "POST",
+        GROQ,
+        vec![
+            groq_answer("Sure! The verdict is {verdict: true,"),
+            groq_answer("```json\n{\"verdict\": \"yes\"}\n```"),
+        ],
+    );
+    let verdict: Verdict = transcriber(&server, LlmProvider::Groq)
+        .complete_json("system", "Reply with JSON")
+        .unwrap();
+    assert_eq!(verdict.verdict, "yes");
+    let requests = server.requests(GROQ);
+    assert_eq!(requests.len(), 2);
+    assert!(requests[1].body.contains("could not be parsed"));
+}
+
+#[test]
+fn json_that_stays_malformed_is_an_error() {
+    let server = FaultServer::start();
+    server.on("POST", GROQ, vec![groq_answer("I'd rather not use JSON.")]);
+    let error = transcriber(&server, LlmProvider::Groq)
+        .complete_json::<Verdict>("system", "Reply with JSON")
+        .unwrap_err();
+    assert!(format!("{:#}", error).contains("did not return the expected JSON"), "{:#}", error);
+    assert_eq!(server.hits(GROQ), 2);
+}
+
+#[test]
+fn cancellation_stops_a_retry_wait() {
+    let server = FaultServer::start();
+    // No Retry-After, so the first retry would wait the 2s backoff
+    server.on("POST", GROQ, vec![Reply::status(503, "")]);
+    let transcriber = transcriber(&server, LlmProvider::Groq)
+        .with_cancellation(claude_video_transcribe::cancel::CancellationToken::new(Some(Duration::from_millis(300))));
+    let error = transcriber.complete("system", "question").unwrap_err();
+    assert!(claude_video_transcribe::cancel::cancelled(&error).is_some(), "{:#}", error);
+    assert_eq!(server.hits(GROQ), 1);
+}
+
+#[test]
+fn cancellation_stops_a_response_body_read() {
+    let server = FaultServer::start();
+    server.on("POST", GROQ, vec![groq_answer("too late").body_after(CLIENT_TIMEOUT * 4)]);
+    let token = claude_video_transcribe::cancel::CancellationToken::new(None);
+    let transcriber = transcriber(&server, LlmProvider::Groq).with_cancellation(token.clone());
+    let cancel = std::thread::spawn(move || {
+        std::thread::sleep(Duration::from_millis(200));
+        token.cancel();
+    });
+    let started = std::time::Instant::now();
+    let error = transcriber.complete("system", "question").unwrap_err();
+    cancel.join().unwrap();
+    assert_eq!(
+        claude_video_transcribe::cancel::cancelled(&error),
+        Some(claude_video_transcribe::cancel::Cancelled::Interrupted),
+        "{:#}",
+        error
+    );
+    assert!(started.elapsed() < CLIENT_TIMEOUT, "{:?}", started.elapsed());
+}
@@ -0,0 +1,267 @@
+//! A transcriber runs against any `IndexStore`, not only the SQLite registry. The store here
+//! keeps everything in memory, as an application with its own database might.
+
+#[allow(dead_code)]
+mod common;
+
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+
+use anyhow::Result;
+use chrono::Utc;
+use claude_video_transcribe::conversation::{Turn, VideoChat};
+use claude_video_transcribe::embeddings::cosine_similarity;
+use claude_video_transcribe::llm::LlmProvider;
+use claude_video_transcribe::parts::UploadedPart;
+use claude_video_transcribe::registry::{ChunkMatch, PlaylistRecord, SessionRecord, VideoRecord};
+use claude_video_transcribe::store::IndexStore;
+use claude_video_transcribe::transcript::Transcript;
+use serde_json::json;
+
+use common::{builder, FaultServer, Reply};
+
+const GROQ: &str = "/openai/v1/chat/completions";
+
+#[derive(Default)]
+struct State {
+    parts: HashMap<String, Vec<UploadedPart>>,
+    videos: HashMap<String, VideoRecord>,
+    playlists: HashMap<String, (PlaylistRecord, Vec<String>)>,
+    sessions: HashMap<String, (SessionRecord, Vec<Turn>)>,
+    vectors: HashMap<String, Vec<(String, Vec<f32>)>>,
+}
+
+/// Cloned handles share one state, so a test can look inside the store it gave the builder
+#[derive(Clone, Default)]
+struct MemoryStore(Arc<Mutex<State>>);
+
+impl IndexStore for MemoryStore {
+    fn parts(&self, video_id: &str) -> Result<Vec<UploadedPart>> {
+        Ok(self.0.lock().unwrap().parts.get(video_id).cloned().unwrap_or_default())
+    }
+
+    fn replace_parts(&self, video_id: &str, parts: &[UploadedPart]) -> Result<()> {
+        self.0.lock().unwrap().parts.insert(video_id.to_string(), parts.to_vec());
+        Ok(())
+    }
+
+    fn record_video(&self, transcript: &Transcript) -> Result<()> {
+        let mut state = self.0.lock().unwrap();
+        let earlier = state.videos.remove(&transcript.video_id);
+        let record = VideoRecord {
+            video_id: transcript.video_id.clone(),
+            url: transcript.url.clone(),
+            title: transcript.title.clone().or_else(|| earlier.as_ref().and_then(|v| v.title.clone())),
+            channel: transcript.channel.clone().or_else(|| earlier.as_ref().and_then(|v| v.channel.clone())),
+            indexed_at: Utc::now(),
+            duration_secs: transcript.duration_secs,
+            published_at: transcript.published_at,
+            private: earlier.as_ref().is_some_and(|v| v.private),
+            access_note: earlier.and_then(|v| v.access_note),
+        };
+        state.videos.insert(record.video_id.clone(), record);
+        Ok(())
+    }
+
+    fn record_playlist(&self, playlist: &PlaylistRecord, video_ids: &[String]) -> Result<()> {
+        let mut state = self.0.lock().unwrap();
+        state
+            .playlists
+            .insert(playlist.playlist_id.clone(), (playlist.clone(), video_ids.to_vec()));
+        Ok(())
+    }
+
+    fn playlist_videos(&self, playlist_id: &str) -> Result<Option<Vec<String>>> {
+        Ok(self.0.lock().unwrap().playlists.get(playlist_id).map(|(_, ids)| ids.clone()))
+    }
+
+    fn playlists(&self) -> Result<Vec<(PlaylistRecord, usize)>> {
+        let state = self.0.lock().unwrap();
+        let mut playlists: Vec<_> = state
+            .playlists
+            .values()
+            .map(|(playlist, ids)| (playlist.clone(), ids.len()))
+            .collect();
+        playlists.sort_by_key(|(playlist, _)| std::cmp::Reverse(playlist.indexed_at));
+        Ok(playlists)
+    }
+
+    fn delete_video(&self, video_id: &str) -> Result<bool> {
+        let mut state = self.0.lock().unwrap();
+        state.parts.remove(video_id);
+        state.vectors.remove(video_id);
+        for (_, ids) in state.playlists.values_mut() {
+            ids.retain(|id| id != video_id);
+        }
+        Ok(state.videos.remove(video_id).is_some())
+    }
+
+    fn videos(&self) -> Result<Vec<VideoRecord>> {
+        let mut videos: Vec<_> = self.0.lock().unwrap().videos.values().cloned().collect();
+        videos.sort_by_key(|video| std::cmp::Reverse(video.indexed_at));
+        Ok(videos)
+    }
+
+    fn set_private(&self, video_id: &str, private: bool, note: Option<&str>) -> Result<bool> {
+        let mut state = self.0.lock().unwrap();
+        let Some(video) = state.videos.get_mut(video_id) else {
+            return Ok(false);
+        };
+        video.private = private;
+        video.access_note = note.filter(|_| private).map(str::to_string);
+        Ok(true)
+    }
+
+    fn private_videos(&self) -> Result<HashMap<String, Option<String>>> {
+        let state = self.0.lock().unwrap();
+        Ok(state
+            .videos
+            .values()
+            .filter(|video| video.private)
+            .map(|video| (video.video_id.clone(), video.access_note.clone()))
+            .collect())
+    }
+
+    fn sessions(&self) -> Result<Vec<SessionRecord>> {
+        let mut sessions: Vec<_> = self.0.lock().unwrap().sessions.values().map(|(s, _)| s.clone()).collect();
+        sessions.sort_by_key(|session| std::cmp::Reverse(session.updated_at));
+        Ok(sessions)
+    }
+
+    fn session_turns(&self, session_id: &str) -> Result<Vec<Turn>> {
+        Ok(self
+            .0
+            .lock()
+            .unwrap()
+            .sessions
+            .get(session_id)
+            .map(|(_, turns)| turns.clone())
+            .unwrap_or_default())
+    }
+
+    fn add_session_turn(&self, session_id: &str, video_id: &str, url: &str, turn: &Turn) -> Result<()> {
+        let mut state = self.0.lock().unwrap();
+        let now = Utc::now();
+        let (session, turns) = state.sessions.entry(session_id.to_string()).or_insert_with(|| {
+            let session = SessionRecord {
+                session_id: session_id.to_string(),
+                video_id: video_id.to_string(),
+                url: url.to_string(),
+                created_at: now,
+                updated_at: now,
+                turns: 0,
+            };
+            (session, Vec::new())
+        });
+        if session.video_id != video_id {
+            anyhow::bail!("Session '{}' is about video {}, not {}", session_id, session.video_id, video_id);
+        }
+        turns.push(turn.clone());
+        session.turns = turns.len();
+        session.updated_at = now;
+        Ok(())
+    }
+
+    fn delete_session(&self, session_id: &str) -> Result<bool> {
+        Ok(self.0.lock().unwrap().sessions.remove(session_id).is_some())
+    }
+
+    fn add_chunk_vectors(&self, video_id: &str, vectors: &[(String, Vec<f32>)]) -> Result<()> {
+        let mut state = self.0.lock().unwrap();
+        let stored = state.vectors.entry(video_id.to_string()).or_default();
+        for (key, vector) in vectors {
+            if !stored.iter().any(|(stored_key, _)| stored_key == key) {
+                stored.push((key.clone(), vector.clone()));
+            }
+        }
+        Ok(())
+    }
+
+    fn delete_chunk_vectors(&self, video_id: &str) -> Result<usize> {
+        Ok(self.0.lock().unwrap().vectors.remove(video_id).map_or(0, |stored| stored.len()))
+    }
+
+    fn chunk_vector_keys(&self, video_id: &str) -> Result<HashSet<String>> {
+        let state = self.0.lock().unwrap();
+        Ok(state
+            .vectors
+            .get(video_id)
+            .map(|stored| stored.iter().map(|(key, _)| key.clone()).collect())
+            .unwrap_or_default())
+    }
+
+    fn nearest_chunks(&self, query: &[f32], k: usize, video_id: Option<&str>) -> Result<Vec<ChunkMatch>> {
+        let state = self.0.lock().unwrap();
+        let mut matches: Vec<ChunkMatch> = state
+            .vectors
+            .iter()
+            .filter(|(id, _)| video_id.is_none_or(|wanted| wanted == id.as_str()))
+            .flat_map(|(id, stored)| {
+                stored.iter().map(move |(key, vector)| ChunkMatch {
+                    video_id: id.clone(),
+                    chunk_key: key.clone(),
+                    similarity: cosine_similarity(query, vector),
+                })
+            })
+            .collect();
+        matches.sort_by(|a, b| b.similarity.total_cmp(&a.similarity));
+        matches.truncate(k);
+        Ok(matches)
+    }
+}
+
+fn video(video_id: &str, title: &str) -> Transcript {
+    Transcript {
+        video_id: video_id.to_string(),
+        url: format!("https://www.youtube.com/watch?v={}", video_id),
+        title: Some(title.to_string()),
+        channel: None,
+        text: Arc::from("We're no strangers to love. You know the rules and so do I."),
+        segments: Vec::new(),
+        published_at: None,
+        duration_secs: None,
+        fetched_at: Utc::now(),
+    }
+}
+
+#[test]
+fn transcriber_keeps_its_index_in_the_given_store() {
+    let server = FaultServer::start();
+    server.on(
+        "POST",
+        GROQ,
+        vec![Reply::json(json!({"choices": [{"message": {"content": "It is about love."}}]}))],
+    );
+    let store = MemoryStore::default();
+    let transcriber = builder(&server, LlmProvider::Groq)
+        .index_store(store.clone())
+        .build()
+        .unwrap();
+    assert!(!transcriber.data_dir().join("index.db").exists());
+
+    // A session's turns are saved in the store and picked up again by a later chat
+    let rick = video("dQw4w9WgXcQ", "Never Gonna Give You Up");
+    transcriber.cache.put(&rick).unwrap();
+    let mut chat = VideoChat::resume(&transcriber, rick.clone(), "rick").unwrap();
+    assert_eq!(chat.ask("What is it about?").unwrap(), "It is about love.");
+    assert_eq!(store.session_turns("rick").unwrap().len(), 1);
+    let chat = VideoChat::resume(&transcriber, rick.clone(), "rick").unwrap();
+    assert_eq!(chat.history.len(), 1);
+    assert_eq!(transcriber.session_url("rick").unwrap(), rick.url);
+    let other = video("aaaaaaaaaaa", "Another Video");
+    assert!(VideoChat::resume(&transcriber, other.clone(), "rick").is_err());
+
+    // Access settings and the library listing come from the store
+    transcriber.cache.put(&other).unwrap();
+    transcriber.set_private(&other.url, true, Some("Team only")).unwrap();
+    assert_eq!(store.private_videos().unwrap()["aaaaaaaaaaa"].as_deref(), Some("Team only"));
+    assert!(transcriber.check_shareable(&other, false).is_err());
+    let listed = transcriber.list_videos(false).unwrap();
+    assert_eq!(listed.len(), 1);
+    assert_eq!(listed[0].video.title.as_deref(), Some("Another Video"));
+    assert_eq!(listed[0].file_state, "NOT UPLOADED");
+
+    let deleted = transcriber.delete_video(&other.url).unwrap();
+    assert!(deleted.registered);
+    assert!(store.videos().unwrap().is_empty());
+}
@@ -0,0 +1,89 @@
+//! Videos marked private stay out of the library-wide digests unless asked for
+
+#[allow(dead_code)]
+mod common;
+
+use std::sync::Arc;
+
+use chrono::{Duration, Utc};
+use claude_video_transcribe::llm::LlmProvider;
+use claude_video_transcribe::mentions::render_report;
+use claude_video_transcribe::transcript::Transcript;
+use claude_video_transcribe::VideoTranscriber;
+use serde_json::json;
+
+use common::{transcriber, FaultServer, Reply};
+
+const GROQ: &str = "/openai/v1/chat/completions";
+
+fn video(video_id: &str, title: &str, text: &str) -> Transcript {
+    Transcript {
+        video_id: video_id.to_string(),
+        url: format!("https://www.youtube.com/watch?v={}", video_id),
+        title: Some(title.to_string()),
+        channel: Some("Acme Reviews".to_string()),
+        text: Arc::from(text),
+        segments: Vec::new(),
+        published_at: Some(Utc::now() - Duration::days(2)),
+        duration_secs: None,
+        fetched_at: Utc::now(),
+    }
+}
+
+/// A library of one public and one private video that both mention Acme
+fn library(server: &FaultServer) -> VideoTranscriber {
+    server.on(
+        "POST",
+        GROQ,
+        vec![Reply::json(
+            json!({"choices": [{"message": {"content": "[{\"sentiment\": \"positive\"}]"}}]}),
+        )],
+    );
+    let transcriber = transcriber(server, LlmProvider::Groq);
+    transcriber
+        .cache
+        .put(&video("aaaaaaaaaaa", "Public unboxing", "Today we unbox the new Acme blender."))
+        .unwrap();
+    transcriber
+        .cache
+        .put(&video("bbbbbbbbbbb", "Internal roadmap", "Next quarter Acme ships a secret blender."))
+        .unwrap();
+    transcriber
+        .set_private("https://www.youtube.com/watch?v=bbbbbbbbbbb", true, Some("Team only"))
+        .unwrap();
+    transcriber
+}
+
+#[test]
+fn private_video_never_appears_in_the_mentions_digest() {
+    let server = FaultServer::start();
+    let transcriber = library(&server);
+    let since = Utc::now() - Duration::days(30);
+
+    let mentions = transcriber.find_brand_mentions("Acme", since, false).unwrap();
+    let ids: Vec<&str> = mentions.iter().map(|m| m.transcript.video_id.as_str()).collect();
+    assert_eq!(ids, ["aaaaaaaaaaa"]);
+    let report = render_report("Acme", since, &mentions);
+    assert!(!report.contains("Internal roadmap"), "{}", report);
+    assert!(!report.contains("secret"), "{}", report);
+    for request in server.requests(GROQ) {
+        assert!(!request.body.contains("secret"), "{}", request.body);
+    }
+
+    let mentions = transcriber.find_brand_mentions("Acme", since, true).unwrap();
+    assert_eq!(mentions.len(), 2);
+}
+
+#[test]
+fn private_video_is_left_out_of_topic_trends() {
+    let server = FaultServer::start();
+    server.on("POST", GROQ, vec![Reply::json(json!({"choices": [{"message": {"content": "{\"sentiment\": 0.5}"}}]}))]);
+    let transcriber = library(&server);
+
+    let points = transcriber.topic_trend("Acme", "blender", false).unwrap();
+    let ids: Vec<&str> = points.iter().map(|p| p.transcript.video_id.as_str()).collect();
+    assert_eq!(ids, ["aaaaaaaaaaa"]);
+
+    let points = transcriber.topic_trend("Acme", "blender", true).unwrap();
+    assert_eq!(points.len(), 2);
+}